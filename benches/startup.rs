@@ -0,0 +1,69 @@
+//! Startup time with a large roster. `dnd_tools --cli` runs the same startup as the
+//! TUI, up to the main menu, and exits; `dnd_tools characters` lists the roster from
+//! the index; `dnd_tools characters --load` reads every sheet the way startup used to.
+
+use std::{fs, io::Write, path::Path, process::{Command, Stdio}, time::{Duration, Instant}};
+
+const SHEETS: usize = 300;
+const RUNS: usize = 5;
+/// Far above what either should take; a regression that blows past it fails the bench
+const BUDGET: Duration = Duration::from_secs(5);
+
+fn write_roster(dir: &Path) {
+    let characters = dir.join("characters");
+    fs::create_dir_all(&characters).expect("create characters dir");
+    for i in 0..SHEETS {
+        let inventory: Vec<String> = (0..40).map(|n| format!("Item {} of hero {}", n, i)).collect();
+        let sheet = serde_json::json!({
+            "schema_version": 2,
+            "character": {
+                "name": format!("Hero {:03}", i),
+                "class": (["Fighter", "Wizard", "Cleric", "Rogue"][i % 4]),
+                "level": 1 + i % 20,
+                "desc": "A benchmark adventurer ".repeat(20),
+                "hp": 10 + i % 50,
+                "max_hp": 60,
+                "inventory": inventory,
+                "cards": [],
+                "spells": []
+            }
+        });
+        fs::write(characters.join(format!("Hero {:03}.json", i)), sheet.to_string()).expect("write sheet");
+    }
+}
+
+/// The best of several runs, so one slow disk read doesn't skew it. `input` is typed
+/// into the menus.
+fn time(dir: &Path, args: &[&str], input: &str) -> Duration {
+    (0..RUNS).map(|_| {
+        let started = Instant::now();
+        let mut child = Command::new(env!("CARGO_BIN_EXE_dnd_tools"))
+            .args(args)
+            .current_dir(dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("run dnd_tools");
+        child.stdin.take().expect("stdin").write_all(input.as_bytes()).expect("type into dnd_tools");
+        let output = child.wait_with_output().expect("run dnd_tools");
+        assert!(output.status.success(), "dnd_tools {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+        started.elapsed()
+    }).min().unwrap_or_default()
+}
+
+fn main() {
+    let dir = std::env::temp_dir().join(format!("dnd_tools_startup_bench_{}", std::process::id()));
+    write_roster(&dir);
+
+    // The first listing builds the index; the runs after it only check file stamps
+    time(&dir, &["characters"], "");
+    let startup = time(&dir, &["--cli", "--offline"], "exit\n");
+    let indexed = time(&dir, &["characters"], "");
+    let full = time(&dir, &["characters", "--load"], "");
+    println!("{} sheets: startup to menu {:>6.1} ms, index {:>6.1} ms, full load {:>6.1} ms", SHEETS,
+        startup.as_secs_f64() * 1000.0, indexed.as_secs_f64() * 1000.0, full.as_secs_f64() * 1000.0);
+
+    let _ = fs::remove_dir_all(&dir);
+    assert!(startup < BUDGET && indexed < BUDGET && full < BUDGET, "startup took longer than {:?}", BUDGET);
+}
@@ -0,0 +1,146 @@
+use crate::character::{AbilityScore, Character};
+use crate::error_handling::{AppError, Result};
+use rand::Rng;
+
+/// The order scores are entered and shown in, as on the character sheet
+pub const ORDER: [AbilityScore; 6] = [
+    AbilityScore::Strength,
+    AbilityScore::Dexterity,
+    AbilityScore::Constitution,
+    AbilityScore::Intelligence,
+    AbilityScore::Wisdom,
+    AbilityScore::Charisma,
+];
+
+pub const STANDARD_ARRAY: [u8; 6] = [15, 14, 13, 12, 10, 8];
+pub const POINT_BUY_BUDGET: u32 = 27;
+
+/// What each score from 8 to 15 costs under point-buy
+const POINT_COSTS: [(u8, u32); 8] = [(8, 0), (9, 1), (10, 2), (11, 3), (12, 4), (13, 5), (14, 7), (15, 9)];
+
+/// How a new character's ability scores are decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreMethod {
+    /// 4d6, dropping the lowest die, six times
+    Roll,
+    StandardArray,
+    PointBuy,
+    /// Typed in later with the rest of the sheet
+    Manual,
+}
+
+impl ScoreMethod {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().replace(['-', ' '], "").as_str() {
+            "1" | "roll" | "4d6" => Some(ScoreMethod::Roll),
+            "2" | "array" | "standard" | "standardarray" => Some(ScoreMethod::StandardArray),
+            "3" | "pointbuy" | "points" | "buy" => Some(ScoreMethod::PointBuy),
+            "4" | "manual" => Some(ScoreMethod::Manual),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScoreMethod::Roll => "4d6 drop lowest",
+            ScoreMethod::StandardArray => "standard array",
+            ScoreMethod::PointBuy => "27-point buy",
+            ScoreMethod::Manual => "manual entry",
+        }
+    }
+
+    /// The scores to hand out, for the methods that have a fixed set
+    pub fn pool<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<[u8; 6]> {
+        match self {
+            ScoreMethod::Roll => Some(roll_scores(rng)),
+            ScoreMethod::StandardArray => Some(STANDARD_ARRAY),
+            ScoreMethod::PointBuy | ScoreMethod::Manual => None,
+        }
+    }
+
+    /// The question that picks a method
+    pub fn menu() -> String {
+        "Ability scores: 1. Roll 4d6 drop lowest  2. Standard array (15, 14, 13, 12, 10, 8)  3. 27-point buy  4. Enter them later".to_string()
+    }
+}
+
+/// Roll 4d6 and add up the highest three
+pub fn roll_4d6_drop_lowest<R: Rng + ?Sized>(rng: &mut R) -> u8 {
+    let mut dice: Vec<u8> = (0..4).map(|_| rng.random_range(1..=6)).collect();
+    dice.sort_unstable();
+    dice[1..].iter().sum()
+}
+
+/// Six rolled scores, highest first
+pub fn roll_scores<R: Rng + ?Sized>(rng: &mut R) -> [u8; 6] {
+    let mut scores = [0; 6].map(|_: u8| roll_4d6_drop_lowest(rng));
+    scores.sort_unstable_by(|a, b| b.cmp(a));
+    scores
+}
+
+pub fn point_cost(score: u8) -> Option<u32> {
+    POINT_COSTS.iter().find(|(s, _)| *s == score).map(|(_, cost)| *cost)
+}
+
+/// "STR DEX CON INT WIS CHA"
+pub fn order_label() -> String {
+    ORDER.iter().map(|a| a.short_name()).collect::<Vec<_>>().join(" ")
+}
+
+/// Six whole numbers, in ability order
+fn parse_six(text: &str) -> Result<[u8; 6]> {
+    let numbers: Vec<u8> = text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+        .map(|word| word.parse::<u8>().map_err(|_| AppError::ValidationError(format!("'{}' isn't a score", word))))
+        .collect::<Result<_>>()?;
+    numbers.try_into()
+        .map_err(|numbers: Vec<u8>| AppError::ValidationError(format!("Enter six scores ({}), not {}", order_label(), numbers.len())))
+}
+
+/// Scores typed in ability order that use each score in `pool` exactly once
+pub fn assign(text: &str, pool: &[u8; 6]) -> Result<[u8; 6]> {
+    let scores = parse_six(text)?;
+    let (mut given, mut expected) = (scores, *pool);
+    given.sort_unstable();
+    expected.sort_unstable();
+    if given != expected {
+        let pool: Vec<String> = pool.iter().map(u8::to_string).collect();
+        return Err(AppError::ValidationError(format!("Use each of {} once", pool.join(", "))));
+    }
+    Ok(scores)
+}
+
+/// Point-buy scores typed in ability order: each from 8 to 15, costing 27 points or fewer.
+/// Returns the scores and the points left over.
+pub fn point_buy(text: &str) -> Result<([u8; 6], u32)> {
+    let scores = parse_six(text)?;
+    let mut spent = 0;
+    for (ability, score) in ORDER.iter().zip(scores) {
+        spent += point_cost(score)
+            .ok_or_else(|| AppError::ValidationError(format!("{} {} is outside point-buy's 8 to 15", ability.short_name(), score)))?;
+    }
+    if spent > POINT_BUY_BUDGET {
+        return Err(AppError::ValidationError(format!("Those scores cost {} points; point-buy has {}", spent, POINT_BUY_BUDGET)));
+    }
+    Ok((scores, POINT_BUY_BUDGET - spent))
+}
+
+/// "8 costs 0, 9 costs 1 ... 15 costs 9"
+pub fn point_cost_table() -> String {
+    let costs: Vec<String> = POINT_COSTS.iter().map(|(score, cost)| format!("{}={}", score, cost)).collect();
+    format!("Point costs: {} ({} points to spend)", costs.join(" "), POINT_BUY_BUDGET)
+}
+
+pub fn apply(character: &mut Character, scores: [u8; 6]) {
+    for (ability, score) in ORDER.iter().zip(scores) {
+        *character.ability_score_mut(*ability) = Some(score);
+    }
+}
+
+/// "STR 15 (+2), DEX 14 (+2), ..."
+pub fn describe(scores: &[u8; 6]) -> String {
+    ORDER.iter().zip(scores)
+        .map(|(ability, score)| format!("{} {} ({:+})", ability.short_name(), score, Character::calculate_modifier(*score)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
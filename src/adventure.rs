@@ -0,0 +1,238 @@
+use crate::error_handling::{AppError, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// The adventure outline is saved with the rest of the campaign
+pub const ADVENTURE_FILE: &str = "campaign/adventure.ron";
+
+/// One step of the adventure, with the prep the DM wants at hand when it comes up
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scene {
+    pub title: String,
+    #[serde(default)]
+    pub notes: Vec<String>,
+    /// Names of fights saved with `save encounter`
+    #[serde(default)]
+    pub encounters: Vec<String>,
+    /// Names of NPC sheets in the npcs folder
+    #[serde(default)]
+    pub npcs: Vec<String>,
+    #[serde(default)]
+    pub done: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Adventure {
+    pub scenes: Vec<Scene>,
+}
+
+impl Adventure {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        ron::de::from_str(&contents).map_err(|e| AppError::ParseError(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create campaign folder")?;
+        }
+        let serialized = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| AppError::ParseError(e.to_string()))?;
+        fs::write(path, serialized).context(format!("Failed to write {}", path.display()))
+    }
+
+    /// The first scene not yet marked done
+    pub fn current(&self) -> Option<usize> {
+        self.scenes.iter().position(|scene| !scene.done)
+    }
+
+    /// A scene by its number in the outline (from 1), or the current scene
+    pub fn scene_index(&self, number: Option<&str>) -> Result<usize> {
+        match number {
+            Some(text) => text.parse::<usize>().ok()
+                .filter(|n| (1..=self.scenes.len()).contains(n))
+                .map(|n| n - 1)
+                .ok_or_else(|| AppError::ValidationError(format!("'{}' isn't a scene number (1-{})", text, self.scenes.len()))),
+            None if self.scenes.is_empty() => Err(AppError::ValidationError("No scenes yet; 'scene add <title>' adds one".to_string())),
+            None => self.current().ok_or_else(|| AppError::ValidationError("Every scene is done".to_string())),
+        }
+    }
+
+    /// Scenes in order: ✅ done, ▶ current, ⬜ still to come
+    pub fn outline(&self) -> Vec<String> {
+        if self.scenes.is_empty() {
+            return vec!["📖 No scenes yet. 'scene add <title>' starts the outline".to_string()];
+        }
+        let done = self.scenes.iter().filter(|scene| scene.done).count();
+        let current = self.current();
+        let mut lines = vec![format!("📖 Adventure outline ({}/{} done):", done, self.scenes.len())];
+        for (i, scene) in self.scenes.iter().enumerate() {
+            let mark = if scene.done { "✅" } else if Some(i) == current { "▶" } else { "⬜" };
+            let mut prep = Vec::new();
+            if !scene.encounters.is_empty() {
+                prep.push(format!("⚔️ {}", scene.encounters.len()));
+            }
+            if !scene.npcs.is_empty() {
+                prep.push(format!("👤 {}", scene.npcs.len()));
+            }
+            if !scene.notes.is_empty() {
+                prep.push(format!("📝 {}", scene.notes.len()));
+            }
+            let prep = if prep.is_empty() { String::new() } else { format!("  [{}]", prep.join(", ")) };
+            lines.push(format!("  {} {}. {}{}", mark, i + 1, scene.title, prep));
+        }
+        lines
+    }
+
+    /// Everything prepared for a scene: notes, each NPC's sheet and who is in each encounter
+    pub fn prep(&self, index: usize, npcs_dir: &Path, encounters_dir: &Path) -> Vec<String> {
+        let Some(scene) = self.scenes.get(index) else {
+            return Vec::new();
+        };
+        let state = if scene.done { " (done)" } else if Some(index) == self.current() { " (current)" } else { "" };
+        let mut lines = vec![format!("🎬 Scene {}: {}{}", index + 1, scene.title, state)];
+        lines.extend(scene.notes.iter().map(|note| format!("  📝 {}", note)));
+        for npc in &scene.npcs {
+            match fs::read_to_string(npcs_dir.join(format!("{}.txt", npc))) {
+                Ok(sheet) => {
+                    lines.push(format!("  👤 {}", npc));
+                    lines.extend(sheet.lines()
+                        .filter(|line| !line.trim().is_empty() && !line.starts_with("Name: "))
+                        .map(|line| match crate::temperament::Temperament::parse_sheet_line(line) {
+                            Some(temperament) => format!("     {}", temperament.summary()),
+                            None => format!("     {}", line),
+                        }));
+                }
+                Err(_) => lines.push(format!("  👤 {} (no sheet in {})", npc, npcs_dir.display())),
+            }
+        }
+        for (i, name) in scene.encounters.iter().enumerate() {
+            match crate::saved_combat::load_encounter_in(encounters_dir, name, &[]) {
+                Ok((tracker, _)) => {
+                    let combatants: Vec<&str> = tracker.combatants.iter().map(|c| c.name.as_str()).collect();
+                    let how = if i == 0 { "'scene run' starts it".to_string() } else { format!("'load encounter {}' in combat", name) };
+                    lines.push(format!("  ⚔️ {}: {} ({})", name, combatants.join(", "), how));
+                }
+                Err(_) => lines.push(format!("  ⚔️ {} (not saved yet; 'save encounter {}' in combat)", name, name)),
+            }
+        }
+        if scene.notes.is_empty() && scene.npcs.is_empty() && scene.encounters.is_empty() {
+            lines.push("  Nothing prepared. Add prep with 'scene note', 'scene npc' or 'scene encounter'".to_string());
+        }
+        lines
+    }
+
+    /// The saved encounter to start for a scene: its first one
+    pub fn encounter_to_run(&self, number: Option<&str>) -> Result<String> {
+        let index = self.scene_index(number)?;
+        self.scenes[index].encounters.first().cloned().ok_or_else(|| AppError::ValidationError(format!(
+            "Scene {} has no encounter; 'scene encounter {} <saved encounter>' attaches one", index + 1, index + 1)))
+    }
+}
+
+const USAGE: &[&str] = &[
+    "Usage: scene [n|add <title>|done [n]|undo <n>|remove <n>|move <n> <to>]",
+    "       scene note <n> <text> | scene npc <n> <name> | scene encounter <n> <saved encounter> | scene run [n]",
+];
+
+/// `scene` shows the outline and `scene <n>` (or `scene prep`) a scene's prep. The
+/// rest build the outline and mark progress. Returns the lines to show and whether
+/// the outline changed. `scene run` needs the combat tracker, so the caller handles it.
+pub fn scene_command(adventure: &mut Adventure, args: &[&str], npcs_dir: &Path, encounters_dir: &Path) -> (Vec<String>, bool) {
+    match scene_action(adventure, args, npcs_dir, encounters_dir) {
+        Ok(result) => result,
+        Err(e) => (std::iter::once(format!("❌ {}", e)).chain(USAGE.iter().map(|u| u.to_string())).collect(), false),
+    }
+}
+
+fn scene_action(adventure: &mut Adventure, args: &[&str], npcs_dir: &Path, encounters_dir: &Path) -> Result<(Vec<String>, bool)> {
+    let action = args.first().map(|a| a.to_lowercase()).unwrap_or_default();
+    let rest = args.get(1..).unwrap_or_default();
+    // Most actions take a scene number and then a name or text
+    let numbered = |adventure: &Adventure| -> Result<(usize, String)> {
+        let index = adventure.scene_index(rest.first().copied())?;
+        let text = rest.get(1..).unwrap_or_default().join(" ");
+        if text.is_empty() {
+            return Err(AppError::ValidationError(format!("'scene {}' needs a scene number and then a name or text", action)));
+        }
+        Ok((index, text))
+    };
+    let lines = match action.as_str() {
+        "" => return Ok((adventure.outline(), false)),
+        "prep" => {
+            let index = adventure.scene_index(rest.first().copied())?;
+            return Ok((adventure.prep(index, npcs_dir, encounters_dir), false));
+        }
+        number if number.parse::<usize>().is_ok() => {
+            let index = adventure.scene_index(Some(number))?;
+            return Ok((adventure.prep(index, npcs_dir, encounters_dir), false));
+        }
+        "add" => {
+            let title = rest.join(" ");
+            if title.is_empty() {
+                return Err(AppError::ValidationError("'scene add' needs a title".to_string()));
+            }
+            adventure.scenes.push(Scene { title: title.clone(), ..Scene::default() });
+            vec![format!("📖 Added scene {}: {}", adventure.scenes.len(), title)]
+        }
+        "remove" => {
+            let index = adventure.scene_index(Some(rest.first().copied().unwrap_or_default()))?;
+            let scene = adventure.scenes.remove(index);
+            vec![format!("🗑️ Removed scene {}: {}", index + 1, scene.title)]
+        }
+        "move" => {
+            let from = adventure.scene_index(Some(rest.first().copied().unwrap_or_default()))?;
+            let to = adventure.scene_index(Some(rest.get(1).copied().unwrap_or_default()))?;
+            let scene = adventure.scenes.remove(from);
+            adventure.scenes.insert(to, scene);
+            adventure.outline()
+        }
+        "done" => {
+            let index = adventure.scene_index(rest.first().copied())?;
+            adventure.scenes[index].done = true;
+            let mut lines = vec![format!("✅ Scene {} done: {}", index + 1, adventure.scenes[index].title)];
+            match adventure.current() {
+                Some(next) => {
+                    lines.push("Next up:".to_string());
+                    lines.extend(adventure.prep(next, npcs_dir, encounters_dir));
+                }
+                None => lines.push("🏁 Every scene in the outline is done".to_string()),
+            }
+            lines
+        }
+        "undo" | "reopen" => {
+            let index = adventure.scene_index(Some(rest.first().copied().unwrap_or_default()))?;
+            adventure.scenes[index].done = false;
+            vec![format!("↩️ Scene {} is open again: {}", index + 1, adventure.scenes[index].title)]
+        }
+        "note" => {
+            let (index, text) = numbered(adventure)?;
+            adventure.scenes[index].notes.push(text);
+            vec![format!("📝 Noted on scene {}", index + 1)]
+        }
+        "npc" => {
+            let (index, name) = numbered(adventure)?;
+            let mut lines = vec![format!("👤 {} is attached to scene {}", name, index + 1)];
+            if !npcs_dir.join(format!("{}.txt", name)).exists() {
+                lines.push(format!("  ⚠️ There's no sheet for {} in {} yet", name, npcs_dir.display()));
+            }
+            lines.extend(crate::temperament::join_reminder(npcs_dir, &name));
+            adventure.scenes[index].npcs.push(name);
+            lines
+        }
+        "encounter" => {
+            let (index, name) = numbered(adventure)?;
+            let mut lines = vec![format!("⚔️ Encounter '{}' is attached to scene {}", name, index + 1)];
+            if crate::saved_combat::load_encounter_in(encounters_dir, &name, &[]).is_err() {
+                lines.push(format!("  ⚠️ No saved encounter '{}' yet; build the fight and 'save encounter {}'", name, name));
+            }
+            adventure.scenes[index].encounters.push(name);
+            lines
+        }
+        other => return Err(AppError::ValidationError(format!("Unknown scene command '{}'", other))),
+    };
+    Ok((lines, true))
+}
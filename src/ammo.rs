@@ -0,0 +1,103 @@
+use crate::character::Character;
+use crate::error_handling::{AppError, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A stack of ammunition or thrown weapons carried by a character
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AmmoCounter {
+    pub kind: String,
+    pub count: u32,
+    /// Shots fired since the last recovery
+    #[serde(default)]
+    pub expended: u32,
+}
+
+/// (weapon, ammunition it uses). Thrown weapons are their own ammunition.
+const WEAPON_AMMO: &[(&str, &str)] = &[
+    ("longbow", "arrows"),
+    ("shortbow", "arrows"),
+    ("light crossbow", "bolts"),
+    ("heavy crossbow", "bolts"),
+    ("hand crossbow", "bolts"),
+    ("crossbow", "bolts"),
+    ("sling", "bullets"),
+    ("blowgun", "needles"),
+    ("dagger", "daggers"),
+    ("dart", "darts"),
+    ("handaxe", "handaxes"),
+    ("javelin", "javelins"),
+    ("light hammer", "light hammers"),
+    ("spear", "spears"),
+    ("trident", "tridents"),
+];
+
+/// The ammunition a ranged attack uses; the ammunition name itself also works
+pub fn ammo_for_weapon(weapon: &str) -> Option<&'static str> {
+    let weapon = weapon.trim().to_lowercase();
+    WEAPON_AMMO.iter()
+        .find(|(name, kind)| *name == weapon || *kind == weapon)
+        .map(|(_, kind)| *kind)
+}
+
+pub fn ammo_counter_mut<'a>(character: &'a mut Character, kind: &str) -> Option<&'a mut AmmoCounter> {
+    character.ammo.iter_mut().find(|a| a.kind.eq_ignore_ascii_case(kind))
+}
+
+/// Set how much of one kind of ammunition a character carries
+pub fn set_ammo(character: &mut Character, kind: &str, count: u32) {
+    let kind = ammo_for_weapon(kind).unwrap_or(kind).to_lowercase();
+    match ammo_counter_mut(character, &kind) {
+        Some(counter) => {
+            counter.count = count;
+            counter.expended = 0;
+        }
+        None => character.ammo.push(AmmoCounter { kind, count, expended: 0 }),
+    }
+}
+
+/// Spend one piece of ammunition for an attack with `weapon`. Returns the kind and
+/// how many are left, or None when the weapon doesn't use tracked ammunition.
+pub fn expend_ammo(character: &mut Character, weapon: &str) -> Result<Option<(String, u32)>> {
+    let Some(kind) = ammo_for_weapon(weapon) else {
+        return Ok(None);
+    };
+    let name = character.name.clone();
+    let Some(counter) = ammo_counter_mut(character, kind) else {
+        return Ok(None);
+    };
+    if counter.count == 0 {
+        return Err(AppError::ValidationError(format!("{} is out of {}", name, counter.kind)));
+    }
+    counter.count -= 1;
+    counter.expended += 1;
+    Ok(Some((counter.kind.clone(), counter.count)))
+}
+
+/// After a fight each expended piece has an even chance of being found intact,
+/// so about half comes back. Returns (kind, recovered, expended) per counter.
+pub fn recover_ammo<R: Rng + ?Sized>(character: &mut Character, rng: &mut R) -> Vec<(String, u32, u32)> {
+    character.ammo.iter_mut()
+        .filter(|counter| counter.expended > 0)
+        .map(|counter| {
+            let expended = std::mem::take(&mut counter.expended);
+            let recovered = (0..expended).filter(|_| rng.random_bool(0.5)).count() as u32;
+            counter.count += recovered;
+            (counter.kind.clone(), recovered, expended)
+        })
+        .collect()
+}
+
+pub fn describe_ammo(character: &Character) -> String {
+    if character.ammo.is_empty() {
+        return format!("{} carries no tracked ammunition", character.name);
+    }
+    let stacks: Vec<String> = character.ammo.iter()
+        .map(|a| if a.expended > 0 {
+            format!("{} {} ({} spent)", a.count, a.kind, a.expended)
+        } else {
+            format!("{} {}", a.count, a.kind)
+        })
+        .collect();
+    format!("🏹 {}: {}", character.name, stacks.join(", "))
+}
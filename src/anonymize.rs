@@ -0,0 +1,202 @@
+use crate::character::Character;
+use crate::combat::CombatTracker;
+use crate::diagnostics::DIAGNOSTICS_DIR;
+use crate::error_handling::{AppError, Result, ResultExt};
+use regex::Regex;
+use serde_json::Value;
+use std::{fs, path::{Path, PathBuf}, time::{SystemTime, UNIX_EPOCH}};
+
+/// Lists whose entries' names are the campaign's own, and what each entry is called instead
+const PSEUDONYMS: &[(&str, &str)] = &[
+    ("combatants", "Combatant"),
+    ("companions", "Companion"),
+    ("morale_groups", "Group"),
+    ("attacks", "Attack"),
+];
+
+/// Free text that says nothing about the numbers: blanked wherever it appears
+const REDACTED: &[&str] = &["desc", "description", "notes", "cause", "died_on", "in_game_date", "faith", "background", "reinforcement_table"];
+
+/// Item names become "Item 1", "Item 2"... so the list keeps its length
+const NUMBERED: &[&str] = &["inventory"];
+
+const REDACTED_TEXT: &str = "<redacted>";
+
+/// Swaps names for stand-ins like "Combatant 3" and blanks free text, leaving the
+/// numbers and the shape of the data alone. The same name gets the same stand-in
+/// everywhere it's mentioned, so references between records still line up.
+#[derive(Debug, Default)]
+pub struct Anonymizer {
+    names: Vec<(String, String)>,
+    counts: Vec<(&'static str, usize)>,
+}
+
+impl Anonymizer {
+    /// The stand-in for a name, numbered per prefix in the order names are seen
+    pub fn register(&mut self, name: &str, prefix: &'static str) -> String {
+        let name = name.trim();
+        if let Some((_, stand_in)) = self.names.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            return stand_in.clone();
+        }
+        let count = match self.counts.iter_mut().find(|(p, _)| *p == prefix) {
+            Some((_, count)) => {
+                *count += 1;
+                *count
+            }
+            None => {
+                self.counts.push((prefix, 1));
+                1
+            }
+        };
+        let stand_in = format!("{} {}", prefix, count);
+        if !name.is_empty() {
+            self.names.push((name.to_string(), stand_in.clone()));
+        }
+        stand_in
+    }
+
+    /// Find every name in the pseudonymized lists
+    fn collect(&mut self, value: &Value, prefix: Option<&'static str>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(prefix) = prefix
+                    && let Some(Value::String(name)) = map.get("name") {
+                    self.register(name, prefix);
+                }
+                for (key, child) in map {
+                    let prefix = PSEUDONYMS.iter().find(|(k, _)| k == key).map(|(_, p)| *p);
+                    match child {
+                        Value::Array(items) => items.iter().for_each(|item| self.collect(item, prefix)),
+                        _ => self.collect(child, None),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|item| self.collect(item, None)),
+            _ => {}
+        }
+    }
+
+    /// Every known name, longest first so "Goblin Boss" wins over "Goblin"
+    fn name_pattern(&self) -> Option<Regex> {
+        let mut names: Vec<&str> = self.names.iter().map(|(n, _)| n.as_str()).collect();
+        names.sort_by_key(|n| std::cmp::Reverse(n.len()));
+        let alternatives: Vec<String> = names.iter().map(|n| regex::escape(n)).collect();
+        (!alternatives.is_empty())
+            .then(|| Regex::new(&format!(r"(?i)\b(?:{})\b", alternatives.join("|"))).ok())
+            .flatten()
+    }
+
+    fn replace_names(&self, text: &str, pattern: Option<&Regex>) -> String {
+        let Some(pattern) = pattern else { return text.to_string() };
+        pattern.replace_all(text, |caps: &regex::Captures| {
+            self.names.iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(&caps[0]))
+                .map(|(_, stand_in)| stand_in.clone())
+                .unwrap_or_default()
+        }).into_owned()
+    }
+
+    fn scrub(&self, value: &mut Value, key: Option<&str>, pattern: Option<&Regex>) {
+        let redacted = key.is_some_and(|k| REDACTED.contains(&k));
+        let numbered = key.is_some_and(|k| NUMBERED.contains(&k));
+        match value {
+            Value::String(text) if redacted => *text = REDACTED_TEXT.to_string(),
+            Value::String(text) => *text = self.replace_names(text, pattern),
+            Value::Array(items) if numbered => {
+                for (i, item) in items.iter_mut().enumerate() {
+                    if item.is_string() {
+                        *item = Value::String(format!("Item {}", i + 1));
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.scrub(item, key, pattern)),
+            Value::Object(map) => {
+                for (child_key, child) in map.iter_mut() {
+                    self.scrub(child, Some(child_key), pattern);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Anonymize a serialized record in place
+    pub fn anonymize(&mut self, value: &mut Value) {
+        self.collect(value, None);
+        let pattern = self.name_pattern();
+        self.scrub(value, None, pattern.as_ref());
+    }
+}
+
+/// A character sheet with the name, notes and item names taken out. It is still a
+/// valid character file, so it can be loaded to reproduce a problem.
+pub fn anonymize_character(character: &Character) -> Result<String> {
+    let mut value: Value = serde_json::from_str(&crate::file_manager::serialize_character(character)?)
+        .map_err(|e| AppError::ParseError(e.to_string()))?;
+    let mut anonymizer = Anonymizer::default();
+    anonymizer.register(&character.name, "Character");
+    anonymizer.anonymize(&mut value);
+    serde_json::to_string_pretty(&value).map_err(|e| AppError::ParseError(e.to_string()))
+}
+
+/// The fight with every combatant renamed and campaign details taken out
+pub fn anonymize_combat(tracker: &CombatTracker, characters: &[Character]) -> Result<String> {
+    let mut value = serde_json::to_value(tracker).map_err(|e| AppError::ParseError(e.to_string()))?;
+    let mut anonymizer = Anonymizer::default();
+    // Party members are mentioned by their sheet's name too, e.g. as a familiar's owner
+    for character in characters {
+        anonymizer.register(&character.name, "Character");
+    }
+    anonymizer.anonymize(&mut value);
+    serde_json::to_string_pretty(&value).map_err(|e| AppError::ParseError(e.to_string()))
+}
+
+/// Write an anonymized export beside the diagnostics bundles
+pub fn write_export_in(out_dir: &Path, kind: &str, contents: &str) -> Result<PathBuf> {
+    fs::create_dir_all(out_dir).context("Failed to create diagnostics folder")?;
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let path = out_dir.join(format!("anonymized_{}_{}.json", kind, secs));
+    fs::write(&path, contents).context(format!("Failed to write {}", path.display()))?;
+    log::info!("Wrote anonymized {} to {}", kind, path.display());
+    Ok(path)
+}
+
+fn export_message(kind: &str, result: Result<String>) -> String {
+    match result.and_then(|contents| write_export_in(Path::new(DIAGNOSTICS_DIR), kind, &contents)) {
+        Ok(path) => format!("🕶️ Wrote an anonymized copy to {}. Names and notes are replaced; check it over before attaching it to an issue", path.display()),
+        Err(e) => format!("❌ Could not anonymize the {}: {}", kind, e),
+    }
+}
+
+/// `anonymize <character>` for a saved sheet, or `anonymize` for the fight when one is given
+pub fn anonymize_command(args: &[&str], characters: &[Character], tracker: Option<&CombatTracker>) -> String {
+    let name = args.join(" ");
+    if name.is_empty() || name.eq_ignore_ascii_case("combat") {
+        return match tracker {
+            Some(tracker) => export_message("combat", anonymize_combat(tracker, characters)),
+            None => "Usage: anonymize <character> (or anonymize in a running fight)".to_string(),
+        };
+    }
+    match characters.iter().find(|c| c.name.eq_ignore_ascii_case(&name)) {
+        Some(character) => export_message("character", anonymize_character(character)),
+        None => format!("❌ No character named '{}'", name),
+    }
+}
+
+/// `dnd_tools anonymize <character>` or `dnd_tools anonymize encounter <name>`
+pub fn run_anonymize_command(args: &[String]) {
+    let characters = crate::file_manager::load_characters_in(Path::new(crate::file_manager::CHARACTERS_DIR));
+    let message = match args {
+        [encounter, name @ ..] if encounter == "encounter" && !name.is_empty() => {
+            match crate::saved_combat::load_encounter_in(Path::new(crate::saved_combat::ENCOUNTERS_DIR), &name.join(" "), &characters) {
+                Ok((tracker, _)) => export_message("combat", anonymize_combat(&tracker, &characters)),
+                Err(e) => format!("❌ {}", e),
+            }
+        }
+        [] => "Usage: dnd_tools anonymize <character> | anonymize encounter <name>".to_string(),
+        name => {
+            let name: Vec<&str> = name.iter().map(String::as_str).collect();
+            anonymize_command(&name, &characters, None)
+        }
+    };
+    println!("{}", message);
+}
@@ -0,0 +1,182 @@
+use crate::character::Character;
+use crate::combat::{Combatant, DamageType, Edge};
+use crate::dice::{RollResult, roll_dice_with_crits, roll_expression};
+use crate::error_handling::{AppError, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// One way a creature attacks: "Longsword +5, 1d8+3 slashing"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttackProfile {
+    pub name: String,
+    pub to_hit: i32,
+    /// Dice as the roller takes them, e.g. "1d8+3"
+    #[serde(default)]
+    pub damage: Option<String>,
+    #[serde(default)]
+    pub damage_type: Option<DamageType>,
+}
+
+impl AttackProfile {
+    /// "Longsword +5, 1d8+3 slashing", or the stat block form "Spear: +3 to hit,
+    /// reach 5 ft, 1d6+1 piercing". The first dice after the bonus are the damage.
+    pub fn parse(text: &str) -> Result<Self> {
+        let attack = Regex::new(r"^(.+?):?\s+([+-]\d+)(?:\s+to hit)?\b(.*)$").expect("valid attack regex");
+        let damage = Regex::new(r"(\d*d\d+(?:\s*[+-]\s*\d+)?)(?:\s+(\w+))?").expect("valid damage regex");
+        let caps = attack.captures(text.trim())
+            .ok_or_else(|| AppError::ValidationError(format!("'{}' isn't an attack; try 'Longsword +5, 1d8+3 slashing'", text.trim())))?;
+        let hit = damage.captures(&caps[3]);
+        Ok(AttackProfile {
+            name: caps[1].trim().to_string(),
+            to_hit: caps[2].parse().map_err(|_| AppError::ValidationError(format!("'{}' isn't an attack bonus", &caps[2])))?,
+            damage: hit.as_ref().map(|d| d[1].replace(' ', "")),
+            damage_type: hit.as_ref().and_then(|d| d.get(2)).and_then(|kind| DamageType::parse(kind.as_str())),
+        })
+    }
+
+    /// "Longsword: +5 to hit, 1d8+3 slashing"
+    pub fn describe(&self) -> String {
+        let damage = match (&self.damage, self.damage_type) {
+            (Some(dice), Some(kind)) => format!(", {} {}", dice, kind.name()),
+            (Some(dice), None) => format!(", {}", dice),
+            _ => String::new(),
+        };
+        format!("{}: {:+} to hit{}", self.name, self.to_hit, damage)
+    }
+}
+
+/// The profile a weapon name picks out, matching the start of a word in the profile's
+/// name ("sword" finds "Longsword" only as a last resort). Without a weapon, the first.
+pub fn choose_attack<'a>(attacks: &'a [AttackProfile], weapon: Option<&str>) -> Option<&'a AttackProfile> {
+    let Some(weapon) = weapon.map(str::to_lowercase) else {
+        return attacks.first();
+    };
+    attacks.iter().find(|a| a.name.eq_ignore_ascii_case(&weapon))
+        .or_else(|| attacks.iter().find(|a| a.name.to_lowercase().split_whitespace().any(|word| word.starts_with(&weapon))))
+        .or_else(|| attacks.iter().find(|a| a.name.to_lowercase().contains(&weapon)))
+}
+
+/// Damage dice doubled for a critical hit; the flat bonus stays as it is. "1d8+3" → "2d8+3"
+pub fn crit_damage(dice: &str) -> String {
+    let mut doubled = String::new();
+    let mut count = String::new();
+    for c in dice.chars() {
+        match c {
+            '0'..='9' => count.push(c),
+            'd' => {
+                let n: u32 = count.parse().unwrap_or(1);
+                doubled.push_str(&format!("{}d", n * 2));
+                count.clear();
+            }
+            _ => {
+                doubled.push_str(&count);
+                count.clear();
+                doubled.push(c);
+            }
+        }
+    }
+    doubled + count.as_str()
+}
+
+/// Damage rolled for a hit from the attack's dice, which a critical hit doubles
+#[derive(Debug, Clone)]
+pub struct AttackDamage {
+    pub amount: i32,
+    pub kind: Option<DamageType>,
+    /// "🎲 Damage roll: 1d8 (6) + 3 = 9 slashing"
+    pub line: String,
+}
+
+/// An attack roll with the attacker's bonus, and the damage it did on a hit
+#[derive(Debug, Clone)]
+pub struct AttackOutcome {
+    /// The profile used, the roll and the target's AC
+    pub lines: Vec<String>,
+    /// The d20 expression rolled, for script hooks
+    pub expression: String,
+    pub roll: RollResult,
+    pub hit: bool,
+    /// None on a miss, or when the attacker has no profile for the attack and the
+    /// damage has to be entered
+    pub damage: Option<AttackDamage>,
+}
+
+/// The bonus for an attack without a profile, from the sheet: proficiency plus
+/// Dexterity for a weapon that takes ammunition, Strength otherwise
+fn sheet_bonus(sheet: &Character, weapon: Option<&str>) -> i32 {
+    let ranged = weapon.is_some_and(|w| crate::ammo::ammo_for_weapon(w).is_some());
+    let score = if ranged { sheet.dext } else { sheet.stre };
+    sheet.prof_bonus.unwrap_or(0) as i32 + score.map_or(0, |s| Character::calculate_modifier(s) as i32)
+}
+
+/// Roll `attacker`'s attack against `target`. A profile matching the weapon (or the
+/// first profile) gives the bonus and damage; a sheet without one still gives a
+/// bonus; anyone else rolls a bare d20. A natural 20 always hits and a 1 always misses.
+pub fn resolve_attack(attacker: Option<&Combatant>, target: &Combatant, weapon: Option<&str>, kind: Option<DamageType>,
+    edge: Option<Edge>, characters: &[Character]) -> Result<AttackOutcome> {
+    let sheet = attacker.and_then(|a| a.sheet(characters));
+    let attacks = attacker.map(|a| a.attack_profiles(characters)).unwrap_or_default();
+    let profile = choose_attack(attacks, weapon);
+    let mut lines = Vec::new();
+    let bonus = match (profile, sheet) {
+        (Some(profile), _) => {
+            lines.push(format!("🗡️ {}", profile.describe()));
+            profile.to_hit
+        }
+        (None, Some(sheet)) => sheet_bonus(sheet, weapon),
+        (None, None) => 0,
+    };
+    let expression = match bonus {
+        0 => Edge::d20(edge).to_string(),
+        bonus => format!("{}{:+}", Edge::d20(edge), bonus),
+    };
+    let roll = roll_dice_with_crits(&expression).map_err(AppError::ValidationError)?;
+    lines.push(format!("⚔️  Attack Roll{}: {}", Edge::with(edge), roll));
+    lines.extend(roll.crit.clone());
+    lines.push(format!("🎯 Target AC: {}", target.ac));
+    let critical = roll.is_critical_success();
+    let hit = critical || (roll.natural() != 1 && roll.total >= target.ac);
+    let mut damage = None;
+    if hit && let Some(dice) = profile.and_then(|p| p.damage.as_deref()) {
+        let dice = if critical { crit_damage(dice) } else { dice.to_string() };
+        let damage_roll = roll_expression(&dice).map_err(AppError::ValidationError)?;
+        let kind = kind.or(profile.and_then(|p| p.damage_type));
+        let doubled = if critical { " (critical: dice doubled)" } else { "" };
+        let line = format!("🎲 Damage roll{}: {}{}", doubled, damage_roll, kind.map(|k| format!(" {}", k.name())).unwrap_or_default());
+        damage = Some(AttackDamage { amount: damage_roll.total.max(0), kind, line });
+    }
+    Ok(AttackOutcome { lines, expression, roll, hit, damage })
+}
+
+const USAGE: &str = "Usage: attacks <name> [add <attack> +<bonus>, <damage> <type> | remove <attack>]";
+
+/// `attacks <name>` lists someone's attacks, `attacks <name> add Longsword +5, 1d8+3 slashing`
+/// gives them one and `attacks <name> remove longsword` takes it away. Returns the lines to show.
+pub fn attacks_command(owner: &str, attacks: &mut Vec<AttackProfile>, args: &[&str]) -> Vec<String> {
+    match args {
+        [] if attacks.is_empty() => vec![format!("{} has no attacks. Add one with 'attacks {} add Longsword +5, 1d8+3 slashing'", owner, owner)],
+        [] => std::iter::once(format!("⚔️ {}'s attacks:", owner))
+            .chain(attacks.iter().map(|a| format!("  {}", a.describe())))
+            .collect(),
+        [action, attack @ ..] if action.eq_ignore_ascii_case("add") && !attack.is_empty() => match AttackProfile::parse(&attack.join(" ")) {
+            Ok(profile) => {
+                let line = format!("⚔️ {} can now attack with {}", owner, profile.describe());
+                attacks.retain(|a| !a.name.eq_ignore_ascii_case(&profile.name));
+                attacks.push(profile);
+                vec![line]
+            }
+            Err(e) => vec![format!("❌ {}", e)],
+        },
+        [action, name @ ..] if action.eq_ignore_ascii_case("remove") && !name.is_empty() => {
+            let name = name.join(" ");
+            let before = attacks.len();
+            attacks.retain(|a| !a.name.eq_ignore_ascii_case(&name));
+            if attacks.len() < before {
+                vec![format!("Removed {} from {}'s attacks", name, owner)]
+            } else {
+                vec![format!("❌ {} has no attack called {}", owner, name)]
+            }
+        }
+        _ => vec![USAGE.to_string()],
+    }
+}
@@ -0,0 +1,189 @@
+use crate::character::Character;
+use crate::error_handling::{AppError, Result, ResultExt};
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+/// Homebrew backgrounds are added (or override built-in ones by name) from this file
+pub const BACKGROUNDS_FILE: &str = "campaign/backgrounds.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Background {
+    pub name: String,
+    pub skills: Vec<String>,
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// Extra languages of the player's choice
+    #[serde(default)]
+    pub languages: u8,
+    #[serde(default)]
+    pub equipment: Vec<String>,
+    #[serde(default)]
+    pub gold: u32,
+    pub feature: String,
+    pub feature_text: String,
+}
+
+impl Background {
+    pub fn summary(&self) -> String {
+        format!("{} - {} ({})", self.name, self.skills.join(", "), self.feature)
+    }
+
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = vec![format!("📜 {}", self.name), format!("  Skills: {}", self.skills.join(", "))];
+        if !self.tools.is_empty() {
+            lines.push(format!("  Tools: {}", self.tools.join(", ")));
+        }
+        if self.languages > 0 {
+            lines.push(format!("  Languages: {} of your choice", self.languages));
+        }
+        let mut equipment = self.equipment.clone();
+        if self.gold > 0 {
+            equipment.push(format!("{} gp", self.gold));
+        }
+        if !equipment.is_empty() {
+            lines.push(format!("  Equipment: {}", equipment.join(", ")));
+        }
+        lines.push(format!("  Feature - {}: {}", self.feature, self.feature_text));
+        lines
+    }
+
+    /// Give a character this background: its skill and tool proficiencies, and its
+    /// starting gear and gold. Returns what was added.
+    pub fn apply(&self, character: &mut Character) -> Vec<String> {
+        character.background = Some(self.name.clone());
+        let mut gained = Vec::new();
+        for proficiency in self.skills.iter().chain(&self.tools) {
+            if !character.is_proficient(proficiency) {
+                character.proficiencies.push(proficiency.clone());
+                gained.push(proficiency.clone());
+            }
+        }
+        character.inventory.extend(self.equipment.iter().cloned());
+        if self.gold > 0 {
+            character.inventory.push(format!("{} gp", self.gold));
+        }
+
+        let mut lines = vec![format!("📜 {} is now a {}", character.name, self.name)];
+        if !gained.is_empty() {
+            lines.push(format!("  Proficient in: {}", gained.join(", ")));
+        }
+        if !self.equipment.is_empty() || self.gold > 0 {
+            lines.push(format!("  Added {} item(s) and {} gp to the inventory", self.equipment.len(), self.gold));
+        }
+        if self.languages > 0 {
+            lines.push(format!("  Choose {} extra language(s)", self.languages));
+        }
+        lines.push(format!("  Feature - {}: {}", self.feature, self.feature_text));
+        lines
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BackgroundFile {
+    backgrounds: Vec<Background>,
+}
+
+/// (name, skills, tools, languages, equipment, gold, feature, feature text)
+type BackgroundRow = (&'static str, &'static [&'static str], &'static [&'static str], u8, &'static [&'static str], u32, &'static str, &'static str);
+
+/// The standard backgrounds, with each feature summed up in a sentence
+const BUILTIN: &[BackgroundRow] = &[
+    ("Acolyte", &["Insight", "Religion"], &[], 2,
+        &["Holy symbol", "Prayer book", "5 sticks of incense", "Vestments", "Common clothes"], 15,
+        "Shelter of the Faithful", "Temples of your faith give you and your companions free healing and care, and its priests will support you"),
+    ("Charlatan", &["Deception", "Sleight of Hand"], &["Disguise kit", "Forgery kit"], 0,
+        &["Fine clothes", "Disguise kit", "Con tools"], 15,
+        "False Identity", "You keep a second identity with documents and contacts, and can forge documents you have seen"),
+    ("Criminal", &["Deception", "Stealth"], &["Thieves' tools", "Gaming set"], 0,
+        &["Crowbar", "Dark common clothes with a hood"], 15,
+        "Criminal Contact", "A reliable contact passes messages for you through a network of criminals"),
+    ("Entertainer", &["Acrobatics", "Performance"], &["Disguise kit", "Musical instrument"], 0,
+        &["Musical instrument", "Favor of an admirer", "Costume"], 15,
+        "By Popular Demand", "You can always find a place to perform, and are given modest or comfortable lodging and food for it"),
+    ("Folk Hero", &["Animal Handling", "Survival"], &["Artisan's tools", "Vehicles (land)"], 0,
+        &["Artisan's tools", "Shovel", "Iron pot", "Common clothes"], 10,
+        "Rustic Hospitality", "Common folk will shelter and hide you, as long as it doesn't put their lives at risk"),
+    ("Guild Artisan", &["Insight", "Persuasion"], &["Artisan's tools"], 1,
+        &["Artisan's tools", "Letter of introduction from your guild", "Traveler's clothes"], 15,
+        "Guild Membership", "Your guild gives you lodging and food when needed and backs you in legal matters, for dues of 5 gp a month"),
+    ("Hermit", &["Medicine", "Religion"], &["Herbalism kit"], 1,
+        &["Scroll case of notes", "Winter blanket", "Common clothes", "Herbalism kit"], 5,
+        "Discovery", "Your seclusion led you to a unique and powerful discovery; work out what it is with the DM"),
+    ("Noble", &["History", "Persuasion"], &["Gaming set"], 1,
+        &["Fine clothes", "Signet ring", "Scroll of pedigree"], 25,
+        "Position of Privilege", "People assume you belong wherever you are, and you can get an audience with local nobles"),
+    ("Outlander", &["Athletics", "Survival"], &["Musical instrument"], 1,
+        &["Staff", "Hunting trap", "Trophy from an animal you killed", "Traveler's clothes"], 10,
+        "Wanderer", "You remember maps and terrain, and where the land allows you can find food and water for up to six people each day"),
+    ("Sage", &["Arcana", "History"], &[], 2,
+        &["Bottle of black ink", "Quill", "Small knife", "Letter from a dead colleague", "Common clothes"], 10,
+        "Researcher", "When you don't know a piece of lore, you usually know where or from whom to learn it"),
+    ("Sailor", &["Athletics", "Perception"], &["Navigator's tools", "Vehicles (water)"], 0,
+        &["Belaying pin (club)", "50 feet of silk rope", "Lucky charm", "Common clothes"], 10,
+        "Ship's Passage", "You can get free passage on a sailing ship for yourself and your companions, in return for helping the crew"),
+    ("Soldier", &["Athletics", "Intimidation"], &["Gaming set", "Vehicles (land)"], 0,
+        &["Insignia of rank", "Trophy from a fallen enemy", "Gaming set", "Common clothes"], 10,
+        "Military Rank", "Soldiers loyal to your old army recognize your rank, and you can borrow simple gear and horses and visit friendly camps"),
+    ("Urchin", &["Sleight of Hand", "Stealth"], &["Disguise kit", "Thieves' tools"], 0,
+        &["Small knife", "Map of your home city", "Pet mouse", "Token to remember your parents by", "Common clothes"], 10,
+        "City Secrets", "You know a city's hidden ways, and outside combat you can lead others through it at twice your normal pace"),
+];
+
+pub fn builtin_backgrounds() -> Vec<Background> {
+    BUILTIN.iter().map(|&(name, skills, tools, languages, equipment, gold, feature, feature_text)| Background {
+        name: name.to_string(),
+        skills: skills.iter().map(|s| s.to_string()).collect(),
+        tools: tools.iter().map(|t| t.to_string()).collect(),
+        languages,
+        equipment: equipment.iter().map(|e| e.to_string()).collect(),
+        gold,
+        feature: feature.to_string(),
+        feature_text: feature_text.to_string(),
+    }).collect()
+}
+
+/// Built-in backgrounds plus any from the campaign's backgrounds file
+#[derive(Debug, Clone)]
+pub struct BackgroundBook {
+    pub backgrounds: Vec<Background>,
+}
+
+impl Default for BackgroundBook {
+    fn default() -> Self {
+        BackgroundBook { backgrounds: builtin_backgrounds() }
+    }
+}
+
+impl BackgroundBook {
+    /// Parse `[[backgrounds]]` entries; one with a built-in's name replaces it.
+    /// Skills must be real skills, and are stored with their usual spelling.
+    pub fn add_toml(&mut self, source: &str) -> Result<usize> {
+        let file: BackgroundFile = toml::from_str(source).map_err(|e| AppError::ParseError(e.to_string()))?;
+        let count = file.backgrounds.len();
+        for mut background in file.backgrounds {
+            for skill in &mut background.skills {
+                *skill = crate::dc::skill_name(skill).ok_or_else(|| AppError::ValidationError(
+                    format!("Background '{}' has an unknown skill '{}'", background.name, skill)))?.to_string();
+            }
+            self.backgrounds.retain(|b| !b.name.eq_ignore_ascii_case(&background.name));
+            self.backgrounds.push(background);
+        }
+        Ok(count)
+    }
+
+    pub fn load_file(&mut self, path: &Path) -> Result<usize> {
+        if !path.exists() {
+            return Ok(0);
+        }
+        let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        self.add_toml(&contents).context(format!("Background file {}", path.display()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Background> {
+        self.backgrounds.iter().find(|b| b.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.backgrounds.iter().map(|b| b.name.as_str()).collect()
+    }
+}
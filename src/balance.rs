@@ -0,0 +1,129 @@
+use crate::combat::{CombatTracker, Combatant};
+use crate::pacing::hp_percent;
+use crate::tables::TableRegistry;
+use crate::tactics::with_party;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Rounds the party has to stay healthy before the fight counts as too easy
+const EASY_AFTER_ROUNDS: i32 = 3;
+/// Party HP above this share of the maximum, after those rounds, is too easy
+const EASY_PARTY_HP_PERCENT: u32 = 60;
+/// Party members down at once for the fight to count as too hard
+const HARD_PCS_DOWN: usize = 2;
+
+/// Which way a fight has tipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tilt {
+    TooEasy,
+    TooHard,
+}
+
+fn foes(tracker: &CombatTracker) -> impl Iterator<Item = &Combatant> {
+    tracker.combatants.iter().filter(|c| !with_party(c) && c.current_hp > 0)
+}
+
+/// Whether the fight has tipped: two PCs down is too hard, and the party above 60% HP
+/// after three full rounds is too easy. None once the foes are all down.
+pub fn assess(tracker: &CombatTracker) -> Option<Tilt> {
+    foes(tracker).next()?;
+    let party = || tracker.combatants.iter().filter(|c| c.is_player);
+    if party().filter(|c| c.current_hp <= 0).count() >= HARD_PCS_DOWN {
+        Some(Tilt::TooHard)
+    } else if tracker.round_number > EASY_AFTER_ROUNDS && party().next().is_some() && hp_percent(party()) > EASY_PARTY_HP_PERCENT {
+        Some(Tilt::TooEasy)
+    } else {
+        None
+    }
+}
+
+/// Concrete ways to bring the fight back, for the DM's eyes only
+pub fn suggestions<R: Rng + ?Sized>(tracker: &CombatTracker, tilt: Tilt, tables: &TableRegistry, rng: &mut R) -> Vec<String> {
+    let party = tracker.combatants.iter().filter(|c| c.is_player);
+    let mut lines = Vec::new();
+    match tilt {
+        Tilt::TooHard => {
+            let down: Vec<&str> = party.filter(|c| c.current_hp <= 0).map(|c| c.name.as_str()).collect();
+            lines.push(format!("⚖️ The fight has tipped against the party ({} down). Ways to ease off:", down.join(", ")));
+            let cuts: Vec<String> = foes(tracker)
+                .map(|c| format!("'hit {} {}'", tracker.display_name(c), (c.current_hp / 4).max(1)))
+                .collect();
+            lines.push(format!("  • Take a quarter off what the monsters have left: {}", cuts.join(", ")));
+            match tracker.morale_groups.first() {
+                Some(_) => lines.push(format!("  • Call a morale check: {}", tracker.morale_groups.iter()
+                    .map(|g| format!("'morale {}'", g.name)).collect::<Vec<_>>().join(", "))),
+                None => lines.push("  • Have the monsters check morale ('morale add <group> <2-12> <members...>'), or let the weakest flee".to_string()),
+            }
+            lines.push("  • Have the monsters take prisoners or loot the fallen instead of finishing them".to_string());
+        }
+        Tilt::TooEasy => {
+            lines.push(format!("⚖️ The party is cruising ({}% HP after {} rounds). Ways to raise the stakes:",
+                hp_percent(party), tracker.round_number - 1));
+            match &tracker.reinforcement_table {
+                Some(table) => match tables.roll_with(table, rng) {
+                    Ok(result) => lines.push(format!("  • Reinforcements arrive: {} (rolled on '{}')", result, table)),
+                    Err(e) => lines.push(format!("  • ⚠️ Could not roll reinforcements on '{}': {}", table, e)),
+                },
+                None => lines.push("  • Bring in reinforcements: choose an encounter table with 'adjust table <name>'".to_string()),
+            }
+            let boosts: Vec<String> = foes(tracker)
+                .filter(|c| c.current_hp < c.max_hp)
+                .map(|c| format!("'heal {} {}'", tracker.display_name(c), ((c.max_hp - c.current_hp) / 2).max(1)))
+                .collect();
+            if !boosts.is_empty() {
+                lines.push(format!("  • Give the monsters back half the HP they've lost: {}", boosts.join(", ")));
+            }
+            lines.push("  • Let the monsters fight smarter: focus the weakest PC, use cover, or spring a hazard".to_string());
+        }
+    }
+    lines
+}
+
+/// Suggestions when the fight has newly tipped, for the turn announcements. Each tilt
+/// is suggested once until the fight tips back.
+pub fn turn_hints<R: Rng + ?Sized>(tracker: &mut CombatTracker, tables: &TableRegistry, rng: &mut R) -> Vec<String> {
+    let tilt = assess(tracker);
+    if tilt == tracker.suggested_tilt {
+        return Vec::new();
+    }
+    tracker.suggested_tilt = tilt;
+    tilt.map(|tilt| suggestions(tracker, tilt, tables, rng)).unwrap_or_default()
+}
+
+/// `adjust` weighs up the fight now, and `adjust table <name|none>` picks the encounter
+/// table reinforcements are rolled on
+pub fn adjust_command<R: Rng + ?Sized>(tracker: &mut CombatTracker, args: &[&str], tables: &TableRegistry, rng: &mut R) -> Vec<String> {
+    match args {
+        [] => match assess(tracker) {
+            Some(tilt) => suggestions(tracker, tilt, tables, rng),
+            None => {
+                let party = hp_percent(tracker.combatants.iter().filter(|c| c.is_player));
+                let foes = hp_percent(tracker.combatants.iter().filter(|c| !with_party(c)));
+                vec![format!("⚖️ The fight looks balanced: party at {}% HP, foes at {}%, round {}", party, foes, tracker.round_number)]
+            }
+        },
+        [table] if table.eq_ignore_ascii_case("table") => match &tracker.reinforcement_table {
+            Some(table) => vec![format!("Reinforcements come from '{}'", table)],
+            None => vec!["No reinforcement table. Usage: adjust table <name|none>".to_string()],
+        },
+        [table, name @ ..] if table.eq_ignore_ascii_case("table") => {
+            let name = name.join(" ");
+            if ["none", "off", "clear"].iter().any(|word| name.eq_ignore_ascii_case(word)) {
+                tracker.reinforcement_table = None;
+                return vec!["No more reinforcement table".to_string()];
+            }
+            match tables.get(&name) {
+                Some(table) => {
+                    tracker.reinforcement_table = Some(table.name.clone());
+                    vec![format!("⚖️ Reinforcements will be rolled on '{}' if the fight gets too easy", table.name)]
+                }
+                None => {
+                    let known: Vec<&str> = tables.tables().map(|t| t.name.as_str()).collect();
+                    vec![format!("❌ No table '{}'. Tables: {}", name,
+                        if known.is_empty() { format!("none yet (add them in {})", crate::tables::TABLES_DIR) } else { known.join(", ") })]
+                }
+            }
+        }
+        _ => vec!["Usage: adjust [table <name|none>]".to_string()],
+    }
+}
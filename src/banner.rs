@@ -0,0 +1,102 @@
+/// Height in terminal rows of one line of banner text
+pub const BANNER_HEIGHT: usize = 5;
+
+/// A small FIGlet-style block font. Rows use '#' for filled cells.
+fn glyph(c: char) -> [&'static str; BANNER_HEIGHT] {
+    match c.to_ascii_uppercase() {
+        'A' => [" ### ", "#   #", "#####", "#   #", "#   #"],
+        'B' => ["#### ", "#   #", "#### ", "#   #", "#### "],
+        'C' => [" ####", "#    ", "#    ", "#    ", " ####"],
+        'D' => ["#### ", "#   #", "#   #", "#   #", "#### "],
+        'E' => ["#####", "#    ", "#### ", "#    ", "#####"],
+        'F' => ["#####", "#    ", "#### ", "#    ", "#    "],
+        'G' => [" ####", "#    ", "#  ##", "#   #", " ####"],
+        'H' => ["#   #", "#   #", "#####", "#   #", "#   #"],
+        'I' => ["###", " # ", " # ", " # ", "###"],
+        'J' => ["  ###", "   # ", "   # ", "#  # ", " ##  "],
+        'K' => ["#   #", "#  # ", "###  ", "#  # ", "#   #"],
+        'L' => ["#    ", "#    ", "#    ", "#    ", "#####"],
+        'M' => ["#   #", "## ##", "# # #", "#   #", "#   #"],
+        'N' => ["#   #", "##  #", "# # #", "#  ##", "#   #"],
+        'O' => [" ### ", "#   #", "#   #", "#   #", " ### "],
+        'P' => ["#### ", "#   #", "#### ", "#    ", "#    "],
+        'Q' => [" ### ", "#   #", "# # #", "#  # ", " ## #"],
+        'R' => ["#### ", "#   #", "#### ", "#  # ", "#   #"],
+        'S' => [" ####", "#    ", " ### ", "    #", "#### "],
+        'T' => ["#####", "  #  ", "  #  ", "  #  ", "  #  "],
+        'U' => ["#   #", "#   #", "#   #", "#   #", " ### "],
+        'V' => ["#   #", "#   #", "#   #", " # # ", "  #  "],
+        'W' => ["#   #", "#   #", "# # #", "## ##", "#   #"],
+        'X' => ["#   #", " # # ", "  #  ", " # # ", "#   #"],
+        'Y' => ["#   #", " # # ", "  #  ", "  #  ", "  #  "],
+        'Z' => ["#####", "   # ", "  #  ", " #   ", "#####"],
+        '0' => [" ### ", "#  ##", "# # #", "##  #", " ### "],
+        '1' => [" # ", "## ", " # ", " # ", "###"],
+        '2' => [" ### ", "#   #", "  ## ", " #   ", "#####"],
+        '3' => ["#### ", "    #", " ### ", "    #", "#### "],
+        '4' => ["#   #", "#   #", "#####", "    #", "    #"],
+        '5' => ["#####", "#    ", "#### ", "    #", "#### "],
+        '6' => [" ### ", "#    ", "#### ", "#   #", " ### "],
+        '7' => ["#####", "    #", "   # ", "  #  ", "  #  "],
+        '8' => [" ### ", "#   #", " ### ", "#   #", " ### "],
+        '9' => [" ### ", "#   #", " ####", "    #", " ### "],
+        ' ' => ["   ", "   ", "   ", "   ", "   "],
+        '-' => ["    ", "    ", "####", "    ", "    "],
+        '.' => [" ", " ", " ", " ", "#"],
+        ',' => ["  ", "  ", "  ", " #", "# "],
+        ':' => [" ", "#", " ", "#", " "],
+        '!' => ["#", "#", "#", " ", "#"],
+        '\'' => ["#", "#", " ", " ", " "],
+        '(' => [" #", "# ", "# ", "# ", " #"],
+        ')' => ["# ", " #", " #", " #", "# "],
+        _ => ["### ", "   #", " ## ", "    ", " #  "],
+    }
+}
+
+/// Render text as BANNER_HEIGHT rows of block letters, one column between letters.
+/// All rows are the same width so they stay aligned when centered.
+pub fn render_banner(text: &str) -> Vec<String> {
+    let mut rows = vec![String::new(); BANNER_HEIGHT];
+    for (i, c) in text.trim().chars().enumerate() {
+        for (row, line) in rows.iter_mut().zip(glyph(c)) {
+            if i > 0 {
+                row.push(' ');
+            }
+            row.push_str(&line.replace('#', "█"));
+        }
+    }
+    rows
+}
+
+/// Width in columns of the banner for `text`
+pub fn banner_width(text: &str) -> usize {
+    render_banner(text).iter().map(|row| row.chars().count()).max().unwrap_or(0)
+}
+
+/// Render text as banners no wider than `max_width`, breaking between words.
+/// A single word that is still too wide falls back to plain uppercase text.
+pub fn render_banner_wrapped(text: &str, max_width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if banner_width(&format!("{} {}", line, word)) <= max_width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            rows.push(String::new());
+        }
+        if banner_width(line) <= max_width {
+            rows.extend(render_banner(line));
+        } else {
+            rows.push(line.to_uppercase());
+        }
+    }
+    rows
+}
@@ -0,0 +1,188 @@
+use crate::error_handling::{AppError, Result, ResultExt};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, path::Path};
+
+/// Strongholds are saved with the rest of the campaign
+pub const BASTIONS_FILE: &str = "campaign/bastions.ron";
+/// Wages and upkeep per hireling per bastion turn (one week)
+pub const UPKEEP_GP_PER_HIRELING: i64 = 5;
+pub const DAYS_PER_BASTION_TURN: u32 = 7;
+
+/// What a facility's hirelings do during a bastion turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Order {
+    Craft,
+    Harvest,
+    Maintain,
+    Recruit,
+    Research,
+    Trade,
+}
+
+impl Order {
+    pub const ALL: [Order; 6] = [Order::Craft, Order::Harvest, Order::Maintain, Order::Recruit, Order::Research, Order::Trade];
+
+    pub fn parse(input: &str) -> Option<Order> {
+        Order::ALL.into_iter().find(|o| o.to_string().eq_ignore_ascii_case(input.trim()))
+    }
+}
+
+impl fmt::Display for Order {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Order::Craft => "Craft",
+            Order::Harvest => "Harvest",
+            Order::Maintain => "Maintain",
+            Order::Recruit => "Recruit",
+            Order::Research => "Research",
+            Order::Trade => "Trade",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Facility {
+    pub name: String,
+    pub hirelings: u32,
+    pub order: Option<Order>,
+}
+
+/// (d100 ceiling, event) rolled when a bastion is ordered to maintain
+const EVENTS: &[(u32, &str)] = &[
+    (50, "All is well. Nothing of note happens this week."),
+    (55, "Attack! Raiders strike the bastion; roll 6d6 and lose a defender for each 1."),
+    (58, "Criminal hireling: one hireling is caught with a secret past. Pay 1d6 x 100 gp in bribes or lose them."),
+    (63, "Extraordinary opportunity: a chance to host a festival or fund research for 500 gp, with a favor in return."),
+    (72, "Friendly visitors pay for the bastion's hospitality: gain 1d6 x 100 gp."),
+    (76, "A guest arrives seeking sanctuary and offers their help for a few weeks."),
+    (79, "Lost hirelings: one facility's hirelings quit; the facility is idle next turn."),
+    (83, "Magical discovery: hirelings uncover an uncommon potion or scroll."),
+    (91, "Refugees arrive asking for shelter; they offer 1d6 x 100 gp if they are taken in."),
+    (98, "Request for aid: a local leader asks for the bastion's defenders to help."),
+    (100, "Treasure! Hirelings find an art object or a rare trinket worth keeping."),
+];
+
+pub fn bastion_event(roll: u32) -> &'static str {
+    EVENTS.iter()
+        .find(|(ceiling, _)| roll <= *ceiling)
+        .map(|(_, event)| *event)
+        .unwrap_or(EVENTS[EVENTS.len() - 1].1)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bastion {
+    pub name: String,
+    pub owner: String,
+    pub gold: i64,
+    pub week: u32,
+    pub facilities: Vec<Facility>,
+}
+
+impl Bastion {
+    pub fn new(name: &str, owner: &str) -> Self {
+        Bastion { name: name.to_string(), owner: owner.to_string(), gold: 0, week: 0, facilities: Vec::new() }
+    }
+
+    pub fn facility_mut(&mut self, name: &str) -> Result<&mut Facility> {
+        let bastion = self.name.clone();
+        self.facilities.iter_mut()
+            .find(|f| f.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| AppError::NotFound(format!("Facility '{}' in {}", name, bastion)))
+    }
+
+    pub fn hirelings(&self) -> u32 {
+        self.facilities.iter().map(|f| f.hirelings).sum()
+    }
+
+    /// Resolve one week: each facility carries out its order, then upkeep is paid.
+    /// Orders are cleared afterwards, so they must be given again each turn.
+    pub fn take_turn<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Vec<String> {
+        self.week += 1;
+        let mut report = vec![format!("🏰 {} - week {}", self.name, self.week)];
+
+        for facility in &mut self.facilities {
+            let Some(order) = facility.order.take() else {
+                report.push(format!("  {}: idle", facility.name));
+                continue;
+            };
+            if facility.hirelings == 0 && order != Order::Recruit {
+                report.push(format!("  {}: no hirelings to carry out {}", facility.name, order));
+                continue;
+            }
+            let outcome = match order {
+                Order::Trade => {
+                    let income: i64 = (0..facility.hirelings).map(|_| rng.random_range(1..=6) * 10).sum();
+                    self.gold += income;
+                    format!("trade brings in {} gp", income)
+                }
+                Order::Recruit => {
+                    facility.hirelings += 1;
+                    format!("recruits a new hireling ({} now)", facility.hirelings)
+                }
+                Order::Maintain => bastion_event(rng.random_range(1..=100)).to_string(),
+                Order::Craft => "hirelings craft a common item or mundane gear for the owner".to_string(),
+                Order::Harvest => "hirelings gather materials for crafting".to_string(),
+                Order::Research => "hirelings dig up a rumor or answer a question about the area".to_string(),
+            };
+            report.push(format!("  {} ({}): {}", facility.name, order, outcome));
+        }
+
+        let upkeep = self.hirelings() as i64 * UPKEEP_GP_PER_HIRELING;
+        self.gold -= upkeep;
+        report.push(format!("  Upkeep: {} gp • Treasury: {} gp", upkeep, self.gold));
+        report
+    }
+
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = vec![format!("🏰 {} (owner {}, week {}, treasury {} gp)", self.name, self.owner, self.week, self.gold)];
+        if self.facilities.is_empty() {
+            lines.push("  No facilities yet".to_string());
+        }
+        for facility in &self.facilities {
+            let order = facility.order.map(|o| o.to_string()).unwrap_or_else(|| "no order".to_string());
+            lines.push(format!("  {} - {} hireling(s), {}", facility.name, facility.hirelings, order));
+        }
+        lines
+    }
+}
+
+/// Every stronghold in the campaign
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bastions {
+    pub bastions: Vec<Bastion>,
+}
+
+impl Bastions {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        ron::de::from_str(&contents).map_err(|e| AppError::ParseError(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create campaign folder")?;
+        }
+        let serialized = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| AppError::ParseError(e.to_string()))?;
+        fs::write(path, serialized).context(format!("Failed to write {}", path.display()))
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Result<&mut Bastion> {
+        self.bastions.iter_mut()
+            .find(|b| b.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| AppError::NotFound(format!("Bastion '{}'", name)))
+    }
+
+    pub fn add(&mut self, bastion: Bastion) -> Result<()> {
+        if self.bastions.iter().any(|b| b.name.eq_ignore_ascii_case(&bastion.name)) {
+            return Err(AppError::ValidationError(format!("A bastion named '{}' already exists", bastion.name)));
+        }
+        self.bastions.push(bastion);
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+use crate::error_handling::{Result, ResultExt};
+use flate2::{read::GzDecoder, Compression, GzBuilder};
+use std::{fs, io::{Read, Write}, path::{Path, PathBuf}, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+/// Cap on the search cache's size on disk, overridden by DND_TOOLS_CACHE_MAX_MB
+pub const DEFAULT_CACHE_MAX_MB: u64 = 50;
+/// Cached pages older than this are fetched again, overridden by DND_TOOLS_CACHE_TTL_DAYS
+pub const DEFAULT_CACHE_TTL_DAYS: u64 = 30;
+
+/// Read the cache size limit from the environment, falling back to the default
+pub fn cache_max_bytes() -> u64 {
+    std::env::var("DND_TOOLS_CACHE_MAX_MB")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_CACHE_MAX_MB)
+        * 1024 * 1024
+}
+
+/// How long a cached page stays fresh; None when DND_TOOLS_CACHE_TTL_DAYS is 0, so
+/// pages never expire
+pub fn cache_ttl() -> Option<Duration> {
+    let days = std::env::var("DND_TOOLS_CACHE_TTL_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_CACHE_TTL_DAYS);
+    (days > 0).then(|| Duration::from_secs(days * 24 * 60 * 60))
+}
+
+fn compressed_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".gz");
+    PathBuf::from(name)
+}
+
+/// Read an entry, marking it as just used. Entries written as plain text by
+/// older versions are still read.
+pub fn read_entry(path: &Path) -> Option<String> {
+    let gz = compressed_path(path);
+    let Ok(file) = fs::File::open(&gz) else {
+        return fs::read_to_string(path).ok();
+    };
+    let mut contents = String::new();
+    GzDecoder::new(file).read_to_string(&mut contents).ok()?;
+    // The modified time doubles as the last-used time for eviction
+    if let Err(e) = fs::File::options().write(true).open(&gz).and_then(|f| f.set_modified(SystemTime::now())) {
+        log::debug!("Could not mark {} as used: {}", gz.display(), e);
+    }
+    Some(contents)
+}
+
+/// Write an entry gzipped, replacing any plain-text copy
+pub fn write_entry(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create search cache folder")?;
+    }
+    let gz = compressed_path(path);
+    let file = fs::File::create(&gz).context(format!("Failed to write {}", gz.display()))?;
+    // The gzip header keeps when the page was fetched, for expiry
+    let written = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as u32).unwrap_or_default();
+    let mut encoder = GzBuilder::new().mtime(written).write(file, Compression::default());
+    encoder.write_all(contents.as_bytes())
+        .and_then(|_| encoder.finish().map(|_| ()))
+        .context(format!("Failed to write {}", gz.display()))?;
+    if path.exists() {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
+
+/// When an entry was written. Plain-text entries from older versions go by their
+/// file's modified time.
+pub fn written_at(path: &Path) -> Option<SystemTime> {
+    let gz = compressed_path(path);
+    match fs::File::open(&gz) {
+        Ok(file) => GzDecoder::new(file).header()
+            .map(|header| header.mtime())
+            .filter(|secs| *secs > 0)
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs as u64)),
+        Err(_) => fs::metadata(path).and_then(|meta| meta.modified()).ok(),
+    }
+}
+
+/// Whether an entry is older than the TTL and should be fetched again
+pub fn is_expired(path: &Path, ttl: Option<Duration>) -> bool {
+    let (Some(ttl), Some(written)) = (ttl, written_at(path)) else { return false };
+    written.elapsed().is_ok_and(|age| age > ttl)
+}
+
+/// The cached copies of a page, one per content type it was found under
+pub fn entries_for(cache_dir: &Path, slug: &str) -> Vec<PathBuf> {
+    let file_name = format!("{}.txt", slug);
+    let Ok(read) = fs::read_dir(cache_dir) else { return Vec::new() };
+    read.flatten()
+        .map(|dir| dir.path().join(&file_name))
+        .filter(|path| path.exists() || compressed_path(path).exists())
+        .collect()
+}
+
+/// Remove every cached page. Returns how many were removed.
+pub fn clear(cache_dir: &Path) -> usize {
+    let removed = entries(cache_dir).into_iter().filter(|entry| fs::remove_file(&entry.path).is_ok()).count();
+    log::info!("Cleared {} entries from the search cache", removed);
+    removed
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub last_used: SystemTime,
+}
+
+/// Every file under the cache folder
+pub fn entries(cache_dir: &Path) -> Vec<CacheEntry> {
+    let mut found = Vec::new();
+    let mut dirs = vec![cache_dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(read) = fs::read_dir(&dir) else { continue };
+        for entry in read.flatten() {
+            let Ok(meta) = entry.metadata() else { continue };
+            if meta.is_dir() {
+                dirs.push(entry.path());
+            } else {
+                found.push(CacheEntry {
+                    path: entry.path(),
+                    bytes: meta.len(),
+                    last_used: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                });
+            }
+        }
+    }
+    found
+}
+
+/// Remove the least recently used entries until the cache fits in `max_bytes`.
+/// Returns how many were removed.
+pub fn evict(cache_dir: &Path, max_bytes: u64) -> usize {
+    let mut entries = entries(cache_dir);
+    let mut total: u64 = entries.iter().map(|e| e.bytes).sum();
+    entries.sort_by_key(|e| e.last_used);
+    let mut removed = 0;
+    for entry in entries {
+        if total <= max_bytes {
+            break;
+        }
+        if fs::remove_file(&entry.path).is_ok() {
+            log::info!("Evicted {} from the search cache", entry.path.display());
+            total = total.saturating_sub(entry.bytes);
+            removed += 1;
+        }
+    }
+    removed
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub bytes: u64,
+    pub max_bytes: u64,
+    /// Entries older than the TTL, fetched again on their next lookup
+    pub expired: usize,
+    pub ttl: Option<Duration>,
+    /// Entry count per content type folder, e.g. ("spell", 12)
+    pub by_type: Vec<(String, usize)>,
+}
+
+impl CacheStats {
+    pub fn collect(cache_dir: &Path, max_bytes: u64, ttl: Option<Duration>) -> Self {
+        let entries = entries(cache_dir);
+        // Expiry goes by the entry's own path, without the .gz
+        let expired = entries.iter()
+            .map(|entry| match entry.path.extension() {
+                Some(ext) if ext == "gz" => entry.path.with_extension(""),
+                _ => entry.path.clone(),
+            })
+            .filter(|path| is_expired(path, ttl))
+            .count();
+        let mut by_type: Vec<(String, usize)> = Vec::new();
+        for entry in &entries {
+            let kind = entry.path.parent()
+                .filter(|p| *p != cache_dir)
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "other".to_string());
+            match by_type.iter_mut().find(|(k, _)| *k == kind) {
+                Some((_, count)) => *count += 1,
+                None => by_type.push((kind, 1)),
+            }
+        }
+        by_type.sort();
+        CacheStats { entries: entries.len(), bytes: entries.iter().map(|e| e.bytes).sum(), max_bytes, expired, ttl, by_type }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("🗄️ Search cache: {} page(s), {} of {} used",
+            self.entries, format_size(self.bytes), format_size(self.max_bytes))];
+        lines.extend(self.by_type.iter().map(|(kind, count)| format!("  {:<10} {}", kind, count)));
+        lines.push(match self.ttl {
+            Some(ttl) => format!("  {} older than {} days, fetched again on their next lookup", self.expired, ttl.as_secs() / (24 * 60 * 60)),
+            None => "  Pages never expire (DND_TOOLS_CACHE_TTL_DAYS=0)".to_string(),
+        });
+        lines
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1024 * 1024 => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+        b if b >= 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{} B", b),
+    }
+}
+
+/// `cache stats`: the output shared by the TUI and CLI search modes. `cache clear`
+/// asks first, so each frontend confirms it and then calls `clear_confirmed`;
+/// `cache refresh <name>` needs the network, so the search client handles it.
+pub fn cache_command(args: &[&str]) -> Vec<String> {
+    let cache_dir = Path::new(crate::search::CACHE_DIR);
+    match args {
+        ["stats"] | [] => CacheStats::collect(cache_dir, cache_max_bytes(), cache_ttl()).lines(),
+        _ => vec!["Usage: cache [stats|refresh <name>]".to_string()],
+    }
+}
+
+/// The question `cache clear` asks before deleting anything
+pub fn clear_prompt() -> String {
+    let entries = entries(Path::new(crate::search::CACHE_DIR)).len();
+    format!("⚠️ Delete all {} cached page(s)? They're fetched from the wiki again when next looked up", entries)
+}
+
+/// Empty the cache once the user has said yes
+pub fn clear_confirmed() -> String {
+    format!("🗑️ Removed {} cached page(s)", clear(Path::new(crate::search::CACHE_DIR)))
+}
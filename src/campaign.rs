@@ -0,0 +1,235 @@
+use crate::content::{license_notice, Source};
+use crate::error_handling::{AppError, Result, ResultExt};
+use crate::file_manager::{CHARACTERS_DIR, NPCS_DIR, TRASH_DIR};
+use serde::{Deserialize, Serialize};
+use std::{fs, io::{Read, Write}, path::{Path, PathBuf}, time::{SystemTime, UNIX_EPOCH}};
+use zip::{write::SimpleFileOptions, ZipArchive, ZipWriter};
+
+pub const EXPORTS_DIR: &str = "exports";
+/// Encounters, notes, tables, scripts and the rest of the DM's homebrew
+pub const CAMPAIGN_DIR: &str = "campaign";
+const MANIFEST_NAME: &str = "campaign.toml";
+const NOTICE_NAME: &str = "NOTICE.txt";
+pub const ARCHIVE_EXTENSION: &str = "campaign.zip";
+/// The active campaign's name, kept beside its homebrew so it travels with an export
+pub const CAMPAIGN_INFO_FILE: &str = "campaign/campaign.toml";
+
+/// Folders copied into an export. The search cache holds copyrighted wiki
+/// pages, so it is never included.
+const EXPORTED_DIRS: &[&str] = &[CHARACTERS_DIR, NPCS_DIR, CAMPAIGN_DIR];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    pub exported_by: String,
+    pub exported_at: u64,
+    pub files: usize,
+}
+
+/// What `campaign/campaign.toml` holds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CampaignInfo {
+    name: String,
+}
+
+/// The name of the campaign whose data is rooted at `base`, if one has been named
+pub fn campaign_name_in(base: &Path) -> Option<String> {
+    let contents = fs::read_to_string(base.join(CAMPAIGN_INFO_FILE)).ok()?;
+    match toml::from_str::<CampaignInfo>(&contents) {
+        Ok(info) => Some(info.name).filter(|name| !name.trim().is_empty()),
+        Err(e) => {
+            log::warn!("Ignoring {}: {}", CAMPAIGN_INFO_FILE, e);
+            None
+        }
+    }
+}
+
+/// Record the campaign's name unless one is already recorded
+fn name_campaign_in(base: &Path, name: &str) -> Result<()> {
+    if base.join(CAMPAIGN_INFO_FILE).exists() {
+        return Ok(());
+    }
+    set_campaign_name_in(base, name)
+}
+
+/// Record the campaign's name, replacing any name it already has
+pub fn set_campaign_name_in(base: &Path, name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(AppError::ValidationError("A campaign needs a name".to_string()));
+    }
+    let path = base.join(CAMPAIGN_INFO_FILE);
+    let info = toml::to_string(&CampaignInfo { name: name.trim().to_string() })
+        .map_err(|e| AppError::ParseError(format!("Failed to write {}: {}", CAMPAIGN_INFO_FILE, e)))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context(format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, info).context(format!("Failed to write {}", path.display()))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub campaign: String,
+    pub imported: Vec<String>,
+    /// Files already present on this machine, left untouched
+    pub skipped: Vec<String>,
+}
+
+/// Lowercase letters, digits and dashes, so the name is safe as a file name
+pub fn slug(name: &str) -> String {
+    let slug: String = name.trim().to_lowercase().chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-");
+    if slug.is_empty() { "campaign".to_string() } else { slug }
+}
+
+/// Every file under `dir` as a forward-slash path relative to `base`, skipping the character trash
+fn exported_files(base: &Path, dir: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut dirs = vec![base.join(dir)];
+    while let Some(current) = dirs.pop() {
+        let Ok(read) = fs::read_dir(&current) else { continue };
+        for entry in read.flatten() {
+            let path = entry.path();
+            let Ok(relative) = path.strip_prefix(base) else { continue };
+            let relative = relative.components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/");
+            if relative == TRASH_DIR {
+                continue;
+            }
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                found.push(relative);
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Write `<out_dir>/<name>.campaign.zip` holding the campaign data rooted at `base`
+pub fn export_campaign_in(base: &Path, out_dir: &Path, name: &str) -> Result<PathBuf> {
+    name_campaign_in(base, name)?;
+    let files: Vec<String> = EXPORTED_DIRS.iter().flat_map(|dir| exported_files(base, dir)).collect();
+    fs::create_dir_all(out_dir).context("Failed to create exports folder")?;
+    let path = out_dir.join(format!("{}.{}", slug(name), ARCHIVE_EXTENSION));
+    let file = fs::File::create(&path).context("Failed to create campaign export")?;
+
+    let manifest = Manifest {
+        name: name.trim().to_string(),
+        exported_by: format!("dnd_tools {}", env!("CARGO_PKG_VERSION")),
+        exported_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        files: files.len(),
+    };
+    let manifest = toml::to_string(&manifest)
+        .map_err(|e| AppError::ParseError(format!("Failed to write campaign manifest: {}", e)))?;
+
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+    let mut add = |name: &str, contents: &[u8]| -> Result<()> {
+        zip.start_file(name, options).context("Failed to add file to campaign export")?;
+        zip.write_all(contents).context("Failed to add file to campaign export")
+    };
+
+    add(MANIFEST_NAME, manifest.as_bytes())?;
+    add(NOTICE_NAME, license_notice(&[Source::Campaign, Source::Srd]).as_bytes())?;
+    for relative in &files {
+        let bytes = fs::read(base.join(relative)).context(format!("Failed to read {}", relative))?;
+        add(relative, &bytes)?;
+    }
+
+    zip.finish().context("Failed to finish campaign export")?;
+    log::info!("Exported campaign '{}' ({} files) to {}", name, files.len(), path.display());
+    Ok(path)
+}
+
+/// Unpack a campaign export into `base`. Files that already exist are kept, so
+/// importing never overwrites the receiving DM's own data.
+pub fn import_campaign_in(base: &Path, archive: &Path) -> Result<ImportReport> {
+    let file = fs::File::open(archive).context(format!("Failed to open {}", archive.display()))?;
+    let mut zip = ZipArchive::new(file)
+        .map_err(|e| AppError::FileError(format!("{} is not a campaign export: {}", archive.display(), e)))?;
+
+    let mut report = ImportReport::default();
+    let mut manifest = String::new();
+    zip.by_name(MANIFEST_NAME)
+        .map_err(|_| AppError::FileError(format!("{} has no {}", archive.display(), MANIFEST_NAME)))?
+        .read_to_string(&mut manifest)
+        .context("Failed to read campaign manifest")?;
+    let manifest: Manifest = toml::from_str(&manifest)
+        .map_err(|e| AppError::ParseError(format!("Invalid campaign manifest: {}", e)))?;
+    report.campaign = manifest.name;
+
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).context("Failed to read campaign export")?;
+        if entry.is_dir() {
+            continue;
+        }
+        // enclosed_name rejects absolute paths and `..`, so an archive can't write outside `base`
+        let Some(relative) = entry.enclosed_name() else {
+            log::warn!("Skipping unsafe path {} in campaign export", entry.name());
+            continue;
+        };
+        let in_exported_dir = relative.components().next()
+            .is_some_and(|first| EXPORTED_DIRS.iter().any(|dir| first.as_os_str() == *dir));
+        if !in_exported_dir {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let target = base.join(&relative);
+        if target.exists() {
+            report.skipped.push(name);
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).context(format!("Failed to create {}", parent.display()))?;
+        }
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).context(format!("Failed to read {}", name))?;
+        fs::write(&target, bytes).context(format!("Failed to write {}", target.display()))?;
+        report.imported.push(name);
+    }
+    // Archives from before the name was kept in the campaign folder still name it
+    name_campaign_in(base, &report.campaign)?;
+    log::info!("Imported campaign '{}': {} added, {} skipped", report.campaign, report.imported.len(), report.skipped.len());
+    Ok(report)
+}
+
+/// `dnd_tools campaign export <name>` / `dnd_tools campaign import <file>` / `dnd_tools campaign name <name>`
+pub fn run_campaign_command(args: &[String]) {
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["name", name @ ..] if !name.is_empty() => {
+            let name = name.join(" ");
+            match set_campaign_name_in(Path::new("."), &name) {
+                Ok(()) => println!("📜 This campaign is now called '{}'", name),
+                Err(e) => println!("❌ Could not name the campaign: {}", e),
+            }
+        }
+        ["export", name @ ..] if !name.is_empty() => {
+            let name = name.join(" ");
+            match export_campaign_in(Path::new("."), Path::new(EXPORTS_DIR), &name) {
+                Ok(path) => {
+                    println!("📦 Campaign '{}' exported to {}", name, path.display());
+                    println!("It holds your characters, NPCs, encounters, notes and homebrew. Cached wiki pages are left out.");
+                }
+                Err(e) => println!("❌ Could not export campaign: {}", e),
+            }
+        }
+        ["import", file] => match import_campaign_in(Path::new("."), Path::new(file)) {
+            Ok(report) => {
+                println!("📥 Imported campaign '{}': {} file(s) added", report.campaign, report.imported.len());
+                if !report.skipped.is_empty() {
+                    println!("Kept {} existing file(s) instead of overwriting them:", report.skipped.len());
+                    for name in &report.skipped {
+                        println!("  {}", name);
+                    }
+                }
+            }
+            Err(e) => println!("❌ Could not import campaign: {}", e),
+        },
+        _ => println!("Usage: dnd_tools campaign export <name> | dnd_tools campaign import <file> | dnd_tools campaign name <name>"),
+    }
+}
@@ -0,0 +1,238 @@
+use crate::character::Character;
+use crate::error_handling::{AppError, Result, ResultExt};
+use crate::names::NameRegistry;
+use rand::Rng;
+use rand::seq::IndexedRandom;
+use std::{fs, path::{Path, PathBuf}};
+
+/// Line prefix a contact's link to the character who met them is stored under on the NPC sheet
+const CONTACT_PREFIX: &str = "Contact: ";
+
+/// Who the character spends their evenings with, which sets the cost and the sort of people met
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Company {
+    Lower,
+    Middle,
+    Upper,
+}
+
+impl Company {
+    pub fn parse(text: &str) -> Option<Company> {
+        match text.trim().to_lowercase().as_str() {
+            "lower" | "low" => Some(Company::Lower),
+            "middle" | "mid" => Some(Company::Middle),
+            "upper" | "high" => Some(Company::Upper),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Company::Lower => "lower",
+            Company::Middle => "middle",
+            Company::Upper => "upper",
+        }
+    }
+
+    /// Gold spent for each workweek of carousing
+    pub fn cost_per_week(&self) -> u32 {
+        match self {
+            Company::Lower => 10,
+            Company::Middle => 50,
+            Company::Upper => 250,
+        }
+    }
+
+    fn occupations(&self) -> &'static [&'static str] {
+        match self {
+            Company::Lower => &[
+                "dockhand", "fence", "street vendor", "stablehand", "tavern cook", "rat catcher",
+                "pickpocket", "laundry hand", "sell-sword", "gravedigger",
+            ],
+            Company::Middle => &[
+                "guild clerk", "merchant", "journeyman smith", "town guard sergeant", "innkeeper",
+                "scribe", "apothecary", "ship's mate", "temple acolyte", "moneylender",
+            ],
+            Company::Upper => &[
+                "courtier", "magistrate", "minor noble", "court wizard", "wealthy patron",
+                "knight of the realm", "ambassador's aide", "high priest", "guildmaster", "heir to a trading house",
+            ],
+        }
+    }
+
+    fn complications(&self) -> &'static [&'static str] {
+        match self {
+            Company::Lower => &[
+                "a brawl breaks out and the watch wants someone to blame",
+                "a new friend picks the character's pocket on the last night",
+                "the character wakes up owing a loan shark",
+                "a local gang takes the character for a rival's agent",
+                "the character is accused of cheating at dice",
+                "the character boasts about a job the party hasn't finished",
+            ],
+            Company::Middle => &[
+                "a merchant the character drank with claims they promised an investment",
+                "the character insults a guild officer and is barred from the guildhall",
+                "a jealous suitor challenges the character to a duel",
+                "the character signs something they don't remember signing",
+                "a rumor spreads that the character is wanted in another town",
+                "the character is caught up in a tavern owner's debts",
+            ],
+            Company::Upper => &[
+                "the character offends a noble, who starts spreading gossip",
+                "a courtier asks for a favor that the character can't easily refuse",
+                "the character is drawn into a feud between two noble houses",
+                "a lavish bet is lost, and the winner wants payment in service",
+                "the character's manners are mocked and the story gets around court",
+                "a political rival takes the character for someone else's agent",
+            ],
+        }
+    }
+}
+
+/// Someone the character met while carousing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contact {
+    pub name: String,
+    pub occupation: String,
+    pub hostile: bool,
+}
+
+impl Contact {
+    pub fn attitude(&self) -> &'static str {
+        if self.hostile { "hostile" } else { "allied" }
+    }
+}
+
+/// What came of a stretch of carousing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Carousal {
+    pub gold_spent: u32,
+    pub contacts: Vec<Contact>,
+    pub complications: Vec<String>,
+}
+
+/// Gold held as plain "N gp" inventory entries
+pub fn purse(character: &Character) -> u32 {
+    character.inventory.iter().filter_map(|item| coins(item)).sum()
+}
+
+fn coins(item: &str) -> Option<u32> {
+    item.trim().strip_suffix(" gp")?.parse().ok()
+}
+
+/// Take gold from the purse, merging the coin entries into what's left.
+/// Returns false (changing nothing) if there isn't enough.
+fn spend(character: &mut Character, amount: u32) -> bool {
+    let held = purse(character);
+    if held < amount {
+        return false;
+    }
+    character.inventory.retain(|item| coins(item).is_none());
+    if held > amount {
+        character.inventory.push(format!("{} gp", held - amount));
+    }
+    true
+}
+
+/// Carouse for some workweeks. Each week rolls d100 + level for contacts: 10 or less is
+/// a hostile contact, 41-70 one allied contact and 71 or more two. There is a 10% chance
+/// a week also brings a complication.
+pub fn carouse<R: Rng + ?Sized>(character: &Character, company: Company, weeks: u32, names: &NameRegistry, rng: &mut R) -> Result<Carousal> {
+    let level = character.level.unwrap_or(1) as u32;
+    let mut carousal = Carousal { gold_spent: company.cost_per_week() * weeks, contacts: Vec::new(), complications: Vec::new() };
+    for _ in 0..weeks {
+        let (count, hostile) = match rng.random_range(1..=100) + level {
+            ..=10 => (1, true),
+            11..=40 => (0, false),
+            41..=70 => (1, false),
+            _ => (2, false),
+        };
+        for _ in 0..count {
+            carousal.contacts.push(Contact {
+                name: names.generate(None, rng)?,
+                occupation: company.occupations().choose(rng).copied().unwrap_or_default().to_string(),
+                hostile,
+            });
+        }
+        if rng.random_range(1..=100) <= 10 {
+            carousal.complications.push(company.complications().choose(rng).copied().unwrap_or_default().to_string());
+        }
+    }
+    Ok(carousal)
+}
+
+fn sheet_path(npcs_dir: &Path, name: &str) -> PathBuf {
+    npcs_dir.join(format!("{}.txt", name))
+}
+
+/// Save a contact as a minimal NPC sheet, linked to the character who met them
+pub fn save_contact_in(npcs_dir: &Path, character: &str, contact: &Contact) -> Result<PathBuf> {
+    fs::create_dir_all(npcs_dir).context("Failed to create npcs directory")?;
+    let path = sheet_path(npcs_dir, &contact.name);
+    let existing = fs::read_to_string(&path).unwrap_or_else(|_| format!("Name: {}\nType: NPC\n", contact.name));
+    let mut lines: Vec<String> = existing.lines().map(str::to_string).collect();
+    lines.push(format!("{}{} | {} | {}", CONTACT_PREFIX, character, contact.attitude(), contact.occupation));
+    fs::write(&path, lines.join("\n") + "\n").context(format!("Failed to write {}", path.display()))?;
+    log::info!("Saved {}'s contact {} to {}", character, contact.name, path.display());
+    Ok(path)
+}
+
+/// Every saved contact with the character who met them, optionally only one character's
+pub fn contacts_in(npcs_dir: &Path, character: Option<&str>) -> Vec<(String, Contact)> {
+    let Ok(entries) = fs::read_dir(npcs_dir) else {
+        return Vec::new();
+    };
+    let mut contacts: Vec<_> = entries.flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .flat_map(|path| {
+            let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            let contents = fs::read_to_string(&path).unwrap_or_default();
+            contents.lines()
+                .filter_map(|line| {
+                    let fields: Vec<&str> = line.strip_prefix(CONTACT_PREFIX)?.split(" | ").collect();
+                    let [who, attitude, occupation] = fields.as_slice() else { return None };
+                    Some((who.to_string(), Contact { name: name.clone(), occupation: occupation.to_string(), hostile: *attitude == "hostile" }))
+                })
+                .collect::<Vec<_>>()
+        })
+        .filter(|(who, _)| character.is_none_or(|c| who.eq_ignore_ascii_case(c)))
+        .collect();
+    contacts.sort_by(|a, b| (&a.0, &a.1.name).cmp(&(&b.0, &b.1.name)));
+    contacts
+}
+
+/// `carouse <character> <lower|middle|upper> [weeks]`: roll the weeks, pay for them from
+/// the character's purse, and save any contacts to the NPC folder
+pub fn carouse_command<R: Rng + ?Sized>(npcs_dir: &Path, character: &mut Character, company: &str, weeks: Option<&str>, names: &NameRegistry, rng: &mut R) -> Result<Vec<String>> {
+    let company = Company::parse(company)
+        .ok_or_else(|| AppError::ValidationError(format!("Unknown company '{}'. Use lower, middle or upper", company)))?;
+    let weeks = match weeks {
+        Some(weeks) => weeks.parse::<u32>().ok().filter(|w| (1..=52).contains(w))
+            .ok_or_else(|| AppError::ValidationError(format!("'{}' isn't a number of workweeks (1-52)", weeks)))?,
+        None => 1,
+    };
+    let carousal = carouse(character, company, weeks, names, rng)?;
+
+    let mut lines = vec![format!("🍻 {} carouses with the {} class for {} workweek{}", character.name, company.name(), weeks, if weeks == 1 { "" } else { "s" })];
+    if spend(character, carousal.gold_spent) {
+        lines.push(format!("  💰 Spent {} gp ({} gp left)", carousal.gold_spent, purse(character)));
+    } else {
+        lines.push(format!("  💰 Costs {} gp, but {} only has {} gp on their sheet; settle it by hand", carousal.gold_spent, character.name, purse(character)));
+    }
+    if carousal.contacts.is_empty() {
+        lines.push("  No new contacts".to_string());
+    }
+    for contact in &carousal.contacts {
+        let icon = if contact.hostile { "😠" } else { "🤝" };
+        lines.push(format!("  {} {} contact: {}, a {}", icon, contact.attitude(), contact.name, contact.occupation));
+        if let Err(e) = save_contact_in(npcs_dir, &character.name, contact) {
+            lines.push(format!("  ⚠️ Could not save {}: {}", contact.name, e));
+        }
+    }
+    for complication in &carousal.complications {
+        lines.push(format!("  ⚠️ Complication: {}", complication));
+    }
+    Ok(lines)
+}
@@ -1,5 +1,10 @@
+use crate::ammo::AmmoCounter;
+use crate::companions::Companion;
+use crate::pact_magic::PactSlots;
+use crate::resources::ClassResource;
+use crate::spell_slots::SpellSlots;
+use crate::error_handling::{AppError, Result, validate_character_name, validate_stat};
 use serde::{Deserialize, Serialize};
-use std::{fs, io::{self, Write}};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AbilityScore {
@@ -34,6 +39,13 @@ impl AbilityScore {
         }
     }
 
+    /// "str", "Strength" and the like
+    pub fn parse(text: &str) -> Option<AbilityScore> {
+        let text = text.trim().to_lowercase();
+        AbilityScore::all().into_iter()
+            .find(|ability| text == ability.name().to_lowercase() || text == ability.short_name().to_lowercase())
+    }
+
     pub fn short_name(&self) -> &'static str {
         match self {
             AbilityScore::Strength => "STR",
@@ -61,8 +73,21 @@ pub struct Cards {
     pub desc: String,
 }
 
+/// How and when a character died, kept on their archived sheet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeathRecord {
+    pub cause: String,
+    /// Real-world session date, YYYY-MM-DD
+    pub died_on: String,
+    #[serde(default)]
+    pub in_game_date: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Character {
+    /// Registry id that combat and other records refer to the sheet by
+    #[serde(default)]
+    pub id: u32,
     pub name: String,
     pub race: Option<String>,
     pub class: Option<String>, 
@@ -85,11 +110,59 @@ pub struct Character {
     pub inventory: Vec<String>,
     pub cards: Vec<Cards>,
     pub spells: Vec<String>,
+    #[serde(default)]
+    pub ammo: Vec<AmmoCounter>,
+    /// Feet; when unset the race's usual darkvision applies
+    #[serde(default)]
+    pub darkvision: Option<u32>,
+    #[serde(default)]
+    pub exhaustion: u8,
+    /// The deity the character worships, by name
+    #[serde(default)]
+    pub faith: Option<String>,
+    #[serde(default)]
+    pub background: Option<String>,
+    /// Skills and tools the character adds their proficiency bonus to
+    #[serde(default)]
+    pub proficiencies: Vec<String>,
+    /// Experience points earned so far
+    #[serde(default)]
+    pub xp: u32,
+    /// Rages, ki, channel divinity and other limited uses
+    #[serde(default)]
+    pub resources: Vec<ClassResource>,
+    /// Spell slots per level and how many are spent, for classes that cast
+    #[serde(default)]
+    pub spell_slots: Option<SpellSlots>,
+    /// Warlock pact magic, kept apart from ordinary spell slots
+    #[serde(default)]
+    pub pact_slots: Option<PactSlots>,
+    #[serde(default)]
+    pub invocations: Vec<String>,
+    /// Spells a cleric, druid or wizard has prepared today, from `spells`
+    #[serde(default)]
+    pub prepared: Vec<String>,
+    /// Familiars and beast companions
+    #[serde(default)]
+    pub companions: Vec<Companion>,
+    /// Conditions from the last fight, kept in step with the combat tracker
+    #[serde(default)]
+    pub conditions: Vec<String>,
+    /// Set when the character has died and been moved to the archive
+    #[serde(default)]
+    pub death: Option<DeathRecord>,
+    /// Weapon and spell attacks with their bonus and damage, used by `attack` in combat
+    #[serde(default)]
+    pub attacks: Vec<crate::attacks::AttackProfile>,
+    /// Feats taken in place of an Ability Score Improvement
+    #[serde(default)]
+    pub feats: Vec<String>,
 }
 
 impl Character {
     pub fn new(name: &str) -> Character {
         Character {
+            id: 0,
             name: name.to_string(),
             race: None,
             class: None,
@@ -112,6 +185,23 @@ impl Character {
             inventory: Vec::new(),
             cards: Vec::new(),
             spells: Vec::new(),
+            ammo: Vec::new(),
+            darkvision: None,
+            exhaustion: 0,
+            faith: None,
+            background: None,
+            proficiencies: Vec::new(),
+            xp: 0,
+            resources: Vec::new(),
+            spell_slots: None,
+            pact_slots: None,
+            invocations: Vec::new(),
+            prepared: Vec::new(),
+            companions: Vec::new(),
+            conditions: Vec::new(),
+            death: None,
+            attacks: Vec::new(),
+            feats: Vec::new(),
         }
     }
 
@@ -132,6 +222,17 @@ impl Character {
         }
     }
 
+    pub fn ability_score_mut(&mut self, ability: AbilityScore) -> &mut Option<u8> {
+        match ability {
+            AbilityScore::Strength => &mut self.stre,
+            AbilityScore::Dexterity => &mut self.dext,
+            AbilityScore::Constitution => &mut self.cons,
+            AbilityScore::Wisdom => &mut self.wisd,
+            AbilityScore::Intelligence => &mut self.intl,
+            AbilityScore::Charisma => &mut self.chas,
+        }
+    }
+
     pub fn get_ability_modifier(&self, ability: AbilityScore) -> i8 {
         if let Some(score) = self.get_ability_score(ability) {
             Self::calculate_modifier(score)
@@ -164,6 +265,11 @@ impl Character {
         self.get_ability_modifier(AbilityScore::Charisma)
     }
 
+    /// Whether the character is proficient in a skill or tool
+    pub fn is_proficient(&self, name: &str) -> bool {
+        self.proficiencies.iter().any(|p| p.eq_ignore_ascii_case(name.trim()))
+    }
+
     /// Calculate passive perception: 10 + Wisdom Modifier + Proficiency Bonus
     pub fn calculate_passive_perception(&self) -> u8 {
         let wisdom_mod = self.get_wisdom_modifier();
@@ -198,7 +304,7 @@ impl Character {
             self.class = Some(self.prompt_for_stat("Class", "Fighter"));
         }
         if self.level.is_none() {
-            self.level = Some(self.prompt_for_stat("Level", "1").parse().unwrap_or(1));
+            self.level = Some(self.prompt_for_valid_stat("Level", "level", 1));
         }
         if self.prof_bonus.is_none() {
             let default_prof = match self.level.unwrap_or(1) {
@@ -208,41 +314,41 @@ impl Character {
                 13..=16 => 5,
                 _ => 6,
             };
-            self.prof_bonus = Some(self.prompt_for_stat("Proficiency Bonus", &default_prof.to_string()).parse().unwrap_or(default_prof));
+            self.prof_bonus = Some(self.prompt_for_valid_stat("Proficiency Bonus", "prof_bonus", default_prof));
         }
 
         // Ensure all ability scores are present
         if self.stre.is_none() {
-            self.stre = Some(self.prompt_for_stat("Strength", "10").parse().unwrap_or(10));
+            self.stre = Some(self.prompt_for_valid_stat("Strength", "stre", 10));
         }
         if self.dext.is_none() {
-            self.dext = Some(self.prompt_for_stat("Dexterity", "10").parse().unwrap_or(10));
+            self.dext = Some(self.prompt_for_valid_stat("Dexterity", "dext", 10));
         }
         if self.cons.is_none() {
-            self.cons = Some(self.prompt_for_stat("Constitution", "10").parse().unwrap_or(10));
+            self.cons = Some(self.prompt_for_valid_stat("Constitution", "cons", 10));
         }
         if self.wisd.is_none() {
-            self.wisd = Some(self.prompt_for_stat("Wisdom", "10").parse().unwrap_or(10));
+            self.wisd = Some(self.prompt_for_valid_stat("Wisdom", "wisd", 10));
         }
         if self.intl.is_none() {
-            self.intl = Some(self.prompt_for_stat("Intelligence", "10").parse().unwrap_or(10));
+            self.intl = Some(self.prompt_for_valid_stat("Intelligence", "intl", 10));
         }
         if self.chas.is_none() {
-            self.chas = Some(self.prompt_for_stat("Charisma", "10").parse().unwrap_or(10));
+            self.chas = Some(self.prompt_for_valid_stat("Charisma", "chas", 10));
         }
 
         // Ensure other core stats
         if self.ac.is_none() {
-            self.ac = Some(self.prompt_for_stat("Armor Class", "10").parse().unwrap_or(10));
+            self.ac = Some(self.prompt_for_valid_stat("Armor Class", "ac", 10));
         }
         if self.max_hp.is_none() {
-            self.max_hp = Some(self.prompt_for_stat("Max HP", "10").parse().unwrap_or(10));
+            self.max_hp = Some(self.prompt_for_valid_stat("Max HP", "max_hp", 10));
         }
         if self.hp.is_none() {
             self.hp = self.max_hp;
         }
         if self.speed.is_none() {
-            self.speed = Some(self.prompt_for_stat("Speed", "30").parse().unwrap_or(30));
+            self.speed = Some(self.prompt_for_valid_stat("Speed", "speed", 30));
         }
 
         // Update calculated stats
@@ -305,6 +411,56 @@ impl Character {
         }
     }
 
+    /// Prompt for a numeric stat until the answer passes validation
+    fn prompt_for_valid_stat(&self, stat_name: &str, key: &str, default_value: u8) -> u8 {
+        loop {
+            match validate_stat(key, &self.prompt_for_stat(stat_name, &default_value.to_string())) {
+                Ok(value) => return value,
+                Err(e) => println!("❌ {}", e),
+            }
+        }
+    }
+
+    /// Set a field by key, validating numeric stats against the configured limits
+    pub fn set_stat(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "name" => {
+                validate_character_name(value)?;
+                self.name = value.to_string();
+            }
+            "race" => self.race = Some(value.to_string()),
+            "class" => self.class = Some(value.to_string()),
+            "desc" => self.desc = Some(value.to_string()),
+            "darkvision" => {
+                let feet = value.trim().trim_end_matches("ft").trim().parse::<u32>()
+                    .map_err(|_| AppError::ValidationError(format!("Darkvision must be a distance in feet, not '{}'", value)))?;
+                self.darkvision = Some(feet);
+            }
+            _ => {
+                let field = match key {
+                    "level" => &mut self.level,
+                    "ac" => &mut self.ac,
+                    "hp" => &mut self.hp,
+                    "max_hp" => &mut self.max_hp,
+                    "temp_hp" => &mut self.temp_hp,
+                    "speed" => &mut self.speed,
+                    "intl" => &mut self.intl,
+                    "wisd" => &mut self.wisd,
+                    "chas" => &mut self.chas,
+                    "stre" => &mut self.stre,
+                    "dext" => &mut self.dext,
+                    "cons" => &mut self.cons,
+                    "passive_perception" => &mut self.passive_perception,
+                    "initiative" => &mut self.initiative,
+                    "prof_bonus" => &mut self.prof_bonus,
+                    _ => return Err(AppError::ValidationError(format!("Unknown stat '{}'", key))),
+                };
+                *field = Some(validate_stat(key, value)?);
+            }
+        }
+        Ok(())
+    }
+
     pub fn get_value(&self, key: String) -> String {
         match key.as_str() {
             "name" => self.name.clone(),
@@ -367,17 +523,10 @@ impl Character {
             "Proficiency Bonus: {}",
             self.prof_bonus.unwrap_or(0)
         ));
-        stats
-    }
-
-    pub fn write_to_file(&self) -> io::Result<()> {
-        let path = format!("characters/{}.txt", self.name);
-        let mut file = fs::File::create(path)?;
-        for stat in self.get_ordered_stats() {
-            file.write_all(stat.as_bytes())?;
-            file.write_all(b"\n")?;
+        if !self.feats.is_empty() {
+            stats.push(format!("Feats: {}", self.feats.join(", ")));
         }
-        Ok(())
+        stats
     }
 
     pub fn as_vec(&self) -> Vec<String> {
@@ -442,27 +591,8 @@ impl Character {
     ) -> Character {
         let mut new_character = self.clone();
         for (key, value) in changes {
-            match key.as_str() {
-                "name" => new_character.name = value,
-                "level" => new_character.level = Some(value.parse().unwrap()),
-                "desc" => new_character.desc = Some(value),
-                "ac" => new_character.ac = Some(value.parse().unwrap()),
-                "hp" => new_character.hp = Some(value.parse().unwrap()),
-                "max_hp" => new_character.max_hp = Some(value.parse().unwrap()),
-                "temp_hp" => new_character.temp_hp = Some(value.parse().unwrap()),
-                "speed" => new_character.speed = Some(value.parse().unwrap()),
-                "intl" => new_character.intl = Some(value.parse().unwrap()),
-                "wisd" => new_character.wisd = Some(value.parse().unwrap()),
-                "chas" => new_character.chas = Some(value.parse().unwrap()),
-                "stre" => new_character.stre = Some(value.parse().unwrap()),
-                "dext" => new_character.dext = Some(value.parse().unwrap()),
-                "cons" => new_character.cons = Some(value.parse().unwrap()),
-                "passive_perception" => {
-                    new_character.passive_perception = Some(value.parse().unwrap())
-                }
-                "initiative" => new_character.initiative = Some(value.parse().unwrap()),
-                "prof_bonus" => new_character.prof_bonus = Some(value.parse().unwrap()),
-                _ => (),
+            if let Err(e) = new_character.set_stat(&key, &value) {
+                println!("Skipping {}: {}", key, e);
             }
         }
         new_character
@@ -1,19 +1,456 @@
 use crate::character::Character;
-use crate::file_manager::load_character_files;
+use crate::error_handling::{AppError, Result};
+use crate::input_handler::stat_or_default;
+use crate::morale::{MoraleGroup, MoraleTrigger};
+use crate::movement::Movement;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::{fs, io::{self, Write}};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Whether a status effect's rounds count down as the affected creature's turn starts or ends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TickPoint {
+    StartOfTurn,
+    #[default]
+    EndOfTurn,
+}
+
+impl TickPoint {
+    pub fn parse(text: &str) -> Option<TickPoint> {
+        match text.trim().to_lowercase().as_str() {
+            "start" | "sot" => Some(TickPoint::StartOfTurn),
+            "end" | "eot" => Some(TickPoint::EndOfTurn),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TickPoint::StartOfTurn => "start",
+            TickPoint::EndOfTurn => "end",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StatusEffect {
     pub name: String,
     pub description: Option<String>,
     pub duration: Option<i32>, // rounds remaining, None for permanent
+    #[serde(default)]
+    pub ticks_at: TickPoint,
+    /// The combatant concentrating on the effect; it ends when they drop to 0 HP
+    #[serde(default)]
+    pub concentration: Option<String>,
+    /// Damage rolled each time the effect ticks, e.g. "1d6 fire"
+    #[serde(default)]
+    pub damage: Option<String>,
+    #[serde(default)]
+    pub modifiers: RollModifiers,
+}
+
+/// How an effect changes rolls. Each is "adv", "dis", or a bonus such as "+1d4" or "-2".
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RollModifiers {
+    /// Attack rolls the affected creature makes
+    #[serde(default)]
+    pub attacks: Option<String>,
+    /// Attack rolls made against the affected creature
+    #[serde(default)]
+    pub attacked: Option<String>,
+    /// Saving throws the affected creature makes
+    #[serde(default)]
+    pub saves: Option<String>,
+}
+
+/// "🔽 Disadvantage on Bob's attack rolls (Frostbitten)", with the edge if the modifier is one
+fn modifier_hint(modifier: &str, rolls: &str, status: &str) -> (Option<Edge>, String) {
+    let edge = Edge::parse(modifier);
+    let hint = match edge {
+        Some(Edge::Advantage) => format!("🔼 Advantage on {} ({})", rolls, status),
+        Some(Edge::Disadvantage) => format!("🔽 Disadvantage on {} ({})", rolls, status),
+        None => format!("🎲 {} to {} ({})", modifier, rolls, status),
+    };
+    (edge, hint)
+}
+
+impl RollModifiers {
+    /// One hint per modifier the effect has
+    pub fn hints(&self, name: &str, status: &str) -> Vec<String> {
+        [(&self.attacks, format!("{}'s attack rolls", name)), (&self.attacked, format!("attack rolls against {}", name)),
+            (&self.saves, format!("{}'s saving throws", name))]
+            .into_iter()
+            .filter_map(|(modifier, rolls)| Some(modifier_hint(modifier.as_deref()?, &rolls, status).1))
+            .collect()
+    }
+}
+
+impl StatusEffect {
+    /// A standard condition's name is stored with its usual spelling ("prone" → "Prone")
+    pub fn new(name: impl Into<String>, duration: Option<i32>) -> Self {
+        let name = name.into();
+        let name = Condition::parse(&name).map(|c| c.name().to_string()).unwrap_or(name);
+        StatusEffect { name, duration, ..Default::default() }
+    }
+
+    /// The standard condition this status is, if it is one
+    pub fn condition(&self) -> Option<Condition> {
+        Condition::parse(&self.name)
+    }
+
+    /// " (3 rounds, ends at end of turn; Mira's concentration)" for status lists
+    pub fn duration_text(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(rounds) = self.duration {
+            parts.push(format!("{} round{}, ticks at {} of turn", rounds, if rounds == 1 { "" } else { "s" }, self.ticks_at.name()));
+        }
+        if let Some(owner) = &self.concentration {
+            parts.push(format!("{}'s concentration", owner));
+        }
+        if parts.is_empty() { " (permanent)".to_string() } else { format!(" ({})", parts.join("; ")) }
+    }
+
+    /// What a preset adds beyond the name: its description, damage each turn and roll modifiers
+    pub fn detail_lines(&self, target: &str) -> Vec<String> {
+        let mut lines: Vec<String> = self.description.iter().map(|text| format!("📝 {}", text)).collect();
+        if let Some(damage) = &self.damage {
+            lines.push(format!("🔥 {} damage at the {} of each of {}'s turns", damage, self.ticks_at.name(), target));
+        }
+        lines.extend(self.modifiers.hints(target, &self.name));
+        lines
+    }
+}
+
+/// The standard conditions. Statuses with any other name ("hasted", "blessed") are
+/// still tracked, but have no rules attached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Condition {
+    Blinded,
+    Charmed,
+    Deafened,
+    Exhaustion,
+    Frightened,
+    Grappled,
+    Incapacitated,
+    Invisible,
+    Paralyzed,
+    Petrified,
+    Poisoned,
+    Prone,
+    Restrained,
+    Stunned,
+    Unconscious,
+}
+
+/// Which way a condition tips a d20 roll
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Advantage,
+    Disadvantage,
+}
+
+impl Edge {
+    /// "adv", "advantage", "dis", "disadvantage" in any case
+    pub fn parse(text: &str) -> Option<Edge> {
+        match text.trim().to_lowercase().as_str() {
+            "adv" | "advantage" => Some(Edge::Advantage),
+            "dis" | "disadv" | "disadvantage" => Some(Edge::Disadvantage),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Edge::Advantage => "advantage",
+            Edge::Disadvantage => "disadvantage",
+        }
+    }
+
+    /// " with advantage" to follow the name of a roll, or nothing
+    pub fn with(edge: Option<Edge>) -> String {
+        edge.map(|edge| format!(" with {}", edge.name())).unwrap_or_default()
+    }
+
+    /// The d20 roll for this edge, or a single d20 without one
+    pub fn d20(edge: Option<Edge>) -> &'static str {
+        match edge {
+            Some(Edge::Advantage) => "2d20kh1",
+            Some(Edge::Disadvantage) => "2d20kl1",
+            None => "1d20",
+        }
+    }
+}
+
+/// Take a trailing `adv` or `dis` off a command's arguments
+pub fn split_edge<'a, 'b>(args: &'a [&'b str]) -> (&'a [&'b str], Option<Edge>) {
+    match args.split_last() {
+        Some((last, rest)) => match Edge::parse(last) {
+            Some(edge) => (rest, Some(edge)),
+            None => (args, None),
+        },
+        None => (args, None),
+    }
+}
+
+impl Condition {
+    pub const ALL: [Condition; 15] = [
+        Condition::Blinded, Condition::Charmed, Condition::Deafened, Condition::Exhaustion,
+        Condition::Frightened, Condition::Grappled, Condition::Incapacitated, Condition::Invisible,
+        Condition::Paralyzed, Condition::Petrified, Condition::Poisoned, Condition::Prone,
+        Condition::Restrained, Condition::Stunned, Condition::Unconscious,
+    ];
+
+    /// The condition's full name, in any case ("prone", "Paralyzed")
+    pub fn parse(text: &str) -> Option<Condition> {
+        Condition::ALL.into_iter().find(|c| c.name().eq_ignore_ascii_case(text.trim()))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Condition::Blinded => "Blinded",
+            Condition::Charmed => "Charmed",
+            Condition::Deafened => "Deafened",
+            Condition::Exhaustion => "Exhaustion",
+            Condition::Frightened => "Frightened",
+            Condition::Grappled => "Grappled",
+            Condition::Incapacitated => "Incapacitated",
+            Condition::Invisible => "Invisible",
+            Condition::Paralyzed => "Paralyzed",
+            Condition::Petrified => "Petrified",
+            Condition::Poisoned => "Poisoned",
+            Condition::Prone => "Prone",
+            Condition::Restrained => "Restrained",
+            Condition::Stunned => "Stunned",
+            Condition::Unconscious => "Unconscious",
+        }
+    }
+
+    /// The SRD rules for the condition, one effect per line
+    pub fn rules_text(&self) -> &'static [&'static str] {
+        crate::reference::condition_text(self.name()).map(|(_, text)| text).unwrap_or_default()
+    }
+
+    /// The creature can't take actions or reactions
+    pub fn incapacitates(&self) -> bool {
+        matches!(self, Condition::Incapacitated | Condition::Paralyzed | Condition::Petrified | Condition::Stunned | Condition::Unconscious)
+    }
+
+    /// How the condition affects attack rolls the creature makes
+    pub fn attacking(&self) -> Option<Edge> {
+        match self {
+            Condition::Blinded | Condition::Frightened | Condition::Poisoned | Condition::Prone | Condition::Restrained => Some(Edge::Disadvantage),
+            Condition::Invisible => Some(Edge::Advantage),
+            _ => None,
+        }
+    }
+
+    /// How the condition affects attack rolls made against the creature. Prone
+    /// depends on range, so it's handled by the attack hints instead.
+    pub fn attacked(&self) -> Option<Edge> {
+        match self {
+            Condition::Blinded | Condition::Paralyzed | Condition::Petrified | Condition::Restrained
+            | Condition::Stunned | Condition::Unconscious => Some(Edge::Advantage),
+            Condition::Invisible => Some(Edge::Disadvantage),
+            _ => None,
+        }
+    }
+
+    /// A hit from within 5 feet is a critical hit
+    pub fn melee_hits_crit(&self) -> bool {
+        matches!(self, Condition::Paralyzed | Condition::Unconscious)
+    }
+
+    pub fn auto_fails_save(&self, ability: crate::character::AbilityScore) -> bool {
+        use crate::character::AbilityScore;
+        matches!(ability, AbilityScore::Strength | AbilityScore::Dexterity)
+            && matches!(self, Condition::Paralyzed | Condition::Petrified | Condition::Stunned | Condition::Unconscious)
+    }
+
+    pub fn save_disadvantage(&self, ability: crate::character::AbilityScore) -> bool {
+        *self == Condition::Restrained && ability == crate::character::AbilityScore::Dexterity
+    }
+}
+
+/// What the attacker's and target's conditions mean for an attack roll: who can't
+/// act, advantage and disadvantage (which cancel out), and automatic critical hits
+pub fn condition_attack_hints(attacker: Option<&Combatant>, target: &Combatant) -> Vec<String> {
+    let mut hints = Vec::new();
+    let mut edges = Vec::new();
+    if let Some(attacker) = attacker {
+        if let Some(condition) = attacker.incapacitated_by() {
+            hints.push(format!("⛔ {} is {} and can't take actions", attacker.name, condition.name().to_lowercase()));
+        }
+        for condition in attacker.conditions() {
+            if let Some(edge) = condition.attacking() {
+                edges.push(edge);
+                hints.push(edge_hint(edge, &attacker.name, condition));
+            }
+        }
+    }
+    for condition in target.conditions() {
+        if condition == Condition::Prone {
+            hints.push(format!("↕️ {} is prone: advantage within 5 ft, disadvantage from farther away", target.name));
+        } else if let Some(edge) = condition.attacked() {
+            edges.push(edge);
+            hints.push(edge_hint(edge, &target.name, condition));
+        }
+    }
+    // Roll modifiers from status presets, e.g. a homebrew "Frostbitten" giving disadvantage
+    let attacking = attacker.into_iter().flat_map(|a| a.status_effects.iter()
+        .filter_map(move |s| Some(modifier_hint(s.modifiers.attacks.as_deref()?, &format!("{}'s attack rolls", a.name), &s.name))));
+    let attacked = target.status_effects.iter()
+        .filter_map(|s| Some(modifier_hint(s.modifiers.attacked.as_deref()?, &format!("attack rolls against {}", target.name), &s.name)));
+    for (edge, hint) in attacking.chain(attacked) {
+        edges.extend(edge);
+        hints.push(hint);
+    }
+    if edges.contains(&Edge::Advantage) && edges.contains(&Edge::Disadvantage) {
+        hints.push("⚖️ Advantage and disadvantage cancel out: roll one d20".to_string());
+    }
+    if let Some(condition) = target.conditions().find(Condition::melee_hits_crit) {
+        hints.push(format!("💥 {} is {}: a hit from within 5 ft is a critical hit", target.name, condition.name().to_lowercase()));
+    }
+    hints
+}
+
+fn edge_hint(edge: Edge, name: &str, condition: Condition) -> String {
+    let (icon, word) = match edge {
+        Edge::Advantage => ("🔼", "Advantage"),
+        Edge::Disadvantage => ("🔽", "Disadvantage"),
+    };
+    let note = if condition == Condition::Frightened { " (while the source of its fear is in sight)" } else { "" };
+    format!("{} {}: {} is {}{}", icon, word, name, condition.name().to_lowercase(), note)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DamageType {
+    Acid,
+    Bludgeoning,
+    Cold,
+    Fire,
+    Force,
+    Lightning,
+    Necrotic,
+    Piercing,
+    Poison,
+    Psychic,
+    Radiant,
+    Slashing,
+    Thunder,
+}
+
+impl DamageType {
+    pub const ALL: [DamageType; 13] = [
+        DamageType::Acid, DamageType::Bludgeoning, DamageType::Cold, DamageType::Fire, DamageType::Force,
+        DamageType::Lightning, DamageType::Necrotic, DamageType::Piercing, DamageType::Poison,
+        DamageType::Psychic, DamageType::Radiant, DamageType::Slashing, DamageType::Thunder,
+    ];
+
+    pub fn parse(text: &str) -> Option<DamageType> {
+        DamageType::ALL.into_iter().find(|t| t.name().eq_ignore_ascii_case(text.trim()))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DamageType::Acid => "acid",
+            DamageType::Bludgeoning => "bludgeoning",
+            DamageType::Cold => "cold",
+            DamageType::Fire => "fire",
+            DamageType::Force => "force",
+            DamageType::Lightning => "lightning",
+            DamageType::Necrotic => "necrotic",
+            DamageType::Piercing => "piercing",
+            DamageType::Poison => "poison",
+            DamageType::Psychic => "psychic",
+            DamageType::Radiant => "radiant",
+            DamageType::Slashing => "slashing",
+            DamageType::Thunder => "thunder",
+        }
+    }
+}
+
+/// Split a trailing damage type off command arguments: ["8", "fire"] → (["8"], Some(Fire))
+pub fn split_damage_type<'a, 'b>(args: &'a [&'b str]) -> (&'a [&'b str], Option<DamageType>) {
+    match args.split_last() {
+        Some((last, rest)) => match DamageType::parse(last) {
+            Some(kind) => (rest, Some(kind)),
+            None => (args, None),
+        },
+        None => (args, None),
+    }
+}
+
+fn type_list(types: &[DamageType]) -> String {
+    types.iter().map(DamageType::name).collect::<Vec<_>>().join(", ")
+}
+
+/// A downed party member's death saving throws
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeathSaves {
+    pub successes: u8,
+    pub failures: u8,
+}
+
+/// A spell a combatant is concentrating on, and the Constitution saves they still
+/// owe for damage taken while holding it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Concentration {
+    pub spell: String,
+    /// DCs of the saves asked for and not yet rolled, oldest first
+    #[serde(default)]
+    pub pending_saves: Vec<i32>,
+}
+
+/// The DC to keep concentrating after taking damage: 10, or half the damage if that's higher
+pub fn concentration_dc(damage: i32) -> i32 {
+    (damage / 2).max(10)
+}
+
+/// A legendary creature's actions between other creatures' turns, which come back at the
+/// start of its own turn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LegendaryActions {
+    pub per_round: u32,
+    pub spent: u32,
+}
+
+impl LegendaryActions {
+    pub fn left(&self) -> u32 {
+        self.per_round.saturating_sub(self.spent)
+    }
+}
+
+impl DeathSaves {
+    pub fn is_stable(&self) -> bool {
+        self.successes >= 3
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.failures >= 3
+    }
+
+    pub fn fail(&mut self, count: u8) {
+        self.failures = (self.failures + count).min(3);
+    }
+
+    /// "✅✅⬜ ❌⬜⬜"
+    pub fn tally(&self) -> String {
+        let marks = |count: u8, mark: &str| (0..3).map(|i| if i < count { mark } else { "⬜" }).collect::<String>();
+        format!("{} {}", marks(self.successes, "✅"), marks(self.failures, "❌"))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Combatant {
+    #[serde(default)]
+    pub id: u32, // unique within a tracker, so several combatants can share a display name
     pub name: String,
-    pub character_data: Option<Character>, // None for quick NPCs
+    /// Registry id of the party character this is; their sheet is read from the registry, not copied
+    #[serde(default)]
+    pub character_id: Option<u32>,
+    /// A stat block for an NPC that has no saved sheet (presets); None for quick NPCs
+    #[serde(default)]
+    pub npc_sheet: Option<Character>,
     pub current_hp: i32,
     pub max_hp: i32,
     pub temp_hp: i32,
@@ -21,18 +458,60 @@ pub struct Combatant {
     pub initiative: i32,
     pub is_player: bool,
     pub status_effects: Vec<StatusEffect>,
+    /// Left out of displayed initiative lists until revealed (e.g. ambushers)
+    #[serde(default)]
+    pub hidden: bool,
+    /// Challenge rating, for the XP award when the combatant is defeated
+    #[serde(default)]
+    pub cr: Option<String>,
+    /// The character a familiar or companion belongs to
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Recharge abilities used and not yet recharged, for the tactics hints
+    #[serde(default)]
+    pub spent_recharges: Vec<String>,
+    /// Flying (with altitude), swimming or burrowing; None when on the ground
+    #[serde(default)]
+    pub movement: Option<Movement>,
+    /// Set while a party member is down at 0 HP and rolling death saves
+    #[serde(default)]
+    pub death_saves: Option<DeathSaves>,
+    /// Damage of these types is halved
+    #[serde(default)]
+    pub resistances: Vec<DamageType>,
+    /// Damage of these types is doubled
+    #[serde(default)]
+    pub vulnerabilities: Vec<DamageType>,
+    /// Damage of these types is ignored
+    #[serde(default)]
+    pub immunities: Vec<DamageType>,
+    /// The number on the base of the mini standing in for an NPC; shown after its name
+    #[serde(default)]
+    pub token: Option<u32>,
+    /// Attacks for an NPC without a sheet, such as a monster looked up for the fight
+    #[serde(default)]
+    pub attacks: Vec<crate::attacks::AttackProfile>,
+    /// Legendary actions per round, for the creatures that have them
+    #[serde(default)]
+    pub legendary: Option<LegendaryActions>,
+    /// The spell the combatant is concentrating on
+    #[serde(default)]
+    pub concentration: Option<Concentration>,
 }
 
 impl Combatant {
-    pub fn from_character(character: Character, initiative: i32) -> Self {
+    /// A party character, linked to their sheet by registry id
+    pub fn from_character(character: &Character, initiative: i32) -> Self {
         let current_hp = character.hp.unwrap_or(10) as i32;
         let max_hp = character.max_hp.unwrap_or(current_hp as u8) as i32;
         let temp_hp = character.temp_hp.unwrap_or(0) as i32;
         let ac = character.ac.unwrap_or(10) as i32;
 
         Combatant {
+            id: 0,
             name: character.name.clone(),
-            character_data: Some(character),
+            character_id: Some(character.id),
+            npc_sheet: None,
             current_hp,
             max_hp,
             temp_hp,
@@ -40,13 +519,28 @@ impl Combatant {
             initiative,
             is_player: true,
             status_effects: Vec::new(),
+            hidden: false,
+            cr: None,
+            owner: None,
+            spent_recharges: Vec::new(),
+            movement: None,
+            death_saves: None,
+            resistances: Vec::new(),
+            vulnerabilities: Vec::new(),
+            immunities: Vec::new(),
+            token: None,
+            attacks: Vec::new(),
+            legendary: None,
+            concentration: None,
         }
     }
 
     pub fn new_npc(name: String, hp: i32, ac: i32, initiative: i32) -> Self {
         Combatant {
+            id: 0,
             name,
-            character_data: None,
+            character_id: None,
+            npc_sheet: None,
             current_hp: hp,
             max_hp: hp,
             temp_hp: 0,
@@ -54,22 +548,195 @@ impl Combatant {
             initiative,
             is_player: false,
             status_effects: Vec::new(),
+            hidden: false,
+            cr: None,
+            owner: None,
+            spent_recharges: Vec::new(),
+            movement: None,
+            death_saves: None,
+            resistances: Vec::new(),
+            vulnerabilities: Vec::new(),
+            immunities: Vec::new(),
+            token: None,
+            attacks: Vec::new(),
+            legendary: None,
+            concentration: None,
+        }
+    }
+
+    /// An NPC with a full stat block that isn't one of the saved characters
+    pub fn with_sheet(sheet: Character, initiative: i32) -> Self {
+        let mut combatant = Combatant::new_npc(sheet.name.clone(), sheet.max_hp.unwrap_or(10) as i32,
+            sheet.ac.unwrap_or(10) as i32, initiative);
+        combatant.current_hp = sheet.hp.unwrap_or(10) as i32;
+        combatant.npc_sheet = Some(sheet);
+        combatant
+    }
+
+    /// The combatant's sheet: a party character's from the registry, or an NPC's own stat block
+    pub fn sheet<'a>(&'a self, characters: &'a [Character]) -> Option<&'a Character> {
+        match self.character_id {
+            Some(id) => crate::registry::by_id(characters, id),
+            None => self.npc_sheet.as_ref(),
+        }
+    }
+
+    /// The combatant's own attacks, or else the ones on their sheet
+    pub fn attack_profiles<'a>(&'a self, characters: &'a [Character]) -> &'a [crate::attacks::AttackProfile] {
+        if !self.attacks.is_empty() {
+            return &self.attacks;
+        }
+        self.sheet(characters).map_or(&[], |sheet| sheet.attacks.as_slice())
+    }
+
+    /// " [🪽 30 ft]" for the initiative list, or nothing on the ground
+    pub fn movement_tag(&self) -> String {
+        self.movement.map(|m| format!(" [{}]", m.label())).unwrap_or_default()
+    }
+
+    /// " [💀 ✅⬜⬜ ❌⬜⬜]" while making death saves, or nothing
+    pub fn death_save_tag(&self) -> String {
+        self.death_saves.map(|saves| format!(" [💀 {}]", saves.tally())).unwrap_or_default()
+    }
+
+    /// " [🧠 Bless]" while concentrating on a spell
+    pub fn concentration_tag(&self) -> String {
+        self.concentration.as_ref().map(|c| format!(" [🧠 {}]", c.spell)).unwrap_or_default()
+    }
+
+    /// How hurt the combatant looks, without giving away exact numbers
+    pub fn hp_descriptor(&self) -> &'static str {
+        if self.current_hp <= 0 {
+            "Down"
+        } else if self.current_hp * 4 <= self.max_hp {
+            "Near death"
+        } else if self.current_hp * 2 <= self.max_hp {
+            "Bloodied"
+        } else if self.current_hp < self.max_hp {
+            "Wounded"
+        } else {
+            "Unhurt"
+        }
+    }
+
+    /// HP as players should see it: exact for player characters, and for monsters
+    /// unless `hide_monster_hp` is on
+    pub fn hp_display(&self, hide_monster_hp: bool) -> String {
+        if hide_monster_hp && !self.is_player {
+            self.hp_descriptor().to_string()
+        } else {
+            format!("{}/{}", self.current_hp, self.max_hp)
+        }
+    }
+
+    /// Describe an HP change for the log, e.g. "HP: 12 → 5" or just "Bloodied"
+    pub fn hp_change_display(&self, old_hp: i32, hide_monster_hp: bool) -> String {
+        if hide_monster_hp && !self.is_player {
+            self.hp_descriptor().to_string()
+        } else {
+            format!("HP: {} → {}", old_hp, self.current_hp)
         }
     }
 
     pub fn add_status(&mut self, status: StatusEffect) {
         // Remove existing status with same name
-        self.status_effects.retain(|s| s.name != status.name);
+        self.status_effects.retain(|s| !s.name.eq_ignore_ascii_case(&status.name));
         self.status_effects.push(status);
     }
 
     pub fn remove_status(&mut self, status_name: &str) -> bool {
         let original_len = self.status_effects.len();
-        self.status_effects.retain(|s| s.name != status_name);
+        self.status_effects.retain(|s| !s.name.eq_ignore_ascii_case(status_name.trim()));
         self.status_effects.len() != original_len
     }
 
-    pub fn display_stats(&self) {
+    /// The resistance, vulnerability or immunity list named by a command word
+    pub fn defenses_mut(&mut self, kind: &str) -> Option<&mut Vec<DamageType>> {
+        match kind.to_lowercase().as_str() {
+            "resist" | "resistant" | "resistance" => Some(&mut self.resistances),
+            "vulnerable" | "vuln" | "vulnerability" => Some(&mut self.vulnerabilities),
+            "immune" | "immunity" => Some(&mut self.immunities),
+            _ => None,
+        }
+    }
+
+    /// "Resistant: fire, cold" and so on, for the lists that aren't empty
+    pub fn defense_lines(&self) -> Vec<String> {
+        [("Resistant", &self.resistances), ("Vulnerable", &self.vulnerabilities), ("Immune", &self.immunities)]
+            .into_iter()
+            .filter(|(_, types)| !types.is_empty())
+            .map(|(label, types)| format!("{}: {}", label, type_list(types)))
+            .collect()
+    }
+
+    /// Damage after immunity, resistance and vulnerability to its type, with a note
+    /// when it changed. A petrified creature resists all damage, typed or not.
+    pub fn adjust_damage(&self, damage: i32, kind: Option<DamageType>) -> (i32, Option<String>) {
+        let has = |list: &[DamageType]| kind.is_some_and(|k| list.contains(&k));
+        let type_name = kind.map(|k| k.name()).unwrap_or_default();
+        if has(&self.immunities) {
+            return (0, Some(format!("🛡️ {} is immune to {} damage", self.name, type_name)));
+        }
+        let mut adjusted = damage;
+        let mut reasons = Vec::new();
+        if has(&self.resistances) {
+            adjusted /= 2;
+            reasons.push(format!("resists {}", type_name));
+        } else if self.conditions().any(|c| c == Condition::Petrified) {
+            adjusted /= 2;
+            reasons.push("is petrified and resists all damage".to_string());
+        }
+        if has(&self.vulnerabilities) {
+            adjusted *= 2;
+            reasons.push(format!("is vulnerable to {}", type_name));
+        }
+        if reasons.is_empty() {
+            return (damage, None);
+        }
+        let icon = if adjusted > damage { "💢" } else { "🛡️" };
+        (adjusted, Some(format!("{} {} {}: {} → {} damage", icon, self.name, reasons.join(" and "), damage, adjusted)))
+    }
+
+    /// Damage taken while making death saves counts as a failed save, and a stable
+    /// creature starts rolling again
+    pub fn damaged_while_dying(&mut self) -> Option<String> {
+        let saves = self.death_saves.as_mut().filter(|saves| !saves.is_dead())?;
+        saves.successes = 0;
+        saves.fail(1);
+        let mut message = format!("💔 {} takes damage while down: a failed death save ({})", self.name, saves.tally());
+        if saves.is_dead() {
+            message.push_str(&format!("\n☠️ {} has failed three death saves and dies", self.name));
+        }
+        Some(message)
+    }
+
+    /// The standard conditions among this combatant's statuses
+    pub fn conditions(&self) -> impl Iterator<Item = Condition> + '_ {
+        self.status_effects.iter().filter_map(StatusEffect::condition)
+    }
+
+    /// The condition stopping this combatant from taking actions, if any
+    pub fn incapacitated_by(&self) -> Option<Condition> {
+        self.conditions().find(Condition::incapacitates)
+    }
+
+    /// The condition making this combatant fail a Strength or Dexterity save outright
+    pub fn auto_fails_save(&self, ability: crate::character::AbilityScore) -> Option<Condition> {
+        self.conditions().find(|c| c.auto_fails_save(ability))
+    }
+
+    /// Disadvantage reminders for a save this combatant is making
+    pub fn save_hints(&self, ability: crate::character::AbilityScore) -> Vec<String> {
+        self.conditions()
+            .filter(|c| c.save_disadvantage(ability))
+            .map(|c| format!("🔽 Disadvantage: {} is {}", self.name, c.name().to_lowercase()))
+            .chain(self.status_effects.iter().filter_map(|s| {
+                Some(modifier_hint(s.modifiers.saves.as_deref()?, &format!("{}'s saving throws", self.name), &s.name).1)
+            }))
+            .collect()
+    }
+
+    pub fn display_stats(&self, characters: &[Character]) {
         // Enhanced 3-column display
         println!("\n╔═══════════════════════════════════════════════════════════════╗");
         println!("║                    {} ({})", 
@@ -83,9 +750,15 @@ impl Combatant {
                  self.ac, self.current_hp, self.max_hp);
         println!("║ Initiative: {:<10} │ Temp HP: {:<12} │                    ║", 
                  self.initiative, self.temp_hp);
+        if let Some(saves) = self.death_saves {
+            println!("║ Death Saves: {:<48} ║", saves.tally());
+        }
+        for line in self.defense_lines() {
+            println!("║ {:<61} ║", line);
+        }
 
         // If we have character data, show more stats
-        if let Some(character) = &self.character_data {
+        if let Some(character) = self.sheet(characters) {
             println!("║                       │                       │                    ║");
             println!("║ Ability Scores        │ Saves & Skills        │ Other              ║");
             
@@ -138,11 +811,67 @@ impl Combatant {
     }
 }
 
-#[derive(Debug)]
+/// The kind of creature a combatant is, for numbering: "Goblin 2" and "goblin#4" are goblins
+pub fn creature_kind(name: &str) -> String {
+    kind_name(name).to_lowercase()
+}
+
+/// A name without the number on the end
+fn kind_name(name: &str) -> String {
+    name.trim_end_matches(|ch: char| ch.is_ascii_digit() || ch == '#').trim().to_string()
+}
+
+/// Serializable so a fight can be saved mid-round and resumed next session
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CombatTracker {
     pub combatants: Vec<Combatant>,
     pub current_turn: usize,
     pub round_number: i32,
+    /// Report monster HP descriptively in shared output (DND_TOOLS_HIDE_MONSTER_HP)
+    #[serde(default)]
+    pub hide_monster_hp: bool,
+    /// Optional NPC groups that check morale when their leader or half their number falls
+    #[serde(default)]
+    pub morale_groups: Vec<MoraleGroup>,
+    /// Suggest tactics at the start of each monster's turn (DND_TOOLS_TACTICS_HINTS)
+    #[serde(default)]
+    pub tactics_hints: bool,
+    /// Variant rule: roll initiative again at the top of every round (DND_TOOLS_REROLL_INITIATIVE)
+    #[serde(default)]
+    pub reroll_each_round: bool,
+    /// The last round initiative was re-rolled for, so a round is only re-rolled once
+    #[serde(default)]
+    rerolled_round: i32,
+    /// Round-by-round HP and resource totals for the pacing dashboard
+    #[serde(default)]
+    pub pacing: crate::pacing::PacingLog,
+    /// Whose turn the classic loop started last, so their end-of-turn effects tick on `next`
+    #[serde(default)]
+    last_actor: Option<u32>,
+    /// Whether someone's turn has begun, so a combatant added at the very top of round 1
+    /// joins the order around them instead of taking the turn
+    #[serde(default)]
+    turn_underway: bool,
+    /// Whose lair the fight is in; its lair action comes on initiative 20
+    #[serde(default)]
+    pub lair: Option<String>,
+    /// The encounter table reinforcements come from when the fight is too easy
+    #[serde(default)]
+    pub reinforcement_table: Option<String>,
+    /// Which way the fight had tipped when adjustments were last suggested
+    #[serde(default)]
+    pub suggested_tilt: Option<crate::balance::Tilt>,
+    next_id: u32,
+}
+
+/// Whether DND_TOOLS_HIDE_MONSTER_HP asks for descriptive monster HP
+pub fn hide_monster_hp_from_env() -> bool {
+    std::env::var("DND_TOOLS_HIDE_MONSTER_HP").is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Whether DND_TOOLS_REROLL_INITIATIVE turns on the re-roll-every-round variant
+pub fn reroll_initiative_from_env() -> bool {
+    std::env::var("DND_TOOLS_REROLL_INITIATIVE").is_ok_and(|v| !v.is_empty() && v != "0")
 }
 
 impl CombatTracker {
@@ -151,17 +880,482 @@ impl CombatTracker {
             combatants: Vec::new(),
             current_turn: 0,
             round_number: 1,
+            hide_monster_hp: hide_monster_hp_from_env(),
+            morale_groups: Vec::new(),
+            tactics_hints: crate::tactics::tactics_hints_from_env(),
+            reroll_each_round: reroll_initiative_from_env(),
+            rerolled_round: 1,
+            pacing: crate::pacing::PacingLog::default(),
+            last_actor: None,
+            turn_underway: false,
+            lair: None,
+            reinforcement_table: None,
+            suggested_tilt: None,
+            next_id: 1,
         }
     }
 
-    pub fn add_combatant(&mut self, combatant: Combatant) {
+    /// Add a combatant. Once a kind of monster has token numbers, another of the
+    /// kind joining takes the next number.
+    pub fn add_combatant(&mut self, mut combatant: Combatant) {
+        combatant.id = self.next_id;
+        self.next_id += 1;
+        if !combatant.is_player && combatant.token.is_none() {
+            let kind = creature_kind(&combatant.name);
+            let highest = self.combatants.iter()
+                .filter(|c| !c.is_player && creature_kind(&c.name) == kind)
+                .filter_map(|c| c.token)
+                .max();
+            if let Some(highest) = highest {
+                combatant.name = kind_name(&combatant.name);
+                combatant.token = Some(highest + 1);
+            }
+        }
         self.combatants.push(combatant);
         self.sort_by_initiative();
     }
 
+    /// Number the NPCs of each kind 1, 2, 3... in the order they joined, to match
+    /// numbered minis. Without `kind`, only kinds with more than one combatant are
+    /// numbered. Names already ending in a number ("Goblin 2") lose it for the token.
+    pub fn renumber(&mut self, kind: Option<&str>) -> Result<Vec<String>> {
+        let mut kinds: Vec<String> = Vec::new();
+        for combatant in self.combatants.iter().filter(|c| !c.is_player) {
+            let k = creature_kind(&combatant.name);
+            if !kinds.contains(&k) && kind.is_none_or(|wanted| creature_kind(wanted) == k) {
+                kinds.push(k);
+            }
+        }
+        if kinds.is_empty() {
+            return Err(AppError::NotFound(match kind {
+                Some(kind) => format!("Monster '{}'", kind),
+                None => "Monsters to number".to_string(),
+            }));
+        }
+        let mut lines = Vec::new();
+        for k in kinds {
+            let mut members: Vec<usize> = (0..self.combatants.len())
+                .filter(|&i| !self.combatants[i].is_player && creature_kind(&self.combatants[i].name) == k)
+                .collect();
+            if kind.is_none() && members.len() < 2 {
+                continue;
+            }
+            members.sort_by_key(|&i| self.combatants[i].id);
+            for (number, &i) in members.iter().enumerate() {
+                let combatant = &mut self.combatants[i];
+                combatant.name = kind_name(&combatant.name);
+                combatant.token = Some(number as u32 + 1);
+            }
+            let names: Vec<String> = members.iter().map(|&i| self.display_name(&self.combatants[i])).collect();
+            lines.push(format!("🔢 {}", names.join(", ")));
+        }
+        Ok(lines)
+    }
+
+    /// `attacks [name]` lists a combatant's attacks (the current one's by default), and
+    /// `attacks <name> add|remove ...` changes them for this fight. A party member's
+    /// sheet keeps its own list; the Characters display edits that.
+    pub fn attacks_command(&mut self, args: &[&str], characters: &[Character]) -> Vec<String> {
+        let (name, rest) = match args.split_first() {
+            Some((name, rest)) => (name.to_string(), rest),
+            None => match self.combatants.get(self.current_turn) {
+                Some(current) => (current.name.clone(), args),
+                None => return vec!["No combatants yet".to_string()],
+            },
+        };
+        let Some(combatant) = self.get_combatant(&name) else {
+            return vec![format!("❌ Combatant '{}' not found", name)];
+        };
+        let owner = self.display_name(combatant);
+        let mut attacks = combatant.attack_profiles(characters).to_vec();
+        let linked = combatant.character_id.is_some();
+        let mut lines = crate::attacks::attacks_command(&owner, &mut attacks, rest);
+        if !rest.is_empty() && let Some(combatant) = self.get_combatant_mut(&name) {
+            combatant.attacks = attacks;
+            if linked {
+                lines.push("  (for this fight; 'attacks' in Characters → Display changes the sheet)".to_string());
+            }
+        }
+        lines
+    }
+
+    /// `renumber [kind]` and `token <name> <number|none>`, as lines to show
+    pub fn token_command(&mut self, command: &str, args: &[&str]) -> Vec<String> {
+        let result = if command == "renumber" {
+            let kind = args.join(" ");
+            self.renumber((!kind.is_empty()).then_some(kind.as_str()))
+        } else {
+            match args.split_last() {
+                Some((token, name)) if !name.is_empty() => {
+                    let name = name.join(" ");
+                    let before = self.get_combatant(&name).map(|c| self.display_name(c));
+                    let token = match token.to_lowercase().as_str() {
+                        "none" | "clear" => Ok(None),
+                        number => number.parse::<u32>().ok().filter(|&n| n > 0).map(Some)
+                            .ok_or_else(|| AppError::ValidationError(format!("'{}' isn't a token number", token))),
+                    };
+                    token.and_then(|token| self.set_token(&name, token))
+                        .map(|after| vec![format!("🔢 {} is now {}", before.unwrap_or(name), after)])
+                }
+                _ => Err(AppError::ValidationError("Usage: token <name> <number|none>".to_string())),
+            }
+        };
+        result.unwrap_or_else(|e| vec![format!("❌ {}", e)])
+    }
+
+    /// The combatant named by the longest run of leading words, so "adult red dragon"
+    /// works, and the words after. At least one word is left over when there are several.
+    fn find_leading<'a, 'b>(&self, args: &'a [&'b str]) -> Option<(usize, &'a [&'b str])> {
+        (1..=args.len().saturating_sub(1).max(1)).rev()
+            .find_map(|words| self.find_index(&args[..words].join(" ")).map(|index| (index, &args[words..])))
+    }
+
+    /// `legendary` lists what the legendary creatures have left, `legendary <name> <count>`
+    /// gives one that many a round (0 takes them away) and `legendary <name> <action> [cost]`
+    /// spends them. Returns the lines to show.
+    pub fn legendary_command(&mut self, args: &[&str]) -> Vec<String> {
+        if args.is_empty() {
+            let pools: Vec<String> = self.combatants.iter()
+                .filter_map(|c| c.legendary.map(|pool| format!("  👑 {}: {}/{} left", self.display_name(c), pool.left(), pool.per_round)))
+                .collect();
+            if pools.is_empty() {
+                return vec!["No one has legendary actions. Give a creature some with 'legendary dragon 3'".to_string()];
+            }
+            return std::iter::once("Legendary actions:".to_string()).chain(pools).collect();
+        }
+        let Some((index, rest)) = self.find_leading(args) else {
+            return vec![format!("❌ Combatant '{}' not found", args[0])];
+        };
+        let name = self.combatants[index].name.to_lowercase();
+        let who = self.display_name(&self.combatants[index]);
+        let own_turn = index == self.current_turn;
+        let combatant = &mut self.combatants[index];
+        match rest {
+            [] => match combatant.legendary {
+                Some(pool) => vec![format!("👑 {}: {}/{} legendary actions left", who, pool.left(), pool.per_round)],
+                None => vec![format!("{} has no legendary actions", who)],
+            },
+            [count] if count.parse::<u32>().is_ok() => {
+                let per_round = count.parse().unwrap_or(0);
+                combatant.legendary = (per_round > 0).then_some(LegendaryActions { per_round, spent: 0 });
+                match per_round {
+                    0 => vec![format!("{} no longer has legendary actions", who)],
+                    _ => vec![format!("👑 {} can take {} legendary actions a round, at the end of other creatures' turns", who, per_round)],
+                }
+            }
+            action => {
+                let (action, cost) = match action.split_last() {
+                    Some((cost, words)) if !words.is_empty() && cost.parse::<u32>().is_ok() => (words.join(" "), cost.parse().unwrap_or(1)),
+                    _ => (action.join(" "), 1),
+                };
+                if combatant.current_hp <= 0 {
+                    return vec![format!("❌ {} is down and can't take legendary actions", who)];
+                }
+                let Some(pool) = combatant.legendary.as_mut() else {
+                    return vec![format!("❌ {} has no legendary actions; give them some with 'legendary {} 3'", who, name)];
+                };
+                if pool.left() < cost {
+                    return vec![format!("❌ {} has {} legendary action{} left this round", who, pool.left(), if pool.left() == 1 { "" } else { "s" })];
+                }
+                pool.spent += cost;
+                let mut lines = vec![format!("👑 {} uses {} ({} legendary action{}): {}/{} left",
+                    who, action, cost, if cost == 1 { "" } else { "s" }, pool.left(), pool.per_round)];
+                if own_turn {
+                    lines.push("  ⚠️ Legendary actions come at the end of another creature's turn, not its own".to_string());
+                }
+                lines
+            }
+        }
+    }
+
+    /// `lair` shows whose lair the fight is in, `lair <name>` sets it and `lair off` clears it
+    pub fn lair_command(&mut self, args: &[&str]) -> Vec<String> {
+        let owner = args.join(" ");
+        match owner.to_lowercase().as_str() {
+            "" => match &self.lair {
+                Some(lair) => vec![format!("🏰 The fight is in {}'s lair; its lair action comes on initiative 20", lair)],
+                None => vec!["No lair in this fight. Set one with 'lair <name>'".to_string()],
+            },
+            "off" | "none" | "clear" => {
+                self.lair = None;
+                vec!["🏰 No more lair actions".to_string()]
+            }
+            _ => {
+                let owner = self.get_combatant(&owner).map_or(owner, |c| self.display_name(c));
+                let line = format!("🏰 The fight is in {}'s lair: a lair action on initiative 20 each round (losing ties)", owner);
+                self.lair = Some(owner);
+                vec![line]
+            }
+        }
+    }
+
+    /// Whether the lair acts just before the turn at `acting`. It goes on initiative 20
+    /// and loses ties, so after everyone on 20 or more; at the top of the round it goes
+    /// first if no one rolled that high, or closes the round if everyone did.
+    fn lair_acts_before(&self, acting: usize) -> bool {
+        let Some(current) = self.combatants.get(acting) else {
+            return false;
+        };
+        if acting == 0 {
+            return current.initiative < 20 || self.combatants.iter().all(|c| c.initiative >= 20 || c.initiative == 0);
+        }
+        let previous = self.combatants[..acting].iter().rev().find(|c| c.initiative > 0);
+        current.initiative > 0 && current.initiative < 20 && previous.is_some_and(|c| c.initiative >= 20)
+    }
+
+    /// What comes with the start of the turn at `acting`: the lair action when the order
+    /// passes initiative 20, and a legendary creature getting its actions back
+    pub fn turn_reminders(&mut self, acting: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(lair) = &self.lair && self.lair_acts_before(acting) {
+            lines.push(format!("🏰 Lair action on initiative 20 ({}'s lair)", lair));
+        }
+        let Some(combatant) = self.combatants.get(acting) else {
+            return lines;
+        };
+        let name = self.display_name(combatant);
+        if let Some(pool) = self.combatants[acting].legendary.as_mut() && pool.spent > 0 {
+            pool.spent = 0;
+            lines.push(format!("👑 {} regains its {} legendary actions", name, pool.per_round));
+        }
+        lines
+    }
+
+    /// Where the lair's initiative 20 sits among the rows of `visible_combatants`, when
+    /// there is a lair
+    pub fn lair_row(&self) -> Option<usize> {
+        self.lair.as_ref()?;
+        Some(self.visible_combatants().filter(|(_, c)| c.initiative >= 20).count())
+    }
+
+    /// Give one combatant the number on the mini it's using, or clear it
+    pub fn set_token(&mut self, name: &str, token: Option<u32>) -> Result<String> {
+        let index = self.find_index(name)
+            .ok_or_else(|| AppError::NotFound(format!("Combatant '{}'", name)))?;
+        if let Some(token) = token {
+            let kind = creature_kind(&self.combatants[index].name);
+            if let Some(other) = self.combatants.iter().enumerate()
+                .find(|(i, c)| *i != index && c.token == Some(token) && creature_kind(&c.name) == kind) {
+                return Err(AppError::ValidationError(format!("{} already has token {}", self.display_name(other.1), token)));
+            }
+            self.combatants[index].name = kind_name(&self.combatants[index].name);
+        }
+        self.combatants[index].token = token;
+        Ok(self.display_name(&self.combatants[index]))
+    }
+
+    /// Name shown in lists: with its token number when it has one, or with the
+    /// internal id when several combatants share a name
+    pub fn display_name(&self, combatant: &Combatant) -> String {
+        if let Some(token) = combatant.token {
+            return format!("{} {}", combatant.name, token);
+        }
+        let shared = self.combatants.iter()
+            .filter(|c| c.name.eq_ignore_ascii_case(&combatant.name))
+            .count() > 1;
+        if shared {
+            format!("{}#{}", combatant.name, combatant.id)
+        } else {
+            combatant.name.clone()
+        }
+    }
+
+    /// Combatants players may see, with their position in the full initiative order
+    pub fn visible_combatants(&self) -> impl Iterator<Item = (usize, &Combatant)> {
+        self.combatants.iter().enumerate().filter(|(_, c)| !c.hidden)
+    }
+
+    pub fn hidden_names(&self) -> Vec<String> {
+        self.combatants.iter().filter(|c| c.hidden).map(|c| self.display_name(c)).collect()
+    }
+
+    /// Hide or reveal a combatant; it keeps its place in the turn order either way
+    pub fn set_hidden(&mut self, name: &str, hidden: bool) -> Result<String> {
+        let index = self.find_index(name)
+            .ok_or_else(|| AppError::NotFound(format!("Combatant '{}'", name)))?;
+        self.combatants[index].hidden = hidden;
+        Ok(self.display_name(&self.combatants[index]))
+    }
+
+    /// Ids for "name#id", or for every combatant with a plain name
+    fn ids_matching(&self, query: &str) -> Vec<u32> {
+        if query.contains('#') || self.token_index(query).is_some() {
+            return self.find_index(query).map(|i| vec![self.combatants[i].id]).unwrap_or_default();
+        }
+        self.combatants.iter()
+            .filter(|c| c.name.eq_ignore_ascii_case(query))
+            .map(|c| c.id)
+            .collect()
+    }
+
+    /// Create or replace a morale group. Members are names (a shared name adds every
+    /// combatant with it); prefix one with `*` to mark the leader.
+    pub fn add_morale_group(&mut self, name: &str, morale: i32, members: &[&str]) -> Result<String> {
+        let mut ids = Vec::new();
+        let mut leader = None;
+        for member in members {
+            let (is_leader, query) = match member.strip_prefix('*') {
+                Some(query) => (true, query),
+                None => (false, *member),
+            };
+            let found = self.ids_matching(query);
+            if found.is_empty() {
+                return Err(AppError::NotFound(format!("Combatant '{}'", query)));
+            }
+            if is_leader {
+                leader = found.first().copied();
+            }
+            ids.extend(found.into_iter().filter(|id| !ids.contains(id)).collect::<Vec<_>>());
+        }
+
+        let group = MoraleGroup::new(name, morale, ids, leader)?;
+        let summary = format!("{} (morale {}, {} members{})", group.name, group.morale, group.members.len(),
+            if group.leader.is_some() { ", with a leader" } else { "" });
+        self.morale_groups.retain(|g| !g.name.eq_ignore_ascii_case(name));
+        self.morale_groups.push(group);
+        Ok(summary)
+    }
+
+    pub fn morale_group_mut(&mut self, name: &str) -> Option<&mut MoraleGroup> {
+        self.morale_groups.iter_mut().find(|g| g.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Groups that should now check morale, and why
+    pub fn morale_triggers(&mut self) -> Vec<(String, MoraleTrigger)> {
+        let combatants = &self.combatants;
+        self.morale_groups.iter_mut()
+            .flat_map(|group| {
+                let name = group.name.clone();
+                group.pending_triggers(combatants).into_iter().map(move |t| (name.clone(), t))
+            })
+            .collect()
+    }
+
+    /// Roll 2d6 against a group's morale and describe what they do
+    pub fn roll_morale(&mut self, group_name: &str) -> Result<String> {
+        let roll = crate::dice::roll_dice_with_crits("2d6").map_err(AppError::ParseError)?;
+        let group = self.morale_group_mut(group_name)
+            .ok_or_else(|| AppError::NotFound(format!("Morale group '{}'", group_name)))?;
+        let outcome = group.check(roll.total);
+        Ok(format!("🏳️ {} morale check: {} vs morale {} → {}: {}",
+            group.name, roll, group.morale, outcome, outcome.advice()))
+    }
+
+    /// Find a combatant by name, or by "name#id" / "#id" when names are shared
+    fn find_index(&self, query: &str) -> Option<usize> {
+        if let Some((name, id)) = query.rsplit_once('#')
+            && let Ok(id) = id.parse::<u32>() {
+            return self.combatants.iter()
+                .position(|c| c.id == id && (name.is_empty() || c.name.eq_ignore_ascii_case(name)));
+        }
+        self.token_index(query)
+            .or_else(|| self.combatants.iter().position(|c| c.name.eq_ignore_ascii_case(query)))
+    }
+
+    /// "Goblin 3", or "goblin3" for one-word commands, is the goblin with token 3
+    fn token_index(&self, query: &str) -> Option<usize> {
+        let query = query.trim();
+        let name = query.trim_end_matches(|ch: char| ch.is_ascii_digit());
+        let token = query[name.len()..].parse::<u32>().ok()?;
+        self.combatants.iter().position(|c| c.token == Some(token) && c.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// The combat-start HP review: every combatant, numbered, with its saved HP
+    pub fn hp_review_lines(&self) -> Vec<String> {
+        let width = self.combatants.iter().map(|c| self.display_name(c).chars().count()).max().unwrap_or(4).max(4);
+        let mut lines = vec![format!("  #  {:<width$}  {:>7}", "Name", "HP")];
+        for (i, combatant) in self.combatants.iter().enumerate() {
+            lines.push(format!("{:>3}  {:<width$}  {:>7}", i + 1, self.display_name(combatant),
+                format!("{}/{}", combatant.current_hp, combatant.max_hp)));
+        }
+        lines
+    }
+
+    /// Apply HP overrides from the review, e.g. "2 5, goblin#3 4, Aria 12". Each entry
+    /// picks a combatant by its number in the review or by name; bad entries are
+    /// reported and skipped.
+    pub fn apply_hp_overrides(&mut self, input: &str) -> Vec<String> {
+        let mut results = Vec::new();
+        for entry in input.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((who, hp)) = entry.rsplit_once(char::is_whitespace) else {
+                results.push(format!("❌ '{}': expected <#|name> <hp>", entry));
+                continue;
+            };
+            let who = who.trim();
+            let index = match who.parse::<usize>() {
+                Ok(n) if (1..=self.combatants.len()).contains(&n) => Some(n - 1),
+                Ok(_) => None,
+                Err(_) => self.find_index(who),
+            };
+            let Some(index) = index else {
+                results.push(format!("❌ No combatant '{}'", who));
+                continue;
+            };
+            let combatant = &mut self.combatants[index];
+            match hp.parse::<i32>() {
+                Ok(hp) if (0..=combatant.max_hp).contains(&hp) => {
+                    combatant.current_hp = hp;
+                    results.push(format!("✅ Set {}'s HP to {}", combatant.name, hp));
+                }
+                Ok(_) => results.push(format!("❌ {}'s HP must be between 0 and {}", combatant.name, combatant.max_hp)),
+                Err(_) => results.push(format!("❌ '{}' is not a number", hp)),
+            }
+        }
+        results
+    }
+
+    /// Roll d20+DEX for everyone, re-sort and start from the top of the order.
+    /// Returns the announcement of the new order; hidden combatants are left out of it.
+    pub fn reroll_initiative<R: Rng + ?Sized>(&mut self, characters: &[Character], rng: &mut R) -> Vec<String> {
+        for combatant in &mut self.combatants {
+            let dex = combatant.sheet(characters).map(|sheet| sheet.get_dexterity_modifier() as i32).unwrap_or(0);
+            // Initiative 0 means "skip this turn" to the tracker, so a roll never goes below 1
+            combatant.initiative = (rng.random_range(1..=20) + dex).max(1);
+        }
+        self.sort_by_initiative();
+        self.current_turn = 0;
+        self.rerolled_round = self.round_number;
+        let mut lines = vec![format!("🎲 Initiative re-rolled for round {}:", self.round_number)];
+        lines.extend(self.visible_combatants().map(|(_, c)| format!("  {:>2}  {}", c.initiative, self.display_name(c))));
+        lines
+    }
+
+    /// At the top of a round with the re-roll variant on, re-roll once for that round
+    pub fn reroll_for_new_round<R: Rng + ?Sized>(&mut self, characters: &[Character], rng: &mut R) -> Vec<String> {
+        if !self.reroll_each_round || self.current_turn != 0 || self.rerolled_round >= self.round_number {
+            return Vec::new();
+        }
+        self.reroll_initiative(characters, rng)
+    }
+
+    /// Who the classic loop's `next_turn` last started a turn for
+    pub fn last_actor_name(&self) -> Option<String> {
+        self.last_actor.and_then(|id| self.combatants.iter().find(|c| c.id == id)).map(|c| c.name.clone())
+    }
+
+    /// Mark the current combatant's turn as begun, as the TUI does once they act
+    pub fn begin_turn(&mut self) {
+        self.turn_underway = true;
+    }
+
+    /// Once turns have started moving, someone joining mid-round doesn't send the order
+    /// back to the top. The classic loop has already moved `current_turn` on to whoever
+    /// is up next, so the order carries on after whoever acted last; the TUI's
+    /// `current_turn` is whoever is acting, and the turn stays theirs.
     fn sort_by_initiative(&mut self) {
+        let anchor = match self.last_actor {
+            // A round that has just wrapped starts again from the top
+            Some(_) if self.current_turn == 0 => None,
+            Some(id) => Some((id, 1)),
+            None if self.turn_underway || self.current_turn > 0 || self.round_number > 1 => {
+                self.combatants.get(self.current_turn).map(|c| (c.id, 0))
+            }
+            None => None,
+        };
         self.combatants.sort_by(|a, b| b.initiative.cmp(&a.initiative));
-        self.current_turn = 0;
+        self.current_turn = anchor
+            .and_then(|(id, after)| self.combatants.iter().position(|c| c.id == id).map(|i| (i + after) % self.combatants.len()))
+            .unwrap_or(0);
     }
 
     pub fn next_turn(&mut self) -> Option<&mut Combatant> {
@@ -176,6 +1370,18 @@ impl CombatTracker {
             
             if combatant.initiative > 0 {
                 let current_turn = self.current_turn;
+                if let Some(ended) = self.last_actor.and_then(|id| self.combatants.iter().position(|c| c.id == id)) {
+                    for message in self.tick_status_effects(ended, TickPoint::EndOfTurn) {
+                        println!("{}", message);
+                    }
+                }
+                for message in self.tick_status_effects(current_turn, TickPoint::StartOfTurn) {
+                    println!("{}", message);
+                }
+                for message in self.turn_reminders(current_turn) {
+                    println!("{}", message);
+                }
+                self.last_actor = Some(self.combatants[current_turn].id);
                 self.current_turn = (self.current_turn + 1) % self.combatants.len();
                 
                 // If we've looped back to the beginning, increment round
@@ -200,16 +1406,316 @@ impl CombatTracker {
         None
     }
 
+    /// Count down the effects on the combatant at `index` that tick at `point`,
+    /// removing those that run out. Returns the expiry announcements.
+    pub fn tick_status_effects(&mut self, index: usize, point: TickPoint) -> Vec<String> {
+        let mut messages = self.status_damage(index, point);
+        let Some(combatant) = self.combatants.get_mut(index) else {
+            return messages;
+        };
+        let mut expired = Vec::new();
+        combatant.status_effects.retain_mut(|status| match status.duration.as_mut() {
+            Some(rounds) if status.ticks_at == point => {
+                *rounds -= 1;
+                if *rounds <= 0 {
+                    expired.push(status.name.clone());
+                }
+                *rounds > 0
+            }
+            _ => true,
+        });
+        let name = self.display_name(&self.combatants[index]);
+        messages.extend(expired.into_iter().map(|status| format!("⌛ {} is no longer {}", name, status)));
+        messages
+    }
+
+    /// Roll and deal the damage of effects like burning that hurt each time they tick
+    fn status_damage(&mut self, index: usize, point: TickPoint) -> Vec<String> {
+        let Some(combatant) = self.combatants.get(index).filter(|c| c.current_hp > 0) else {
+            return Vec::new();
+        };
+        let name = self.display_name(combatant);
+        let damaging: Vec<(String, String)> = combatant.status_effects.iter()
+            .filter(|s| s.ticks_at == point)
+            .filter_map(|s| Some((s.name.clone(), s.damage.clone()?)))
+            .collect();
+        let mut messages = Vec::new();
+        for (status, damage) in damaging {
+            let words: Vec<&str> = damage.split_whitespace().collect();
+            let (dice, kind) = split_damage_type(&words);
+            match crate::dice::roll_expression(&dice.join("")) {
+                Ok(roll) => {
+                    messages.push(format!("🔥 {} is {}: {}{} damage", name, status.to_lowercase(), roll,
+                        kind.map(|k| format!(" {}", k.name())).unwrap_or_default()));
+                    match self.apply_damage(&name, roll.total.max(0), kind) {
+                        Ok(message) => messages.extend(message.lines().map(str::to_string)),
+                        Err(e) => messages.push(format!("❌ {}", e)),
+                    }
+                }
+                Err(e) => messages.push(format!("❌ Could not roll {}'s damage '{}': {}", status, damage, e)),
+            }
+        }
+        messages
+    }
+
+    /// Name a concentration effect's caster the way this fight finds them, so the
+    /// effect can end when they drop
+    pub fn resolve_concentration(&self, status: &mut StatusEffect) -> Result<()> {
+        if let Some(owner) = &status.concentration {
+            let caster = self.get_combatant(owner)
+                .ok_or_else(|| AppError::NotFound(format!("Concentrating combatant '{}'", owner)))?;
+            status.concentration = Some(self.display_name(caster));
+        }
+        Ok(())
+    }
+
+    /// End effects held by concentration when the concentrating combatant is at 0 HP
+    /// (or has left the fight)
+    pub fn end_broken_concentration(&mut self) -> Vec<String> {
+        let broken: Vec<(usize, String, String)> = self.combatants.iter().enumerate()
+            .flat_map(|(i, c)| c.status_effects.iter().map(move |s| (i, s)))
+            .filter_map(|(i, status)| {
+                let owner = status.concentration.as_ref()?;
+                let holding = self.get_combatant(owner).is_some_and(|c| c.current_hp > 0);
+                (!holding).then(|| (i, status.name.clone(), owner.clone()))
+            })
+            .collect();
+        broken.into_iter().map(|(index, status, owner)| {
+            let combatant = &mut self.combatants[index];
+            combatant.remove_status(&status);
+            format!("💫 {} lost concentration: {} is no longer {}", owner, combatant.name, status)
+        }).collect()
+    }
+
+    /// `concentrate` lists who is concentrating, `concentrate <name> <spell>` starts (ending
+    /// any spell they held before), `concentrate <name> end` stops, and `concentrate <name>
+    /// roll [adv|dis]` or `concentrate <name> <total>` settles a save they owe
+    pub fn concentrate_command(&mut self, args: &[&str], characters: &[Character]) -> Vec<String> {
+        if args.is_empty() {
+            let held: Vec<String> = self.combatants.iter()
+                .filter_map(|c| c.concentration.as_ref().map(|held| {
+                    let owed = held.pending_saves.iter().map(|dc| format!(" (owes a DC {} save)", dc)).collect::<String>();
+                    format!("  🧠 {}: {}{}", self.display_name(c), held.spell, owed)
+                }))
+                .collect();
+            if held.is_empty() {
+                return vec!["No one is concentrating. Usage: concentrate <name> <spell>".to_string()];
+            }
+            return std::iter::once("Concentrating:".to_string()).chain(held).collect();
+        }
+        let Some((index, rest)) = self.find_leading(args) else {
+            return vec![format!("❌ Combatant '{}' not found", args[0])];
+        };
+        let name = self.display_name(&self.combatants[index]);
+        let (words, edge) = split_edge(rest);
+        match words {
+            [] if edge.is_none() => match &self.combatants[index].concentration {
+                Some(held) => vec![format!("🧠 {} is concentrating on {}", name, held.spell)],
+                None => vec![format!("{} isn't concentrating on anything", name)],
+            },
+            [stop] if ["end", "stop", "drop"].iter().any(|word| stop.eq_ignore_ascii_case(word)) => {
+                let lines = self.end_concentration(index, "stops concentrating");
+                if lines.is_empty() { vec![format!("{} isn't concentrating on anything", name)] } else { lines }
+            }
+            [roll] if roll.eq_ignore_ascii_case("roll") || roll.parse::<i32>().is_ok() => {
+                let Some(dc) = self.combatants[index].concentration.as_mut()
+                    .and_then(|held| (!held.pending_saves.is_empty()).then(|| held.pending_saves.remove(0))) else {
+                    return vec![format!("{} doesn't owe a concentration save", name)];
+                };
+                match roll.parse::<i32>() {
+                    Ok(total) => self.settle_concentration_save(index, dc, format!("🎲 {} rolled {} on the Constitution save", name, total), total),
+                    Err(_) => {
+                        let modifier = self.combatants[index].sheet(characters)
+                            .map_or(0, |sheet| sheet.get_ability_modifier(crate::character::AbilityScore::Constitution) as i32);
+                        self.roll_concentration_save(index, dc, modifier, edge)
+                    }
+                }
+            }
+            [] => vec![format!("Usage: concentrate {} roll [adv|dis]", name)],
+            spell => {
+                let spell = spell.join(" ");
+                let mut lines = self.end_concentration(index, "stops concentrating");
+                self.combatants[index].concentration = Some(Concentration { spell: spell.clone(), pending_saves: Vec::new() });
+                lines.push(format!("🧠 {} is concentrating on {}. Damage calls for a Constitution save (DC 10, or half the damage)", name, spell));
+                lines
+            }
+        }
+    }
+
+    /// Check a concentrating combatant's hold after `damage`: dropping to 0 HP ends the
+    /// spell, an NPC rolls its Constitution save at once, and a party member is asked
+    /// to roll theirs
+    pub fn concentration_check(&mut self, target_name: &str, damage: i32) -> Vec<String> {
+        let Some(index) = self.find_index(target_name) else {
+            return Vec::new();
+        };
+        let combatant = &self.combatants[index];
+        let Some(held) = combatant.concentration.as_ref().filter(|_| damage > 0) else {
+            return Vec::new();
+        };
+        if combatant.current_hp <= 0 {
+            return self.end_concentration(index, "drops to 0 HP");
+        }
+        let dc = concentration_dc(damage);
+        if combatant.is_player {
+            let name = self.display_name(combatant);
+            let line = format!("🧠 {} must make a DC {} Constitution save to keep concentrating on {}: 'concentrate {} roll', or 'concentrate {} <total>' for their own roll",
+                name, dc, held.spell, name, name);
+            if let Some(held) = self.combatants[index].concentration.as_mut() {
+                held.pending_saves.push(dc);
+            }
+            return vec![line];
+        }
+        let modifier = combatant.npc_sheet.as_ref()
+            .map_or(0, |sheet| sheet.get_ability_modifier(crate::character::AbilityScore::Constitution) as i32);
+        self.roll_concentration_save(index, dc, modifier, None)
+    }
+
+    fn roll_concentration_save(&mut self, index: usize, dc: i32, modifier: i32, edge: Option<Edge>) -> Vec<String> {
+        let name = self.display_name(&self.combatants[index]);
+        match crate::dice::roll_dice_with_crits(Edge::d20(edge)) {
+            Ok(roll) => {
+                let roll = roll.plus(modifier);
+                let total = roll.total;
+                self.settle_concentration_save(index, dc, format!("🎲 {} makes a Constitution save{}: {}", name, Edge::with(edge), roll), total)
+            }
+            Err(e) => vec![format!("❌ Error rolling d20: {}", e)],
+        }
+    }
+
+    fn settle_concentration_save(&mut self, index: usize, dc: i32, roll_line: String, total: i32) -> Vec<String> {
+        let name = self.display_name(&self.combatants[index]);
+        let mut lines = vec![roll_line];
+        match &self.combatants[index].concentration {
+            Some(held) if total >= dc => lines.push(format!("✅ {} keeps concentrating on {} (DC {})", name, held.spell, dc)),
+            _ => lines.extend(self.end_concentration(index, &format!("fails the DC {} save", dc))),
+        }
+        lines
+    }
+
+    /// End the spell the combatant at `index` is concentrating on, and the statuses it held
+    pub fn end_concentration(&mut self, index: usize, reason: &str) -> Vec<String> {
+        let Some(held) = self.combatants.get_mut(index).and_then(|c| c.concentration.take()) else {
+            return Vec::new();
+        };
+        let caster = &self.combatants[index];
+        let names = [self.display_name(caster), caster.name.clone()];
+        let mut lines = vec![format!("💫 {} {} and loses concentration on {}", names[0], reason, held.spell)];
+        for combatant in &mut self.combatants {
+            let ended: Vec<String> = combatant.status_effects.iter()
+                .filter(|status| status.concentration.as_ref().is_some_and(|owner| names.iter().any(|n| n.eq_ignore_ascii_case(owner))))
+                .map(|status| status.name.clone())
+                .collect();
+            for status in ended {
+                combatant.remove_status(&status);
+                lines.push(format!("  {} is no longer {}", combatant.name, status));
+            }
+        }
+        lines
+    }
+
+    /// `resist troll fire cold`, `vulnerable skeleton bludgeoning`, `immune golem poison`,
+    /// or `none` to clear the list
+    pub fn set_defenses(&mut self, name: &str, kind: &str, types: &[&str]) -> Result<String> {
+        let mut parsed = Vec::new();
+        if !matches!(types, [none] if none.eq_ignore_ascii_case("none")) {
+            for text in types {
+                let damage_type = DamageType::parse(text).ok_or_else(|| AppError::ValidationError(format!(
+                    "Unknown damage type '{}'. Types: {}", text, type_list(&DamageType::ALL))))?;
+                if !parsed.contains(&damage_type) {
+                    parsed.push(damage_type);
+                }
+            }
+        }
+        parsed.sort();
+        let combatant = self.get_combatant_mut(name)
+            .ok_or_else(|| AppError::NotFound(format!("Combatant '{}'", name)))?;
+        let list = combatant.defenses_mut(kind)
+            .ok_or_else(|| AppError::ValidationError(format!("Unknown defense '{}'. Use resist, vulnerable or immune", kind)))?;
+        *list = parsed;
+        let lines = combatant.defense_lines();
+        Ok(if lines.is_empty() {
+            format!("🛡️ {} has no resistances, vulnerabilities or immunities", combatant.name)
+        } else {
+            format!("🛡️ {}: {}", combatant.name, lines.join("; "))
+        })
+    }
+
+    /// Start death saves for party members who have dropped to 0 HP, and stop them
+    /// for anyone who has been healed. Returns the announcements.
+    pub fn update_death_saves(&mut self) -> Vec<String> {
+        let mut messages = Vec::new();
+        for combatant in self.combatants.iter_mut().filter(|c| c.is_player) {
+            match (combatant.current_hp > 0, combatant.death_saves.is_some()) {
+                (false, false) => {
+                    combatant.death_saves = Some(DeathSaves::default());
+                    messages.push(format!("🩸 {} is dying: 'deathsave {}' on their turn", combatant.name, combatant.name));
+                }
+                (true, true) => {
+                    combatant.death_saves = None;
+                    messages.push(format!("💗 {} is back on their feet and stops making death saves", combatant.name));
+                }
+                _ => {}
+            }
+        }
+        messages
+    }
+
+    /// Record a death save for a dying combatant from a d20 roll: 10 or higher
+    /// succeeds, a 1 counts as two failures and a 20 brings them back with 1 HP
+    pub fn record_death_save(&mut self, name: &str, roll: i32) -> Result<String> {
+        let combatant = self.get_combatant_mut(name)
+            .ok_or_else(|| AppError::NotFound(format!("Combatant '{}'", name)))?;
+        let Some(saves) = combatant.death_saves.as_mut() else {
+            return Err(AppError::ValidationError(format!("{} isn't making death saves", combatant.name)));
+        };
+        if saves.is_dead() {
+            return Err(AppError::ValidationError(format!("{} has already died", combatant.name)));
+        }
+        if saves.is_stable() {
+            return Err(AppError::ValidationError(format!("{} is already stable", combatant.name)));
+        }
+
+        if roll >= 20 {
+            combatant.death_saves = None;
+            combatant.current_hp = 1;
+            return Ok(format!("🌟 {} rolls a natural 20 on their death save, regains 1 HP and is back up!", combatant.name));
+        }
+        let result = match roll {
+            ..=1 => {
+                saves.fail(2);
+                "natural 1, two failures"
+            }
+            2..=9 => {
+                saves.fail(1);
+                "failure"
+            }
+            _ => {
+                saves.successes += 1;
+                "success"
+            }
+        };
+        let mut message = format!("🎲 {} death save: {} - {} ({})", combatant.name, roll, result, saves.tally());
+        if saves.is_dead() {
+            message.push_str(&format!("\n☠️ {} has failed three death saves and dies", combatant.name));
+        } else if saves.is_stable() {
+            message.push_str(&format!("\n💤 {} is stable at 0 HP", combatant.name));
+        }
+        Ok(message)
+    }
+
     pub fn get_combatant_mut(&mut self, name: &str) -> Option<&mut Combatant> {
-        self.combatants.iter_mut().find(|c| c.name.eq_ignore_ascii_case(name))
+        let index = self.find_index(name)?;
+        self.combatants.get_mut(index)
     }
 
     pub fn get_combatant(&self, name: &str) -> Option<&Combatant> {
-        self.combatants.iter().find(|c| c.name.eq_ignore_ascii_case(name))
+        let index = self.find_index(name)?;
+        self.combatants.get(index)
     }
 
     pub fn remove_combatant(&mut self, name: &str) -> bool {
-        if let Some(pos) = self.combatants.iter().position(|c| c.name.eq_ignore_ascii_case(name)) {
+        if let Some(pos) = self.find_index(name) {
             self.combatants.remove(pos);
             if self.current_turn >= self.combatants.len() && !self.combatants.is_empty() {
                 self.current_turn = 0;
@@ -220,12 +1726,28 @@ impl CombatTracker {
         }
     }
 
-    pub fn apply_damage(&mut self, target_name: &str, damage: i32) -> Result<String, String> {
+    /// Deal damage, halved, doubled or ignored by the target's defenses against its type
+    pub fn apply_damage(&mut self, target_name: &str, damage: i32, kind: Option<DamageType>) -> Result<String> {
+        let target = self.get_combatant_mut(target_name)
+            .ok_or_else(|| AppError::NotFound(format!("Target '{}'", target_name)))?;
+        let (damage, adjusted) = target.adjust_damage(damage, kind);
+        let dying = if damage > 0 { target.damaged_while_dying() } else { None };
+        let message = self.apply_damage_to_hp(target_name, damage)?;
+        let concentration = self.concentration_check(target_name, damage);
+        Ok(adjusted.into_iter().chain([message]).chain(dying).chain(concentration).collect::<Vec<_>>().join("\n"))
+    }
+
+    fn apply_damage_to_hp(&mut self, target_name: &str, damage: i32) -> Result<String> {
+        let hide = self.hide_monster_hp;
         if let Some(target) = self.get_combatant_mut(target_name) {
+            let hidden = hide && !target.is_player;
             // Apply damage to temp HP first, then regular HP
             if target.temp_hp > 0 {
                 if damage <= target.temp_hp {
                     target.temp_hp -= damage;
+                    if hidden {
+                        return Ok(format!("💛 {} takes {} damage to temporary HP", target_name, damage));
+                    }
                     return Ok(format!("💛 {} takes {} damage to temporary HP (Temp HP: {}/{})", 
                              target_name, damage, target.temp_hp, target.current_hp));
                 } else {
@@ -233,12 +1755,19 @@ impl CombatTracker {
                     let remaining_damage = damage - temp_damage;
                     target.temp_hp = 0;
                     target.current_hp = (target.current_hp - remaining_damage).max(0);
+                    if hidden {
+                        return Ok(format!("💛❤️ {} takes {} damage through temporary HP ({})",
+                                 target_name, damage, target.hp_descriptor()));
+                    }
                     return Ok(format!("💛❤️ {} takes {} damage ({} to temp HP, {} to HP). HP: {}/{}, Temp: 0", 
                              target_name, damage, temp_damage, remaining_damage, 
                              target.current_hp, target.max_hp));
                 }
             } else {
                 target.current_hp = (target.current_hp - damage).max(0);
+                if hidden {
+                    return Ok(format!("❤️ {} takes {} damage ({})", target_name, damage, target.hp_descriptor()));
+                }
                 let status = if target.current_hp == 0 {
                     "💀 DOWN!"
                 } else if target.current_hp <= target.max_hp / 4 {
@@ -251,11 +1780,12 @@ impl CombatTracker {
                          target_name, damage, target.current_hp, target.max_hp, status));
             }
         } else {
-            Err(format!("Target '{}' not found in combat", target_name))
+            Err(AppError::NotFound(format!("Target '{}'", target_name)))
         }
     }
 
-    pub fn make_saving_throw(&self, combatant_name: &str, ability: &str) -> Result<String, String> {
+    /// With advantage or disadvantage, both dice are shown and the right one kept
+    pub fn make_saving_throw(&self, combatant_name: &str, ability: &str, characters: &[Character], edge: Option<Edge>) -> Result<String> {
         use crate::character::AbilityScore;
         use crate::dice::roll_dice_with_crits;
 
@@ -267,58 +1797,86 @@ impl CombatTracker {
                 "wis" | "wisdom" => AbilityScore::Wisdom,
                 "int" | "intelligence" => AbilityScore::Intelligence,
                 "cha" | "charisma" => AbilityScore::Charisma,
-                _ => return Err(format!("Invalid ability score: {}. Use str, dex, con, wis, int, or cha", ability)),
+                _ => return Err(AppError::ValidationError(format!("Invalid ability score: {}. Use str, dex, con, wis, int, or cha", ability))),
             };
 
-            let modifier = if let Some(character_data) = &combatant.character_data {
-                character_data.get_ability_modifier(ability_type)
+            if let Some(condition) = combatant.auto_fails_save(ability_type) {
+                return Ok(format!("🎲 {} automatically fails the {} saving throw ({})",
+                          combatant_name, ability_type.name(), condition.name().to_lowercase()));
+            }
+
+            let modifier = if let Some(sheet) = combatant.sheet(characters) {
+                sheet.get_ability_modifier(ability_type)
             } else {
                 // For NPCs without character data, assume average stats (10-11, modifier 0)
                 0
             };
 
-            match roll_dice_with_crits("1d20") {
-                Ok((rolls, base_roll, crit_message)) => {
-                    let total = base_roll as i32 + modifier as i32;
-                    let modifier_str = if modifier >= 0 {
-                        format!("+{}", modifier)
-                    } else {
-                        modifier.to_string()
-                    };
-
-                    let mut result = format!("🎲 {} makes a {} saving throw: {} (d20: {}, modifier: {}) = {}", 
-                              combatant_name, ability_type.name(), total, rolls[0], modifier_str, total);
+            match roll_dice_with_crits(Edge::d20(edge)) {
+                Ok(roll) => {
+                    let roll = roll.plus(modifier as i32);
+                    let mut result = format!("🎲 {} makes a {} saving throw{}: {}", 
+                              combatant_name, ability_type.name(), Edge::with(edge), roll);
                     
-                    if let Some(message) = crit_message {
+                    if let Some(message) = roll.crit {
                         result.push_str(&format!("\n{}", message));
                     }
+                    for hint in combatant.save_hints(ability_type) {
+                        result.push_str(&format!("\n{}", hint));
+                    }
                     
                     Ok(result)
                 }
-                Err(e) => Err(format!("Error rolling d20: {}", e)),
+                Err(e) => Err(AppError::ParseError(format!("Error rolling d20: {}", e))),
             }
         } else {
-            Err(format!("Combatant '{}' not found in combat", combatant_name))
+            Err(AppError::NotFound(format!("Combatant '{}'", combatant_name)))
         }
     }
 
-    pub fn save_characters_on_exit(&self) {
+    /// Bring linked combatants up to date with their sheets: HP, AC and any
+    /// condition noted on the sheet that the tracker doesn't have yet
+    pub fn refresh_linked(&mut self, characters: &[Character]) {
+        for combatant in &mut self.combatants {
+            let Some(character) = combatant.character_id.and_then(|id| crate::registry::by_id(characters, id)) else {
+                continue;
+            };
+            combatant.name = character.name.clone();
+            combatant.current_hp = character.hp.map(|hp| hp as i32).unwrap_or(combatant.current_hp);
+            combatant.max_hp = character.max_hp.map(|hp| hp as i32).unwrap_or(combatant.max_hp);
+            combatant.temp_hp = character.temp_hp.unwrap_or(0) as i32;
+            combatant.ac = character.ac.map(|ac| ac as i32).unwrap_or(combatant.ac);
+            for condition in &character.conditions {
+                if !combatant.status_effects.iter().any(|s| s.name.eq_ignore_ascii_case(condition)) {
+                    combatant.status_effects.push(StatusEffect::new(condition.clone(), None));
+                }
+            }
+        }
+    }
+
+    /// Write linked combatants' HP and conditions back to their sheets
+    pub fn write_back(&self, characters: &mut [Character]) {
+        for combatant in &self.combatants {
+            let Some(character) = combatant.character_id.and_then(|id| crate::registry::by_id_mut(characters, id)) else {
+                continue;
+            };
+            character.hp = Some(combatant.current_hp.clamp(0, u8::MAX as i32) as u8);
+            character.temp_hp = Some(combatant.temp_hp.clamp(0, u8::MAX as i32) as u8);
+            character.conditions = combatant.status_effects.iter().map(|s| s.name.clone()).collect();
+        }
+    }
+
+    pub fn save_characters_on_exit(&self, characters: &mut [Character]) {
         use crate::file_manager::save_character;
         
         println!("💾 Auto-saving player characters...");
+        self.write_back(characters);
         let mut saved_count = 0;
         
         for combatant in &self.combatants {
-            if combatant.is_player {
-                if let Some(character_data) = &combatant.character_data {
-                    // Update character HP from combat
-                    let mut updated_character = character_data.clone();
-                    updated_character.hp = Some(combatant.current_hp as u8);
-                    updated_character.temp_hp = Some(combatant.temp_hp as u8);
-                    
-                    save_character(updated_character.name.clone(), updated_character);
-                    saved_count += 1;
-                }
+            if let Some(character) = combatant.character_id.and_then(|id| crate::registry::by_id(characters, id)) {
+                save_character(character.name.clone(), character.clone());
+                saved_count += 1;
             }
         }
         
@@ -331,9 +1889,13 @@ impl CombatTracker {
         println!("\n📋 Initiative Order (Round {}):", self.round_number);
         println!("═══════════════════════════════════════════════════════════");
         
-        for (i, combatant) in self.combatants.iter().enumerate() {
+        let lair_row = self.lair_row();
+        for (row, (i, combatant)) in self.visible_combatants().enumerate() {
+            if lair_row == Some(row) && let Some(lair) = &self.lair {
+                println!("    🏰 Init 20: {}'s lair action", lair);
+            }
             let marker = if i == self.current_turn { ">>> " } else { "    " };
-            let hp_display = format!("{}/{}", combatant.current_hp, combatant.max_hp);
+            let hp_display = combatant.hp_display(self.hide_monster_hp);
             let status_info = if combatant.status_effects.is_empty() {
                 String::new()
             } else {
@@ -343,10 +1905,13 @@ impl CombatTracker {
             
             let type_marker = if combatant.is_player { "🧙" } else { "👹" };
             
-            println!("{}{}Init {}: {} {} (AC: {}, HP: {}){}", 
-                marker, type_marker, combatant.initiative, combatant.name,
+            println!("{}{}Init {}: {} {} (AC: {}, HP: {}){}{}", 
+                marker, type_marker, combatant.initiative, self.display_name(combatant),
                 if combatant.initiative == 0 { "(SKIPPED)" } else { "" },
-                combatant.ac, hp_display, status_info);
+                combatant.ac, hp_display, status_info, combatant.movement_tag());
+        }
+        if let Some(lair) = &self.lair && lair_row == Some(self.visible_combatants().count()) {
+            println!("    🏰 Init 20: {}'s lair action", lair);
         }
         println!("═══════════════════════════════════════════════════════════");
     }
@@ -409,9 +1974,8 @@ impl CombatTracker {
     }
 }
 
-pub fn enhanced_initiative_setup() -> CombatTracker {
+pub fn enhanced_initiative_setup(existing_characters: &mut [Character]) -> CombatTracker {
     let mut tracker = CombatTracker::new();
-    let existing_characters = load_character_files();
     
     println!("\n⚔️  Setting up Initiative Tracker ⚔️");
     println!("═══════════════════════════════════════");
@@ -424,7 +1988,7 @@ pub fn enhanced_initiative_setup() -> CombatTracker {
         }
         
         println!("\n🎲 Please enter initiative for each player (or press Enter to auto-roll d20+DEX):");
-        for mut character in existing_characters {
+        for character in existing_characters.iter_mut() {
             // Ensure character has complete stats before using in combat
             character.ensure_complete_stats();
             
@@ -442,17 +2006,17 @@ pub fn enhanced_initiative_setup() -> CombatTracker {
                     if input.is_empty() {
                         // Auto-roll initiative: d20 + DEX modifier
                         match crate::dice::roll_dice_with_crits("1d20") {
-                            Ok((rolls, base_roll, crit_message)) => {
-                                let initiative = base_roll as i32 + dex_mod as i32;
-                                let mut message = format!("🎲 Rolled {} (d20: {}, DEX: {}) = {}", 
-                                        initiative, rolls[0], dex_mod_str, initiative);
+                            Ok(roll) => {
+                                let roll = roll.plus(dex_mod as i32);
+                                let initiative = roll.total;
+                                let mut message = format!("🎲 Rolled initiative: {}", roll);
                                 
-                                if let Some(crit) = crit_message {
+                                if let Some(crit) = roll.crit {
                                     message.push_str(&format!("\n{}", crit));
                                 }
                                 println!("{}", message);
                                 
-                                let combatant = Combatant::from_character(character.clone(), initiative);
+                                let combatant = Combatant::from_character(character, initiative);
                                 tracker.add_combatant(combatant);
                                 println!("✅ Added {} with initiative {}", character.name, initiative);
                                 break;
@@ -464,7 +2028,7 @@ pub fn enhanced_initiative_setup() -> CombatTracker {
                         }
                     } else if let Ok(initiative) = input.parse::<i32>() {
                         if initiative > 0 {
-                            let combatant = Combatant::from_character(character.clone(), initiative);
+                            let combatant = Combatant::from_character(character, initiative);
                             tracker.add_combatant(combatant);
                             println!("✅ Added {} with initiative {}", character.name, initiative);
                         } else {
@@ -488,7 +2052,9 @@ pub fn enhanced_initiative_setup() -> CombatTracker {
         if io::stdin().read_line(&mut buffer).is_ok() {
             match buffer.trim().to_lowercase().as_str() {
                 "y" | "yes" => {
-                    add_manual_combatant(&mut tracker);
+                    if let Err(e) = add_manual_combatant(&mut tracker) {
+                        println!("❌ Could not add combatant: {}", e);
+                    }
                 }
                 "n" | "no" => break,
                 _ => println!("Please enter 'y' or 'n'"),
@@ -499,35 +2065,32 @@ pub fn enhanced_initiative_setup() -> CombatTracker {
     tracker
 }
 
-fn add_manual_combatant(tracker: &mut CombatTracker) {
+fn read_prompt(prompt: &str) -> Result<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut buffer = String::new();
+    io::stdin().read_line(&mut buffer)?;
+    Ok(buffer.trim().to_string())
+}
+
+fn add_manual_combatant(tracker: &mut CombatTracker) -> Result<()> {
     println!("\n📝 Adding new combatant:");
     
-    print!("Name: ");
-    io::stdout().flush().unwrap();
-    let mut name = String::new();
-    io::stdin().read_line(&mut name).expect("Failed to read name");
-    let name = name.trim().to_string();
-    
-    print!("HP: ");
-    io::stdout().flush().unwrap();
-    let mut hp_input = String::new();
-    io::stdin().read_line(&mut hp_input).expect("Failed to read HP");
-    let hp = hp_input.trim().parse::<i32>().unwrap_or(10);
-    
-    print!("AC: ");
-    io::stdout().flush().unwrap();
-    let mut ac_input = String::new();
-    io::stdin().read_line(&mut ac_input).expect("Failed to read AC");
-    let ac = ac_input.trim().parse::<i32>().unwrap_or(10);
+    let name = read_prompt("Name: ")?;
+    let hp = stat_or_default(&read_prompt("HP: ")?, "max_hp", 10) as i32;
+    let ac = stat_or_default(&read_prompt("AC: ")?, "ac", 10) as i32;
+    let initiative = read_prompt("Initiative: ")?.parse::<i32>().unwrap_or(0);
+    let cr = read_prompt("CR (blank for none): ")?;
     
-    print!("Initiative: ");
-    io::stdout().flush().unwrap();
-    let mut init_input = String::new();
-    io::stdin().read_line(&mut init_input).expect("Failed to read initiative");
-    let initiative = init_input.trim().parse::<i32>().unwrap_or(0);
-    
-    let combatant = Combatant::new_npc(name.clone(), hp, ac, initiative);
+    let mut combatant = Combatant::new_npc(name.clone(), hp, ac, initiative);
+    if crate::xp::xp_for_cr(&cr).is_some() {
+        combatant.cr = Some(cr);
+    }
     tracker.add_combatant(combatant);
     
     println!("✅ Added {} to combat tracker!", name);
+    if let Some(reminder) = crate::temperament::join_reminder(std::path::Path::new(crate::file_manager::NPCS_DIR), &name) {
+        println!("{}", reminder);
+    }
+    Ok(())
 }
\ No newline at end of file
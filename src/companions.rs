@@ -0,0 +1,95 @@
+use crate::character::Character;
+use crate::combat::Combatant;
+use crate::error_handling::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompanionKind {
+    Familiar,
+    /// A ranger's animal companion
+    Beast,
+}
+
+impl CompanionKind {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "familiar" => Some(CompanionKind::Familiar),
+            "beast" | "companion" => Some(CompanionKind::Beast),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CompanionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompanionKind::Familiar => write!(f, "familiar"),
+            CompanionKind::Beast => write!(f, "beast companion"),
+        }
+    }
+}
+
+/// A small sheet kept on the character it serves
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Companion {
+    pub name: String,
+    pub kind: CompanionKind,
+    /// What it is: owl, wolf, imp...
+    #[serde(default)]
+    pub creature: Option<String>,
+    pub hp: i32,
+    pub max_hp: i32,
+    pub ac: i32,
+    #[serde(default)]
+    pub attacks: Vec<String>,
+}
+
+impl Companion {
+    pub fn new(name: &str, kind: CompanionKind, hp: i32, ac: i32) -> Self {
+        Companion { name: name.to_string(), kind, creature: None, hp, max_hp: hp, ac, attacks: Vec::new() }
+    }
+
+    pub fn summary(&self) -> String {
+        let creature = self.creature.as_deref().map(|c| format!(" {}", c)).unwrap_or_default();
+        format!("{} ({}{}, HP {}/{}, AC {})", self.name, self.kind, creature, self.hp, self.max_hp, self.ac)
+    }
+
+    /// Lines to show under the owner, attacks indented beneath
+    pub fn describe(&self) -> Vec<String> {
+        std::iter::once(format!("  🐾 {}", self.summary()))
+            .chain(self.attacks.iter().map(|a| format!("      ⚔️ {}", a)))
+            .collect()
+    }
+
+    /// Joins the fight on its master's side, linked back to them
+    pub fn to_combatant(&self, owner: &str, initiative: i32) -> Combatant {
+        let mut combatant = Combatant::new_npc(self.name.clone(), self.max_hp, self.ac, initiative);
+        combatant.current_hp = self.hp;
+        combatant.is_player = true;
+        combatant.owner = Some(owner.to_string());
+        combatant
+    }
+}
+
+pub fn find_companion_mut<'a>(character: &'a mut Character, name: &str) -> Result<&'a mut Companion> {
+    let owner = character.name.clone();
+    character.companions.iter_mut()
+        .find(|c| c.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| AppError::NotFound(format!("{}'s companion '{}'", owner, name)))
+}
+
+pub fn add_companion(character: &mut Character, companion: Companion) -> Result<()> {
+    if character.companions.iter().any(|c| c.name.eq_ignore_ascii_case(&companion.name)) {
+        return Err(AppError::ValidationError(format!("{} already has a companion named {}", character.name, companion.name)));
+    }
+    character.companions.push(companion);
+    Ok(())
+}
+
+/// The character a companion belongs to, and the companion
+pub fn find_owner<'a>(characters: &'a [Character], companion: &str) -> Option<(&'a Character, &'a Companion)> {
+    characters.iter().find_map(|owner| owner.companions.iter()
+        .find(|c| c.name.eq_ignore_ascii_case(companion))
+        .map(|c| (owner, c)))
+}
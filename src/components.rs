@@ -0,0 +1,96 @@
+use crate::character::Character;
+use crate::error_handling::{AppError, Result};
+use regex::Regex;
+
+/// A material component with a gold cost, which a focus or component pouch can't replace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CostlyComponent {
+    pub spell: &'static str,
+    /// Matched case-insensitively against inventory entries
+    pub item: &'static str,
+    pub description: &'static str,
+    pub cost_gp: u32,
+    pub consumed: bool,
+}
+
+const fn component(spell: &'static str, item: &'static str, description: &'static str, cost_gp: u32, consumed: bool) -> CostlyComponent {
+    CostlyComponent { spell, item, description, cost_gp, consumed }
+}
+
+pub const COSTLY_COMPONENTS: &[CostlyComponent] = &[
+    component("Arcane Lock", "gold dust", "gold dust worth at least 25 gp", 25, true),
+    component("Astral Projection", "jacinth", "a jacinth worth at least 1,000 gp per creature", 1000, true),
+    component("Chromatic Orb", "diamond", "a diamond worth at least 50 gp", 50, false),
+    component("Clone", "diamond", "a diamond worth at least 1,000 gp", 1000, true),
+    component("Continual Flame", "ruby dust", "ruby dust worth 50 gp", 50, true),
+    component("Forbiddance", "ruby dust", "ruby dust worth at least 1,000 gp", 1000, true),
+    component("Gate", "diamond", "a diamond worth at least 5,000 gp", 5000, false),
+    component("Glyph of Warding", "diamond dust", "powdered diamond worth at least 200 gp", 200, true),
+    component("Greater Restoration", "diamond dust", "diamond dust worth at least 100 gp", 100, true),
+    component("Heroes' Feast", "bowl", "a gem-encrusted bowl worth at least 1,000 gp", 1000, true),
+    component("Identify", "pearl", "a pearl worth at least 100 gp", 100, false),
+    component("Legend Lore", "incense", "incense worth at least 250 gp", 250, true),
+    component("Magic Circle", "powdered silver", "powdered silver and iron worth at least 100 gp", 100, true),
+    component("Magic Jar", "gem", "a gem, crystal, or reliquary worth at least 500 gp", 500, false),
+    component("Nondetection", "diamond dust", "diamond dust worth 25 gp", 25, true),
+    component("Planar Binding", "jewel", "a jewel worth at least 1,000 gp", 1000, true),
+    component("Raise Dead", "diamond", "a diamond worth at least 500 gp", 500, true),
+    component("Resurrection", "diamond", "a diamond worth at least 1,000 gp", 1000, true),
+    component("Revivify", "diamond", "a diamond worth at least 300 gp", 300, true),
+    component("Scrying", "focus", "a focus worth at least 1,000 gp, such as a crystal ball", 1000, false),
+    component("Sequester", "gem dust", "diamond, emerald, ruby, and sapphire dust worth at least 5,000 gp", 5000, true),
+    component("Simulacrum", "ruby dust", "powdered ruby worth 1,500 gp", 1500, true),
+    component("Stoneskin", "diamond dust", "diamond dust worth 100 gp", 100, true),
+    component("True Resurrection", "diamond", "diamonds worth at least 25,000 gp", 25000, true),
+    component("Warding Bond", "platinum ring", "a pair of platinum rings worth at least 50 gp each", 50, false),
+];
+
+pub fn costly_component(spell: &str) -> Option<&'static CostlyComponent> {
+    COSTLY_COMPONENTS.iter().find(|c| c.spell.eq_ignore_ascii_case(spell.trim()))
+}
+
+/// Read a value like "(300 gp)" or "1,000gp" out of an inventory entry
+pub fn item_value_gp(entry: &str) -> Option<u32> {
+    let value = Regex::new(r"(\d[\d,]*)\s*gp").expect("valid gp regex");
+    value.captures(&entry.to_lowercase())
+        .and_then(|caps| caps[1].replace(',', "").parse().ok())
+}
+
+/// The cheapest inventory entry that satisfies the component, so pricier gems are kept
+pub fn find_component(inventory: &[String], component: &CostlyComponent) -> Option<usize> {
+    inventory.iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.to_lowercase().contains(component.item))
+        .filter_map(|(i, entry)| item_value_gp(entry).map(|value| (i, value)))
+        .filter(|(_, value)| *value >= component.cost_gp)
+        .min_by_key(|(_, value)| *value)
+        .map(|(i, _)| i)
+}
+
+/// Check a spell's costly component against the caster's inventory and use it up
+/// if the spell consumes it. Errors describe what is missing.
+pub fn cast_spell(caster: &mut Character, spell: &str) -> Result<String> {
+    let Some(component) = costly_component(spell) else {
+        return Ok(format!("✨ {} casts {}", caster.name, spell));
+    };
+    let Some(index) = find_component(&caster.inventory, component) else {
+        let near_misses: Vec<&String> = caster.inventory.iter()
+            .filter(|entry| entry.to_lowercase().contains(component.item))
+            .collect();
+        let hint = if near_misses.is_empty() {
+            String::new()
+        } else {
+            format!(" (has {}, which isn't enough or has no gp value noted)",
+                near_misses.iter().map(|e| e.as_str()).collect::<Vec<_>>().join(", "))
+        };
+        return Err(AppError::ValidationError(format!(
+            "{} needs {}, and {} doesn't have it{}", component.spell, component.description, caster.name, hint)));
+    };
+
+    if component.consumed {
+        let used = caster.inventory.remove(index);
+        Ok(format!("✨ {} casts {}, consuming {}", caster.name, component.spell, used))
+    } else {
+        Ok(format!("✨ {} casts {} using {}", caster.name, component.spell, caster.inventory[index]))
+    }
+}
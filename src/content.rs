@@ -0,0 +1,399 @@
+use crate::error_handling::{AppError, Result};
+use crate::search::WikiPageContent;
+use regex::Regex;
+
+/// Where a piece of reference content came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Wikidot { url: String },
+    /// Built into the tool from the SRD 5.1
+    Srd,
+    /// The campaign's own files, e.g. campaign/monsters.toml
+    Campaign,
+}
+
+impl Source {
+    pub fn license(&self) -> License {
+        match self {
+            Source::Wikidot { .. } => License::CcBySa30,
+            Source::Srd => License::CcBy40,
+            Source::Campaign => License::Homebrew,
+        }
+    }
+
+    /// One line of credit to show beside an entry
+    pub fn attribution(&self) -> String {
+        match self {
+            Source::Wikidot { url } => format!("From {} · {}", url, self.license().name()),
+            Source::Srd => format!("SRD 5.1 by Wizards of the Coast · {}", self.license().name()),
+            Source::Campaign => "Homebrew from the campaign files".to_string(),
+        }
+    }
+}
+
+/// The terms content is shared under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum License {
+    /// dnd5e.wikidot.com pages
+    CcBySa30,
+    /// The SRD 5.1
+    CcBy40,
+    /// The table's own material; whoever wrote it decides
+    Homebrew,
+}
+
+impl License {
+    pub fn name(&self) -> &'static str {
+        match self {
+            License::CcBySa30 => "CC BY-SA 3.0",
+            License::CcBy40 => "CC BY 4.0",
+            License::Homebrew => "Homebrew",
+        }
+    }
+
+    /// The notice an export has to carry when it includes content under this license
+    pub fn notice(&self) -> &'static str {
+        match self {
+            License::CcBySa30 => "Includes content from dnd5e.wikidot.com, licensed under the Creative Commons \
+                Attribution-ShareAlike 3.0 License (https://creativecommons.org/licenses/by-sa/3.0/). \
+                Changes were made: pages were converted to plain text. Share adaptations under the same license.",
+            License::CcBy40 => "This work includes material taken from the System Reference Document 5.1 (\"SRD 5.1\") \
+                by Wizards of the Coast LLC and available at https://dnd.wizards.com/resources/systems-reference-document. \
+                The SRD 5.1 is licensed under the Creative Commons Attribution 4.0 International License \
+                available at https://creativecommons.org/licenses/by/4.0/legalcode.",
+            License::Homebrew => "Includes homebrew material written for this campaign by its players and DM.",
+        }
+    }
+}
+
+/// The notices for an export that embeds content from these sources, one per license
+pub fn license_notice(sources: &[Source]) -> String {
+    let mut licenses: Vec<License> = sources.iter().map(Source::license).collect();
+    licenses.sort();
+    licenses.dedup();
+    licenses.iter()
+        .map(|license| format!("{}\n{}\n", license.name(), license.notice()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A spell, whichever backend described it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spell {
+    pub name: String,
+    /// 0 for cantrips
+    pub level: u8,
+    pub school: String,
+    pub casting_time: String,
+    pub range: String,
+    pub components: String,
+    pub duration: String,
+    pub description: String,
+    pub higher_levels: Option<String>,
+    pub classes: Vec<String>,
+    pub source: Source,
+}
+
+/// A creature's stat block, whichever backend described it. Fields a backend
+/// doesn't know are left empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Monster {
+    pub name: String,
+    pub size: Option<String>,
+    /// Creature type: beast, humanoid, dragon...
+    pub kind: String,
+    pub alignment: Option<String>,
+    pub ac: Option<i32>,
+    pub hp: Option<i32>,
+    pub hit_dice: Option<String>,
+    pub speed: Option<String>,
+    pub cr: Option<String>,
+    pub swim: bool,
+    pub fly: bool,
+    pub actions: Vec<String>,
+    pub source: Source,
+}
+
+/// A piece of equipment or a valuable, whichever backend described it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub name: String,
+    pub category: String,
+    pub cost_gp: Option<u32>,
+    pub weight_lb: Option<f32>,
+    pub description: String,
+    pub source: Source,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Content {
+    Spell(Spell),
+    Monster(Monster),
+    Item(Item),
+}
+
+impl Content {
+    /// Normalize a fetched or cached wiki page; classes and races have no schema yet
+    pub fn from_page(page: &WikiPageContent) -> Result<Self> {
+        match page.content_type.as_str() {
+            "spell" => Ok(Content::Spell(Spell::from_page(page)?)),
+            "monster" => Ok(Content::Monster(Monster::from_page(page))),
+            "equipment" => Ok(Content::Item(Item::from_page(page))),
+            other => Err(AppError::ValidationError(format!("No normalized form for {} pages", other))),
+        }
+    }
+
+    pub fn source(&self) -> &Source {
+        match self {
+            Content::Spell(spell) => &spell.source,
+            Content::Monster(monster) => &monster.source,
+            Content::Item(item) => &item.source,
+        }
+    }
+
+    /// The few lines worth showing above a page's full text
+    pub fn card_lines(&self) -> Vec<String> {
+        match self {
+            Content::Spell(spell) => spell.card_lines(),
+            Content::Monster(monster) => monster.card_lines(),
+            Content::Item(item) => item.card_lines(),
+        }
+    }
+}
+
+/// The value of a `Label: value` or table-cell `Label | value` line. The label may
+/// follow an emphasis fragment the parser left on the same line.
+fn field(content: &str, label: &str) -> Option<String> {
+    let pattern = Regex::new(&format!(r"(?m)(?:^|\*)\s*{}:?\s*\|?\s*([^|\n]+)", regex::escape(label))).expect("valid field regex");
+    pattern.captures(content)
+        .map(|caps| caps[1].trim().trim_end_matches(|c: char| c == '*' || c.is_whitespace()).to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Like `field`, for stat blocks where the parser ran several cells into one line
+fn inline_field(content: &str, label: &str) -> Option<String> {
+    let pattern = Regex::new(&format!(r"(?:^|[\s*|]){}:?\s*\|?\s*([^|\n]+)", regex::escape(label))).expect("valid field regex");
+    pattern.captures(content)
+        .map(|caps| caps[1].trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Wiki text repeats inline emphasis as `*...*`/`**...**` fragments at the start of
+/// the following line; they add nothing, so strip them and drop lines left empty
+fn without_markup_echo(line: &str) -> Option<&str> {
+    let echo = Regex::new(r"^(?:\*{1,2}[^*]*\*{1,2})+").expect("valid emphasis regex");
+    let rest = line.get(echo.find(line).map_or(0, |m| m.end())..)?.trim();
+    (!rest.is_empty() && !rest.starts_with("Source:")).then_some(rest)
+}
+
+fn ordinal_level(text: &str) -> Option<(u8, String)> {
+    let leveled = Regex::new(r"(?i)(\d)(?:st|nd|rd|th)-level (\w+)").expect("valid level regex");
+    let cantrip = Regex::new(r"(?i)(\w+) cantrip").expect("valid cantrip regex");
+    leveled.captures(text)
+        .and_then(|caps| Some((caps[1].parse().ok()?, caps[2].to_lowercase())))
+        .or_else(|| cantrip.captures(text).map(|caps| (0, caps[1].to_lowercase())))
+}
+
+impl Spell {
+    pub fn from_page(page: &WikiPageContent) -> Result<Self> {
+        let content = &page.content;
+        let (level, school) = ordinal_level(content)
+            .ok_or_else(|| AppError::ParseError(format!("No spell level or school on the page for {}", page.name)))?;
+        let labelled = ["Casting Time", "Range", "Components", "Duration", "At Higher Levels", "Spell Lists"];
+        let description = content.lines()
+            .filter_map(|line| without_markup_echo(line.trim()))
+            .filter(|line| !labelled.iter().any(|label| line.starts_with(label)) && ordinal_level(line).is_none())
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(Spell {
+            name: page.name.clone(),
+            level,
+            school,
+            casting_time: field(content, "Casting Time").unwrap_or_default(),
+            range: field(content, "Range").unwrap_or_default(),
+            components: field(content, "Components").unwrap_or_default(),
+            duration: field(content, "Duration").unwrap_or_default(),
+            description,
+            higher_levels: field(content, "At Higher Levels.").or_else(|| field(content, "At Higher Levels")),
+            classes: field(content, "Spell Lists.").or_else(|| field(content, "Spell Lists"))
+                .map(|list| list.split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect())
+                .unwrap_or_default(),
+            source: page.source(),
+        })
+    }
+
+    /// "3rd-level evocation" or "Evocation cantrip"
+    pub fn level_line(&self) -> String {
+        match self.level {
+            0 => format!("{}{} cantrip", self.school[..1].to_uppercase(), &self.school[1..]),
+            1 => format!("1st-level {}", self.school),
+            2 => format!("2nd-level {}", self.school),
+            3 => format!("3rd-level {}", self.school),
+            n => format!("{}th-level {}", n, self.school),
+        }
+    }
+
+    pub fn card_lines(&self) -> Vec<String> {
+        let mut lines = vec![self.level_line()];
+        let details: Vec<String> = [("Cast", &self.casting_time), ("Range", &self.range), ("Duration", &self.duration)].iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(label, value)| format!("{} {}", label, value))
+            .collect();
+        if !details.is_empty() {
+            lines.push(details.join(" · "));
+        }
+        if !self.components.is_empty() {
+            lines.push(format!("Components {}", self.components));
+        }
+        lines
+    }
+}
+
+impl Monster {
+    /// Wiki monster pages vary a lot, so anything that can't be found is left empty
+    pub fn from_page(page: &WikiPageContent) -> Self {
+        let content = &page.content;
+        let type_line = Regex::new(r"(?m)^\*?(Tiny|Small|Medium|Large|Huge|Gargantuan) ([\w ]+?)(?: \([^)]*\))?, ([\w ]+?)\*?$")
+            .expect("valid type regex");
+        let (size, kind, alignment) = type_line.captures(content)
+            .map(|caps| (Some(caps[1].to_string()), caps[2].to_lowercase(), Some(caps[3].to_string())))
+            .unwrap_or((None, String::new(), None));
+        let number = |text: Option<String>| text.and_then(|t| t.split_whitespace().next().and_then(|n| n.parse().ok()));
+        let hit_points = inline_field(content, "Hit Points");
+        let speed = inline_field(content, "Speed").map(|s| s.split(" Armor").next().unwrap_or(&s).trim().to_string());
+        let attack = Regex::new(r"(?m)^(?:• )?([A-Z][\w' ()/-]*?)\.\s+((?:Melee|Ranged) (?:Weapon|Spell) Attack:[^•\n]*)").expect("valid attack regex");
+        let mut actions: Vec<String> = Vec::new();
+        for caps in attack.captures_iter(content) {
+            let action = format!("{}. {}", caps[1].trim(), caps[2].trim());
+            if !actions.contains(&action) {
+                actions.push(action);
+            }
+        }
+        Monster {
+            name: page.name.clone(),
+            size,
+            kind,
+            alignment,
+            ac: number(inline_field(content, "Armor Class")),
+            hp: number(hit_points.clone()),
+            hit_dice: hit_points.and_then(|hp| Some(hp.split_once('(')?.1.split_once(')')?.0.to_string())),
+            swim: speed.as_deref().is_some_and(|s| s.contains("swim")),
+            fly: speed.as_deref().is_some_and(|s| s.contains("fly")),
+            speed,
+            cr: inline_field(content, "Challenge").and_then(|c| c.split_whitespace().next().map(str::to_string)),
+            actions,
+            source: page.source(),
+        }
+    }
+
+    pub fn card_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let kind_line: Vec<&str> = [self.size.as_deref(), Some(self.kind.as_str()).filter(|k| !k.is_empty()), self.alignment.as_deref()]
+            .into_iter().flatten().collect();
+        if !kind_line.is_empty() {
+            lines.push(kind_line.join(" "));
+        }
+        let stats: Vec<String> = [
+            self.ac.map(|ac| format!("AC {}", ac)),
+            self.hp.map(|hp| match &self.hit_dice {
+                Some(dice) => format!("HP {} ({})", hp, dice),
+                None => format!("HP {}", hp),
+            }),
+            self.speed.as_ref().map(|s| format!("Speed {}", s)),
+            self.cr.as_ref().map(|cr| format!("CR {}", cr)),
+        ].into_iter().flatten().collect();
+        if !stats.is_empty() {
+            lines.push(stats.join(" · "));
+        }
+        lines.extend(self.actions.iter().cloned());
+        lines
+    }
+}
+
+impl From<&crate::monsters::MonsterEntry> for Monster {
+    fn from(entry: &crate::monsters::MonsterEntry) -> Self {
+        Monster {
+            name: entry.name.clone(),
+            size: None,
+            kind: entry.kind.clone(),
+            alignment: None,
+            ac: None,
+            hp: None,
+            hit_dice: None,
+            speed: None,
+            cr: Some(entry.cr.clone()),
+            swim: entry.swim,
+            fly: entry.fly,
+            actions: Vec::new(),
+            source: if crate::monsters::is_srd_beast(&entry.name) { Source::Srd } else { Source::Campaign },
+        }
+    }
+}
+
+impl From<&crate::npc_presets::NpcPreset> for Monster {
+    fn from(preset: &crate::npc_presets::NpcPreset) -> Self {
+        Monster {
+            name: preset.role.to_string(),
+            size: Some("Medium".to_string()),
+            kind: "humanoid".to_string(),
+            alignment: None,
+            ac: Some(preset.ac as i32),
+            hp: Some(preset.hp as i32),
+            hit_dice: Some(preset.hit_dice.to_string()),
+            speed: Some(format!("{} ft.", preset.speed)),
+            cr: Some(preset.cr.to_string()),
+            swim: false,
+            fly: false,
+            actions: preset.attacks.iter().map(|a| a.to_string()).collect(),
+            source: Source::Srd,
+        }
+    }
+}
+
+impl Item {
+    pub fn from_page(page: &WikiPageContent) -> Self {
+        let content = &page.content;
+        let cost = Regex::new(r"(?i)cost:?\s*\|?\s*([\d,]+)\s*gp").expect("valid cost regex");
+        let weight = Regex::new(r"(?i)weight:?\s*\|?\s*([\d.]+)\s*lb").expect("valid weight regex");
+        Item {
+            name: page.name.clone(),
+            category: page.content_type.clone(),
+            cost_gp: cost.captures(content).and_then(|caps| caps[1].replace(',', "").parse().ok()),
+            weight_lb: weight.captures(content).and_then(|caps| caps[1].parse().ok()),
+            description: content.lines()
+                .filter_map(|line| without_markup_echo(line.trim()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            source: page.source(),
+        }
+    }
+
+    pub fn card_lines(&self) -> Vec<String> {
+        let details: Vec<String> = [
+            Some(self.category.clone()),
+            self.cost_gp.map(|gp| format!("{} gp", gp)),
+            self.weight_lb.map(|lb| format!("{} lb", lb)),
+        ].into_iter().flatten().collect();
+        vec![details.join(" · ")]
+    }
+}
+
+impl From<&crate::components::CostlyComponent> for Item {
+    fn from(component: &crate::components::CostlyComponent) -> Self {
+        Item {
+            name: component.item.to_string(),
+            category: "material component".to_string(),
+            cost_gp: Some(component.cost_gp),
+            weight_lb: None,
+            description: format!("{} for {}{}", component.description, component.spell,
+                if component.consumed { ", consumed by the spell" } else { "" }),
+            source: Source::Srd,
+        }
+    }
+}
+
+/// A spell's normalized details from the search cache, without going online
+pub fn cached_spell(name: &str) -> Option<Spell> {
+    let page = crate::search::read_cached_page(std::path::Path::new(crate::search::CACHE_DIR), "spell", name)?;
+    Spell::from_page(&page).ok()
+}
@@ -0,0 +1,141 @@
+use crate::character::Character;
+use crate::error_handling::{AppError, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Crafting projects and the downtime day count survive between sessions here
+pub const DOWNTIME_FILE: &str = "campaign/downtime.ron";
+/// Crafting progresses at 10 gp of item value per downtime day (50 gp per workweek)
+pub const GP_PER_CRAFTING_DAY: u32 = 10;
+
+/// (material, harvest DC) by creature type. One check is compared against every DC.
+const HARVEST_TABLES: &[(&str, &[(&str, i32)])] = &[
+    ("aberration", &[("Aberrant ichor", 12), ("Eye stalk", 15), ("Psionic gland", 20)]),
+    ("beast", &[("Hide", 10), ("Sinew", 10), ("Fangs", 12), ("Venom sac", 15)]),
+    ("celestial", &[("Radiant feather", 15), ("Holy tears", 18), ("Halo shard", 22)]),
+    ("construct", &[("Scrap metal", 10), ("Arcane gear", 14), ("Animating core", 19)]),
+    ("dragon", &[("Dragon scale", 13), ("Dragon blood", 15), ("Dragon tooth", 15), ("Breath gland", 20)]),
+    ("elemental", &[("Elemental mote", 14), ("Primordial essence", 19)]),
+    ("fey", &[("Fey dust", 12), ("Glamour-thread hair", 15), ("Dream pearl", 19)]),
+    ("fiend", &[("Brimstone horn", 13), ("Infernal ichor", 16), ("Fiendish heart", 20)]),
+    ("giant", &[("Giant's toenail", 10), ("Giant bone", 13), ("Giant's blood", 16)]),
+    ("humanoid", &[("Lock of hair", 10)]),
+    ("monstrosity", &[("Tough hide", 12), ("Claw", 12), ("Monstrous gland", 16)]),
+    ("ooze", &[("Acidic residue", 12), ("Ooze membrane", 15)]),
+    ("plant", &[("Bark", 8), ("Rare spores", 13), ("Heartwood", 17)]),
+    ("undead", &[("Grave dust", 10), ("Ectoplasm", 14), ("Necrotic marrow", 18)]),
+];
+
+pub fn creature_types() -> Vec<&'static str> {
+    HARVEST_TABLES.iter().map(|(kind, _)| *kind).collect()
+}
+
+pub fn harvest_table(creature_type: &str) -> Option<&'static [(&'static str, i32)]> {
+    HARVEST_TABLES.iter()
+        .find(|(kind, _)| kind.eq_ignore_ascii_case(creature_type))
+        .map(|(_, table)| *table)
+}
+
+/// Which materials a harvesting check recovers: everything whose DC the roll meets
+pub fn harvest(creature_type: &str, roll: i32) -> Result<Vec<(&'static str, i32, bool)>> {
+    let table = harvest_table(creature_type).ok_or_else(|| AppError::NotFound(format!(
+        "Creature type '{}' (known: {})", creature_type, creature_types().join(", "))))?;
+    Ok(table.iter().map(|&(material, dc)| (material, dc, roll >= dc)).collect())
+}
+
+/// An item being made over downtime
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CraftingProject {
+    pub crafter: String,
+    pub item: String,
+    pub days_required: u32,
+    pub days_spent: u32,
+}
+
+impl CraftingProject {
+    pub fn is_complete(&self) -> bool {
+        self.days_spent >= self.days_required
+    }
+
+    pub fn progress(&self) -> String {
+        format!("{} for {}: {}/{} days", self.item, self.crafter, self.days_spent.min(self.days_required), self.days_required)
+    }
+}
+
+/// Days needed to craft an item worth `value_gp`
+pub fn crafting_days(value_gp: u32) -> u32 {
+    value_gp.div_ceil(GP_PER_CRAFTING_DAY).max(1)
+}
+
+/// Downtime day count and crafting projects for the campaign
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CraftingLedger {
+    pub day: u32,
+    pub projects: Vec<CraftingProject>,
+}
+
+impl CraftingLedger {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        ron::de::from_str(&contents).map_err(|e| AppError::ParseError(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create campaign folder")?;
+        }
+        let serialized = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| AppError::ParseError(e.to_string()))?;
+        fs::write(path, serialized).context(format!("Failed to write {}", path.display()))
+    }
+
+    /// Start a project, taking the listed materials out of the crafter's inventory
+    pub fn start_project(&mut self, crafter: &mut Character, item: &str, days: u32, materials: &[String]) -> Result<&CraftingProject> {
+        let missing: Vec<&String> = materials.iter()
+            .filter(|m| !crafter.inventory.iter().any(|i| i.eq_ignore_ascii_case(m)))
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::ValidationError(format!("{} is missing: {}", crafter.name,
+                missing.iter().map(|m| m.as_str()).collect::<Vec<_>>().join(", "))));
+        }
+        for material in materials {
+            if let Some(index) = crafter.inventory.iter().position(|i| i.eq_ignore_ascii_case(material)) {
+                crafter.inventory.remove(index);
+            }
+        }
+        self.projects.push(CraftingProject {
+            crafter: crafter.name.clone(),
+            item: item.to_string(),
+            days_required: days.max(1),
+            days_spent: 0,
+        });
+        Ok(self.projects.last().expect("project was just pushed"))
+    }
+
+    /// Spend downtime days on every project. Finished items go into their crafter's
+    /// inventory and are returned.
+    pub fn pass_days(&mut self, days: u32, characters: &mut [Character]) -> Vec<CraftingProject> {
+        self.day += days;
+        for project in &mut self.projects {
+            project.days_spent += days;
+        }
+        let (finished, ongoing): (Vec<_>, Vec<_>) = std::mem::take(&mut self.projects)
+            .into_iter()
+            .partition(CraftingProject::is_complete);
+        self.projects = ongoing;
+        for project in &finished {
+            if let Some(crafter) = characters.iter_mut().find(|c| c.name.eq_ignore_ascii_case(&project.crafter)) {
+                crafter.inventory.push(project.item.clone());
+            }
+        }
+        finished
+    }
+
+    /// Shown in the status bar date slot
+    pub fn date_label(&self) -> String {
+        format!("Downtime day {}", self.day)
+    }
+}
@@ -0,0 +1,137 @@
+use crate::character::AbilityScore;
+use crate::error_handling::{AppError, Result, ResultExt};
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+/// Overrides for the difficulty bands, e.g. `hard = 18`
+pub const DC_FILE: &str = "campaign/dc.toml";
+
+/// The DC behind each difficulty name; the defaults are the Player's Handbook's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct DcBands {
+    pub very_easy: i32,
+    pub easy: i32,
+    pub medium: i32,
+    pub hard: i32,
+    pub very_hard: i32,
+    pub nearly_impossible: i32,
+}
+
+impl Default for DcBands {
+    fn default() -> Self {
+        DcBands { very_easy: 5, easy: 10, medium: 15, hard: 20, very_hard: 25, nearly_impossible: 30 }
+    }
+}
+
+/// Every skill and the ability it's rolled with
+const SKILLS: &[(&str, AbilityScore)] = &[
+    ("Acrobatics", AbilityScore::Dexterity),
+    ("Animal Handling", AbilityScore::Wisdom),
+    ("Arcana", AbilityScore::Intelligence),
+    ("Athletics", AbilityScore::Strength),
+    ("Deception", AbilityScore::Charisma),
+    ("History", AbilityScore::Intelligence),
+    ("Insight", AbilityScore::Wisdom),
+    ("Intimidation", AbilityScore::Charisma),
+    ("Investigation", AbilityScore::Intelligence),
+    ("Medicine", AbilityScore::Wisdom),
+    ("Nature", AbilityScore::Intelligence),
+    ("Perception", AbilityScore::Wisdom),
+    ("Performance", AbilityScore::Charisma),
+    ("Persuasion", AbilityScore::Charisma),
+    ("Religion", AbilityScore::Intelligence),
+    ("Sleight of Hand", AbilityScore::Dexterity),
+    ("Stealth", AbilityScore::Dexterity),
+    ("Survival", AbilityScore::Wisdom),
+];
+
+fn find_skill(name: &str) -> Option<(&'static str, AbilityScore)> {
+    let query = name.trim().to_lowercase().replace(['-', '_'], " ");
+    SKILLS.iter()
+        .find(|(skill, _)| skill.to_lowercase() == query || skill.to_lowercase().replace(' ', "") == query)
+        .copied()
+}
+
+/// A skill's usual spelling, from any case or "sleight-of-hand" style
+pub fn skill_name(name: &str) -> Option<&'static str> {
+    find_skill(name).map(|(skill, _)| skill)
+}
+
+/// A skill, or a plain ability check like `str` or `wisdom`, with the ability it uses
+pub fn check_ability(name: &str) -> Option<(&'static str, AbilityScore)> {
+    let query = name.trim();
+    find_skill(query)
+        .or_else(|| AbilityScore::all().into_iter()
+            .find(|a| a.name().eq_ignore_ascii_case(query) || a.short_name().eq_ignore_ascii_case(query))
+            .map(|a| (a.name(), a)))
+}
+
+impl DcBands {
+    pub fn bands(&self) -> [(&'static str, i32); 6] {
+        [
+            ("very easy", self.very_easy),
+            ("easy", self.easy),
+            ("medium", self.medium),
+            ("hard", self.hard),
+            ("very hard", self.very_hard),
+            ("nearly impossible", self.nearly_impossible),
+        ]
+    }
+
+    /// A difficulty name: `hard`, `very-hard`, `very_hard` and `veryhard` all work
+    pub fn preset(&self, name: &str) -> Option<i32> {
+        let name = name.trim().to_lowercase().replace(['-', '_', ' '], "");
+        match name.as_str() {
+            "veryeasy" | "trivial" => Some(self.very_easy),
+            "easy" => Some(self.easy),
+            "medium" | "moderate" => Some(self.medium),
+            "hard" => Some(self.hard),
+            "veryhard" => Some(self.very_hard),
+            "nearlyimpossible" | "impossible" => Some(self.nearly_impossible),
+            _ => None,
+        }
+    }
+
+    /// A DC given as a number or a difficulty name
+    pub fn resolve(&self, dc: &str) -> Result<i32> {
+        dc.trim().parse::<i32>().ok()
+            .or_else(|| self.preset(dc))
+            .ok_or_else(|| AppError::ValidationError(format!(
+                "Unknown DC '{}'. Use a number or very-easy, easy, medium, hard, very-hard, nearly-impossible", dc)))
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        std::iter::once("🎯 Difficulty classes:".to_string())
+            .chain(self.bands().iter().map(|(name, dc)| format!("  {:<18} DC {}", name, dc)))
+            .collect()
+    }
+
+    pub fn from_toml(source: &str) -> Result<Self> {
+        let bands: DcBands = toml::from_str(source).map_err(|e| AppError::ParseError(e.to_string()))?;
+        let values = bands.bands();
+        if values.iter().any(|(_, dc)| !(1..=40).contains(dc)) || values.windows(2).any(|w| w[0].1 > w[1].1) {
+            return Err(AppError::ValidationError("DC bands must be between 1 and 40, easiest first".to_string()));
+        }
+        Ok(bands)
+    }
+
+    pub fn load_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(DcBands::default());
+        }
+        let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        Self::from_toml(&contents).context(format!("DC file {}", path.display()))
+    }
+}
+
+/// `dc <difficulty>` shows that band's number; `dc list` shows them all
+pub fn quick_command(bands: &DcBands, args: &[&str]) -> Vec<String> {
+    match args {
+        [] | ["list"] => bands.lines(),
+        _ => match bands.preset(&args.join(" ")) {
+            Some(dc) => vec![format!("🎯 {}: DC {}", args.join(" "), dc)],
+            None => vec![format!("❌ Unknown difficulty '{}'. Use very-easy, easy, medium, hard, very-hard or nearly-impossible", args.join(" "))],
+        },
+    }
+}
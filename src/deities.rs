@@ -0,0 +1,125 @@
+use crate::error_handling::{AppError, Result, ResultExt};
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+/// Homebrew gods are added (or override built-in ones by name) from this file
+pub const DEITIES_FILE: &str = "campaign/deities.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Deity {
+    pub name: String,
+    #[serde(default)]
+    pub pantheon: Option<String>,
+    pub alignment: String,
+    pub domains: Vec<String>,
+    pub symbol: String,
+    #[serde(default)]
+    pub tenets: Vec<String>,
+}
+
+impl Deity {
+    pub fn summary(&self) -> String {
+        let pantheon = self.pantheon.as_deref().map(|p| format!(" [{}]", p)).unwrap_or_default();
+        format!("{}{} ({}) - {}", self.name, pantheon, self.alignment, self.domains.join(", "))
+    }
+
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = vec![format!("🛐 {}", self.summary()), format!("  Symbol: {}", self.symbol)];
+        for tenet in &self.tenets {
+            lines.push(format!("  • {}", tenet));
+        }
+        lines
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.name.to_lowercase().contains(&query)
+            || self.symbol.to_lowercase().contains(&query)
+            || self.alignment.to_lowercase() == query
+            || self.pantheon.as_deref().is_some_and(|p| p.to_lowercase().contains(&query))
+            || self.domains.iter().any(|d| d.to_lowercase() == query)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DeityFile {
+    deities: Vec<Deity>,
+}
+
+/// (name, pantheon, alignment, domains, symbol, tenets)
+type DeityRow = (&'static str, Option<&'static str>, &'static str, &'static [&'static str], &'static str, &'static [&'static str]);
+
+/// Setting-agnostic archetypes a DM can rename, plus the classical Greek gods
+const BUILTIN: &[DeityRow] = &[
+    ("The Dawnbringer", None, "LG", &["Life", "Light"], "A rising sun", &["Bring light to dark places", "Heal the wounded, friend or foe"]),
+    ("The Forgefather", None, "LN", &["Forge", "Knowledge"], "Hammer and anvil", &["Make things that last", "Honor a craftsman's work"]),
+    ("The Green Mother", None, "N", &["Nature", "Life"], "An oak leaf", &["Take only what you need", "Protect the wild places"]),
+    ("The Reaper", None, "N", &["Death", "Grave"], "A scythe over an hourglass", &["Every life has its hour", "Let the dead rest; destroy the undead"]),
+    ("The Stormlord", None, "CN", &["Tempest"], "A lightning bolt", &["Respect the sea and sky", "Strength is proven in the storm"]),
+    ("The Trickster", None, "CN", &["Trickery"], "A grinning mask", &["No lock should go unpicked", "Humble the proud"]),
+    ("The Warden", None, "LN", &["War", "Order"], "A tower shield", &["Stand your ground", "Keep your oaths"]),
+    ("The Whisperer", None, "NE", &["Knowledge", "Trickery"], "A closed eye", &["Every secret has a price", "Knowledge is power; hoard it"]),
+    ("Zeus", Some("Greek"), "N", &["Tempest"], "Fist full of lightning bolts", &[]),
+    ("Athena", Some("Greek"), "LG", &["Knowledge", "War"], "Owl", &[]),
+    ("Apollo", Some("Greek"), "CG", &["Knowledge", "Life", "Light"], "Lyre", &[]),
+    ("Ares", Some("Greek"), "CE", &["War"], "Spear", &[]),
+    ("Artemis", Some("Greek"), "NG", &["Life", "Nature"], "Bow and arrow on lunar disk", &[]),
+    ("Hades", Some("Greek"), "LE", &["Death"], "Black ram", &[]),
+    ("Hephaestus", Some("Greek"), "NG", &["Knowledge", "Forge"], "Hammer and anvil", &[]),
+    ("Hermes", Some("Greek"), "CG", &["Trickery"], "Caduceus (winged staff and serpents)", &[]),
+    ("Poseidon", Some("Greek"), "CN", &["Tempest"], "Trident", &[]),
+];
+
+pub fn builtin_deities() -> Vec<Deity> {
+    BUILTIN.iter().map(|&(name, pantheon, alignment, domains, symbol, tenets)| Deity {
+        name: name.to_string(),
+        pantheon: pantheon.map(str::to_string),
+        alignment: alignment.to_string(),
+        domains: domains.iter().map(|d| d.to_string()).collect(),
+        symbol: symbol.to_string(),
+        tenets: tenets.iter().map(|t| t.to_string()).collect(),
+    }).collect()
+}
+
+/// Built-in deities plus any from the campaign's deities file
+#[derive(Debug, Clone)]
+pub struct Pantheon {
+    pub deities: Vec<Deity>,
+}
+
+impl Default for Pantheon {
+    fn default() -> Self {
+        Pantheon { deities: builtin_deities() }
+    }
+}
+
+impl Pantheon {
+    /// Parse `[[deities]]` entries; a deity with a built-in's name replaces it
+    pub fn add_toml(&mut self, source: &str) -> Result<usize> {
+        let file: DeityFile = toml::from_str(source).map_err(|e| AppError::ParseError(e.to_string()))?;
+        let count = file.deities.len();
+        for deity in file.deities {
+            self.deities.retain(|d| !d.name.eq_ignore_ascii_case(&deity.name));
+            self.deities.push(deity);
+        }
+        Ok(count)
+    }
+
+    pub fn load_file(&mut self, path: &Path) -> Result<usize> {
+        if !path.exists() {
+            return Ok(0);
+        }
+        let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        self.add_toml(&contents).context(format!("Deity file {}", path.display()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Deity> {
+        self.deities.iter().find(|d| d.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Deities whose name, symbol, or pantheon contains the query, or whose
+    /// domain or alignment is exactly it
+    pub fn search(&self, query: &str) -> Vec<&Deity> {
+        self.deities.iter().filter(|d| d.matches(query.trim())).collect()
+    }
+}
@@ -0,0 +1,117 @@
+use crate::error_handling::{Result, ResultExt};
+use crate::file_manager::CHARACTERS_DIR;
+use crate::logging::{LOG_DIR, LOG_FILE_NAME, MAX_ROTATED_LOGS};
+use std::{fs, io::Write, path::{Path, PathBuf}, time::{SystemTime, UNIX_EPOCH}};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+pub const DIAGNOSTICS_DIR: &str = "diagnostics";
+/// Only the tail of each log goes into the bundle
+const MAX_LOG_BYTES_IN_BUNDLE: usize = 256 * 1024;
+
+/// Environment variable names containing any of these are redacted
+const SECRET_MARKERS: &[&str] = &["TOKEN", "KEY", "SECRET", "PASSWORD", "PASS", "AUTH"];
+
+/// Replace secrets and the user's home directory so bundles are safe to post publicly
+pub fn redact_config_entry(key: &str, value: &str, home: Option<&str>) -> String {
+    let upper = key.to_uppercase();
+    if SECRET_MARKERS.iter().any(|marker| upper.contains(marker)) {
+        return format!("{}=<redacted>", key);
+    }
+    let value = match home {
+        Some(home) if !home.is_empty() => value.replace(home, "~"),
+        _ => value.to_string(),
+    };
+    format!("{}={}", key, value)
+}
+
+fn version_info() -> String {
+    format!(
+        "dnd_tools {}\nos: {}\narch: {}\nargs: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::env::args().skip(1).collect::<Vec<_>>().join(" "),
+    )
+}
+
+/// DND_TOOLS_* settings plus terminal details that affect the TUI, redacted
+fn config_info(vars: &[(String, String)], home: Option<&str>) -> String {
+    let mut lines: Vec<String> = vars.iter()
+        .filter(|(key, _)| key.starts_with("DND_TOOLS_") || key == "TERM" || key == "COLORTERM")
+        .map(|(key, value)| redact_config_entry(key, value, home))
+        .collect();
+    lines.sort();
+    if lines.is_empty() {
+        lines.push("(no dnd_tools settings in the environment)".to_string());
+    }
+    lines.join("\n") + "\n"
+}
+
+fn tail(bytes: &[u8], max: usize) -> &[u8] {
+    &bytes[bytes.len().saturating_sub(max)..]
+}
+
+/// The most recently written character sheet, standing in for the last autosave
+fn latest_save(characters_dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(characters_dir).ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// Write a diagnostics zip for the data rooted at `base` into `out_dir`
+pub fn create_bundle_in(base: &Path, out_dir: &Path, vars: &[(String, String)], home: Option<&str>) -> Result<PathBuf> {
+    fs::create_dir_all(out_dir).context("Failed to create diagnostics folder")?;
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let path = out_dir.join(format!("dnd_tools_diagnostics_{}.zip", secs));
+    let file = fs::File::create(&path).context("Failed to create diagnostics bundle")?;
+
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+    let mut add = |name: &str, contents: &[u8]| -> Result<()> {
+        zip.start_file(name, options).context("Failed to add file to bundle")?;
+        zip.write_all(contents).context("Failed to add file to bundle")
+    };
+
+    add("version.txt", version_info().as_bytes())?;
+    add("config.txt", config_info(vars, home).as_bytes())?;
+
+    let log_dir = base.join(LOG_DIR);
+    let mut log_names = vec![LOG_FILE_NAME.to_string()];
+    log_names.extend((1..=MAX_ROTATED_LOGS).map(|n| format!("{}.{}", LOG_FILE_NAME, n)));
+    for name in log_names {
+        if let Ok(bytes) = fs::read(log_dir.join(&name)) {
+            add(&format!("logs/{}", name), tail(&bytes, MAX_LOG_BYTES_IN_BUNDLE))?;
+        }
+    }
+
+    if let Some(save) = latest_save(&base.join(CHARACTERS_DIR))
+        && let Ok(bytes) = fs::read(&save) {
+        let name = save.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        add(&format!("last_save/{}", name), &bytes)?;
+    }
+
+    zip.finish().context("Failed to finish diagnostics bundle")?;
+    log::info!("Wrote diagnostics bundle {}", path.display());
+    Ok(path)
+}
+
+/// Build a bundle from the current working directory into diagnostics/
+pub fn create_bundle() -> Result<PathBuf> {
+    let vars: Vec<(String, String)> = std::env::vars().collect();
+    let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE")).ok();
+    create_bundle_in(Path::new("."), Path::new(DIAGNOSTICS_DIR), &vars, home.as_deref())
+}
+
+/// Create a bundle and tell the user where it went
+pub fn run_diagnostics_command() {
+    match create_bundle() {
+        Ok(path) => {
+            println!("📦 Diagnostics bundle written to {}", path.display());
+            println!("Attach it to your GitHub issue. It contains version info, redacted settings, recent logs, and your latest character save.");
+        }
+        Err(e) => println!("❌ Could not create diagnostics bundle: {}", e),
+    }
+}
@@ -1,40 +1,215 @@
-pub fn roll_dice(input: &str) -> Result<(Vec<u8>, u32), String> {
+use crate::combat::Edge;
+use std::fmt;
+
+/// A roll with its working shown: every die, what was kept, and the modifier
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollResult {
+    pub count: u8,
+    pub sides: u8,
+    /// Dice that count toward the total, in the order rolled
+    pub kept: Vec<u8>,
+    /// Dice thrown away by keep-highest/keep-lowest (4d6kh3, 2d20kl1)
+    pub dropped: Vec<u8>,
+    /// First rolls replaced by a reroll (2d6r1, 2d6r<2); each die is rerolled once
+    pub rerolled: Vec<u8>,
+    /// Each die showing its highest face added another die (d6!)
+    pub explodes: bool,
+    pub modifier: i32,
+    pub total: i32,
+    /// Set for a natural 1 or 20 on a single kept d20
+    pub crit: Option<String>,
+}
+
+impl RollResult {
+    /// The die that decides a d20 roll, before modifiers
+    pub fn natural(&self) -> u8 {
+        self.kept.first().copied().unwrap_or(0)
+    }
+
+    pub fn is_critical_success(&self) -> bool {
+        self.sides == 20 && self.kept.len() == 1 && self.natural() == 20
+    }
+
+    /// Add a bonus on top of the roll, like an ability modifier
+    pub fn plus(mut self, modifier: i32) -> Self {
+        self.modifier += modifier;
+        self.total += modifier;
+        self
+    }
+
+    /// Just the dice: `2d6 (4,5)`, with dropped and rerolled dice listed after the
+    /// kept ones. Exploding dice are marked: `2d6! (6!,3,2)`.
+    pub fn dice_text(&self) -> String {
+        let list = |dice: &[u8]| dice.iter().map(|&d| {
+            if self.explodes && d == self.sides { format!("{}!", d) } else { d.to_string() }
+        }).collect::<Vec<_>>().join(",");
+        let mut text = format!("{}d{}{} ({}", self.count, self.sides, if self.explodes { "!" } else { "" }, list(&self.kept));
+        if !self.dropped.is_empty() {
+            text.push_str(&format!(", dropped {}", list(&self.dropped)));
+        }
+        if !self.rerolled.is_empty() {
+            let rerolled: Vec<String> = self.rerolled.iter().map(|d| d.to_string()).collect();
+            text.push_str(&format!(", rerolled {}", rerolled.join(",")));
+        }
+        text + ")"
+    }
+}
+
+impl fmt::Display for RollResult {
+    /// `2d6 (4,5) + 3 = 12`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.dice_text())?;
+        match self.modifier {
+            0 => {}
+            m if m > 0 => write!(f, " + {}", m)?,
+            m => write!(f, " - {}", -m)?,
+        }
+        write!(f, " = {}", self.total)
+    }
+}
+
+/// Extra dice an exploding roll may add
+const MAX_EXPLOSIONS: usize = 100;
+
+/// How a term after the dice changes the roll: `!`, `r1`/`r<2` and `kh3`/`kl1`, in any order
+#[derive(Debug, Default)]
+struct DiceOptions {
+    explode: bool,
+    /// Reroll dice showing this or less, once each
+    reroll_at_most: Option<u8>,
+    /// (keep the highest?, how many)
+    keep: Option<(bool, u8)>,
+}
+
+/// The sides (`6`, `%`) and any options that follow them
+fn parse_sides(rest: &str, num: u8) -> Result<(u8, DiceOptions), String> {
+    let split = rest.find(['!', 'r', 'k']).unwrap_or(rest.len());
+    let (sides_str, mut options_str) = rest.split_at(split);
+    // "d20" is one die and "d%" is a d100
+    let sides = if sides_str == "%" { 100 } else { sides_str.parse::<u8>().map_err(|_| "Invalid number of sides")? };
+    let digits = |text: &str| text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+
+    let mut options = DiceOptions::default();
+    while let Some(option) = options_str.chars().next() {
+        options_str = &options_str[1..];
+        match option {
+            '!' => options.explode = true,
+            'r' => {
+                let (below, text) = match options_str.strip_prefix('<') {
+                    Some(text) => (true, text),
+                    None => (false, options_str),
+                };
+                let (value, rest) = text.split_at(digits(text));
+                let value = if value.is_empty() { 1 } else { value.parse::<u8>().map_err(|_| "Invalid reroll value")? };
+                // r1 rerolls ones; r<2 rerolls ones and twos
+                options.reroll_at_most = Some(if below { value.saturating_sub(1) } else { value });
+                options_str = rest;
+            }
+            'k' => {
+                let (highest, text) = match options_str.strip_prefix('l') {
+                    Some(text) => (false, text),
+                    None => (true, options_str.strip_prefix('h').unwrap_or(options_str)),
+                };
+                let (count, rest) = text.split_at(digits(text));
+                let count = if count.is_empty() { 1 } else { count.parse::<u8>().map_err(|_| "Invalid number of dice to keep")? };
+                if count == 0 || count > num {
+                    return Err(format!("Can only keep between 1 and {} dice", num));
+                }
+                options.keep = Some((highest, count));
+                options_str = rest;
+            }
+            other => return Err(format!("Unknown dice option '{}'", other)),
+        }
+    }
+    if options.explode && sides < 2 {
+        return Err("Only dice with two or more sides can explode".to_string());
+    }
+    if options.reroll_at_most.is_some_and(|at_most| at_most >= sides) {
+        return Err(format!("Rerolling that would reroll every face of a d{}", sides));
+    }
+    Ok((sides, options))
+}
+
+/// A trailing `adv` or `dis` (or the words in full), with the rest of the expression
+fn split_edge_suffix(input: &str) -> (&str, Option<Edge>) {
+    // "disadvantage" ends in "advantage", so the longer words are tried first
+    ["disadvantage", "disadv", "dis", "advantage", "adv"].iter()
+        .find_map(|word| input.strip_suffix(word).map(|rest| (rest, Edge::parse(word))))
+        .unwrap_or((input, None))
+}
+
+/// Roll an expression like `2d6+3`, `1d8-1`, `4d6kh3`, `2d20kl1`, `3d6!` (a die on its
+/// highest face adds another) or `2d6r1` (reroll ones, once). An `r` prefix is allowed,
+/// and `1d20+5 adv` (or `dis`) rolls a d20 with advantage (or disadvantage).
+pub fn roll_dice_with_crits(input: &str) -> Result<RollResult, String> {
     // Remove 'r' prefix if present
-    let input = input.strip_prefix('r').unwrap_or(input);
-    
-    let mut split = input.split('d');
-    let num_str = split.next().ok_or("Invalid dice format")?;
-    let sides_str = split.next().ok_or("Invalid dice format")?;
-    
-    let num = num_str.parse::<u8>().map_err(|_| "Invalid number of dice")?;
-    let sides = sides_str.parse::<u8>().map_err(|_| "Invalid number of sides")?;
-    
+    let input: String = input.trim().to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    let input = input.strip_prefix('r').unwrap_or(&input);
+    let (input, edge) = split_edge_suffix(input);
+
+    let (dice, modifier) = match input.find(['+', '-']) {
+        Some(at) => {
+            let modifier = input[at..].trim_start_matches('+').parse::<i32>().map_err(|_| "Invalid modifier")?;
+            (&input[..at], modifier)
+        }
+        None => (input, 0),
+    };
+    let (num_str, rest) = dice.split_once('d').ok_or("Invalid dice format")?;
+    let mut num = if num_str.is_empty() { 1 } else { num_str.parse::<u8>().map_err(|_| "Invalid number of dice")? };
+    let (sides, mut options) = parse_sides(rest, num)?;
+    if let Some(edge) = edge {
+        if num != 1 || sides != 20 || options.keep.is_some() {
+            return Err("Advantage and disadvantage apply to a single d20".to_string());
+        }
+        num = 2;
+        options.keep = Some((edge == Edge::Advantage, 1));
+    }
+
     if num == 0 || sides == 0 {
         return Err("Number of dice and sides must be greater than 0".to_string());
     }
-    
+
     if num > 100 {
         return Err("Too many dice (maximum 100)".to_string());
     }
-    
-    let mut rolls = Vec::new();
-    let mut total = 0u32;
-    
-    for _ in 0..num {
-        let roll = (rand::random::<u8>() % sides) + 1;
-        rolls.push(roll);
-        total += roll as u32;
+    let keep = options.keep;
+
+    let die = || (rand::random::<u8>() % sides) + 1;
+    let mut rerolled = Vec::new();
+    let mut rolls: Vec<u8> = (0..num).map(|_| match die() {
+        first if options.reroll_at_most.is_some_and(|at_most| first <= at_most) => {
+            rerolled.push(first);
+            die()
+        }
+        first => first,
+    }).collect();
+    if options.explode {
+        // Capped so a run of luck can't roll forever
+        let mut at = 0;
+        while at < rolls.len() && rolls.len() < num as usize + MAX_EXPLOSIONS {
+            if rolls[at] == sides {
+                rolls.push(die());
+            }
+            at += 1;
+        }
     }
-    
-    Ok((rolls, total))
-}
 
-pub fn roll_dice_with_crits(input: &str) -> Result<(Vec<u8>, u32, Option<String>), String> {
-    let (rolls, total) = roll_dice(input)?;
-    
+    let mut order: Vec<usize> = (0..rolls.len()).collect();
+    let mut dropped_at = Vec::new();
+    if let Some((highest, count)) = keep {
+        order.sort_by_key(|&i| rolls[i]);
+        if highest {
+            order.reverse();
+        }
+        dropped_at = order.split_off(count as usize);
+    }
+    let kept: Vec<u8> = (0..rolls.len()).filter(|i| !dropped_at.contains(i)).map(|i| rolls[i]).collect();
+    let dropped: Vec<u8> = dropped_at.iter().map(|&i| rolls[i]).collect();
+    let total = kept.iter().map(|&d| d as i32).sum::<i32>() + modifier;
+
     // Check for critical results on d20 rolls
-    let crit_message = if input.contains("d20") && rolls.len() == 1 {
-        match rolls[0] {
+    let crit = if sides == 20 && kept.len() == 1 {
+        match kept[0] {
             1 => Some("🎲💀 CRITICAL FAILURE! 💀🎲".to_string()),
             20 => Some("🎲⭐ CRITICAL SUCCESS! ⭐🎲".to_string()),
             _ => None,
@@ -42,13 +217,246 @@ pub fn roll_dice_with_crits(input: &str) -> Result<(Vec<u8>, u32, Option<String>
     } else {
         None
     };
-    
-    Ok((rolls, total, crit_message))
+
+    Ok(RollResult { count: num, sides, kept, dropped, rerolled, explodes: options.explode, modifier, total, crit })
+}
+
+/// Percentile dice: a tens d10 (00-90) and a ones d10 (0-9), where 00 and 0 read as 100
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PercentileRoll {
+    /// 0-9, read as 00-90
+    pub tens: u8,
+    /// 0-9
+    pub ones: u8,
+}
+
+impl PercentileRoll {
+    pub fn total(&self) -> u32 {
+        match self.tens as u32 * 10 + self.ones as u32 {
+            0 => 100,
+            total => total,
+        }
+    }
+}
+
+impl fmt::Display for PercentileRoll {
+    /// `d% (40, 7) = 47`, or `d% (00, 0) = 100`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "d% ({:02}, {}) = {}", self.tens as u32 * 10, self.ones, self.total())
+    }
+}
+
+pub fn roll_percentile() -> PercentileRoll {
+    PercentileRoll { tens: rand::random_range(0..10), ones: rand::random_range(0..10) }
+}
+
+/// d%, d100 and 1d100 are read off two d10s
+pub fn is_percentile(expression: &str) -> bool {
+    matches!(expression.trim().to_lowercase().trim_start_matches('r'), "d%" | "1d%" | "d100" | "1d100")
+}
+
+/// A roll with arithmetic around the dice, like `(1d8+4)*2` or `floor(8d6/2)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpressionRoll {
+    /// The expression with each die's result and each bracket's value filled in
+    pub breakdown: String,
+    /// Fractions round down, as in the rules
+    pub total: i32,
+    pub dice: Vec<RollResult>,
+}
+
+impl ExpressionRoll {
+    /// A natural 1 or 20 when the expression is a single d20 roll
+    pub fn crit(&self) -> Option<String> {
+        match self.dice.as_slice() {
+            [roll] => roll.crit.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn is_critical_success(&self) -> bool {
+        matches!(self.dice.as_slice(), [roll] if roll.is_critical_success())
+    }
+}
+
+impl fmt::Display for ExpressionRoll {
+    /// `(1d8 (5) + 4 = 9) * 2 = 18`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.breakdown, self.total)
+    }
+}
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.2}", value).trim_end_matches('0').to_string()
+    }
+}
+
+/// Recursive descent over `+ - * /`, brackets, `floor`/`ceil`/`round` and dice,
+/// rolling each die as it's reached
+struct ExpressionParser<'a> {
+    chars: &'a [char],
+    at: usize,
+    dice: Vec<RollResult>,
+    /// Advantage or disadvantage for the expression's d20, until it's rolled
+    edge: Option<Edge>,
+}
+
+impl ExpressionParser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.at).copied()
+    }
+
+    fn take_while(&mut self, test: impl Fn(char) -> bool) -> String {
+        let start = self.at;
+        while self.peek().is_some_and(&test) {
+            self.at += 1;
+        }
+        self.chars[start..self.at].iter().collect()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.peek() == Some(c) {
+            self.at += 1;
+            Ok(())
+        } else {
+            Err(format!("Expected '{}' at position {}", c, self.at + 1))
+        }
+    }
+
+    /// sum := product (('+' | '-') product)*
+    fn sum(&mut self) -> Result<(f64, String), String> {
+        let (mut value, mut text) = self.product()?;
+        while let Some(op @ ('+' | '-')) = self.peek() {
+            self.at += 1;
+            let (rhs, rhs_text) = self.product()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+            text = format!("{} {} {}", text, op, rhs_text);
+        }
+        Ok((value, text))
+    }
+
+    /// product := factor (('*' | '/') factor)*
+    fn product(&mut self) -> Result<(f64, String), String> {
+        let (mut value, mut text) = self.factor()?;
+        while let Some(op @ ('*' | '/')) = self.peek() {
+            self.at += 1;
+            let (rhs, rhs_text) = self.factor()?;
+            if op == '/' && rhs == 0.0 {
+                return Err("Division by zero".to_string());
+            }
+            value = if op == '*' { value * rhs } else { value / rhs };
+            text = format!("{} {} {}", text, op, rhs_text);
+        }
+        Ok((value, text))
+    }
+
+    /// factor := '-' factor | '(' sum ')' | function '(' sum ')' | dice | number
+    fn factor(&mut self) -> Result<(f64, String), String> {
+        match self.peek() {
+            Some('-') => {
+                self.at += 1;
+                let (value, text) = self.factor()?;
+                Ok((-value, format!("-{}", text)))
+            }
+            Some('(') => {
+                self.at += 1;
+                let (value, text) = self.sum()?;
+                self.expect(')')?;
+                Ok((value, format!("({} = {})", text, format_value(value))))
+            }
+            Some(c) if c.is_ascii_digit() || c == 'd' => self.dice_or_number(),
+            Some(c) if c.is_ascii_alphabetic() => {
+                let name = self.take_while(|c| c.is_ascii_alphabetic());
+                let round: fn(f64) -> f64 = match name.as_str() {
+                    "floor" => f64::floor,
+                    "ceil" => f64::ceil,
+                    "round" => f64::round,
+                    _ => return Err(format!("Unknown function '{}' (use floor, ceil or round)", name)),
+                };
+                self.expect('(')?;
+                let (value, text) = self.sum()?;
+                self.expect(')')?;
+                Ok((round(value), format!("{}({} = {})", name, text, format_value(value))))
+            }
+            Some(c) => Err(format!("Unexpected '{}' at position {}", c, self.at + 1)),
+            None => Err("Expression ends early".to_string()),
+        }
+    }
+
+    fn dice_or_number(&mut self) -> Result<(f64, String), String> {
+        let count = self.take_while(|c| c.is_ascii_digit());
+        if self.peek() != Some('d') {
+            let value = count.parse::<f64>().map_err(|_| format!("Invalid number '{}'", count))?;
+            return Ok((value, count));
+        }
+        self.at += 1;
+        let sides = if self.peek() == Some('%') {
+            self.at += 1;
+            "%".to_string()
+        } else {
+            self.take_while(|c| c.is_ascii_digit())
+        };
+        // Options follow the sides: ! to explode, r1 or r<2 to reroll, kh3 or kl1 to keep
+        let mut options = String::new();
+        while let Some(option @ ('!' | 'r' | 'k')) = self.peek() {
+            self.at += 1;
+            options.push(option);
+            options.push_str(&self.take_while(|c| c == 'h' || c == 'l' || c == '<' || c.is_ascii_digit()));
+        }
+        if (count.is_empty() || count == "1") && sides == "20" && options.is_empty()
+            && let Some(edge) = self.edge.take() {
+            options.push_str(edge.name());
+        }
+        let roll = roll_dice_with_crits(&format!("{}d{}{}", count, sides, options))?;
+        let result = (roll.total as f64, roll.dice_text());
+        self.dice.push(roll);
+        Ok(result)
+    }
+}
+
+/// The CLI's `r` shorthand (`r2d6`, `rfloor(8d6/2)`, `rround(7/2)`), but not the `r`
+/// of a bare `round(...)`
+fn strip_roll_prefix(input: &str) -> &str {
+    if input.starts_with("round(") {
+        return input;
+    }
+    input.strip_prefix('r').unwrap_or(input)
+}
+
+/// Roll an expression with arithmetic: `(1d8+4)*2`, `floor(8d6/2)`, `2d6+1d4+3`.
+/// A trailing `adv` or `dis` applies to the expression's d20, as in `1d20+5 adv`.
+pub fn roll_expression(input: &str) -> Result<ExpressionRoll, String> {
+    let input: String = input.trim().to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    let (input, edge) = split_edge_suffix(strip_roll_prefix(&input));
+    let chars: Vec<char> = input.chars().collect();
+    if chars.is_empty() {
+        return Err("Nothing to roll".to_string());
+    }
+    let mut parser = ExpressionParser { chars: &chars, at: 0, dice: Vec::new(), edge };
+    let (value, breakdown) = parser.sum()?;
+    if let Some(c) = parser.peek() {
+        return Err(format!("Unexpected '{}' at position {}", c, parser.at + 1));
+    }
+    if parser.edge.is_some() {
+        return Err("Advantage and disadvantage need a single d20 in the roll".to_string());
+    }
+    Ok(ExpressionRoll { breakdown, total: value.floor() as i32, dice: parser.dice })
+}
+
+/// The kept dice from every term and the total (never below zero), for any expression
+/// `roll_expression` accepts
+pub fn roll_dice(input: &str) -> Result<(Vec<u8>, u32), String> {
+    let result = roll_expression(input)?;
+    let kept = result.dice.iter().flat_map(|roll| roll.kept.iter().copied()).collect();
+    Ok((kept, result.total.max(0) as u32))
 }
 
 pub fn roll_dice_mode() {
     println!("Dice Rolling Mode");
-    println!("Commands: r<num>d<sides> (e.g., r3d6), q to quit");
+    println!("Commands: r<num>d<sides>[!][r<n>][kh|kl<n>][+/-mod] (e.g., r3d6, r2d6+3, r4d6kh3, r3d6!), q to quit");
     
     let mut ending = false;
     while !ending {
@@ -62,15 +470,12 @@ pub fn roll_dice_mode() {
         let input = buffer.trim();
         match input.chars().next() {
             Some('r') => {
-                match roll_dice_with_crits(input) {
-                    Ok((rolls, total, crit_message)) => {
-                        for (i, roll) in rolls.iter().enumerate() {
-                            println!("Roll {}: {}", i + 1, roll);
-                        }
-                        println!("Total: {}", total);
+                match roll_expression(input) {
+                    Ok(result) => {
+                        println!("{}", result);
                         
                         // Display critical message if applicable
-                        if let Some(message) = crit_message {
+                        if let Some(message) = result.crit() {
                             println!("{}", message);
                         }
                     }
@@ -81,6 +486,9 @@ pub fn roll_dice_mode() {
             Some('h') | Some('?') => {
                 println!("Commands:");
                 println!("  r<num>d<sides> - Roll dice (e.g., r3d6 rolls 3 six-sided dice)");
+                println!("  r2d6+3 adds a modifier, r4d6kh3 keeps the highest 3, r2d20kl1 the lowest");
+                println!("  r3d6! explodes (a 6 adds another die), r2d6r1 rerolls ones once, r2d6r<3 ones and twos");
+                println!("  r(1d8+4)*2 and rfloor(8d6/2) do arithmetic; fractions round down");
                 println!("  q - Quit dice mode");
                 println!("  h or ? - Show this help");
             }
@@ -0,0 +1,224 @@
+use crate::combat::Combatant;
+use crate::content::Monster;
+use crate::error_handling::{AppError, Result};
+use crate::monsters::MonsterIndex;
+use crate::npc_presets::{find_preset, preset_roles, NpcPreset};
+use crate::xp::{adjusted_xp, cr_value, encounter_multiplier, format_xp, xp_for_cr};
+use rand::Rng;
+use rand::seq::IndexedRandom;
+
+/// XP thresholds per character for levels 1-20: easy, medium, hard, deadly (DMG p.82)
+const XP_THRESHOLDS: [[u32; 4]; 20] = [
+    [25, 50, 75, 100], [50, 100, 150, 200], [75, 150, 225, 400], [125, 250, 375, 500],
+    [250, 500, 750, 1_100], [300, 600, 900, 1_400], [350, 750, 1_100, 1_700], [450, 900, 1_400, 2_100],
+    [550, 1_100, 1_600, 2_400], [600, 1_200, 1_900, 2_800], [800, 1_600, 2_400, 3_600], [1_000, 2_000, 3_000, 4_500],
+    [1_100, 2_200, 3_400, 5_100], [1_250, 2_500, 3_800, 5_700], [1_400, 2_800, 4_300, 6_400], [1_600, 3_200, 4_800, 7_200],
+    [2_000, 3_900, 5_900, 8_800], [2_100, 4_200, 6_300, 9_500], [2_400, 4_900, 7_300, 10_900], [2_800, 5_700, 8_500, 12_700],
+];
+
+/// Typical (AC, HP) by CR from the DMG's monster statistics table, for creatures
+/// whose stat block isn't known offline
+const CR_STATS: &[(&str, i32, i32)] = &[
+    ("0", 13, 4), ("1/8", 13, 21), ("1/4", 13, 42), ("1/2", 13, 60), ("1", 13, 78), ("2", 13, 93),
+    ("3", 13, 108), ("4", 14, 123), ("5", 15, 138), ("6", 15, 153), ("7", 15, 168), ("8", 16, 183),
+    ("9", 16, 198), ("10", 17, 213), ("11", 17, 228), ("12", 17, 243), ("13", 18, 258), ("14", 18, 273),
+    ("15", 18, 288), ("16", 18, 303), ("17", 19, 318), ("18", 19, 333), ("19", 19, 348), ("20", 19, 378),
+];
+
+/// The most creatures the generator puts in one encounter
+const MAX_MONSTERS: usize = 8;
+const ATTEMPTS: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Deadly,
+}
+
+impl Difficulty {
+    pub fn parse(text: &str) -> Option<Difficulty> {
+        match text.trim().to_lowercase().as_str() {
+            "easy" | "e" => Some(Difficulty::Easy),
+            "medium" | "m" => Some(Difficulty::Medium),
+            "hard" | "h" => Some(Difficulty::Hard),
+            "deadly" | "d" => Some(Difficulty::Deadly),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Deadly => "deadly",
+        }
+    }
+
+    fn column(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// The party's XP threshold for a difficulty
+pub fn party_threshold(party_size: usize, party_level: u8, difficulty: Difficulty) -> u32 {
+    let level = party_level.clamp(1, 20) as usize;
+    XP_THRESHOLDS[level - 1][difficulty.column()] * party_size as u32
+}
+
+/// The adjusted XP range that counts as `difficulty`: from its threshold up to the
+/// next one. Deadly fights are capped at one and a half times the deadly threshold.
+pub fn xp_band(party_size: usize, party_level: u8, difficulty: Difficulty) -> (u32, u32) {
+    let low = party_threshold(party_size, party_level, difficulty);
+    let high = match difficulty {
+        Difficulty::Easy => party_threshold(party_size, party_level, Difficulty::Medium),
+        Difficulty::Medium => party_threshold(party_size, party_level, Difficulty::Hard),
+        Difficulty::Hard => party_threshold(party_size, party_level, Difficulty::Deadly),
+        Difficulty::Deadly => low * 3 / 2,
+    };
+    (low, high)
+}
+
+/// One creature in a generated encounter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncounterMonster {
+    pub name: String,
+    pub cr: String,
+    pub ac: i32,
+    pub hp: i32,
+    /// AC and HP are the DMG's typical values for the CR, not the creature's own
+    pub estimated: bool,
+    pub preset: Option<&'static NpcPreset>,
+}
+
+impl EncounterMonster {
+    /// Stats from an NPC preset or a cached wiki page when there is one
+    pub fn new(name: &str, cr: &str) -> Self {
+        if let Some(preset) = find_preset(name).filter(|p| p.role.eq_ignore_ascii_case(name)) {
+            return EncounterMonster { name: name.to_string(), cr: cr.to_string(), ac: preset.ac as i32, hp: preset.hp as i32, estimated: false, preset: Some(preset) };
+        }
+        let cached = crate::search::read_cached_page(std::path::Path::new(crate::search::CACHE_DIR), "monster", name)
+            .map(|page| Monster::from_page(&page))
+            .and_then(|monster| Some((monster.ac?, monster.hp?)));
+        let (ac, hp, estimated) = match cached {
+            Some((ac, hp)) => (ac, hp, false),
+            None => {
+                let (_, ac, hp) = CR_STATS.iter().find(|(rating, ..)| *rating == cr).copied().unwrap_or(("", 19, 400));
+                (ac, hp, true)
+            }
+        };
+        EncounterMonster { name: name.to_string(), cr: cr.to_string(), ac, hp, estimated, preset: None }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Encounter {
+    pub party_size: usize,
+    pub party_level: u8,
+    pub difficulty: Difficulty,
+    pub monsters: Vec<EncounterMonster>,
+    pub base_xp: u32,
+    pub adjusted_xp: u32,
+}
+
+/// (name, CR) of everything the generator can pick from: the monster index and the NPC presets
+fn candidates(index: &MonsterIndex) -> Vec<(String, String)> {
+    let mut found: Vec<(String, String)> = index.monsters.iter()
+        .filter(|m| cr_value(&m.cr).is_some_and(|cr| cr > 0.0))
+        .map(|m| (m.name.clone(), m.cr.clone()))
+        .collect();
+    found.extend(preset_roles().into_iter()
+        .filter_map(|role| find_preset(role).map(|p| (role.to_string(), p.cr.to_string()))));
+    found
+}
+
+/// Build an encounter for the party from the 5e XP budget: pick a group size,
+/// the strongest CR that keeps the adjusted XP inside the difficulty's band, and
+/// creatures of that CR. The closest fit is kept if no attempt lands in the band.
+pub fn generate<R: Rng + ?Sized>(index: &MonsterIndex, party_size: usize, party_level: u8, difficulty: Difficulty, rng: &mut R) -> Result<Encounter> {
+    if party_size == 0 {
+        return Err(AppError::ValidationError("The party needs at least one member".to_string()));
+    }
+    let pool = candidates(index);
+    let (low, high) = xp_band(party_size, party_level, difficulty);
+    let mut best: Option<(u32, Vec<(String, String)>)> = None;
+
+    for _ in 0..ATTEMPTS {
+        let count = rng.random_range(1..=MAX_MONSTERS);
+        let per_monster = (high as f64 / (encounter_multiplier(count, party_size) * count as f64)) as u32;
+        // Creatures worth no more than the share, and as close to it as the pool allows
+        let Some(top_xp) = pool.iter().filter_map(|(_, cr)| xp_for_cr(cr)).filter(|xp| *xp <= per_monster).max() else { continue };
+        let choices: Vec<&(String, String)> = pool.iter().filter(|(_, cr)| xp_for_cr(cr) == Some(top_xp)).collect();
+        // One or two kinds of creature keep the fight readable
+        let kinds: Vec<&(String, String)> = (0..rng.random_range(1..=2)).filter_map(|_| choices.choose(rng).copied()).collect();
+        let group: Vec<(String, String)> = (0..count).map(|i| kinds[i % kinds.len()].clone()).collect();
+        let ratings: Vec<&str> = group.iter().map(|(_, cr)| cr.as_str()).collect();
+        let (_, _, adjusted) = adjusted_xp(party_size, &ratings)?;
+        let miss = if adjusted < low { low - adjusted } else { adjusted.saturating_sub(high) };
+        if best.as_ref().is_none_or(|(best_miss, _)| miss < *best_miss) {
+            best = Some((miss, group));
+        }
+        if miss == 0 && rng.random_bool(0.5) {
+            break;
+        }
+    }
+
+    let Some((_, mut group)) = best else {
+        return Err(AppError::NotFound("A creature weak enough for this party".to_string()));
+    };
+    group.sort();
+    let ratings: Vec<&str> = group.iter().map(|(_, cr)| cr.as_str()).collect();
+    let (base_xp, _, adjusted_xp) = adjusted_xp(party_size, &ratings)?;
+    Ok(Encounter {
+        party_size,
+        party_level,
+        difficulty,
+        monsters: group.iter().map(|(name, cr)| EncounterMonster::new(name, cr)).collect(),
+        base_xp,
+        adjusted_xp,
+    })
+}
+
+impl Encounter {
+    /// Names for the tracker: "Wolf 1", "Wolf 2"... when a creature appears more than once
+    fn combatant_names(&self) -> Vec<String> {
+        let mut seen: Vec<(&str, usize)> = Vec::new();
+        self.monsters.iter().map(|m| {
+            let total = self.monsters.iter().filter(|other| other.name == m.name).count();
+            let number = match seen.iter_mut().find(|(name, _)| *name == m.name) {
+                Some((_, n)) => { *n += 1; *n }
+                None => { seen.push((&m.name, 1)); 1 }
+            };
+            let mut chars = m.name.chars();
+            let name: String = chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default();
+            if total > 1 { format!("{} {}", name, number) } else { name }
+        }).collect()
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let (low, high) = xp_band(self.party_size, self.party_level, self.difficulty);
+        let mut lines = vec![format!("⚔️ {} encounter for {} level-{} character(s): {} XP ({} adjusted; {} is {}-{})",
+            self.difficulty.name(), self.party_size, self.party_level, format_xp(self.base_xp), format_xp(self.adjusted_xp),
+            self.difficulty.name(), format_xp(low), format_xp(high))];
+        for (monster, name) in self.monsters.iter().zip(self.combatant_names()) {
+            let estimate = if monster.estimated { " (typical for CR)" } else { "" };
+            lines.push(format!("  • {} - CR {}, AC {}, HP {}{}", name, monster.cr, monster.ac, monster.hp, estimate));
+        }
+        lines
+    }
+
+    /// Combatants ready for the tracker, with initiative rolled (d20 + DEX for presets)
+    pub fn to_combatants<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<Combatant> {
+        self.monsters.iter().zip(self.combatant_names()).map(|(monster, name)| {
+            let roll = rng.random_range(1..=20);
+            let mut combatant = match monster.preset {
+                Some(preset) => preset.to_combatant(&name, roll + preset.dex_mod()),
+                None => Combatant::new_npc(name, monster.hp, monster.ac, roll),
+            };
+            combatant.cr = Some(monster.cr.clone());
+            combatant
+        }).collect()
+    }
+}
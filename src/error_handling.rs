@@ -7,6 +7,12 @@ pub enum AppError {
     ParseError(String),
     ValidationError(String),
     FileError(String),
+    NetworkError(String),
+    NotFound(String),
+    // The wiki answered but its pages no longer look the way the parser expects
+    LayoutChanged(String),
+    // Wraps another error with a description of what was being attempted
+    Context { context: String, source: Box<AppError> },
 }
 
 impl fmt::Display for AppError {
@@ -16,11 +22,23 @@ impl fmt::Display for AppError {
             AppError::ParseError(e) => write!(f, "Parse Error: {}", e),
             AppError::ValidationError(e) => write!(f, "Validation Error: {}", e),
             AppError::FileError(e) => write!(f, "File Error: {}", e),
+            AppError::NetworkError(e) => write!(f, "Network Error: {}", e),
+            AppError::NotFound(what) => write!(f, "{} not found", what),
+            AppError::LayoutChanged(e) => write!(f, "Site layout changed (the page parser needs updating): {}", e),
+            AppError::Context { context, source } => write!(f, "{}: {}", context, source),
         }
     }
 }
 
-impl Error for AppError {}
+impl Error for AppError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AppError::IoError(e) => Some(e),
+            AppError::Context { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 impl From<std::io::Error> for AppError {
     fn from(error: std::io::Error) -> Self {
@@ -28,6 +46,36 @@ impl From<std::io::Error> for AppError {
     }
 }
 
+impl From<ron::error::SpannedError> for AppError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        AppError::ParseError(error.to_string())
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(error: reqwest::Error) -> Self {
+        AppError::NetworkError(error.to_string())
+    }
+}
+
+impl From<zip::result::ZipError> for AppError {
+    fn from(error: zip::result::ZipError) -> Self {
+        AppError::FileError(error.to_string())
+    }
+}
+
+/// Attach a description of the failed operation to an error, e.g.
+/// `fs::read_to_string(path).context("Failed to read character sheet")?`
+pub trait ResultExt<T> {
+    fn context(self, context: impl Into<String>) -> Result<T>;
+}
+
+impl<T, E: Into<AppError>> ResultExt<T> for std::result::Result<T, E> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|e| AppError::Context { context: context.into(), source: Box::new(e.into()) })
+    }
+}
+
 pub type Result<T> = std::result::Result<T, AppError>;
 
 pub fn validate_character_name(name: &str) -> Result<()> {
@@ -43,21 +91,111 @@ pub fn validate_character_name(name: &str) -> Result<()> {
     Ok(())
 }
 
-pub fn validate_numeric_input(input: &str, field_name: &str, min: Option<u8>, max: Option<u8>) -> Result<u8> {
-    match input.parse::<u8>() {
-        Ok(value) => {
-            if let Some(min_val) = min {
-                if value < min_val {
-                    return Err(AppError::ValidationError(format!("{} must be at least {}", field_name, min_val)));
-                }
-            }
-            if let Some(max_val) = max {
-                if value > max_val {
-                    return Err(AppError::ValidationError(format!("{} must be at most {}", field_name, max_val)));
-                }
-            }
-            Ok(value)
+/// Per-field numeric limits for character stats, with optional house-rule overrides
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatLimits {
+    overrides: Vec<(String, u8, u8)>,
+}
+
+/// Default (min, max) for each numeric stat key
+const DEFAULT_STAT_LIMITS: &[(&str, u8, u8)] = &[
+    ("level", 1, 20),
+    ("ac", 1, 30),
+    ("hp", 0, 255),
+    ("max_hp", 1, 255),
+    ("temp_hp", 0, 255),
+    ("speed", 0, 120),
+    ("stre", 1, 30),
+    ("dext", 1, 30),
+    ("cons", 1, 30),
+    ("intl", 1, 30),
+    ("wisd", 1, 30),
+    ("chas", 1, 30),
+    ("passive_perception", 1, 50),
+    ("initiative", 0, 50),
+    ("prof_bonus", 0, 10),
+];
+
+impl StatLimits {
+    pub fn new() -> Self {
+        StatLimits { overrides: Vec::new() }
+    }
+
+    /// Read house-rule overrides from DND_TOOLS_STAT_LIMITS, e.g. "level=0-30,ac=1-40"
+    pub fn from_env() -> Self {
+        std::env::var("DND_TOOLS_STAT_LIMITS")
+            .map(|spec| Self::parse(&spec))
+            .unwrap_or_default()
+    }
+
+    /// Parse "key=min-max" pairs; malformed entries are ignored
+    pub fn parse(spec: &str) -> Self {
+        let overrides = spec.split(',')
+            .filter_map(|entry| {
+                let (key, range) = entry.split_once('=')?;
+                let (min, max) = range.split_once('-')?;
+                let (min, max) = (min.trim().parse().ok()?, max.trim().parse().ok()?);
+                (min <= max).then(|| (key.trim().to_lowercase(), min, max))
+            })
+            .collect();
+        StatLimits { overrides }
+    }
+
+    pub fn range(&self, key: &str) -> Option<(u8, u8)> {
+        self.overrides.iter()
+            .find(|(k, _, _)| k == key)
+            .map(|&(_, min, max)| (min, max))
+            .or_else(|| DEFAULT_STAT_LIMITS.iter()
+                .find(|(k, _, _)| *k == key)
+                .map(|&(_, min, max)| (min, max)))
+    }
+
+    /// Parse and range-check a value for the given stat key
+    pub fn validate(&self, key: &str, input: &str) -> Result<u8> {
+        let label = stat_label(key);
+        let value: i64 = input.trim().parse()
+            .map_err(|_| AppError::ParseError(format!("{} must be a whole number", label)))?;
+        let (min, max) = self.range(key).unwrap_or((0, u8::MAX));
+        if value < min as i64 || value > max as i64 {
+            return Err(AppError::ValidationError(format!("{} must be between {} and {}", label, min, max)));
         }
-        Err(_) => Err(AppError::ParseError(format!("Invalid number for {}", field_name)))
+        Ok(value as u8)
+    }
+}
+
+impl Default for StatLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn is_numeric_stat(key: &str) -> bool {
+    DEFAULT_STAT_LIMITS.iter().any(|(k, _, _)| *k == key)
+}
+
+/// Human-readable name for a stat key, used in validation messages
+pub fn stat_label(key: &str) -> &str {
+    match key {
+        "level" => "Level",
+        "ac" => "AC",
+        "hp" => "HP",
+        "max_hp" => "Max HP",
+        "temp_hp" => "Temp HP",
+        "speed" => "Speed",
+        "stre" => "Strength",
+        "dext" => "Dexterity",
+        "cons" => "Constitution",
+        "intl" => "Intelligence",
+        "wisd" => "Wisdom",
+        "chas" => "Charisma",
+        "passive_perception" => "Passive Perception",
+        "initiative" => "Initiative",
+        "prof_bonus" => "Proficiency Bonus",
+        other => other,
     }
-}
\ No newline at end of file
+}
+
+/// Validate a stat against the default limits plus any house-rule overrides
+pub fn validate_stat(key: &str, input: &str) -> Result<u8> {
+    StatLimits::from_env().validate(key, input)
+}
@@ -1,44 +1,430 @@
 use crate::character::Character;
-use std::{fs, io::Write, path::Path};
+use crate::error_handling::{AppError, Result, ResultExt};
+use crate::input_handler::confirm;
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::{Path, PathBuf}, time::{SystemTime, UNIX_EPOCH}};
 
-pub fn load_character_files() -> Vec<Character> {
-    let mut characters = Vec::new();
-    if let Ok(paths) = fs::read_dir("characters") {
-        for path in paths {
-            if let Ok(path) = path {
-                if let Ok(character_sheet) = fs::read_to_string(path.path()) {
-                    if let Ok(character) = ron::de::from_str::<Character>(&character_sheet) {
-                        characters.push(character);
-                    }
+pub const CHARACTERS_DIR: &str = "characters";
+pub const TRASH_DIR: &str = "characters/trash";
+pub const NPCS_DIR: &str = "npcs";
+/// Sheets of characters who have died, kept for the campaign's history
+pub const ARCHIVE_DIR: &str = "characters/archive";
+/// Layout version written into every character file. Version 1 was the RON
+/// sheet saved as `<name>.txt`; version 2 is the first JSON layout.
+pub const CHARACTER_SCHEMA_VERSION: u32 = 2;
+pub const CHARACTER_EXTENSION: &str = "json";
+/// Name, class, level and HP of every sheet, so startup doesn't have to parse them all
+pub const CHARACTER_INDEX_FILE: &str = "data/character_index.json";
+const LEGACY_EXTENSION: &str = "txt";
+/// Old sheets are kept under this extension after they are migrated
+const MIGRATED_BACKUP_EXTENSION: &str = "txt.v1.bak";
+/// Trashed characters older than this are purged at startup unless overridden
+/// with the DND_TOOLS_TRASH_MAX_AGE_DAYS environment variable
+pub const DEFAULT_TRASH_MAX_AGE_DAYS: u64 = 30;
+
+/// A character file sitting in the trash folder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedCharacter {
+    pub name: String,
+    pub deleted_at: u64, // seconds since the Unix epoch
+    pub path: PathBuf,
+}
+
+#[derive(Serialize)]
+struct CharacterFileOut<'a> {
+    schema_version: u32,
+    character: &'a Character,
+}
+
+#[derive(Deserialize)]
+struct CharacterFileIn {
+    schema_version: u32,
+    character: serde_json::Value,
+}
+
+/// `<dir>/<name>.json`
+pub fn character_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.{}", name, CHARACTER_EXTENSION))
+}
+
+fn legacy_character_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.{}", name, LEGACY_EXTENSION))
+}
+
+/// A character as a versioned JSON document
+pub fn serialize_character(character: &Character) -> Result<String> {
+    serde_json::to_string_pretty(&CharacterFileOut { schema_version: CHARACTER_SCHEMA_VERSION, character })
+        .map_err(|e| AppError::ParseError(format!("Failed to serialize {}: {}", character.name, e)))
+}
+
+/// Bring a JSON sheet up to the current schema. A future layout change adds a
+/// step here for each version it replaces.
+fn migrate(version: u32, character: serde_json::Value) -> Result<serde_json::Value> {
+    match version {
+        CHARACTER_SCHEMA_VERSION => Ok(character),
+        v if v > CHARACTER_SCHEMA_VERSION => Err(AppError::ParseError(format!(
+            "written by a newer dnd_tools (schema version {}, this build reads up to {})", v, CHARACTER_SCHEMA_VERSION))),
+        v => Err(AppError::ParseError(format!("unknown character schema version {}", v))),
+    }
+}
+
+/// Parse a character file of any version: RON `.txt` sheets (version 1) or versioned JSON
+pub fn parse_character_file(path: &Path, contents: &str) -> Result<Character> {
+    if path.extension().is_some_and(|ext| ext == LEGACY_EXTENSION) {
+        return ron::de::from_str::<Character>(contents)
+            .map_err(|e| AppError::ParseError(format!("{}: {}", path.display(), e)));
+    }
+    let file: CharacterFileIn = serde_json::from_str(contents)
+        .map_err(|e| AppError::ParseError(format!("{}: {}", path.display(), e)))?;
+    let character = migrate(file.schema_version, file.character)
+        .map_err(|e| AppError::ParseError(format!("{}: {}", path.display(), e)))?;
+    serde_json::from_value(character).map_err(|e| AppError::ParseError(format!("{}: {}", path.display(), e)))
+}
+
+pub fn read_character_file(path: &Path) -> Result<Character> {
+    let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+    parse_character_file(path, &contents)
+}
+
+/// Write `<dir>/<name>.json`, replacing any version 1 sheet of the same name
+pub fn write_character_file(dir: &Path, character: &Character) -> Result<PathBuf> {
+    fs::create_dir_all(dir).context(format!("Failed to create {}", dir.display()))?;
+    let path = character_path(dir, &character.name);
+    fs::write(&path, serialize_character(character)?).context(format!("Failed to write {}", path.display()))?;
+    let legacy = legacy_character_path(dir, &character.name);
+    if legacy.exists() {
+        fs::remove_file(&legacy).context(format!("Failed to remove {}", legacy.display()))?;
+    }
+    Ok(path)
+}
+
+/// The current file for a character's sheet, JSON or version 1
+fn existing_character_path(dir: &Path, name: &str) -> Option<PathBuf> {
+    [character_path(dir, name), legacy_character_path(dir, name)].into_iter().find(|path| path.exists())
+}
+
+fn is_character_file(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|ext| ext == CHARACTER_EXTENSION || ext == LEGACY_EXTENSION)
+}
+
+/// Every sheet file in `dir`, JSON first so a leftover version 1 sheet never shadows a newer save
+fn character_files_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).filter(|path| is_character_file(path)).collect();
+    paths.sort_by_key(|path| (path.extension().is_some_and(|ext| ext == LEGACY_EXTENSION), path.clone()));
+    paths
+}
+
+/// Parse sheets on several threads, in the order given; hundreds of them one at a
+/// time hold up startup
+fn read_character_files(paths: &[PathBuf]) -> Vec<Result<Character>> {
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1).min(8);
+    let chunk_size = paths.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = paths.chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(|path| read_character_file(path)).collect::<Vec<_>>()))
+            .collect();
+        workers.into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect()
+    })
+}
+
+/// Load every sheet in `dir`. Version 1 sheets are rewritten as JSON and the
+/// original is kept beside it as `<name>.txt.v1.bak`.
+pub fn load_characters_in(dir: &Path) -> Vec<Character> {
+    load_sheets_in(dir, &character_files_in(dir))
+}
+
+/// Load the given sheets from `dir`, the way `load_characters_in` loads all of them
+pub fn load_sheets_in(dir: &Path, paths: &[PathBuf]) -> Vec<Character> {
+    let mut characters: Vec<Character> = Vec::new();
+    let parsed = read_character_files(paths);
+    for (path, parsed) in paths.iter().zip(parsed) {
+        let character = match parsed {
+            Ok(character) => character,
+            Err(e) => {
+                log::warn!("Skipping unreadable character file {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        if characters.iter().any(|c| c.name == character.name) {
+            log::warn!("Ignoring {}: {} was already loaded from a newer file", path.display(), character.name);
+            continue;
+        }
+        if path.extension().is_some_and(|ext| ext == LEGACY_EXTENSION) {
+            migrate_legacy_file(dir, path, &character);
+        }
+        characters.push(character);
+    }
+    characters
+}
+
+/// The columns of the startup table, readable without the rest of the sheet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CharacterSummary {
+    pub name: String,
+    pub class: Option<String>,
+    pub level: Option<u8>,
+    pub hp: Option<u8>,
+    pub max_hp: Option<u8>,
+}
+
+impl CharacterSummary {
+    pub fn of(character: &Character) -> Self {
+        CharacterSummary {
+            name: character.name.clone(),
+            class: character.class.clone(),
+            level: character.level,
+            hp: character.hp,
+            max_hp: character.max_hp,
+        }
+    }
+}
+
+/// One sheet in the index, with the size and modified time it had when summarized
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct IndexedSheet {
+    file: String,
+    bytes: u64,
+    modified: u64,
+    /// The sheet's registry id; indexes written before ids were recorded are read again
+    #[serde(default)]
+    id: Option<u32>,
+    summary: CharacterSummary,
+}
+
+/// A sheet the index knows about, for reading it when it's first needed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub path: PathBuf,
+    pub id: u32,
+    pub summary: CharacterSummary,
+}
+
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let meta = fs::metadata(path).ok()?;
+    let modified = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64;
+    Some((meta.len(), modified))
+}
+
+/// Summaries of the sheets in `dir` from the index at `index_path`. Only sheets
+/// added or changed since the index was written are parsed, and the index is
+/// rewritten when any were.
+pub fn character_index_in(dir: &Path, index_path: &Path) -> Vec<CharacterSummary> {
+    character_index_entries_in(dir, index_path).into_iter().map(|entry| entry.summary).collect()
+}
+
+/// The index's entries, with where each sheet is and its id
+pub fn character_index_entries_in(dir: &Path, index_path: &Path) -> Vec<IndexEntry> {
+    let previous: Vec<IndexedSheet> = fs::read_to_string(index_path).ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    let mut changed = false;
+    let mut index: Vec<IndexedSheet> = Vec::new();
+    for path in character_files_in(dir) {
+        let file = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let Some((bytes, modified)) = file_stamp(&path) else { continue };
+        let sheet = match previous.iter().find(|s| s.file == file && s.bytes == bytes && s.modified == modified && s.id.is_some()) {
+            Some(sheet) => sheet.clone(),
+            None => match read_character_file(&path) {
+                Ok(character) => {
+                    changed = true;
+                    IndexedSheet { file, bytes, modified, id: Some(character.id), summary: CharacterSummary::of(&character) }
                 }
+                Err(e) => {
+                    log::warn!("Leaving unreadable character file {} out of the index: {}", path.display(), e);
+                    continue;
+                }
+            },
+        };
+        if !index.iter().any(|s| s.summary.name == sheet.summary.name) {
+            index.push(sheet);
+        }
+    }
+    // Sheets deleted since the index was written
+    changed |= index.len() != previous.len();
+    if changed {
+        let written = index_path.parent().map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(index_path, serde_json::to_string(&index).unwrap_or_default()));
+        if let Err(e) = written {
+            log::warn!("Could not write {}: {}", index_path.display(), e);
+        }
+    }
+    index.into_iter()
+        .map(|sheet| IndexEntry { path: dir.join(&sheet.file), id: sheet.id.unwrap_or_default(), summary: sheet.summary })
+        .collect()
+}
+
+fn migrate_legacy_file(dir: &Path, path: &Path, character: &Character) {
+    let backup = path.with_extension(MIGRATED_BACKUP_EXTENSION);
+    let json = character_path(dir, &character.name);
+    let migrated = fs::write(&json, serialize_character(character).unwrap_or_default())
+        .and_then(|_| fs::rename(path, &backup));
+    match migrated {
+        Ok(()) => log::info!("Migrated {} to {} (original kept as {})", path.display(), json.display(), backup.display()),
+        Err(e) => log::warn!("Failed to migrate {}: {}", path.display(), e),
+    }
+}
+
+/// How to resolve saving a character whose name is already taken
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameCollisionChoice {
+    Rename(String),
+    Overwrite,
+    Cancel,
+}
+
+/// Sheets in `dir` saved under `name` in any letter case
+fn saved_sheets_named(dir: &Path, name: &str) -> Vec<PathBuf> {
+    character_files_in(dir).into_iter()
+        .filter(|path| path.file_stem().and_then(|stem| stem.to_str()).is_some_and(|stem| stem.eq_ignore_ascii_case(name)))
+        .collect()
+}
+
+/// Whether a name is already used by a loaded character or a file on disk
+pub fn character_name_taken(characters: &[Character], name: &str) -> bool {
+    character_name_taken_in(Path::new(CHARACTERS_DIR), characters, name)
+}
+
+pub fn character_name_taken_in(dir: &Path, characters: &[Character], name: &str) -> bool {
+    characters.iter().any(|c| c.name.eq_ignore_ascii_case(name)) || !saved_sheets_named(dir, name).is_empty()
+}
+
+/// Suggest "Name 2", "Name 3", ... until one is free among the given characters and saved sheets
+pub fn suggest_unique_name(characters: &[Character], name: &str) -> String {
+    suggest_unique_name_in(Path::new(CHARACTERS_DIR), characters, name)
+}
+
+pub fn suggest_unique_name_in(dir: &Path, characters: &[Character], name: &str) -> String {
+    (2..)
+        .map(|n| format!("{} {}", name, n))
+        .find(|candidate| !character_name_taken_in(dir, characters, candidate))
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// Ask the user how to handle a name collision
+pub fn prompt_name_collision(characters: &[Character], name: &str) -> NameCollisionChoice {
+    let suggestion = suggest_unique_name(characters, name);
+    loop {
+        println!("A character named '{}' already exists.", name);
+        println!("1. Rename (default: {})", suggestion);
+        println!("2. Overwrite existing character");
+        println!("0. Cancel");
+
+        let mut buffer = String::new();
+        if io::stdin().read_line(&mut buffer).is_err() {
+            return NameCollisionChoice::Cancel;
+        }
+        match buffer.trim() {
+            "1" => {
+                println!("Enter a new name (blank for '{}'):", suggestion);
+                let mut new_name = String::new();
+                if io::stdin().read_line(&mut new_name).is_err() {
+                    return NameCollisionChoice::Cancel;
+                }
+                let new_name = new_name.trim();
+                if new_name.is_empty() {
+                    return NameCollisionChoice::Rename(suggestion);
+                }
+                if character_name_taken(characters, new_name) {
+                    println!("'{}' is also taken.", new_name);
+                    continue;
+                }
+                return NameCollisionChoice::Rename(new_name.to_string());
             }
+            "2" => return NameCollisionChoice::Overwrite,
+            "0" => return NameCollisionChoice::Cancel,
+            _ => println!("Invalid input"),
         }
     }
+}
+
+/// Apply a collision choice, returning false if the character was not added
+pub fn add_character_with_choice(characters: &mut Vec<Character>, character: Character, choice: NameCollisionChoice) -> bool {
+    add_character_with_choice_in(Path::new(CHARACTERS_DIR), Path::new(TRASH_DIR), characters, character, choice)
+}
+
+/// Overwriting moves a sheet saved under a differently-cased name ("Mira.json" for "mira")
+/// to the trash, since saving the new sheet won't replace it
+pub fn add_character_with_choice_in(dir: &Path, trash_dir: &Path, characters: &mut Vec<Character>, mut character: Character, choice: NameCollisionChoice) -> bool {
+    match choice {
+        NameCollisionChoice::Rename(new_name) => {
+            character.name = new_name;
+            crate::registry::insert(characters, character);
+        }
+        NameCollisionChoice::Overwrite => {
+            // The replacement takes over the old sheet's id, so combat still finds it
+            if let Some(old) = characters.iter().find(|c| c.name.eq_ignore_ascii_case(&character.name)) {
+                character.id = old.id;
+            }
+            characters.retain(|c| !c.name.eq_ignore_ascii_case(&character.name));
+            for path in saved_sheets_named(dir, &character.name) {
+                let Some(old_name) = path.file_stem().and_then(|stem| stem.to_str()).filter(|stem| *stem != character.name) else { continue };
+                if let Err(e) = trash_character_in(dir, trash_dir, old_name) {
+                    log::warn!("Could not move {} to the trash: {}", path.display(), e);
+                }
+            }
+            crate::registry::insert(characters, character);
+        }
+        NameCollisionChoice::Cancel => return false,
+    }
+    true
+}
+
+/// Add a newly created character, asking what to do if its name is already taken
+pub fn add_character(characters: &mut Vec<Character>, character: Character) -> bool {
+    if !character_name_taken(characters, &character.name) {
+        crate::registry::insert(characters, character);
+        return true;
+    }
+    let choice = prompt_name_collision(characters, &character.name);
+    if choice == NameCollisionChoice::Cancel {
+        println!("Character creation cancelled.");
+    }
+    add_character_with_choice(characters, character, choice)
+}
+
+pub fn load_character_files() -> Vec<Character> {
+    let mut characters = load_characters_in(Path::new(CHARACTERS_DIR));
+    log::info!("Loaded {} character(s) from {}", characters.len(), CHARACTERS_DIR);
+    crate::registry::register(&mut characters);
     characters
 }
 
-pub fn save_characters(characters: Vec<Character>) {
+/// `dnd_tools characters` prints the roster from the index; `--load` reads every
+/// sheet in full, which also finds any that can't be read
+pub fn run_characters_command(args: &[String]) {
+    let started = std::time::Instant::now();
+    if args.iter().any(|arg| arg == "--load") {
+        let characters = load_character_files();
+        println!("{}", character_summary_table(&characters));
+        println!("Read {} sheet(s) in {} ms", characters.len(), started.elapsed().as_millis());
+    } else {
+        let summaries = character_index_in(Path::new(CHARACTERS_DIR), Path::new(CHARACTER_INDEX_FILE));
+        println!("{}", summary_table(&summaries));
+        println!("{} character(s), listed in {} ms", summaries.len(), started.elapsed().as_millis());
+    }
+}
+
+pub fn save_characters(characters: &[Character]) {
     for character in characters {
-        save_character(character.name.clone(), character);
+        save_character(character.name.clone(), character.clone());
     }
 }
 
 pub fn save_character(name: String, data: Character) {
     println!("Saving character sheet for {}", name);
 
-    let path = format!("characters/{}.txt", name);
-    if let Ok(mut file) = fs::File::create(path) {
-        if let Ok(serialized) = ron::ser::to_string_pretty(&data, ron::ser::PrettyConfig::default()) {
-            if file.write(serialized.as_bytes()).is_ok() {
-                println!("Character sheet saved!");
-            } else {
-                println!("Failed to write character data to file");
-            }
-        } else {
-            println!("Failed to serialize character data");
+    match write_character_file(Path::new(CHARACTERS_DIR), &data) {
+        Ok(path) => {
+            log::debug!("Saved {}", path.display());
+            println!("Character sheet saved!");
+        }
+        Err(e) => {
+            log::error!("Failed to save character {}: {}", name, e);
+            println!("Failed to save character sheet: {}", e);
         }
-    } else {
-        println!("Failed to create character file");
     }
 }
 
@@ -50,8 +436,8 @@ pub fn display_character_info() {
         let name = buffer.trim();
         println!("Loading character sheet for {}", name);
 
-        let path = format!("characters/{}.txt", name);
-        match fs::read_to_string(Path::new(&path)) {
+        let path = existing_character_path(Path::new(CHARACTERS_DIR), name).unwrap_or_else(|| character_path(Path::new(CHARACTERS_DIR), name));
+        match fs::read_to_string(&path) {
             Ok(character_sheet) => {
                 println!("Read: {}", character_sheet);
                 println!("Finished loading character sheet");
@@ -63,6 +449,55 @@ pub fn display_character_info() {
     }
 }
 
+/// Names of NPCs saved from combat or the NPC generator
+pub fn saved_npc_names() -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(NPCS_DIR)
+        .map(|entries| entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+            .collect())
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Format a compact name/class/level/HP table of the loaded characters
+pub fn character_summary_table(characters: &[Character]) -> String {
+    summary_table(&characters.iter().map(CharacterSummary::of).collect::<Vec<_>>())
+}
+
+/// The name/class/level/HP table, from summaries alone
+pub fn summary_table(characters: &[CharacterSummary]) -> String {
+    let name_width = characters.iter()
+        .map(|c| c.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let class_width = characters.iter()
+        .map(|c| c.class.as_deref().unwrap_or("-").chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(5);
+
+    let mut lines = vec![
+        format!("{:<name_width$}  {:<class_width$}  {:>5}  {:>7}", "Name", "Class", "Level", "HP"),
+        format!("{}  {}  {}  {}", "-".repeat(name_width), "-".repeat(class_width), "-".repeat(5), "-".repeat(7)),
+    ];
+    for c in characters {
+        let level = c.level.map_or("-".to_string(), |l| l.to_string());
+        let hp = match (c.hp, c.max_hp) {
+            (Some(hp), Some(max)) => format!("{}/{}", hp, max),
+            (Some(hp), None) => hp.to_string(),
+            _ => "-".to_string(),
+        };
+        lines.push(format!("{:<name_width$}  {:<class_width$}  {:>5}  {:>7}",
+            c.name, c.class.as_deref().unwrap_or("-"), level, hp));
+    }
+    lines.join("\n")
+}
+
 pub fn display_single_character(characters: &[Character]) {
     if characters.is_empty() {
         println!("No characters available.");
@@ -124,16 +559,20 @@ pub fn delete_character_menu(characters: &mut Vec<Character>) {
     if std::io::stdin().read_line(&mut buffer).is_ok() {
         if let Ok(choice) = buffer.trim().parse::<usize>() {
             if choice > 0 && choice <= characters.len() {
+                let name = characters[choice - 1].name.clone();
+                if !confirm(&format!("Really delete '{}'?", name), &name) {
+                    println!("Deletion cancelled.");
+                    return;
+                }
                 let character = characters.remove(choice - 1);
                 
-                // Delete the character file
-                let path = format!("characters/{}.txt", character.name);
-                if let Err(e) = fs::remove_file(&path) {
-                    println!("Warning: Could not delete character file {}: {}", path, e);
+                // Move the character file to the trash so it can be restored later
+                if let Err(e) = trash_character(&character.name) {
+                    println!("Warning: Could not move {} to the trash: {}", character.name, e);
                 }
                 
-                println!("Character '{}' deleted successfully.", character.name);
-                save_characters(characters.clone());
+                println!("Character '{}' moved to the trash. Use 'Undelete' to restore it.", character.name);
+                save_characters(characters);
             } else {
                 println!("Invalid selection.");
             }
@@ -143,4 +582,149 @@ pub fn delete_character_menu(characters: &mut Vec<Character>) {
     } else {
         println!("Failed to read input");
     }
-}
\ No newline at end of file
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Move a character's file into the trash folder, prefixed with the deletion time
+pub fn trash_character(name: &str) -> io::Result<PathBuf> {
+    trash_character_in(Path::new(CHARACTERS_DIR), Path::new(TRASH_DIR), name)
+}
+
+pub fn trash_character_in(characters_dir: &Path, trash_dir: &Path, name: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(trash_dir)?;
+    let source = existing_character_path(characters_dir, name)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("No saved sheet for {}", name)))?;
+    let extension = source.extension().and_then(|ext| ext.to_str()).unwrap_or(CHARACTER_EXTENSION);
+    let target = trash_dir.join(format!("{}_{}.{}", now_secs(), name, extension));
+    fs::rename(&source, &target)?;
+    log::info!("Moved {} to {}", source.display(), target.display());
+    Ok(target)
+}
+
+/// Write a character's sheet to the archive and remove their live sheet
+pub fn archive_character_in(characters_dir: &Path, archive_dir: &Path, character: &Character) -> Result<PathBuf> {
+    fs::create_dir_all(archive_dir).context("Failed to create archive folder")?;
+    let target = write_character_file(archive_dir, character)?;
+    for source in [character_path(characters_dir, &character.name), legacy_character_path(characters_dir, &character.name)] {
+        if source.exists() {
+            fs::remove_file(&source).context(format!("Failed to remove {}", source.display()))?;
+        }
+    }
+    log::info!("Archived {} to {}", character.name, target.display());
+    Ok(target)
+}
+
+/// Archived characters, sorted by name
+pub fn list_archived_characters_in(archive_dir: &Path) -> Vec<Character> {
+    let mut archived: Vec<Character> = fs::read_dir(archive_dir)
+        .map(|entries| entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_character_file(path))
+            .filter_map(|path| read_character_file(&path).ok())
+            .collect())
+        .unwrap_or_default();
+    archived.sort_by(|a, b| a.name.cmp(&b.name));
+    archived
+}
+
+/// List trashed characters, most recently deleted first
+pub fn list_trashed_characters() -> Vec<TrashedCharacter> {
+    list_trashed_characters_in(Path::new(TRASH_DIR))
+}
+
+pub fn list_trashed_characters_in(trash_dir: &Path) -> Vec<TrashedCharacter> {
+    let mut trashed = Vec::new();
+    if let Ok(entries) = fs::read_dir(trash_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+            if let Some((timestamp, name)) = stem.split_once('_')
+                && let Ok(deleted_at) = timestamp.parse::<u64>() {
+                trashed.push(TrashedCharacter { name: name.to_string(), deleted_at, path });
+            }
+        }
+    }
+    trashed.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+    trashed
+}
+
+/// Move a trashed character back into the characters folder and load it
+pub fn restore_character(entry: &TrashedCharacter) -> Result<Character> {
+    restore_character_in(Path::new(CHARACTERS_DIR), entry)
+}
+
+pub fn restore_character_in(characters_dir: &Path, entry: &TrashedCharacter) -> Result<Character> {
+    if existing_character_path(characters_dir, &entry.name).is_some() {
+        return Err(AppError::FileError(format!("A character named '{}' already exists", entry.name)));
+    }
+    let character = read_character_file(&entry.path)
+        .map_err(|e| AppError::FileError(format!("Trashed file is not a valid character sheet: {}", e)))?;
+    // Sheets trashed before the JSON format are restored as JSON
+    let target = write_character_file(characters_dir, &character)?;
+    fs::remove_file(&entry.path)
+        .context("Failed to remove restored file from the trash")?;
+    log::info!("Restored {} from {}", target.display(), entry.path.display());
+    Ok(character)
+}
+
+/// Delete trashed characters older than the given age, returning how many were purged
+pub fn purge_trash(max_age_days: u64) -> usize {
+    purge_trash_in(Path::new(TRASH_DIR), max_age_days, now_secs())
+}
+
+pub fn purge_trash_in(trash_dir: &Path, max_age_days: u64, now: u64) -> usize {
+    let max_age_secs = max_age_days * 24 * 60 * 60;
+    list_trashed_characters_in(trash_dir)
+        .into_iter()
+        .filter(|entry| now.saturating_sub(entry.deleted_at) > max_age_secs)
+        .filter(|entry| fs::remove_file(&entry.path).is_ok())
+        .inspect(|entry| log::info!("Purged {} from the trash", entry.path.display()))
+        .count()
+}
+
+/// Read the trash retention period from the environment, falling back to the default
+pub fn trash_max_age_days() -> u64 {
+    std::env::var("DND_TOOLS_TRASH_MAX_AGE_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_TRASH_MAX_AGE_DAYS)
+}
+
+pub fn undelete_character_menu(characters: &mut Vec<Character>) {
+    let trashed = list_trashed_characters();
+    if trashed.is_empty() {
+        println!("Trash is empty.");
+        return;
+    }
+
+    println!("\nSelect a character to restore:");
+    for (i, entry) in trashed.iter().enumerate() {
+        let days_ago = now_secs().saturating_sub(entry.deleted_at) / (24 * 60 * 60);
+        println!("{}. {} (deleted {} day(s) ago)", i + 1, entry.name, days_ago);
+    }
+
+    let mut buffer = String::new();
+    if std::io::stdin().read_line(&mut buffer).is_ok() {
+        if let Ok(choice) = buffer.trim().parse::<usize>() {
+            if choice > 0 && choice <= trashed.len() {
+                match restore_character(&trashed[choice - 1]) {
+                    Ok(character) => {
+                        println!("Character '{}' restored.", character.name);
+                        crate::registry::insert(characters, character);
+                    }
+                    Err(e) => println!("Could not restore character: {}", e),
+                }
+            } else {
+                println!("Invalid selection.");
+            }
+        } else {
+            println!("Invalid input. Please enter a number.");
+        }
+    } else {
+        println!("Failed to read input");
+    }
+}
@@ -12,6 +12,9 @@ pub struct InitiativeEntry {
 pub struct InitiativeTracker {
     entries: Vec<InitiativeEntry>,
     current_turn: usize,
+    /// 0 until `advance` starts the first round
+    #[serde(default)]
+    round: u32,
 }
 
 impl InitiativeTracker {
@@ -19,6 +22,7 @@ impl InitiativeTracker {
         InitiativeTracker {
             entries: Vec::new(),
             current_turn: 0,
+            round: 0,
         }
     }
 
@@ -32,9 +36,40 @@ impl InitiativeTracker {
         self.sort_by_initiative();
     }
 
+    /// Once the rounds have started the turn stays with whoever is acting
     fn sort_by_initiative(&mut self) {
+        let acting = self.current().cloned().filter(|_| self.round > 0);
         self.entries.sort_by(|a, b| b.initiative.cmp(&a.initiative));
-        self.current_turn = 0;
+        self.current_turn = acting
+            .and_then(|acting| self.entries.iter().position(|entry| *entry == acting))
+            .unwrap_or(0);
+    }
+
+    /// Whoever is acting, once the rounds have started
+    pub fn current(&self) -> Option<&InitiativeEntry> {
+        self.entries.get(self.current_turn).filter(|_| self.round > 0)
+    }
+
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    /// Move the turn on and return who acts now. The first call starts round 1 at
+    /// the top of the order.
+    pub fn advance(&mut self) -> Option<&InitiativeEntry> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        if self.round == 0 {
+            self.round = 1;
+            self.current_turn = 0;
+        } else {
+            self.current_turn = (self.current_turn + 1) % self.entries.len();
+            if self.current_turn == 0 {
+                self.round += 1;
+            }
+        }
+        self.entries.get(self.current_turn)
     }
 
     pub fn next_turn(&mut self) -> Option<&InitiativeEntry> {
@@ -55,11 +90,41 @@ impl InitiativeTracker {
         }
     }
 
+    /// The order with ▶ on whoever is acting, for the TUI
+    pub fn order_lines(&self) -> Vec<String> {
+        if self.entries.is_empty() {
+            return vec!["📋 Nobody has rolled yet. 'roll <name>' adds someone".to_string()];
+        }
+        let heading = match self.round {
+            0 => "📋 Initiative order (not started; 'next' begins round 1):".to_string(),
+            round => format!("📋 Initiative order, round {}:", round),
+        };
+        std::iter::once(heading)
+            .chain(self.entries.iter().enumerate().map(|(i, entry)| {
+                let marker = if self.round > 0 && i == self.current_turn { "▶" } else { " " };
+                let player_type = if entry.is_player { "Player" } else { "NPC" };
+                format!("  {} {:>3}  {} ({})", marker, entry.initiative, entry.name, player_type)
+            }))
+            .collect()
+    }
+
+    /// Remove the first entry with the name, in any case. Removing someone ahead of the
+    /// acting entry keeps the turn where it is.
     pub fn remove_entry(&mut self, name: &str) -> bool {
-        if let Some(pos) = self.entries.iter().position(|entry| entry.name == name) {
+        if let Some(pos) = self.entries.iter().position(|entry| entry.name.eq_ignore_ascii_case(name)) {
             self.entries.remove(pos);
-            if self.current_turn >= self.entries.len() && !self.entries.is_empty() {
+            if pos < self.current_turn {
+                self.current_turn -= 1;
+            }
+            if self.current_turn >= self.entries.len() {
+                // The last in the order left on their turn; the next round begins
                 self.current_turn = 0;
+                if self.round > 0 && !self.entries.is_empty() {
+                    self.round += 1;
+                }
+            }
+            if self.entries.is_empty() {
+                self.round = 0;
             }
             true
         } else {
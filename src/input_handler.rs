@@ -1,15 +1,41 @@
+use crate::backgrounds::{Background, BackgroundBook};
 use crate::character::Character;
-use crate::error_handling::{Result, validate_character_name, validate_numeric_input};
+use crate::error_handling::{Result, is_numeric_stat, validate_character_name, validate_stat};
 use std::{io, collections::HashMap};
 
-fn read_user_input(prompt: &str) -> Result<String> {
+pub fn read_user_input(prompt: &str) -> Result<String> {
     println!("{}", prompt);
     let mut buffer = String::new();
     io::stdin().read_line(&mut buffer)?;
     Ok(buffer.trim().to_string())
 }
 
-pub fn create_character() -> Character {
+/// Check whether a reply confirms a destructive action: y/yes, or re-typing the target's name
+pub fn is_confirmation(input: &str, name: &str) -> bool {
+    let trimmed = input.trim();
+    trimmed.eq_ignore_ascii_case("y") || trimmed.eq_ignore_ascii_case("yes") || trimmed.eq_ignore_ascii_case(name)
+}
+
+/// Ask a y/n question on stdin before a destructive action
+pub fn confirm(prompt: &str, name: &str) -> bool {
+    match read_user_input(&format!("{} (y/n, or type '{}' to confirm):", prompt, name)) {
+        Ok(input) => is_confirmation(&input, name),
+        Err(_) => false,
+    }
+}
+
+/// Validate a typed stat, falling back to the default (with a message) when blank or out of range
+pub fn stat_or_default(input: &str, key: &str, default: u8) -> u8 {
+    if input.trim().is_empty() {
+        return default;
+    }
+    validate_stat(key, input).unwrap_or_else(|e| {
+        println!("❌ {}. Using {}.", e, default);
+        default
+    })
+}
+
+pub fn create_character(backgrounds: &BackgroundBook) -> Character {
     println!("Creating a new character");
     
     let name = loop {
@@ -33,6 +59,17 @@ pub fn create_character() -> Character {
     let mut character = Character::new(&name);
     println!("Character {} created!", name);
 
+    choose_ability_scores(&mut character);
+    character.class = choose_class();
+    if let Some(background) = choose_background(backgrounds) {
+        for line in background.apply(&mut character) {
+            println!("{}", line);
+        }
+    }
+    if let Some(kit) = character.class.as_deref().and_then(crate::starting_equipment::class_kit) {
+        choose_starting_equipment(&mut character, kit);
+    }
+
     loop {
         match read_user_input("Would you like to add more information to the character sheet?\n1. Yes\n2. No") {
             Ok(input) => {
@@ -56,6 +93,103 @@ pub fn create_character() -> Character {
     character
 }
 
+/// Pick how the ability scores are generated, then roll, assign or buy them.
+/// Manual entry (or a read error) leaves them for the data entry step.
+fn choose_ability_scores(character: &mut Character) {
+    use crate::ability_scores::{self, ScoreMethod};
+    let method = loop {
+        let Ok(input) = read_user_input(&ScoreMethod::menu()) else { return };
+        match ScoreMethod::parse(&input) {
+            Some(method) => break method,
+            None => println!("Invalid input, please enter 1, 2, 3 or 4"),
+        }
+    };
+    let pool = method.pool(&mut rand::rng());
+    if let Some(pool) = pool {
+        let listed: Vec<String> = pool.iter().map(u8::to_string).collect();
+        println!("🎲 Scores to assign ({}): {}", method.name(), listed.join(", "));
+    } else if method == ScoreMethod::PointBuy {
+        println!("{}", ability_scores::point_cost_table());
+    } else {
+        return;
+    }
+    loop {
+        let Ok(input) = read_user_input(&format!("Enter six scores in the order {} (e.g. 15 14 13 12 10 8):", ability_scores::order_label())) else { return };
+        let scores = match pool {
+            Some(pool) => ability_scores::assign(&input, &pool),
+            None => ability_scores::point_buy(&input).map(|(scores, left)| {
+                if left > 0 {
+                    println!("⚠️  {} point(s) left unspent", left);
+                }
+                scores
+            }),
+        };
+        match scores {
+            Ok(scores) => {
+                ability_scores::apply(character, scores);
+                println!("Ability scores: {}", ability_scores::describe(&scores));
+                return;
+            }
+            Err(e) => println!("❌ {}", e),
+        }
+    }
+}
+
+/// Ask for a class by name; blank skips it
+fn choose_class() -> Option<String> {
+    use crate::races_classes::CLASSES;
+    loop {
+        let input = read_user_input("Class (press Enter to skip, 'list' to see them):").ok()?;
+        if input.is_empty() {
+            return None;
+        }
+        match CLASSES.iter().find(|class| class.eq_ignore_ascii_case(&input)) {
+            Some(class) => return Some(class.to_string()),
+            None => println!("Unknown class '{}'. Choose from: {}", input, CLASSES.join(", ")),
+        }
+    }
+}
+
+/// Offer the class's equipment packages or a gold roll; blank skips it
+fn choose_starting_equipment(character: &mut Character, kit: &crate::starting_equipment::ClassKit) {
+    for line in kit.describe() {
+        println!("{}", line);
+    }
+    loop {
+        let Ok(input) = read_user_input("Starting equipment (package letter, 'gold', or press Enter to skip):") else { return };
+        if input.is_empty() {
+            return;
+        }
+        match kit.equip(character, &input, &mut rand::rng()) {
+            Ok(lines) => {
+                lines.iter().for_each(|line| println!("{}", line));
+                return;
+            }
+            Err(e) => println!("❌ {}", e),
+        }
+    }
+}
+
+/// Ask for a background by name; blank skips it
+fn choose_background(backgrounds: &BackgroundBook) -> Option<&Background> {
+    loop {
+        let input = read_user_input("Background (press Enter to skip, 'list' to see them):").ok()?;
+        if input.is_empty() {
+            return None;
+        }
+        if input.eq_ignore_ascii_case("list") {
+            for background in &backgrounds.backgrounds {
+                println!("  {}", background.summary());
+            }
+            continue;
+        }
+        match backgrounds.get(&input) {
+            Some(background) => return Some(background),
+            None => println!("Unknown background '{}'. Choose from: {}", input, backgrounds.names().join(", ")),
+        }
+    }
+}
+
 pub fn data_entry(mut character: Character) -> Character {
     let data = character.as_vec();
     let stats = character.get_ordered_stats();
@@ -86,29 +220,19 @@ pub fn data_entry(mut character: Character) -> Character {
                             "constitution" => "cons",
                             "passive_perception" => "passive_perception",
                             "proficiency_bonus" => "prof_bonus",
+                            "description" => "desc",
                             other => other,
                         }.to_string();
                         
-                        // Validate numeric inputs
-                        let is_valid = match key.as_str() {
-                            "level" => validate_numeric_input(&new_value, &key, Some(1), Some(20)).is_ok(),
-                            "ac" => validate_numeric_input(&new_value, &key, Some(1), Some(30)).is_ok(),
-                            "hp" | "max_hp" | "temp_hp" => validate_numeric_input(&new_value, &key, Some(0), Some(255)).is_ok(),
-                            "speed" => validate_numeric_input(&new_value, &key, Some(0), Some(100)).is_ok(),
-                            "intl" | "wisd" | "chas" | "stre" | "dext" | "cons" => {
-                                validate_numeric_input(&new_value, &key, Some(1), Some(30)).is_ok()
+                        // Validate numeric inputs against the shared stat limits
+                        match validate_stat(&key, &new_value) {
+                            Err(e) if is_numeric_stat(&key) => {
+                                println!("{}. Keeping current value.", e);
                             }
-                            "passive_perception" | "initiative" | "prof_bonus" => {
-                                validate_numeric_input(&new_value, &key, Some(0), Some(50)).is_ok()
+                            _ => {
+                                println!("Updated {} from {} to {}", stat, item, new_value);
+                                changes.insert(key, new_value);
                             }
-                            _ => true, // Non-numeric fields like name and description
-                        };
-                        
-                        if is_valid {
-                            println!("Updated {} from {} to {}", stat, item, new_value);
-                            changes.insert(key, new_value);
-                        } else {
-                            println!("Invalid value for {}. Keeping current value.", stat);
                         }
                     }
                 }
@@ -0,0 +1,116 @@
+use crate::character::{Character, DeathRecord};
+use crate::error_handling::{Result, ResultExt};
+use crate::file_manager::{archive_character_in, list_archived_characters_in};
+use std::{fs, io::Write, path::Path, time::{SystemTime, UNIX_EPOCH}};
+
+/// One line per notable event, appended as the campaign goes
+pub const SESSION_LOG_FILE: &str = "campaign/session_log.txt";
+
+/// A Unix timestamp as a YYYY-MM-DD date (UTC)
+pub fn civil_date(secs: u64) -> String {
+    // Howard Hinnant's days-to-civil conversion
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+pub fn today() -> String {
+    civil_date(SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0))
+}
+
+/// Add a dated line to the session log
+pub fn append_session_log(path: &Path, date: &str, entry: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create campaign folder")?;
+    }
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(path)
+        .context(format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}  {}", date, entry).context(format!("Failed to write {}", path.display()))
+}
+
+fn describe(character: &Character) -> String {
+    match (character.level, character.class.as_deref()) {
+        (Some(level), Some(class)) => format!("{}, level {} {}", character.name, level, class),
+        (None, Some(class)) => format!("{}, {}", character.name, class),
+        _ => character.name.clone(),
+    }
+}
+
+/// Where a death is recorded: the archive folder and the session log
+#[derive(Debug, Clone, Copy)]
+pub struct Legacy<'a> {
+    pub characters_dir: &'a Path,
+    pub archive_dir: &'a Path,
+    pub session_log: &'a Path,
+}
+
+impl Legacy<'static> {
+    pub fn campaign() -> Self {
+        Legacy {
+            characters_dir: Path::new(crate::file_manager::CHARACTERS_DIR),
+            archive_dir: Path::new(crate::file_manager::ARCHIVE_DIR),
+            session_log: Path::new(SESSION_LOG_FILE),
+        }
+    }
+}
+
+impl Legacy<'_> {
+    /// Mark a character dead, move their belongings to `stash` if given, archive
+    /// their sheet and log the death. Returns lines describing what was done.
+    pub fn lay_to_rest(&self, mut character: Character, cause: &str, in_game_date: Option<&str>, stash: Option<&mut Vec<String>>) -> Result<Vec<String>> {
+        let died_on = today();
+        let cause = if cause.trim().is_empty() { "unknown causes" } else { cause.trim() };
+        character.hp = Some(0);
+        character.conditions.clear();
+        character.death = Some(DeathRecord {
+            cause: cause.to_string(),
+            died_on: died_on.clone(),
+            in_game_date: in_game_date.map(str::to_string),
+        });
+
+        let mut lines = vec![format!("🪦 {} has died: {}", describe(&character), cause)];
+        if let Some(stash) = stash
+            && !character.inventory.is_empty() {
+            let items = std::mem::take(&mut character.inventory);
+            lines.push(format!("🎒 Moved {} item(s) to the party stash: {}", items.len(), items.join(", ")));
+            stash.extend(items);
+        }
+
+        let archived = archive_character_in(self.characters_dir, self.archive_dir, &character)?;
+        lines.push(format!("📜 Sheet archived to {}", archived.display()));
+
+        let mut entry = format!("💀 {} died: {}", describe(&character), cause);
+        if let Some(date) = in_game_date {
+            entry.push_str(&format!(" (in game: {})", date));
+        }
+        append_session_log(self.session_log, &died_on, &entry)?;
+        lines.push("📝 Noted in the session log".to_string());
+        log::info!("{} died ({}) and was archived", character.name, cause);
+        Ok(lines)
+    }
+
+    /// `fallen`: the archived characters and how they died
+    pub fn fallen_lines(&self) -> Vec<String> {
+        let archived = list_archived_characters_in(self.archive_dir);
+        if archived.is_empty() {
+            return vec!["No fallen characters yet.".to_string()];
+        }
+        let mut lines = vec!["🪦 Fallen heroes:".to_string()];
+        for character in &archived {
+            let line = match &character.death {
+                Some(death) => format!("  {} - {} ({})", describe(character), death.cause,
+                    death.in_game_date.as_deref().unwrap_or(&death.died_on)),
+                None => format!("  {}", describe(character)),
+            };
+            lines.push(line);
+        }
+        lines
+    }
+}
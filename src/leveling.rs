@@ -0,0 +1,265 @@
+use crate::character::{AbilityScore, Character};
+use crate::error_handling::{AppError, Result};
+use crate::input_handler::read_user_input;
+
+/// The highest score an Ability Score Improvement can raise an ability to
+const ABILITY_CAP: u8 = 20;
+
+/// Hit die size by class; classes the table doesn't know use a d8
+pub fn hit_die(class: &str) -> u32 {
+    match class.trim().to_lowercase().as_str() {
+        "barbarian" => 12,
+        "fighter" | "paladin" | "ranger" => 10,
+        "sorcerer" | "wizard" => 6,
+        _ => 8,
+    }
+}
+
+/// What a hit die averages to when a player takes the fixed value instead of rolling
+pub fn average_roll(hit_die: u32) -> u32 {
+    hit_die / 2 + 1
+}
+
+pub fn proficiency_bonus(level: u8) -> u8 {
+    2 + (level.clamp(1, 20) - 1) / 4
+}
+
+/// Levels that grant an Ability Score Improvement. Fighters and rogues get extra ones.
+pub fn improvement_levels(class: &str) -> &'static [u8] {
+    match class.trim().to_lowercase().as_str() {
+        "fighter" => &[4, 6, 8, 12, 14, 16, 19],
+        "rogue" => &[4, 8, 10, 12, 16, 19],
+        _ => &[4, 8, 12, 16, 19],
+    }
+}
+
+pub fn grants_improvement(class: &str, level: u8) -> bool {
+    improvement_levels(class).contains(&level)
+}
+
+/// How the new level's hit points are decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HpMethod {
+    Roll,
+    Average,
+}
+
+impl HpMethod {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "roll" | "r" | "1" => Some(HpMethod::Roll),
+            "average" | "avg" | "a" | "2" => Some(HpMethod::Average),
+            _ => None,
+        }
+    }
+
+    /// The hit die result, before the Constitution modifier
+    pub fn hit_die_result(&self, hit_die: u32) -> u32 {
+        match self {
+            HpMethod::Roll => crate::dice::roll_dice(&format!("1d{}", hit_die)).map(|(_, total)| total).unwrap_or(1),
+            HpMethod::Average => average_roll(hit_die),
+        }
+    }
+}
+
+/// What an Ability Score Improvement is spent on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Improvement {
+    /// Two points, either +2 to one ability or +1 to two
+    Abilities(Vec<(AbilityScore, u8)>),
+    Feat(String),
+}
+
+impl Improvement {
+    /// "+2 str", "+1 dex +1 con", "dex con" (one point each), "str" (both points) or "feat Alert"
+    pub fn parse(text: &str) -> Result<Self> {
+        let usage = || AppError::ValidationError(format!("'{}' isn't an improvement. Use '+2 str', '+1 dex +1 con' or 'feat <name>'", text.trim()));
+        let words: Vec<&str> = text.split_whitespace().collect();
+        if let Some((first, feat)) = words.split_first()
+            && first.eq_ignore_ascii_case("feat") {
+            return match feat.join(" ") {
+                feat if feat.is_empty() => Err(usage()),
+                feat => Ok(Improvement::Feat(feat)),
+            };
+        }
+        let two_points = || AppError::ValidationError("An Ability Score Improvement is two points: +2 to one ability or +1 to two".to_string());
+        let mut increases: Vec<(AbilityScore, u8)> = Vec::new();
+        let mut amount = None;
+        let mut spent = 0u32;
+        for word in &words {
+            if let Some(points) = word.strip_prefix('+') {
+                amount = Some(points.parse::<u8>().map_err(|_| usage())?);
+                continue;
+            }
+            let ability = AbilityScore::parse(word).ok_or_else(usage)?;
+            let points = amount.take().unwrap_or(1);
+            // Stop as soon as there's more than two points, before any sum can overflow
+            spent += points as u32;
+            if spent > 2 {
+                return Err(two_points());
+            }
+            match increases.iter_mut().find(|(a, _)| *a == ability) {
+                Some((_, total)) => *total += points,
+                None => increases.push((ability, points)),
+            }
+        }
+        // A lone ability gets both points
+        if let [(_, points)] = increases.as_mut_slice()
+            && words.len() == 1 {
+            *points = 2;
+            spent = 2;
+        }
+        if spent != 2 {
+            return Err(two_points());
+        }
+        Ok(Improvement::Abilities(increases))
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Improvement::Abilities(increases) => increases.iter()
+                .map(|(ability, points)| format!("+{} {}", points, ability.short_name()))
+                .collect::<Vec<_>>()
+                .join(", "),
+            Improvement::Feat(feat) => format!("the {} feat", feat),
+        }
+    }
+}
+
+/// "1st ●●●● 2nd ●●" for slot levels that gained slots
+fn new_slots(before: &[u32; 9], after: &[u32; 9]) -> Option<String> {
+    let gained: Vec<String> = (0..9)
+        .filter(|&i| after[i] > before[i])
+        .map(|i| format!("{} {}", crate::spell_slots::ordinal(i as u32 + 1), "●".repeat((after[i] - before[i]) as usize)))
+        .collect();
+    (!gained.is_empty()).then(|| gained.join(" "))
+}
+
+/// Take a character up one level: hit points from `hit_die_result` plus Constitution,
+/// a new proficiency bonus, the improvement if one is due, and any new spell slots.
+/// Nothing changes if the improvement can't be applied.
+pub fn level_up(character: &mut Character, hit_die_result: u32, improvement: Option<&Improvement>) -> Result<Vec<String>> {
+    let level = character.level.unwrap_or(1);
+    if level >= 20 {
+        return Err(AppError::ValidationError(format!("{} is already level 20", character.name)));
+    }
+    let new_level = level + 1;
+    let class = character.class.clone().unwrap_or_default();
+    if let Some(Improvement::Abilities(increases)) = improvement
+        && let Some((ability, _)) = increases.iter().find(|(ability, points)| character.get_ability_score(*ability).unwrap_or(10) + points > ABILITY_CAP) {
+        return Err(AppError::ValidationError(format!("{} can't go above {}", ability.name(), ABILITY_CAP)));
+    }
+
+    let mut lines = vec![format!("⬆️ {} is now level {}", character.name, new_level)];
+    let con_before = character.get_constitution_modifier();
+    match improvement {
+        Some(Improvement::Abilities(increases)) => {
+            for (ability, points) in increases {
+                let score = character.ability_score_mut(*ability);
+                *score = Some(score.unwrap_or(10) + points);
+            }
+        }
+        Some(Improvement::Feat(feat)) => character.feats.push(feat.clone()),
+        None => {}
+    }
+    if let Some(improvement) = improvement {
+        lines.push(format!("  Ability Score Improvement: {}", improvement.describe()));
+    }
+
+    // A higher Constitution modifier counts for every earlier level too
+    let con_after = character.get_constitution_modifier();
+    let gain = (hit_die_result as i32 + con_after as i32).max(1) + (con_after - con_before) as i32 * level as i32;
+    let max_hp = (character.max_hp.unwrap_or(0) as i32 + gain).clamp(1, u8::MAX as i32) as u8;
+    let hp = (character.hp.unwrap_or(0) as i32 + gain).clamp(0, max_hp as i32) as u8;
+    character.max_hp = Some(max_hp);
+    character.hp = Some(hp);
+    lines.push(format!("  HP +{} (now {}/{})", gain, hp, max_hp));
+
+    let old_bonus = character.prof_bonus.unwrap_or(proficiency_bonus(level));
+    character.level = Some(new_level);
+    character.prof_bonus = Some(proficiency_bonus(new_level));
+    if proficiency_bonus(new_level) != old_bonus {
+        lines.push(format!("  Proficiency bonus +{} → +{}", old_bonus, proficiency_bonus(new_level)));
+    }
+    character.update_passive_perception();
+
+    let slots_before = crate::spell_slots::slots_for(&class, level as u32);
+    crate::resources::refresh_resources(character);
+    crate::pact_magic::refresh_pact_slots(character);
+    crate::spell_slots::refresh_spell_slots(character);
+    if let Some(slots) = new_slots(&slots_before, &crate::spell_slots::slots_for(&class, new_level as u32)) {
+        lines.push(format!("  New spell slots: {}", slots));
+    }
+    Ok(lines)
+}
+
+/// The CLI level-up wizard. Returns the name of the character who levelled, so the
+/// caller can save the sheet.
+pub fn level_up_menu(characters: &mut [Character]) -> Option<String> {
+    if characters.is_empty() {
+        println!("No characters available.");
+        return None;
+    }
+    for (i, character) in characters.iter().enumerate() {
+        println!("{}. {} ({} {})", i + 1, character.name, character.class.as_deref().unwrap_or("Unknown"), character.level.unwrap_or(1));
+    }
+    let choice = read_user_input("Level up which character?").ok()?.parse::<usize>().ok().filter(|n| (1..=characters.len()).contains(n));
+    let Some(character) = choice.map(|n| &mut characters[n - 1]) else {
+        println!("Invalid choice");
+        return None;
+    };
+    let level = character.level.unwrap_or(1);
+    if level >= 20 {
+        println!("{} is already level 20", character.name);
+        return None;
+    }
+
+    let class = character.class.clone().unwrap_or_default();
+    let die = hit_die(&class);
+    let method = loop {
+        match HpMethod::parse(&read_user_input(&format!("Hit points: 1. Roll 1d{}  2. Take the average ({})", die, average_roll(die))).ok()?) {
+            Some(method) => break method,
+            None => println!("Enter 1 or 2"),
+        }
+    };
+    let result = method.hit_die_result(die);
+    if method == HpMethod::Roll {
+        println!("🎲 Rolled {} on 1d{}", result, die);
+    }
+
+    let mut improvement = None;
+    if grants_improvement(&class, level + 1) {
+        println!("Level {} brings an Ability Score Improvement.", level + 1);
+        improvement = loop {
+            match Improvement::parse(&read_user_input("Enter '+2 str', '+1 dex +1 con' or 'feat <name>':").ok()?) {
+                Ok(Improvement::Abilities(increases)) if increases.iter()
+                    .any(|(ability, points)| character.get_ability_score(*ability).unwrap_or(10) + points > ABILITY_CAP) => {
+                    println!("❌ Scores can't go above {}", ABILITY_CAP);
+                }
+                Ok(improvement) => break Some(improvement),
+                Err(e) => println!("❌ {}", e),
+            }
+        };
+    }
+
+    match level_up(character, result, improvement.as_ref()) {
+        Ok(lines) => lines.iter().for_each(|line| println!("{}", line)),
+        Err(e) => {
+            println!("❌ {}", e);
+            return None;
+        }
+    }
+
+    let due = crate::pact_magic::invocations_due(character);
+    if due > 0 {
+        println!("  {}", crate::pact_magic::available_invocations(level as u32 + 1).join(", "));
+        let question = format!("✨ Choose {} eldritch invocation(s), comma-separated (or press Enter to skip):", due);
+        for choice in read_user_input(&question).unwrap_or_default().split(',').filter(|c| !c.trim().is_empty()) {
+            match crate::pact_magic::learn_invocation(character, choice) {
+                Ok(invocation) => println!("✨ {} learns {}", character.name, invocation),
+                Err(e) => println!("❌ {}", e),
+            }
+        }
+    }
+    Some(character.name.clone())
+}
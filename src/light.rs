@@ -0,0 +1,133 @@
+use crate::character::Character;
+use crate::error_handling::{AppError, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Burning light sources are saved with the rest of the campaign
+pub const LIGHTS_FILE: &str = "campaign/lights.ron";
+pub const SECONDS_PER_ROUND: u32 = 6;
+/// A light with this little time left gets a warning
+const GUTTER_WARNING_SECONDS: u32 = 10 * 60;
+
+/// (name, burn time in minutes, bright radius, dim radius). None burns forever.
+const LIGHT_KINDS: &[(&str, Option<u32>, u32, u32)] = &[
+    ("torch", Some(60), 20, 20),
+    ("lantern", Some(360), 30, 30),
+    ("hooded lantern", Some(360), 30, 30),
+    ("bullseye lantern", Some(360), 60, 60),
+    ("candle", Some(60), 5, 5),
+    ("lamp", Some(360), 15, 30),
+    ("light", Some(60), 20, 20),
+    ("continual flame", None, 20, 20),
+];
+
+/// Races that see in the dark without anything noted on the sheet, in feet
+const RACE_DARKVISION: &[(&str, u32)] = &[
+    ("drow", 120), ("duergar", 120), ("deep gnome", 120),
+    ("dwarf", 60), ("elf", 60), ("eladrin", 60), ("sea elf", 60), ("shadar-kai", 60),
+    ("gnome", 60), ("half-elf", 60), ("half-orc", 60), ("orc", 60), ("tiefling", 60),
+    ("aasimar", 60), ("bugbear", 60), ("goblin", 60), ("hobgoblin", 60), ("kobold", 60),
+    ("tabaxi", 60), ("triton", 60), ("genasi", 60), ("owlin", 120),
+];
+
+pub fn light_kinds() -> Vec<&'static str> {
+    LIGHT_KINDS.iter().map(|(name, ..)| *name).collect()
+}
+
+/// A character's darkvision: the sheet's value if set, otherwise their race's
+pub fn darkvision_range(character: &Character) -> u32 {
+    character.darkvision.unwrap_or_else(|| {
+        let race = character.race.as_deref().unwrap_or_default().to_lowercase();
+        RACE_DARKVISION.iter()
+            .find(|(name, _)| *name == race)
+            .map(|(_, range)| *range)
+            .unwrap_or(0)
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LightSource {
+    pub kind: String,
+    pub holder: String,
+    /// None for magical light that never goes out
+    pub seconds_left: Option<u32>,
+    pub bright: u32,
+    pub dim: u32,
+}
+
+impl LightSource {
+    pub fn describe(&self) -> String {
+        let left = match self.seconds_left {
+            Some(seconds) if seconds >= 60 => format!("{}h {:02}m left", seconds / 3600, seconds % 3600 / 60),
+            Some(seconds) => format!("{} rounds left", seconds.div_ceil(SECONDS_PER_ROUND)),
+            None => "permanent".to_string(),
+        };
+        format!("🔥 {}'s {} - {} ft bright / {} ft dim, {}", self.holder, self.kind, self.bright, self.dim, left)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LightTracker {
+    pub sources: Vec<LightSource>,
+}
+
+impl LightTracker {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        ron::de::from_str(&contents).map_err(|e| AppError::ParseError(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create campaign folder")?;
+        }
+        let serialized = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| AppError::ParseError(e.to_string()))?;
+        fs::write(path, serialized).context(format!("Failed to write {}", path.display()))
+    }
+
+    pub fn light(&mut self, kind: &str, holder: &str) -> Result<&LightSource> {
+        let &(name, minutes, bright, dim) = LIGHT_KINDS.iter()
+            .find(|(name, ..)| name.eq_ignore_ascii_case(kind.trim()))
+            .ok_or_else(|| AppError::NotFound(format!("Light source '{}' (known: {})", kind, light_kinds().join(", "))))?;
+        self.sources.push(LightSource {
+            kind: name.to_string(),
+            holder: holder.to_string(),
+            seconds_left: minutes.map(|m| m * 60),
+            bright,
+            dim,
+        });
+        Ok(self.sources.last().expect("light was just pushed"))
+    }
+
+    /// Put out every light a holder carries, or a single kind of light
+    pub fn douse(&mut self, holder_or_kind: &str) -> Vec<LightSource> {
+        let (doused, kept) = std::mem::take(&mut self.sources).into_iter()
+            .partition(|s| s.holder.eq_ignore_ascii_case(holder_or_kind) || s.kind.eq_ignore_ascii_case(holder_or_kind));
+        self.sources = kept;
+        doused
+    }
+
+    /// Burn every light for `seconds`. Returns warnings for lights about to gutter
+    /// out and notices for ones that went out, which are removed.
+    pub fn pass_seconds(&mut self, seconds: u32) -> Vec<String> {
+        let mut notices = Vec::new();
+        for source in &mut self.sources {
+            let Some(left) = source.seconds_left.as_mut() else {
+                continue;
+            };
+            let before = *left;
+            *left = left.saturating_sub(seconds);
+            if *left == 0 {
+                notices.push(format!("🌑 {}'s {} goes out", source.holder, source.kind));
+            } else if before > GUTTER_WARNING_SECONDS && *left <= GUTTER_WARNING_SECONDS {
+                notices.push(format!("🕯️ {}'s {} is guttering - {} minutes left", source.holder, source.kind, left.div_ceil(60)));
+            }
+        }
+        self.sources.retain(|s| s.seconds_left != Some(0));
+        notices
+    }
+}
@@ -0,0 +1,103 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::{fs::{self, File, OpenOptions}, io::Write, path::{Path, PathBuf}, sync::Mutex, time::{SystemTime, UNIX_EPOCH}};
+
+pub const LOG_DIR: &str = "logs";
+pub const LOG_FILE_NAME: &str = "dnd_tools.log";
+/// Rotate the log once it grows past this many bytes
+pub const MAX_LOG_BYTES: u64 = 1024 * 1024;
+/// Number of rotated files kept next to the live log (dnd_tools.log.1, .2, ...)
+pub const MAX_ROTATED_LOGS: usize = 3;
+
+/// Appends log records to a file, rotating it when it gets too large
+struct FileLogger {
+    level: LevelFilter,
+    path: PathBuf,
+    file: Mutex<Option<File>>,
+}
+
+impl FileLogger {
+    fn open(path: &Path) -> Option<File> {
+        OpenOptions::new().create(true).append(true).open(path).ok()
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let Ok(mut guard) = self.file.lock() else {
+            return;
+        };
+
+        let too_big = guard.as_ref()
+            .and_then(|file| file.metadata().ok())
+            .is_some_and(|meta| meta.len() >= MAX_LOG_BYTES);
+        if too_big {
+            *guard = None;
+            rotate_logs(&self.path, MAX_ROTATED_LOGS);
+        }
+        if guard.is_none() {
+            *guard = Self::open(&self.path);
+        }
+
+        if let Some(file) = guard.as_mut() {
+            let _ = writeln!(file, "{}", format_record(now_secs(), record.level(), record.target(), &record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut guard) = self.file.lock()
+            && let Some(file) = guard.as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// One log line: "<unix secs> LEVEL target: message"
+pub fn format_record(timestamp: u64, level: Level, target: &str, message: &str) -> String {
+    format!("{} {:<5} {}: {}", timestamp, level, target, message)
+}
+
+/// Shift dnd_tools.log -> .1 -> .2 ..., dropping the oldest
+pub fn rotate_logs(path: &Path, keep: usize) {
+    let rotated = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+    if keep == 0 {
+        let _ = fs::remove_file(path);
+        return;
+    }
+    let _ = fs::remove_file(rotated(keep));
+    for n in (1..keep).rev() {
+        let _ = fs::rename(rotated(n), rotated(n + 1));
+    }
+    let _ = fs::rename(path, rotated(1));
+}
+
+pub fn log_file_path() -> PathBuf {
+    Path::new(LOG_DIR).join(LOG_FILE_NAME)
+}
+
+/// Install the file logger. `--verbose` logs debug detail, otherwise info and above.
+pub fn init(verbose: bool) {
+    let level = if verbose { LevelFilter::Debug } else { LevelFilter::Info };
+    if fs::create_dir_all(LOG_DIR).is_err() {
+        return;
+    }
+    let path = log_file_path();
+    let logger = FileLogger {
+        level,
+        file: Mutex::new(FileLogger::open(&path)),
+        path,
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(level);
+    }
+}
@@ -12,8 +12,68 @@ mod error_handling;
 mod combat;
 mod tests;
 mod races_classes;
+mod saved_combat;
 mod search;
+mod runtime;
 mod tui;
+mod logging;
+mod campaign;
+mod diagnostics;
+mod legacy;
+mod leveling;
+mod update;
+mod voices;
+mod weather;
+mod ability_scores;
+mod adventure;
+mod scripting;
+mod tables;
+mod rumors;
+mod banner;
+mod player_view;
+mod encounter;
+mod morale;
+mod movement;
+mod pacing;
+mod skill_challenge;
+mod crafting;
+mod bastion;
+mod carousing;
+mod sidekick;
+mod components;
+mod ammo;
+mod light;
+mod supplies;
+mod tactics;
+mod names;
+mod deities;
+mod backgrounds;
+mod starting_equipment;
+mod reference;
+mod xp;
+mod npc_presets;
+mod resources;
+mod pact_magic;
+mod spell_slots;
+mod spell_cards;
+mod prepared;
+mod monsters;
+mod companions;
+mod dc;
+mod registry;
+mod cache;
+mod content;
+mod palette;
+mod attacks;
+mod wealth;
+mod temperament;
+mod balance;
+mod srd;
+mod status_presets;
+mod overlay;
+mod anonymize;
+#[cfg(feature = "rest-api")]
+mod rest_api;
 
 fn clear_console() {
     print!("\x1B[2J\x1B[1;1H");
@@ -31,42 +91,187 @@ fn check_universal_exit(input: &str) {
 }
 
 use character::Character;
-use file_manager::{load_character_files, save_characters, display_single_character, display_all_characters, delete_character_menu};
+use registry::CharacterStore;
+use file_manager::{display_single_character, display_all_characters, delete_character_menu, undelete_character_menu, add_character, purge_trash, trash_max_age_days};
 use initiative::initiative_tracker_mode;
 use dice::{roll_dice_mode};
-use input_handler::create_character;
+use input_handler::{create_character, confirm, stat_or_default};
 use events::Data;
 use combat::{enhanced_initiative_setup, CombatTracker, StatusEffect, Combatant};
 
 
 fn main() -> io::Result<()> {
-    println!("Welcome to DnD tools!");
-    let characters = load_character_files();
-    println!("Loaded {} character sheets.", characters.len());
+    // --quiet skips the startup banner and character summary
+    let quiet = std::env::args().skip(1).any(|arg| arg == "--quiet" || arg == "-q");
+    // --verbose adds debug detail to the log file
+    let verbose = std::env::args().skip(1).any(|arg| arg == "--verbose" || arg == "-v");
+    logging::init(verbose);
+    log::info!("Starting dnd_tools {}", env!("CARGO_PKG_VERSION"));
+
+    // `dnd_tools diagnostics` writes a bug-report bundle and exits
+    if std::env::args().nth(1).as_deref() == Some("diagnostics") {
+        diagnostics::run_diagnostics_command();
+        return Ok(());
+    }
+    // `dnd_tools anonymize <character>` writes a copy safe to attach to an issue and exits
+    if std::env::args().nth(1).as_deref() == Some("anonymize") {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        anonymize::run_anonymize_command(&args);
+        return Ok(());
+    }
+    // `dnd_tools characters [--load]` lists the saved characters and exits
+    if std::env::args().nth(1).as_deref() == Some("characters") {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        file_manager::run_characters_command(&args);
+        return Ok(());
+    }
+    // `dnd_tools campaign export <name>` / `campaign import <file>` hands a campaign to another DM
+    if std::env::args().nth(1).as_deref() == Some("campaign") {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        campaign::run_campaign_command(&args);
+        return Ok(());
+    }
+    // `dnd_tools changelog` shows the notes for the latest release and exits
+    if std::env::args().nth(1).as_deref() == Some("changelog") {
+        update::run_changelog_command();
+        return Ok(());
+    }
+    // `dnd_tools player-view [addr]` opens the read-only screen for players
+    if std::env::args().nth(1).as_deref() == Some("player-view") {
+        let addr = std::env::args().nth(2).unwrap_or_else(|| player_view::DEFAULT_PLAYER_VIEW_ADDR.to_string());
+        if let Err(e) = player_view::run_player_view(&addr) {
+            eprintln!("❌ {}", e);
+        }
+        return Ok(());
+    }
+    // `dnd_tools srd [download]` fetches the SRD data used for offline lookups
+    if std::env::args().nth(1).as_deref() == Some("srd") {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        srd::run_srd_command(&args);
+        return Ok(());
+    }
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    srd::set_offline(srd::offline_requested(&args));
+    let update_rx = (update::update_checks_enabled(&args) && !srd::is_offline()).then(update::spawn_update_check);
+    // The first run online fetches the SRD data, so later searches work without the network
+    let srd_rx = (!srd::is_offline() && !srd::is_installed(std::path::Path::new(srd::SRD_DIR)))
+        .then(srd::spawn_first_run_download);
+
+    if !quiet {
+        println!("Welcome to DnD tools!");
+    }
+    let purged = purge_trash(trash_max_age_days());
+    if purged > 0 && !quiet {
+        println!("Purged {} old character(s) from the trash.", purged);
+    }
+    // Every mode shares this one set of sheets
+    let characters = CharacterStore::load();
+    if !quiet {
+        println!("Loaded {} character sheets.", characters.len());
+        if !characters.is_empty() {
+            println!("{}", file_manager::summary_table(&characters.summaries()));
+        }
+    }
 
     let _events = Data::new();
 
     // Initialize TUI
-    let app = tui::App::new(characters);
-    
+    let mut app = tui::App::new(characters.clone());
+    app.update_rx = update_rx;
+    app.srd_rx = srd_rx;
+    app.campaign_name = campaign::campaign_name_in(std::path::Path::new("."));
+    for e in app.scripts.load_dir(std::path::Path::new(scripting::SCRIPTS_DIR)) {
+        eprintln!("⚠️  {}", e);
+    }
+    if !app.scripts.is_empty() && !quiet {
+        println!("Loaded house-rule scripts: {}", app.scripts.script_names().join(", "));
+    }
+    for e in app.tables.load_dir(std::path::Path::new(tables::TABLES_DIR)) {
+        eprintln!("⚠️  {}", e);
+    }
+    for e in app.names.load_dir(std::path::Path::new(names::NAMES_DIR)) {
+        eprintln!("⚠️  {}", e);
+    }
+    if let Err(e) = app.pantheon.load_file(std::path::Path::new(deities::DEITIES_FILE)) {
+        eprintln!("⚠️  {}", e);
+    }
+    if let Err(e) = app.status_presets.load_file(std::path::Path::new(status_presets::STATUS_PRESETS_FILE)) {
+        eprintln!("⚠️  {}", e);
+    }
+    if let Err(e) = app.backgrounds.load_file(std::path::Path::new(backgrounds::BACKGROUNDS_FILE)) {
+        eprintln!("⚠️  {}", e);
+    }
+    if let Err(e) = app.monsters.load_file(std::path::Path::new(monsters::MONSTERS_FILE)) {
+        eprintln!("⚠️  {}", e);
+    }
+    match dc::DcBands::load_file(std::path::Path::new(dc::DC_FILE)) {
+        Ok(bands) => app.dc_bands = bands,
+        Err(e) => eprintln!("⚠️  {}", e),
+    }
+    match crafting::CraftingLedger::load(std::path::Path::new(crafting::DOWNTIME_FILE)) {
+        Ok(ledger) => {
+            if ledger.day > 0 {
+                app.in_game_date = Some(ledger.date_label());
+            }
+            app.crafting = ledger;
+        }
+        Err(e) => eprintln!("⚠️  {}", e),
+    }
+    match bastion::Bastions::load(std::path::Path::new(bastion::BASTIONS_FILE)) {
+        Ok(bastions) => app.bastions = bastions,
+        Err(e) => eprintln!("⚠️  {}", e),
+    }
+    match light::LightTracker::load(std::path::Path::new(light::LIGHTS_FILE)) {
+        Ok(lights) => app.lights = lights,
+        Err(e) => eprintln!("⚠️  {}", e),
+    }
+    match supplies::Supplies::load(std::path::Path::new(supplies::SUPPLIES_FILE)) {
+        Ok(supplies) => app.supplies = supplies,
+        Err(e) => eprintln!("⚠️  {}", e),
+    }
+    match weather::Weather::load(std::path::Path::new(weather::WEATHER_FILE)) {
+        Ok(weather) => app.weather = weather,
+        Err(e) => eprintln!("⚠️  {}", e),
+    }
+    match adventure::Adventure::load(std::path::Path::new(adventure::ADVENTURE_FILE)) {
+        Ok(adventure) => app.adventure = adventure,
+        Err(e) => eprintln!("⚠️  {}", e),
+    }
+    match sidekick::Sidekicks::load(std::path::Path::new(sidekick::SIDEKICKS_FILE)) {
+        Ok(sidekicks) => {
+            if !sidekicks.sidekicks.is_empty() && !quiet {
+                let level = sidekick::party_level(&app.characters.read());
+                let names: Vec<String> = sidekicks.sidekicks.iter().map(|s| format!("{} ({} {})", s.name, s.class, level)).collect();
+                println!("Sidekicks: {}", names.join(", "));
+            }
+            app.sidekicks = sidekicks;
+        }
+        Err(e) => eprintln!("⚠️  {}", e),
+    }
+
+    // --cli skips the TUI and goes straight to the numbered menus
+    if args.iter().any(|arg| arg == "--cli") {
+        return run_cli_mode(characters);
+    }
     match tui::run_tui(app) {
         Ok(final_app) => {
             // Save any character changes before exiting
-            save_characters(final_app.characters);
+            final_app.characters.save_all();
             println!("Goodbye! 👋");
         }
         Err(e) => {
+            log::error!("TUI exited with error: {}", e);
             eprintln!("Error running TUI: {}", e);
             // Fall back to CLI mode if TUI fails
             println!("Falling back to CLI mode...");
-            run_cli_mode(load_character_files())?;
+            run_cli_mode(characters)?;
         }
     }
     
     Ok(())
 }
 
-fn run_cli_mode(mut characters: Vec<Character>) -> io::Result<()> {
+fn run_cli_mode(characters: CharacterStore) -> io::Result<()> {
     println!("Running in CLI mode...");
     
     let mut ending = false;
@@ -83,8 +288,8 @@ fn run_cli_mode(mut characters: Vec<Character>) -> io::Result<()> {
         check_universal_exit(&buffer);
         
         match buffer.trim() {
-            "1" => characters_menu(&mut characters),
-            "2" => tools_menu(),
+            "1" => characters_menu(&characters),
+            "2" => tools_menu(&characters),
             "3" => {
                 if exit_menu() {
                     ending = true;
@@ -96,13 +301,15 @@ fn run_cli_mode(mut characters: Vec<Character>) -> io::Result<()> {
     Ok(())
 }
 
-fn characters_menu(characters: &mut Vec<Character>) {
+fn characters_menu(characters: &CharacterStore) {
     loop {
         println!("\n=== Characters Menu ===");
         println!("1. Creation");
         println!("2. Display single character");
         println!("3. Display all characters");
         println!("4. Character deletion");
+        println!("5. Undelete character");
+        println!("6. Level up");
         println!("0. Back to main menu");
         
         let mut buffer = String::new();
@@ -113,20 +320,36 @@ fn characters_menu(characters: &mut Vec<Character>) {
         
         match buffer.trim() {
             "1" => {
-                let new_c = create_character();
-                characters.push(new_c);
-                save_characters(characters.clone());
+                let mut backgrounds = backgrounds::BackgroundBook::default();
+                if let Err(e) = backgrounds.load_file(std::path::Path::new(backgrounds::BACKGROUNDS_FILE)) {
+                    println!("⚠️  {}", e);
+                }
+                let new_c = create_character(&backgrounds);
+                let added = add_character(&mut characters.write(), new_c);
+                if added {
+                    characters.save_all();
+                }
+            }
+            "2" => display_single_character(&characters.read()),
+            "3" => display_all_characters(&characters.read()),
+            "4" => delete_character_menu(&mut characters.write()),
+            "5" => {
+                undelete_character_menu(&mut characters.write());
+                characters.save_all();
+            }
+            "6" => {
+                let levelled = leveling::level_up_menu(&mut characters.write());
+                if let Some(character) = levelled.and_then(|name| characters.find(&name).map(|c| c.clone())) {
+                    file_manager::save_character(character.name.clone(), character);
+                }
             }
-            "2" => display_single_character(characters),
-            "3" => display_all_characters(characters),
-            "4" => delete_character_menu(characters),
             "0" => break,
             _ => println!("Invalid input"),
         }
     }
 }
 
-fn tools_menu() {
+fn tools_menu(characters: &CharacterStore) {
     loop {
         println!("\n=== Tools Menu ===");
         println!("1. Initiative tracker");
@@ -134,6 +357,8 @@ fn tools_menu() {
         println!("3. Dice");
         println!("4. Combat tracker");
         println!("5. Search D&D 5e API");
+        println!("6. Create diagnostics bundle");
+        println!("7. Encounter generator");
         println!("0. Back to main menu");
         
         let mut buffer = String::new();
@@ -146,8 +371,10 @@ fn tools_menu() {
             "1" => initiative_tracker_mode(),
             "2" => npc_randomizer_mode(),
             "3" => roll_dice_mode(),
-            "4" => combat_tracker_mode(),
+            "4" => combat_tracker_mode(characters),
             "5" => search_mode(),
+            "6" => diagnostics::run_diagnostics_command(),
+            "7" => encounter_generator_mode(characters),
             "0" => break,
             _ => println!("Invalid input"),
         }
@@ -502,6 +729,10 @@ fn save_generated_npc(race: &str, class: &str, ac: u8, hp: u8, speed: u8, str: u
     }
     
     let path = format!("npcs/{}.txt", name);
+    if std::path::Path::new(&path).exists() && !confirm(&format!("{} already exists. Overwrite?", path), name) {
+        println!("Save cancelled.");
+        return;
+    }
     
     let npc_data = format!(
         "Name: {}\nRace: {}\nClass: {}\nAC: {}\nHP: {}\nSpeed: {}\nSTR: {}\nDEX: {}\nCON: {}\nINT: {}\nWIS: {}\nCHA: {}",
@@ -521,12 +752,36 @@ fn roll_3d6() -> u8 {
     (roll1 + roll2 + roll3).clamp(1, 20)
 }
 
-pub fn combat_tracker_mode() {
+pub fn combat_tracker_mode(characters: &CharacterStore) {
     println!("\n⚔️  Enhanced Combat Tracker ⚔️");
+
+    // A fight left mid-round last session can pick up where it stopped
+    let dir = std::path::Path::new(saved_combat::ENCOUNTERS_DIR);
+    let saved = saved_combat::list_encounters_in(dir);
+    if !saved.is_empty() {
+        println!("📂 Saved encounters: {}", saved.join(", "));
+        let name = input_handler::read_user_input("Type one to resume it, or press Enter for a new fight:").unwrap_or_default();
+        if !name.is_empty() {
+            let loaded = saved_combat::load_encounter_in(dir, &name, &characters.read());
+            match loaded {
+                Ok((tracker, warnings)) => {
+                    println!("{}", saved_combat::resume_summary(&name, &tracker));
+                    for warning in warnings {
+                        println!("{}", warning);
+                    }
+                    tracker.write_back(&mut characters.write());
+                    tracker.display_initiative_order();
+                    enhanced_combat_mode(tracker, characters, true);
+                    return;
+                }
+                Err(e) => println!("❌ Could not load the encounter: {}. Starting a new fight.", e),
+            }
+        }
+    }
     println!("Starting with Initiative setup...\n");
     
     // Set up initiative with enhanced features
-    let mut combat_tracker = enhanced_initiative_setup();
+    let mut combat_tracker = enhanced_initiative_setup(&mut characters.write());
     
     if combat_tracker.combatants.is_empty() {
         println!("❌ No combatants added. Exiting combat tracker.");
@@ -543,48 +798,163 @@ pub fn combat_tracker_mode() {
     }
     
     if buffer.trim().to_lowercase() == "y" || buffer.trim().to_lowercase() == "yes" {
-        // Ask for current HP for all combatants
-        println!("\n💖 Please confirm current HP for all combatants:");
-        for combatant in &mut combat_tracker.combatants {
-            println!("Current HP for {} (max: {}): ", combatant.name, combatant.max_hp);
+        // Warm the search cache so lookups still work if the network drops mid-fight
+        let targets = search::prefetch_targets(&characters.read(), &combat_tracker.combatants);
+        if !targets.is_empty() {
+            println!("📥 Fetching {} spell and monster page(s) in the background", targets.len());
+            search::spawn_prefetch(targets);
+        }
+        // One review of everyone's saved HP; only the entries that are wrong need typing
+        println!("\n💖 Starting HP:");
+        loop {
+            for line in combat_tracker.hp_review_lines() {
+                println!("{}", line);
+            }
+            println!("Press Enter to accept, or override as <#|name> <hp>, separated by commas (e.g. '2 5, Aria 12'):");
             let mut hp_input = String::new();
-            if io::stdin().read_line(&mut hp_input).is_ok() {
-                if let Ok(hp) = hp_input.trim().parse::<i32>() {
-                    combatant.current_hp = hp;
-                    println!("✅ Set {}'s HP to {}", combatant.name, hp);
-                } else {
-                    println!("Invalid input, keeping current HP: {}", combatant.current_hp);
-                }
+            if io::stdin().read_line(&mut hp_input).is_err() || hp_input.trim().is_empty() {
+                break;
+            }
+            for result in combat_tracker.apply_hp_overrides(&hp_input) {
+                println!("{}", result);
             }
         }
         
-        enhanced_combat_mode(combat_tracker);
+        enhanced_combat_mode(combat_tracker, characters, false);
+    }
+}
+
+/// Build an encounter from the party's XP budget and optionally fight it right away
+fn encounter_generator_mode(characters: &CharacterStore) {
+    println!("\n👹 Encounter Generator 👹");
+    let (default_size, default_level) = {
+        let party = characters.read();
+        (party.len().max(1), sidekick::party_level(&party))
+    };
+    let size = match input_handler::read_user_input(&format!("Party size [{}]:", default_size)) {
+        Ok(input) if input.is_empty() => default_size,
+        Ok(input) => match input.parse::<usize>() {
+            Ok(size) if size > 0 => size,
+            _ => {
+                println!("❌ Party size must be a positive number");
+                return;
+            }
+        },
+        Err(_) => return,
+    };
+    let level = match input_handler::read_user_input(&format!("Party level [{}]:", default_level)) {
+        Ok(input) if input.is_empty() => default_level,
+        Ok(input) => match input.parse::<u8>() {
+            Ok(level) if (1..=20).contains(&level) => level,
+            _ => {
+                println!("❌ Party level must be between 1 and 20");
+                return;
+            }
+        },
+        Err(_) => return,
+    };
+    let Some(difficulty) = input_handler::read_user_input("Difficulty (easy/medium/hard/deadly) [medium]:").ok()
+        .and_then(|input| if input.is_empty() { Some(encounter::Difficulty::Medium) } else { encounter::Difficulty::parse(&input) }) else {
+        println!("❌ Difficulty must be easy, medium, hard or deadly");
+        return;
+    };
+
+    let mut index = monsters::MonsterIndex::default();
+    if let Err(e) = index.load_file(std::path::Path::new(monsters::MONSTERS_FILE)) {
+        log::warn!("Failed to load campaign monsters: {}", e);
+    }
+    let encounter = match encounter::generate(&index, size, level, difficulty, &mut rand::rng()) {
+        Ok(encounter) => encounter,
+        Err(e) => {
+            println!("❌ Could not build an encounter: {}", e);
+            return;
+        }
+    };
+    for line in encounter.lines() {
+        println!("{}", line);
+    }
+
+    let start = input_handler::read_user_input("\n⚔️ Start this fight in the combat tracker? (y/n)").unwrap_or_default();
+    if !input_handler::is_confirmation(&start, "fight") {
+        return;
+    }
+    let mut combat_tracker = CombatTracker::new();
+    for combatant in encounter.to_combatants(&mut rand::rng()) {
+        combat_tracker.add_combatant(combatant);
+    }
+    // The party rolls in like any other fight
+    for character in characters.read().iter() {
+        let initiative = rand::random_range(1..=20) + character.get_dexterity_modifier() as i32;
+        combat_tracker.add_combatant(Combatant::from_character(character, initiative));
     }
+    combat_tracker.display_initiative_order();
+    enhanced_combat_mode(combat_tracker, characters, false);
 }
 
-fn enhanced_combat_mode(mut combat_tracker: CombatTracker) {
+/// The CLI combat loop. A `resumed` fight carries on with the saved turn instead of starting a new one.
+fn enhanced_combat_mode(mut combat_tracker: CombatTracker, characters: &CharacterStore, resumed: bool) {
     println!("\n⚔️  COMBAT MODE ACTIVATED ⚔️");
     println!("═══════════════════════════════════════════════════════════");
     println!("Available commands:");
     println!("  📊 stats [name] - Show character stats");
-    println!("  ⚔️  attack <target> - Roll attack vs target's AC");
+    println!("  ⚔️  attack <target> [weapon] [damage type] [adv|dis] - Roll attack vs target's AC (e.g., attack troll longsword adv)");
+    println!("  🛡️ resist|vulnerable|immune <name> <types...|none> - Set a combatant's damage defenses");
     println!("  🎭 status [add|remove|list] [self|name] <status> - Manage status effects");
-    println!("  🎲 save [ability] [self|name] - Make saving throw (e.g., save wis Gandalf)");
+    println!("  📚 status presets - List status presets (campaign/statuses.toml)");
+    println!("  🎲 save [ability] [self|name] [adv|dis] - Make saving throw (e.g., save dex self dis)");
+    println!("  💀 deathsave <name> - Roll a death save for a party member at 0 HP");
+    println!("  📖 ref <monster> - Condensed stat block, cached for next time");
     println!("  🔍 search <query> - Search D&D 5e API (returns to combat after)");
     println!("  ➡️  next|continue - Advance to next combatant");
     println!("  ⬅️  back - Go back to previous combatant's turn");
     println!("  ➕ insert <name> - Add new combatant mid-fight");
+    println!("  👹 add monster <name> [count] - Add a monster from the wiki with its AC, HP and initiative");
     println!("  🗑️  remove <name> - Remove combatant from combat");
+    println!("  🙈 hide [name] / reveal <name> - Keep an ambusher out of the initiative list");
+    println!("  🔢 renumber [monster] / token <name> <n> - Number monsters to match numbered minis");
     println!("  💾 save <npc_name> - Save NPC to npcs/ directory");
+    println!("  📂 save encounter <name> / load encounter <name> - Stop mid-fight and resume next session");
+    println!("  📝 export combat <file> - Write the fight as Markdown for your notes, or to carry on with on paper");
+    println!("  🕶️ anonymize [character] - Copy the fight (or a sheet) with names and notes removed, for bug reports");
+    println!("  🗣️ voice [save <npc> | <npc>] - Suggest a voice for an improvised NPC, or keep/recall one");
+    println!("  🎭 temperament [save <npc> | <npc>] - Suggest an alignment, temperament and behavior hints, or keep/recall one");
+    println!("  🌧️ weather [roll|clear|<conditions>] - Show or set the weather and the rules it brings");
+    println!("  📖 scene [n|add|done|note|npc|encounter...] - The adventure outline and each scene's prep");
     println!("  🔍 show|list - Display current initiative order");
     println!("  ❓ help - Show this help");
     println!("  🚪 quit - Exit combat mode (auto-saves characters)");
     println!("═══════════════════════════════════════════════════════════");
     
-    // Start the first turn
-    if let Some(current_combatant) = combat_tracker.next_turn() {
+    // House-rule scripts, called at turn start, on damage and on attack rolls as in the TUI
+    let mut scripts = scripting::ScriptEngine::new();
+    for e in scripts.load_dir(std::path::Path::new(scripting::SCRIPTS_DIR)) {
+        println!("⚠️  {}", e);
+    }
+
+    if resumed {
+        if let Some(current_combatant) = combat_tracker.combatants.get(combat_tracker.current_turn) {
+            println!("\n🎯 Picking up with {}", current_combatant.name);
+            current_combatant.display_stats(&characters.read());
+        }
+        (combat_tracker.current_turn, combat_tracker.round_number) = saved_combat::saved_turn_to_cli(
+            combat_tracker.current_turn, combat_tracker.round_number, combat_tracker.combatants.len());
+    } else if let Some(current_combatant) = combat_tracker.next_turn() {
+        // Start the first turn
         println!("\n🎯 Starting combat with {}", current_combatant.name);
-        current_combatant.display_stats();
+        current_combatant.display_stats(&characters.read());
+        run_turn_start_hook(&scripts, &mut combat_tracker);
+    }
+    // Last `voice` suggestion, for `voice save <npc>`
+    let mut last_voice = None;
+    // Last `temperament` suggestion, for `temperament save <npc>`
+    let mut last_temperament = None;
+    // Encounter tables, for reinforcements when the fight is too easy
+    let mut tables = tables::TableRegistry::new();
+    tables.load_dir(std::path::Path::new(tables::TABLES_DIR));
+    // Status presets, so `status add bob burning` brings the preset's details
+    let mut status_presets = status_presets::StatusLibrary::default();
+    if let Err(e) = status_presets.load_file(std::path::Path::new(status_presets::STATUS_PRESETS_FILE)) {
+        println!("⚠️  {}", e);
     }
     
     loop {
@@ -600,10 +970,19 @@ fn enhanced_combat_mode(mut combat_tracker: CombatTracker) {
         let command = parts.get(0).map(|s| s.to_lowercase()).unwrap_or_default();
         
         match command.as_str() {
+            "resist" | "vulnerable" | "immune" => {
+                match parts.get(1) {
+                    Some(name) if parts.len() > 2 => match combat_tracker.set_defenses(name, &command, &parts[2..]) {
+                        Ok(message) => println!("{}", message),
+                        Err(e) => println!("❌ {}", e),
+                    },
+                    _ => println!("Usage: {} <name> <damage types...|none>", command),
+                }
+            }
             "stats" => {
                 if let Some(name) = parts.get(1) {
                     if let Some(combatant) = combat_tracker.get_combatant(name) {
-                        combatant.display_stats();
+                        combatant.display_stats(&characters.read());
                     } else {
                         println!("❌ Combatant '{}' not found", name);
                     }
@@ -620,46 +999,147 @@ fn enhanced_combat_mode(mut combat_tracker: CombatTracker) {
                     println!("Example: search fireball");
                 }
             }
+            "cond" | "condition" | "rule" | "rules" | "xp" | "cr" => {
+                if let Some(lines) = reference::quick_reference(input) {
+                    for line in lines {
+                        println!("{}", line);
+                    }
+                }
+            }
+            "voice" => {
+                for line in voices::voice_command(std::path::Path::new(file_manager::NPCS_DIR), &parts[1..], &mut last_voice, &mut rand::rng()) {
+                    println!("{}", line);
+                }
+            }
+            "temperament" => {
+                for line in temperament::temperament_command(std::path::Path::new(file_manager::NPCS_DIR), &parts[1..], &mut last_temperament, &mut rand::rng()) {
+                    println!("{}", line);
+                }
+            }
+            "weather" => {
+                let path = std::path::Path::new(weather::WEATHER_FILE);
+                let mut current = weather::Weather::load(path).unwrap_or_default();
+                let (lines, changed) = weather::weather_command(&mut current, &parts[1..], &mut rand::rng());
+                for line in lines {
+                    println!("{}", line);
+                }
+                if changed && let Err(e) = current.save(path) {
+                    println!("⚠️ Could not save the weather: {}", e);
+                }
+            }
+            "scene" => {
+                let path = std::path::Path::new(adventure::ADVENTURE_FILE);
+                let mut outline = adventure::Adventure::load(path).unwrap_or_default();
+                if parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("run")) {
+                    match outline.encounter_to_run(parts.get(2).copied()) {
+                        Ok(name) => println!("Type 'load encounter {}' to start it", name),
+                        Err(e) => println!("❌ {}", e),
+                    }
+                    continue;
+                }
+                let (lines, changed) = adventure::scene_command(&mut outline, &parts[1..],
+                    std::path::Path::new(file_manager::NPCS_DIR), std::path::Path::new(saved_combat::ENCOUNTERS_DIR));
+                for line in lines {
+                    println!("{}", line);
+                }
+                if changed && let Err(e) = outline.save(path) {
+                    println!("⚠️ Could not save the adventure outline: {}", e);
+                }
+            }
+            "ref" | "reference" => {
+                if parts.len() > 1 {
+                    handle_reference_in_combat(&parts[1..].join(" "));
+                } else {
+                    println!("Usage: ref <monster>");
+                }
+            }
             "attack" => {
-                if let Some(target_name) = parts.get(1) {
-                    handle_attack_command(&mut combat_tracker, target_name);
+                let (args, edge) = combat::split_edge(&parts[1..]);
+                if let Some((target_name, rest)) = args.split_first() {
+                    let (weapon, kind) = combat::split_damage_type(rest);
+                    let weapon = (!weapon.is_empty()).then(|| weapon.join(" "));
+                    handle_attack_command(&mut combat_tracker, &scripts, target_name, weapon.as_deref(), kind, edge, &characters.read());
+                    let mut messages = combat_tracker.end_broken_concentration();
+                    messages.extend(combat_tracker.update_death_saves());
+                    for message in messages {
+                        println!("{}", message);
+                    }
                 } else {
-                    println!("Usage: attack <target>");
+                    println!("Usage: attack <target> [weapon] [damage type] [adv|dis]");
                 }
             }
             "status" => {
-                handle_status_command(&mut combat_tracker, &parts[1..]);
+                handle_status_command(&mut combat_tracker, &parts[1..], &status_presets);
             }
             "next" | "continue" => {
                 clear_console();
+                for line in combat_tracker.reroll_for_new_round(&characters.read(), &mut rand::rng()) {
+                    println!("{}", line);
+                }
                 if let Some(next_combatant) = combat_tracker.next_turn() {
                     println!("\n🎯 It's {}'s turn!", next_combatant.name);
-                    next_combatant.display_stats();
+                    next_combatant.display_stats(&characters.read());
+                    run_turn_start_hook(&scripts, &mut combat_tracker);
                 } else {
                     println!("❌ No combatants available for turns");
                 }
+                for line in balance::turn_hints(&mut combat_tracker, &tables, &mut rand::rng()) {
+                    println!("{}", line);
+                }
             }
             "back" => {
                 if combat_tracker.previous_turn() {
                     clear_console();
                     if let Some(prev_combatant) = combat_tracker.get_current_combatant() {
                         println!("\n⬅️  Going back to {}'s turn!", prev_combatant.name);
-                        prev_combatant.display_stats();
+                        prev_combatant.display_stats(&characters.read());
                     }
                 } else {
                     println!("❌ Cannot go back further");
                 }
             }
+            "init" if parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("import")) && parts.len() > 2 => {
+                let path = parts[2..].join(" ");
+                match saved_combat::import_initiative_file(std::path::Path::new(&path), &mut combat_tracker, &characters.read()) {
+                    Ok(lines) => {
+                        for line in lines {
+                            println!("{}", line);
+                        }
+                    }
+                    Err(e) => println!("❌ {}", e),
+                }
+            }
+            "add" if parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("monster")) => {
+                let (name, count) = match parts.last().and_then(|p| p.parse::<usize>().ok()) {
+                    Some(count) if parts.len() > 3 => (parts[2..parts.len() - 1].join(" "), count.clamp(1, 20)),
+                    _ => (parts[2..].join(" "), 1),
+                };
+                if name.is_empty() {
+                    println!("Usage: add monster <name> [count]");
+                    continue;
+                }
+                match search::fetch_monster(&name) {
+                    Ok(monster) => {
+                        for line in monster.add_to_tracker(&mut combat_tracker, count, &mut rand::rng()) {
+                            println!("{}", line);
+                        }
+                    }
+                    Err(e) => println!("❌ Could not add {}: {}", name, e),
+                }
+            }
             "insert" => {
                 if let Some(name) = parts.get(1) {
-                    handle_insert_combatant(&mut combat_tracker, name);
+                    handle_insert_combatant(&mut combat_tracker, name, &characters.read());
                 } else {
                     println!("Usage: insert <combatant_name>");
                 }
             }
             "remove" => {
                 if let Some(name) = parts.get(1) {
-                    if combat_tracker.remove_combatant(name) {
+                    if combat_tracker.get_combatant(name).is_some()
+                        && !confirm(&format!("Remove {} from combat?", name), name) {
+                        println!("Removal cancelled.");
+                    } else if combat_tracker.remove_combatant(name) {
                         println!("✅ Removed {} from combat", name);
                         combat_tracker.display_initiative_order();
                     } else {
@@ -669,7 +1149,80 @@ fn enhanced_combat_mode(mut combat_tracker: CombatTracker) {
                     println!("Usage: remove <name>");
                 }
             }
+            "deathsave" | "ds" => {
+                let name = parts[1..].join(" ");
+                match dice::roll_dice_with_crits("1d20") {
+                    Ok(roll) => match combat_tracker.record_death_save(&name, roll.total) {
+                        Ok(message) => println!("{}", message),
+                        Err(e) => println!("❌ {}", e),
+                    },
+                    Err(e) => println!("❌ Error rolling death save: {}", e),
+                }
+            }
+            "died" | "death" => {
+                let name = parts[1..].join(" ");
+                handle_death(&mut combat_tracker, characters, &name);
+            }
+            "save" | "load" if parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("encounter")) => {
+                let name = parts[2..].join(" ");
+                let dir = std::path::Path::new(saved_combat::ENCOUNTERS_DIR);
+                if name.is_empty() {
+                    println!("Usage: {} encounter <name>", command);
+                } else if command == "save" {
+                    let cli_turn = (combat_tracker.current_turn, combat_tracker.round_number);
+                    (combat_tracker.current_turn, combat_tracker.round_number) =
+                        saved_combat::cli_turn_to_saved(cli_turn.0, cli_turn.1, combat_tracker.combatants.len());
+                    match saved_combat::save_encounter_in(dir, &name, &combat_tracker) {
+                        Ok(path) => println!("💾 Saved the fight (round {}) to {}", combat_tracker.round_number, path.display()),
+                        Err(e) => println!("❌ Could not save the encounter: {}", e),
+                    }
+                    (combat_tracker.current_turn, combat_tracker.round_number) = cli_turn;
+                } else {
+                    let loaded = saved_combat::load_encounter_in(dir, &name, &characters.read());
+                    match loaded {
+                        Ok((tracker, warnings)) => {
+                            println!("{}", saved_combat::resume_summary(&name, &tracker));
+                            tracker.write_back(&mut characters.write());
+                            combat_tracker = tracker;
+                            for warning in warnings {
+                                println!("{}", warning);
+                            }
+                            combat_tracker.display_initiative_order();
+                            if let Some(current) = combat_tracker.combatants.get(combat_tracker.current_turn) {
+                                println!("\n🎯 Picking up with {}", current.name);
+                            }
+                            (combat_tracker.current_turn, combat_tracker.round_number) = saved_combat::saved_turn_to_cli(
+                                combat_tracker.current_turn, combat_tracker.round_number, combat_tracker.combatants.len());
+                        }
+                        Err(e) => println!("❌ Could not load the encounter: {}", e),
+                    }
+                }
+            }
+            "anonymize" => {
+                // Written the way saved fights are, like `export combat`
+                let cli_turn = (combat_tracker.current_turn, combat_tracker.round_number);
+                (combat_tracker.current_turn, combat_tracker.round_number) =
+                    saved_combat::cli_turn_to_saved(cli_turn.0, cli_turn.1, combat_tracker.combatants.len());
+                println!("{}", anonymize::anonymize_command(&parts[1..], &characters.read(), Some(&combat_tracker)));
+                (combat_tracker.current_turn, combat_tracker.round_number) = cli_turn;
+            }
+            "export" if parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("combat")) => {
+                let file = parts[2..].join(" ");
+                if file.is_empty() {
+                    println!("Usage: export combat <file>");
+                } else {
+                    let cli_turn = (combat_tracker.current_turn, combat_tracker.round_number);
+                    (combat_tracker.current_turn, combat_tracker.round_number) =
+                        saved_combat::cli_turn_to_saved(cli_turn.0, cli_turn.1, combat_tracker.combatants.len());
+                    match saved_combat::export_combat(std::path::Path::new(&file), &combat_tracker, &characters.read()) {
+                        Ok(path) => println!("📝 Wrote the fight (round {}) to {}", combat_tracker.round_number, path.display()),
+                        Err(e) => println!("❌ Could not export the fight: {}", e),
+                    }
+                    (combat_tracker.current_turn, combat_tracker.round_number) = cli_turn;
+                }
+            }
             "save" => {
+                let (parts, edge) = combat::split_edge(&parts);
                 if parts.len() >= 2 {
                     // Check if this is a saving throw or NPC save
                     let potential_ability = parts[1].to_lowercase();
@@ -699,43 +1252,126 @@ fn enhanced_combat_mode(mut combat_tracker: CombatTracker) {
                             target_name
                         };
                         
-                        match combat_tracker.make_saving_throw(&actual_target, ability) {
+                        match combat_tracker.make_saving_throw(&actual_target, ability, &characters.read(), edge) {
                             Ok(result) => println!("{}", result),
                             Err(e) => println!("❌ {}", e),
                         }
                     } else {
                         // This is an NPC save command
                         let npc_name = parts[1];
+                        if std::path::Path::new(&format!("npcs/{}.txt", npc_name)).exists()
+                            && !confirm(&format!("npcs/{}.txt already exists. Overwrite?", npc_name), npc_name) {
+                            println!("Save cancelled.");
+                            continue;
+                        }
                         if let Err(e) = combat_tracker.save_npc(npc_name) {
                             println!("❌ Failed to save NPC: {}", e);
                         }
                     }
                 } else {
                     println!("Usage: save [ability] [self|name] for saving throws, or save <npc_name> for NPC saving");
-                    println!("Examples: save wis Gandalf, save dex self, save Orc");
+                    println!("Examples: save wis Gandalf, save dex self dis, save Orc");
                 }
             }
             "show" | "list" => {
                 combat_tracker.display_initiative_order();
             }
+            "hide" | "reveal" => {
+                let hidden = command == "hide";
+                if parts.len() < 2 {
+                    let names = combat_tracker.hidden_names();
+                    if !hidden || names.is_empty() {
+                        println!("Usage: hide <name> / reveal <name>");
+                    } else {
+                        println!("🙈 Hidden: {}", names.join(", "));
+                    }
+                    continue;
+                }
+                match combat_tracker.set_hidden(&parts[1..].join(" "), hidden) {
+                    Ok(name) if hidden => println!("🙈 {} is hidden from the initiative order", name),
+                    Ok(name) => println!("👁️ {} is revealed!", name),
+                    Err(e) => println!("❌ {}", e),
+                }
+            }
+            "attacks" => {
+                for line in combat_tracker.attacks_command(&parts[1..], &characters.read()) {
+                    println!("{}", line);
+                }
+            }
+            "concentrate" | "conc" => {
+                for line in combat_tracker.concentrate_command(&parts[1..], &characters.read()) {
+                    println!("{}", line);
+                }
+            }
+            "adjust" => {
+                for line in balance::adjust_command(&mut combat_tracker, &parts[1..], &tables, &mut rand::rng()) {
+                    println!("{}", line);
+                }
+            }
+            "legendary" => {
+                for line in combat_tracker.legendary_command(&parts[1..]) {
+                    println!("{}", line);
+                }
+            }
+            "lair" => {
+                for line in combat_tracker.lair_command(&parts[1..]) {
+                    println!("{}", line);
+                }
+            }
+            "renumber" | "token" => {
+                for line in combat_tracker.token_command(&command, &parts[1..]) {
+                    println!("{}", line);
+                }
+            }
+            "reroll" => {
+                combat_tracker.reroll_each_round = match parts.get(1).map(|a| a.to_lowercase()).as_deref() {
+                    Some("on") => true,
+                    Some("off") => false,
+                    _ => !combat_tracker.reroll_each_round,
+                };
+                if combat_tracker.reroll_each_round {
+                    println!("🎲 Initiative will be re-rolled (d20+DEX) at the top of each round");
+                } else {
+                    println!("Initiative stays the same from round to round");
+                }
+            }
             "quit" | "q" => {
                 println!("💀 Exiting combat mode...");
-                combat_tracker.save_characters_on_exit();
+                combat_tracker.save_characters_on_exit(&mut characters.write());
                 break;
             }
             "help" | "h" => {
                 println!("Combat Mode Commands:");
                 println!("  stats [name] - Show character stats");
-                println!("  attack <target> - Roll d20 attack vs target's AC");
+                println!("  attack <target> [weapon] [adv|dis] - Roll the current combatant's attack vs target's AC");
+                println!("  attacks [name] [add Scimitar +4, 1d6+2 slashing|remove <attack>] - Attack bonuses and damage");
+                println!("  concentrate [name] [<spell> | end | roll [adv|dis] | <total>] - Track concentration and its CON saves");
+                println!("  adjust [table <name|none>] - Suggest adjustments when the fight is too easy or too hard");
+                println!("  legendary [name] [count | <action> [cost]] - Set or spend legendary actions");
+                println!("  lair [name|off] - Lair action reminder on initiative 20");
                 println!("  status [add|remove|list] [self|name] <status> - Manage status effects");
+                println!("  status presets - List status presets; 'status add bob burning' uses one");
+                println!("  ref <monster> - Print a condensed stat block without leaving combat");
+                println!("  cond <condition> - Print the rules for a condition");
+                println!("  rule <topic> - Summarise grapple, shove, hide, help, two-weapon, mounted or underwater rules");
+                println!("  credits - Show content sources and licenses");
+                println!("  xp for cr <cr> | cr for xp <xp> | xp adjust <party> <cr...> - XP and CR calculators");
                 println!("  search <query> - Search D&D 5e API (returns to combat after)");
-                println!("  save [ability] [self|name] - Make saving throw (e.g., save wis Gandalf)");
+                println!("  save [ability] [self|name] [adv|dis] - Make saving throw (e.g., save dex self dis)");
                 println!("  save <npc_name> - Save NPC stats to npcs/ directory");
                 println!("  next|continue - Advance to next combatant");
                 println!("  back - Go back to previous combatant's turn");
                 println!("  insert <name> - Add new combatant mid-fight");
+                println!("  init import <file.csv> - Add name,initiative,hp,ac rows rolled during prep");
+                println!("  add monster <name> [count] - Look a monster up and add it with its stats and rolled initiative");
                 println!("  remove <name> - Remove combatant from combat loop");
+                println!("  died <character> - Record a party character's death, archive their sheet and log it");
+                println!("  hide [name] - Hide a combatant from the initiative list (no name lists hidden ones)");
+                println!("  reveal <name> - Show a hidden combatant in its initiative slot");
+                println!("  renumber [monster] - Number each kind of monster 1, 2, 3... to match numbered minis");
+                println!("  token <name> <number|none> - Set the number on the mini a combatant is using");
                 println!("  show|list - Display current initiative order");
+                println!("  reroll [on|off] - Re-roll initiative at the top of each round");
                 println!("  quit - Exit combat mode (auto-saves player characters)");
             }
             _ => {
@@ -746,75 +1382,165 @@ fn enhanced_combat_mode(mut combat_tracker: CombatTracker) {
     }
 }
 
-fn handle_attack_command(combat_tracker: &mut CombatTracker, target_name: &str) {
+/// Ask how a party character died, offer their belongings to the party stash,
+/// then archive them and note it in the session log
+fn handle_death(combat_tracker: &mut CombatTracker, characters: &CharacterStore, name: &str) {
+    let Some(name) = combat_tracker.get_combatant(name)
+        .filter(|combatant| combatant.character_id.is_some())
+        .map(|combatant| combatant.name.clone()) else {
+        println!("❌ '{}' is not a party character in this fight", name);
+        return;
+    };
+    let Ok(cause) = input_handler::read_user_input(&format!("🪦 How did {} die? (blank to cancel)", name)) else { return };
+    if cause.trim().is_empty() {
+        println!("{} is not recorded as dead.", name);
+        return;
+    }
+    let Some(character) = characters.remove(&name) else { return };
+    combat_tracker.remove_combatant(&name);
+
+    let mut supplies = supplies::Supplies::load(std::path::Path::new(supplies::SUPPLIES_FILE)).unwrap_or_default();
+    let to_stash = !character.inventory.is_empty()
+        && confirm(&format!("Move {}'s {} item(s) to the party stash?", name, character.inventory.len()), "stash");
+    match legacy::Legacy::campaign().lay_to_rest(character, &cause, None, to_stash.then_some(&mut supplies.stash)) {
+        Ok(lines) => lines.iter().for_each(|line| println!("{}", line)),
+        Err(e) => println!("⚠️ Could not archive {}: {}", name, e),
+    }
+    if to_stash && let Err(e) = supplies.save(std::path::Path::new(supplies::SUPPLIES_FILE)) {
+        println!("⚠️ Could not save the party stash: {}", e);
+    }
+}
+
+/// "8" or "8 fire": a damage amount with an optional type, falling back to `default_kind`
+fn parse_typed_damage(input: &str, default_kind: Option<combat::DamageType>) -> Option<(i32, Option<combat::DamageType>)> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let (amount, kind) = combat::split_damage_type(&words);
+    match amount {
+        [amount] => Some((amount.parse().ok()?, kind.or(default_kind))),
+        _ => None,
+    }
+}
+
+/// Run a house-rule script hook and carry out what the scripts ask for
+fn run_script_hook(scripts: &scripting::ScriptEngine, combat_tracker: &mut CombatTracker, hook: scripting::Hook) {
+    if scripts.is_empty() {
+        return;
+    }
+    for line in scripting::apply_actions(Some(combat_tracker), scripts.run_hook(&hook)) {
+        println!("{}", line);
+    }
+}
+
+/// on_turn_start for whoever `next_turn` just started
+fn run_turn_start_hook(scripts: &scripting::ScriptEngine, combat_tracker: &mut CombatTracker) {
+    let Some(name) = combat_tracker.last_actor_name() else { return };
+    // next_turn has already moved on, and into the next round if this was the last turn of one
+    let round = combat_tracker.round_number - i32::from(combat_tracker.current_turn == 0);
+    run_script_hook(scripts, combat_tracker, scripting::Hook::TurnStart { name, round });
+}
+
+/// Deal damage, then let scripts react to what the target actually took
+fn deal_damage(combat_tracker: &mut CombatTracker, scripts: &scripting::ScriptEngine, target_name: &str, damage: i32, kind: Option<combat::DamageType>) {
+    match combat_tracker.apply_damage(target_name, damage, kind) {
+        Ok(result) => println!("{}", result),
+        Err(e) => {
+            println!("❌ {}", e);
+            return;
+        }
+    }
     if let Some(target) = combat_tracker.get_combatant(target_name) {
-        let target_ac = target.ac;
-        
-        // Roll d20 for attack with critical announcements
-        match dice::roll_dice_with_crits("1d20") {
-            Ok((rolls, total, crit_message)) => {
-                let attack_roll = rolls[0] as i32;
-                let hit = attack_roll >= target_ac;
-                
-                println!("\n⚔️  Attack Roll: {} (d20: {})", total, attack_roll);
-                
-                // Display critical message if applicable
-                if let Some(message) = crit_message {
-                    println!("{}", message);
-                }
-                
-                println!("🎯 Target AC: {}", target_ac);
-                
-                if hit {
-                    println!("💥 HIT! The attack connects!");
-                    println!("🎲 Enter damage amount (or type 'roll' to use dice mode):");
-                    
-                    let mut damage_input = String::new();
-                    if std::io::stdin().read_line(&mut damage_input).is_ok() {
-                        let damage_input = damage_input.trim();
-                        
-                        if damage_input.to_lowercase() == "roll" {
-                            println!("💡 Use the dice mode in another terminal or enter damage manually.");
-                            println!("Enter damage amount:");
-                            let mut manual_damage = String::new();
-                            if std::io::stdin().read_line(&mut manual_damage).is_ok() {
-                                if let Ok(damage) = manual_damage.trim().parse::<i32>() {
-                                    match combat_tracker.apply_damage(target_name, damage) {
-                                        Ok(result) => println!("{}", result),
-                                        Err(e) => println!("❌ {}", e),
-                                    }
-                                } else {
-                                    println!("❌ Invalid damage amount");
-                                }
-                            }
-                        } else if let Ok(damage) = damage_input.parse::<i32>() {
-                            match combat_tracker.apply_damage(target_name, damage) {
-                                Ok(result) => println!("{}", result),
-                                Err(e) => println!("❌ {}", e),
-                            }
-                        } else {
-                            println!("❌ Invalid damage amount");
-                        }
-                    }
+        let hook = scripting::Hook::Damage {
+            target: target.name.clone(),
+            amount: target.adjust_damage(damage, kind).0,
+            hp: target.current_hp,
+            max_hp: target.max_hp,
+        };
+        run_script_hook(scripts, combat_tracker, hook);
+    }
+}
+
+fn handle_attack_command(combat_tracker: &mut CombatTracker, scripts: &scripting::ScriptEngine, target_name: &str, weapon: Option<&str>, kind: Option<combat::DamageType>,
+    edge: Option<combat::Edge>, characters: &[Character]) {
+    let Some(target) = combat_tracker.get_combatant(target_name) else {
+        println!("❌ Target '{}' not found in combat", target_name);
+        return;
+    };
+    let attacker = combat_tracker.combatants.get(combat_tracker.current_turn);
+    let hints = combat::condition_attack_hints(None, target);
+
+    // The attacker's bonus and damage dice come from their attack profiles or sheet
+    let outcome = match attacks::resolve_attack(attacker, target, weapon, kind, edge, characters) {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            println!("❌ Error rolling attack: {}", e);
+            return;
+        }
+    };
+    println!();
+    for line in &outcome.lines {
+        println!("{}", line);
+    }
+    for hint in hints {
+        println!("{}", hint);
+    }
+    run_script_hook(scripts, combat_tracker, scripting::Hook::Roll {
+        kind: "attack".to_string(),
+        expression: outcome.expression,
+        total: outcome.roll.total,
+        crit: outcome.roll.is_critical_success(),
+        target: target_name.to_string(),
+    });
+
+    if !outcome.hit {
+        println!("🛡️  MISS! The attack fails to connect.");
+        return;
+    }
+    println!("💥 HIT! The attack connects!");
+    if let Some(damage) = outcome.damage {
+        println!("{}", damage.line);
+        deal_damage(combat_tracker, scripts, target_name, damage.amount, damage.kind);
+        return;
+    }
+    println!("🎲 Enter damage amount, with an optional type (e.g. '8 fire'), or 'roll' to use dice mode:");
+
+    let mut damage_input = String::new();
+    if std::io::stdin().read_line(&mut damage_input).is_ok() {
+        let damage_input = damage_input.trim();
+
+        if damage_input.to_lowercase() == "roll" {
+            println!("💡 Use the dice mode in another terminal or enter damage manually.");
+            println!("Enter damage amount:");
+            let mut manual_damage = String::new();
+            if std::io::stdin().read_line(&mut manual_damage).is_ok() {
+                if let Some((damage, kind)) = parse_typed_damage(&manual_damage, kind) {
+                    deal_damage(combat_tracker, scripts, target_name, damage, kind);
                 } else {
-                    println!("🛡️  MISS! The attack fails to connect.");
+                    println!("❌ Invalid damage amount");
                 }
             }
-            Err(e) => println!("❌ Error rolling attack: {}", e),
+        } else if let Some((damage, kind)) = parse_typed_damage(damage_input, kind) {
+            deal_damage(combat_tracker, scripts, target_name, damage, kind);
+        } else {
+            println!("❌ Invalid damage amount");
         }
-    } else {
-        println!("❌ Target '{}' not found in combat", target_name);
     }
 }
 
-fn handle_status_command(combat_tracker: &mut CombatTracker, args: &[&str]) {
+fn handle_status_command(combat_tracker: &mut CombatTracker, args: &[&str], presets: &status_presets::StatusLibrary) {
     if args.is_empty() {
-        println!("Usage: status [add|remove|list] [self|name] <status_name>");
+        println!("Usage: status [add|remove|list|presets] [self|name] <status_name>");
         return;
     }
     
     let action = args[0].to_lowercase();
     
+    if action == "presets" {
+        for line in presets.lines() {
+            println!("{}", line);
+        }
+        return;
+    }
+    
     // Handle status list command
     if action == "list" {
         if args.len() >= 2 {
@@ -836,11 +1562,7 @@ fn handle_status_command(combat_tracker: &mut CombatTracker, args: &[&str]) {
                 } else {
                     println!("📋 Status effects for {}:", target_name);
                     for status in &combatant.status_effects {
-                        let duration_str = match status.duration {
-                            Some(d) => format!(" ({} rounds remaining)", d),
-                            None => " (permanent)".to_string(),
-                        };
-                        println!("  • {}{}", status.name, duration_str);
+                        println!("  • {}{}", status.name, status.duration_text());
                     }
                 }
             } else {
@@ -861,12 +1583,13 @@ fn handle_status_command(combat_tracker: &mut CombatTracker, args: &[&str]) {
     }
     
     if args.len() < 3 {
-        println!("Usage: status [add|remove] [self|name] <status_name>");
+        println!("Usage: status [add|remove] [self|name] <status_name> [rounds] [start|end] [by <concentrating>]");
         return;
     }
     
     let target = args[1];
-    let status_name = args[2..].join(" ");
+    let mut status = parse_status_effect(&args[2..], presets);
+    let status_name = if action == "add" { status.name.clone() } else { args[2..].join(" ") };
     
     // For now, we'll determine "self" based on current turn
     let target_name = if target.to_lowercase() == "self" {
@@ -883,14 +1606,20 @@ fn handle_status_command(combat_tracker: &mut CombatTracker, args: &[&str]) {
     
     match action.as_str() {
         "add" => {
-            if let Some(combatant) = combat_tracker.get_combatant_mut(&target_name) {
-                let status = StatusEffect {
-                    name: status_name.clone(),
-                    description: None,
-                    duration: None, // Could be enhanced to ask for duration
-                };
+            if let Err(e) = combat_tracker.resolve_concentration(&mut status) {
+                println!("❌ {}", e);
+            } else if let Some(combatant) = combat_tracker.get_combatant_mut(&target_name) {
+                let duration_text = status.duration_text();
+                let rules = status.condition().map(|c| c.rules_text()).unwrap_or_default();
+                let details = status.detail_lines(&target_name);
                 combatant.add_status(status);
-                println!("✅ Added status '{}' to {}", status_name, target_name);
+                println!("✅ Added status '{}' to {}{}", status_name, target_name, duration_text);
+                for line in rules {
+                    println!("   • {}", line);
+                }
+                for line in details {
+                    println!("   {}", line);
+                }
             } else {
                 println!("❌ Combatant '{}' not found", target_name);
             }
@@ -912,12 +1641,40 @@ fn handle_status_command(combat_tracker: &mut CombatTracker, args: &[&str]) {
     }
 }
 
-fn handle_insert_combatant(combat_tracker: &mut CombatTracker, name: &str) {
+/// "held 10 start by Mira": the status name, then optional rounds, when they
+/// count down, and who is concentrating on it. A preset's details fill in the rest.
+fn parse_status_effect(words: &[&str], presets: &status_presets::StatusLibrary) -> StatusEffect {
+    let mut words = words.to_vec();
+    let mut concentration = None;
+    if let Some(by) = words.iter().rposition(|w| w.eq_ignore_ascii_case("by")) {
+        concentration = Some(words.split_off(by)[1..].join(" ")).filter(|owner| !owner.is_empty());
+    }
+    let (mut duration, mut ticks_at) = (None, None);
+    while words.len() > 1 {
+        let last = words[words.len() - 1];
+        if let Ok(rounds) = last.parse::<i32>() {
+            duration = Some(rounds);
+        } else if let Some(point) = combat::TickPoint::parse(last) {
+            ticks_at = Some(point);
+        } else {
+            break;
+        }
+        words.pop();
+    }
+    let status = presets.status(&words.join(" "));
+    StatusEffect {
+        duration: duration.or(status.duration),
+        ticks_at: ticks_at.unwrap_or(status.ticks_at),
+        concentration,
+        ..status
+    }
+}
+
+fn handle_insert_combatant(combat_tracker: &mut CombatTracker, name: &str, characters: &[Character]) {
     println!("\n➕ Inserting new combatant: {}", name);
     
     // Check if character already exists in saved characters
-    let existing_characters = load_character_files();
-    if let Some(character) = existing_characters.iter().find(|c| c.name.eq_ignore_ascii_case(name)) {
+    if let Some(character) = characters.iter().find(|c| c.name.eq_ignore_ascii_case(name)) {
         println!("📝 Found existing character: {}", character.name);
         
         // Get initiative
@@ -932,24 +1689,24 @@ fn handle_insert_combatant(combat_tracker: &mut CombatTracker, name: &str) {
             if input.is_empty() {
                 // Auto-roll initiative
                 match dice::roll_dice_with_crits("1d20") {
-                    Ok((rolls, base_roll, crit_message)) => {
-                        let initiative = base_roll as i32 + dex_mod as i32;
-                        let mut message = format!("🎲 Rolled {} (d20: {}, DEX: {}) = {}", 
-                                initiative, rolls[0], dex_mod_str, initiative);
+                    Ok(roll) => {
+                        let roll = roll.plus(dex_mod as i32);
+                        let initiative = roll.total;
+                        let mut message = format!("🎲 Rolled initiative: {}", roll);
                         
-                        if let Some(crit) = crit_message {
+                        if let Some(crit) = roll.crit {
                             message.push_str(&format!("\n{}", crit));
                         }
                         println!("{}", message);
                         
-                        let combatant = Combatant::from_character(character.clone(), initiative);
+                        let combatant = Combatant::from_character(character, initiative);
                         combat_tracker.add_combatant(combatant);
                         println!("✅ Added {} to combat with initiative {}", character.name, initiative);
                     }
                     Err(e) => println!("❌ Error rolling initiative: {}", e),
                 }
             } else if let Ok(initiative) = input.parse::<i32>() {
-                let combatant = Combatant::from_character(character.clone(), initiative);
+                let combatant = Combatant::from_character(character, initiative);
                 combat_tracker.add_combatant(combatant);
                 println!("✅ Added {} to combat with initiative {}", character.name, initiative);
             } else {
@@ -964,13 +1721,13 @@ fn handle_insert_combatant(combat_tracker: &mut CombatTracker, name: &str) {
         io::stdout().flush().unwrap();
         let mut hp_input = String::new();
         io::stdin().read_line(&mut hp_input).expect("Failed to read HP");
-        let hp = hp_input.trim().parse::<i32>().unwrap_or(10);
+        let hp = stat_or_default(&hp_input, "max_hp", 10) as i32;
         
         print!("AC: ");
         io::stdout().flush().unwrap();
         let mut ac_input = String::new();
         io::stdin().read_line(&mut ac_input).expect("Failed to read AC");
-        let ac = ac_input.trim().parse::<i32>().unwrap_or(10);
+        let ac = stat_or_default(&ac_input, "ac", 10) as i32;
         
         print!("Initiative: ");
         io::stdout().flush().unwrap();
@@ -981,6 +1738,9 @@ fn handle_insert_combatant(combat_tracker: &mut CombatTracker, name: &str) {
         let combatant = Combatant::new_npc(name.to_string(), hp, ac, initiative);
         combat_tracker.add_combatant(combatant);
         println!("✅ Added {} to combat as NPC!", name);
+        if let Some(reminder) = temperament::join_reminder(std::path::Path::new(file_manager::NPCS_DIR), name) {
+            println!("{}", reminder);
+        }
     }
     
     combat_tracker.display_initiative_order();
@@ -993,17 +1753,22 @@ pub fn search_mode() {
     println!("Powered by http://dnd5e.wikidot.com - Live data from the web!");
     println!("═══════════════════════════════════════════════════════════");
     
-    // Create runtime for async operations
-    let rt = match tokio::runtime::Runtime::new() {
-        Ok(runtime) => runtime,
+    let rt = match runtime::shared() {
+        Ok(rt) => rt,
         Err(e) => {
-            println!("❌ Failed to create async runtime: {}", e);
+            println!("❌ {}", e);
             println!("Search functionality unavailable.");
             return;
         }
     };
     
-    let client = DndSearchClient::new();
+    let client = match DndSearchClient::try_new() {
+        Ok(client) => client,
+        Err(e) => {
+            println!("❌ {}", e);
+            return;
+        }
+    };
     
     println!("🌐 Online mode - connecting to Wikidot D&D 5e site");
     
@@ -1081,6 +1846,45 @@ pub fn search_mode() {
                 println!("  search spell fireball");
                 println!("  search equipment longsword");
             },
+            "cache" if parts.get(1).is_some_and(|arg| arg.eq_ignore_ascii_case("refresh")) => {
+                if parts.len() < 3 {
+                    println!("Usage: cache refresh <name>");
+                } else if srd::is_offline() {
+                    println!("❌ Offline: refreshing needs the wiki");
+                } else {
+                    for line in rt.block_on(client.refresh_cached(&parts[2..].join(" "))) {
+                        println!("{}", line);
+                    }
+                }
+            },
+            "cache" if parts.get(1).is_some_and(|arg| arg.eq_ignore_ascii_case("clear")) => {
+                if confirm(&cache::clear_prompt(), "clear") {
+                    println!("{}", cache::clear_confirmed());
+                } else {
+                    println!("Cache left as it was.");
+                }
+            },
+            "cache" => {
+                for line in cache::cache_command(&parts[1..]) {
+                    println!("{}", line);
+                }
+            },
+            "crawl" => {
+                if srd::is_offline() {
+                    println!("❌ Offline: crawling needs the wiki");
+                    continue;
+                }
+                println!("🗂️ Crawling the wiki's listing pages...");
+                match rt.block_on(client.crawl_index()) {
+                    Ok(report) => println!("{}", report.summary()),
+                    Err(e) => println!("❌ Crawl failed: {}", e),
+                }
+            },
+            "list" | "browse" => {
+                for line in search::list_command(&parts[1..]) {
+                    println!("{}", line);
+                }
+            },
             "help" => {
                 show_search_help();
             },
@@ -1222,6 +2026,11 @@ fn show_search_help() {
     println!("BASIC USAGE:");
     println!("  search <query>              - Search all categories");
     println!("  search <category> <query>   - Search specific category");
+    println!("  cache stats                 - Cached pages and disk usage");
+    println!("  cache clear                 - Remove every cached page (asks first)");
+    println!("  cache refresh <name>        - Fetch a cached page again from the wiki");
+    println!("  crawl                       - Index the wiki's spell, monster and item listings");
+    println!("  list <category> [filters]   - Browse the index, e.g. list spells 3 wizard");
     println!();
     println!("CATEGORIES:");
     println!("  spells      - Magic spells (e.g., fireball, cure wounds)");
@@ -1288,20 +2097,38 @@ async fn test_api_connectivity(client: &DndSearchClient) {
     }
 }
 
+fn handle_reference_in_combat(query: &str) {
+    match search::lookup_reference(query, Some(SearchCategory::Monsters)) {
+        Ok(page) => {
+            println!("\n📖 {}", page.name);
+            println!("{}", "─".repeat(50));
+            for line in search::condensed_lines(&page.content, search::REFERENCE_LINES) {
+                println!("  {}", line);
+            }
+        }
+        Err(e) => println!("❌ {}", e),
+    }
+}
+
 fn handle_search_in_combat(query: &str) {
     println!("\n🔍 Searching for '{}' in D&D 5e database...", query);
     
-    // Create runtime for async operations
-    let rt = match tokio::runtime::Runtime::new() {
-        Ok(runtime) => runtime,
+    let rt = match runtime::shared() {
+        Ok(rt) => rt,
         Err(e) => {
-            println!("❌ Failed to create async runtime: {}", e);
+            println!("❌ {}", e);
             println!("Search functionality unavailable.");
             return;
         }
     };
     
-    let client = DndSearchClient::new();
+    let client = match DndSearchClient::try_new() {
+        Ok(client) => client,
+        Err(e) => {
+            println!("❌ {}", e);
+            return;
+        }
+    };
     
     rt.block_on(async {
         match client.search(query, None).await {
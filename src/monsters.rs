@@ -0,0 +1,165 @@
+use crate::error_handling::{AppError, Result, ResultExt};
+use crate::xp::cr_value;
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+/// Extra creatures for the local index; entries with a built-in's name replace it
+pub const MONSTERS_FILE: &str = "campaign/monsters.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct MonsterEntry {
+    pub name: String,
+    /// Creature type: beast, monstrosity, dragon...
+    #[serde(default = "default_kind")]
+    pub kind: String,
+    pub cr: String,
+    #[serde(default)]
+    pub swim: bool,
+    #[serde(default)]
+    pub fly: bool,
+}
+
+fn default_kind() -> String {
+    "beast".to_string()
+}
+
+impl MonsterEntry {
+    pub fn summary(&self) -> String {
+        let movement: Vec<&str> = [(self.swim, "swim"), (self.fly, "fly")].iter()
+            .filter(|(has, _)| *has)
+            .map(|(_, name)| *name)
+            .collect();
+        let movement = if movement.is_empty() { String::new() } else { format!(", {}", movement.join(", ")) };
+        format!("{} (CR {}{})", self.name, self.cr, movement)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MonsterFile {
+    monsters: Vec<MonsterEntry>,
+}
+
+/// SRD 5.1 beasts: (name, CR, swim speed, fly speed)
+const SRD_BEASTS: &[(&str, &str, bool, bool)] = &[
+    ("Baboon", "0", false, false), ("Badger", "0", false, false), ("Bat", "0", false, true),
+    ("Cat", "0", false, false), ("Crab", "0", true, false), ("Deer", "0", false, false),
+    ("Eagle", "0", false, true), ("Frog", "0", true, false), ("Giant Fire Beetle", "0", false, false),
+    ("Goat", "0", false, false), ("Hawk", "0", false, true), ("Hyena", "0", false, false),
+    ("Jackal", "0", false, false), ("Lizard", "0", false, false), ("Octopus", "0", true, false),
+    ("Owl", "0", false, true), ("Quipper", "0", true, false), ("Rat", "0", false, false),
+    ("Raven", "0", false, true), ("Scorpion", "0", false, false), ("Spider", "0", false, false),
+    ("Vulture", "0", false, true), ("Weasel", "0", false, false),
+    ("Blood Hawk", "1/8", false, true), ("Camel", "1/8", false, false), ("Flying Snake", "1/8", true, true),
+    ("Giant Crab", "1/8", true, false), ("Giant Rat", "1/8", false, false), ("Giant Weasel", "1/8", false, false),
+    ("Mastiff", "1/8", false, false), ("Mule", "1/8", false, false), ("Poisonous Snake", "1/8", true, false),
+    ("Pony", "1/8", false, false),
+    ("Axe Beak", "1/4", false, false), ("Boar", "1/4", false, false), ("Constrictor Snake", "1/4", true, false),
+    ("Draft Horse", "1/4", false, false), ("Elk", "1/4", false, false), ("Giant Badger", "1/4", false, false),
+    ("Giant Bat", "1/4", false, true), ("Giant Centipede", "1/4", false, false), ("Giant Frog", "1/4", true, false),
+    ("Giant Lizard", "1/4", false, false), ("Giant Owl", "1/4", false, true), ("Giant Poisonous Snake", "1/4", true, false),
+    ("Giant Wolf Spider", "1/4", false, false), ("Panther", "1/4", false, false), ("Pteranodon", "1/4", false, true),
+    ("Riding Horse", "1/4", false, false), ("Wolf", "1/4", false, false),
+    ("Ape", "1/2", false, false), ("Black Bear", "1/2", false, false), ("Crocodile", "1/2", true, false),
+    ("Giant Goat", "1/2", false, false), ("Giant Sea Horse", "1/2", true, false), ("Giant Wasp", "1/2", false, true),
+    ("Reef Shark", "1/2", true, false), ("Warhorse", "1/2", false, false),
+    ("Brown Bear", "1", false, false), ("Dire Wolf", "1", false, false), ("Giant Eagle", "1", false, true),
+    ("Giant Hyena", "1", false, false), ("Giant Octopus", "1", true, false), ("Giant Spider", "1", false, false),
+    ("Giant Toad", "1", true, false), ("Giant Vulture", "1", false, true), ("Lion", "1", false, false),
+    ("Tiger", "1", false, false),
+    ("Allosaurus", "2", false, false), ("Giant Boar", "2", false, false), ("Giant Constrictor Snake", "2", true, false),
+    ("Giant Elk", "2", false, false), ("Hunter Shark", "2", true, false), ("Plesiosaurus", "2", true, false),
+    ("Polar Bear", "2", true, false), ("Rhinoceros", "2", false, false), ("Saber-Toothed Tiger", "2", false, false),
+    ("Ankylosaurus", "3", false, false), ("Giant Scorpion", "3", false, false), ("Killer Whale", "3", true, false),
+    ("Elephant", "4", false, false),
+    ("Giant Crocodile", "5", true, false), ("Giant Shark", "5", true, false), ("Triceratops", "5", false, false),
+    ("Mammoth", "6", false, false),
+    ("Giant Ape", "7", false, false),
+    ("Tyrannosaurus Rex", "8", false, false),
+];
+
+pub fn is_srd_beast(name: &str) -> bool {
+    SRD_BEASTS.iter().any(|(beast, ..)| beast.eq_ignore_ascii_case(name))
+}
+
+/// Creatures known without going online: the SRD beasts plus the campaign's own
+#[derive(Debug, Clone)]
+pub struct MonsterIndex {
+    pub monsters: Vec<MonsterEntry>,
+}
+
+impl Default for MonsterIndex {
+    fn default() -> Self {
+        MonsterIndex {
+            monsters: SRD_BEASTS.iter().map(|&(name, cr, swim, fly)| MonsterEntry {
+                name: name.to_string(),
+                kind: default_kind(),
+                cr: cr.to_string(),
+                swim,
+                fly,
+            }).collect(),
+        }
+    }
+}
+
+/// The most a druid can become: (max CR, swimming forms allowed, flying forms allowed)
+pub fn wild_shape_limits(druid_level: u32, moon: bool) -> Result<(f64, bool, bool)> {
+    let (max_cr, swim, fly) = match druid_level {
+        0..=1 => return Err(AppError::ValidationError("Druids gain Wild Shape at level 2".to_string())),
+        2..=3 => (0.25, false, false),
+        4..=7 => (0.5, true, false),
+        _ => (1.0, true, true),
+    };
+    // Circle of the Moon: CR 1 from level 2, then a third of the druid level from 6th
+    let max_cr = match (moon, druid_level) {
+        (false, _) => max_cr,
+        (true, 2..=5) => 1.0,
+        (true, level) => (level / 3) as f64,
+    };
+    Ok((max_cr, swim, fly))
+}
+
+impl MonsterIndex {
+    pub fn add_toml(&mut self, source: &str) -> Result<usize> {
+        let file: MonsterFile = toml::from_str(source).map_err(|e| AppError::ParseError(e.to_string()))?;
+        let count = file.monsters.len();
+        for monster in file.monsters {
+            if cr_value(&monster.cr).is_none() {
+                return Err(AppError::ValidationError(format!("{} has an unknown CR '{}'", monster.name, monster.cr)));
+            }
+            self.monsters.retain(|m| !m.name.eq_ignore_ascii_case(&monster.name));
+            self.monsters.push(monster);
+        }
+        Ok(count)
+    }
+
+    pub fn load_file(&mut self, path: &Path) -> Result<usize> {
+        if !path.exists() {
+            return Ok(0);
+        }
+        let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        self.add_toml(&contents).context(format!("Monster file {}", path.display()))
+    }
+
+    /// Creatures at or under a CR, weakest first; `beasts_only` for Wild Shape and Polymorph
+    pub fn filter(&self, max_cr: f64, beasts_only: bool, swim: bool, fly: bool) -> Vec<&MonsterEntry> {
+        let mut found: Vec<&MonsterEntry> = self.monsters.iter()
+            .filter(|m| !beasts_only || m.kind.eq_ignore_ascii_case("beast"))
+            .filter(|m| (swim || !m.swim) && (fly || !m.fly))
+            .filter(|m| cr_value(&m.cr).is_some_and(|cr| cr <= max_cr))
+            .collect();
+        found.sort_by(|a, b| cr_value(&a.cr).partial_cmp(&cr_value(&b.cr)).unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.name.cmp(&b.name)));
+        found
+    }
+
+    pub fn wild_shape_forms(&self, druid_level: u32, moon: bool) -> Result<Vec<&MonsterEntry>> {
+        let (max_cr, swim, fly) = wild_shape_limits(druid_level, moon)?;
+        Ok(self.filter(max_cr, true, swim, fly))
+    }
+
+    /// Polymorph turns a creature into a beast of CR up to its level (or CR);
+    /// True Polymorph allows any kind of creature
+    pub fn polymorph_forms(&self, target_level: u32, true_polymorph: bool) -> Vec<&MonsterEntry> {
+        self.filter(target_level as f64, !true_polymorph, true, true)
+    }
+}
@@ -0,0 +1,132 @@
+use crate::combat::Combatant;
+use crate::error_handling::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Morale scores run 2-12; a group holds if 2d6 rolls at or under its score
+pub const MIN_MORALE: i32 = 2;
+pub const MAX_MORALE: i32 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MoraleOutcome {
+    StandsFirm,
+    Retreat,
+    Flee,
+    Surrender,
+}
+
+impl MoraleOutcome {
+    /// Compare a 2d6 roll to the morale score; the further over, the worse it goes
+    pub fn from_roll(morale: i32, roll: i32) -> Self {
+        match roll - morale {
+            i32::MIN..=0 => MoraleOutcome::StandsFirm,
+            1..=2 => MoraleOutcome::Retreat,
+            3..=4 => MoraleOutcome::Flee,
+            _ => MoraleOutcome::Surrender,
+        }
+    }
+
+    pub fn advice(&self) -> &'static str {
+        match self {
+            MoraleOutcome::StandsFirm => "Stands firm and keeps fighting",
+            MoraleOutcome::Retreat => "Falls back in a fighting retreat, covering the wounded",
+            MoraleOutcome::Flee => "Breaks and flees by the quickest route",
+            MoraleOutcome::Surrender => "Throws down weapons and begs for quarter",
+        }
+    }
+}
+
+impl fmt::Display for MoraleOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MoraleOutcome::StandsFirm => "Stands firm",
+            MoraleOutcome::Retreat => "Retreat",
+            MoraleOutcome::Flee => "Flee",
+            MoraleOutcome::Surrender => "Surrender",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Why a group should check morale
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoraleTrigger {
+    LeaderDown(String),
+    HalfFallen { fallen: usize, total: usize },
+}
+
+impl fmt::Display for MoraleTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoraleTrigger::LeaderDown(leader) => write!(f, "their leader {} has fallen", leader),
+            MoraleTrigger::HalfFallen { fallen, total } => write!(f, "{} of {} have fallen", fallen, total),
+        }
+    }
+}
+
+/// NPCs that check morale together. Members are combatant ids.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoraleGroup {
+    pub name: String,
+    pub morale: i32,
+    pub members: Vec<u32>,
+    pub leader: Option<u32>,
+    pub last_outcome: Option<MoraleOutcome>,
+    leader_triggered: bool,
+    half_triggered: bool,
+}
+
+impl MoraleGroup {
+    pub fn new(name: &str, morale: i32, members: Vec<u32>, leader: Option<u32>) -> Result<Self> {
+        if !(MIN_MORALE..=MAX_MORALE).contains(&morale) {
+            return Err(AppError::ValidationError(format!("Morale must be between {} and {}", MIN_MORALE, MAX_MORALE)));
+        }
+        if members.is_empty() {
+            return Err(AppError::ValidationError("A morale group needs at least one member".to_string()));
+        }
+        Ok(MoraleGroup {
+            name: name.to_string(),
+            morale,
+            members,
+            leader,
+            last_outcome: None,
+            leader_triggered: false,
+            half_triggered: false,
+        })
+    }
+
+    /// Members at 0 HP or removed from combat
+    pub fn fallen(&self, combatants: &[Combatant]) -> usize {
+        self.members.iter()
+            .filter(|id| !combatants.iter().any(|c| c.id == **id && c.current_hp > 0))
+            .count()
+    }
+
+    /// Triggers that have newly happened; each one fires only once per group
+    pub fn pending_triggers(&mut self, combatants: &[Combatant]) -> Vec<MoraleTrigger> {
+        let mut triggers = Vec::new();
+        if let Some(leader) = self.leader
+            && !self.leader_triggered {
+            let leader_combatant = combatants.iter().find(|c| c.id == leader);
+            if leader_combatant.is_none_or(|c| c.current_hp <= 0) {
+                self.leader_triggered = true;
+                let name = leader_combatant.map(|c| c.name.clone()).unwrap_or_else(|| "leader".to_string());
+                triggers.push(MoraleTrigger::LeaderDown(name));
+            }
+        }
+        let fallen = self.fallen(combatants);
+        let total = self.members.len();
+        if !self.half_triggered && fallen * 2 >= total && fallen > 0 {
+            self.half_triggered = true;
+            triggers.push(MoraleTrigger::HalfFallen { fallen, total });
+        }
+        triggers
+    }
+
+    /// Resolve a morale check against a 2d6 roll
+    pub fn check(&mut self, roll: i32) -> MoraleOutcome {
+        let outcome = MoraleOutcome::from_roll(self.morale, roll);
+        self.last_outcome = Some(outcome);
+        outcome
+    }
+}
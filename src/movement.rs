@@ -0,0 +1,104 @@
+use crate::combat::Combatant;
+use serde::{Deserialize, Serialize};
+
+/// Melee weapons that work normally underwater
+const UNDERWATER_MELEE_WEAPONS: &[&str] = &["dagger", "javelin", "shortsword", "spear", "trident"];
+/// Ranged weapons that work normally underwater within their normal range
+const UNDERWATER_RANGED_WEAPONS: &[&str] = &["net", "javelin", "spear", "trident", "dart"];
+
+/// How a combatant is moving, when it isn't simply on the ground
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Movement {
+    Flying { altitude: u32 },
+    Swimming,
+    Burrowing,
+}
+
+impl Movement {
+    /// `fly 30`, `swim`, `burrow`; `land` (or `walk`) returns Ok(None)
+    pub fn parse(mode: &str, altitude: Option<&str>) -> Result<Option<Movement>, String> {
+        match mode.to_lowercase().as_str() {
+            "fly" | "flying" => match altitude.map(|a| a.trim_end_matches("ft").parse::<u32>()) {
+                Some(Ok(altitude)) => Ok(Some(Movement::Flying { altitude })),
+                _ => Err("Usage: fly <name> <altitude in feet>".to_string()),
+            },
+            "swim" | "swimming" => Ok(Some(Movement::Swimming)),
+            "burrow" | "burrowing" => Ok(Some(Movement::Burrowing)),
+            "land" | "walk" => Ok(None),
+            other => Err(format!("Unknown movement '{}' (fly, swim, burrow, land)", other)),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Movement::Flying { altitude } => format!("🪽 {} ft", altitude),
+            Movement::Swimming => "🌊 swimming".to_string(),
+            Movement::Burrowing => "⛏️ burrowed".to_string(),
+        }
+    }
+
+    /// The rule worth remembering when a combatant starts moving this way
+    pub fn reminder(&self) -> &'static str {
+        match self {
+            Movement::Flying { .. } => "A flyer that is knocked prone, drops to 0 HP or has its speed reduced to 0 falls, unless it can hover",
+            Movement::Swimming => "Underwater, most weapon attacks have disadvantage and ranged attacks miss beyond normal range",
+            Movement::Burrowing => "A burrowed creature has total cover and can't be targeted until it surfaces",
+        }
+    }
+}
+
+/// "1d6 per 10 feet fallen, to a maximum of 20d6"; nothing for a fall under 10 feet
+pub fn fall_damage(feet: u32) -> Option<String> {
+    let dice = (feet / 10).min(20);
+    (dice > 0).then(|| format!("{}d6", dice))
+}
+
+/// When a flying combatant is knocked prone or drops, it falls and is back on the ground
+pub fn knock_down(combatant: &mut Combatant) -> Option<String> {
+    let Some(Movement::Flying { altitude }) = combatant.movement else { return None };
+    combatant.movement = None;
+    Some(match fall_damage(altitude) {
+        Some(dice) => format!("🪂 {} falls {} ft: {} bludgeoning damage and lands prone (unless it can hover)", combatant.name, altitude, dice),
+        None => format!("🪂 {} drops {} ft to the ground without taking damage", combatant.name, altitude),
+    })
+}
+
+/// Underwater combat rules for an attack, by weapon. A named weapon that uses
+/// ammunition or is thrown counts as a ranged attack.
+pub fn underwater_attack_reminder(weapon: Option<&str>) -> String {
+    let Some(weapon) = weapon.map(|w| w.trim().to_lowercase()) else {
+        return "🌊 Underwater: melee attacks have disadvantage unless made with a dagger, javelin, shortsword, spear or trident (or by a creature with a swimming speed)".to_string();
+    };
+    let singular = weapon.trim_end_matches('s');
+    let ranged = crate::ammo::ammo_for_weapon(&weapon).is_some();
+    if ranged && (weapon.contains("crossbow") || singular == "bolt" || UNDERWATER_RANGED_WEAPONS.contains(&singular)) {
+        format!("🌊 Underwater: the {} works normally within its normal range and misses beyond it", weapon)
+    } else if ranged {
+        format!("🌊 Underwater: ranged attacks with the {} have disadvantage and miss beyond normal range", weapon)
+    } else if UNDERWATER_MELEE_WEAPONS.contains(&singular) {
+        format!("🌊 Underwater: the {} works normally", weapon)
+    } else {
+        format!("🌊 Underwater: melee attacks with the {} have disadvantage unless the attacker has a swimming speed", weapon)
+    }
+}
+
+/// Reminders for an attack given where the attacker and target are
+pub fn attack_reminders(attacker: Option<&Combatant>, target: &Combatant, weapon: Option<&str>) -> Vec<String> {
+    let mut reminders = Vec::new();
+    if attacker.is_some_and(|a| a.movement == Some(Movement::Swimming)) {
+        reminders.push(underwater_attack_reminder(weapon));
+    }
+    let attacker_altitude = match attacker.and_then(|a| a.movement) {
+        Some(Movement::Flying { altitude }) => altitude,
+        _ => 0,
+    };
+    let melee = weapon.is_none_or(|w| crate::ammo::ammo_for_weapon(w).is_none());
+    match target.movement {
+        Some(Movement::Flying { altitude }) if melee && altitude.abs_diff(attacker_altitude) > 5 => {
+            reminders.push(format!("🪽 {} is {} ft up: out of reach of most melee attacks", target.name, altitude));
+        }
+        Some(Movement::Burrowing) => reminders.push(format!("⛏️ {} is burrowed and can't be targeted", target.name)),
+        _ => {}
+    }
+    reminders
+}
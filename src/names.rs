@@ -0,0 +1,192 @@
+use crate::error_handling::{AppError, Result, ResultExt};
+use crate::tables::RandomTable;
+use rand::Rng;
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// User name lists (.toml or .csv, one file per culture) live in the campaign directory
+pub const NAMES_DIR: &str = "campaign/names";
+/// The list used when no culture is given or none matches
+pub const DEFAULT_CULTURE: &str = "common";
+
+fn default_weight() -> u32 {
+    1
+}
+
+fn default_middle_chance() -> f64 {
+    0.3
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WeightedName {
+    pub name: String,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+/// Build names from parts: a prefix, sometimes a middle, then a suffix
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SyllableRules {
+    pub prefixes: Vec<String>,
+    #[serde(default)]
+    pub middles: Vec<String>,
+    pub suffixes: Vec<String>,
+    #[serde(default = "default_middle_chance")]
+    pub middle_chance: f64,
+}
+
+impl SyllableRules {
+    fn build<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<String> {
+        let pick = |parts: &[String], rng: &mut R| (!parts.is_empty()).then(|| parts[rng.random_range(0..parts.len())].clone());
+        let mut name = pick(&self.prefixes, rng)?;
+        if rng.random_bool(self.middle_chance.clamp(0.0, 1.0))
+            && let Some(middle) = pick(&self.middles, rng)
+        {
+            name.push_str(&middle);
+        }
+        name.push_str(&pick(&self.suffixes, rng)?);
+        Some(name)
+    }
+}
+
+/// Names for one culture or region. With both a list and syllable rules,
+/// `combine_chance` is how often a name is built from syllables instead.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NameList {
+    pub culture: String,
+    #[serde(default)]
+    pub names: Vec<WeightedName>,
+    #[serde(default)]
+    pub surnames: Vec<WeightedName>,
+    #[serde(default)]
+    pub syllables: Option<SyllableRules>,
+    #[serde(default)]
+    pub combine_chance: f64,
+}
+
+fn pick_weighted<'a, R: Rng + ?Sized>(names: &'a [WeightedName], rng: &mut R) -> Option<&'a str> {
+    let total: u32 = names.iter().map(|n| n.weight).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut roll = rng.random_range(0..total);
+    names.iter().find(|n| {
+        if roll < n.weight {
+            true
+        } else {
+            roll -= n.weight;
+            false
+        }
+    }).map(|n| n.name.as_str())
+}
+
+impl NameList {
+    pub fn from_toml(source: &str) -> Result<Self> {
+        toml::from_str(source).map_err(|e| AppError::ParseError(e.to_string()))
+    }
+
+    /// "weight,name" lines, the same layout as table CSVs
+    pub fn from_csv(culture: &str, source: &str) -> Self {
+        let table = RandomTable::from_csv(culture, source);
+        NameList {
+            culture: culture.to_string(),
+            names: table.entries.into_iter().map(|e| WeightedName { name: e.text, weight: e.weight }).collect(),
+            surnames: Vec::new(),
+            syllables: None,
+            combine_chance: 0.0,
+        }
+    }
+
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<String> {
+        let use_syllables = self.names.is_empty() || rng.random_bool(self.combine_chance.clamp(0.0, 1.0));
+        let first = match (&self.syllables, use_syllables) {
+            (Some(rules), true) => rules.build(rng),
+            _ => pick_weighted(&self.names, rng).map(str::to_string),
+        }.ok_or_else(|| AppError::ValidationError(format!("Name list '{}' has no names or syllables", self.culture)))?;
+
+        Ok(match pick_weighted(&self.surnames, rng) {
+            Some(surname) => format!("{} {}", first, surname),
+            None => first,
+        })
+    }
+}
+
+fn builtin_common() -> NameList {
+    let names = |list: &[&str]| list.iter().map(|n| WeightedName { name: n.to_string(), weight: 1 }).collect();
+    NameList {
+        culture: DEFAULT_CULTURE.to_string(),
+        names: names(&["Alden", "Bree", "Cora", "Dorn", "Edda", "Finn", "Greta", "Hal", "Ilse", "Jory", "Kestra", "Lem", "Mara", "Nim", "Osric", "Pell", "Rowan", "Sable", "Tam", "Wren"]),
+        surnames: names(&["Ashford", "Brightwater", "Cobble", "Dunmore", "Fairweather", "Greaves", "Holloway", "Marsh", "Thorne", "Underhill"]),
+        syllables: None,
+        combine_chance: 0.0,
+    }
+}
+
+/// Every culture the name generator knows, looked up case-insensitively
+#[derive(Debug, Clone)]
+pub struct NameRegistry {
+    lists: BTreeMap<String, NameList>,
+}
+
+impl Default for NameRegistry {
+    fn default() -> Self {
+        let mut registry = NameRegistry { lists: BTreeMap::new() };
+        registry.add(builtin_common());
+        registry
+    }
+}
+
+impl NameRegistry {
+    /// Add or replace a culture's list
+    pub fn add(&mut self, list: NameList) {
+        self.lists.insert(list.culture.to_lowercase(), list);
+    }
+
+    pub fn cultures(&self) -> Vec<&str> {
+        self.lists.values().map(|l| l.culture.as_str()).collect()
+    }
+
+    /// Load every .toml / .csv file in a directory, returning the errors for any that failed
+    pub fn load_dir(&mut self, dir: &Path) -> Vec<AppError> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<_> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+        paths.sort();
+
+        let mut errors = Vec::new();
+        for path in paths {
+            let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+            let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            let list = match extension.as_str() {
+                "toml" => fs::read_to_string(&path).map_err(AppError::from).and_then(|s| NameList::from_toml(&s)),
+                "csv" => fs::read_to_string(&path).map_err(AppError::from).map(|s| NameList::from_csv(&stem, &s)),
+                _ => continue,
+            };
+            match list.context(format!("Name list {}", path.display())) {
+                Ok(list) => {
+                    log::info!("Loaded names for '{}' from {}", list.culture, path.display());
+                    self.add(list);
+                }
+                Err(e) => {
+                    log::warn!("{}", e);
+                    errors.push(e);
+                }
+            }
+        }
+        errors
+    }
+
+    /// A name from the given culture, or the common list when there's no such culture
+    pub fn generate<R: Rng + ?Sized>(&self, culture: Option<&str>, rng: &mut R) -> Result<String> {
+        let list = culture
+            .and_then(|c| self.lists.get(&c.trim().to_lowercase()))
+            .or_else(|| self.lists.get(DEFAULT_CULTURE))
+            .ok_or_else(|| AppError::NotFound(format!("Name list '{}'", culture.unwrap_or(DEFAULT_CULTURE))))?;
+        list.generate(rng)
+    }
+
+    pub fn has_culture(&self, culture: &str) -> bool {
+        self.lists.contains_key(&culture.trim().to_lowercase())
+    }
+}
@@ -0,0 +1,183 @@
+use crate::character::{AbilityScore, Character};
+use crate::combat::Combatant;
+use crate::error_handling::{AppError, Result};
+
+/// A stock stat block for a common NPC role, after the SRD's NPC appendix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NpcPreset {
+    pub role: &'static str,
+    pub cr: &'static str,
+    pub ac: u8,
+    /// Average hit points for the hit dice
+    pub hp: u8,
+    pub hit_dice: &'static str,
+    pub speed: u8,
+    /// STR, DEX, CON, INT, WIS, CHA
+    pub abilities: [u8; 6],
+    pub equipment: &'static [&'static str],
+    pub attacks: &'static [&'static str],
+    pub spells: &'static [&'static str],
+}
+
+const PRESETS: &[NpcPreset] = &[
+    NpcPreset {
+        role: "guard",
+        cr: "1/8",
+        ac: 16,
+        hp: 11,
+        hit_dice: "2d8+2",
+        speed: 30,
+        abilities: [13, 12, 12, 10, 11, 10],
+        equipment: &["Chain shirt", "Shield", "Spear", "Signal horn"],
+        attacks: &["Spear: +3 to hit, reach 5 ft or range 20/60 ft, 1d6+1 piercing (1d8+1 two-handed)"],
+        spells: &[],
+    },
+    NpcPreset {
+        role: "thug",
+        cr: "1/2",
+        ac: 11,
+        hp: 32,
+        hit_dice: "5d8+10",
+        speed: 30,
+        abilities: [15, 11, 14, 10, 10, 11],
+        equipment: &["Leather armor", "Mace", "Heavy crossbow", "Bolts (20)", "Pouch of stolen coins"],
+        attacks: &[
+            "Multiattack: two melee attacks",
+            "Mace: +4 to hit, reach 5 ft, 1d6+2 bludgeoning",
+            "Heavy Crossbow: +2 to hit, range 100/400 ft, 1d10 piercing",
+        ],
+        spells: &[],
+    },
+    NpcPreset {
+        role: "mage apprentice",
+        cr: "1/4",
+        ac: 10,
+        hp: 9,
+        hit_dice: "2d8",
+        speed: 30,
+        abilities: [10, 10, 10, 14, 10, 11],
+        equipment: &["Dagger", "Spellbook", "Component pouch", "Robes"],
+        attacks: &["Dagger: +2 to hit, reach 5 ft or range 20/60 ft, 1d4 piercing"],
+        spells: &["Fire Bolt", "Mending", "Prestidigitation", "Burning Hands", "Disguise Self", "Shield"],
+    },
+    NpcPreset {
+        role: "priest",
+        cr: "2",
+        ac: 13,
+        hp: 27,
+        hit_dice: "5d8+5",
+        speed: 25,
+        abilities: [10, 10, 12, 13, 16, 13],
+        equipment: &["Chain shirt", "Mace", "Holy symbol", "Vestments"],
+        attacks: &["Mace: +2 to hit, reach 5 ft, 1d6 bludgeoning"],
+        spells: &["Light", "Sacred Flame", "Thaumaturgy", "Cure Wounds", "Guiding Bolt", "Sanctuary",
+            "Lesser Restoration", "Spiritual Weapon", "Dispel Magic", "Spirit Guardians"],
+    },
+    NpcPreset {
+        role: "noble",
+        cr: "1/8",
+        ac: 15,
+        hp: 9,
+        hit_dice: "2d8",
+        speed: 30,
+        abilities: [11, 12, 11, 12, 14, 16],
+        equipment: &["Breastplate", "Rapier", "Fine clothes", "Signet ring", "Purse (25 gp)"],
+        attacks: &[
+            "Rapier: +3 to hit, reach 5 ft, 1d8+1 piercing",
+            "Parry (reaction): +2 AC against one melee attack that would hit",
+        ],
+        spells: &[],
+    },
+];
+
+pub fn preset_roles() -> Vec<&'static str> {
+    PRESETS.iter().map(|p| p.role).collect()
+}
+
+/// Look a preset up by role; "mage", "apprentice" and "mage-apprentice" all find the mage apprentice
+pub fn find_preset(role: &str) -> Option<&'static NpcPreset> {
+    let role = role.trim().to_lowercase().replace(['-', '_'], " ");
+    PRESETS.iter()
+        .find(|p| p.role == role)
+        .or_else(|| PRESETS.iter().find(|p| p.role.split(' ').any(|word| word == role)))
+}
+
+impl NpcPreset {
+    /// A character sheet for a named NPC in this role, attacks noted in the description
+    pub fn to_character(&self, name: &str) -> Character {
+        let [stre, dext, cons, intl, wisd, chas] = self.abilities;
+        let mut character = Character::new(name);
+        character.class = Some(self.role.to_string());
+        character.ac = Some(self.ac);
+        character.hp = Some(self.hp);
+        character.max_hp = Some(self.hp);
+        character.speed = Some(self.speed);
+        character.stre = Some(stre);
+        character.dext = Some(dext);
+        character.cons = Some(cons);
+        character.intl = Some(intl);
+        character.wisd = Some(wisd);
+        character.chas = Some(chas);
+        character.desc = Some(format!("CR {}. {}", self.cr, self.attacks.join(". ")));
+        character.attacks = self.attacks.iter().filter_map(|a| crate::attacks::AttackProfile::parse(a).ok()).collect();
+        character.inventory = self.equipment.iter().map(|e| e.to_string()).collect();
+        character.spells = self.spells.iter().map(|s| s.to_string()).collect();
+        character
+    }
+
+    /// Join a fight as an NPC, with the preset's CR for the XP award
+    pub fn to_combatant(&self, name: &str, initiative: i32) -> Combatant {
+        let mut combatant = Combatant::with_sheet(self.to_character(name), initiative);
+        combatant.cr = Some(self.cr.to_string());
+        combatant
+    }
+
+    pub fn dex_mod(&self) -> i32 {
+        self.to_character("").get_ability_modifier(AbilityScore::Dexterity) as i32
+    }
+
+    pub fn describe(&self, name: &str) -> Vec<String> {
+        let labels = ["STR", "DEX", "CON", "INT", "WIS", "CHA"];
+        let abilities: Vec<String> = labels.iter().zip(self.abilities)
+            .map(|(label, score)| format!("{} {} ({:+})", label, score, Character::calculate_modifier(score)))
+            .collect();
+        let mut lines = vec![
+            format!("🛡️ {} - {} (CR {})", name, self.role, self.cr),
+            format!("  AC {} | HP {} ({}) | Speed {} ft", self.ac, self.hp, self.hit_dice, self.speed),
+            format!("  {}", abilities.join("  ")),
+            format!("  Equipment: {}", self.equipment.join(", ")),
+        ];
+        lines.extend(self.attacks.iter().map(|a| format!("  ⚔️ {}", a)));
+        if !self.spells.is_empty() {
+            lines.push(format!("  ✨ Spells: {}", self.spells.join(", ")));
+        }
+        lines
+    }
+}
+
+/// Split `guard "Gate Sergeant"` into the preset and the NPC's name. Without a
+/// name the role itself is used, capitalised.
+pub fn parse_preset_args(args: &str) -> Result<(&'static NpcPreset, String)> {
+    let args = args.trim();
+    let (role, name) = match args.find('"') {
+        Some(quote) => (&args[..quote], args[quote..].trim_matches('"').trim().to_string()),
+        None => (args, String::new()),
+    };
+    // Roles are one or two words; try the longest first so "mage apprentice Tam" works
+    let words: Vec<&str> = role.split_whitespace().collect();
+    for take in (1..=words.len().min(2)).rev() {
+        if let Some(preset) = find_preset(&words[..take].join(" ")) {
+            let rest = words[take..].join(" ");
+            let name = [rest, name].into_iter().filter(|s| !s.is_empty()).collect::<Vec<_>>().join(" ");
+            let name = if name.is_empty() {
+                let mut role = preset.role.to_string();
+                role[..1].make_ascii_uppercase();
+                role
+            } else {
+                name
+            };
+            return Ok((preset, name));
+        }
+    }
+    Err(AppError::ValidationError(format!("Unknown NPC preset '{}' (presets: {})", role.trim(), preset_roles().join(", "))))
+}
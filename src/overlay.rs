@@ -0,0 +1,214 @@
+use crate::combat::CombatTracker;
+use crate::error_handling::{AppError, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::{Path, PathBuf}};
+
+/// What the stream may show, and where the overlay files go
+pub const OVERLAY_CONFIG_FILE: &str = "campaign/overlay.toml";
+/// `overlay` writes initiative.txt, initiative.json and initiative.html here
+pub const DEFAULT_OVERLAY_DIR: &str = "overlay";
+const FILE_STEM: &str = "initiative";
+
+fn yes() -> bool {
+    true
+}
+
+fn default_dir() -> String {
+    DEFAULT_OVERLAY_DIR.to_string()
+}
+
+fn default_formats() -> Vec<String> {
+    ["txt", "json", "html"].iter().map(|f| f.to_string()).collect()
+}
+
+/// Which fields viewers see. Hidden combatants never appear, whatever this says.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct OverlayConfig {
+    #[serde(default = "default_dir")]
+    pub dir: String,
+    /// Any of "txt", "json" and "html"
+    #[serde(default = "default_formats")]
+    pub formats: Vec<String>,
+    #[serde(default = "yes")]
+    pub round: bool,
+    /// "Bloodied" and the like
+    #[serde(default = "yes")]
+    pub condition: bool,
+    #[serde(default = "yes")]
+    pub statuses: bool,
+    /// Exact HP of the player characters
+    #[serde(default)]
+    pub player_hp: bool,
+    /// Exact HP of everything else; spoils a fight, so it's off unless asked for
+    #[serde(default)]
+    pub monster_hp: bool,
+    #[serde(default)]
+    pub ac: bool,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        OverlayConfig {
+            dir: default_dir(),
+            formats: default_formats(),
+            round: true,
+            condition: true,
+            statuses: true,
+            player_hp: false,
+            monster_hp: false,
+            ac: false,
+        }
+    }
+}
+
+impl OverlayConfig {
+    pub fn from_toml(source: &str) -> Result<Self> {
+        let config: OverlayConfig = toml::from_str(source).map_err(|e| AppError::ParseError(e.to_string()))?;
+        if let Some(format) = config.formats.iter().find(|f| !["txt", "json", "html"].contains(&f.as_str())) {
+            return Err(AppError::ValidationError(format!("Overlay format '{}' isn't txt, json or html", format)));
+        }
+        Ok(config)
+    }
+
+    /// The defaults when the campaign has no overlay file
+    pub fn load_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(OverlayConfig::default());
+        }
+        let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        OverlayConfig::from_toml(&contents).context(format!("Overlay file {}", path.display()))
+    }
+}
+
+/// One row of the overlay; fields the config keeps private are left out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OverlayEntry {
+    pub name: String,
+    pub is_current: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ac: Option<i32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub statuses: Vec<String>,
+}
+
+/// What the overlay files show. No fight is an empty list.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct OverlayState {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub round: Option<i32>,
+    pub entries: Vec<OverlayEntry>,
+}
+
+impl OverlayState {
+    pub fn from_tracker(tracker: Option<&CombatTracker>, config: &OverlayConfig) -> Self {
+        let Some(tracker) = tracker else { return OverlayState::default() };
+        let entries = tracker.visible_combatants()
+            .map(|(i, combatant)| {
+                let exact_hp = if combatant.is_player { config.player_hp } else { config.monster_hp };
+                OverlayEntry {
+                    name: tracker.display_name(combatant),
+                    is_current: i == tracker.current_turn,
+                    condition: config.condition.then(|| combatant.hp_descriptor().to_string()),
+                    hp: exact_hp.then(|| format!("{}/{}", combatant.current_hp, combatant.max_hp)),
+                    ac: config.ac.then_some(combatant.ac),
+                    statuses: if config.statuses { combatant.status_effects.iter().map(|s| s.name.clone()).collect() } else { Vec::new() },
+                }
+            })
+            .collect();
+        OverlayState { round: config.round.then_some(tracker.round_number), entries }
+    }
+
+    /// "▶ Aria (Wounded, 18/30 HP, AC 15) [Blessed]"
+    fn entry_line(entry: &OverlayEntry) -> String {
+        let details: Vec<String> = [
+            entry.condition.clone(),
+            entry.hp.as_ref().map(|hp| format!("{} HP", hp)),
+            entry.ac.map(|ac| format!("AC {}", ac)),
+        ].into_iter().flatten().collect();
+        let mut line = format!("{} {}", if entry.is_current { "▶" } else { " " }, entry.name);
+        if !details.is_empty() {
+            line.push_str(&format!(" ({})", details.join(", ")));
+        }
+        if !entry.statuses.is_empty() {
+            line.push_str(&format!(" [{}]", entry.statuses.join(", ")));
+        }
+        line
+    }
+
+    /// Plain text for an OBS text source
+    pub fn to_text(&self) -> String {
+        if self.entries.is_empty() {
+            return "No combat\n".to_string();
+        }
+        let round = self.round.map(|round| format!("Round {}\n", round)).unwrap_or_default();
+        let rows: Vec<String> = self.entries.iter().map(Self::entry_line).collect();
+        format!("{}{}\n", round, rows.join("\n"))
+    }
+
+    /// A page for an OBS browser source. It reloads itself, so the stream follows along.
+    pub fn to_html(&self) -> String {
+        let escape = |text: &str| text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+        let heading = match (self.entries.is_empty(), self.round) {
+            (true, _) => "<h1>No combat</h1>\n".to_string(),
+            (false, Some(round)) => format!("<h1>Round {}</h1>\n", round),
+            (false, None) => String::new(),
+        };
+        let rows: String = self.entries.iter()
+            .map(|entry| format!("<li{}>{}</li>\n", if entry.is_current { " class=\"current\"" } else { "" }, escape(Self::entry_line(entry).trim_start_matches('▶').trim())))
+            .collect();
+        format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta http-equiv=\"refresh\" content=\"1\">\n<style>\n\
+            body {{ background: transparent; color: #fff; font: 24px sans-serif; text-shadow: 2px 2px 3px #000; }}\n\
+            ul {{ list-style: none; padding: 0; }}\n\
+            li.current {{ color: #ffd75f; font-weight: bold; }}\n\
+            li.current::before {{ content: \"▶ \"; }}\n\
+            </style>\n</head>\n<body>\n{}<ul>\n{}</ul>\n</body>\n</html>\n", heading, rows)
+    }
+}
+
+/// Keeps the overlay files up to date, rewriting them only when what they show changes
+#[derive(Debug)]
+pub struct OverlayWriter {
+    pub config: OverlayConfig,
+    last_written: Option<OverlayState>,
+}
+
+impl OverlayWriter {
+    pub fn new(config: OverlayConfig) -> Self {
+        OverlayWriter { config, last_written: None }
+    }
+
+    pub fn dir(&self) -> PathBuf {
+        PathBuf::from(&self.config.dir)
+    }
+
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.config.formats.iter().map(|format| self.dir().join(format!("{}.{}", FILE_STEM, format))).collect()
+    }
+
+    /// Write the files if the fight has changed since last time. Returns whether it did.
+    pub fn update(&mut self, tracker: Option<&CombatTracker>) -> Result<bool> {
+        let state = OverlayState::from_tracker(tracker, &self.config);
+        if self.last_written.as_ref() == Some(&state) {
+            return Ok(false);
+        }
+        fs::create_dir_all(self.dir()).context(format!("Failed to create {}", self.config.dir))?;
+        for path in self.paths() {
+            let contents = match path.extension().and_then(|e| e.to_str()) {
+                Some("json") => serde_json::to_string_pretty(&state).map_err(|e| AppError::ParseError(e.to_string()))?,
+                Some("html") => state.to_html(),
+                _ => state.to_text(),
+            };
+            // Write beside the file and rename over it, so OBS never reads half a file
+            let partial = path.with_extension("partial");
+            fs::write(&partial, contents)
+                .and_then(|_| fs::rename(&partial, &path))
+                .context(format!("Failed to write {}", path.display()))?;
+        }
+        self.last_written = Some(state);
+        Ok(true)
+    }
+}
@@ -0,0 +1,124 @@
+use crate::character::Character;
+use crate::combat::Combatant;
+use crate::resources::UNLIMITED;
+use crate::tactics::with_party;
+use serde::{Deserialize, Serialize};
+
+const SPARK_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Rounds shown in the sparklines; older rounds scroll off the left
+const SPARK_WIDTH: usize = 12;
+/// Monsters losing less than this share of their HP over two rounds means the fight is dragging
+const DRAGGING_PERCENT: u32 = 10;
+
+/// Where the fight stood at the end of a round (or now, for the round in progress)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundSnapshot {
+    pub round: i32,
+    pub party_hp_percent: u32,
+    pub monster_hp_percent: u32,
+    /// Class resource uses and pact slots spent by the party since the fight began
+    pub resources_spent: u32,
+}
+
+/// Round-by-round totals for the pacing dashboard
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PacingLog {
+    /// Resources already spent when the fight started, so earlier fights don't count
+    baseline_spent: Option<u32>,
+    pub rounds: Vec<RoundSnapshot>,
+}
+
+/// Current HP as a share of maximum HP, over the combatants given
+pub fn hp_percent<'a>(combatants: impl Iterator<Item = &'a Combatant>) -> u32 {
+    let (current, max) = combatants.fold((0, 0), |(current, max), c| (current + c.current_hp.max(0), max + c.max_hp.max(0)));
+    if max == 0 { 0 } else { (current * 100 / max) as u32 }
+}
+
+/// Uses of limited features missing from the party's sheets right now
+fn party_resources_spent(combatants: &[Combatant], characters: &[Character]) -> u32 {
+    combatants.iter()
+        .filter(|c| c.character_id.is_some())
+        .filter_map(|c| c.sheet(characters))
+        .map(|sheet| {
+            let features: u32 = sheet.resources.iter()
+                .filter(|r| r.max != UNLIMITED)
+                .map(|r| r.max.saturating_sub(r.current))
+                .sum();
+            features + sheet.pact_slots.as_ref().map_or(0, |slots| slots.max.saturating_sub(slots.current))
+                + sheet.spell_slots.as_ref().map_or(0, |slots| slots.expended.iter().sum())
+        })
+        .sum()
+}
+
+/// ▁▃▅█ for the values, scaled against `max`
+pub fn sparkline(values: &[u32], max: u32) -> String {
+    let max = max.max(1);
+    values.iter()
+        .map(|v| SPARK_BARS[((*v).min(max) as usize * (SPARK_BARS.len() - 1)) / max as usize])
+        .collect()
+}
+
+impl PacingLog {
+    /// Update the snapshot for `round`, starting a new one when the round has moved on
+    pub fn observe(&mut self, round: i32, combatants: &[Combatant], characters: &[Character]) {
+        let spent_now = party_resources_spent(combatants, characters);
+        let baseline = *self.baseline_spent.get_or_insert(spent_now);
+        let snapshot = RoundSnapshot {
+            round,
+            party_hp_percent: hp_percent(combatants.iter().filter(|c| with_party(c))),
+            monster_hp_percent: hp_percent(combatants.iter().filter(|c| !with_party(c))),
+            resources_spent: spent_now.saturating_sub(baseline),
+        };
+        match self.rounds.last_mut() {
+            Some(last) if last.round == round => *last = snapshot,
+            _ => self.rounds.push(snapshot),
+        }
+    }
+
+    /// A warning when monsters have barely been hurt over the last two finished rounds
+    pub fn dragging(&self) -> Option<String> {
+        let finished = self.rounds.len().checked_sub(1)?;
+        if finished < 3 {
+            return None;
+        }
+        let before = self.rounds[finished - 3].monster_hp_percent;
+        let after = self.rounds[finished - 1].monster_hp_percent;
+        (after > 0 && before.saturating_sub(after) < DRAGGING_PERCENT)
+            .then(|| format!("⏳ Dragging: monsters lost {}% HP in two rounds", before.saturating_sub(after)))
+    }
+
+    /// The dashboard: one sparkline per series with its latest value
+    pub fn dashboard_lines(&self) -> Vec<String> {
+        let Some(latest) = self.rounds.last() else {
+            return vec!["No rounds tracked yet".to_string()];
+        };
+        let shown = &self.rounds[self.rounds.len().saturating_sub(SPARK_WIDTH)..];
+        let series = |value: fn(&RoundSnapshot) -> u32| shown.iter().map(value).collect::<Vec<u32>>();
+        let spent = series(|s| s.resources_spent);
+        let most_spent = spent.iter().copied().max().unwrap_or(0);
+        let mut lines = vec![
+            format!("Party {:<12} {:>3}%", sparkline(&series(|s| s.party_hp_percent), 100), latest.party_hp_percent),
+            format!("Foes  {:<12} {:>3}%", sparkline(&series(|s| s.monster_hp_percent), 100), latest.monster_hp_percent),
+            format!("Spent {:<12} {:>4}", sparkline(&spent, most_spent), latest.resources_spent),
+        ];
+        if let Some(warning) = self.dragging() {
+            lines.push(warning);
+        }
+        lines
+    }
+
+    /// `pacing`: the per-round table behind the sparklines
+    pub fn table_lines(&self) -> Vec<String> {
+        if self.rounds.is_empty() {
+            return vec!["📈 No rounds tracked yet".to_string()];
+        }
+        let mut lines = vec!["📈 Pacing by round:".to_string(), "  Round  Party HP  Foes HP  Resources spent".to_string()];
+        for snapshot in &self.rounds {
+            lines.push(format!("  {:>5}  {:>7}%  {:>6}%  {:>15}", snapshot.round, snapshot.party_hp_percent, snapshot.monster_hp_percent, snapshot.resources_spent));
+        }
+        if let Some(warning) = self.dragging() {
+            lines.push(warning);
+        }
+        lines
+    }
+}
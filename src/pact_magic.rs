@@ -0,0 +1,161 @@
+use crate::character::Character;
+use crate::error_handling::{AppError, Result};
+use serde::{Deserialize, Serialize};
+
+/// A warlock's spell slots: few, all the same level, and back after a short rest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PactSlots {
+    pub max: u32,
+    pub current: u32,
+    pub slot_level: u32,
+}
+
+impl PactSlots {
+    pub fn describe(&self) -> String {
+        format!("Pact slots: {}/{} at level {} {}{} (short rest)", self.current, self.max, self.slot_level,
+            "●".repeat(self.current as usize), "○".repeat(self.max.saturating_sub(self.current) as usize))
+    }
+}
+
+/// (name, minimum warlock level, other prerequisite)
+const INVOCATIONS: &[(&str, u32, Option<&str>)] = &[
+    ("Agonizing Blast", 1, Some("eldritch blast cantrip")),
+    ("Armor of Shadows", 1, None),
+    ("Beast Speech", 1, None),
+    ("Beguiling Influence", 1, None),
+    ("Book of Ancient Secrets", 1, Some("Pact of the Tome")),
+    ("Devil's Sight", 1, None),
+    ("Eldritch Sight", 1, None),
+    ("Eldritch Spear", 1, Some("eldritch blast cantrip")),
+    ("Eyes of the Rune Keeper", 1, None),
+    ("Fiendish Vigor", 1, None),
+    ("Gaze of Two Minds", 1, None),
+    ("Mask of Many Faces", 1, None),
+    ("Misty Visions", 1, None),
+    ("Repelling Blast", 1, Some("eldritch blast cantrip")),
+    ("Thief of Five Fates", 1, None),
+    ("Voice of the Chain Master", 1, Some("Pact of the Chain")),
+    ("Mire the Mind", 5, None),
+    ("One with Shadows", 5, None),
+    ("Sign of Ill Omen", 5, None),
+    ("Thirsting Blade", 5, Some("Pact of the Blade")),
+    ("Bewitching Whispers", 7, None),
+    ("Dreadful Word", 7, None),
+    ("Sculptor of Flesh", 7, None),
+    ("Ascendant Step", 9, None),
+    ("Minions of Chaos", 9, None),
+    ("Otherworldly Leap", 9, None),
+    ("Whispers of the Grave", 9, None),
+    ("Lifedrinker", 12, Some("Pact of the Blade")),
+    ("Chains of Carceri", 15, Some("Pact of the Chain")),
+    ("Master of Myriad Forms", 15, None),
+    ("Visions of Distant Realms", 15, None),
+    ("Witch Sight", 15, None),
+];
+
+pub fn is_warlock(character: &Character) -> bool {
+    character.class.as_deref().is_some_and(|c| c.eq_ignore_ascii_case("warlock"))
+}
+
+/// (number of slots, slot level) for a warlock level
+pub fn pact_slots_for_level(level: u32) -> (u32, u32) {
+    let slots = match level {
+        0..=1 => 1,
+        2..=10 => 2,
+        11..=16 => 3,
+        _ => 4,
+    };
+    (slots, level.div_ceil(2).clamp(1, 5))
+}
+
+pub fn invocations_known(level: u32) -> u32 {
+    match level {
+        0..=1 => 0,
+        2..=4 => 2,
+        5..=6 => 3,
+        7..=8 => 4,
+        9..=11 => 5,
+        12..=14 => 6,
+        15..=17 => 7,
+        _ => 8,
+    }
+}
+
+/// Invocations a warlock of `level` qualifies for, with any pact or cantrip prerequisite noted
+pub fn available_invocations(level: u32) -> Vec<String> {
+    INVOCATIONS.iter()
+        .filter(|(_, min_level, _)| *min_level <= level)
+        .map(|(name, _, prereq)| match prereq {
+            Some(prereq) => format!("{} (needs {})", name, prereq),
+            None => name.to_string(),
+        })
+        .collect()
+}
+
+/// Keep a warlock's pact slots in step with their level, carrying over slots
+/// already spent. Anyone else has none.
+pub fn refresh_pact_slots(character: &mut Character) {
+    if !is_warlock(character) {
+        character.pact_slots = None;
+        return;
+    }
+    let (max, slot_level) = pact_slots_for_level(character.level.unwrap_or(1) as u32);
+    let spent = character.pact_slots.map(|s| s.max.saturating_sub(s.current)).unwrap_or(0);
+    character.pact_slots = Some(PactSlots { max, current: max.saturating_sub(spent), slot_level });
+}
+
+/// Cast with a pact slot, returning the slots left
+pub fn spend_pact_slot(character: &mut Character) -> Result<PactSlots> {
+    refresh_pact_slots(character);
+    let name = character.name.clone();
+    let slots = character.pact_slots.as_mut()
+        .ok_or_else(|| AppError::ValidationError(format!("{} isn't a warlock", name)))?;
+    if slots.current == 0 {
+        return Err(AppError::ValidationError(format!("{} has no pact slots left until a short rest", name)));
+    }
+    slots.current -= 1;
+    Ok(*slots)
+}
+
+/// Pact slots come back on any rest; returns true when some were spent
+pub fn rest_pact_slots(character: &mut Character) -> bool {
+    refresh_pact_slots(character);
+    match character.pact_slots.as_mut() {
+        Some(slots) if slots.current < slots.max => {
+            slots.current = slots.max;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Learn an invocation, checking the level requirement and the number known
+pub fn learn_invocation(character: &mut Character, name: &str) -> Result<String> {
+    let level = character.level.unwrap_or(1) as u32;
+    let &(invocation, min_level, _) = INVOCATIONS.iter()
+        .find(|(n, ..)| n.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| AppError::NotFound(format!("Invocation '{}'", name.trim())))?;
+    if !is_warlock(character) {
+        return Err(AppError::ValidationError(format!("{} isn't a warlock", character.name)));
+    }
+    if min_level > level {
+        return Err(AppError::ValidationError(format!("{} needs warlock level {}", invocation, min_level)));
+    }
+    if character.invocations.iter().any(|i| i == invocation) {
+        return Err(AppError::ValidationError(format!("{} already knows {}", character.name, invocation)));
+    }
+    if character.invocations.len() as u32 >= invocations_known(level) {
+        return Err(AppError::ValidationError(format!("{} already knows {} invocations, the most for level {}",
+            character.name, character.invocations.len(), level)));
+    }
+    character.invocations.push(invocation.to_string());
+    Ok(invocation.to_string())
+}
+
+/// How many invocations a warlock may still choose at their level
+pub fn invocations_due(character: &Character) -> u32 {
+    if !is_warlock(character) {
+        return 0;
+    }
+    invocations_known(character.level.unwrap_or(1) as u32).saturating_sub(character.invocations.len() as u32)
+}
@@ -0,0 +1,145 @@
+use crate::tui::AppMode;
+
+/// The interactive modes the palette reaches, in Tools menu order, with the name shown beside their commands
+pub const PALETTE_MODES: [(AppMode, &str); 12] = [
+    (AppMode::CombatTrackerTUI, "Combat"),
+    (AppMode::InitiativeTrackerTUI, "Initiative"),
+    (AppMode::DiceTUI, "Dice"),
+    (AppMode::SearchTUI, "Search"),
+    (AppMode::NpcGeneratorTUI, "NPC"),
+    (AppMode::SkillChallengeTUI, "Challenge"),
+    (AppMode::DowntimeTUI, "Downtime"),
+    (AppMode::EncounterTUI, "Encounter"),
+    (AppMode::CharacterCreationTUI, "Creation"),
+    (AppMode::CharacterDisplayTUI, "Characters"),
+    (AppMode::CharacterDeletionTUI, "Deletion"),
+    (AppMode::CharacterUndeleteTUI, "Undelete"),
+];
+
+pub fn mode_label(mode: &AppMode) -> &'static str {
+    PALETTE_MODES.iter().find(|(m, _)| m == mode).map_or("", |(_, label)| label)
+}
+
+/// What picking an entry does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteAction {
+    /// Switch to the entry's mode
+    Open,
+    /// Run the command as it is
+    Run(String),
+    /// Put the command in the input line so its arguments can be typed
+    Fill(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteEntry {
+    pub mode: AppMode,
+    /// The command as the mode's help shows it, e.g. "attack <target> [weapon]"
+    pub usage: String,
+    pub description: String,
+    pub action: PaletteAction,
+}
+
+impl PaletteEntry {
+    /// "Open Combat", to switch modes from anywhere
+    pub fn open(mode: AppMode) -> Self {
+        let label = mode_label(&mode);
+        Self { mode, usage: format!("open {}", label.to_lowercase()), description: format!("Go to the {} tool", label), action: PaletteAction::Open }
+    }
+
+    /// Read an entry from a line of a mode's help, "  roll <dice> - Roll dice". Lines
+    /// with required `<arguments>` fill the input line; the rest run straight away.
+    pub fn from_help(mode: AppMode, line: &str) -> Option<Self> {
+        let text = line.strip_prefix("  ").filter(|text| !text.starts_with(' '))?;
+        let (usage, description) = text.split_once(" - ")?;
+        let first = usage.split(" | ").next().unwrap_or(usage);
+        let command: Vec<&str> = first.split_whitespace()
+            .take_while(|word| !word.starts_with(['<', '[', '(']))
+            .map(|word| word.split('|').next().unwrap_or(word))
+            .collect();
+        if command.is_empty() {
+            return None;
+        }
+        let command = command.join(" ");
+        let action = if first.contains('<') { PaletteAction::Fill(format!("{} ", command)) } else { PaletteAction::Run(command) };
+        Some(Self { mode, usage: usage.trim().to_string(), description: description.trim().to_string(), action })
+    }
+
+    /// "[Combat] attack <target> [weapon] - Roll attack against target's AC"
+    pub fn line(&self) -> String {
+        format!("[{}] {} - {}", mode_label(&self.mode), self.usage, self.description)
+    }
+}
+
+/// How well `query` matches `text` as a subsequence, ignoring case: letters in a row
+/// and letters that start a word score higher. None when some letter is missing.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut at = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = at + text[at..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 3;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 2;
+        }
+        previous = Some(found);
+        at = found + 1;
+    }
+    Some(score)
+}
+
+/// The Ctrl+P overlay: a query, the entries it matches and the one highlighted
+#[derive(Debug, Clone, Default)]
+pub struct CommandPalette {
+    pub query: String,
+    pub selected: usize,
+    pub entries: Vec<PaletteEntry>,
+}
+
+impl CommandPalette {
+    pub fn new(entries: Vec<PaletteEntry>) -> Self {
+        Self { entries, ..Self::default() }
+    }
+
+    /// Entries matching every word of the query, best first. The command itself counts
+    /// for more than its mode or description, and on a tie the current mode comes first.
+    pub fn matches(&self, current: &AppMode) -> Vec<&PaletteEntry> {
+        let mut scored: Vec<(u32, bool, &PaletteEntry)> = self.entries.iter()
+            .filter_map(|entry| {
+                let haystack = format!("{} {}", mode_label(&entry.mode), entry.description);
+                let score = self.query.split_whitespace()
+                    .map(|word| fuzzy_score(word, &entry.usage).map(|s| s * 2).or_else(|| fuzzy_score(word, &haystack)))
+                    .sum::<Option<u32>>()?;
+                Some((score, &entry.mode != current, entry))
+            })
+            .collect();
+        scored.sort_by_key(|&(score, elsewhere, _)| (std::cmp::Reverse(score), elsewhere));
+        scored.into_iter().map(|(_, _, entry)| entry).collect()
+    }
+
+    pub fn type_char(&mut self, c: char) {
+        self.query.push(c);
+        self.selected = 0;
+    }
+
+    pub fn backspace(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    pub fn move_selection(&mut self, current: &AppMode, down: bool) {
+        let count = self.matches(current).len();
+        if count > 0 {
+            self.selected = if down { (self.selected + 1) % count } else { (self.selected + count - 1) % count };
+        }
+    }
+
+    pub fn chosen(&self, current: &AppMode) -> Option<PaletteEntry> {
+        self.matches(current).get(self.selected).map(|entry| (*entry).clone())
+    }
+}
@@ -0,0 +1,238 @@
+use crate::combat::CombatTracker;
+use crate::error_handling::{AppError, Result, ResultExt};
+use crate::tui::{BACKGROUND_COLOR, BORDER_COLOR, MENU_COLOR, SELECTED_COLOR, TEXT_COLOR};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+    Frame, Terminal,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+
+/// The DM's TUI listens here; `dnd_tools player-view` connects to it
+pub const DEFAULT_PLAYER_VIEW_ADDR: &str = "127.0.0.1:7878";
+
+/// How long a screen gets to take a view before it's dropped, so a stalled screen
+/// can't freeze the DM's TUI
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// One row of the player-facing initiative list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerEntry {
+    pub name: String,
+    pub condition: String,
+    pub statuses: Vec<String>,
+    pub is_current: bool,
+}
+
+/// Everything the players' screen is allowed to know
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerViewState {
+    pub round: i32,
+    pub entries: Vec<PlayerEntry>,
+}
+
+impl PlayerViewState {
+    /// Build the player-safe view: descriptive HP only, no AC or exact numbers,
+    /// and nothing about hidden combatants
+    pub fn from_tracker(tracker: &CombatTracker) -> Self {
+        let entries = tracker.visible_combatants()
+            .map(|(i, combatant)| PlayerEntry {
+                name: tracker.display_name(combatant),
+                condition: combatant.hp_descriptor().to_string(),
+                statuses: combatant.status_effects.iter().map(|s| s.name.clone()).collect(),
+                is_current: i == tracker.current_turn,
+            })
+            .collect();
+        PlayerViewState { round: tracker.round_number, entries }
+    }
+
+    pub fn current_name(&self) -> Option<&str> {
+        self.entries.iter().find(|e| e.is_current).map(|e| e.name.as_str())
+    }
+}
+
+/// Sends the player view to any connected player screens, one JSON object per line
+#[derive(Debug)]
+pub struct PlayerViewServer {
+    listener: TcpListener,
+    clients: Vec<TcpStream>,
+    last_sent: Option<String>,
+}
+
+impl PlayerViewServer {
+    pub fn bind(addr: &str) -> Result<Self> {
+        let socket: SocketAddr = addr.parse()
+            .map_err(|_| AppError::ValidationError(format!("'{}' is not an address like {}", addr, DEFAULT_PLAYER_VIEW_ADDR)))?;
+        if !socket.ip().is_loopback() {
+            return Err(AppError::ValidationError(format!("The player view only listens on this machine (127.0.0.1 or [::1]), not {}", socket.ip())));
+        }
+        let listener = TcpListener::bind(socket).context(format!("Could not listen on {}", addr))?;
+        listener.set_nonblocking(true).context("Could not configure player view socket")?;
+        log::info!("Player view listening on {}", addr);
+        Ok(PlayerViewServer { listener, clients: Vec::new(), last_sent: None })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.listener.local_addr().ok()
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Accept new screens and send them the latest view; push changes to everyone.
+    /// Screens that have gone away, or stop reading, are dropped.
+    pub fn publish(&mut self, state: &PlayerViewState) {
+        let line = match serde_json::to_string(state) {
+            Ok(json) => json + "\n",
+            Err(e) => {
+                log::warn!("Could not encode player view: {}", e);
+                return;
+            }
+        };
+        let changed = self.last_sent.as_deref() != Some(line.as_str());
+
+        while let Ok((mut stream, peer)) = self.listener.accept() {
+            log::info!("Player view connected from {}", peer);
+            if stream.set_nonblocking(false).and_then(|_| stream.set_write_timeout(Some(WRITE_TIMEOUT))).is_err() {
+                continue;
+            }
+            if changed || stream.write_all(line.as_bytes()).is_ok() {
+                self.clients.push(stream);
+            }
+        }
+
+        if changed {
+            self.clients.retain_mut(|client| client.write_all(line.as_bytes()).is_ok());
+            self.last_sent = Some(line);
+        }
+    }
+}
+
+/// Read views from the DM's TUI on a background thread; the channel closes on disconnect
+pub fn subscribe(addr: &str) -> Result<Receiver<PlayerViewState>> {
+    let stream = TcpStream::connect(addr)
+        .map_err(|e| AppError::NetworkError(format!("Could not connect to the DM view at {}: {}", addr, e)))?;
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            match serde_json::from_str::<PlayerViewState>(&line) {
+                Ok(state) => {
+                    if tx.send(state).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => log::warn!("Ignoring bad player view data: {}", e),
+            }
+        }
+    });
+    Ok(rx)
+}
+
+/// `dnd_tools player-view [addr]`: a read-only screen for the players
+pub fn run_player_view(addr: &str) -> Result<()> {
+    let rx = subscribe(addr)?;
+
+    enable_raw_mode().context("Failed to start the player view")?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen).context("Failed to start the player view")?;
+    let result = player_view_loop(&mut Terminal::new(CrosstermBackend::new(stdout))?, &rx);
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen);
+    result
+}
+
+fn player_view_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, rx: &Receiver<PlayerViewState>) -> Result<()> {
+    let mut state = None;
+    let mut connected = true;
+    loop {
+        loop {
+            match rx.try_recv() {
+                Ok(latest) => state = Some(latest),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    connected = false;
+                    break;
+                }
+            }
+        }
+        terminal.draw(|f| render_player_view(f, state.as_ref(), connected))?;
+
+        if event::poll(std::time::Duration::from_millis(250))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+            return Ok(());
+        }
+    }
+}
+
+fn render_player_view(f: &mut Frame, state: Option<&PlayerViewState>, connected: bool) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(crate::banner::BANNER_HEIGHT as u16 + 2), // Whose turn
+            Constraint::Min(5),                                            // Initiative
+            Constraint::Length(1),                                         // Status
+        ])
+        .split(f.area());
+
+    // A hidden combatant's turn shows as "..." rather than giving it away
+    let current = state.map(|s| s.current_name().unwrap_or("...")).unwrap_or("Waiting for combat");
+    let round = state.map(|s| format!("Round {}", s.round)).unwrap_or_default();
+    let banner = crate::banner::render_banner_wrapped(current, chunks[0].width.saturating_sub(2) as usize);
+    let banner = Paragraph::new(banner.into_iter().take(crate::banner::BANNER_HEIGHT).collect::<Vec<_>>().join("\n"))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(TEXT_COLOR).add_modifier(Modifier::BOLD))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(BORDER_COLOR))
+                .style(Style::default().bg(BACKGROUND_COLOR))
+                .title(format!("⚔️ Now Up — {}", round))
+        );
+    f.render_widget(banner, chunks[0]);
+
+    let items: Vec<ListItem> = state.map(|s| s.entries.as_slice()).unwrap_or_default().iter()
+        .map(|entry| {
+            let marker = if entry.is_current { "►" } else { " " };
+            let statuses = if entry.statuses.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", entry.statuses.join(", "))
+            };
+            let style = if entry.is_current {
+                Style::default().bg(SELECTED_COLOR).fg(TEXT_COLOR).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(TEXT_COLOR)
+            };
+            ListItem::new(format!("{} {} — {}{}", marker, entry.name, entry.condition, statuses)).style(style)
+        })
+        .collect();
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(BORDER_COLOR))
+            .style(Style::default().bg(MENU_COLOR))
+            .title("Initiative")
+    );
+    f.render_widget(list, chunks[1]);
+
+    let status = if connected { "Player view (read-only) | q: quit" } else { "⚠️ Disconnected from the DM view | q: quit" };
+    f.render_widget(Paragraph::new(status).style(Style::default().fg(TEXT_COLOR)), chunks[2]);
+}
@@ -0,0 +1,87 @@
+use crate::character::{AbilityScore, Character};
+use crate::error_handling::{AppError, Result};
+
+/// The ability a prepared caster's daily list depends on
+pub fn preparation_ability(character: &Character) -> Option<AbilityScore> {
+    match character.class.as_deref().unwrap_or_default().to_lowercase().as_str() {
+        "cleric" | "druid" => Some(AbilityScore::Wisdom),
+        "wizard" => Some(AbilityScore::Intelligence),
+        _ => None,
+    }
+}
+
+/// Level + spellcasting modifier, at least one
+pub fn max_prepared(character: &Character) -> Option<u32> {
+    let ability = preparation_ability(character)?;
+    let level = character.level.unwrap_or(1) as i32;
+    Some((level + character.get_ability_modifier(ability) as i32).max(1) as u32)
+}
+
+/// A day's preparation in progress: spells are toggled on and off, and the
+/// count is only checked when it's saved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preparation {
+    pub character: String,
+    pub max: u32,
+    /// The class list or spellbook to choose from
+    pub known: Vec<String>,
+    pub selected: Vec<String>,
+}
+
+impl Preparation {
+    pub fn start(character: &Character) -> Result<Self> {
+        let max = max_prepared(character).ok_or_else(|| AppError::ValidationError(format!(
+            "{} isn't a prepared caster (cleric, druid or wizard)", character.name)))?;
+        if character.spells.is_empty() {
+            return Err(AppError::ValidationError(format!("{} has no spells on their list to prepare from", character.name)));
+        }
+        Ok(Preparation {
+            character: character.name.clone(),
+            max,
+            known: character.spells.clone(),
+            selected: character.prepared.iter().filter(|s| character.spells.contains(s)).cloned().collect(),
+        })
+    }
+
+    /// Toggle a spell by name or by its number in the list. Returns the spell and whether it's now prepared.
+    pub fn toggle(&mut self, choice: &str) -> Result<(String, bool)> {
+        let choice = choice.trim();
+        let spell = match choice.parse::<usize>() {
+            Ok(n) => self.known.get(n.wrapping_sub(1)),
+            Err(_) => self.known.iter().find(|s| s.eq_ignore_ascii_case(choice)),
+        }.cloned().ok_or_else(|| AppError::NotFound(format!("Spell '{}' on {}'s list", choice, self.character)))?;
+
+        if let Some(index) = self.selected.iter().position(|s| *s == spell) {
+            self.selected.remove(index);
+            Ok((spell, false))
+        } else {
+            self.selected.push(spell.clone());
+            Ok((spell, true))
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let count = self.selected.len() as u32;
+        if count > self.max {
+            return Err(AppError::ValidationError(format!("{} spells prepared but {} can prepare only {}; unprepare {}",
+                count, self.character, self.max, count - self.max)));
+        }
+        Ok(())
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("📖 {} prepares {}/{} spells:", self.character, self.selected.len(), self.max)];
+        lines.extend(self.known.iter().enumerate().map(|(i, spell)| {
+            let mark = if self.selected.contains(spell) { "x" } else { " " };
+            format!("  {:>2}. [{}] {}", i + 1, mark, spell)
+        }));
+        lines
+    }
+
+    /// Check the count and write the list to the character
+    pub fn save(self, character: &mut Character) -> Result<()> {
+        self.validate()?;
+        character.prepared = self.selected;
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+/// Condition rules text from the SRD 5.1 (CC BY 4.0), one effect per line
+const CONDITIONS: &[(&str, &[&str])] = &[
+    ("Blinded", &[
+        "A blinded creature can't see and automatically fails any ability check that requires sight.",
+        "Attack rolls against the creature have advantage, and the creature's attack rolls have disadvantage.",
+    ]),
+    ("Charmed", &[
+        "A charmed creature can't attack the charmer or target the charmer with harmful abilities or magical effects.",
+        "The charmer has advantage on any ability check to interact socially with the creature.",
+    ]),
+    ("Deafened", &[
+        "A deafened creature can't hear and automatically fails any ability check that requires hearing.",
+    ]),
+    ("Exhaustion", &[
+        "Level 1: Disadvantage on ability checks.",
+        "Level 2: Speed halved.",
+        "Level 3: Disadvantage on attack rolls and saving throws.",
+        "Level 4: Hit point maximum halved.",
+        "Level 5: Speed reduced to 0.",
+        "Level 6: Death.",
+        "Effects are cumulative. A long rest with food and drink reduces exhaustion by 1 level.",
+    ]),
+    ("Frightened", &[
+        "A frightened creature has disadvantage on ability checks and attack rolls while the source of its fear is within line of sight.",
+        "The creature can't willingly move closer to the source of its fear.",
+    ]),
+    ("Grappled", &[
+        "A grappled creature's speed becomes 0, and it can't benefit from any bonus to its speed.",
+        "The condition ends if the grappler is incapacitated.",
+        "The condition also ends if an effect removes the grappled creature from the reach of the grappler or grappling effect, such as when a creature is hurled away by the thunderwave spell.",
+    ]),
+    ("Incapacitated", &[
+        "An incapacitated creature can't take actions or reactions.",
+    ]),
+    ("Invisible", &[
+        "An invisible creature is impossible to see without the aid of magic or a special sense. For the purpose of hiding, the creature is heavily obscured. The creature's location can be detected by any noise it makes or any tracks it leaves.",
+        "Attack rolls against the creature have disadvantage, and the creature's attack rolls have advantage.",
+    ]),
+    ("Paralyzed", &[
+        "A paralyzed creature is incapacitated and can't move or speak.",
+        "The creature automatically fails Strength and Dexterity saving throws.",
+        "Attack rolls against the creature have advantage.",
+        "Any attack that hits the creature is a critical hit if the attacker is within 5 feet of the creature.",
+    ]),
+    ("Petrified", &[
+        "A petrified creature is transformed, along with any nonmagical object it is wearing or carrying, into a solid inanimate substance (usually stone). Its weight increases by a factor of ten, and it ceases aging.",
+        "The creature is incapacitated, can't move or speak, and is unaware of its surroundings.",
+        "Attack rolls against the creature have advantage.",
+        "The creature automatically fails Strength and Dexterity saving throws.",
+        "The creature has resistance to all damage.",
+        "The creature is immune to poison and disease, although a poison or disease already in its system is suspended, not neutralized.",
+    ]),
+    ("Poisoned", &[
+        "A poisoned creature has disadvantage on attack rolls and ability checks.",
+    ]),
+    ("Prone", &[
+        "A prone creature's only movement option is to crawl, unless it stands up and thereby ends the condition.",
+        "The creature has disadvantage on attack rolls.",
+        "An attack roll against the creature has advantage if the attacker is within 5 feet of the creature. Otherwise, the attack roll has disadvantage.",
+    ]),
+    ("Restrained", &[
+        "A restrained creature's speed becomes 0, and it can't benefit from any bonus to its speed.",
+        "Attack rolls against the creature have advantage, and the creature's attack rolls have disadvantage.",
+        "The creature has disadvantage on Dexterity saving throws.",
+    ]),
+    ("Stunned", &[
+        "A stunned creature is incapacitated, can't move, and can speak only falteringly.",
+        "The creature automatically fails Strength and Dexterity saving throws.",
+        "Attack rolls against the creature have advantage.",
+    ]),
+    ("Unconscious", &[
+        "An unconscious creature is incapacitated, can't move or speak, and is unaware of its surroundings.",
+        "The creature drops whatever it's holding and falls prone.",
+        "The creature automatically fails Strength and Dexterity saving throws.",
+        "Attack rolls against the creature have advantage.",
+        "Any attack that hits the creature is a critical hit if the attacker is within 5 feet of the creature.",
+    ]),
+];
+
+/// Short rulings for actions that come up at the table, summarised from the SRD 5.1
+const RULES: &[(&str, &[&str])] = &[
+    ("Grapple", &[
+        "Replaces one attack of the Attack action. The target can be at most one size larger than you and within reach.",
+        "Contest: your Athletics vs the target's Athletics or Acrobatics (its choice). On a win the target is grappled.",
+        "You need a free hand. The grappled creature's speed is 0; you can drag or carry it at half speed.",
+        "It escapes with an action: Athletics or Acrobatics vs your Athletics. You can release it at any time (no action).",
+    ]),
+    ("Shove", &[
+        "Replaces one attack of the Attack action. The target can be at most one size larger than you and within reach.",
+        "Contest: your Athletics vs the target's Athletics or Acrobatics (its choice).",
+        "On a win you knock the target prone or push it 5 feet away from you.",
+    ]),
+    ("Hide", &[
+        "Take the Hide action and make a Dexterity (Stealth) check; it becomes the DC to find you.",
+        "You can't hide from a creature that can see you clearly, and noise or attacking gives your position away.",
+        "A creature searching for you uses its action on a Wisdom (Perception) check against your Stealth result.",
+        "Unseen attackers have advantage on attack rolls; you reveal yourself when the attack hits or misses.",
+    ]),
+    ("Help", &[
+        "Take the Help action to give an ally advantage on their next ability check for a task you could help with.",
+        "Or distract a creature within 5 feet of you: the next attack roll an ally makes against it before your next turn has advantage.",
+    ]),
+    ("Two-Weapon Fighting", &[
+        "When you take the Attack action with a light melee weapon in one hand, you can use a bonus action to attack with a different light melee weapon in the other.",
+        "Don't add your ability modifier to the bonus attack's damage unless it's negative (the Two-Weapon Fighting style adds it).",
+        "A light weapon you throw can make the bonus attack too.",
+    ]),
+    ("Mounted Combat", &[
+        "Mounting or dismounting costs half your speed. The mount must be at least one size larger than you.",
+        "A controlled mount acts on your initiative and can only Dash, Disengage, or Dodge; it must have been trained to accept a rider.",
+        "An independent mount keeps its own initiative and acts as it likes.",
+        "If the mount is moved against its will while you're on it, or you're knocked prone, make a DC 10 Dexterity save or fall off and land prone within 5 feet.",
+    ]),
+    ("Underwater Combat", &[
+        "Melee weapon attacks have disadvantage unless made with a dagger, javelin, shortsword, spear, or trident (or by a creature with a swimming speed).",
+        "Ranged weapon attacks automatically miss beyond normal range, and have disadvantage within it unless made with a crossbow, net, or a thrown weapon like a javelin, spear, trident, or dart.",
+        "Creatures and objects fully underwater have resistance to fire damage.",
+    ]),
+];
+
+pub fn condition_names() -> Vec<&'static str> {
+    CONDITIONS.iter().map(|(name, _)| *name).collect()
+}
+
+/// Find an entry by exact name, or by a prefix of its name or one of its words
+/// that only one entry matches ("para", "restr", "mount")
+fn lookup(table: &'static [(&'static str, &'static [&'static str])], query: &str) -> Option<(&'static str, &'static [&'static str])> {
+    let normalize = |s: &str| s.trim().to_lowercase().replace('-', " ");
+    let query = normalize(query);
+    if query.is_empty() {
+        return None;
+    }
+    if let Some(&(name, text)) = table.iter().find(|(name, _)| normalize(name) == query) {
+        return Some((name, text));
+    }
+    let mut matches = table.iter().filter(|(name, _)| {
+        let name = normalize(name);
+        name.starts_with(&query) || name.split(' ').any(|word| word.starts_with(&query))
+    });
+    match (matches.next(), matches.next()) {
+        (Some(&(name, text)), None) => Some((name, text)),
+        _ => None,
+    }
+}
+
+/// Full rules text for a condition. A unique prefix is enough ("para", "restr").
+pub fn condition_text(query: &str) -> Option<(&'static str, &'static [&'static str])> {
+    lookup(CONDITIONS, query)
+}
+
+pub fn rule_topics() -> Vec<&'static str> {
+    RULES.iter().map(|(name, _)| *name).collect()
+}
+
+/// Rules summary for a common action, e.g. "grapple", "two-weapon", "underwater"
+pub fn rule_text(query: &str) -> Option<(&'static str, &'static [&'static str])> {
+    lookup(RULES, query)
+}
+
+fn entry_lines(icon: &str, name: &str, text: &[&str]) -> Vec<String> {
+    std::iter::once(format!("{} {}", icon, name))
+        .chain(text.iter().map(|line| format!("  • {}", line)))
+        .chain([format!("  — {}", crate::content::Source::Srd.attribution())])
+        .collect()
+}
+
+/// `credits`: where the reference content comes from and the terms it's under
+fn credit_lines() -> Vec<String> {
+    use crate::content::{license_notice, Source};
+    let every_source = [Source::Srd, Source::Wikidot { url: String::new() }, Source::Campaign];
+    std::iter::once("📜 Content sources and licenses".to_string())
+        .chain(license_notice(&every_source).lines().map(|line| format!("  {}", line)))
+        .collect()
+}
+
+/// Output for the commands that work in every mode, or None if `command` isn't one
+pub fn quick_reference(command: &str) -> Option<Vec<String>> {
+    let mut parts = command.split_whitespace();
+    let cmd = parts.next()?.to_lowercase();
+    let topic = parts.collect::<Vec<_>>().join(" ");
+    match cmd.as_str() {
+        "cond" | "condition" => Some(match condition_text(&topic) {
+            Some((name, text)) => entry_lines("📕", name, text),
+            None if topic.is_empty() => vec![format!("📕 Conditions: {}", condition_names().join(", "))],
+            None => vec![format!("❌ No condition matches '{}'. Conditions: {}", topic, condition_names().join(", "))],
+        }),
+        "rule" | "rules" => Some(match rule_text(&topic) {
+            Some((name, text)) => entry_lines("📘", name, text),
+            None if topic.is_empty() => vec![format!("📘 Rules: {}", rule_topics().join(", "))],
+            None => vec![format!("❌ No rule matches '{}'. Rules: {}", topic, rule_topics().join(", "))],
+        }),
+        "credits" | "licenses" => Some(credit_lines()),
+        "xp" | "cr" => {
+            let args = topic.to_lowercase();
+            Some(crate::xp::quick_command(&cmd, &args.split_whitespace().collect::<Vec<_>>()))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,181 @@
+use crate::character::Character;
+use crate::file_manager::{CharacterSummary, IndexEntry};
+use std::cell::{Ref, RefCell, RefMut};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Characters are looked up by id rather than copied into other records, so combat,
+/// the character screens and the saved files all read the same sheet.
+/// Ids are saved with the sheet; 0 means not registered yet.
+pub fn register(characters: &mut [Character]) {
+    register_above(characters, 0);
+}
+
+/// Like `register`, with new ids also kept above `reserved`, the highest id of a
+/// sheet that hasn't been read yet
+fn register_above(characters: &mut [Character], reserved: u32) {
+    let mut next = characters.iter().map(|c| c.id).max().unwrap_or(0).max(reserved) + 1;
+    let mut seen = Vec::new();
+    for character in characters.iter_mut() {
+        if character.id == 0 || seen.contains(&character.id) {
+            character.id = next;
+            next += 1;
+        }
+        seen.push(character.id);
+    }
+}
+
+/// Add a character and give it an id, returning the id
+pub fn insert(characters: &mut Vec<Character>, character: Character) -> u32 {
+    characters.push(character);
+    register(characters);
+    characters.last().map(|c| c.id).unwrap_or_default()
+}
+
+pub fn by_id(characters: &[Character], id: u32) -> Option<&Character> {
+    characters.iter().find(|c| c.id == id)
+}
+
+pub fn by_id_mut(characters: &mut [Character], id: u32) -> Option<&mut Character> {
+    characters.iter_mut().find(|c| c.id == id)
+}
+
+/// The one set of character sheets for a session. Clones share the same sheets,
+/// so the combat tracker, the TUI screens and the save routines all see each
+/// other's changes instead of working on copies.
+#[derive(Debug, Clone, Default)]
+pub struct CharacterStore {
+    characters: Rc<RefCell<Vec<Character>>>,
+    /// Sheets in the index that haven't been read yet; each is read the first time it's needed
+    unread: Rc<RefCell<Vec<IndexEntry>>>,
+    /// The characters directory, for reading those sheets
+    dir: Rc<PathBuf>,
+    /// Names in index order, so sheets read one at a time still list in that order
+    order: Rc<Vec<String>>,
+}
+
+impl CharacterStore {
+    pub fn new(mut characters: Vec<Character>) -> Self {
+        register(&mut characters);
+        CharacterStore { characters: Rc::new(RefCell::new(characters)), ..Default::default() }
+    }
+
+    /// The sheets saved in the characters directory. Only the index is read up
+    /// front; each sheet is read when it's first looked up.
+    pub fn load() -> Self {
+        Self::load_in(Path::new(crate::file_manager::CHARACTERS_DIR), Path::new(crate::file_manager::CHARACTER_INDEX_FILE))
+    }
+
+    pub fn load_in(dir: &Path, index_path: &Path) -> Self {
+        let entries = crate::file_manager::character_index_entries_in(dir, index_path);
+        let order = entries.iter().map(|entry| entry.summary.name.clone()).collect();
+        CharacterStore {
+            unread: Rc::new(RefCell::new(entries)),
+            dir: Rc::new(dir.to_path_buf()),
+            order: Rc::new(order),
+            ..Default::default()
+        }
+    }
+
+    fn position(&self, name: &str) -> usize {
+        self.order.iter().position(|n| n == name).unwrap_or(usize::MAX)
+    }
+
+    /// Read the unread sheets `wanted` picks out and add them in index order
+    fn read_unread(&self, wanted: impl Fn(&IndexEntry) -> bool) {
+        let entries: Vec<IndexEntry> = {
+            let mut unread = self.unread.borrow_mut();
+            let (taken, kept) = unread.drain(..).partition(|entry| wanted(entry));
+            *unread = kept;
+            taken
+        };
+        if entries.is_empty() {
+            return;
+        }
+        let paths: Vec<PathBuf> = entries.iter().map(|entry| entry.path.clone()).collect();
+        let sheets = crate::file_manager::load_sheets_in(&self.dir, &paths);
+        log::debug!("Read {} of the character sheets", sheets.len());
+        let reserved = self.unread.borrow().iter().map(|entry| entry.id).max().unwrap_or(0);
+        let mut characters = self.characters.borrow_mut();
+        characters.extend(sheets);
+        register_above(&mut characters, reserved);
+        characters.sort_by_key(|c| self.position(&c.name));
+    }
+
+    /// Read every sheet not read yet
+    fn ensure_loaded(&self) {
+        self.read_unread(|_| true);
+    }
+
+    /// Read access to every sheet; don't hold it across a call that might change them
+    pub fn read(&self) -> Ref<'_, Vec<Character>> {
+        self.ensure_loaded();
+        self.characters.borrow()
+    }
+
+    pub fn write(&self) -> RefMut<'_, Vec<Character>> {
+        self.ensure_loaded();
+        self.characters.borrow_mut()
+    }
+
+    /// How many sheets there are, without reading them
+    pub fn len(&self) -> usize {
+        self.characters.borrow().len() + self.unread.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Name, class, level and HP of every sheet, without reading the ones not read yet
+    pub fn summaries(&self) -> Vec<CharacterSummary> {
+        let mut summaries: Vec<CharacterSummary> = self.characters.borrow().iter().map(CharacterSummary::of).collect();
+        summaries.extend(self.unread.borrow().iter().map(|entry| entry.summary.clone()));
+        summaries.sort_by_key(|summary| self.position(&summary.name));
+        summaries
+    }
+
+    pub fn find(&self, name: &str) -> Option<Ref<'_, Character>> {
+        self.read_unread(|entry| entry.summary.name.eq_ignore_ascii_case(name));
+        Ref::filter_map(self.characters.borrow(), |characters| characters.iter().find(|c| c.name.eq_ignore_ascii_case(name))).ok()
+    }
+
+    pub fn find_mut(&self, name: &str) -> Option<RefMut<'_, Character>> {
+        self.read_unread(|entry| entry.summary.name.eq_ignore_ascii_case(name));
+        RefMut::filter_map(self.characters.borrow_mut(), |characters| characters.iter_mut().find(|c| c.name.eq_ignore_ascii_case(name))).ok()
+    }
+
+    pub fn get(&self, id: u32) -> Option<Ref<'_, Character>> {
+        if by_id(&self.characters.borrow(), id).is_none() {
+            self.read_unread(|entry| entry.id == id && id != 0);
+        }
+        Ref::filter_map(self.characters.borrow(), |characters| by_id(characters, id)).ok()
+    }
+
+    /// Add a sheet, returning its id
+    pub fn insert(&self, character: Character) -> u32 {
+        let reserved = self.unread.borrow().iter().map(|entry| entry.id).max().unwrap_or(0);
+        let mut characters = self.characters.borrow_mut();
+        characters.push(character);
+        register_above(&mut characters, reserved);
+        characters.last().map(|c| c.id).unwrap_or_default()
+    }
+
+    /// Take a sheet out of the store, matching its exact name
+    pub fn remove(&self, name: &str) -> Option<Character> {
+        self.read_unread(|entry| entry.summary.name == name);
+        let mut characters = self.characters.borrow_mut();
+        let index = characters.iter().position(|c| c.name == name)?;
+        Some(characters.remove(index))
+    }
+
+    /// A copy of every sheet, for code that needs to own them
+    pub fn snapshot(&self) -> Vec<Character> {
+        self.read().clone()
+    }
+
+    /// Write every sheet that's been read; the rest are still as they are on disk
+    pub fn save_all(&self) {
+        crate::file_manager::save_characters(&self.characters.borrow());
+    }
+}
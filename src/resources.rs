@@ -0,0 +1,176 @@
+use crate::character::{AbilityScore, Character};
+use crate::error_handling::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A resource with this maximum never runs out (a 20th-level barbarian's rage)
+pub const UNLIMITED: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Recharge {
+    ShortRest,
+    LongRest,
+}
+
+impl Recharge {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "short" | "sr" => Some(Recharge::ShortRest),
+            "long" | "lr" => Some(Recharge::LongRest),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Recharge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recharge::ShortRest => write!(f, "short rest"),
+            Recharge::LongRest => write!(f, "long rest"),
+        }
+    }
+}
+
+/// A limited-use feature: rages, ki points, channel divinity and the like
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassResource {
+    pub name: String,
+    pub max: u32,
+    pub current: u32,
+    pub recharge: Recharge,
+    /// Added by hand rather than granted by the character's class
+    #[serde(default)]
+    pub custom: bool,
+}
+
+impl ClassResource {
+    pub fn new(name: &str, max: u32, recharge: Recharge) -> Self {
+        ClassResource { name: name.to_string(), max, current: max, recharge, custom: false }
+    }
+
+    pub fn describe(&self) -> String {
+        if self.max == UNLIMITED {
+            return format!("{}: unlimited", self.name);
+        }
+        // Pips only for small pools; Lay on Hands counts in hit points
+        let pips = if self.max <= 10 {
+            format!(" {}{}", "●".repeat(self.current as usize), "○".repeat((self.max - self.current) as usize))
+        } else {
+            String::new()
+        };
+        format!("{}: {}/{}{} ({})", self.name, self.current, self.max, pips, self.recharge)
+    }
+}
+
+/// What a class grants at a level, before anything is spent
+pub fn class_resources(character: &Character) -> Vec<ClassResource> {
+    use Recharge::*;
+
+    let level = character.level.unwrap_or(1) as u32;
+    let class = character.class.as_deref().unwrap_or_default().to_lowercase();
+    let cha = (character.get_ability_modifier(AbilityScore::Charisma) as i32).max(1) as u32;
+    let mut resources = Vec::new();
+    match class.as_str() {
+        "barbarian" => {
+            let rages = match level {
+                1..=2 => 2,
+                3..=5 => 3,
+                6..=11 => 4,
+                12..=16 => 5,
+                17..=19 => 6,
+                _ => UNLIMITED,
+            };
+            resources.push(ClassResource::new("Rage", rages, LongRest));
+        }
+        "bard" => {
+            let recharge = if level >= 5 { ShortRest } else { LongRest };
+            resources.push(ClassResource::new("Bardic Inspiration", cha, recharge));
+        }
+        "cleric" if level >= 2 => {
+            let uses = match level {
+                2..=5 => 1,
+                6..=17 => 2,
+                _ => 3,
+            };
+            resources.push(ClassResource::new("Channel Divinity", uses, ShortRest));
+        }
+        "druid" if level >= 2 => resources.push(ClassResource::new("Wild Shape", 2, ShortRest)),
+        "fighter" => {
+            resources.push(ClassResource::new("Second Wind", 1, ShortRest));
+            if level >= 2 {
+                resources.push(ClassResource::new("Action Surge", if level >= 17 { 2 } else { 1 }, ShortRest));
+            }
+        }
+        "monk" if level >= 2 => resources.push(ClassResource::new("Ki", level, ShortRest)),
+        "paladin" => {
+            resources.push(ClassResource::new("Lay on Hands", 5 * level, LongRest));
+            if level >= 3 {
+                resources.push(ClassResource::new("Channel Divinity", 1, ShortRest));
+            }
+        }
+        "sorcerer" if level >= 2 => resources.push(ClassResource::new("Sorcery Points", level, LongRest)),
+        "wizard" => resources.push(ClassResource::new("Arcane Recovery", 1, LongRest)),
+        _ => {}
+    }
+    resources
+}
+
+/// Bring a character's resources in line with their class and level, keeping
+/// what's been spent and anything added by hand
+pub fn refresh_resources(character: &mut Character) {
+    let granted = class_resources(character);
+    let mut resources: Vec<ClassResource> = granted.into_iter().map(|mut resource| {
+        if let Some(existing) = character.resources.iter().find(|r| !r.custom && r.name == resource.name) {
+            let spent = existing.max.saturating_sub(existing.current);
+            resource.current = resource.max.saturating_sub(spent);
+        }
+        resource
+    }).collect();
+    resources.extend(character.resources.iter().filter(|r| r.custom).cloned());
+    character.resources = resources;
+}
+
+fn find_resource<'a>(character: &'a mut Character, name: &str) -> Result<&'a mut ClassResource> {
+    let query = name.trim().to_lowercase();
+    let known: Vec<String> = character.resources.iter().map(|r| r.name.clone()).collect();
+    let character_name = character.name.clone();
+    character.resources.iter_mut()
+        .find(|r| r.name.to_lowercase() == query || r.name.to_lowercase().starts_with(&query))
+        .ok_or_else(|| AppError::NotFound(if known.is_empty() {
+            format!("Resource '{}' ({} has none)", name, character_name)
+        } else {
+            format!("Resource '{}' ({} has {})", name, character_name, known.join(", "))
+        }))
+}
+
+/// Spend `amount` of a resource, returning it afterwards
+pub fn use_resource(character: &mut Character, name: &str, amount: u32) -> Result<ClassResource> {
+    let character_name = character.name.clone();
+    let resource = find_resource(character, name)?;
+    if resource.max == UNLIMITED {
+        return Ok(resource.clone());
+    }
+    if resource.current < amount {
+        return Err(AppError::ValidationError(format!("{} has only {} {} left", character_name, resource.current, resource.name)));
+    }
+    resource.current -= amount;
+    Ok(resource.clone())
+}
+
+/// Track a resource the class tables don't cover (a feat, a magic item's charges)
+pub fn add_custom_resource(character: &mut Character, name: &str, max: u32, recharge: Recharge) {
+    character.resources.retain(|r| !r.name.eq_ignore_ascii_case(name));
+    character.resources.push(ClassResource { custom: true, ..ClassResource::new(name, max, recharge) });
+}
+
+/// Recover what a rest restores; a long rest restores short-rest resources too.
+/// Returns the names of the resources that came back.
+pub fn rest(character: &mut Character, kind: Recharge) -> Vec<String> {
+    character.resources.iter_mut()
+        .filter(|r| r.current < r.max && (kind == Recharge::LongRest || r.recharge == Recharge::ShortRest))
+        .map(|r| {
+            r.current = r.max;
+            r.name.clone()
+        })
+        .collect()
+}
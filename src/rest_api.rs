@@ -0,0 +1,232 @@
+use crate::combat::{CombatTracker, Combatant, DamageType};
+use crate::error_handling::{AppError, Result, ResultExt};
+use serde::Serialize;
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    time::Duration,
+};
+
+/// `api` in the combat tracker listens here
+pub const DEFAULT_API_ADDR: &str = "127.0.0.1:7879";
+/// Requests are tiny; anything bigger isn't from a companion app
+const MAX_BODY_BYTES: usize = 4096;
+
+/// One combatant as companion apps see them. HP the DM hides from the table is left
+/// out, so an overlay can't give it away.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApiCombatant {
+    pub id: u32,
+    pub name: String,
+    pub initiative: i32,
+    pub hp: Option<i32>,
+    pub max_hp: Option<i32>,
+    pub temp_hp: Option<i32>,
+    pub ac: Option<i32>,
+    /// "Unhurt", "Bloodied" and so on, always shown
+    pub condition: String,
+    pub statuses: Vec<String>,
+    pub is_player: bool,
+    pub is_current: bool,
+}
+
+impl ApiCombatant {
+    fn new(tracker: &CombatTracker, index: usize, combatant: &Combatant) -> Self {
+        let exact = combatant.is_player || !tracker.hide_monster_hp;
+        ApiCombatant {
+            id: combatant.id,
+            name: tracker.display_name(combatant),
+            initiative: combatant.initiative,
+            hp: exact.then_some(combatant.current_hp),
+            max_hp: exact.then_some(combatant.max_hp),
+            temp_hp: exact.then_some(combatant.temp_hp),
+            ac: exact.then_some(combatant.ac),
+            condition: combatant.hp_descriptor().to_string(),
+            statuses: combatant.status_effects.iter().map(|s| s.name.clone()).collect(),
+            is_player: combatant.is_player,
+            is_current: index == tracker.current_turn,
+        }
+    }
+}
+
+/// The fight as `GET /combat` returns it. Hidden combatants are left out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApiState {
+    pub round: i32,
+    pub current: Option<String>,
+    pub combatants: Vec<ApiCombatant>,
+}
+
+impl ApiState {
+    pub fn from_tracker(tracker: &CombatTracker) -> Self {
+        let combatants: Vec<ApiCombatant> = tracker.visible_combatants()
+            .map(|(i, combatant)| ApiCombatant::new(tracker, i, combatant))
+            .collect();
+        ApiState {
+            round: tracker.round_number,
+            current: combatants.iter().find(|c| c.is_current).map(|c| c.name.clone()),
+            combatants,
+        }
+    }
+}
+
+/// What a companion app asked for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiRequest {
+    /// GET /combat
+    State,
+    /// GET /combatants
+    Combatants,
+    /// POST /combatants/<name>/damage with `{"amount": 7, "type": "fire"}`
+    Damage { target: String, amount: i32, kind: Option<DamageType> },
+    /// POST /next
+    Next,
+}
+
+/// "Goblin%202" → "Goblin 2"
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok()).and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Work out the request from its method, path and JSON body
+pub fn parse_request(method: &str, path: &str, body: &str) -> Result<ApiRequest> {
+    let path = path.split('?').next().unwrap_or_default().trim_end_matches('/');
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match (method, segments.as_slice()) {
+        ("GET", ["combat"]) => Ok(ApiRequest::State),
+        ("GET", ["combatants"]) => Ok(ApiRequest::Combatants),
+        ("POST", ["next"]) => Ok(ApiRequest::Next),
+        ("POST", ["combatants", target, "damage"]) => {
+            let json: serde_json::Value = serde_json::from_str(body)
+                .map_err(|e| AppError::ParseError(format!("Body must be JSON like {{\"amount\": 7}}: {}", e)))?;
+            let amount = json["amount"].as_i64().filter(|n| *n >= 0)
+                .ok_or_else(|| AppError::ValidationError("'amount' must be a whole number, 0 or more".to_string()))?;
+            let kind = match json["type"].as_str() {
+                Some(kind) => Some(DamageType::parse(kind)
+                    .ok_or_else(|| AppError::ValidationError(format!("Unknown damage type '{}'", kind)))?),
+                None => None,
+            };
+            Ok(ApiRequest::Damage { target: percent_decode(target), amount: amount.min(i32::MAX as i64) as i32, kind })
+        }
+        _ => Err(AppError::NotFound(format!("{} /{}", method, segments.join("/")))),
+    }
+}
+
+/// A connection waiting for the tracker to answer its request
+pub struct ApiCall {
+    stream: TcpStream,
+}
+
+impl ApiCall {
+    /// Send the result as JSON: 200 with the value, or an error status with a message
+    pub fn respond(mut self, result: Result<serde_json::Value>) {
+        let (status, body) = match result {
+            Ok(value) => ("200 OK", value),
+            Err(e) => {
+                let status = match e {
+                    AppError::NotFound(_) => "404 Not Found",
+                    AppError::ValidationError(_) | AppError::ParseError(_) => "400 Bad Request",
+                    // A request that couldn't be read off the connection
+                    AppError::Context { .. } => "400 Bad Request",
+                    _ => "500 Internal Server Error",
+                };
+                (status, serde_json::json!({ "error": e.to_string() }))
+            }
+        };
+        let body = body.to_string();
+        let response = format!("HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status, body.len(), body);
+        if let Err(e) = self.stream.write_all(response.as_bytes()) {
+            log::debug!("API client went away before the response: {}", e);
+        }
+    }
+}
+
+/// Read one HTTP request: the request line, headers up to a blank line, then the body
+fn read_request(stream: &TcpStream) -> Result<ApiRequest> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).context("Could not read the API request")?;
+    let mut words = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (words.next(), words.next()) else {
+        return Err(AppError::ParseError("Malformed HTTP request".to_string()));
+    };
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).context("Could not read the API request")?;
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length") {
+            length = value.trim().parse().map_err(|_| AppError::ParseError("Bad Content-Length".to_string()))?;
+        }
+    }
+    if length > MAX_BODY_BYTES {
+        return Err(AppError::ValidationError(format!("Request body over {} bytes", MAX_BODY_BYTES)));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).context("Could not read the API request body")?;
+    parse_request(method, path, &String::from_utf8_lossy(&body))
+}
+
+/// A small JSON API over the combat tracker for overlays, LED initiative trackers and
+/// dashboards. It only listens on this machine.
+#[derive(Debug)]
+pub struct ApiServer {
+    listener: TcpListener,
+}
+
+impl ApiServer {
+    pub fn bind(addr: &str) -> Result<Self> {
+        let socket: SocketAddr = addr.parse()
+            .map_err(|_| AppError::ValidationError(format!("'{}' is not an address like {}", addr, DEFAULT_API_ADDR)))?;
+        if !socket.ip().is_loopback() {
+            return Err(AppError::ValidationError(format!("The API only listens on this machine (127.0.0.1 or [::1]), not {}", socket.ip())));
+        }
+        let listener = TcpListener::bind(socket).context(format!("Could not listen on {}", addr))?;
+        listener.set_nonblocking(true).context("Could not configure the API socket")?;
+        log::info!("Combat API listening on {}", addr);
+        Ok(ApiServer { listener })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.listener.local_addr().ok()
+    }
+
+    /// Requests that have arrived since the last call, without waiting for more
+    pub fn accept(&self) -> Vec<(ApiCall, Result<ApiRequest>)> {
+        let mut calls = Vec::new();
+        while let Ok((stream, peer)) = self.listener.accept() {
+            // A slow client gets a short wait, so it can't stall the tracker
+            let configured = stream.set_nonblocking(false)
+                .and_then(|_| stream.set_read_timeout(Some(Duration::from_millis(250))));
+            if let Err(e) = configured {
+                log::warn!("Dropping API connection from {}: {}", peer, e);
+                continue;
+            }
+            let request = read_request(&stream);
+            log::debug!("API request from {}: {:?}", peer, request);
+            calls.push((ApiCall { stream }, request));
+        }
+        calls
+    }
+}
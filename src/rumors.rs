@@ -0,0 +1,160 @@
+use crate::error_handling::{AppError, Result};
+use crate::tables::{RandomTable, TableEntry, TableRegistry};
+use rand::Rng;
+
+pub const SETTLEMENT_SIZES: &[&str] = &["village", "town", "city"];
+pub const THEMES: &[&str] = &["crime", "mystery", "politics", "trade", "supernatural", "danger"];
+
+const OPENERS: &[&str] = &[
+    "They say", "I heard", "Word is", "My cousin swears", "Don't repeat this, but",
+    "A drunk sailor claimed", "The priest let slip that", "Everyone knows",
+];
+
+const SUBJECTS: &[&str] = &[
+    "the miller's daughter", "a hooded stranger", "the old blacksmith", "a retired adventurer",
+    "the innkeeper", "a traveling tinker", "the local priest", "a pair of dwarven merchants",
+    "a half-elf bard", "the watch captain", "a widow nobody trusts", "a child with silver eyes",
+];
+
+const TRUTH: &[(u32, &str)] = &[(2, "true"), (2, "partly true"), (1, "false")];
+
+const PLACES: &[(&str, &[&str])] = &[
+    ("village", &[
+        "near the old well", "out past the sheep fields", "at the crossroads shrine", "in the abandoned mill",
+        "by the river ford", "under the hanging tree", "in the cellar of the tavern", "at the edge of the woods",
+    ]),
+    ("town", &[
+        "behind the market square", "in the guildhall", "at the temple steps", "down by the docks",
+        "in the tannery district", "at the town gate", "under the bridge", "in the graveyard on the hill",
+    ]),
+    ("city", &[
+        "in the noble quarter", "beneath the sewers", "at the great library", "in the thieves' market",
+        "at the arena", "inside the lord's keep", "in the foreign quarter", "on a ship just arrived in the harbor",
+    ]),
+];
+
+const EVENTS: &[(&str, &[&str])] = &[
+    ("crime", &[
+        "has been fencing stolen jewels", "owes a fortune to the thieves' guild", "was seen burying a body",
+        "is smuggling something that hisses", "bribed the magistrate", "knows who started the fire",
+        "has a second family", "keeps a ledger of everyone's secrets",
+    ]),
+    ("mystery", &[
+        "found a map with no legend", "hasn't aged a day in twenty years", "hears singing from underground",
+        "received a letter sealed with black wax", "vanished for a week and can't remember where",
+        "dug up a door in a field", "speaks in their sleep in a dead language", "has a twin nobody's met",
+    ]),
+    ("politics", &[
+        "is plotting against the mayor", "carries the seal of a rival lord", "is the rightful heir to the barony",
+        "is spying for the neighboring kingdom", "was promised a seat on the council", "forged the tax records",
+        "is secretly funding the rebels", "has the duke's ear",
+    ]),
+    ("trade", &[
+        "is cornering the grain market", "found a new silver vein", "is hiring guards for a dangerous caravan",
+        "sells potions that don't work", "is buying up every horse in the region", "lost a ship full of spices",
+        "pays double for monster parts", "has goods from a land nobody's heard of",
+    ]),
+    ("supernatural", &[
+        "made a pact with something in the woods", "is haunted by a weeping ghost", "turns into a wolf at the full moon",
+        "has a fey creature living in their chimney", "was cursed by a hag", "can talk to crows",
+        "saw the dead walking last night", "keeps a dragon egg in a strongbox",
+    ]),
+    ("danger", &[
+        "saw goblins scouting the road", "was attacked by something with too many legs", "says bandits are massing in the hills",
+        "found a nest of giant spiders", "barely escaped a troll", "warns that the dam is cracking",
+        "heard an owlbear's cry close by", "found hoofprints that glow",
+    ]),
+];
+
+fn table(name: &str, entries: impl IntoIterator<Item = (u32, String)>) -> RandomTable {
+    RandomTable {
+        name: name.to_string(),
+        description: None,
+        entries: entries.into_iter().map(|(weight, text)| TableEntry { weight, text }).collect(),
+    }
+}
+
+fn even(items: &[&str]) -> impl Iterator<Item = (u32, String)> {
+    items.iter().map(|s| (1, s.to_string()))
+}
+
+/// The built-in rumor tables: "Rumor Opener", "Rumor Subject", "Rumor Truth",
+/// "Rumor Place (<size>)" and "Rumor Event (<theme>)"
+pub fn builtin_tables() -> Vec<RandomTable> {
+    let mut tables = vec![
+        table("Rumor Opener", even(OPENERS)),
+        table("Rumor Subject", even(SUBJECTS)),
+        table("Rumor Truth", TRUTH.iter().map(|&(w, t)| (w, t.to_string()))),
+    ];
+    tables.extend(PLACES.iter().map(|(size, places)| table(&format!("Rumor Place ({})", size), even(places))));
+    tables.extend(EVENTS.iter().map(|(theme, events)| table(&format!("Rumor Event ({})", theme), even(events))));
+    tables
+}
+
+pub fn register_builtin_tables(registry: &mut TableRegistry) {
+    for table in builtin_tables() {
+        registry.add(table);
+    }
+}
+
+/// Options for the `rumors` command; unset size/theme are picked at random per rumor
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RumorOptions {
+    pub count: usize,
+    pub size: Option<String>,
+    pub theme: Option<String>,
+    pub use_npcs: bool,
+}
+
+impl RumorOptions {
+    /// Parse `[n] [village|town|city] [theme] [npcs]` in any order
+    pub fn parse(args: &[&str]) -> Result<Self> {
+        let mut options = RumorOptions { count: 3, ..Default::default() };
+        for arg in args {
+            let arg = arg.to_lowercase();
+            if let Ok(count) = arg.parse::<usize>() {
+                options.count = count.clamp(1, 20);
+            } else if SETTLEMENT_SIZES.contains(&arg.as_str()) {
+                options.size = Some(arg);
+            } else if THEMES.contains(&arg.as_str()) {
+                options.theme = Some(arg);
+            } else if arg == "npcs" || arg == "npc" {
+                options.use_npcs = true;
+            } else {
+                return Err(AppError::ValidationError(format!(
+                    "Unknown rumor option '{}'. Sizes: {}. Themes: {}",
+                    arg, SETTLEMENT_SIZES.join(", "), THEMES.join(", ")
+                )));
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn pick<'a, R: Rng + ?Sized>(items: &[&'a str], rng: &mut R) -> &'a str {
+    items[rng.random_range(0..items.len())]
+}
+
+/// Generate tavern gossip, optionally naming the given NPCs as subjects.
+/// Each rumor ends with a DM-only note on how true it is.
+pub fn generate_rumors<R: Rng + ?Sized>(registry: &TableRegistry, options: &RumorOptions, npc_names: &[String], rng: &mut R) -> Result<Vec<String>> {
+    (0..options.count)
+        .map(|_| {
+            let size = options.size.clone().unwrap_or_else(|| pick(SETTLEMENT_SIZES, rng).to_string());
+            let theme = options.theme.clone().unwrap_or_else(|| pick(THEMES, rng).to_string());
+            let subject = if options.use_npcs && !npc_names.is_empty() {
+                npc_names[rng.random_range(0..npc_names.len())].clone()
+            } else {
+                registry.roll_with("Rumor Subject", rng)?
+            };
+            Ok(format!(
+                "{} {} {} {}. [{}]",
+                registry.roll_with("Rumor Opener", rng)?,
+                subject,
+                registry.roll_with(&format!("Rumor Event ({})", theme), rng)?,
+                registry.roll_with(&format!("Rumor Place ({})", size), rng)?,
+                registry.roll_with("Rumor Truth", rng)?,
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,28 @@
+use crate::error_handling::{AppError, Result, ResultExt};
+use std::{future::Future, io, sync::OnceLock};
+use tokio::runtime::{Handle, Runtime, RuntimeFlavor};
+
+/// Built on first use and kept for the life of the program, so each lookup doesn't
+/// pay for starting a new one
+static RUNTIME: OnceLock<io::Result<Runtime>> = OnceLock::new();
+
+/// The runtime itself, for frontends that run many lookups in a row
+pub fn shared() -> Result<&'static Runtime> {
+    RUNTIME.get_or_init(Runtime::new)
+        .as_ref()
+        .map_err(|e| io::Error::new(e.kind(), e.to_string()))
+        .context("Failed to create async runtime")
+}
+
+/// Run async work (searches, downloads) to completion from ordinary code. Safe to
+/// call from background threads at the same time. Called from inside async code,
+/// it waits on that runtime instead of starting another, which would panic.
+pub fn block_on<F: Future>(future: F) -> Result<F::Output> {
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            Ok(tokio::task::block_in_place(|| handle.block_on(future)))
+        }
+        Ok(_) => Err(AppError::ValidationError("Can't wait for network work inside a single-threaded async task".to_string())),
+        Err(_) => Ok(shared()?.block_on(future)),
+    }
+}
@@ -0,0 +1,234 @@
+use crate::campaign::slug;
+use crate::character::Character;
+use crate::combat::{CombatTracker, Combatant};
+use crate::error_handling::{AppError, Result, ResultExt};
+use std::{fs, path::{Path, PathBuf}};
+
+/// Fights stopped mid-session, one RON file each
+pub const ENCOUNTERS_DIR: &str = "campaign/encounters";
+
+fn encounter_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.ron", slug(name)))
+}
+
+/// Write the whole fight: combatants with HP, temp HP and statuses, the round and whose turn it is
+pub fn save_encounter_in(dir: &Path, name: &str, tracker: &CombatTracker) -> Result<PathBuf> {
+    fs::create_dir_all(dir).context("Failed to create encounters folder")?;
+    let path = encounter_path(dir, name);
+    let serialized = ron::ser::to_string_pretty(tracker, ron::ser::PrettyConfig::default())
+        .map_err(|e| AppError::ParseError(format!("Failed to serialize encounter: {}", e)))?;
+    fs::write(&path, serialized).context(format!("Failed to write {}", path.display()))?;
+    log::info!("Saved encounter '{}' ({} combatants, round {}) to {}", name, tracker.combatants.len(), tracker.round_number, path.display());
+    Ok(path)
+}
+
+/// Read a saved fight back. Party combatants are linked to the loaded sheets by
+/// name, since registry ids can change between sessions; the returned lines
+/// report anyone whose sheet is missing.
+pub fn load_encounter_in(dir: &Path, name: &str, characters: &[Character]) -> Result<(CombatTracker, Vec<String>)> {
+    let path = encounter_path(dir, name);
+    if !path.exists() {
+        return Err(AppError::NotFound(format!("Saved encounter '{}'", name)));
+    }
+    let contents = fs::read_to_string(&path).context(format!("Failed to read {}", path.display()))?;
+    let mut tracker: CombatTracker = ron::de::from_str(&contents)
+        .map_err(|e| AppError::ParseError(format!("{}: {}", path.display(), e)))?;
+
+    let mut warnings = Vec::new();
+    for combatant in tracker.combatants.iter_mut().filter(|c| c.character_id.is_some()) {
+        match characters.iter().find(|c| c.name.eq_ignore_ascii_case(&combatant.name)) {
+            Some(character) => combatant.character_id = Some(character.id),
+            None => {
+                combatant.character_id = None;
+                warnings.push(format!("⚠️ No sheet for {}; they stay in the fight unlinked", combatant.name));
+            }
+        }
+    }
+    tracker.current_turn = tracker.current_turn.min(tracker.combatants.len().saturating_sub(1));
+    log::info!("Loaded encounter '{}' from {}", name, path.display());
+    Ok((tracker, warnings))
+}
+
+/// Names of the saved fights, as file stems
+pub fn list_encounters_in(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .map(|entries| entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+            .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+            .collect())
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// The CLI loop moves `current_turn` past the acting combatant as their turn
+/// starts, and counts the new round as soon as the last one acts. Saved files
+/// hold the acting combatant, as the TUI does; these convert between the two.
+pub fn cli_turn_to_saved(turn: usize, round: i32, len: usize) -> (usize, i32) {
+    if len == 0 {
+        return (0, round);
+    }
+    let round = if turn == 0 && round > 1 { round - 1 } else { round };
+    ((turn + len - 1) % len, round)
+}
+
+pub fn saved_turn_to_cli(turn: usize, round: i32, len: usize) -> (usize, i32) {
+    if len == 0 {
+        return (0, round);
+    }
+    let next = (turn + 1) % len;
+    (next, if next == 0 { round + 1 } else { round })
+}
+
+/// A one-line summary shown after loading
+pub fn resume_summary(name: &str, tracker: &CombatTracker) -> String {
+    let current = tracker.combatants.get(tracker.current_turn)
+        .map(|c| tracker.display_name(c))
+        .unwrap_or_else(|| "nobody".to_string());
+    format!("📂 Resumed '{}': round {}, {} combatant(s), {}'s turn", name, tracker.round_number, tracker.combatants.len(), current)
+}
+
+/// One row of an initiative sheet rolled during prep
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitiativeRow {
+    pub name: String,
+    pub initiative: i32,
+    pub hp: Option<i32>,
+    pub ac: Option<i32>,
+}
+
+/// Split a CSV line on commas, keeping commas inside double quotes
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().expect("at least one field").push(c),
+        }
+    }
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+/// Read `name,initiative,hp,ac` rows. A header row, blank lines and lines starting
+/// with `#` are skipped; HP and AC may be left blank. Any bad row fails the whole
+/// file, naming the line.
+pub fn parse_initiative_csv(source: &str) -> Result<Vec<InitiativeRow>> {
+    let mut rows = Vec::new();
+    let mut first = true;
+    for (number, line) in source.lines().enumerate().map(|(i, line)| (i + 1, line.trim())) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields = csv_fields(line);
+        let header = std::mem::replace(&mut first, false);
+        let field = |i: usize| fields.get(i).map(String::as_str).filter(|f| !f.is_empty());
+        let optional = |i: usize, label: &str| -> Result<Option<i32>> {
+            field(i).map(|f| f.parse().map_err(|_| AppError::ValidationError(
+                format!("Line {}: '{}' isn't a number for {}", number, f, label)))).transpose()
+        };
+        let Some(name) = field(0) else {
+            return Err(AppError::ValidationError(format!("Line {}: no name", number)));
+        };
+        let initiative = match field(1).map(str::parse::<i32>) {
+            Some(Ok(initiative)) => initiative,
+            _ if header => continue,
+            _ => return Err(AppError::ValidationError(format!("Line {}: {} needs a number for initiative", number, name))),
+        };
+        rows.push(InitiativeRow { name: name.to_string(), initiative, hp: optional(2, "hp")?, ac: optional(3, "ac")? });
+    }
+    Ok(rows)
+}
+
+/// Add the rows to a fight. A row naming a saved character brings them in linked to
+/// their sheet; anyone else needs HP and AC. Returns a line per combatant.
+pub fn import_initiative(rows: &[InitiativeRow], tracker: &mut CombatTracker, characters: &[Character]) -> Result<Vec<String>> {
+    let mut combatants = Vec::new();
+    for row in rows {
+        let combatant = match characters.iter().find(|c| c.name.eq_ignore_ascii_case(&row.name)) {
+            Some(character) => Combatant::from_character(character, row.initiative),
+            None => match (row.hp, row.ac) {
+                (Some(hp), Some(ac)) => Combatant::new_npc(row.name.clone(), hp, ac, row.initiative),
+                _ => return Err(AppError::ValidationError(format!("{} isn't a saved character, so it needs hp and ac", row.name))),
+            },
+        };
+        if combatant.character_id.is_some() && tracker.combatants.iter().chain(&combatants).any(|c| c.character_id == combatant.character_id) {
+            return Err(AppError::ValidationError(format!("{} is already in the fight", combatant.name)));
+        }
+        combatants.push(combatant);
+    }
+    Ok(combatants.into_iter().map(|combatant| {
+        let line = format!("  • {} (HP: {}, AC: {}, Init: {}){}", combatant.name, combatant.max_hp, combatant.ac, combatant.initiative,
+            if combatant.character_id.is_some() { " - party" } else { "" });
+        tracker.add_combatant(combatant);
+        line
+    }).collect())
+}
+
+/// `init import <file.csv>`: read the file and add everyone in it to the fight
+pub fn import_initiative_file(path: &Path, tracker: &mut CombatTracker, characters: &[Character]) -> Result<Vec<String>> {
+    let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+    let rows = parse_initiative_csv(&contents).context(format!("Initiative file {}", path.display()))?;
+    if rows.is_empty() {
+        return Err(AppError::ValidationError(format!("{} has no name,initiative,hp,ac rows", path.display())));
+    }
+    let mut lines = vec![format!("📋 Imported {} combatant(s) from {}:", rows.len(), path.display())];
+    lines.extend(import_initiative(&rows, tracker, characters)?);
+    log::info!("Imported {} combatants from {}", rows.len(), path.display());
+    Ok(lines)
+}
+
+/// The fight as Markdown for the DM's notes, or to carry on with on paper: the round,
+/// whose turn it is, and each combatant in initiative order with HP, AC and conditions
+pub fn combat_markdown(tracker: &CombatTracker, characters: &[Character]) -> String {
+    let current = tracker.combatants.get(tracker.current_turn);
+    let mut lines = vec![format!("# Combat: round {}", tracker.round_number), String::new()];
+    if let Some(current) = current {
+        lines.push(format!("It's **{}**'s turn.", tracker.display_name(current)));
+        lines.push(String::new());
+    }
+    for (i, combatant) in tracker.combatants.iter().enumerate() {
+        let marker = if i == tracker.current_turn { "▶ " } else { "" };
+        let side = if combatant.is_player { "PC" } else { "NPC" };
+        let temp = if combatant.temp_hp > 0 { format!(" (+{} temp)", combatant.temp_hp) } else { String::new() };
+        let hidden = if combatant.hidden { ", hidden" } else { "" };
+        lines.push(format!("{}. {}**{}** ({}{}): HP {}/{}{}, AC {}, initiative {}", i + 1, marker, tracker.display_name(combatant),
+            side, hidden, combatant.current_hp, combatant.max_hp, temp, combatant.ac, combatant.initiative));
+        let mut details: Vec<String> = combatant.status_effects.iter()
+            .map(|effect| format!("{}{}", effect.name, effect.duration_text()))
+            .collect();
+        if let Some(saves) = combatant.death_saves {
+            details.push(format!("Death saves: {}", saves.tally()));
+        }
+        details.extend(combatant.defense_lines());
+        if let Some(movement) = &combatant.movement {
+            details.push(movement.label());
+        }
+        if let Some(sheet) = combatant.sheet(characters) {
+            let mut sheet = sheet.clone();
+            crate::spell_slots::refresh_spell_slots(&mut sheet);
+            crate::pact_magic::refresh_pact_slots(&mut sheet);
+            details.extend(sheet.spell_slots.iter().map(|slots| slots.describe()));
+            details.extend(sheet.pact_slots.iter().map(|slots| slots.describe()));
+        }
+        lines.extend(details.into_iter().map(|detail| format!("   - {}", detail)));
+    }
+    if tracker.combatants.is_empty() {
+        lines.push("Nobody is in the fight.".to_string());
+    }
+    lines.join("\n") + "\n"
+}
+
+/// `export combat <file>`: write the Markdown to a file, adding `.md` when it has no extension
+pub fn export_combat(path: &Path, tracker: &CombatTracker, characters: &[Character]) -> Result<PathBuf> {
+    let path = if path.extension().is_none() { path.with_extension("md") } else { path.to_path_buf() };
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).context(format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, combat_markdown(tracker, characters)).context(format!("Failed to write {}", path.display()))?;
+    log::info!("Exported combat (round {}) to {}", tracker.round_number, path.display());
+    Ok(path)
+}
@@ -0,0 +1,209 @@
+use crate::combat::{CombatTracker, StatusEffect};
+use crate::error_handling::{AppError, Result};
+use rhai::{Dynamic, Engine, Scope, AST};
+use std::{fmt, fs, path::Path, sync::{Arc, Mutex}};
+
+/// House-rule scripts live in the campaign directory and end in .rhai
+pub const SCRIPTS_DIR: &str = "campaign/scripts";
+/// Guard against runaway scripts (e.g. an accidental infinite loop)
+const MAX_SCRIPT_OPERATIONS: u64 = 100_000;
+
+/// Something a script asked the tracker to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptAction {
+    Message(String),
+    AddStatus { target: String, status: String, rounds: Option<i32> },
+    Damage { target: String, amount: i32 },
+}
+
+/// Points in play where scripts are called
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hook {
+    /// on_turn_start(name, round)
+    TurnStart { name: String, round: i32 },
+    /// on_damage(target, amount, hp, max_hp)
+    Damage { target: String, amount: i32, hp: i32, max_hp: i32 },
+    /// on_roll(kind, expression, total, crit, target) where kind is "attack", "damage" or "roll"
+    Roll { kind: String, expression: String, total: i32, crit: bool, target: String },
+}
+
+impl Hook {
+    pub fn function_name(&self) -> &'static str {
+        match self {
+            Hook::TurnStart { .. } => "on_turn_start",
+            Hook::Damage { .. } => "on_damage",
+            Hook::Roll { .. } => "on_roll",
+        }
+    }
+
+    fn args(&self) -> Vec<Dynamic> {
+        match self {
+            Hook::TurnStart { name, round } => vec![name.clone().into(), (*round as i64).into()],
+            Hook::Damage { target, amount, hp, max_hp } => vec![
+                target.clone().into(), (*amount as i64).into(), (*hp as i64).into(), (*max_hp as i64).into(),
+            ],
+            Hook::Roll { kind, expression, total, crit, target } => vec![
+                kind.clone().into(), expression.clone().into(), (*total as i64).into(), (*crit).into(), target.clone().into(),
+            ],
+        }
+    }
+}
+
+/// Loaded scripts plus the engine that runs their hook functions
+pub struct ScriptEngine {
+    engine: Engine,
+    scripts: Vec<(String, AST)>,
+    actions: Arc<Mutex<Vec<ScriptAction>>>,
+}
+
+impl fmt::Debug for ScriptEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScriptEngine")
+            .field("scripts", &self.script_names())
+            .finish()
+    }
+}
+
+impl Default for ScriptEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScriptEngine {
+    pub fn new() -> Self {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_SCRIPT_OPERATIONS);
+        let actions: Arc<Mutex<Vec<ScriptAction>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let queue = actions.clone();
+        engine.register_fn("say", move |message: &str| {
+            push_action(&queue, ScriptAction::Message(message.to_string()));
+        });
+        let queue = actions.clone();
+        engine.register_fn("add_status", move |target: &str, status: &str| {
+            push_action(&queue, ScriptAction::AddStatus { target: target.to_string(), status: status.to_string(), rounds: None });
+        });
+        let queue = actions.clone();
+        engine.register_fn("add_status", move |target: &str, status: &str, rounds: i64| {
+            push_action(&queue, ScriptAction::AddStatus { target: target.to_string(), status: status.to_string(), rounds: Some(rounds as i32) });
+        });
+        let queue = actions.clone();
+        engine.register_fn("damage", move |target: &str, amount: i64| {
+            push_action(&queue, ScriptAction::Damage { target: target.to_string(), amount: amount as i32 });
+        });
+        engine.register_fn("roll", |expression: &str| -> i64 {
+            crate::dice::roll_dice(expression).map(|(_, total)| total as i64).unwrap_or(0)
+        });
+
+        ScriptEngine { engine, scripts: Vec::new(), actions }
+    }
+
+    /// Compile a script and keep it for hook calls
+    pub fn load_script(&mut self, name: &str, source: &str) -> Result<()> {
+        let ast = self.engine.compile(source)
+            .map_err(|e| AppError::ParseError(format!("Script '{}': {}", name, e)))?;
+        self.scripts.push((name.to_string(), ast));
+        Ok(())
+    }
+
+    /// Load every .rhai file in a directory, returning the errors for any that failed
+    pub fn load_dir(&mut self, dir: &Path) -> Vec<AppError> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .collect();
+        paths.sort();
+
+        let mut errors = Vec::new();
+        for path in paths {
+            let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            let result = fs::read_to_string(&path)
+                .map_err(AppError::from)
+                .and_then(|source| self.load_script(&name, &source));
+            match result {
+                Ok(()) => log::info!("Loaded script {}", path.display()),
+                Err(e) => {
+                    log::warn!("Failed to load script {}: {}", path.display(), e);
+                    errors.push(e);
+                }
+            }
+        }
+        errors
+    }
+
+    pub fn script_names(&self) -> Vec<&str> {
+        self.scripts.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    /// Call the hook in every script that defines it and collect what they asked for.
+    /// A string returned from a hook is treated as a message.
+    pub fn run_hook(&self, hook: &Hook) -> Vec<ScriptAction> {
+        let name = hook.function_name();
+        let args = hook.args();
+        let mut results = Vec::new();
+
+        for (script, ast) in &self.scripts {
+            let defines_hook = ast.iter_functions().any(|f| f.name == name && f.params.len() == args.len());
+            if !defines_hook {
+                continue;
+            }
+            match self.engine.call_fn::<Dynamic>(&mut Scope::new(), ast, name, args.clone()) {
+                Ok(value) => {
+                    results.append(&mut self.take_actions());
+                    if let Ok(message) = value.into_string()
+                        && !message.is_empty() {
+                        results.push(ScriptAction::Message(message));
+                    }
+                }
+                Err(e) => {
+                    self.take_actions();
+                    log::warn!("Script {} failed in {}: {}", script, name, e);
+                    results.push(ScriptAction::Message(format!("⚠️ Script {} failed in {}: {}", script, name, e)));
+                }
+            }
+        }
+        results
+    }
+
+    fn take_actions(&self) -> Vec<ScriptAction> {
+        self.actions.lock().map(|mut queue| std::mem::take(&mut *queue)).unwrap_or_default()
+    }
+}
+
+/// Carry out what house-rule scripts asked for, returning a line for each. Script
+/// damage doesn't re-trigger hooks.
+pub fn apply_actions(mut tracker: Option<&mut CombatTracker>, actions: Vec<ScriptAction>) -> Vec<String> {
+    actions.into_iter().map(|action| match action {
+        ScriptAction::Message(message) => format!("📜 {}", message),
+        ScriptAction::AddStatus { target, status, rounds } => {
+            match tracker.as_deref_mut().and_then(|tracker| tracker.get_combatant_mut(&target)) {
+                Some(combatant) => {
+                    combatant.add_status(StatusEffect::new(status.clone(), rounds));
+                    format!("📜 {} is now {}", combatant.name, status)
+                }
+                None => format!("⚠️ Script target '{}' not found", target),
+            }
+        }
+        ScriptAction::Damage { target, amount } => {
+            match tracker.as_deref_mut().map(|tracker| tracker.apply_damage(&target, amount, None)) {
+                Some(Ok(message)) => format!("📜 {}", message),
+                Some(Err(e)) => format!("⚠️ {}", e),
+                None => "⚠️ Script damage ignored: no combat initialized".to_string(),
+            }
+        }
+    }).collect()
+}
+
+fn push_action(queue: &Arc<Mutex<Vec<ScriptAction>>>, action: ScriptAction) {
+    if let Ok(mut queue) = queue.lock() {
+        queue.push(action);
+    }
+}
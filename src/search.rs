@@ -1,5 +1,33 @@
+use crate::attacks::AttackProfile;
+use crate::character::Character;
+use crate::combat::{CombatTracker, Combatant, DamageType};
+use crate::error_handling::{AppError, Result, ResultExt};
+use rand::Rng;
 use regex::Regex;
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::{Path, PathBuf}, sync::mpsc::{self, Receiver}};
+
+/// Fetched wiki pages are kept here so lookups work again without the network
+pub const CACHE_DIR: &str = "cache";
+/// Lines kept when a page is condensed for the combat reference pane
+pub const REFERENCE_LINES: usize = 40;
+/// Where a wiki page's article text lives, tried in order
+const CONTENT_SELECTORS: &[&str] = &["#page-content", ".main-content"];
+/// Even a cantrip's page has more text than this; less means the parser grabbed the wrong element
+const MIN_CONTENT_CHARS: usize = 80;
+/// Every page named on the wiki's listing pages, built by `crawl`
+pub const INDEX_FILE: &str = "data/search_index.json";
+/// Classes with a spell list page on the wiki (`/spells:wizard`)
+const SPELL_LIST_CLASSES: &[&str] = &["artificer", "bard", "cleric", "druid", "paladin", "ranger", "sorcerer", "warlock", "wizard"];
+/// Listing pages the crawler reads besides the class spell lists, with the content type they list
+const LISTINGS: &[(&str, &str)] = &[
+    ("spells", "spell"),
+    ("monsters", "monster"),
+    ("weapons", "equipment"),
+    ("armor", "equipment"),
+    ("adventuring-gear", "equipment"),
+];
 
 // Simplified data structure for wikidot page content
 #[derive(Debug, Clone)]
@@ -11,6 +39,18 @@ pub struct WikiPageContent {
     pub content_type: String, // "spell", "class", "equipment", "monster", "race", etc.
 }
 
+impl WikiPageContent {
+    /// Wiki pages, fetched or cached, are the wiki's content under its license; pages
+    /// read from the downloaded SRD data are the SRD's
+    pub fn source(&self) -> crate::content::Source {
+        if self.url.starts_with(crate::srd::SRD_URL_PREFIX) {
+            crate::content::Source::Srd
+        } else {
+            crate::content::Source::Wikidot { url: self.url.clone() }
+        }
+    }
+}
+
 // Search categories
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SearchCategory {
@@ -54,6 +94,17 @@ impl SearchCategory {
     }
 }
 
+/// The content type pages of a category are fetched and cached as
+fn category_content_type(category: SearchCategory) -> &'static str {
+    match category {
+        SearchCategory::Spells => "spell",
+        SearchCategory::Classes => "class",
+        SearchCategory::Equipment => "equipment",
+        SearchCategory::Monsters => "monster",
+        SearchCategory::Races => "race",
+    }
+}
+
 // Simplified search result - just wiki page content
 #[derive(Debug, Clone)]
 pub struct SearchResult {
@@ -88,6 +139,11 @@ impl SearchResult {
             page.url,
             " ".repeat(70_i32.saturating_sub(page.url.len() as i32 + 8) as usize)
         );
+        let license = page.source().license().name();
+        println!("║ License: {} {} ║",
+            license,
+            " ".repeat(69_i32.saturating_sub(license.len() as i32 + 8) as usize)
+        );
         println!("╚═══════════════════════════════════════════════════════════════════════════════╝");
         
         // Display the content with nice formatting
@@ -156,10 +212,521 @@ impl SearchResult {
     }
 }
 
-// Main search client for Wikidot HTML scraping
+fn cache_slug(query: &str) -> String {
+    query.trim().to_lowercase().replace(' ', "-")
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-')
+        .collect()
+}
+
+fn cache_path(cache_dir: &Path, content_type: &str, query: &str) -> PathBuf {
+    cache_dir.join(content_type).join(format!("{}.txt", cache_slug(query)))
+}
+
+/// Read a cached page: the URL and title on the first two lines, then the content
+pub fn read_cached_page(cache_dir: &Path, content_type: &str, query: &str) -> Option<WikiPageContent> {
+    let contents = crate::cache::read_entry(&cache_path(cache_dir, content_type, query))?;
+    let mut lines = contents.splitn(3, '\n');
+    let url = lines.next()?.to_string();
+    let name = lines.next()?.to_string();
+    Some(WikiPageContent {
+        index: query.trim().to_lowercase().replace(' ', "-"),
+        name,
+        url,
+        content: lines.next().unwrap_or_default().to_string(),
+        content_type: content_type.to_string(),
+    })
+}
+
+/// Store a page compressed, then trim the cache back under its size limit
+pub fn write_cached_page(cache_dir: &Path, query: &str, page: &WikiPageContent) -> Result<()> {
+    let path = cache_path(cache_dir, &page.content_type, query);
+    crate::cache::write_entry(&path, &format!("{}\n{}\n{}", page.url, page.name, page.content))?;
+    crate::cache::evict(cache_dir, crate::cache::cache_max_bytes());
+    Ok(())
+}
+
+/// A page trimmed for a narrow pane: blank lines dropped, at most `max_lines` kept
+pub fn condensed_lines(content: &str, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect();
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        lines.push("... (use search mode for full details)".to_string());
+    }
+    lines
+}
+
+/// Look a page up for quick reference: the cache first, then the wiki (or the SRD data offline)
+pub fn lookup_reference(query: &str, category: Option<SearchCategory>) -> Result<WikiPageContent> {
+    let client = DndSearchClient::try_new()?;
+    let categories = category.map(|c| vec![c]).unwrap_or_else(SearchCategory::all);
+    if let Some(page) = categories.iter().find_map(|c| client.cached(query, *c)) {
+        return Ok(page);
+    }
+    crate::runtime::block_on(client.search(query, category))??
+        .into_iter()
+        .next()
+        .map(|result| result.page)
+        .ok_or_else(|| AppError::NotFound(format!("'{}'", query)))
+}
+
+/// The pages a fight is likely to need: every spell on the party's sheets and each
+/// kind of monster in the encounter ("Goblin 2" is looked up as "Goblin")
+pub fn prefetch_targets(characters: &[Character], combatants: &[Combatant]) -> Vec<(SearchCategory, String)> {
+    let spells = characters.iter()
+        .flat_map(|c| c.spells.iter())
+        .map(|spell| (SearchCategory::Spells, spell.trim().to_string()));
+    let monsters = combatants.iter()
+        .filter(|c| !c.is_player && c.character_id.is_none() && c.owner.is_none())
+        .map(|c| (SearchCategory::Monsters, c.name.trim_end_matches(|ch: char| ch.is_ascii_digit() || ch == '#').trim().to_string()));
+
+    let mut targets: Vec<(SearchCategory, String)> = Vec::new();
+    for (category, query) in spells.chain(monsters) {
+        if !query.is_empty() && !targets.iter().any(|(c, q)| *c == category && q.eq_ignore_ascii_case(&query)) {
+            targets.push((category, query));
+        }
+    }
+    targets
+}
+
+/// What a prefetch run managed to put in the cache
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrefetchReport {
+    pub already_cached: usize,
+    pub fetched: usize,
+    pub failed: Vec<String>,
+}
+
+impl PrefetchReport {
+    pub fn summary(&self) -> String {
+        let mut summary = format!("📥 {} page(s) ready for offline lookup ({} fetched, {} already cached)",
+            self.already_cached + self.fetched, self.fetched, self.already_cached);
+        if !self.failed.is_empty() {
+            summary.push_str(&format!("; couldn't fetch {}", self.failed.join(", ")));
+        }
+        summary
+    }
+}
+
+/// Fill the cache in the background; the receiver yields a report once every target is tried
+pub fn spawn_prefetch(targets: Vec<(SearchCategory, String)>) -> Receiver<PrefetchReport> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let report = DndSearchClient::try_new().and_then(|client| crate::runtime::block_on(client.prefetch(&targets)));
+        match report {
+            Ok(report) => {
+                log::info!("Prefetch finished: {} fetched, {} failed", report.fetched, report.failed.len());
+                let _ = tx.send(report);
+            }
+            Err(e) => log::warn!("Prefetch skipped: {}", e),
+        }
+    });
+    rx
+}
+
+/// One page named on a listing, with what the listing says about it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    /// "fireball", from the link to "/spell:fireball"
+    pub slug: String,
+    pub url: String,
+    pub content_type: String,
+    /// Spell level, 0 for cantrips
+    #[serde(default)]
+    pub level: Option<u8>,
+    #[serde(default)]
+    pub school: Option<String>,
+    /// Classes whose spell list has it
+    #[serde(default)]
+    pub classes: Vec<String>,
+}
+
+impl IndexEntry {
+    /// "Fireball (3rd-level evocation; sorcerer, wizard)"
+    pub fn summary(&self) -> String {
+        let level = self.level.map(|level| match level {
+            0 => "cantrip".to_string(),
+            1 => "1st-level".to_string(),
+            2 => "2nd-level".to_string(),
+            3 => "3rd-level".to_string(),
+            n => format!("{}th-level", n),
+        });
+        let kind = [level, self.school.clone()].into_iter().flatten().collect::<Vec<_>>().join(" ");
+        let details: Vec<String> = [Some(kind).filter(|k| !k.is_empty()), Some(self.classes.join(", ")).filter(|c| !c.is_empty())]
+            .into_iter().flatten().collect();
+        if details.is_empty() { self.name.clone() } else { format!("{} ({})", self.name, details.join("; ")) }
+    }
+}
+
+/// "Cantrip" → 0, "3rd Level" → 3
+fn listing_level(label: &str) -> Option<u8> {
+    let label = label.trim().to_lowercase();
+    if label.starts_with("cantrip") {
+        return Some(0);
+    }
+    Regex::new(r"^(\d)(?:st|nd|rd|th)").expect("valid level regex")
+        .captures(&label)
+        .and_then(|caps| caps[1].parse().ok())
+}
+
+/// "Evocation cantrip" → "evocation"
+fn listing_school(cell: &str) -> Option<String> {
+    const SCHOOLS: &[&str] = &["abjuration", "conjuration", "divination", "enchantment", "evocation", "illusion", "necromancy", "transmutation"];
+    let cell = cell.to_lowercase();
+    SCHOOLS.iter().find(|school| cell.contains(*school)).map(|school| school.to_string())
+}
+
+fn cell_text(cell: ElementRef) -> String {
+    cell.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Every linked row of a listing page's tables. Spell listings keep each level on its
+/// own tab, so the tab's label gives the level. `class` is set when reading a class's
+/// spell list.
+pub fn parse_listing(html: &str, content_type: &str, base_url: &str, class: Option<&str>) -> Vec<IndexEntry> {
+    let document = Html::parse_document(html);
+    let tab_labels = Selector::parse("ul.yui-nav li").expect("valid tab selector");
+    let tabs = Selector::parse("div.yui-content > div").expect("valid tab selector");
+    let rows = Selector::parse("tr").expect("valid row selector");
+    let cells = Selector::parse("td").expect("valid cell selector");
+    let link = Selector::parse("a[href]").expect("valid link selector");
+
+    let labels: Vec<String> = document.select(&tab_labels).map(cell_text).collect();
+    let sections: Vec<(Option<u8>, ElementRef)> = match document.select(&tabs).collect::<Vec<_>>() {
+        tabs if !tabs.is_empty() => tabs.into_iter().enumerate()
+            .map(|(i, tab)| (labels.get(i).and_then(|label| listing_level(label)), tab))
+            .collect(),
+        _ => vec![(None, document.root_element())],
+    };
+
+    let mut entries: Vec<IndexEntry> = Vec::new();
+    for (level, section) in sections {
+        for row in section.select(&rows) {
+            let row_cells: Vec<ElementRef> = row.select(&cells).collect();
+            let Some(anchor) = row_cells.first().and_then(|cell| cell.select(&link).next()) else { continue };
+            let href = anchor.value().attr("href").unwrap_or_default();
+            let path = href.strip_prefix(base_url).unwrap_or(href);
+            // Only pages on the wiki itself, not links out to other sites
+            if !path.starts_with('/') || path.starts_with("//") {
+                continue;
+            }
+            let name = cell_text(anchor);
+            let slug = path.rsplit([':', '/']).next().unwrap_or_default().to_string();
+            if name.is_empty() || slug.is_empty() || entries.iter().any(|e| e.slug == slug) {
+                continue;
+            }
+            entries.push(IndexEntry {
+                name,
+                slug,
+                url: format!("{}{}", base_url, path),
+                content_type: content_type.to_string(),
+                level,
+                school: (content_type == "spell").then(|| row_cells.get(1).and_then(|cell| listing_school(&cell_text(*cell)))).flatten(),
+                classes: class.map(|c| vec![c.to_string()]).unwrap_or_default(),
+            });
+        }
+    }
+    entries
+}
+
+/// The crawled listings, kept on disk so browsing and suggestions work without
+/// fetching them again
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchIndex {
+    pub entries: Vec<IndexEntry>,
+}
+
+impl SearchIndex {
+    /// An index that hasn't been crawled yet is empty
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(SearchIndex::default());
+        }
+        let json = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&json).map_err(|e| AppError::ParseError(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create the index folder")?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| AppError::ParseError(e.to_string()))?;
+        fs::write(path, json).context(format!("Failed to write {}", path.display()))
+    }
+
+    /// Add a listing's entries. A page seen on several listings (a spell on two class
+    /// lists) is kept once, with what each listing said about it.
+    pub fn merge(&mut self, entries: Vec<IndexEntry>) {
+        for entry in entries {
+            match self.entries.iter_mut().find(|e| e.content_type == entry.content_type && e.slug == entry.slug) {
+                Some(existing) => {
+                    existing.level = existing.level.or(entry.level);
+                    existing.school = existing.school.take().or(entry.school);
+                    for class in entry.classes {
+                        if !existing.classes.contains(&class) {
+                            existing.classes.push(class);
+                        }
+                    }
+                    existing.classes.sort();
+                }
+                None => self.entries.push(entry),
+            }
+        }
+        self.entries.sort_by(|a, b| (&a.content_type, &a.name).cmp(&(&b.content_type, &b.name)));
+    }
+
+    /// The indexed page for a name or slug, so it can be fetched without guessing its URL
+    pub fn find(&self, query: &str, content_type: &str) -> Option<&IndexEntry> {
+        let slug = query.trim().to_lowercase().replace(' ', "-");
+        self.entries.iter()
+            .filter(|e| e.content_type == content_type)
+            .find(|e| e.slug == slug || e.name.eq_ignore_ascii_case(query.trim()))
+    }
+
+    /// Entries of a category matching every filter word: a level ("3", "3rd",
+    /// "cantrip"), a class, a school, or part of the name
+    pub fn browse(&self, category: SearchCategory, filters: &[&str]) -> Vec<&IndexEntry> {
+        let content_type = category_content_type(category);
+        self.entries.iter()
+            .filter(|e| e.content_type == content_type)
+            .filter(|e| filters.iter().all(|filter| {
+                let filter = filter.to_lowercase();
+                let level = filter.parse::<u8>().ok().or_else(|| listing_level(&filter));
+                match level {
+                    Some(level) => e.level == Some(level),
+                    None => e.classes.iter().any(|c| c.eq_ignore_ascii_case(&filter))
+                        || e.school.as_deref() == Some(filter.as_str())
+                        || e.name.to_lowercase().contains(&filter),
+                }
+            }))
+            .collect()
+    }
+
+    /// Indexed names close to the query: those starting with it, then those containing it
+    pub fn suggestions(&self, query: &str, category: Option<SearchCategory>) -> Vec<String> {
+        let query = query.trim().to_lowercase().replace('-', " ");
+        let wanted = |e: &&IndexEntry| category.is_none_or(|c| e.content_type == category_content_type(c));
+        let (mut starts, mut contains): (Vec<&IndexEntry>, Vec<&IndexEntry>) = self.entries.iter()
+            .filter(wanted)
+            .filter(|e| e.name.to_lowercase().contains(&query))
+            .partition(|e| e.name.to_lowercase().starts_with(&query));
+        starts.append(&mut contains);
+        starts.into_iter().map(|e| e.slug.clone()).collect()
+    }
+}
+
+/// What a crawl found
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CrawlReport {
+    pub listings: usize,
+    pub entries: usize,
+    pub failed: Vec<String>,
+}
+
+impl CrawlReport {
+    pub fn summary(&self) -> String {
+        let mut summary = format!("🗂️ Indexed {} pages from {} listing(s) into {}", self.entries, self.listings, INDEX_FILE);
+        if !self.failed.is_empty() {
+            summary.push_str(&format!("; couldn't read {}", self.failed.join(", ")));
+        }
+        summary
+    }
+}
+
+/// Crawl the listings in the background; the receiver yields the report once it's saved
+pub fn spawn_crawl() -> Receiver<CrawlReport> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let report = DndSearchClient::try_new().and_then(|client| crate::runtime::block_on(client.crawl_index())?);
+        match report {
+            Ok(report) => {
+                let _ = tx.send(report);
+            }
+            Err(e) => log::warn!("Crawl failed: {}", e),
+        }
+    });
+    rx
+}
+
+/// `list <category> [filters...]`: browse the crawled index, e.g. `list spells 3 wizard`
+pub fn list_command(args: &[&str]) -> Vec<String> {
+    let Some(category) = args.first().and_then(|c| SearchCategory::from_str(c)) else {
+        return vec!["Usage: list <spells|monsters|equipment> [level] [class] [school] [name], e.g. list spells 3 wizard".to_string()];
+    };
+    let index = match SearchIndex::load(Path::new(INDEX_FILE)) {
+        Ok(index) => index,
+        Err(e) => return vec![format!("❌ {}", e)],
+    };
+    if index.entries.is_empty() {
+        return vec!["No index yet. Type 'crawl' to read the wiki's listings (it takes a few seconds)".to_string()];
+    }
+    let found = index.browse(category, &args[1..]);
+    if found.is_empty() {
+        return vec![format!("No {} match '{}'", category.as_str(), args[1..].join(" "))];
+    }
+    std::iter::once(format!("📚 {} {}:", found.len(), category.as_str()))
+        .chain(found.iter().map(|e| format!("  {}", e.summary())))
+        .collect()
+}
+
+/// One attack from a monster's actions:
+/// "Scimitar. Melee Weapon Attack: +4 to hit, ... Hit: 5 (1d6 + 2) slashing damage."
+fn monster_attack(action: &str) -> Option<AttackProfile> {
+    let attack = Regex::new(r"^(.+?)\. (?:Melee|Ranged) (?:Weapon|Spell) Attack: ([+-]\d+) to hit")
+        .expect("valid attack regex");
+    let hit = Regex::new(r"Hit: (?:\d+ )?\(?(\d+d\d+(?: ?[+-] ?\d+)?)\)? (\w+) damage").expect("valid hit regex");
+    let caps = attack.captures(action)?;
+    let damage = hit.captures(action);
+    Some(AttackProfile {
+        name: caps[1].trim().to_string(),
+        to_hit: caps[2].parse().ok()?,
+        damage: damage.as_ref().map(|d| d[1].replace(' ', "")),
+        damage_type: damage.as_ref().and_then(|d| DamageType::parse(&d[2])),
+    })
+}
+
+/// The numbers from a wiki monster page that the combat tracker needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonsterStatBlock {
+    pub name: String,
+    pub ac: i32,
+    pub hp: i32,
+    pub hit_dice: Option<String>,
+    pub speed: Option<String>,
+    /// STR, DEX, CON, INT, WIS, CHA; None when the page's table couldn't be read
+    pub abilities: Option<[u8; 6]>,
+    pub attacks: Vec<AttackProfile>,
+    pub cr: Option<String>,
+    pub resistances: Vec<DamageType>,
+    pub vulnerabilities: Vec<DamageType>,
+    pub immunities: Vec<DamageType>,
+    /// "The dragon can take 3 legendary actions"
+    pub legendary_actions: Option<u32>,
+    /// Whether the page has lair actions for the creature
+    pub lair_actions: bool,
+}
+
+/// The six scores from the "STR DEX CON INT WIS CHA" table, written "8 (-1)"
+fn ability_scores(content: &str) -> Option<[u8; 6]> {
+    let start = content.find("STR")?;
+    let score = Regex::new(r"\b(\d{1,2}) ?\([+\-−–]?\d+\)").expect("valid score regex");
+    let scores: Vec<u8> = score.captures_iter(&content[start..]).take(6).filter_map(|caps| caps[1].parse().ok()).collect();
+    scores.try_into().ok()
+}
+
+/// Damage types listed after "Damage Resistances" and the like. Qualifiers such as
+/// "from nonmagical attacks" are dropped, so the DM should check the list.
+fn damage_types(content: &str, label: &str) -> Vec<DamageType> {
+    let line = Regex::new(&format!(r"(?m){} (.*?)(?:Damage |Condition |Senses|Languages|Challenge|$)", label)).expect("valid defense regex");
+    let mut types: Vec<DamageType> = line.captures_iter(content)
+        .flat_map(|caps| caps[1].split(|c: char| !c.is_alphabetic()).filter_map(DamageType::parse).collect::<Vec<_>>())
+        .collect();
+    types.sort();
+    types.dedup();
+    types
+}
+
+impl MonsterStatBlock {
+    /// AC and HP are required; anything else the page doesn't show is left empty
+    pub fn from_page(page: &WikiPageContent) -> Result<Self> {
+        let monster = crate::content::Monster::from_page(page);
+        let (Some(ac), Some(hp)) = (monster.ac, monster.hp) else {
+            return Err(AppError::ParseError(format!("No Armor Class and Hit Points on the page for {}", page.name)));
+        };
+        Ok(MonsterStatBlock {
+            name: page.name.clone(),
+            ac,
+            hp,
+            hit_dice: monster.hit_dice,
+            speed: monster.speed,
+            abilities: ability_scores(&page.content),
+            attacks: monster.actions.iter().filter_map(|action| monster_attack(action)).collect(),
+            cr: monster.cr,
+            resistances: damage_types(&page.content, "Damage Resistances"),
+            vulnerabilities: damage_types(&page.content, "Damage Vulnerabilities"),
+            immunities: damage_types(&page.content, "Damage Immunities"),
+            legendary_actions: Regex::new(r"(?i)can take (\d+) legendary actions").expect("valid legendary regex")
+                .captures(&page.content).and_then(|caps| caps[1].parse().ok()),
+            lair_actions: page.content.to_lowercase().contains("lair actions"),
+        })
+    }
+
+    pub fn dex_mod(&self) -> i32 {
+        self.abilities.map_or(0, |scores| Character::calculate_modifier(scores[1]) as i32)
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let mut stats = vec![format!("AC {}", self.ac), match &self.hit_dice {
+            Some(dice) => format!("HP {} ({})", self.hp, dice),
+            None => format!("HP {}", self.hp),
+        }];
+        stats.extend(self.speed.as_ref().map(|speed| format!("Speed {}", speed)));
+        stats.extend(self.cr.as_ref().map(|cr| format!("CR {}", cr)));
+        let mut lines = vec![format!("👹 {} - {}", self.name, stats.join(" | "))];
+        if let Some(scores) = self.abilities {
+            let labels = ["STR", "DEX", "CON", "INT", "WIS", "CHA"];
+            lines.push(format!("  {}", labels.iter().zip(scores)
+                .map(|(label, score)| format!("{} {} ({:+})", label, score, Character::calculate_modifier(score)))
+                .collect::<Vec<_>>()
+                .join("  ")));
+        }
+        lines.extend(self.attacks.iter().map(|attack| format!("  ⚔️ {}", attack.describe())));
+        lines.extend(self.legendary_actions.map(|count| format!("  👑 {} legendary actions a round ('legendary {} <action>')", count, self.name.to_lowercase())));
+        if self.lair_actions {
+            lines.push(format!("  🏰 Has lair actions: 'lair {}' if the fight is in its lair", self.name));
+        }
+        lines
+    }
+
+    pub fn to_combatant(&self, name: String, initiative: i32) -> Combatant {
+        let mut combatant = Combatant::new_npc(name, self.hp, self.ac, initiative);
+        combatant.cr = self.cr.clone();
+        combatant.resistances = self.resistances.clone();
+        combatant.vulnerabilities = self.vulnerabilities.clone();
+        combatant.immunities = self.immunities.clone();
+        combatant.attacks = self.attacks.clone();
+        combatant.legendary = self.legendary_actions.map(|per_round| crate::combat::LegendaryActions { per_round, spent: 0 });
+        combatant
+    }
+
+    /// Put `count` of the monster in the fight, each with its own initiative roll.
+    /// Copies are numbered on from any of the same kind already there ("Goblin 3").
+    pub fn add_to_tracker<R: Rng + ?Sized>(&self, tracker: &mut CombatTracker, count: usize, rng: &mut R) -> Vec<String> {
+        let present = tracker.combatants.iter().filter(|c| crate::combat::creature_kind(&c.name) == self.name.to_lowercase()).count();
+        let mut lines = self.lines();
+        let mut defenses = Vec::new();
+        for i in 0..count {
+            let name = if count == 1 && present == 0 { self.name.clone() } else { format!("{} {}", self.name, present + i + 1) };
+            let roll = rng.random_range(1..=20);
+            let initiative = roll + self.dex_mod();
+            lines.push(format!("✅ {} joins the fight (initiative {} = d20 {} {:+})", name, initiative, roll, self.dex_mod()));
+            let combatant = self.to_combatant(name, initiative);
+            defenses = combatant.defense_lines();
+            tracker.add_combatant(combatant);
+        }
+        lines.extend(defenses.into_iter().map(|line| format!("  🛡️ {} (check the page for qualifiers)", line)));
+        lines
+    }
+}
+
+/// Look a monster up (the cache first) and read its stat block
+pub fn fetch_monster(name: &str) -> Result<MonsterStatBlock> {
+    MonsterStatBlock::from_page(&lookup_reference(name, Some(SearchCategory::Monsters))?)
+}
+
+// Main search client for Wikidot HTML scraping. Clones share the HTTP connection pool.
+#[derive(Clone)]
 pub struct DndSearchClient {
     base_url: String,
     client: reqwest::Client,
+    cache_dir: PathBuf,
+    srd_dir: PathBuf,
+    index_path: PathBuf,
 }
 
 impl Default for DndSearchClient {
@@ -169,30 +736,94 @@ impl Default for DndSearchClient {
 }
 
 impl DndSearchClient {
+    /// Build a client, panicking if the HTTP backend can't be initialised.
+    /// Frontends should prefer `try_new` so they can report the failure.
     pub fn new() -> Self {
+        Self::try_new().expect("Failed to create HTTP client - network required for Wikidot API")
+    }
+
+    pub fn try_new() -> Result<Self> {
         let client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(10))
             .build()
-            .expect("Failed to create HTTP client - network required for Wikidot API");
+            .context("Failed to create HTTP client")?;
         
-        DndSearchClient {
+        Ok(DndSearchClient {
             base_url: "http://dnd5e.wikidot.com".to_string(),
             client,
+            cache_dir: PathBuf::from(CACHE_DIR),
+            srd_dir: PathBuf::from(crate::srd::SRD_DIR),
+            index_path: PathBuf::from(INDEX_FILE),
+        })
+    }
+
+    /// A page fetched earlier, without touching the network. Pages older than the
+    /// cache's TTL only count offline; online they're fetched again.
+    pub fn cached(&self, query: &str, category: SearchCategory) -> Option<WikiPageContent> {
+        let content_type = category_content_type(category);
+        let fresh = crate::srd::is_offline()
+            || !crate::cache::is_expired(&cache_path(&self.cache_dir, content_type, query), crate::cache::cache_ttl());
+        if !fresh {
+            log::debug!("Cached {} '{}' has expired", content_type, query);
+            return None;
+        }
+        read_cached_page(&self.cache_dir, content_type, query)
+    }
+
+    /// `cache refresh <name>`: fetch every cached copy of a page again from the URL it
+    /// came from. A copy that can't be fetched is kept.
+    pub async fn refresh_cached(&self, name: &str) -> Vec<String> {
+        let paths = crate::cache::entries_for(&self.cache_dir, &cache_slug(name));
+        if paths.is_empty() {
+            return vec![format!("Nothing cached for '{}'; searching for it fetches it", name)];
         }
+        let mut lines = Vec::new();
+        for path in paths {
+            let content_type = path.parent().and_then(|p| p.file_name()).map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let Some(cached) = read_cached_page(&self.cache_dir, &content_type, name) else { continue };
+            if !cached.url.starts_with("http") {
+                lines.push(format!("{} ({}) didn't come from the wiki; nothing to refresh", cached.name, content_type));
+                continue;
+            }
+            match self.fetch_page(&cached.url, name, &content_type).await {
+                Ok(Some(page)) => match write_cached_page(&self.cache_dir, name, &page) {
+                    Ok(()) => lines.push(format!("🔄 Refreshed {} ({}) from {}", page.name, content_type, page.url)),
+                    Err(e) => lines.push(format!("❌ {}", e)),
+                },
+                Ok(None) => lines.push(format!("❌ {} is gone from the wiki; kept the cached copy", cached.url)),
+                Err(e) => lines.push(format!("❌ Could not refresh {} ({}): {}; kept the cached copy", cached.name, content_type, e)),
+            }
+        }
+        lines
     }
 
     // Search with fuzzy matching using Wikidot HTML scraping
-    pub async fn search(&self, query: &str, category: Option<SearchCategory>) -> Result<Vec<SearchResult>, String> {
+    pub async fn search(&self, query: &str, category: Option<SearchCategory>) -> Result<Vec<SearchResult>> {
         let categories = match category {
             Some(cat) => vec![cat],
             None => SearchCategory::all(),
         };
 
         let mut all_results = Vec::new();
+        let mut layout_error = None;
 
-        for cat in categories {
-            match self.search_category(query, cat).await {
+        // Every category is looked up at once; results keep the categories' order
+        let mut lookups = tokio::task::JoinSet::new();
+        for (i, cat) in categories.into_iter().enumerate() {
+            let client = self.clone();
+            let query = query.to_string();
+            lookups.spawn(async move { (i, cat, client.search_category(&query, cat).await) });
+        }
+        let mut outcomes = lookups.join_all().await;
+        outcomes.sort_by_key(|(i, _, _)| *i);
+
+        for (_, cat, outcome) in outcomes {
+            match outcome {
                 Ok(mut results) => all_results.append(&mut results),
+                Err(e @ AppError::LayoutChanged(_)) => {
+                    log::error!("{} page for '{}': {}", cat.as_str(), query, e);
+                    layout_error.get_or_insert(e);
+                }
                 Err(e) => {
                     eprintln!("Warning: Failed to search {}: {}", cat.as_str(), e);
                     // Continue with other categories
@@ -201,57 +832,167 @@ impl DndSearchClient {
         }
 
         if all_results.is_empty() {
-            // Try fuzzy matching
-            self.fuzzy_search(query, category).await
+            // Try fuzzy matching, but a page we found and couldn't read is the better explanation
+            match (self.fuzzy_search(query, category).await, layout_error) {
+                (Err(AppError::NotFound(_)), Some(layout_error)) => Err(layout_error),
+                (result, _) => result,
+            }
         } else {
             Ok(all_results)
         }
     }
 
-    async fn search_category(&self, query: &str, category: SearchCategory) -> Result<Vec<SearchResult>, String> {
-        match category {
+    /// Fetch each page that isn't cached yet, so later lookups don't need the network
+    pub async fn prefetch(&self, targets: &[(SearchCategory, String)]) -> PrefetchReport {
+        let mut report = PrefetchReport::default();
+        for (category, query) in targets {
+            if self.cached(query, *category).is_some() {
+                report.already_cached += 1;
+                continue;
+            }
+            match self.search_category(query, *category).await {
+                Ok(_) => report.fetched += 1,
+                Err(e) => {
+                    log::debug!("Prefetch of {} '{}' failed: {}", category.as_str(), query, e);
+                    report.failed.push(query.clone());
+                }
+            }
+        }
+        report
+    }
+
+    /// Read every listing page into the search index and save it. A listing that can't
+    /// be read is reported, and what was indexed from it before is kept.
+    pub async fn crawl_index(&self) -> Result<CrawlReport> {
+        let mut index = SearchIndex::load(&self.index_path)?;
+        let mut report = CrawlReport::default();
+        let class_lists = SPELL_LIST_CLASSES.iter().map(|class| (format!("spells:{}", class), "spell", Some(*class)));
+        let listings = LISTINGS.iter().map(|(path, content_type)| (path.to_string(), *content_type, None)).chain(class_lists);
+        for (path, content_type, class) in listings {
+            let url = format!("{}/{}", self.base_url, path);
+            log::debug!("GET {}", url);
+            let html = match self.client.get(&url).send().await.and_then(|r| r.error_for_status()) {
+                Ok(response) => response.text().await.context("Failed to read listing")?,
+                Err(e) => {
+                    log::warn!("Listing {} failed: {}", url, e);
+                    report.failed.push(path);
+                    continue;
+                }
+            };
+            let entries = parse_listing(&html, content_type, &self.base_url, class);
+            if entries.is_empty() {
+                log::warn!("No entries on listing {}; the layout may have changed", url);
+                report.failed.push(path);
+                continue;
+            }
+            report.listings += 1;
+            index.merge(entries);
+        }
+        report.entries = index.entries.len();
+        index.save(&self.index_path)?;
+        log::info!("Crawl indexed {} pages from {} listings", report.entries, report.listings);
+        Ok(report)
+    }
+
+    async fn search_category(&self, query: &str, category: SearchCategory) -> Result<Vec<SearchResult>> {
+        if let Some(page) = self.cached(query, category) {
+            log::debug!("Cache hit for {} '{}'", page.content_type, query);
+            return Ok(vec![SearchResult { page }]);
+        }
+        if crate::srd::is_offline() {
+            if !crate::srd::covers(category) {
+                // Nothing to look in offline; an empty result keeps `search` from warning about it
+                return Ok(Vec::new());
+            }
+            return crate::srd::lookup_in(&self.srd_dir, query, category).map(|page| vec![SearchResult { page }]);
+        }
+        let fetched = match category {
             SearchCategory::Spells => self.fetch_wiki_page(query, "spell", "spell").await,
             SearchCategory::Classes => self.fetch_wiki_page(query, "class", "class").await,
             SearchCategory::Equipment => self.fetch_wiki_page(query, "equipment", "equipment").await,
             SearchCategory::Monsters => self.fetch_wiki_page(query, "monster", "monster").await,
             SearchCategory::Races => self.fetch_wiki_page(query, "race", "race").await,
+        };
+        // An expired copy still beats nothing when the wiki can't give us a new one
+        let fetched = match fetched {
+            Err(e) => match read_cached_page(&self.cache_dir, category_content_type(category), query) {
+                Some(page) => {
+                    log::info!("Refetching {} '{}' failed ({}); using the expired cached copy", category.as_str(), query, e);
+                    return Ok(vec![SearchResult { page }]);
+                }
+                None => Err(e),
+            },
+            fetched => fetched,
+        };
+        match fetched {
+            // The wiki couldn't be reached or read; the SRD data may still have the page
+            Err(e) if crate::srd::covers(category) && !matches!(e, AppError::NotFound(_)) => {
+                match crate::srd::lookup_in(&self.srd_dir, query, category) {
+                    Ok(page) => {
+                        log::info!("Wiki lookup of {} '{}' failed ({}); using the SRD data", category.as_str(), query, e);
+                        Ok(vec![SearchResult { page }])
+                    }
+                    Err(_) => Err(e),
+                }
+            }
+            fetched => fetched,
         }
     }
 
-    async fn fetch_wiki_page(&self, query: &str, content_type: &str, url_prefix: &str) -> Result<Vec<SearchResult>, String> {
-        // Try different URL patterns that wikidot might use
-        let possible_urls = self.generate_possible_urls(query, url_prefix);
+    async fn fetch_wiki_page(&self, query: &str, content_type: &str, url_prefix: &str) -> Result<Vec<SearchResult>> {
+        // A page found by the crawler first, then the URL patterns wikidot might use
+        let indexed = SearchIndex::load(&self.index_path).ok()
+            .and_then(|index| index.find(query, content_type).map(|entry| entry.url.clone()));
+        let mut possible_urls: Vec<String> = indexed.into_iter().collect();
+        for url in self.generate_possible_urls(query, url_prefix) {
+            if !possible_urls.contains(&url) {
+                possible_urls.push(url);
+            }
+        }
         
         for url in possible_urls {
-            let response = self.client
-                .get(&url)
-                .send()
-                .await
-                .map_err(|e| format!("Network request failed: {}", e))?;
-
-            if response.status().is_success() {
-                let html = response.text().await
-                    .map_err(|e| format!("Failed to read response: {}", e))?;
-
-                let document = Html::parse_document(&html);
-                
-                // Extract the main page content
-                let content = self.extract_page_content(&document)?;
-                let title = self.extract_page_title(&document, query);
-                
-                let page = WikiPageContent {
-                    index: query.to_lowercase().replace(" ", "-"),
-                    name: title,
-                    url: url.clone(),
-                    content,
-                    content_type: content_type.to_string(),
-                };
+            if let Some(page) = self.fetch_page(&url, query, content_type).await? {
+                if let Err(e) = write_cached_page(&self.cache_dir, query, &page) {
+                    log::warn!("{}", e);
+                }
                 
                 return Ok(vec![SearchResult { page }]);
             }
         }
         
-        Err(format!("{} '{}' not found", content_type, query))
+        Err(AppError::NotFound(format!("{} '{}'", content_type, query)))
+    }
+
+    /// Fetch and parse one page; None when the wiki has no page at that URL
+    async fn fetch_page(&self, url: &str, query: &str, content_type: &str) -> Result<Option<WikiPageContent>> {
+        log::debug!("GET {}", url);
+        let response = self.client
+            .get(url)
+            .send()
+            .await
+            .inspect_err(|e| log::warn!("Request to {} failed: {}", url, e))
+            .context("Network request failed")?;
+        log::debug!("{} -> {}", url, response.status());
+        if !response.status().is_success() {
+            return Ok(None);
+        }
+
+        let html = response.text().await
+            .context("Failed to read response")?;
+
+        let document = Html::parse_document(&html);
+        
+        // Extract the main page content
+        let content = self.extract_page_content(&document)?;
+        let title = self.extract_page_title(&document, query);
+        
+        Ok(Some(WikiPageContent {
+            index: query.to_lowercase().replace(" ", "-"),
+            name: title,
+            url: url.to_string(),
+            content,
+            content_type: content_type.to_string(),
+        }))
     }
 
     fn generate_possible_urls(&self, query: &str, url_prefix: &str) -> Vec<String> {
@@ -277,21 +1018,26 @@ impl DndSearchClient {
         urls
     }
 
-    fn extract_page_content(&self, document: &Html) -> Result<String, String> {
-        let content_selector = Selector::parse("#page-content").unwrap();
-        let content = document.select(&content_selector).next()
-            .ok_or("Could not find page content")?;
-
-        let html_content = content.inner_html();
-        
-        // Clean up the HTML and convert to readable text
-        let cleaned_content = self.html_to_readable_text(&html_content);
-        
-        if cleaned_content.trim().is_empty() {
-            return Err("Page content is empty or could not be parsed".to_string());
+    fn extract_page_content(&self, document: &Html) -> Result<String> {
+        let mut too_short = None;
+        for css in CONTENT_SELECTORS {
+            let selector = Selector::parse(css).unwrap();
+            let Some(content) = document.select(&selector).next() else {
+                continue;
+            };
+            // Clean up the HTML and convert to readable text
+            let cleaned_content = self.html_to_readable_text(&content.inner_html());
+            let length = cleaned_content.trim().chars().count();
+            if length >= MIN_CONTENT_CHARS {
+                return Ok(cleaned_content);
+            }
+            too_short.get_or_insert((css, length));
         }
-        
-        Ok(cleaned_content)
+
+        Err(AppError::LayoutChanged(match too_short {
+            Some((css, length)) => format!("{} held only {} characters of text", css, length),
+            None => format!("no {} element on the page", CONTENT_SELECTORS.join(" or ")),
+        }))
     }
 
     fn extract_page_title(&self, document: &Html, fallback: &str) -> String {
@@ -393,7 +1139,7 @@ impl DndSearchClient {
         }
     }
 
-    async fn fuzzy_search(&self, query: &str, category: Option<SearchCategory>) -> Result<Vec<SearchResult>, String> {
+    async fn fuzzy_search(&self, query: &str, category: Option<SearchCategory>) -> Result<Vec<SearchResult>> {
         // For Wikidot, fuzzy search attempts common variations
         let variations = self.generate_query_variations(query);
         
@@ -412,7 +1158,7 @@ impl DndSearchClient {
             }
         }
 
-        Err(format!("No matches found for '{}'", query))
+        Err(AppError::NotFound(format!("Matches for '{}'", query)))
     }
 
     fn generate_query_variations(&self, query: &str) -> Vec<String> {
@@ -443,8 +1189,14 @@ impl DndSearchClient {
     }
 
     // Method to get suggestions when no exact match is found
-    pub async fn get_suggestions(&self, query: &str, _category: Option<SearchCategory>) -> Vec<String> {
-        // For Wikidot implementation, return common suggestions based on query
+    pub async fn get_suggestions(&self, query: &str, category: Option<SearchCategory>) -> Vec<String> {
+        // Names from the crawled listings beat the guesses below
+        let indexed = SearchIndex::load(&self.index_path).map(|index| index.suggestions(query, category)).unwrap_or_default();
+        if !indexed.is_empty() {
+            return indexed.into_iter().take(5).collect();
+        }
+
+        // Without an index, return common suggestions based on query
         let mut suggestions = Vec::new();
         
         let query_lower = query.to_lowercase();
@@ -562,6 +1314,106 @@ mod tests {
         assert!(result.contains("Dexterity saving throw"));
     }
 
+    #[tokio::test]
+    async fn test_prefetch_skips_cached_pages() {
+        let mut client = DndSearchClient::new();
+        client.cache_dir = std::env::temp_dir().join(format!("dnd_tools_prefetch_{}", std::process::id()));
+        let page = WikiPageContent {
+            index: "ogre".to_string(),
+            name: "Ogre".to_string(),
+            url: "http://dnd5e.wikidot.com/monster:ogre".to_string(),
+            content: "Large giant, chaotic evil".to_string(),
+            content_type: "monster".to_string(),
+        };
+        write_cached_page(&client.cache_dir, "Ogre", &page).unwrap();
+
+        // Already on disk, so no request is made
+        let report = client.prefetch(&[(SearchCategory::Monsters, "ogre".to_string())]).await;
+        assert_eq!(report, PrefetchReport { already_cached: 1, fetched: 0, failed: Vec::new() });
+        let _ = std::fs::remove_dir_all(&client.cache_dir);
+    }
+
+    fn fixture(name: &str) -> Html {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_fixtures/wikidot").join(name);
+        Html::parse_document(&std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e)))
+    }
+
+    /// Compare against `<name>.snap` beside the fixtures. Set UPDATE_SNAPSHOTS=1 to
+    /// write them for a new fixture or after a deliberate parser change, then review the diff.
+    fn assert_snapshot(name: &str, actual: &str) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_fixtures/wikidot").join(format!("{}.snap", name));
+        if std::env::var("UPDATE_SNAPSHOTS").is_ok() {
+            std::fs::write(&path, actual).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("no snapshot for {} at {} ({}); rerun with UPDATE_SNAPSHOTS=1 to write it", name, path.display(), e));
+        assert_eq!(actual, expected, "parser output for {} changed; rerun with UPDATE_SNAPSHOTS=1 if that's intended", name);
+    }
+
+    fn parsed(client: &DndSearchClient, fixture_name: &str, query: &str) -> Result<String> {
+        let document = fixture(fixture_name);
+        let content = client.extract_page_content(&document)?;
+        Ok(format!("{}\n---\n{}\n", client.extract_page_title(&document, query), content))
+    }
+
+    #[test]
+    fn test_parser_snapshots() {
+        let client = DndSearchClient::new();
+        assert_snapshot("spell_fireball", &parsed(&client, "spell_fireball.html", "fireball").unwrap());
+        // Older or mirrored layouts without #page-content fall back to .main-content
+        assert_snapshot("monster_goblin_main_content", &parsed(&client, "monster_goblin_main_content.html", "goblin").unwrap());
+    }
+
+    #[test]
+    fn test_parser_reports_layout_changes() {
+        let client = DndSearchClient::new();
+        for fixture_name in ["layout_changed.html", "empty_page_content.html"] {
+            match parsed(&client, fixture_name, "fireball") {
+                Err(AppError::LayoutChanged(reason)) => assert!(!reason.is_empty()),
+                other => panic!("{} should be a layout change, got {:?}", fixture_name, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_listing_crawl_index() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_fixtures/wikidot/spells_wizard_listing.html");
+        let html = std::fs::read_to_string(&path).unwrap();
+        let base_url = "https://dnd5e.wikidot.com";
+        let wizard = parse_listing(&html, "spell", base_url, Some("wizard"));
+        // Links off the wiki are skipped
+        assert_eq!(wizard.len(), 6);
+        let fireball = wizard.iter().find(|e| e.slug == "fireball").unwrap();
+        assert_eq!(fireball.name, "Fireball");
+        assert_eq!(fireball.url, "https://dnd5e.wikidot.com/spell:fireball");
+        assert_eq!((fireball.level, fireball.school.as_deref()), (Some(3), Some("evocation")));
+        assert_eq!(wizard.iter().find(|e| e.slug == "fire-bolt").unwrap().level, Some(0));
+
+        // The same page from another class's list adds the class instead of a duplicate
+        let mut index = SearchIndex::default();
+        index.merge(wizard);
+        let mut sorcerer = parse_listing(&html, "spell", base_url, Some("sorcerer"));
+        sorcerer.retain(|e| e.slug == "fireball");
+        index.merge(sorcerer);
+        assert_eq!(index.entries.len(), 6);
+        assert_eq!(index.find("Fireball", "spell").unwrap().classes, vec!["sorcerer", "wizard"]);
+        assert_eq!(index.find("fireball", "spell").unwrap().summary(), "Fireball (3rd-level evocation; sorcerer, wizard)");
+
+        let names = |found: Vec<&IndexEntry>| found.iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(index.browse(SearchCategory::Spells, &["3", "wizard"])), vec!["Counterspell", "Fireball"]);
+        assert_eq!(names(index.browse(SearchCategory::Spells, &["cantrip", "evocation"])), vec!["Fire Bolt"]);
+        assert_eq!(names(index.browse(SearchCategory::Spells, &["3rd", "sorcerer"])), vec!["Fireball"]);
+        assert!(index.browse(SearchCategory::Monsters, &[]).is_empty());
+        assert_eq!(index.suggestions("fire", Some(SearchCategory::Spells)), vec!["fire-bolt", "fireball"]);
+        assert_eq!(index.suggestions("missile", None), vec!["magic-missile"]);
+
+        let saved = std::env::temp_dir().join(format!("dnd_tools_index_{}.json", std::process::id()));
+        index.save(&saved).unwrap();
+        assert_eq!(SearchIndex::load(&saved).unwrap(), index);
+        let _ = std::fs::remove_file(&saved);
+    }
+
     #[test]
     fn test_possible_urls_generation() {
         let client = DndSearchClient::new();
@@ -0,0 +1,175 @@
+use crate::character::Character;
+use crate::combat::Combatant;
+use crate::error_handling::{AppError, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, path::Path};
+
+/// Sidekicks are saved with the rest of the campaign
+pub const SIDEKICKS_FILE: &str = "campaign/sidekicks.ron";
+
+/// The three sidekick classes from Tasha's Cauldron of Everything
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SidekickClass {
+    Expert,
+    Spellcaster,
+    Warrior,
+}
+
+impl SidekickClass {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.to_lowercase().as_str() {
+            "expert" => Some(SidekickClass::Expert),
+            "spellcaster" | "caster" => Some(SidekickClass::Spellcaster),
+            "warrior" => Some(SidekickClass::Warrior),
+            _ => None,
+        }
+    }
+
+    /// Hit die size used when a sidekick has no base creature hit die recorded
+    pub fn default_hit_die(&self) -> u32 {
+        match self {
+            SidekickClass::Expert | SidekickClass::Spellcaster => 8,
+            SidekickClass::Warrior => 10,
+        }
+    }
+
+    /// Headline features gained by each level, (level, feature)
+    fn features(&self) -> &'static [(u8, &'static str)] {
+        match self {
+            SidekickClass::Expert => &[
+                (1, "Helpful"), (2, "Cunning Action"), (3, "Expertise"), (6, "Coordinated Strike"),
+                (7, "Evasion"), (9, "Inspiring Help"), (11, "Reliable Talent"), (15, "Sharp Mind"), (20, "Inspiring Help (2d6)"),
+            ],
+            SidekickClass::Spellcaster => &[
+                (1, "Spellcasting"), (2, "Potent Cantrips"), (6, "Empowered Spells"),
+                (14, "Focused Casting"), (18, "Signature Spells"),
+            ],
+            SidekickClass::Warrior => &[
+                (1, "Martial Role"), (2, "Second Wind"), (3, "Improved Critical"), (6, "Extra Attack"),
+                (7, "Battle Readiness"), (10, "Improved Defense"), (11, "Indomitable"), (15, "Extra Attack (2)"), (18, "Survivor"),
+            ],
+        }
+    }
+}
+
+impl fmt::Display for SidekickClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SidekickClass::Expert => "Expert",
+            SidekickClass::Spellcaster => "Spellcaster",
+            SidekickClass::Warrior => "Warrior",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A lightweight companion built on a simple stat block; its level follows the party
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sidekick {
+    pub name: String,
+    pub class: SidekickClass,
+    /// The stat block the sidekick started from, e.g. "Apprentice" or "Guard"
+    pub creature: Option<String>,
+    pub base_hp: i32,
+    pub ac: i32,
+    pub hit_die: u32,
+    pub con_mod: i32,
+    pub dex_mod: i32,
+}
+
+impl Sidekick {
+    pub fn new(name: &str, class: SidekickClass, base_hp: i32, ac: i32) -> Self {
+        Sidekick {
+            name: name.to_string(),
+            class,
+            creature: None,
+            base_hp: base_hp.max(1),
+            ac,
+            hit_die: class.default_hit_die(),
+            con_mod: 0,
+            dex_mod: 0,
+        }
+    }
+
+    /// Base hit points plus the average hit die roll for each level after the first
+    pub fn max_hp(&self, level: u8) -> i32 {
+        let per_level = (self.hit_die as i32 / 2 + 1 + self.con_mod).max(1);
+        self.base_hp + per_level * (level.max(1) as i32 - 1)
+    }
+
+    pub fn proficiency_bonus(level: u8) -> i32 {
+        crate::leveling::proficiency_bonus(level) as i32
+    }
+
+    pub fn features(&self, level: u8) -> Vec<&'static str> {
+        self.class.features().iter()
+            .filter(|(gained, _)| *gained <= level)
+            .map(|(_, feature)| *feature)
+            .collect()
+    }
+
+    pub fn summary(&self, level: u8) -> String {
+        let creature = self.creature.as_deref().map(|c| format!(" {}", c)).unwrap_or_default();
+        format!("{} ({}{} {}, HP {}, AC {}, +{} prof)", self.name, self.class, creature, level,
+            self.max_hp(level), self.ac, Self::proficiency_bonus(level))
+    }
+
+    /// Ready to drop into the initiative order
+    pub fn to_combatant(&self, level: u8, initiative: i32) -> Combatant {
+        let mut combatant = Combatant::new_npc(self.name.clone(), self.max_hp(level), self.ac, initiative);
+        // Sidekicks fight for the party, so their HP is never hidden like a monster's
+        combatant.is_player = true;
+        combatant
+    }
+}
+
+/// Sidekicks level with the party: the average level of the player characters
+pub fn party_level(characters: &[Character]) -> u8 {
+    let levels: Vec<u32> = characters.iter().filter_map(|c| c.level).map(u32::from).collect();
+    if levels.is_empty() {
+        return 1;
+    }
+    (levels.iter().sum::<u32>() / levels.len() as u32).clamp(1, 20) as u8
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sidekicks {
+    pub sidekicks: Vec<Sidekick>,
+}
+
+impl Sidekicks {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        ron::de::from_str(&contents).map_err(|e| AppError::ParseError(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create campaign folder")?;
+        }
+        let serialized = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| AppError::ParseError(e.to_string()))?;
+        fs::write(path, serialized).context(format!("Failed to write {}", path.display()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Sidekick> {
+        self.sidekicks.iter().find(|s| s.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn add(&mut self, sidekick: Sidekick) -> Result<()> {
+        if self.get(&sidekick.name).is_some() {
+            return Err(AppError::ValidationError(format!("A sidekick named '{}' already exists", sidekick.name)));
+        }
+        self.sidekicks.push(sidekick);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<Sidekick> {
+        let index = self.sidekicks.iter().position(|s| s.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| AppError::NotFound(format!("Sidekick '{}'", name)))?;
+        Ok(self.sidekicks.remove(index))
+    }
+}
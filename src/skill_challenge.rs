@@ -0,0 +1,132 @@
+use crate::error_handling::{AppError, Result};
+
+/// DC used when a challenge is started without one (a "medium" check)
+pub const DEFAULT_CHALLENGE_DC: i32 = 13;
+/// Each failure so far raises the suggested DC by this much
+const DC_PER_FAILURE: i32 = 1;
+/// Leaning on a skill that already succeeded gets harder
+const REPEATED_SKILL_PENALTY: i32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckAttempt {
+    pub character: String,
+    pub skill: String,
+    pub dc: i32,
+    pub roll: i32,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChallengeStatus {
+    InProgress,
+    Succeeded,
+    Failed,
+}
+
+/// X successes before Y failures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkillChallenge {
+    pub name: String,
+    pub successes_needed: usize,
+    pub failures_allowed: usize,
+    pub base_dc: i32,
+    pub attempts: Vec<CheckAttempt>,
+}
+
+impl SkillChallenge {
+    pub fn new(name: &str, successes_needed: usize, failures_allowed: usize, base_dc: i32) -> Result<Self> {
+        if successes_needed == 0 || failures_allowed == 0 {
+            return Err(AppError::ValidationError("A skill challenge needs at least 1 success and 1 failure to end".to_string()));
+        }
+        if !(1..=40).contains(&base_dc) {
+            return Err(AppError::ValidationError("DC must be between 1 and 40".to_string()));
+        }
+        Ok(SkillChallenge {
+            name: name.to_string(),
+            successes_needed,
+            failures_allowed,
+            base_dc,
+            attempts: Vec::new(),
+        })
+    }
+
+    pub fn successes(&self) -> usize {
+        self.attempts.iter().filter(|a| a.success).count()
+    }
+
+    pub fn failures(&self) -> usize {
+        self.attempts.iter().filter(|a| !a.success).count()
+    }
+
+    pub fn status(&self) -> ChallengeStatus {
+        if self.successes() >= self.successes_needed {
+            ChallengeStatus::Succeeded
+        } else if self.failures() >= self.failures_allowed {
+            ChallengeStatus::Failed
+        } else {
+            ChallengeStatus::InProgress
+        }
+    }
+
+    /// The DC rises with each failure, and again for a skill that has already succeeded
+    pub fn suggested_dc(&self, skill: Option<&str>) -> i32 {
+        let repeated = skill.is_some_and(|skill| self.attempts.iter()
+            .any(|a| a.success && a.skill.eq_ignore_ascii_case(skill)));
+        let penalty = if repeated { REPEATED_SKILL_PENALTY } else { 0 };
+        self.base_dc + self.failures() as i32 * DC_PER_FAILURE + penalty
+    }
+
+    /// Record a check; without an explicit DC the suggested one is used
+    pub fn record(&mut self, character: &str, skill: &str, roll: i32, dc: Option<i32>) -> Result<&CheckAttempt> {
+        if self.status() != ChallengeStatus::InProgress {
+            return Err(AppError::ValidationError(format!("The '{}' challenge is already over", self.name)));
+        }
+        let dc = dc.unwrap_or_else(|| self.suggested_dc(Some(skill)));
+        self.attempts.push(CheckAttempt {
+            character: character.to_string(),
+            skill: skill.to_string(),
+            dc,
+            roll,
+            success: roll >= dc,
+        });
+        Ok(self.attempts.last().expect("attempt was just pushed"))
+    }
+
+    /// e.g. "Successes ●●○○○ 2/5 | Failures ✗○○ 1/3"
+    pub fn progress(&self) -> String {
+        let pips = |filled: usize, total: usize, mark: &str| {
+            format!("{}{}", mark.repeat(filled.min(total)), "○".repeat(total.saturating_sub(filled)))
+        };
+        format!("Successes {} {}/{} | Failures {} {}/{}",
+            pips(self.successes(), self.successes_needed, "●"), self.successes(), self.successes_needed,
+            pips(self.failures(), self.failures_allowed, "✗"), self.failures(), self.failures_allowed)
+    }
+
+    /// The outcome plus who contributed what
+    pub fn summary(&self) -> Vec<String> {
+        let outcome = match self.status() {
+            ChallengeStatus::Succeeded if self.failures() == 0 => "🏆 Flawless success".to_string(),
+            ChallengeStatus::Succeeded if self.failures() + 1 == self.failures_allowed => "✅ Success, but at a cost".to_string(),
+            ChallengeStatus::Succeeded => "✅ Success".to_string(),
+            ChallengeStatus::Failed if self.successes() + 1 >= self.successes_needed => "❌ Failure, but so close - offer a partial win".to_string(),
+            ChallengeStatus::Failed => "❌ Failure".to_string(),
+            ChallengeStatus::InProgress => "⏳ Still in progress".to_string(),
+        };
+
+        let mut lines = vec![format!("📜 {}: {}", self.name, outcome), format!("  {}", self.progress())];
+        let mut characters: Vec<&str> = Vec::new();
+        for attempt in &self.attempts {
+            if !characters.iter().any(|c| c.eq_ignore_ascii_case(&attempt.character)) {
+                characters.push(&attempt.character);
+            }
+        }
+        for character in characters {
+            let checks: Vec<String> = self.attempts.iter()
+                .filter(|a| a.character.eq_ignore_ascii_case(character))
+                .map(|a| format!("{} {} vs {} {}", a.skill, a.roll, a.dc, if a.success { "✓" } else { "✗" }))
+                .collect();
+            lines.push(format!("  {}: {}", character, checks.join(", ")));
+        }
+        lines
+    }
+}
@@ -0,0 +1,94 @@
+use crate::campaign::slug;
+use crate::character::Character;
+use crate::content::{Spell, license_notice};
+use crate::error_handling::{Result, ResultExt};
+use std::{fs, path::{Path, PathBuf}};
+
+/// Printable card sheets, one Markdown file per character
+pub const SPELL_CARDS_DIR: &str = "campaign/spell_cards";
+
+/// The spells a character knows and has prepared, each once, in the order on the sheet
+pub fn card_spells(character: &Character) -> Vec<String> {
+    let mut spells: Vec<String> = Vec::new();
+    for spell in character.spells.iter().chain(&character.prepared) {
+        if !spells.iter().any(|s| s.eq_ignore_ascii_case(spell)) {
+            spells.push(spell.clone());
+        }
+    }
+    spells
+}
+
+fn card(name: &str, spell: Option<&Spell>, prepared: bool) -> Vec<String> {
+    let mark = if prepared { " ✦" } else { "" };
+    let mut lines = vec!["---".to_string(), String::new(), format!("### {}{}", name, mark)];
+    let Some(spell) = spell else {
+        lines.push(String::new());
+        lines.push("*Not cached yet. Look it up in Search (or `prefetch` in combat) and print again to fill in this card.*".to_string());
+        lines.push(String::new());
+        return lines;
+    };
+    lines.push(format!("*{}*", spell.level_line()));
+    lines.push(String::new());
+    for (label, value) in [("Casting Time", &spell.casting_time), ("Range", &spell.range), ("Components", &spell.components), ("Duration", &spell.duration)] {
+        if !value.is_empty() {
+            lines.push(format!("**{}:** {}  ", label, value));
+        }
+    }
+    lines.push(String::new());
+    lines.extend(spell.description.lines().map(str::to_string));
+    if let Some(higher) = &spell.higher_levels {
+        lines.push(String::new());
+        lines.push(format!("**At Higher Levels.** {}", higher));
+    }
+    lines.push(String::new());
+    lines.push(format!("<sub>{}</sub>", spell.source.attribution()));
+    lines.push(String::new());
+    lines
+}
+
+/// One card per spell, cantrips first and then by level, with uncached spells at the
+/// end. Prepared spells are marked ✦. The licenses of the text used close the sheet.
+pub fn spell_cards_markdown(character: &Character, spells: &[(String, Option<Spell>)]) -> String {
+    let mut ordered: Vec<&(String, Option<Spell>)> = spells.iter().collect();
+    ordered.sort_by_key(|(name, spell)| (spell.as_ref().map_or(u8::MAX, |s| s.level), name.to_lowercase()));
+    let class = match (&character.class, character.level) {
+        (Some(class), Some(level)) => format!(" (level {} {})", level, class),
+        (Some(class), None) => format!(" ({})", class),
+        _ => String::new(),
+    };
+    let mut lines = vec![format!("# {}'s spell cards{}", character.name, class), String::new()];
+    if character.prepared.is_empty() {
+        lines.push(format!("{} spell(s).", spells.len()));
+    } else {
+        lines.push(format!("{} spell(s); ✦ marks the ones prepared today.", spells.len()));
+    }
+    lines.push(String::new());
+    for (name, spell) in ordered {
+        let prepared = character.prepared.iter().any(|p| p.eq_ignore_ascii_case(name));
+        lines.extend(card(name, spell.as_ref(), prepared));
+    }
+    let sources: Vec<_> = spells.iter().filter_map(|(_, spell)| spell.as_ref().map(|s| s.source.clone())).collect();
+    if !sources.is_empty() {
+        lines.push("---".to_string());
+        lines.push(String::new());
+        lines.push(license_notice(&sources));
+    }
+    lines.join("\n")
+}
+
+/// Write a character's cards from the cached spell pages. Returns the file and the
+/// spells that had no cached page.
+pub fn write_spell_cards_in(dir: &Path, character: &Character) -> Result<(PathBuf, Vec<String>)> {
+    let spells: Vec<(String, Option<Spell>)> = card_spells(character).into_iter()
+        .map(|name| {
+            let spell = crate::content::cached_spell(&name);
+            (spell.as_ref().map_or(name, |s| s.name.clone()), spell)
+        })
+        .collect();
+    let missing = spells.iter().filter(|(_, spell)| spell.is_none()).map(|(name, _)| name.clone()).collect();
+    fs::create_dir_all(dir).context("Failed to create spell cards folder")?;
+    let path = dir.join(format!("{}.md", slug(&character.name)));
+    fs::write(&path, spell_cards_markdown(character, &spells)).context(format!("Failed to write {}", path.display()))?;
+    log::info!("Wrote {} spell cards for {} to {}", spells.len(), character.name, path.display());
+    Ok((path, missing))
+}
@@ -0,0 +1,137 @@
+use crate::character::Character;
+use crate::error_handling::{AppError, Result};
+use serde::{Deserialize, Serialize};
+
+/// Ordinary spell slots for levels 1-9, and how many of each are spent. Warlock
+/// pact slots are kept apart in `PactSlots`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpellSlots {
+    pub max: [u32; 9],
+    pub expended: [u32; 9],
+}
+
+impl SpellSlots {
+    pub fn remaining(&self, level: u32) -> u32 {
+        let i = (level as usize).saturating_sub(1);
+        self.max.get(i).copied().unwrap_or(0).saturating_sub(self.expended.get(i).copied().unwrap_or(0))
+    }
+
+    /// "Spell slots: 1st ●●○○ 2nd ●●● (long rest)"
+    pub fn describe(&self) -> String {
+        let levels: Vec<String> = (1..=9u32)
+            .filter(|&level| self.max[level as usize - 1] > 0)
+            .map(|level| {
+                let left = self.remaining(level);
+                format!("{} {}{}", ordinal(level), "●".repeat(left as usize),
+                    "○".repeat(self.max[level as usize - 1].saturating_sub(left) as usize))
+            })
+            .collect();
+        format!("Spell slots: {} (long rest)", levels.join(" "))
+    }
+}
+
+/// "1st", "2nd", "3rd", "4th"...
+pub fn ordinal(level: u32) -> String {
+    let suffix = match level {
+        1 => "st",
+        2 => "nd",
+        3 => "rd",
+        _ => "th",
+    };
+    format!("{}{}", level, suffix)
+}
+
+/// Slots by caster level for a full caster; half casters use the row for half their level
+const FULL_CASTER: [[u32; 9]; 20] = [
+    [2, 0, 0, 0, 0, 0, 0, 0, 0],
+    [3, 0, 0, 0, 0, 0, 0, 0, 0],
+    [4, 2, 0, 0, 0, 0, 0, 0, 0],
+    [4, 3, 0, 0, 0, 0, 0, 0, 0],
+    [4, 3, 2, 0, 0, 0, 0, 0, 0],
+    [4, 3, 3, 0, 0, 0, 0, 0, 0],
+    [4, 3, 3, 1, 0, 0, 0, 0, 0],
+    [4, 3, 3, 2, 0, 0, 0, 0, 0],
+    [4, 3, 3, 3, 1, 0, 0, 0, 0],
+    [4, 3, 3, 3, 2, 0, 0, 0, 0],
+    [4, 3, 3, 3, 2, 1, 0, 0, 0],
+    [4, 3, 3, 3, 2, 1, 0, 0, 0],
+    [4, 3, 3, 3, 2, 1, 1, 0, 0],
+    [4, 3, 3, 3, 2, 1, 1, 0, 0],
+    [4, 3, 3, 3, 2, 1, 1, 1, 0],
+    [4, 3, 3, 3, 2, 1, 1, 1, 0],
+    [4, 3, 3, 3, 2, 1, 1, 1, 1],
+    [4, 3, 3, 3, 3, 1, 1, 1, 1],
+    [4, 3, 3, 3, 3, 2, 1, 1, 1],
+    [4, 3, 3, 3, 3, 2, 2, 1, 1],
+];
+
+/// The slots a class has at a level. Paladins and rangers get theirs from level 2,
+/// artificers from level 1; classes without spellcasting (and warlocks) have none.
+pub fn slots_for(class: &str, level: u32) -> [u32; 9] {
+    let level = level.clamp(1, 20);
+    let caster_level = match class.trim().to_lowercase().as_str() {
+        "bard" | "cleric" | "druid" | "sorcerer" | "wizard" => level,
+        "paladin" | "ranger" if level >= 2 => level.div_ceil(2),
+        "artificer" => level.div_ceil(2),
+        _ => 0,
+    };
+    match caster_level {
+        0 => [0; 9],
+        n => FULL_CASTER[n as usize - 1],
+    }
+}
+
+/// Keep a caster's slots in step with their class and level, carrying over slots
+/// already spent. Anyone without spellcasting has none.
+pub fn refresh_spell_slots(character: &mut Character) {
+    let max = slots_for(character.class.as_deref().unwrap_or_default(), character.level.unwrap_or(1) as u32);
+    if max.iter().all(|&n| n == 0) {
+        character.spell_slots = None;
+        return;
+    }
+    let spent = character.spell_slots.map(|s| s.expended).unwrap_or_default();
+    let mut expended = [0; 9];
+    for (i, slot) in expended.iter_mut().enumerate() {
+        *slot = spent[i].min(max[i]);
+    }
+    character.spell_slots = Some(SpellSlots { max, expended });
+}
+
+/// Spend a slot for a spell of `spell_level`: the `slot_level` asked for, or else the
+/// lowest one left that can hold the spell. Returns the level spent and the slots left.
+pub fn spend_spell_slot(character: &mut Character, spell_level: u32, slot_level: Option<u32>) -> Result<(u32, SpellSlots)> {
+    refresh_spell_slots(character);
+    let name = character.name.clone();
+    let slots = character.spell_slots.as_mut()
+        .ok_or_else(|| AppError::ValidationError(format!("{} has no spell slots", name)))?;
+    let level = match slot_level {
+        Some(level) if !(1..=9).contains(&level) => {
+            return Err(AppError::ValidationError(format!("'{}' isn't a slot level (1-9)", level)));
+        }
+        Some(level) if level < spell_level => {
+            return Err(AppError::ValidationError(format!("A {}-level spell can't be cast with a {}-level slot",
+                ordinal(spell_level), ordinal(level))));
+        }
+        Some(level) if slots.remaining(level) == 0 => {
+            return Err(AppError::ValidationError(format!("{} has no {}-level slots left until a long rest", name, ordinal(level))));
+        }
+        Some(level) => level,
+        None => (spell_level.max(1)..=9).find(|&level| slots.remaining(level) > 0)
+            .ok_or_else(|| AppError::ValidationError(format!("{} has no slots of {} level or higher left until a long rest",
+                name, ordinal(spell_level.max(1)))))?,
+    };
+    slots.expended[level as usize - 1] += 1;
+    Ok((level, *slots))
+}
+
+/// Spell slots come back on a long rest; returns true when some were spent
+pub fn rest_spell_slots(character: &mut Character) -> bool {
+    refresh_spell_slots(character);
+    match character.spell_slots.as_mut() {
+        Some(slots) if slots.expended.iter().any(|&n| n > 0) => {
+            slots.expended = [0; 9];
+            true
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,327 @@
+use crate::error_handling::{AppError, Result, ResultExt};
+use crate::search::{SearchCategory, WikiPageContent};
+use serde_json::Value;
+use std::{fs, path::Path, sync::atomic::{AtomicBool, Ordering}, sync::mpsc::{self, Receiver}};
+
+/// The SRD 5.1 dataset is downloaded here for lookups without the network
+pub const SRD_DIR: &str = "data/srd";
+/// Pages read from the dataset get URLs under this prefix
+pub const SRD_URL_PREFIX: &str = "srd:";
+/// The 5e-bits/5e-database export of the SRD 5.1, shared under CC BY 4.0
+const DATASET_URL: &str = "https://raw.githubusercontent.com/5e-bits/5e-database/main/src/2014";
+/// Each bundled category with the dataset file it's downloaded from and saved as
+const FILES: [(SearchCategory, &str, &str); 3] = [
+    (SearchCategory::Spells, "5e-SRD-Spells.json", "spells.json"),
+    (SearchCategory::Monsters, "5e-SRD-Monsters.json", "monsters.json"),
+    (SearchCategory::Equipment, "5e-SRD-Equipment.json", "equipment.json"),
+];
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Offline mode is chosen with --offline or DND_TOOLS_OFFLINE
+pub fn offline_requested(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "--offline")
+        || std::env::var("DND_TOOLS_OFFLINE").is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// In offline mode searches go straight to the cache and the SRD, never the wiki
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+fn file_for(category: SearchCategory) -> Option<&'static str> {
+    FILES.iter().find(|(c, _, _)| *c == category).map(|(_, _, file)| *file)
+}
+
+/// Whether the category has SRD data to fall back on at all
+pub fn covers(category: SearchCategory) -> bool {
+    file_for(category).is_some()
+}
+
+/// Whether every dataset file has been downloaded
+pub fn is_installed(dir: &Path) -> bool {
+    FILES.iter().all(|(_, _, file)| dir.join(file).exists())
+}
+
+/// "Fire Bolt" and "fire-bolt" both find the "fire-bolt" entry
+fn slug(query: &str) -> String {
+    query.trim().to_lowercase().replace([' ', '_'], "-")
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-')
+        .collect()
+}
+
+fn text(value: &Value) -> &str {
+    value.as_str().unwrap_or_default()
+}
+
+/// Paragraphs stored as an array of strings
+fn paragraphs(value: &Value) -> Vec<String> {
+    value.as_array().into_iter().flatten().map(|p| text(p).to_string()).collect()
+}
+
+fn names(value: &Value) -> Vec<String> {
+    value.as_array().into_iter().flatten().map(|v| text(&v["name"]).to_string()).collect()
+}
+
+fn ordinal(level: u64) -> String {
+    match level {
+        1 => "1st".to_string(),
+        2 => "2nd".to_string(),
+        3 => "3rd".to_string(),
+        n => format!("{}th", n),
+    }
+}
+
+/// A spell entry written out the way the wiki lays out a spell page
+fn spell_text(entry: &Value) -> String {
+    let school = text(&entry["school"]["name"]);
+    let ritual = if entry["ritual"].as_bool() == Some(true) { " (ritual)" } else { "" };
+    let mut lines = vec![match entry["level"].as_u64().unwrap_or_default() {
+        0 => format!("{} cantrip{}", school, ritual),
+        level => format!("{}-level {}{}", ordinal(level), school.to_lowercase(), ritual),
+    }];
+    let mut components = paragraphs(&entry["components"]).join(", ");
+    if let Some(material) = entry["material"].as_str() {
+        components.push_str(&format!(" ({})", material.trim_end_matches('.')));
+    }
+    let duration = text(&entry["duration"]);
+    let duration = if entry["concentration"].as_bool() == Some(true) && !duration.starts_with("Concentration") {
+        format!("Concentration, {}", duration.to_lowercase())
+    } else {
+        duration.to_string()
+    };
+    lines.push(format!("Casting Time: {}", text(&entry["casting_time"])));
+    lines.push(format!("Range: {}", text(&entry["range"])));
+    lines.push(format!("Components: {}", components));
+    lines.push(format!("Duration: {}", duration));
+    lines.extend(paragraphs(&entry["desc"]));
+    let higher = paragraphs(&entry["higher_level"]);
+    if !higher.is_empty() {
+        lines.push(format!("At Higher Levels. {}", higher.join(" ")));
+    }
+    let classes = names(&entry["classes"]);
+    if !classes.is_empty() {
+        lines.push(format!("Spell Lists. {}", classes.join(", ")));
+    }
+    lines.join("\n")
+}
+
+/// "1/4" for 0.25, as stat blocks print it
+fn challenge(cr: f64) -> String {
+    match (cr * 8.0).round() as u32 {
+        1 => "1/8".to_string(),
+        2 => "1/4".to_string(),
+        4 => "1/2".to_string(),
+        _ => format!("{}", cr),
+    }
+}
+
+fn modifier(score: i64) -> i64 {
+    (score - 10).div_euclid(2)
+}
+
+/// A monster entry written out as a stat block the wiki parsers can read
+fn monster_text(entry: &Value) -> String {
+    let name = text(&entry["name"]);
+    let subtype = entry["subtype"].as_str().map(|s| format!(" ({})", s)).unwrap_or_default();
+    let mut lines = vec![format!("{} {}{}, {}", text(&entry["size"]), text(&entry["type"]), subtype, text(&entry["alignment"]))];
+    // Older exports give a bare number, newer ones a list of {type, value}
+    let ac = match &entry["armor_class"] {
+        Value::Array(acs) => acs.first().map(|ac| match ac["type"].as_str() {
+            Some("dex") | None => ac["value"].to_string(),
+            Some(kind) => format!("{} ({} armor)", ac["value"], kind),
+        }),
+        ac => ac.as_i64().map(|ac| ac.to_string()),
+    };
+    lines.push(format!("Armor Class {}", ac.unwrap_or_default()));
+    let roll = entry["hit_points_roll"].as_str().or(entry["hit_dice"].as_str()).unwrap_or_default();
+    lines.push(format!("Hit Points {} ({})", entry["hit_points"], roll.replace('+', " + ").replace('-', " - ")));
+    // Walking speed leads, as in a printed stat block
+    let mut speeds: Vec<(&String, &Value)> = entry["speed"].as_object().into_iter().flatten().collect();
+    speeds.sort_by_key(|(kind, _)| kind.as_str() != "walk");
+    let speed: Vec<String> = speeds.into_iter()
+        .map(|(kind, value)| match kind.as_str() {
+            "walk" => text(value).to_string(),
+            "hover" => "(hover)".to_string(),
+            kind => format!("{} {}", kind, text(value)),
+        })
+        .collect();
+    lines.push(format!("Speed {}", speed.join(", ")));
+    let labels = [("STR", "strength"), ("DEX", "dexterity"), ("CON", "constitution"), ("INT", "intelligence"), ("WIS", "wisdom"), ("CHA", "charisma")];
+    lines.push(labels.iter()
+        .map(|(label, key)| {
+            let score = entry[*key].as_i64().unwrap_or(10);
+            format!("{} {} ({:+})", label, score, modifier(score))
+        })
+        .collect::<Vec<_>>()
+        .join(" | "));
+    for (label, key) in [("Damage Vulnerabilities", "damage_vulnerabilities"), ("Damage Resistances", "damage_resistances"), ("Damage Immunities", "damage_immunities")] {
+        let types = paragraphs(&entry[key]);
+        if !types.is_empty() {
+            lines.push(format!("{} {}", label, types.join(", ")));
+        }
+    }
+    let conditions = names(&entry["condition_immunities"]);
+    if !conditions.is_empty() {
+        lines.push(format!("Condition Immunities {}", conditions.join(", ")));
+    }
+    let senses: Vec<String> = entry["senses"].as_object().into_iter().flatten()
+        .map(|(sense, value)| format!("{} {}", sense.replace('_', " "), value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string())))
+        .collect();
+    if !senses.is_empty() {
+        lines.push(format!("Senses {}", senses.join(", ")));
+    }
+    lines.push(format!("Languages {}", text(&entry["languages"])));
+    lines.push(format!("Challenge {} ({} XP)", challenge(entry["challenge_rating"].as_f64().unwrap_or_default()), entry["xp"]));
+    let abilities = |key: &str| entry[key].as_array().into_iter().flatten()
+        .map(|a| format!("{}. {}", text(&a["name"]), text(&a["desc"])))
+        .collect::<Vec<_>>();
+    lines.extend(abilities("special_abilities"));
+    lines.push("Actions".to_string());
+    lines.extend(abilities("actions"));
+    let legendary = abilities("legendary_actions");
+    if !legendary.is_empty() {
+        lines.push("Legendary Actions".to_string());
+        lines.push(format!("The {} can take 3 legendary actions, choosing from the options below.", name.to_lowercase()));
+        lines.extend(legendary);
+    }
+    lines.join("\n")
+}
+
+/// A piece of equipment with its cost, weight and, for weapons and armor, its stats
+fn equipment_text(entry: &Value) -> String {
+    let category = text(&entry["equipment_category"]["name"]);
+    let mut lines = vec![entry["category_range"].as_str()
+        .or(entry["armor_category"].as_str().map(|_| "Armor"))
+        .map(|detail| format!("{} ({})", category, detail))
+        .unwrap_or_else(|| category.to_string())];
+    if let Some(quantity) = entry["cost"]["quantity"].as_u64() {
+        lines.push(format!("Cost: {} {}", quantity, text(&entry["cost"]["unit"])));
+    }
+    if let Some(weight) = entry["weight"].as_f64() {
+        lines.push(format!("Weight: {} lb.", weight));
+    }
+    if let Some(dice) = entry["damage"]["damage_dice"].as_str() {
+        lines.push(format!("Damage: {} {}", dice, text(&entry["damage"]["damage_type"]["name"]).to_lowercase()));
+    }
+    if let Some(base) = entry["armor_class"]["base"].as_i64() {
+        let dex = match (entry["armor_class"]["dex_bonus"].as_bool(), entry["armor_class"]["max_bonus"].as_i64()) {
+            (Some(true), Some(max)) => format!(" + Dex modifier (max {})", max),
+            (Some(true), None) => " + Dex modifier".to_string(),
+            _ => String::new(),
+        };
+        lines.push(format!("Armor Class: {}{}", base, dex));
+    }
+    let properties = names(&entry["properties"]);
+    if !properties.is_empty() {
+        lines.push(format!("Properties: {}", properties.join(", ")));
+    }
+    lines.extend(paragraphs(&entry["desc"]));
+    lines.join("\n")
+}
+
+/// An entry from the dataset as a page, so it reads like one fetched from the wiki
+fn to_page(category: SearchCategory, entry: &Value) -> WikiPageContent {
+    let (content_type, content) = match category {
+        SearchCategory::Spells => ("spell", spell_text(entry)),
+        SearchCategory::Monsters => ("monster", monster_text(entry)),
+        _ => ("equipment", equipment_text(entry)),
+    };
+    let index = text(&entry["index"]).to_string();
+    WikiPageContent {
+        url: format!("{}{}:{}", SRD_URL_PREFIX, content_type, index),
+        index,
+        name: text(&entry["name"]).to_string(),
+        content,
+        content_type: content_type.to_string(),
+    }
+}
+
+/// Look an entry up in the downloaded dataset by name or index
+pub fn lookup_in(dir: &Path, query: &str, category: SearchCategory) -> Result<WikiPageContent> {
+    let file = file_for(category)
+        .ok_or_else(|| AppError::NotFound(format!("{} in the SRD data", category.as_str())))?;
+    let path = dir.join(file);
+    let json = fs::read_to_string(&path)
+        .map_err(|_| AppError::NotFound(format!("SRD data in {} (run 'dnd_tools srd download')", dir.display())))?;
+    let entries: Vec<Value> = serde_json::from_str(&json)
+        .map_err(|e| AppError::ParseError(format!("{}: {}", path.display(), e)))?;
+    let wanted = slug(query);
+    entries.iter()
+        .find(|entry| text(&entry["index"]) == wanted || slug(text(&entry["name"])) == wanted)
+        .map(|entry| to_page(category, entry))
+        .ok_or_else(|| AppError::NotFound(format!("'{}' in the SRD {}", query, category.as_str())))
+}
+
+/// Download every dataset file into `dir`, returning how many entries it holds
+pub fn download_to(dir: &Path) -> Result<usize> {
+    let files = crate::runtime::block_on(async {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(60))
+            .user_agent(format!("dnd_tools/{}", env!("CARGO_PKG_VERSION")))
+            .build()
+            .context("Failed to create HTTP client")?;
+        let mut files = Vec::new();
+        for (_, remote, local) in FILES {
+            let url = format!("{}/{}", DATASET_URL, remote);
+            log::debug!("GET {}", url);
+            let response = client.get(&url).send().await.context("SRD download failed")?;
+            if !response.status().is_success() {
+                return Err(AppError::NetworkError(format!("{} returned {}", url, response.status())));
+            }
+            files.push((local, response.text().await.context("Failed to read SRD data")?));
+        }
+        Ok(files)
+    })??;
+    // Check every file before writing any, so a bad download can't replace good data
+    let mut entries = 0;
+    for (local, json) in &files {
+        let parsed: Vec<Value> = serde_json::from_str(json)
+            .map_err(|e| AppError::ParseError(format!("Downloaded {} is not an SRD list: {}", local, e)))?;
+        entries += parsed.len();
+    }
+    fs::create_dir_all(dir).context("Failed to create SRD data folder")?;
+    for (local, json) in &files {
+        fs::write(dir.join(local), json).context(format!("Failed to write {}", dir.join(local).display()))?;
+    }
+    log::info!("Downloaded {} SRD entries to {}", entries, dir.display());
+    Ok(entries)
+}
+
+/// Fetch the dataset in the background on first run; the receiver yields the entry count
+pub fn spawn_first_run_download() -> Receiver<usize> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || match download_to(Path::new(SRD_DIR)) {
+        Ok(entries) => {
+            let _ = tx.send(entries);
+        }
+        Err(e) => log::warn!("SRD download skipped: {}", e),
+    });
+    rx
+}
+
+/// `dnd_tools srd` reports whether the offline data is there, and `dnd_tools srd download`
+/// fetches (or refreshes) it
+pub fn run_srd_command(args: &[String]) {
+    let dir = Path::new(SRD_DIR);
+    match args.first().map(String::as_str) {
+        Some("download") => {
+            println!("📥 Downloading the SRD 5.1 spells, monsters and equipment...");
+            match download_to(dir) {
+                Ok(entries) => println!("✅ {} entries saved to {}", entries, dir.display()),
+                Err(e) => eprintln!("❌ {}", e),
+            }
+        }
+        Some(other) => eprintln!("Unknown srd command '{}'. Usage: dnd_tools srd [download]", other),
+        None if is_installed(dir) => {
+            println!("📚 SRD 5.1 data is in {}; searches use it when the wiki can't be reached or with --offline", dir.display());
+            println!("   {}", crate::content::Source::Srd.attribution());
+        }
+        None => println!("No SRD data in {} yet. Run 'dnd_tools srd download' to fetch it.", dir.display()),
+    }
+}
@@ -0,0 +1,152 @@
+use crate::ammo::{ammo_counter_mut, ammo_for_weapon, set_ammo};
+use crate::character::Character;
+use crate::error_handling::{AppError, Result};
+use rand::Rng;
+
+/// (how many, item). Stacks of ammunition and thrown weapons are counted as ammo too.
+type Item = (u32, &'static str);
+
+/// A class's standard starting equipment, as whole packages, and the gold it can
+/// roll for instead
+#[derive(Debug, PartialEq, Eq)]
+pub struct ClassKit {
+    pub class: &'static str,
+    /// `packages[0]` takes the first option of each choice, `packages[1]` the others
+    pub packages: &'static [&'static [Item]],
+    /// Roll this many d4 for starting gold...
+    pub gold_dice: u32,
+    /// ...and multiply by this
+    pub gold_multiplier: u32,
+}
+
+/// Ammunition that isn't a weapon in its own right, so it's only tracked as ammo
+const AMMUNITION: &[&str] = &["arrows", "bolts", "bullets", "needles"];
+
+const KITS: &[ClassKit] = &[
+    ClassKit { class: "Artificer", gold_dice: 5, gold_multiplier: 10, packages: &[
+        &[(1, "Handaxe"), (1, "Light hammer"), (1, "Light crossbow"), (20, "Bolts"), (1, "Studded leather armor"), (1, "Thieves' tools"), (1, "Dungeoneer's pack")],
+        &[(1, "Mace"), (1, "Dagger"), (1, "Light crossbow"), (20, "Bolts"), (1, "Scale mail"), (1, "Thieves' tools"), (1, "Dungeoneer's pack")],
+    ] },
+    ClassKit { class: "Barbarian", gold_dice: 2, gold_multiplier: 10, packages: &[
+        &[(1, "Greataxe"), (2, "Handaxes"), (1, "Explorer's pack"), (4, "Javelins")],
+        &[(1, "Greatsword"), (1, "Spear"), (1, "Explorer's pack"), (4, "Javelins")],
+    ] },
+    ClassKit { class: "Bard", gold_dice: 5, gold_multiplier: 10, packages: &[
+        &[(1, "Rapier"), (1, "Diplomat's pack"), (1, "Lute"), (1, "Leather armor"), (1, "Dagger")],
+        &[(1, "Longsword"), (1, "Entertainer's pack"), (1, "Flute"), (1, "Leather armor"), (1, "Dagger")],
+    ] },
+    ClassKit { class: "Cleric", gold_dice: 5, gold_multiplier: 10, packages: &[
+        &[(1, "Mace"), (1, "Scale mail"), (1, "Light crossbow"), (20, "Bolts"), (1, "Priest's pack"), (1, "Shield"), (1, "Holy symbol")],
+        &[(1, "Warhammer"), (1, "Chain mail"), (1, "Spear"), (1, "Explorer's pack"), (1, "Shield"), (1, "Holy symbol")],
+    ] },
+    ClassKit { class: "Druid", gold_dice: 2, gold_multiplier: 10, packages: &[
+        &[(1, "Wooden shield"), (1, "Scimitar"), (1, "Leather armor"), (1, "Explorer's pack"), (1, "Druidic focus")],
+        &[(1, "Quarterstaff"), (1, "Sickle"), (1, "Leather armor"), (1, "Explorer's pack"), (1, "Druidic focus")],
+    ] },
+    ClassKit { class: "Fighter", gold_dice: 5, gold_multiplier: 10, packages: &[
+        &[(1, "Chain mail"), (1, "Longsword"), (1, "Shield"), (1, "Light crossbow"), (20, "Bolts"), (1, "Dungeoneer's pack")],
+        &[(1, "Leather armor"), (1, "Longbow"), (20, "Arrows"), (2, "Shortswords"), (2, "Handaxes"), (1, "Explorer's pack")],
+    ] },
+    ClassKit { class: "Monk", gold_dice: 5, gold_multiplier: 1, packages: &[
+        &[(1, "Shortsword"), (1, "Dungeoneer's pack"), (10, "Darts")],
+        &[(1, "Quarterstaff"), (1, "Explorer's pack"), (10, "Darts")],
+    ] },
+    ClassKit { class: "Paladin", gold_dice: 5, gold_multiplier: 10, packages: &[
+        &[(1, "Longsword"), (1, "Shield"), (5, "Javelins"), (1, "Priest's pack"), (1, "Chain mail"), (1, "Holy symbol")],
+        &[(1, "Greatsword"), (1, "Warhammer"), (1, "Mace"), (1, "Explorer's pack"), (1, "Chain mail"), (1, "Holy symbol")],
+    ] },
+    ClassKit { class: "Ranger", gold_dice: 5, gold_multiplier: 10, packages: &[
+        &[(1, "Scale mail"), (2, "Shortswords"), (1, "Dungeoneer's pack"), (1, "Longbow"), (1, "Quiver"), (20, "Arrows")],
+        &[(1, "Leather armor"), (2, "Handaxes"), (1, "Explorer's pack"), (1, "Longbow"), (1, "Quiver"), (20, "Arrows")],
+    ] },
+    ClassKit { class: "Rogue", gold_dice: 4, gold_multiplier: 10, packages: &[
+        &[(1, "Rapier"), (1, "Shortbow"), (1, "Quiver"), (20, "Arrows"), (1, "Burglar's pack"), (1, "Leather armor"), (2, "Daggers"), (1, "Thieves' tools")],
+        &[(2, "Shortswords"), (1, "Dungeoneer's pack"), (1, "Leather armor"), (2, "Daggers"), (1, "Thieves' tools")],
+    ] },
+    ClassKit { class: "Sorcerer", gold_dice: 3, gold_multiplier: 10, packages: &[
+        &[(1, "Light crossbow"), (20, "Bolts"), (1, "Component pouch"), (1, "Dungeoneer's pack"), (2, "Daggers")],
+        &[(1, "Quarterstaff"), (1, "Arcane focus"), (1, "Explorer's pack"), (2, "Daggers")],
+    ] },
+    ClassKit { class: "Warlock", gold_dice: 4, gold_multiplier: 10, packages: &[
+        &[(1, "Light crossbow"), (20, "Bolts"), (1, "Component pouch"), (1, "Scholar's pack"), (1, "Leather armor"), (1, "Quarterstaff"), (2, "Daggers")],
+        &[(1, "Spear"), (1, "Arcane focus"), (1, "Dungeoneer's pack"), (1, "Leather armor"), (1, "Sickle"), (2, "Daggers")],
+    ] },
+    ClassKit { class: "Wizard", gold_dice: 4, gold_multiplier: 10, packages: &[
+        &[(1, "Quarterstaff"), (1, "Component pouch"), (1, "Scholar's pack"), (1, "Spellbook")],
+        &[(1, "Dagger"), (1, "Arcane focus"), (1, "Explorer's pack"), (1, "Spellbook")],
+    ] },
+];
+
+/// The starting equipment for a class, by name in any case
+pub fn class_kit(class: &str) -> Option<&'static ClassKit> {
+    KITS.iter().find(|kit| kit.class.eq_ignore_ascii_case(class.trim()))
+}
+
+fn item_text(&(count, item): &Item) -> String {
+    if count == 1 { item.to_string() } else { format!("{} {}", count, item) }
+}
+
+/// "a", "b"... for the packages
+fn package_letter(index: usize) -> char {
+    (b'a' + index as u8) as char
+}
+
+impl ClassKit {
+    /// "5d4 × 10 gp", or "5d4 gp" for monks
+    pub fn gold_formula(&self) -> String {
+        match self.gold_multiplier {
+            1 => format!("{}d4 gp", self.gold_dice),
+            multiplier => format!("{}d4 × {} gp", self.gold_dice, multiplier),
+        }
+    }
+
+    pub fn roll_gold<R: Rng + ?Sized>(&self, rng: &mut R) -> u32 {
+        (0..self.gold_dice).map(|_| rng.random_range(1..=4)).sum::<u32>() * self.gold_multiplier
+    }
+
+    /// The packages to pick from, lettered, and the gold alternative
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = vec![format!("🎒 {} starting equipment:", self.class)];
+        for (i, package) in self.packages.iter().enumerate() {
+            lines.push(format!("  {}) {}", package_letter(i), package.iter().map(item_text).collect::<Vec<_>>().join(", ")));
+        }
+        lines.push(format!("  gold) {} instead, to buy your own", self.gold_formula()));
+        lines
+    }
+
+    /// Equip a character with a package by letter, or with rolled gold for "gold".
+    /// Returns what was added.
+    pub fn equip<R: Rng + ?Sized>(&self, character: &mut Character, choice: &str, rng: &mut R) -> Result<Vec<String>> {
+        let choice = choice.trim().to_lowercase();
+        if choice == "gold" {
+            let gold = self.roll_gold(rng);
+            character.inventory.push(format!("{} gp", gold));
+            return Ok(vec![format!("💰 {} rolls {} for starting gold: {} gp", character.name, self.gold_formula(), gold)]);
+        }
+        let package = self.packages.iter().enumerate()
+            .find(|(i, _)| choice.len() == 1 && choice.starts_with(package_letter(*i)))
+            .map(|(_, package)| *package)
+            .ok_or_else(|| AppError::ValidationError(format!(
+                "Choose package a-{} or 'gold' for the {}", package_letter(self.packages.len() - 1), self.class)))?;
+
+        let mut ammo = Vec::new();
+        for &(count, item) in package {
+            let kind = item.to_lowercase();
+            if ammo_for_weapon(&kind).is_some_and(|k| k == kind) {
+                let carried = ammo_counter_mut(character, &kind).map_or(0, |counter| counter.count);
+                set_ammo(character, &kind, carried + count);
+                ammo.push(item_text(&(count, item)));
+                if AMMUNITION.contains(&kind.as_str()) {
+                    continue;
+                }
+            }
+            character.inventory.push(item_text(&(count, item)));
+        }
+        let mut lines = vec![format!("🎒 {} takes the {} package {}: {}", character.name, self.class,
+            choice, package.iter().map(item_text).collect::<Vec<_>>().join(", "))];
+        if !ammo.is_empty() {
+            lines.push(format!("  Ammunition tracked: {}", ammo.join(", ")));
+        }
+        Ok(lines)
+    }
+}
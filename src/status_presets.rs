@@ -0,0 +1,150 @@
+use crate::combat::{RollModifiers, StatusEffect, TickPoint};
+use crate::error_handling::{AppError, Result, ResultExt};
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+/// Campaign status presets are added (or override built-in ones by name) from this file
+pub const STATUS_PRESETS_FILE: &str = "campaign/statuses.toml";
+
+/// A reusable status: `status add bob burning` gets everything here without retyping it
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct StatusPreset {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Rounds it lasts; none means until removed
+    #[serde(default)]
+    pub duration: Option<i32>,
+    /// "start" or "end" of the affected creature's turn
+    #[serde(default)]
+    pub ticks: Option<String>,
+    /// Damage each time it ticks, e.g. "1d6 fire"
+    #[serde(default)]
+    pub damage: Option<String>,
+    #[serde(default)]
+    pub attacks: Option<String>,
+    #[serde(default)]
+    pub attacked: Option<String>,
+    #[serde(default)]
+    pub saves: Option<String>,
+}
+
+impl StatusPreset {
+    pub fn to_status(&self) -> StatusEffect {
+        StatusEffect {
+            description: self.description.clone(),
+            ticks_at: self.ticks.as_deref().and_then(TickPoint::parse).unwrap_or_default(),
+            damage: self.damage.clone(),
+            modifiers: RollModifiers { attacks: self.attacks.clone(), attacked: self.attacked.clone(), saves: self.saves.clone() },
+            ..StatusEffect::new(self.name.clone(), self.duration)
+        }
+    }
+
+    /// "Burning - 1d4 fire at the start of each turn; until removed"
+    pub fn summary(&self) -> String {
+        let status = self.to_status();
+        let mut parts: Vec<String> = status.damage.iter()
+            .map(|damage| format!("{} at the {} of each turn", damage, status.ticks_at.name()))
+            .collect();
+        for (label, modifier) in [("attacks", &self.attacks), ("attacked", &self.attacked), ("saves", &self.saves)] {
+            parts.extend(modifier.as_ref().map(|m| format!("{} {}", label, m)));
+        }
+        parts.push(match self.duration {
+            Some(rounds) => format!("{} rounds", rounds),
+            None => "until removed".to_string(),
+        });
+        format!("{} - {}", self.name, parts.join("; "))
+    }
+
+    fn check(&self) -> Result<()> {
+        if let Some(ticks) = &self.ticks
+            && TickPoint::parse(ticks).is_none() {
+            return Err(AppError::ValidationError(format!("{}: ticks must be 'start' or 'end', not '{}'", self.name, ticks)));
+        }
+        if let Some(damage) = &self.damage {
+            let words: Vec<&str> = damage.split_whitespace().collect();
+            let (dice, _) = crate::combat::split_damage_type(&words);
+            crate::dice::roll_expression(&dice.join(""))
+                .map_err(|e| AppError::ValidationError(format!("{}: damage '{}': {}", self.name, damage, e)))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct StatusFile {
+    statuses: Vec<StatusPreset>,
+}
+
+/// (name, description, duration, ticks, damage, attacks, attacked, saves)
+type PresetRow = (&'static str, &'static str, Option<i32>, &'static str, Option<&'static str>, Option<&'static str>, Option<&'static str>, Option<&'static str>);
+
+/// Common effects that aren't conditions, so they work before any campaign file exists
+const BUILTIN: &[PresetRow] = &[
+    ("Blessed", "Bless: add 1d4 to attack rolls and saving throws", Some(10), "end", None, Some("+1d4"), None, Some("+1d4")),
+    ("Baned", "Bane: subtract 1d4 from attack rolls and saving throws", Some(10), "end", None, Some("-1d4"), None, Some("-1d4")),
+    ("Burning", "Alchemist's fire: an action and a DC 10 Dexterity check puts the flames out", None, "start", Some("1d4 fire"), None, None, None),
+    ("Dodging", "Dodge action: attacks against it have disadvantage until its next turn", Some(1), "start", None, None, Some("dis"), None),
+];
+
+pub fn builtin_presets() -> Vec<StatusPreset> {
+    BUILTIN.iter().map(|&(name, description, duration, ticks, damage, attacks, attacked, saves)| StatusPreset {
+        name: name.to_string(),
+        description: Some(description.to_string()),
+        duration,
+        ticks: Some(ticks.to_string()),
+        damage: damage.map(str::to_string),
+        attacks: attacks.map(str::to_string),
+        attacked: attacked.map(str::to_string),
+        saves: saves.map(str::to_string),
+    }).collect()
+}
+
+/// Built-in presets plus any from the campaign's statuses file
+#[derive(Debug, Clone)]
+pub struct StatusLibrary {
+    pub presets: Vec<StatusPreset>,
+}
+
+impl Default for StatusLibrary {
+    fn default() -> Self {
+        StatusLibrary { presets: builtin_presets() }
+    }
+}
+
+impl StatusLibrary {
+    /// Parse `[[statuses]]` entries; a preset with a built-in's name replaces it
+    pub fn add_toml(&mut self, source: &str) -> Result<usize> {
+        let file: StatusFile = toml::from_str(source).map_err(|e| AppError::ParseError(e.to_string()))?;
+        let count = file.statuses.len();
+        for preset in file.statuses {
+            preset.check()?;
+            self.presets.retain(|p| !p.name.eq_ignore_ascii_case(&preset.name));
+            self.presets.push(preset);
+        }
+        Ok(count)
+    }
+
+    pub fn load_file(&mut self, path: &Path) -> Result<usize> {
+        if !path.exists() {
+            return Ok(0);
+        }
+        let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        self.add_toml(&contents).context(format!("Status preset file {}", path.display()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&StatusPreset> {
+        self.presets.iter().find(|p| p.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// The status to add for a name: the preset's, or a bare status if there's none
+    pub fn status(&self, name: &str) -> StatusEffect {
+        self.get(name).map(StatusPreset::to_status).unwrap_or_else(|| StatusEffect::new(name, None))
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        std::iter::once(format!("📚 Status presets (add your own in {}):", STATUS_PRESETS_FILE))
+            .chain(self.presets.iter().map(|p| format!("  {}", p.summary())))
+            .collect()
+    }
+}
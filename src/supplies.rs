@@ -0,0 +1,142 @@
+use crate::character::{AbilityScore, Character};
+use crate::error_handling::{AppError, Result, ResultExt};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::{fs, path::Path};
+
+/// The party's shared supplies are saved with the rest of the campaign
+pub const SUPPLIES_FILE: &str = "campaign/supplies.ron";
+/// A normal travel day; every hour beyond this is a forced march
+pub const TRAVEL_HOURS_PER_DAY: u32 = 8;
+/// At this level of exhaustion a creature dies
+pub const MAX_EXHAUSTION: u8 = 6;
+
+/// Rations (1 lb of food per person per day), water in gallons, and feed for mounts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Supplies {
+    pub rations: u32,
+    pub water: u32,
+    pub feed: u32,
+    pub mounts: u32,
+    /// Days each character has gone without food
+    #[serde(default)]
+    pub days_without_food: BTreeMap<String, u32>,
+    /// Gear held for the party, such as a fallen member's belongings
+    #[serde(default)]
+    pub stash: Vec<String>,
+}
+
+fn con_save<R: Rng + ?Sized>(character: &Character, dc: i32, rng: &mut R) -> bool {
+    let roll = rng.random_range(1..=20) + character.get_ability_modifier(AbilityScore::Constitution) as i32;
+    roll >= dc
+}
+
+fn exhaust(character: &mut Character, reason: &str, notices: &mut Vec<String>) {
+    character.exhaustion = (character.exhaustion + 1).min(MAX_EXHAUSTION);
+    if character.exhaustion >= MAX_EXHAUSTION {
+        notices.push(format!("💀 {} succumbs to exhaustion ({})", character.name, reason));
+    } else {
+        notices.push(format!("😫 {} gains a level of exhaustion from {} (now {})", character.name, reason, character.exhaustion));
+    }
+}
+
+impl Supplies {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        ron::de::from_str(&contents).map_err(|e| AppError::ParseError(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create campaign folder")?;
+        }
+        let serialized = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| AppError::ParseError(e.to_string()))?;
+        fs::write(path, serialized).context(format!("Failed to write {}", path.display()))
+    }
+
+    /// Add (or with a negative amount, remove) one kind of supply
+    pub fn adjust(&mut self, kind: &str, amount: i64) -> Result<u32> {
+        let stock = match kind.to_lowercase().trim_end_matches('s') {
+            "ration" | "food" => &mut self.rations,
+            "water" => &mut self.water,
+            "feed" => &mut self.feed,
+            "mount" => &mut self.mounts,
+            _ => return Err(AppError::ValidationError(format!("Unknown supply '{}' (rations, water, feed, mounts)", kind))),
+        };
+        *stock = (*stock as i64 + amount).max(0) as u32;
+        Ok(*stock)
+    }
+
+    /// How many days the stores last for a party of `party_size`
+    pub fn days_remaining(&self, party_size: u32, hot: bool) -> u32 {
+        let party_size = party_size.max(1);
+        let water_per_day = party_size * if hot { 2 } else { 1 };
+        let mut days = (self.rations / party_size).min(self.water / water_per_day);
+        if let Some(feed_days) = self.feed.checked_div(self.mounts) {
+            days = days.min(feed_days);
+        }
+        days
+    }
+
+    pub fn describe(&self, party_size: u32) -> String {
+        format!("🎒 Supplies: {} rations, {} gal water, {} feed for {} mount(s) - about {} day(s) for {}",
+            self.rations, self.water, self.feed, self.mounts, self.days_remaining(party_size, false), party_size)
+    }
+
+    /// One day on the road: everyone eats and drinks from the stores, mounts are fed,
+    /// and shortfalls and forced marching cost exhaustion.
+    pub fn travel_day<R: Rng + ?Sized>(&mut self, party: &mut [Character], hours: u32, hot: bool, rng: &mut R) -> Vec<String> {
+        let mut notices = Vec::new();
+        let water_needed = if hot { 2 } else { 1 };
+
+        for character in party.iter_mut().filter(|c| c.exhaustion < MAX_EXHAUSTION) {
+            // Food: a creature can go 3 + CON modifier days (at least 1) without it
+            if self.rations > 0 {
+                self.rations -= 1;
+                self.days_without_food.remove(&character.name);
+            } else {
+                let hungry = self.days_without_food.entry(character.name.clone()).or_insert(0);
+                *hungry += 1;
+                let limit = (3 + character.get_ability_modifier(AbilityScore::Constitution) as i32).max(1) as u32;
+                if *hungry > limit {
+                    exhaust(character, "starvation", &mut notices);
+                } else {
+                    notices.push(format!("🍖 {} goes hungry (day {} of {} without food)", character.name, hungry, limit));
+                }
+            }
+
+            // Water: half rations call for a DC 15 CON save, less than that is automatic exhaustion
+            let drunk = self.water.min(water_needed);
+            self.water -= drunk;
+            if drunk < water_needed {
+                if drunk * 2 >= water_needed && con_save(character, 15, rng) {
+                    notices.push(format!("💧 {} makes do with half their water", character.name));
+                } else {
+                    exhaust(character, "thirst", &mut notices);
+                }
+            }
+
+            // Forced march: a CON save at DC 10 + each hour past eight
+            for extra in 1..=hours.saturating_sub(TRAVEL_HOURS_PER_DAY) {
+                if character.exhaustion < MAX_EXHAUSTION && !con_save(character, 10 + extra as i32, rng) {
+                    exhaust(character, &format!("hour {} of the forced march", TRAVEL_HOURS_PER_DAY + extra), &mut notices);
+                }
+            }
+        }
+
+        if self.mounts > 0 {
+            if self.feed >= self.mounts {
+                self.feed -= self.mounts;
+            } else {
+                notices.push(format!("🐴 Only {} of {} mount(s) are fed; hungry mounts can't travel at a fast pace", self.feed, self.mounts));
+                self.feed = 0;
+            }
+        }
+        notices
+    }
+}
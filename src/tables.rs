@@ -0,0 +1,204 @@
+use crate::error_handling::{AppError, Result, ResultExt};
+use rand::Rng;
+use regex::Regex;
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// User-defined tables (.toml or .csv) live in the campaign directory
+pub const TABLES_DIR: &str = "campaign/tables";
+/// Stops tables that reference each other from recursing forever
+const MAX_NESTING: usize = 10;
+
+fn default_weight() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TableEntry {
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    pub text: String,
+}
+
+/// A weighted table. Entry text may contain `[[Other Table]]` (roll once),
+/// `[[Other Table x2]]` (roll twice) and inline dice like `{2d6}`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RandomTable {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub entries: Vec<TableEntry>,
+}
+
+impl RandomTable {
+    /// Parse a TOML table: `name = "..."` followed by `[[entries]]` with `weight` and `text`
+    pub fn from_toml(source: &str) -> Result<Self> {
+        toml::from_str(source).map_err(|e| AppError::ParseError(e.to_string()))
+    }
+
+    /// Parse "weight,text" lines. A line without a numeric weight counts as weight 1.
+    pub fn from_csv(name: &str, source: &str) -> Self {
+        let entries = source.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter(|line| !line.eq_ignore_ascii_case("weight,text"))
+            .map(|line| match line.split_once(',') {
+                Some((weight, text)) if weight.trim().parse::<u32>().is_ok() => TableEntry {
+                    weight: weight.trim().parse().unwrap_or(1),
+                    text: text.trim().trim_matches('"').to_string(),
+                },
+                _ => TableEntry { weight: 1, text: line.trim_matches('"').to_string() },
+            })
+            .collect();
+        RandomTable { name: name.to_string(), description: None, entries }
+    }
+
+    fn total_weight(&self) -> u32 {
+        self.entries.iter().map(|e| e.weight).sum()
+    }
+
+    /// The entry a die result lands on, reading weights as ranges in order:
+    /// weights 15, 30, 55 cover 1-15, 16-45 and 46-100
+    pub fn entry_for_roll(&self, roll: u32) -> Result<&TableEntry> {
+        let mut upper = 0;
+        self.entries.iter()
+            .find(|entry| {
+                upper += entry.weight;
+                roll >= 1 && roll <= upper
+            })
+            .ok_or_else(|| AppError::ValidationError(format!("{} is off table '{}', which covers 1-{}",
+                roll, self.name, self.total_weight())))
+    }
+
+    /// Pick one entry by weight, without expanding references
+    pub fn pick<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&TableEntry> {
+        let total = self.total_weight();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = rng.random_range(0..total);
+        self.entries.iter().find(|entry| {
+            if roll < entry.weight {
+                true
+            } else {
+                roll -= entry.weight;
+                false
+            }
+        })
+    }
+}
+
+/// All tables available to the `table` command, looked up case-insensitively
+#[derive(Debug, Clone, Default)]
+pub struct TableRegistry {
+    tables: BTreeMap<String, RandomTable>,
+}
+
+impl TableRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a table
+    pub fn add(&mut self, table: RandomTable) {
+        self.tables.insert(table.name.to_lowercase(), table);
+    }
+
+    /// Hyphens stand in for spaces, so `reaction-table` finds "Reaction Table"
+    pub fn get(&self, name: &str) -> Option<&RandomTable> {
+        let name = name.trim().to_lowercase();
+        self.tables.get(&name).or_else(|| self.tables.get(&name.replace('-', " ")))
+    }
+
+    pub fn tables(&self) -> impl Iterator<Item = &RandomTable> {
+        self.tables.values()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Load every .toml / .csv file in a directory, returning the errors for any that failed
+    pub fn load_dir(&mut self, dir: &Path) -> Vec<AppError> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<_> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+        paths.sort();
+
+        let mut errors = Vec::new();
+        for path in paths {
+            let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+            let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            let table = match extension.as_str() {
+                "toml" => fs::read_to_string(&path).map_err(AppError::from).and_then(|s| RandomTable::from_toml(&s)),
+                "csv" => fs::read_to_string(&path).map_err(AppError::from).map(|s| RandomTable::from_csv(&stem, &s)),
+                _ => continue,
+            };
+            match table.context(format!("Table file {}", path.display())) {
+                Ok(table) => {
+                    log::info!("Loaded table '{}' from {}", table.name, path.display());
+                    self.add(table);
+                }
+                Err(e) => {
+                    log::warn!("{}", e);
+                    errors.push(e);
+                }
+            }
+        }
+        errors
+    }
+
+    /// Roll on a table, expanding nested table references and inline dice
+    pub fn roll(&self, name: &str) -> Result<String> {
+        self.roll_with(name, &mut rand::rng())
+    }
+
+    pub fn roll_with<R: Rng + ?Sized>(&self, name: &str, rng: &mut R) -> Result<String> {
+        self.roll_nested(name, rng, 0)
+    }
+
+    /// Read a roll made elsewhere off a table (`roll d100 on reaction-table`)
+    pub fn lookup(&self, name: &str, roll: u32) -> Result<String> {
+        let table = self.get(name)
+            .ok_or_else(|| AppError::NotFound(format!("Table '{}'", name)))?;
+        let entry = table.entry_for_roll(roll)?;
+        self.expand(&entry.text, &mut rand::rng(), 0)
+    }
+
+    fn roll_nested<R: Rng + ?Sized>(&self, name: &str, rng: &mut R, depth: usize) -> Result<String> {
+        if depth > MAX_NESTING {
+            return Err(AppError::ValidationError(format!("Tables nest more than {} levels deep at '{}'", MAX_NESTING, name)));
+        }
+        let table = self.get(name)
+            .ok_or_else(|| AppError::NotFound(format!("Table '{}'", name)))?;
+        let entry = table.pick(rng)
+            .ok_or_else(|| AppError::ValidationError(format!("Table '{}' has no entries", table.name)))?;
+        self.expand(&entry.text, rng, depth)
+    }
+
+    fn expand<R: Rng + ?Sized>(&self, text: &str, rng: &mut R, depth: usize) -> Result<String> {
+        let reference = Regex::new(r"\[\[\s*([^\]]+?)(?:\s+x(\d+))?\s*\]\]").expect("valid table reference regex");
+        let dice = Regex::new(r"\{(\d+d\d+)\}").expect("valid inline dice regex");
+
+        let mut expanded = String::new();
+        let mut last = 0;
+        for caps in reference.captures_iter(text) {
+            let whole = caps.get(0).expect("match has a whole group");
+            expanded.push_str(&text[last..whole.start()]);
+            let times: usize = caps.get(2).and_then(|m| m.as_str().parse().ok()).unwrap_or(1);
+            let results = (0..times)
+                .map(|_| self.roll_nested(&caps[1], rng, depth + 1))
+                .collect::<Result<Vec<_>>>()?;
+            expanded.push_str(&results.join("; "));
+            last = whole.end();
+        }
+        expanded.push_str(&text[last..]);
+
+        Ok(dice.replace_all(&expanded, |caps: &regex::Captures| {
+            crate::dice::roll_dice(&caps[1])
+                .map(|(_, total)| total.to_string())
+                .unwrap_or_else(|_| caps[0].to_string())
+        }).into_owned())
+    }
+}
@@ -0,0 +1,119 @@
+use crate::character::Character;
+use crate::combat::{CombatTracker, Combatant};
+use crate::search::WikiPageContent;
+use rand::Rng;
+use regex::Regex;
+
+/// Whether DND_TOOLS_TACTICS_HINTS turns on a suggestion line at the start of each monster's turn
+pub fn tactics_hints_from_env() -> bool {
+    std::env::var("DND_TOOLS_TACTICS_HINTS").is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// The parts of a stat block the hints look at
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TacticsProfile {
+    /// Attacks that work at range, e.g. "Shortbow"
+    pub ranged: Vec<String>,
+    /// Abilities that recharge on a d6, with the lowest roll that recharges them
+    pub recharge: Vec<(String, u8)>,
+}
+
+impl TacticsProfile {
+    /// From a wiki monster page: "Ranged ... Attack" actions and "(Recharge 5–6)" abilities
+    pub fn from_page(page: &WikiPageContent) -> Self {
+        let ranged = crate::content::Monster::from_page(page).actions.iter()
+            .filter(|action| action.contains("Ranged") || action.contains("range "))
+            .filter_map(|action| action.split_once(". ").map(|(name, _)| name.to_string()))
+            .collect();
+        let recharge_line = Regex::new(r"(?m)^(?:• )?([A-Z][\w' -]*?) \(Recharge (\d)").expect("valid recharge regex");
+        let mut recharge: Vec<(String, u8)> = Vec::new();
+        for caps in recharge_line.captures_iter(&page.content) {
+            let name = caps[1].trim().to_string();
+            if !recharge.iter().any(|(n, _)| *n == name) {
+                recharge.push((name, caps[2].parse().unwrap_or(6)));
+            }
+        }
+        TacticsProfile { ranged, recharge }
+    }
+
+    /// From an NPC sheet: bows, crossbows and thrown weapons it carries
+    pub fn from_sheet(sheet: &Character) -> Self {
+        let ranged = sheet.inventory.iter()
+            .filter(|item| crate::ammo::ammo_for_weapon(item).is_some_and(|ammo| !ammo.eq_ignore_ascii_case(item)))
+            .cloned()
+            .collect();
+        TacticsProfile { ranged, recharge: Vec::new() }
+    }
+
+    /// The combatant's own sheet if it has one, otherwise its cached monster page.
+    /// Numbered copies ("Goblin 2") share the page of their kind.
+    pub fn for_combatant(combatant: &Combatant) -> Self {
+        if let Some(sheet) = &combatant.npc_sheet {
+            return Self::from_sheet(sheet);
+        }
+        let kind = combatant.name.trim_end_matches(|ch: char| ch.is_ascii_digit() || ch == '#').trim();
+        crate::search::read_cached_page(std::path::Path::new(crate::search::CACHE_DIR), "monster", kind)
+            .map(|page| Self::from_page(&page))
+            .unwrap_or_default()
+    }
+}
+
+/// Player characters, sidekicks and companions are on the party's side
+pub fn with_party(combatant: &Combatant) -> bool {
+    combatant.is_player || combatant.owner.is_some()
+}
+
+/// The conscious, visible enemy with the lowest AC, breaking ties on lowest HP
+pub fn focus_target<'a>(combatants: &'a [Combatant], actor: &Combatant) -> Option<&'a Combatant> {
+    combatants.iter()
+        .filter(|c| with_party(c) != with_party(actor) && c.current_hp > 0 && !c.hidden)
+        .min_by_key(|c| (c.ac, c.current_hp))
+}
+
+/// Suggestions for the current combatant's turn; empty on the party's turns.
+/// At the start of a turn spent recharge abilities are rolled for, and a ready
+/// one that is suggested is marked as used.
+pub fn hint<R: Rng + ?Sized>(tracker: &mut CombatTracker, profile: &TacticsProfile, start_of_turn: bool, rng: &mut R) -> Vec<String> {
+    let Some(actor) = tracker.combatants.get(tracker.current_turn) else { return Vec::new() };
+    if with_party(actor) || actor.current_hp <= 0 {
+        return Vec::new();
+    }
+    let mut lines = Vec::new();
+    let target = focus_target(&tracker.combatants, actor).map(|t| (t.name.clone(), t.ac));
+    let bloodied = actor.current_hp * 2 <= actor.max_hp;
+    let actor = &mut tracker.combatants[tracker.current_turn];
+
+    if start_of_turn {
+        let mut recharged = Vec::new();
+        for (ability, min_roll) in &profile.recharge {
+            if actor.spent_recharges.contains(ability) {
+                let roll: u8 = rng.random_range(1..=6);
+                if roll >= *min_roll {
+                    recharged.push(ability.clone());
+                    lines.push(format!("🔋 {} recharges (rolled {})", ability, roll));
+                } else {
+                    lines.push(format!("🪫 {} is still spent (rolled {}, needs {}+)", ability, roll, min_roll));
+                }
+            }
+        }
+        actor.spent_recharges.retain(|ability| !recharged.contains(ability));
+    }
+    if let Some((ability, min_roll)) = profile.recharge.iter().find(|(ability, _)| !actor.spent_recharges.contains(ability)) {
+        let recharge = if *min_roll >= 6 { "6".to_string() } else { format!("{}-6", min_roll) };
+        lines.push(format!("💡 Use {} while it's ready (Recharge {})", ability, recharge));
+        if start_of_turn {
+            actor.spent_recharges.push(ability.clone());
+        }
+    }
+
+    match (bloodied, profile.ranged.first()) {
+        (true, Some(ranged)) => lines.push(format!("💡 Bloodied: keep its distance and use {} instead of closing to melee", ranged)),
+        (true, None) => lines.push("💡 Bloodied with no ranged attack: fight defensively or consider a retreat".to_string()),
+        _ => {}
+    }
+    match target {
+        Some((name, ac)) => lines.push(format!("💡 Focus {} (AC {}), the easiest target to hit", name, ac)),
+        None => lines.push("💡 No enemy in sight: hold position or move to find one".to_string()),
+    }
+    lines
+}
@@ -0,0 +1,166 @@
+use crate::error_handling::{Result, ResultExt};
+use rand::Rng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use std::{fs, path::{Path, PathBuf}};
+
+/// Line prefix the temperament is stored under on a saved NPC sheet
+const SHEET_PREFIX: &str = "Temperament: ";
+
+const ETHICS: [&str; 3] = ["Lawful", "Neutral", "Chaotic"];
+const MORALS: [&str; 3] = ["Good", "Neutral", "Evil"];
+
+const TEMPERAMENTS: &[&str] = &[
+    "cheerful", "gruff", "anxious", "proud", "suspicious", "easygoing", "hot-headed",
+    "melancholy", "curious", "pompous", "shy", "cunning",
+];
+
+/// Behavior that shows a lawful, neutral or chaotic outlook at the table
+const ETHIC_HINTS: [&[&str]; 3] = [
+    &["won't lie, but will leave things out", "quotes the rules, and expects others to follow them",
+        "keeps every bargain to the letter", "asks for names and credentials before helping"],
+    &["sides with whoever pays or protects them", "won't take a stand until they have to",
+        "keeps their head down when trouble starts", "trades favor for favor, no more"],
+    &["breaks a promise the moment it stops suiting them", "changes plans on a whim",
+        "sneers at guards and officials", "does the opposite of what they're told"],
+];
+
+/// Behavior that shows a good, neutral or evil heart
+const MORAL_HINTS: [&[&str]; 3] = [
+    &["helps strangers even at a cost", "won't abandon the wounded", "gives to beggars without thinking",
+        "refuses a bribe to hurt someone"],
+    &["looks after their own first", "haggles hard, but fairly", "shrugs off other people's troubles",
+        "helps if it's no trouble to them"],
+    &["haggles aggressively and cheats on the weights", "sells out anyone for a better offer",
+        "enjoys a bit too much when others suffer", "threatens before asking"],
+];
+
+/// Behavior for each temperament, in the order of `TEMPERAMENTS`
+const TEMPERAMENT_HINTS: &[&str] = &[
+    "laughs off insults", "answers in as few words as possible", "startles at loud noises",
+    "takes any slight against their family personally", "checks coins twice before taking them",
+    "offers everyone a drink", "raises their voice at the first disagreement", "sighs before every answer",
+    "asks the party more questions than they answer", "name-drops important acquaintances",
+    "won't meet anyone's eyes", "always asks what's in it for them",
+];
+
+/// An alignment and a temperament for an improvised NPC, with hints for playing them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Temperament {
+    /// "Lawful Neutral", or "True Neutral"
+    pub alignment: String,
+    pub temperament: String,
+    /// Two or three things the NPC does that show it
+    pub hints: Vec<String>,
+}
+
+impl Temperament {
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let ethic = rng.random_range(0..ETHICS.len());
+        let moral = rng.random_range(0..MORALS.len());
+        let alignment = match (ETHICS[ethic], MORALS[moral]) {
+            ("Neutral", "Neutral") => "True Neutral".to_string(),
+            (ethic, moral) => format!("{} {}", ethic, moral),
+        };
+        let temperament = rng.random_range(0..TEMPERAMENTS.len());
+        let mut hints: Vec<String> = [ETHIC_HINTS[ethic], MORAL_HINTS[moral]].iter()
+            .filter_map(|table| table.choose(rng).map(|hint| hint.to_string()))
+            .collect();
+        if rng.random_bool(0.5) {
+            hints.push(TEMPERAMENT_HINTS[temperament].to_string());
+        }
+        hints.shuffle(rng);
+        Temperament { alignment, temperament: TEMPERAMENTS[temperament].to_string(), hints }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        std::iter::once(format!("  {}, {}", self.alignment, self.temperament))
+            .chain(self.hints.iter().map(|hint| format!("  • {}", hint)))
+            .collect()
+    }
+
+    /// "🎭 Lawful Neutral, gruff: won't lie, but will leave things out; haggles hard, but fairly"
+    pub fn summary(&self) -> String {
+        format!("🎭 {}, {}: {}", self.alignment, self.temperament, self.hints.join("; "))
+    }
+
+    /// One line for an NPC sheet, readable by `parse_sheet_line`
+    pub fn sheet_line(&self) -> String {
+        format!("{}{} | {} | {}", SHEET_PREFIX, self.alignment, self.temperament, self.hints.join("; "))
+    }
+
+    pub fn parse_sheet_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.strip_prefix(SHEET_PREFIX)?.split(" | ").collect();
+        let [alignment, temperament, hints] = fields.as_slice() else { return None };
+        Some(Temperament {
+            alignment: alignment.to_string(),
+            temperament: temperament.to_string(),
+            hints: hints.split("; ").filter(|hint| !hint.is_empty()).map(str::to_string).collect(),
+        })
+    }
+}
+
+fn sheet_path(npcs_dir: &Path, name: &str) -> PathBuf {
+    npcs_dir.join(format!("{}.txt", name))
+}
+
+/// The temperament saved on an NPC's sheet, if it has one
+pub fn saved_temperament_in(npcs_dir: &Path, name: &str) -> Option<Temperament> {
+    fs::read_to_string(sheet_path(npcs_dir, name)).ok()?
+        .lines()
+        .find_map(Temperament::parse_sheet_line)
+}
+
+/// Put the temperament on the NPC's sheet, replacing any already there. An NPC
+/// without a sheet gets a minimal one.
+pub fn save_temperament_in(npcs_dir: &Path, name: &str, temperament: &Temperament) -> Result<PathBuf> {
+    fs::create_dir_all(npcs_dir).context("Failed to create npcs directory")?;
+    let path = sheet_path(npcs_dir, name);
+    let existing = fs::read_to_string(&path).unwrap_or_else(|_| format!("Name: {}\nType: NPC\n", name));
+    let mut lines: Vec<String> = existing.lines()
+        .filter(|line| !line.starts_with(SHEET_PREFIX))
+        .map(str::to_string)
+        .collect();
+    lines.push(temperament.sheet_line());
+    fs::write(&path, lines.join("\n") + "\n").context(format!("Failed to write {}", path.display()))?;
+    log::info!("Saved a temperament for {} to {}", name, path.display());
+    Ok(path)
+}
+
+/// The reminder shown when a saved NPC joins a fight, if their sheet has a temperament
+pub fn join_reminder(npcs_dir: &Path, name: &str) -> Option<String> {
+    saved_temperament_in(npcs_dir, name).map(|temperament| format!("  {} {}", name, temperament.summary()))
+}
+
+/// `temperament` suggests an alignment, temperament and behavior hints, `temperament
+/// save <npc>` keeps the last suggestion on the NPC's sheet, and `temperament <npc>`
+/// recalls a saved one
+pub fn temperament_command<R: Rng + ?Sized>(npcs_dir: &Path, args: &[&str], last: &mut Option<Temperament>, rng: &mut R) -> Vec<String> {
+    match args {
+        [] => {
+            let temperament = Temperament::random(rng);
+            let mut lines = vec!["🎭 Try this temperament:".to_string()];
+            lines.extend(temperament.lines());
+            lines.push("'temperament save <npc>' keeps it on the NPC's sheet; it's shown when they join a scene or a fight".to_string());
+            *last = Some(temperament);
+            lines
+        }
+        [save, name @ ..] if save.eq_ignore_ascii_case("save") && !name.is_empty() => {
+            let name = name.join(" ");
+            let message = match last.as_ref() {
+                Some(temperament) => match save_temperament_in(npcs_dir, &name, temperament) {
+                    Ok(path) => format!("💾 Saved the temperament to {}", path.display()),
+                    Err(e) => format!("❌ Could not save the temperament: {}", e),
+                },
+                None => "No temperament suggested yet. Type 'temperament' first.".to_string(),
+            };
+            vec![message]
+        }
+        name => {
+            let name = name.join(" ");
+            match saved_temperament_in(npcs_dir, &name) {
+                Some(temperament) => std::iter::once(format!("🎭 {}'s temperament:", name)).chain(temperament.lines()).collect(),
+                None => vec![format!("No temperament saved for {}. 'temperament' suggests one.", name)],
+            }
+        }
+    }
+}
@@ -8,6 +8,7 @@ use crate::combat::*;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::registry::CharacterStore;
 
     #[test]
     fn test_character_creation() {
@@ -45,6 +46,16 @@ mod tests {
         assert!(roll_dice("r101d6").is_err());
     }
 
+    #[test]
+    fn test_roll_dice_multiple_terms() {
+        let (rolls, total) = roll_dice("1d8+1d6").unwrap();
+        assert_eq!(rolls.len(), 2);
+        assert!((2..=14).contains(&total));
+        let (rolls, total) = roll_dice("2d6+3-1").unwrap();
+        assert_eq!(rolls.len(), 2);
+        assert_eq!(total, rolls.iter().map(|&r| r as u32).sum::<u32>() + 2);
+    }
+
     #[test]
     fn test_initiative_tracker() {
         let mut tracker = InitiativeTracker::new();
@@ -79,7 +90,7 @@ mod tests {
         character.max_hp = Some(25);
         character.ac = Some(16);
         
-        let combatant = Combatant::from_character(character, 15);
+        let combatant = Combatant::from_character(&character, 15);
         
         assert_eq!(combatant.name, "TestFighter");
         assert_eq!(combatant.current_hp, 25);
@@ -112,6 +123,7 @@ mod tests {
             name: "Poisoned".to_string(),
             description: Some("Taking poison damage".to_string()),
             duration: Some(3),
+            ..Default::default()
         };
         combatant.add_status(poison_status);
         assert_eq!(combatant.status_effects.len(), 1);
@@ -221,7 +233,8 @@ mod tests {
         // But we can test the function exists and handles d20 format
         let result = crate::dice::roll_dice_with_crits("1d20");
         assert!(result.is_ok());
-        let (rolls, total, crit_message) = result.unwrap();
+        let roll = result.unwrap();
+        let (rolls, total, crit_message) = (roll.kept, roll.total, roll.crit);
         assert_eq!(rolls.len(), 1);
         assert!(total >= 1 && total <= 20);
         
@@ -243,7 +256,7 @@ mod tests {
         // Non-d20 rolls should not have critical messages
         let result = crate::dice::roll_dice_with_crits("2d6");
         assert!(result.is_ok());
-        let (_rolls, _total, crit_message) = result.unwrap();
+        let crit_message = result.unwrap().crit;
         assert!(crit_message.is_none());
     }
 
@@ -353,6 +366,7 @@ mod tests {
             name: "Poisoned".to_string(),
             description: Some("Taking poison damage".to_string()),
             duration: Some(3),
+            ..Default::default()
         };
         combatant.add_status(poison_status);
         
@@ -361,6 +375,7 @@ mod tests {
             name: "Charmed".to_string(),
             description: Some("Charmed until dispelled".to_string()),
             duration: None,
+            ..Default::default()
         };
         combatant.add_status(charmed_status);
         
@@ -386,7 +401,7 @@ mod tests {
         character.max_hp = Some(25);
         character.temp_hp = Some(5);
         
-        let combatant_from_char = Combatant::from_character(character, 15);
+        let combatant_from_char = Combatant::from_character(&character, 15);
         assert_eq!(combatant_from_char.temp_hp, 5);
     }
 
@@ -451,7 +466,7 @@ mod tests {
         tracker.add_combatant(combatant);
         
         // Test basic damage
-        let result = tracker.apply_damage("TestTarget", 5);
+        let result = tracker.apply_damage("TestTarget", 5, None);
         assert!(result.is_ok());
         let message = result.unwrap();
         assert!(message.contains("TestTarget takes 5 damage"));
@@ -462,9 +477,9 @@ mod tests {
         assert_eq!(target.unwrap().current_hp, 15); // 20 - 5 = 15
         
         // Test damage to non-existent target
-        let result = tracker.apply_damage("NonExistent", 10);
+        let result = tracker.apply_damage("NonExistent", 10, None);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("not found"));
+        assert!(result.unwrap_err().to_string().contains("not found"));
     }
 
     #[test]
@@ -475,7 +490,7 @@ mod tests {
         tracker.add_combatant(combatant);
         
         // Test damage to temp HP only
-        let result = tracker.apply_damage("TestTarget", 3);
+        let result = tracker.apply_damage("TestTarget", 3, None);
         assert!(result.is_ok());
         let message = result.unwrap();
         assert!(message.contains("temporary HP"));
@@ -493,21 +508,21 @@ mod tests {
         tracker.add_combatant(combatant);
         
         // Test valid saving throw
-        let result = tracker.make_saving_throw("TestSaver", "dex");
+        let result = tracker.make_saving_throw("TestSaver", "dex", &[], None);
         assert!(result.is_ok());
         let message = result.unwrap();
         assert!(message.contains("TestSaver makes a"));
         assert!(message.contains("saving throw"));
         
         // Test invalid ability score
-        let result = tracker.make_saving_throw("TestSaver", "invalid");
+        let result = tracker.make_saving_throw("TestSaver", "invalid", &[], None);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Invalid ability score"));
+        assert!(result.unwrap_err().to_string().contains("Invalid ability score"));
         
         // Test non-existent combatant
-        let result = tracker.make_saving_throw("NonExistent", "str");
+        let result = tracker.make_saving_throw("NonExistent", "str", &[], None);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("not found"));
+        assert!(result.unwrap_err().to_string().contains("not found"));
     }
 
     #[test]
@@ -538,7 +553,7 @@ mod tests {
         tracker.display_initiative_order();
         
         if let Some(combatant) = tracker.combatants.first() {
-            combatant.display_stats();
+            combatant.display_stats(&[]);
         }
         
         // If we get here without panicking, the test passes
@@ -713,4 +728,3593 @@ mod tests {
         // Test that the content display doesn't panic
         result.display();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_tui_mouse_click_selects_menu_item() {
+        use crate::tui::{App, AppMode};
+        use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+        use ratatui::layout::Rect;
+
+        let mut app = App::new(CharacterStore::default());
+        app.menu_area = Rect::new(0, 0, 40, 10);
+
+        // Second row inside the border is "Tools"
+        app.handle_mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 5,
+            row: 2,
+            modifiers: KeyModifiers::NONE,
+        });
+        assert!(matches!(app.mode, AppMode::ToolsMenu));
+
+        // Clicking the border does nothing
+        app.handle_mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 0,
+            row: 0,
+            modifiers: KeyModifiers::NONE,
+        });
+        assert!(matches!(app.mode, AppMode::ToolsMenu));
+    }
+
+    #[test]
+    fn test_tui_status_bar_text() {
+        use crate::tui::{App, SearchStatus};
+
+        let mut app = App::new(CharacterStore::new(vec![Character::new("A"), Character::new("B")]));
+        let text = app.status_bar_text();
+        assert!(text.contains("No campaign"));
+        assert!(text.contains("2 characters"));
+        assert!(text.contains("Search: not checked"));
+        assert!(!text.contains("Round"));
+
+        app.campaign_name = Some("Curse of Strahd".to_string());
+        app.in_game_date = Some("3rd of Hammer".to_string());
+        app.search_status = SearchStatus::Offline;
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 13, 12));
+        app.combat_tracker = Some(tracker);
+
+        let text = app.status_bar_text();
+        assert!(text.contains("Curse of Strahd"));
+        assert!(text.contains("3rd of Hammer"));
+        assert!(text.contains("Round 1"));
+        assert!(text.contains("Turn: Goblin"));
+        assert!(text.contains("Search: offline"));
+
+        // A hidden combatant's turn doesn't give their name away
+        app.combat_tracker.as_mut().unwrap().set_hidden("Goblin", true).unwrap();
+        let text = app.status_bar_text();
+        assert!(text.contains("Turn: ..."));
+        assert!(!text.contains("Goblin"));
+    }
+
+    #[test]
+    fn test_tui_pending_damage_prompt() {
+        use crate::tui::{App, AppMode, PendingInput};
+        use crossterm::event::KeyCode;
+
+        let mut app = App::new(CharacterStore::default());
+        app.mode = AppMode::CombatTrackerTUI;
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 13, 12));
+        app.combat_tracker = Some(tracker);
+        app.pending_input = Some(PendingInput::DamageFor("Goblin".to_string(), None));
+
+        // Invalid input keeps the prompt open
+        for c in "abc".chars() {
+            app.handle_key(KeyCode::Char(c));
+        }
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.pending_input, Some(PendingInput::DamageFor("Goblin".to_string(), None)));
+
+        app.handle_key(KeyCode::Char('5'));
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.pending_input, None);
+        assert_eq!(app.combat_tracker.as_ref().unwrap().combatants[0].current_hp, 2);
+
+        // Esc cancels a prompt without leaving combat mode
+        app.pending_input = Some(PendingInput::DamageFor("Goblin".to_string(), None));
+        app.handle_key(KeyCode::Esc);
+        assert_eq!(app.pending_input, None);
+        assert!(matches!(app.mode, AppMode::CombatTrackerTUI));
+    }
+
+    #[test]
+    fn test_confirmation_input() {
+        use crate::input_handler::is_confirmation;
+
+        assert!(is_confirmation("y", "Goblin"));
+        assert!(is_confirmation(" YES ", "Goblin"));
+        assert!(is_confirmation("goblin", "Goblin"));
+        assert!(!is_confirmation("n", "Goblin"));
+        assert!(!is_confirmation("", "Goblin"));
+        assert!(!is_confirmation("gob", "Goblin"));
+    }
+
+    #[test]
+    fn test_tui_destructive_actions_need_confirmation() {
+        use crate::tui::{App, AppMode, PendingInput};
+        use crossterm::event::KeyCode;
+
+        fn type_command(app: &mut App, command: &str) {
+            for c in command.chars() {
+                app.handle_key(KeyCode::Char(c));
+            }
+            app.handle_key(KeyCode::Enter);
+        }
+
+        // Declining a character deletion keeps the character
+        let mut app = App::new(CharacterStore::new(vec![Character::new("Keeper")]));
+        app.mode = AppMode::CharacterDeletionTUI;
+        type_command(&mut app, "delete keeper");
+        assert_eq!(app.pending_input, Some(PendingInput::ConfirmDelete("Keeper".to_string())));
+        type_command(&mut app, "n");
+        assert_eq!(app.pending_input, None);
+        assert_eq!(app.characters.len(), 1);
+
+        // Combatant removal only happens after confirming
+        app.mode = AppMode::CombatTrackerTUI;
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 13, 12));
+        app.combat_tracker = Some(tracker);
+        type_command(&mut app, "remove goblin");
+        assert_eq!(app.combat_tracker.as_ref().unwrap().combatants.len(), 1);
+        type_command(&mut app, "y");
+        assert!(app.combat_tracker.as_ref().unwrap().combatants.is_empty());
+    }
+
+    #[test]
+    fn test_cache_clear_needs_confirmation() {
+        use crate::tui::{App, AppMode, PendingInput};
+        use crossterm::event::KeyCode;
+
+        let mut app = App::new(CharacterStore::default());
+        app.mode = AppMode::SearchTUI;
+        for c in "cache clear".chars() {
+            app.handle_key(KeyCode::Char(c));
+        }
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.pending_input, Some(PendingInput::ConfirmCacheClear));
+        assert!(app.output_history.last().unwrap().starts_with("⚠️ Delete all"));
+
+        // Declining leaves the cache alone
+        app.handle_key(KeyCode::Char('n'));
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.pending_input, None);
+        assert_eq!(app.output_history.last().map(String::as_str), Some("Cache left as it was."));
+
+        // The shared command never clears without the prompt, nor offers to
+        let usage = crate::cache::cache_command(&["clear"]);
+        assert_eq!(usage, vec!["Usage: cache [stats|refresh <name>]".to_string()]);
+    }
+
+    #[test]
+    fn test_character_trash_and_restore() {
+        use crate::file_manager::*;
+        use std::fs;
+
+        let base = std::env::temp_dir().join(format!("dnd_tools_trash_test_{}", std::process::id()));
+        let characters_dir = base.join("characters");
+        let trash_dir = characters_dir.join("trash");
+        fs::create_dir_all(&characters_dir).unwrap();
+
+        let character = Character::new("Trashy");
+        let serialized = ron::ser::to_string_pretty(&character, ron::ser::PrettyConfig::default()).unwrap();
+        fs::write(characters_dir.join("Trashy.txt"), serialized).unwrap();
+
+        // Trashing moves the file out of the characters folder
+        trash_character_in(&characters_dir, &trash_dir, "Trashy").unwrap();
+        assert!(!characters_dir.join("Trashy.txt").exists());
+        let trashed = list_trashed_characters_in(&trash_dir);
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].name, "Trashy");
+
+        // Recent entries survive a purge, old ones do not
+        assert_eq!(purge_trash_in(&trash_dir, 30, trashed[0].deleted_at + 60), 0);
+
+        let restored = restore_character_in(&characters_dir, &trashed[0]).unwrap();
+        assert_eq!(restored, character);
+        // A sheet trashed in the old format comes back as JSON
+        assert!(characters_dir.join("Trashy.json").exists());
+        assert!(list_trashed_characters_in(&trash_dir).is_empty());
+
+        trash_character_in(&characters_dir, &trash_dir, "Trashy").unwrap();
+        let deleted_at = list_trashed_characters_in(&trash_dir)[0].deleted_at;
+        assert_eq!(purge_trash_in(&trash_dir, 30, deleted_at + 31 * 24 * 60 * 60), 1);
+        assert!(list_trashed_characters_in(&trash_dir).is_empty());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        use crate::file_manager::*;
+
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 15, 12));
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 15, 8));
+        let ids: Vec<u32> = tracker.combatants.iter().map(|c| c.id).collect();
+        assert_ne!(ids[0], ids[1]);
+
+        let second = format!("Goblin#{}", ids[1]);
+        assert_eq!(tracker.display_name(&tracker.combatants[1]), second);
+        assert_eq!(tracker.get_combatant(&second).unwrap().initiative, 8);
+        assert!(tracker.remove_combatant(&second));
+        assert_eq!(tracker.combatants.len(), 1);
+        assert_eq!(tracker.display_name(&tracker.combatants[0]), "Goblin");
+
+        let mut characters = vec![Character::new("Aria"), Character::new("Aria 2")];
+        assert_eq!(suggest_unique_name(&characters, "Aria"), "Aria 3");
+        assert!(!add_character_with_choice(&mut characters, Character::new("Aria"), NameCollisionChoice::Cancel));
+        assert!(add_character_with_choice(&mut characters, Character::new("Aria"), NameCollisionChoice::Rename("Aria 3".to_string())));
+        assert!(add_character_with_choice(&mut characters, Character::new("aria"), NameCollisionChoice::Overwrite));
+        assert_eq!(characters.len(), 3);
+        assert!(characters.iter().any(|c| c.name == "aria"));
+    }
+
+    #[test]
+    fn test_name_collisions_with_saved_sheets() {
+        use crate::file_manager::*;
+        use crate::tui::{App, AppMode, PendingInput};
+        use crossterm::event::KeyCode;
+        use std::fs;
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_name_collisions_{}", std::process::id()));
+        let trash_dir = dir.join("trash");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        write_character_file(&dir, &Character::new("Mira")).unwrap();
+        write_character_file(&dir, &Character::new("Mira 2")).unwrap();
+
+        // Sheets on disk count, whatever their case
+        assert!(character_name_taken_in(&dir, &[], "mira"));
+        assert_eq!(suggest_unique_name_in(&dir, &[], "Mira"), "Mira 3");
+
+        // Overwriting "Mira" with "mira" doesn't leave the old sheet behind
+        let mut characters = vec![Character::new("Mira")];
+        assert!(add_character_with_choice_in(&dir, &trash_dir, &mut characters, Character::new("mira"), NameCollisionChoice::Overwrite));
+        assert!(!dir.join("Mira.json").exists());
+        assert_eq!(list_trashed_characters_in(&trash_dir).len(), 1);
+        assert_eq!(characters.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["mira"]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        // The TUI asks before creating a character whose name is taken
+        fn type_command(app: &mut App, command: &str) {
+            for c in command.chars() {
+                app.handle_key(KeyCode::Char(c));
+            }
+            app.handle_key(KeyCode::Enter);
+        }
+        let mut app = App::new(CharacterStore::new(vec![Character::new("Zephyrine")]));
+        app.mode = AppMode::CharacterCreationTUI;
+        type_command(&mut app, "create zephyrine");
+        assert_eq!(app.pending_input, Some(PendingInput::NameCollision("zephyrine".to_string())));
+        type_command(&mut app, "cancel");
+        assert_eq!(app.pending_input, None);
+
+        type_command(&mut app, "create zephyrine");
+        type_command(&mut app, "rename");
+        assert_eq!(app.pending_input, Some(PendingInput::CreationMethod("zephyrine 2".to_string())));
+        type_command(&mut app, "cancel");
+
+        type_command(&mut app, "create Zephyrine");
+        type_command(&mut app, "overwrite");
+        assert_eq!(app.pending_input, Some(PendingInput::CreationMethod("Zephyrine".to_string())));
+    }
+
+    #[test]
+    fn test_character_summary_table() {
+        let mut fighter = Character::new("Bruni");
+        fighter.class = Some("Fighter".to_string());
+        fighter.level = Some(3);
+        fighter.hp = Some(20);
+        fighter.max_hp = Some(28);
+        let table = crate::file_manager::character_summary_table(&[fighter, Character::new("Nameless Wanderer")]);
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("Name"));
+        assert!(lines[2].contains("Fighter") && lines[2].contains("20/28"));
+        assert!(lines[3].starts_with("Nameless Wanderer"));
+        assert!(!table.contains("Some("));
+    }
+
+    #[test]
+    fn test_error_context_chain() {
+        use crate::error_handling::{AppError, ResultExt};
+        use std::error::Error;
+
+        let io_failure: std::result::Result<(), std::io::Error> =
+            Err(std::io::Error::new(std::io::ErrorKind::NotFound, "missing.txt"));
+        let err = io_failure.context("Failed to read trashed file").unwrap_err();
+        assert!(err.to_string().starts_with("Failed to read trashed file: IO Error"));
+        assert!(err.source().is_some());
+
+        let mut tracker = CombatTracker::new();
+        match tracker.apply_damage("Nobody", 3, None) {
+            Err(AppError::NotFound(what)) => assert_eq!(what, "Target 'Nobody'"),
+            other => panic!("expected NotFound, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tui_panic_message() {
+        use crate::tui::panic_message;
+
+        let payload = std::panic::catch_unwind(|| panic!("static message")).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "static message");
+
+        let payload = std::panic::catch_unwind(|| panic!("formatted {}", 42)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "formatted 42");
+    }
+
+    #[test]
+    fn test_stat_validation() {
+        use crate::error_handling::StatLimits;
+
+        let limits = StatLimits::new();
+        assert_eq!(limits.validate("level", "5").unwrap(), 5);
+        assert_eq!(limits.validate("level", "0").unwrap_err().to_string(), "Validation Error: Level must be between 1 and 20");
+        assert!(limits.validate("ac", "200").is_err());
+        assert_eq!(limits.validate("hp", "-3").unwrap_err().to_string(), "Validation Error: HP must be between 0 and 255");
+        assert_eq!(limits.validate("speed", "fast").unwrap_err().to_string(), "Parse Error: Speed must be a whole number");
+
+        let house_rules = StatLimits::parse("level=0-30, ac=1-40, bogus");
+        assert_eq!(house_rules.validate("level", "0").unwrap(), 0);
+        assert_eq!(house_rules.validate("ac", "35").unwrap(), 35);
+        assert_eq!(house_rules.range("speed"), StatLimits::new().range("speed"));
+
+        let mut character = Character::new("Setter");
+        assert!(character.set_stat("ac", "200").is_err());
+        assert_eq!(character.ac, None);
+        character.set_stat("ac", "18").unwrap();
+        assert_eq!(character.ac, Some(18));
+    }
+
+    #[test]
+    fn test_log_rotation() {
+        use crate::logging::{format_record, rotate_logs};
+        use std::fs;
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_logs_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let log = dir.join("dnd_tools.log");
+        let rotated = |n: usize| dir.join(format!("dnd_tools.log.{}", n));
+
+        for generation in 1..=3 {
+            fs::write(&log, format!("generation {}", generation)).unwrap();
+            rotate_logs(&log, 2);
+        }
+        assert!(!log.exists());
+        assert_eq!(fs::read_to_string(rotated(1)).unwrap(), "generation 3");
+        assert_eq!(fs::read_to_string(rotated(2)).unwrap(), "generation 2");
+        assert!(!rotated(3).exists());
+
+        let line = format_record(1700000000, log::Level::Warn, "dnd_tools::search", "Request failed");
+        assert_eq!(line, "1700000000 WARN  dnd_tools::search: Request failed");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_diagnostics_bundle() {
+        use crate::diagnostics::{create_bundle_in, redact_config_entry};
+        use std::fs;
+
+        assert_eq!(redact_config_entry("DND_TOOLS_API_TOKEN", "abc123", None), "DND_TOOLS_API_TOKEN=<redacted>");
+        assert_eq!(redact_config_entry("DND_TOOLS_DATA", "/home/alex/dnd", Some("/home/alex")), "DND_TOOLS_DATA=~/dnd");
+
+        let base = std::env::temp_dir().join(format!("dnd_tools_diag_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        fs::create_dir_all(base.join("logs")).unwrap();
+        fs::create_dir_all(base.join("characters")).unwrap();
+        fs::write(base.join("logs/dnd_tools.log"), "1 INFO  dnd_tools: started").unwrap();
+        fs::write(base.join("characters/Aria.txt"), "(name: \"Aria\")").unwrap();
+
+        let vars = vec![
+            ("DND_TOOLS_SECRET_KEY".to_string(), "hunter2".to_string()),
+            ("PATH".to_string(), "/usr/bin".to_string()),
+        ];
+        let bundle = create_bundle_in(&base, &base.join("out"), &vars, None).unwrap();
+        let mut archive = zip::ZipArchive::new(fs::File::open(&bundle).unwrap()).unwrap();
+        let names: Vec<String> = archive.file_names().map(String::from).collect();
+        for expected in ["version.txt", "config.txt", "logs/dnd_tools.log", "last_save/Aria.txt"] {
+            assert!(names.iter().any(|n| n == expected), "missing {}", expected);
+        }
+
+        let mut config = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("config.txt").unwrap(), &mut config).unwrap();
+        assert!(config.contains("DND_TOOLS_SECRET_KEY=<redacted>"));
+        assert!(!config.contains("hunter2") && !config.contains("PATH"));
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_update_check_and_changelog() {
+        use crate::tui::App;
+        use crate::update::{is_newer, parse_release, update_checks_enabled, ReleaseInfo};
+        use crossterm::event::KeyCode;
+
+        assert!(is_newer("v0.2.0", "0.1.9"));
+        assert!(is_newer("1.0", "0.9.9"));
+        assert!(!is_newer("v0.1.0", "0.1.0"));
+        assert!(!is_newer("nightly", "0.1.0"));
+
+        let release = parse_release(r#"{"tag_name": "v9.0.0", "body": "- Added things\n", "html_url": "https://example.com/r"}"#).unwrap();
+        assert_eq!(release.version, "v9.0.0");
+        assert_eq!(release.changelog, "- Added things");
+        assert!(parse_release("{}").is_err());
+
+        assert!(!update_checks_enabled(&["--offline".to_string()]));
+        assert!(!update_checks_enabled(&["--no-update-check".to_string()]));
+
+        let (tx, rx) = std::sync::mpsc::channel::<ReleaseInfo>();
+        let mut app = App::new(CharacterStore::default());
+        app.update_rx = Some(rx);
+        tx.send(release).unwrap();
+        app.poll_update();
+        assert!(app.status_bar_text().contains("v9.0.0 available"));
+
+        app.handle_key(KeyCode::Char('c'));
+        assert!(app.message.as_deref().unwrap().contains("- Added things"));
+        app.handle_key(KeyCode::Down);
+        assert!(app.message.is_none());
+        assert_eq!(app.selected_index, 0);
+    }
+
+    #[test]
+    fn test_script_hooks() {
+        use crate::scripting::{apply_actions, Hook, ScriptAction, ScriptEngine};
+        use crate::tui::{App, AppMode};
+        use crossterm::event::KeyCode;
+
+        let mut scripts = ScriptEngine::new();
+        scripts.load_script("bloodied.rhai", r#"
+            fn on_damage(target, amount, hp, max_hp) {
+                if hp > 0 && hp * 2 <= max_hp { add_status(target, "Bloodied"); }
+            }
+        "#).unwrap();
+        scripts.load_script("flaming_sword.rhai", r#"
+            fn on_roll(kind, expression, total, crit, target) {
+                if kind == "attack" && crit { damage(target, 2); return "Flaming sword ignites!"; }
+                ""
+            }
+        "#).unwrap();
+        assert!(scripts.load_script("broken.rhai", "fn on_roll(").is_err());
+
+        let crit = Hook::Roll { kind: "attack".to_string(), expression: "1d20".to_string(), total: 20, crit: true, target: "Orc".to_string() };
+        let actions = scripts.run_hook(&crit);
+        assert_eq!(actions, vec![
+            ScriptAction::Damage { target: "Orc".to_string(), amount: 2 },
+            ScriptAction::Message("Flaming sword ignites!".to_string()),
+        ]);
+        let miss = Hook::Roll { kind: "attack".to_string(), expression: "1d20".to_string(), total: 4, crit: false, target: "Orc".to_string() };
+        assert!(scripts.run_hook(&miss).is_empty());
+
+        // The classic combat loop carries actions out the same way the TUI does
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Orc".to_string(), 20, 13, 12));
+        let lines = apply_actions(Some(&mut tracker), actions.clone());
+        assert_eq!((tracker.combatants[0].current_hp, lines[1].as_str()), (18, "📜 Flaming sword ignites!"));
+        assert!(apply_actions(None, actions)[0].contains("no combat initialized"));
+        tracker.next_turn();
+        assert_eq!(tracker.last_actor_name().as_deref(), Some("Orc"));
+
+        let mut app = App::new(CharacterStore::default());
+        app.mode = AppMode::CombatTrackerTUI;
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Orc".to_string(), 20, 13, 12));
+        app.combat_tracker = Some(tracker);
+        app.scripts = scripts;
+
+        for c in "hit orc 12".chars() {
+            app.handle_key(KeyCode::Char(c));
+        }
+        app.handle_key(KeyCode::Enter);
+        let orc = &app.combat_tracker.as_ref().unwrap().combatants[0];
+        assert_eq!(orc.current_hp, 8);
+        assert!(orc.status_effects.iter().any(|s| s.name == "Bloodied"));
+    }
+
+    #[test]
+    fn test_random_tables() {
+        use crate::tables::{RandomTable, TableRegistry};
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let loot = RandomTable::from_toml(r#"
+            name = "Loot"
+            [[entries]]
+            weight = 3
+            text = "{1d1} gold and [[Gems x2]]"
+        "#).unwrap();
+        assert_eq!(loot.entries[0].weight, 3);
+        let gems = RandomTable::from_csv("Gems", "weight,text\n# comment\n5,Ruby\nSapphire, cut");
+        assert_eq!(gems.entries.len(), 2);
+        assert_eq!(gems.entries[1].weight, 1);
+        assert_eq!(gems.entries[1].text, "Sapphire, cut");
+
+        let mut registry = TableRegistry::new();
+        registry.add(loot);
+        registry.add(RandomTable::from_csv("Gems", "1,Ruby"));
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(registry.roll_with("loot", &mut rng).unwrap(), "1 gold and Ruby; Ruby");
+        assert!(registry.roll("Missing").unwrap_err().to_string().contains("not found"));
+
+        registry.add(RandomTable::from_csv("Loop", "[[Loop]]"));
+        assert!(registry.roll("Loop").is_err());
+
+        // Weights skew the results
+        let mut weighted = TableRegistry::new();
+        weighted.add(RandomTable::from_csv("Coin", "99,heads\n1,tails"));
+        let heads = (0..200).filter(|_| weighted.roll_with("coin", &mut rng).unwrap() == "heads").count();
+        assert!(heads > 180);
+    }
+
+    #[test]
+    fn test_rumor_generator() {
+        use crate::rumors::{generate_rumors, register_builtin_tables, RumorOptions, THEMES};
+        use crate::tables::TableRegistry;
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let mut registry = TableRegistry::new();
+        register_builtin_tables(&mut registry);
+        for theme in THEMES {
+            assert!(registry.get(&format!("Rumor Event ({})", theme)).is_some());
+        }
+
+        let options = RumorOptions::parse(&["5", "City", "crime", "npcs"]).unwrap();
+        assert_eq!(options, RumorOptions { count: 5, size: Some("city".to_string()), theme: Some("crime".to_string()), use_npcs: true });
+        assert!(RumorOptions::parse(&["metropolis"]).is_err());
+        assert_eq!(RumorOptions::parse(&[]).unwrap().count, 3);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let rumors = generate_rumors(&registry, &options, &["Grizzle".to_string()], &mut rng).unwrap();
+        assert_eq!(rumors.len(), 5);
+        for rumor in &rumors {
+            assert!(rumor.contains("Grizzle"));
+            assert!(rumor.ends_with("true]") || rumor.ends_with("false]"));
+        }
+    }
+
+    #[test]
+    fn test_turn_banner() {
+        use crate::banner::{banner_width, render_banner, render_banner_wrapped, BANNER_HEIGHT};
+        use crate::combat::{CombatTracker, Combatant};
+
+        let rows = render_banner("Hi 2");
+        assert_eq!(rows.len(), BANNER_HEIGHT);
+        assert!(rows.iter().all(|row| row.chars().count() == banner_width("Hi 2")));
+        assert_eq!(rows[2], "█████  █        ██ ");
+
+        // Words wrap onto separate banners rather than overflowing the screen
+        let wrapped = render_banner_wrapped("Goblin Boss", banner_width("Goblin"));
+        assert_eq!(wrapped.len(), BANNER_HEIGHT * 2 + 1);
+        assert_eq!(render_banner_wrapped("Goblin", 3), vec!["GOBLIN".to_string()]);
+
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Orc".to_string(), 15, 13, 12));
+        let lines = crate::tui::turn_banner_lines(&tracker, 80);
+        assert_eq!(lines[..BANNER_HEIGHT], render_banner("Orc")[..]);
+        assert!(lines[BANNER_HEIGHT + 1..] == render_banner(&format!("Round {}", tracker.round_number))[..]);
+    }
+
+    #[test]
+    fn test_player_view_socket() {
+        use crate::combat::{CombatTracker, Combatant};
+        use crate::player_view::{PlayerViewServer, PlayerViewState};
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpStream;
+
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Ogre".to_string(), 60, 11, 8));
+        tracker.add_combatant(Combatant::new_npc("Wolf".to_string(), 10, 13, 15));
+        tracker.apply_damage("Ogre", 35, None).unwrap();
+        tracker.apply_damage("Wolf", 10, None).unwrap();
+
+        let state = PlayerViewState::from_tracker(&tracker);
+        assert_eq!(state.current_name(), Some("Wolf"));
+        assert_eq!(state.entries[0].condition, "Down");
+        assert_eq!(state.entries[1].condition, "Bloodied");
+
+        let mut server = PlayerViewServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let mut reader = BufReader::new(TcpStream::connect(addr).unwrap());
+        // The connection may take a moment to reach the listener
+        for _ in 0..50 {
+            server.publish(&state);
+            if server.client_count() == 1 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(server.client_count(), 1);
+
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let received: PlayerViewState = serde_json::from_str(&line).unwrap();
+        assert_eq!(received, state);
+        // Exact HP never leaves the DM's machine
+        assert!(!line.contains("25") && !line.contains("\"ac\""));
+
+        // Neither does the view itself
+        assert!(PlayerViewServer::bind("0.0.0.0:0").is_err());
+
+        // A screen that stops reading is dropped instead of holding up the DM
+        let mut big = state.clone();
+        big.entries = (0..500).map(|i| crate::player_view::PlayerEntry { name: format!("Goblin {}", i), ..state.entries[0].clone() }).collect();
+        let started = std::time::Instant::now();
+        for round in 0..10_000 {
+            big.round = round;
+            server.publish(&big);
+            if server.client_count() == 0 {
+                break;
+            }
+        }
+        assert_eq!(server.client_count(), 0);
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        drop(reader);
+    }
+
+    #[test]
+    fn test_hidden_monster_hp() {
+        use crate::combat::{CombatTracker, Combatant};
+
+        let mut tracker = CombatTracker::new();
+        tracker.hide_monster_hp = true;
+        tracker.add_combatant(Combatant::new_npc("Troll".to_string(), 84, 15, 13));
+        let mut hero = Combatant::new_npc("Hero".to_string(), 30, 16, 10);
+        hero.is_player = true;
+        tracker.add_combatant(hero);
+
+        let message = tracker.apply_damage("Troll", 20, None).unwrap();
+        assert_eq!(message, "❤️ Troll takes 20 damage (Wounded)");
+        let message = tracker.apply_damage("Troll", 50, None).unwrap();
+        assert!(message.ends_with("(Near death)") && !message.contains("14"));
+
+        // Player characters and DM-only numbers are unaffected
+        assert!(tracker.apply_damage("Hero", 5, None).unwrap().contains("HP: 25/30"));
+        let troll = tracker.get_combatant("Troll").unwrap();
+        assert_eq!(troll.current_hp, 14);
+        assert_eq!(troll.hp_display(true), "Near death");
+        assert_eq!(troll.hp_display(false), "14/84");
+        assert_eq!(troll.hp_change_display(34, true), "Near death");
+        assert_eq!(troll.hp_change_display(34, false), "HP: 34 → 14");
+
+        let mut fresh = Combatant::new_npc("Rat".to_string(), 4, 10, 1);
+        assert_eq!(fresh.hp_descriptor(), "Unhurt");
+        fresh.current_hp = 2;
+        assert_eq!(fresh.hp_descriptor(), "Bloodied");
+        fresh.current_hp = 0;
+        assert_eq!(fresh.hp_descriptor(), "Down");
+    }
+
+    #[test]
+    fn test_hidden_combatants() {
+        use crate::combat::{CombatTracker, Combatant};
+        use crate::player_view::PlayerViewState;
+
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Fighter".to_string(), 30, 16, 12));
+        tracker.add_combatant(Combatant::new_npc("Assassin".to_string(), 40, 15, 20));
+        assert_eq!(tracker.set_hidden("assassin", true).unwrap(), "Assassin");
+        assert!(tracker.set_hidden("Nobody", true).is_err());
+        assert_eq!(tracker.hidden_names(), vec!["Assassin".to_string()]);
+
+        // Still first in the turn order, but not displayed
+        assert_eq!(tracker.combatants[0].name, "Assassin");
+        let visible: Vec<_> = tracker.visible_combatants().map(|(i, c)| (i, c.name.as_str())).collect();
+        assert_eq!(visible, vec![(1, "Fighter")]);
+        let view = PlayerViewState::from_tracker(&tracker);
+        assert_eq!(view.entries.len(), 1);
+        assert_eq!(view.current_name(), None);
+        assert_eq!(crate::tui::turn_banner_lines(&tracker, 80)[..5], crate::banner::render_banner("...")[..]);
+
+        tracker.set_hidden("Assassin", false).unwrap();
+        assert_eq!(tracker.visible_combatants().count(), 2);
+        assert_eq!(PlayerViewState::from_tracker(&tracker).current_name(), Some("Assassin"));
+    }
+
+    #[test]
+    fn test_morale_groups() {
+        use crate::combat::{CombatTracker, Combatant};
+        use crate::morale::{MoraleOutcome, MoraleTrigger};
+
+        assert_eq!(MoraleOutcome::from_roll(7, 7), MoraleOutcome::StandsFirm);
+        assert_eq!(MoraleOutcome::from_roll(7, 9), MoraleOutcome::Retreat);
+        assert_eq!(MoraleOutcome::from_roll(7, 11), MoraleOutcome::Flee);
+        assert_eq!(MoraleOutcome::from_roll(6, 12), MoraleOutcome::Surrender);
+
+        let mut tracker = CombatTracker::new();
+        for _ in 0..3 {
+            tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 15, 10));
+        }
+        tracker.add_combatant(Combatant::new_npc("Boss".to_string(), 21, 17, 12));
+        assert!(tracker.add_morale_group("Goblins", 13, &["goblin"]).is_err());
+        assert!(tracker.add_morale_group("Goblins", 7, &["kobold"]).is_err());
+        tracker.add_morale_group("Goblins", 7, &["goblin", "*boss"]).unwrap();
+        assert_eq!(tracker.morale_groups[0].members.len(), 4);
+        assert!(tracker.morale_triggers().is_empty());
+
+        tracker.apply_damage("Boss", 30, None).unwrap();
+        let triggers = tracker.morale_triggers();
+        assert_eq!(triggers, vec![("Goblins".to_string(), MoraleTrigger::LeaderDown("Boss".to_string()))]);
+        // Each trigger fires once
+        assert!(tracker.morale_triggers().is_empty());
+
+        tracker.apply_damage("Goblin#1", 7, None).unwrap();
+        let triggers = tracker.morale_triggers();
+        assert_eq!(triggers[0].1, MoraleTrigger::HalfFallen { fallen: 2, total: 4 });
+
+        let result = tracker.roll_morale("goblins").unwrap();
+        assert!(result.contains("vs morale 7"));
+        assert!(tracker.morale_groups[0].last_outcome.is_some());
+        assert!(tracker.roll_morale("orcs").is_err());
+    }
+
+    #[test]
+    fn test_skill_challenge() {
+        use crate::skill_challenge::{ChallengeStatus, SkillChallenge};
+
+        assert!(SkillChallenge::new("Nothing", 0, 3, 13).is_err());
+        let mut challenge = SkillChallenge::new("Negotiation", 3, 2, 12).unwrap();
+        assert_eq!(challenge.suggested_dc(Some("Persuasion")), 12);
+
+        assert!(challenge.record("Lia", "Persuasion", 15, None).unwrap().success);
+        // Repeating a skill that worked is harder
+        assert_eq!(challenge.suggested_dc(Some("persuasion")), 14);
+        assert!(!challenge.record("Lia", "Persuasion", 13, None).unwrap().success);
+        // Failures raise the DC for everyone
+        assert_eq!(challenge.suggested_dc(Some("Insight")), 13);
+        assert_eq!(challenge.progress(), "Successes ●○○ 1/3 | Failures ✗○ 1/2");
+
+        challenge.record("Borin", "Intimidation", 20, Some(15)).unwrap();
+        challenge.record("Borin", "Insight", 13, None).unwrap();
+        assert_eq!(challenge.status(), ChallengeStatus::Succeeded);
+        assert!(challenge.record("Lia", "Deception", 20, None).is_err());
+
+        let summary = challenge.summary();
+        assert_eq!(summary[0], "📜 Negotiation: ✅ Success, but at a cost");
+        assert_eq!(summary[2], "  Lia: Persuasion 15 vs 12 ✓, Persuasion 13 vs 14 ✗");
+        assert_eq!(summary[3], "  Borin: Intimidation 20 vs 15 ✓, Insight 13 vs 13 ✓");
+    }
+
+    #[test]
+    fn test_crafting_and_harvesting() {
+        use crate::crafting::{crafting_days, harvest, CraftingLedger};
+
+        let results = harvest("Dragon", 15).unwrap();
+        let gained: Vec<&str> = results.iter().filter(|r| r.2).map(|r| r.0).collect();
+        assert_eq!(gained, vec!["Dragon scale", "Dragon blood", "Dragon tooth"]);
+        assert!(harvest("kaiju", 20).is_err());
+        assert_eq!(crafting_days(50), 5);
+        assert_eq!(crafting_days(5), 1);
+
+        let mut smith = Character::new("Brenna");
+        smith.inventory = vec!["Dragon scale".to_string(), "Rope".to_string()];
+        let mut ledger = CraftingLedger::default();
+        assert!(ledger.start_project(&mut smith, "Scale mail", 3, &["Dragon tooth".to_string()]).is_err());
+        ledger.start_project(&mut smith, "Scale mail", 3, &["dragon scale".to_string()]).unwrap();
+        assert_eq!(smith.inventory, vec!["Rope".to_string()]);
+
+        let mut party = vec![smith];
+        assert!(ledger.pass_days(2, &mut party).is_empty());
+        assert_eq!(ledger.projects[0].progress(), "Scale mail for Brenna: 2/3 days");
+        let finished = ledger.pass_days(2, &mut party);
+        assert_eq!(finished.len(), 1);
+        assert!(ledger.projects.is_empty());
+        assert_eq!(party[0].inventory, vec!["Rope".to_string(), "Scale mail".to_string()]);
+        assert_eq!(ledger.date_label(), "Downtime day 4");
+
+        let path = std::env::temp_dir().join(format!("dnd_tools_downtime_{}", std::process::id())).join("downtime.ron");
+        ledger.save(&path).unwrap();
+        assert_eq!(CraftingLedger::load(&path).unwrap(), ledger);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_bastion_turns() {
+        use crate::bastion::{bastion_event, Bastion, Bastions, Facility, Order};
+        use rand::{SeedableRng, rngs::StdRng};
+
+        assert_eq!(Order::parse("TRADE"), Some(Order::Trade));
+        assert_eq!(Order::parse("pillage"), None);
+        assert!(bastion_event(1).starts_with("All is well"));
+        assert!(bastion_event(100).starts_with("Treasure"));
+
+        let mut bastions = Bastions::default();
+        bastions.add(Bastion::new("Thornkeep", "Brenna")).unwrap();
+        assert!(bastions.add(Bastion::new("thornkeep", "Someone")).is_err());
+        let keep = bastions.get_mut("THORNKEEP").unwrap();
+        keep.facilities.push(Facility { name: "Market".to_string(), hirelings: 2, order: Some(Order::Trade) });
+        keep.facilities.push(Facility { name: "Barracks".to_string(), hirelings: 0, order: Some(Order::Recruit) });
+        keep.facilities.push(Facility { name: "Garden".to_string(), hirelings: 1, order: None });
+
+        let mut rng = StdRng::seed_from_u64(11);
+        let report = keep.take_turn(&mut rng);
+        assert_eq!(keep.week, 1);
+        assert_eq!(keep.facility_mut("barracks").unwrap().hirelings, 1);
+        assert!(report.iter().any(|l| l.contains("Garden: idle")));
+        // Trade income is 10-60 gp per hireling, less 5 gp upkeep for each of the 4 hirelings
+        assert!((0..=100).contains(&keep.gold), "gold was {}", keep.gold);
+        assert!(keep.facilities.iter().all(|f| f.order.is_none()));
+
+        let path = std::env::temp_dir().join(format!("dnd_tools_bastions_{}", std::process::id())).join("bastions.ron");
+        bastions.save(&path).unwrap();
+        assert_eq!(Bastions::load(&path).unwrap(), bastions);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_sidekicks() {
+        use crate::sidekick::{party_level, Sidekick, SidekickClass, Sidekicks};
+
+        let mut fighter = Character::new("Brenna");
+        fighter.level = Some(5);
+        let mut wizard = Character::new("Lia");
+        wizard.level = Some(4);
+        assert_eq!(party_level(&[fighter, wizard]), 4);
+        assert_eq!(party_level(&[]), 1);
+
+        assert_eq!(SidekickClass::parse("Caster"), Some(SidekickClass::Spellcaster));
+        let guard = Sidekick::new("Rook", SidekickClass::Warrior, 11, 16);
+        // d10 averages 6 per level after the first
+        assert_eq!(guard.max_hp(1), 11);
+        assert_eq!(guard.max_hp(4), 29);
+        assert_eq!(Sidekick::proficiency_bonus(5), 3);
+        assert_eq!(guard.features(3), vec!["Martial Role", "Second Wind", "Improved Critical"]);
+
+        let combatant = guard.to_combatant(4, 14);
+        assert_eq!((combatant.max_hp, combatant.ac, combatant.initiative), (29, 16, 14));
+        assert!(combatant.is_player);
+
+        let mut sidekicks = Sidekicks::default();
+        sidekicks.add(guard).unwrap();
+        assert!(sidekicks.add(Sidekick::new("rook", SidekickClass::Expert, 9, 12)).is_err());
+        assert_eq!(sidekicks.get("ROOK").unwrap().summary(4), "Rook (Warrior 4, HP 29, AC 16, +2 prof)");
+        assert!(sidekicks.remove("Nobody").is_err());
+        sidekicks.remove("rook").unwrap();
+        assert!(sidekicks.sidekicks.is_empty());
+    }
+
+    #[test]
+    fn test_spell_components() {
+        use crate::components::{cast_spell, costly_component, item_value_gp};
+
+        assert_eq!(item_value_gp("Diamond (1,000 gp)"), Some(1000));
+        assert_eq!(item_value_gp("Rope"), None);
+        assert!(costly_component("revivify").unwrap().consumed);
+        assert!(costly_component("Fireball").is_none());
+
+        let mut cleric = Character::new("Mira");
+        cleric.inventory = vec!["Diamond (100 gp)".to_string(), "Diamond (500 gp)".to_string(), "Diamond (300 gp)".to_string(), "Pearl (100 gp)".to_string()];
+        assert_eq!(cast_spell(&mut cleric, "Fireball").unwrap(), "✨ Mira casts Fireball");
+
+        // The cheapest diamond that is good enough is used up
+        assert!(cast_spell(&mut cleric, "Revivify").unwrap().contains("Diamond (300 gp)"));
+        assert_eq!(cleric.inventory.len(), 3);
+        assert!(!cleric.inventory.contains(&"Diamond (300 gp)".to_string()));
+
+        // Identify needs its pearl but doesn't consume it
+        cast_spell(&mut cleric, "identify").unwrap();
+        assert!(cleric.inventory.contains(&"Pearl (100 gp)".to_string()));
+
+        cast_spell(&mut cleric, "Raise Dead").unwrap();
+        let error = cast_spell(&mut cleric, "Revivify").unwrap_err().to_string();
+        assert!(error.contains("Diamond (100 gp)"));
+        assert_eq!(cleric.inventory, vec!["Diamond (100 gp)".to_string(), "Pearl (100 gp)".to_string()]);
+    }
+
+    #[test]
+    fn test_ammunition_tracking() {
+        use crate::ammo::{ammo_for_weapon, expend_ammo, recover_ammo, set_ammo};
+        use rand::SeedableRng;
+
+        assert_eq!(ammo_for_weapon("Longbow"), Some("arrows"));
+        assert_eq!(ammo_for_weapon("hand crossbow"), Some("bolts"));
+        assert_eq!(ammo_for_weapon("javelin"), Some("javelins"));
+        assert_eq!(ammo_for_weapon("longsword"), None);
+
+        let mut ranger = Character::new("Lia");
+        set_ammo(&mut ranger, "shortbow", 2);
+        assert_eq!(ranger.ammo[0].kind, "arrows");
+        assert_eq!(expend_ammo(&mut ranger, "longsword").unwrap(), None);
+        assert_eq!(expend_ammo(&mut ranger, "longbow").unwrap(), Some(("arrows".to_string(), 1)));
+        assert_eq!(expend_ammo(&mut ranger, "arrows").unwrap(), Some(("arrows".to_string(), 0)));
+        assert!(expend_ammo(&mut ranger, "longbow").is_err());
+        // Untracked ammunition never blocks an attack
+        assert_eq!(expend_ammo(&mut ranger, "sling").unwrap(), None);
+
+        set_ammo(&mut ranger, "bolts", 0);
+        ranger.ammo[1].expended = 40;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let recovered = recover_ammo(&mut ranger, &mut rng);
+        assert_eq!(recovered.len(), 2);
+        let (kind, found, spent) = &recovered[1];
+        assert_eq!((kind.as_str(), *spent), ("bolts", 40));
+        assert!((10..=30).contains(found), "about half should come back, got {}", found);
+        assert_eq!(ranger.ammo[1].count, *found);
+        assert!(ranger.ammo.iter().all(|a| a.expended == 0));
+
+        // Older character files without ammo still load
+        let old_sheet = ron::ser::to_string(&Character::new("Old")).unwrap().replace(",ammo:[]", "");
+        assert!(!old_sheet.contains("ammo"));
+        let old: Character = ron::de::from_str(&old_sheet).unwrap();
+        assert!(old.ammo.is_empty());
+    }
+
+    #[test]
+    fn test_light_sources_and_vision() {
+        use crate::light::{darkvision_range, LightTracker, SECONDS_PER_ROUND};
+
+        let mut lights = LightTracker::default();
+        assert!(lights.light("glowstick", "Lia").is_err());
+        lights.light("Torch", "Lia").unwrap();
+        lights.light("lantern", "Brenna").unwrap();
+        lights.light("continual flame", "Mira").unwrap();
+        assert_eq!(lights.sources[0].describe(), "🔥 Lia's torch - 20 ft bright / 20 ft dim, 1h 00m left");
+
+        // 10 rounds is a minute
+        assert!(lights.pass_seconds(10 * SECONDS_PER_ROUND).is_empty());
+        let notices = lights.pass_seconds(49 * 60);
+        assert_eq!(notices, vec!["🕯️ Lia's torch is guttering - 10 minutes left".to_string()]);
+        let notices = lights.pass_seconds(10 * 60);
+        assert_eq!(notices, vec!["🌑 Lia's torch goes out".to_string()]);
+        assert_eq!(lights.sources.len(), 2);
+
+        // Continual flame never burns down
+        lights.pass_seconds(7 * 24 * 60 * 60);
+        assert_eq!(lights.sources.len(), 1);
+        assert_eq!(lights.douse("mira").len(), 1);
+        assert!(lights.sources.is_empty());
+
+        let mut dwarf = Character::new("Thrain");
+        dwarf.race = Some("Dwarf".to_string());
+        assert_eq!(darkvision_range(&dwarf), 60);
+        dwarf.set_stat("darkvision", "120 ft").unwrap();
+        assert_eq!(darkvision_range(&dwarf), 120);
+        assert_eq!(darkvision_range(&Character::new("Human")), 0);
+    }
+
+    #[test]
+    fn test_expedition_supplies() {
+        use crate::supplies::{Supplies, MAX_EXHAUSTION};
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let mut supplies = Supplies::default();
+        supplies.adjust("rations", 2).unwrap();
+        supplies.adjust("water", 4).unwrap();
+        supplies.adjust("mounts", 1).unwrap();
+        supplies.adjust("feed", 1).unwrap();
+        assert!(supplies.adjust("ale", 1).is_err());
+        assert_eq!(supplies.days_remaining(2, false), 1);
+
+        let mut party = vec![Character::new("Lia"), Character::new("Brenna")];
+        party[1].cons = Some(10);
+        assert!(supplies.travel_day(&mut party, 8, false, &mut rng).is_empty());
+        assert_eq!((supplies.rations, supplies.water, supplies.feed), (0, 2, 0));
+
+        // No food: hungry but fine for 3 days (CON +0), then exhaustion each day
+        for _ in 0..3 {
+            supplies.water = 2;
+            let notices = supplies.travel_day(&mut party, 8, false, &mut rng);
+            assert!(notices.iter().any(|n| n.contains("goes hungry")));
+            assert!(notices.iter().any(|n| n.contains("mount")));
+        }
+        assert_eq!(party[1].exhaustion, 0);
+        supplies.water = 2;
+        supplies.travel_day(&mut party, 8, false, &mut rng);
+        assert_eq!(party[1].exhaustion, 1);
+
+        // Eating resets the count; no water at all is automatic exhaustion
+        supplies.adjust("rations", 2).unwrap();
+        supplies.travel_day(&mut party, 8, false, &mut rng);
+        assert!(supplies.days_without_food.is_empty());
+        assert_eq!(party[1].exhaustion, 2);
+
+        // A long forced march eventually wears anyone down
+        supplies.adjust("rations", 100).unwrap();
+        supplies.adjust("water", 100).unwrap();
+        supplies.travel_day(&mut party, 24, false, &mut rng);
+        assert_eq!(party[1].exhaustion, MAX_EXHAUSTION);
+    }
+
+    #[test]
+    fn test_weighted_name_lists() {
+        use crate::names::{NameList, NameRegistry};
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let mut registry = NameRegistry::default();
+        // Unknown cultures fall back to the built-in common list
+        assert!(registry.generate(Some("Martian"), &mut rng).unwrap().contains(' '));
+
+        let dwarven = NameList::from_toml(r#"
+            culture = "Dwarven"
+            combine_chance = 0.5
+            names = [{ name = "Thorin", weight = 9 }, { name = "Dis" }]
+            [syllables]
+            prefixes = ["Bal", "Dur"]
+            suffixes = ["in", "rak"]
+            middle_chance = 0.0
+        "#).unwrap();
+        registry.add(dwarven);
+        assert!(registry.has_culture("dwarven"));
+
+        let names: Vec<String> = (0..200).map(|_| registry.generate(Some("DWARVEN"), &mut rng).unwrap()).collect();
+        let built = ["Balin", "Balrak", "Durin", "Durrak"];
+        assert!(names.iter().all(|n| n == "Thorin" || n == "Dis" || built.contains(&n.as_str())));
+        let thorins = names.iter().filter(|n| *n == "Thorin").count();
+        let dises = names.iter().filter(|n| *n == "Dis").count();
+        assert!(thorins > dises, "weight 9 should beat weight 1 ({} vs {})", thorins, dises);
+        assert!(names.iter().any(|n| built.contains(&n.as_str())));
+
+        let elvish = NameList::from_csv("Elvish", "weight,text\n3,Arwen\nLegolas\n");
+        assert_eq!(elvish.names.len(), 2);
+        assert_eq!(elvish.names[0].weight, 3);
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_names_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("elvish.csv"), "Arwen\n").unwrap();
+        std::fs::write(dir.join("broken.toml"), "names = 3").unwrap();
+        let errors = registry.load_dir(&dir);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(registry.generate(Some("elvish"), &mut rng).unwrap(), "Arwen");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_deity_reference() {
+        use crate::deities::Pantheon;
+
+        let mut pantheon = Pantheon::default();
+        assert_eq!(pantheon.get("athena").unwrap().domains, vec!["Knowledge".to_string(), "War".to_string()]);
+        let tempest: Vec<&str> = pantheon.search("tempest").iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(tempest, vec!["The Stormlord", "Zeus", "Poseidon"]);
+        assert_eq!(pantheon.search("owl").len(), 1);
+
+        let added = pantheon.add_toml(r#"
+            [[deities]]
+            name = "Zeus"
+            pantheon = "Homebrew"
+            alignment = "CG"
+            domains = ["Tempest", "Light"]
+            symbol = "A thunderhead"
+
+            [[deities]]
+            name = "Sera"
+            alignment = "LG"
+            domains = ["Light"]
+            symbol = "A silver bell"
+            tenets = ["Ring out the truth"]
+        "#).unwrap();
+        assert_eq!(added, 2);
+        assert_eq!(pantheon.get("Zeus").unwrap().pantheon.as_deref(), Some("Homebrew"));
+        assert_eq!(pantheon.search("zeus").len(), 1);
+        assert_eq!(pantheon.get("sera").unwrap().describe().last().unwrap(), "  • Ring out the truth");
+        assert!(pantheon.add_toml("[[deities]]\nname = \"Nameless\"").is_err());
+
+        let mut cleric = Character::new("Mira");
+        cleric.faith = Some("Sera".to_string());
+        let loaded: Character = ron::de::from_str(&ron::ser::to_string(&cleric).unwrap()).unwrap();
+        assert_eq!(loaded.faith.as_deref(), Some("Sera"));
+    }
+
+    #[test]
+    fn test_reference_cache() {
+        use crate::search::{condensed_lines, read_cached_page, write_cached_page, WikiPageContent};
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_cache_{}", std::process::id()));
+        let page = WikiPageContent {
+            index: "goblin-boss".to_string(),
+            name: "Goblin Boss".to_string(),
+            url: "http://dnd5e.wikidot.com/monster:goblin-boss".to_string(),
+            content: "Small humanoid\n\nArmor Class 17\nHit Points 21 (6d6)\nSpeed 30 ft.".to_string(),
+            content_type: "monster".to_string(),
+        };
+        assert!(read_cached_page(&dir, "monster", "Goblin Boss").is_none());
+        write_cached_page(&dir, "Goblin Boss", &page).unwrap();
+        assert_eq!(read_cached_page(&dir, "monster", "goblin boss").unwrap().content, page.content);
+
+        assert!(read_cached_page(&dir, "spell", "Goblin Boss").is_none());
+
+        assert_eq!(condensed_lines(&page.content, 10), vec!["Small humanoid", "Armor Class 17", "Hit Points 21 (6d6)", "Speed 30 ft."]);
+        let short = condensed_lines(&page.content, 2);
+        assert_eq!(short.len(), 3);
+        assert!(short[2].starts_with("..."));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_condition_reference() {
+        use crate::reference::{condition_text, quick_reference};
+
+        let (name, text) = condition_text("RESTRAINED").unwrap();
+        assert_eq!(name, "Restrained");
+        assert!(text.iter().any(|line| line.contains("disadvantage on Dexterity saving throws")));
+        assert_eq!(condition_text("para").unwrap().0, "Paralyzed");
+        // "p" is ambiguous between Paralyzed, Petrified, Poisoned and Prone
+        assert!(condition_text("p").is_none());
+
+        let lines = quick_reference("cond stunned").unwrap();
+        assert_eq!(lines[0], "📕 Stunned");
+        assert_eq!(lines.len(), 5);
+        assert!(lines[4].contains("SRD 5.1") && lines[4].contains("CC BY 4.0"));
+        assert!(quick_reference("cond").unwrap()[0].contains("Unconscious"));
+        assert!(quick_reference("cond sleepy").unwrap()[0].starts_with("❌"));
+        assert!(quick_reference("attack goblin").is_none());
+    }
+
+    #[test]
+    fn test_rules_reference() {
+        use crate::reference::{quick_reference, rule_text};
+
+        assert_eq!(rule_text("grapple").unwrap().0, "Grapple");
+        assert_eq!(rule_text("two-weapon").unwrap().0, "Two-Weapon Fighting");
+        assert_eq!(rule_text("two weapon fighting").unwrap().0, "Two-Weapon Fighting");
+        assert_eq!(rule_text("mount").unwrap().0, "Mounted Combat");
+        assert_eq!(rule_text("underwater").unwrap().0, "Underwater Combat");
+        // "combat" is a word of both Mounted Combat and Underwater Combat
+        assert!(rule_text("combat").is_none());
+        // "h" is ambiguous between Hide and Help
+        assert!(rule_text("h").is_none());
+
+        let lines = quick_reference("rule shove").unwrap();
+        assert_eq!(lines[0], "📘 Shove");
+        assert!(lines.iter().any(|line| line.contains("prone")));
+        assert!(quick_reference("rules").unwrap()[0].contains("Underwater Combat"));
+        assert!(quick_reference("rule flanking").unwrap()[0].starts_with("❌"));
+    }
+
+    #[test]
+    fn test_xp_cr_calculators() {
+        use crate::xp::*;
+
+        assert_eq!(xp_for_cr("5"), Some(1_800));
+        assert_eq!(xp_for_cr("1/4"), Some(50));
+        assert_eq!(xp_for_cr("0.5"), Some(100));
+        assert_eq!(xp_for_cr("31"), None);
+        assert_eq!(cr_for_xp(1_800), "5");
+        assert_eq!(cr_for_xp(2_000), "5");
+        assert_eq!(cr_for_xp(5), "0");
+
+        assert_eq!(encounter_multiplier(1, 4), 1.0);
+        assert_eq!(encounter_multiplier(4, 4), 2.0);
+        assert_eq!(encounter_multiplier(1, 6), 0.5);
+        assert_eq!(encounter_multiplier(20, 2), 5.0);
+        assert_eq!(adjusted_xp(4, &["2", "1/2", "1/2"]).unwrap(), (650, 2.0, 1_300));
+        assert!(adjusted_xp(4, &["dragon"]).is_err());
+
+        assert_eq!(format_xp(155_000), "155,000");
+        let reference = crate::reference::quick_reference;
+        assert_eq!(reference("xp for cr 5").unwrap(), vec!["⭐ CR 5 = 1,800 XP".to_string()]);
+        assert!(reference("cr for xp 1800").unwrap()[0].contains("CR 5"));
+        assert!(reference("XP MULT 4 2").unwrap()[0].ends_with("×2.5"));
+        assert!(reference("xp adjust 4 2 1/2 1/2").unwrap()[0].contains("1,300 adjusted XP"));
+    }
+
+    #[test]
+    fn test_xp_award_from_combat() {
+        use crate::xp::XpAward;
+
+        let mut tracker = CombatTracker::new();
+        for _ in 0..2 {
+            let mut goblin = Combatant::new_npc("Goblin".to_string(), 7, 15, 10);
+            goblin.cr = Some("1/4".to_string());
+            tracker.add_combatant(goblin);
+        }
+        let mut ogre = Combatant::new_npc("Ogre".to_string(), 59, 11, 8);
+        ogre.cr = Some("2".to_string());
+        tracker.add_combatant(ogre);
+        tracker.add_combatant(Combatant::new_npc("Bandit".to_string(), 11, 12, 9));
+        let party = vec!["Lia".to_string(), "Borin".to_string(), "Cade".to_string()];
+
+        // Nothing has gone down yet
+        assert!(XpAward::from_combat(&tracker, &party).is_none());
+
+        for combatant in tracker.combatants.iter_mut().filter(|c| c.name != "Ogre") {
+            combatant.current_hp = 0;
+        }
+        let award = XpAward::from_combat(&tracker, &party).unwrap();
+        // Two goblins at 50 XP each; the bandit has no CR and the ogre is still up
+        assert_eq!(award.total, 100);
+        assert_eq!(award.defeated.len(), 2);
+        assert_eq!(award.defeated[0].0, "Goblin#1");
+        assert_eq!(award.shares, vec![("Lia".to_string(), 33), ("Borin".to_string(), 33), ("Cade".to_string(), 33)]);
+        assert!(award.describe()[0].contains("100 XP"));
+        assert!(XpAward::from_combat(&tracker, &[]).is_none());
+    }
+
+    #[test]
+    fn test_npc_presets() {
+        use crate::npc_presets::{find_preset, parse_preset_args};
+
+        let (guard, name) = parse_preset_args("guard \"Gate Sergeant\"").unwrap();
+        assert_eq!(guard.role, "guard");
+        assert_eq!(name, "Gate Sergeant");
+        let (mage, name) = parse_preset_args("mage apprentice Tam").unwrap();
+        assert_eq!(mage.role, "mage apprentice");
+        assert_eq!(name, "Tam");
+        assert_eq!(find_preset("apprentice").unwrap().role, "mage apprentice");
+        assert_eq!(parse_preset_args("NOBLE").unwrap().1, "Noble");
+        assert!(parse_preset_args("dragon \"Smaug\"").is_err());
+
+        let character = guard.to_character("Gate Sergeant");
+        assert_eq!(character.ac, Some(16));
+        assert_eq!(character.max_hp, Some(11));
+        assert!(character.inventory.contains(&"Spear".to_string()));
+        assert!(character.desc.as_deref().unwrap().contains("1d6+1 piercing"));
+
+        let thug = find_preset("thug").unwrap().to_combatant("Knuckles", 12);
+        assert!(!thug.is_player);
+        assert_eq!(thug.cr.as_deref(), Some("1/2"));
+        assert_eq!((thug.current_hp, thug.ac), (32, 11));
+        assert!(find_preset("priest").unwrap().describe("Brother Aldo").iter().any(|line| line.contains("Spirit Guardians")));
+    }
+
+    #[test]
+    fn test_class_resources() {
+        use crate::resources::*;
+
+        let mut monk = Character::new("Kai");
+        monk.class = Some("Monk".to_string());
+        monk.level = Some(5);
+        refresh_resources(&mut monk);
+        assert_eq!(monk.resources, vec![ClassResource::new("Ki", 5, Recharge::ShortRest)]);
+
+        assert_eq!(use_resource(&mut monk, "ki", 2).unwrap().current, 3);
+        assert!(use_resource(&mut monk, "ki", 4).is_err());
+        assert!(use_resource(&mut monk, "rage", 1).is_err());
+
+        // Levelling up keeps what's been spent
+        monk.level = Some(6);
+        refresh_resources(&mut monk);
+        assert_eq!((monk.resources[0].current, monk.resources[0].max), (4, 6));
+
+        add_custom_resource(&mut monk, "Luck", 3, Recharge::LongRest);
+        use_resource(&mut monk, "luck", 1).unwrap();
+        refresh_resources(&mut monk);
+        assert_eq!(monk.resources.len(), 2);
+        assert_eq!(rest(&mut monk, Recharge::ShortRest), vec!["Ki".to_string()]);
+        assert_eq!(monk.resources[1].current, 2);
+        assert_eq!(rest(&mut monk, Recharge::LongRest), vec!["Luck".to_string()]);
+
+        let mut barbarian = Character::new("Grog");
+        barbarian.class = Some("barbarian".to_string());
+        barbarian.level = Some(20);
+        refresh_resources(&mut barbarian);
+        assert_eq!(use_resource(&mut barbarian, "rage", 1).unwrap().describe(), "Rage: unlimited");
+
+        let mut fighter = Character::new("Vex");
+        fighter.class = Some("Fighter".to_string());
+        fighter.level = Some(2);
+        refresh_resources(&mut fighter);
+        use_resource(&mut fighter, "action", 1).unwrap();
+        assert_eq!(fighter.resources[1].describe(), "Action Surge: 0/1 ○ (short rest)");
+    }
+
+    #[test]
+    fn test_warlock_pact_magic() {
+        use crate::pact_magic::*;
+
+        assert_eq!(pact_slots_for_level(1), (1, 1));
+        assert_eq!(pact_slots_for_level(5), (2, 3));
+        assert_eq!(pact_slots_for_level(11), (3, 5));
+        assert_eq!(pact_slots_for_level(20), (4, 5));
+        assert_eq!(invocations_known(1), 0);
+        assert_eq!(invocations_known(5), 3);
+
+        let mut warlock = Character::new("Hex");
+        warlock.class = Some("Warlock".to_string());
+        warlock.level = Some(2);
+        assert_eq!(invocations_due(&warlock), 2);
+        assert!(learn_invocation(&mut warlock, "Thirsting Blade").is_err());
+        assert_eq!(learn_invocation(&mut warlock, "agonizing blast").unwrap(), "Agonizing Blast");
+        assert!(learn_invocation(&mut warlock, "Agonizing Blast").is_err());
+        learn_invocation(&mut warlock, "Devil's Sight").unwrap();
+        assert!(learn_invocation(&mut warlock, "Mask of Many Faces").is_err());
+        assert_eq!(invocations_due(&warlock), 0);
+
+        assert_eq!(spend_pact_slot(&mut warlock).unwrap().current, 1);
+        spend_pact_slot(&mut warlock).unwrap();
+        assert!(spend_pact_slot(&mut warlock).is_err());
+        assert!(rest_pact_slots(&mut warlock));
+        assert_eq!(warlock.pact_slots, Some(PactSlots { max: 2, current: 2, slot_level: 1 }));
+
+        warlock.level = Some(3);
+        refresh_pact_slots(&mut warlock);
+        assert_eq!(warlock.pact_slots.unwrap().slot_level, 2);
+
+        let mut fighter = Character::new("Vex");
+        assert!(spend_pact_slot(&mut fighter).is_err());
+        assert!(fighter.pact_slots.is_none());
+    }
+
+    #[test]
+    fn test_prepared_caster_preparation() {
+        use crate::prepared::{Preparation, max_prepared};
+
+        let mut cleric = Character::new("Aldo");
+        cleric.class = Some("Cleric".to_string());
+        cleric.level = Some(1);
+        cleric.wisd = Some(14);
+        cleric.spells = ["Bless", "Cure Wounds", "Guiding Bolt", "Sanctuary"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(max_prepared(&cleric), Some(3));
+        assert_eq!(max_prepared(&Character::new("Vex")), None);
+        assert!(Preparation::start(&Character::new("Vex")).is_err());
+
+        let mut preparation = Preparation::start(&cleric).unwrap();
+        assert_eq!(preparation.toggle("bless").unwrap(), ("Bless".to_string(), true));
+        preparation.toggle("2").unwrap();
+        preparation.toggle("Guiding Bolt").unwrap();
+        preparation.toggle("4").unwrap();
+        assert!(preparation.toggle("Fireball").is_err());
+        assert!(preparation.toggle("9").is_err());
+        // Four is one over the limit, so it won't save
+        assert!(preparation.validate().is_err());
+        assert!(preparation.clone().save(&mut cleric).is_err());
+        assert!(cleric.prepared.is_empty());
+
+        assert!(!preparation.toggle("Bless").unwrap().1);
+        assert!(preparation.lines()[0].contains("3/3"));
+        preparation.save(&mut cleric).unwrap();
+        assert_eq!(cleric.prepared, vec!["Cure Wounds", "Guiding Bolt", "Sanctuary"]);
+        assert_eq!(Preparation::start(&cleric).unwrap().selected.len(), 3);
+    }
+
+    #[test]
+    fn test_wild_shape_and_polymorph_forms() {
+        use crate::monsters::{MonsterIndex, wild_shape_limits};
+
+        let mut index = MonsterIndex::default();
+        assert!(wild_shape_limits(1, false).is_err());
+        assert_eq!(crate::xp::cr_label(0.25), "1/4");
+        assert_eq!(crate::xp::cr_label(2.0), "2");
+
+        let level_two: Vec<&str> = index.wild_shape_forms(2, false).unwrap().iter().map(|m| m.name.as_str()).collect();
+        assert!(level_two.contains(&"Wolf"));
+        assert!(!level_two.contains(&"Giant Frog"), "no swimming forms before level 4");
+        assert!(!level_two.contains(&"Black Bear"), "CR 1/2 is too high at level 2");
+
+        let level_four: Vec<&str> = index.wild_shape_forms(4, false).unwrap().iter().map(|m| m.name.as_str()).collect();
+        assert!(level_four.contains(&"Crocodile") && level_four.contains(&"Black Bear"));
+        assert!(!level_four.contains(&"Giant Wasp"), "no flying forms before level 8");
+        assert_eq!(level_four.first(), Some(&"Baboon"));
+
+        let moon: Vec<&str> = index.wild_shape_forms(6, true).unwrap().iter().map(|m| m.name.as_str()).collect();
+        assert!(moon.contains(&"Polar Bear"));
+        assert!(!moon.contains(&"Giant Eagle"));
+
+        index.add_toml("[[monsters]]\nname = \"Owlbear\"\nkind = \"monstrosity\"\ncr = \"3\"").unwrap();
+        assert!(index.add_toml("[[monsters]]\nname = \"Blob\"\ncr = \"99\"").is_err());
+        let polymorph: Vec<&str> = index.polymorph_forms(4, false).iter().map(|m| m.name.as_str()).collect();
+        assert!(polymorph.contains(&"Elephant") && !polymorph.contains(&"Owlbear"));
+        assert!(index.polymorph_forms(4, true).iter().any(|m| m.name == "Owlbear"));
+        assert!(!index.polymorph_forms(4, true).iter().any(|m| m.name == "Giant Crocodile"));
+    }
+
+    #[test]
+    fn test_companions() {
+        use crate::companions::{add_companion, find_companion_mut, find_owner, Companion, CompanionKind};
+
+        let mut ranger = Character::new("Kai");
+        let mut fang = Companion::new("Fang", CompanionKind::parse("beast").unwrap(), 11, 13);
+        fang.creature = Some("wolf".to_string());
+        add_companion(&mut ranger, fang.clone()).unwrap();
+        assert!(add_companion(&mut ranger, fang).is_err(), "names are unique per owner");
+
+        find_companion_mut(&mut ranger, "fang").unwrap().attacks.push("Bite +4 2d4+2".to_string());
+        assert!(find_companion_mut(&mut ranger, "Hoot").is_err());
+        let lines = ranger.companions[0].describe();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("Fang (beast companion wolf, HP 11/11, AC 13)"));
+
+        let party = vec![Character::new("Lia"), ranger];
+        let (owner, companion) = find_owner(&party, "FANG").unwrap();
+        assert_eq!(owner.name, "Kai");
+        let combatant = companion.to_combatant(&owner.name, 15);
+        assert_eq!(combatant.owner.as_deref(), Some("Kai"));
+        assert!(combatant.is_player);
+        assert_eq!((combatant.max_hp, combatant.ac, combatant.initiative), (11, 13, 15));
+        assert!(find_owner(&party, "Hoot").is_none());
+    }
+
+    #[test]
+    fn test_roll_result_breakdown() {
+        use crate::dice::{roll_dice_with_crits, RollResult};
+
+        let roll = roll_dice_with_crits("2d6+3").unwrap();
+        assert_eq!((roll.count, roll.sides, roll.modifier), (2, 6, 3));
+        assert_eq!(roll.total, roll.kept.iter().map(|&d| d as i32).sum::<i32>() + 3);
+        assert_eq!(roll.to_string(), format!("2d6 ({},{}) + 3 = {}", roll.kept[0], roll.kept[1], roll.total));
+
+        let stats = roll_dice_with_crits("r4d6kh3").unwrap();
+        assert_eq!((stats.kept.len(), stats.dropped.len()), (3, 1));
+        assert!(stats.kept.iter().all(|&d| d >= stats.dropped[0]));
+        assert!(stats.to_string().contains(", dropped "));
+        assert!(roll_dice_with_crits("2d20kl1").unwrap().kept.len() == 1);
+        assert!(roll_dice_with_crits("2d6kh3").is_err());
+        assert!(roll_dice_with_crits("1d8+x").is_err());
+
+        let save = RollResult { count: 1, sides: 20, kept: vec![12], dropped: vec![], rerolled: vec![], explodes: false, modifier: 0, total: 12, crit: None }.plus(-2);
+        assert_eq!(save.to_string(), "1d20 (12) - 2 = 10");
+        assert_eq!(save.natural(), 12);
+        assert!(!save.is_critical_success());
+        let nat20 = RollResult { count: 2, sides: 20, kept: vec![20], dropped: vec![3], rerolled: vec![], explodes: false, modifier: 5, total: 25, crit: None };
+        assert_eq!(nat20.to_string(), "2d20 (20, dropped 3) + 5 = 25");
+        assert!(nat20.is_critical_success());
+    }
+
+    #[test]
+    fn test_percentile_dice_and_table_lookup() {
+        use crate::dice::{is_percentile, roll_dice_with_crits, roll_percentile, PercentileRoll};
+        use crate::tables::{RandomTable, TableRegistry};
+
+        assert_eq!(PercentileRoll { tens: 0, ones: 0 }.total(), 100);
+        assert_eq!(PercentileRoll { tens: 0, ones: 7 }.total(), 7);
+        assert_eq!(PercentileRoll { tens: 4, ones: 0 }.to_string(), "d% (40, 0) = 40");
+        assert_eq!(PercentileRoll { tens: 0, ones: 0 }.to_string(), "d% (00, 0) = 100");
+        assert!((1..=100).contains(&roll_percentile().total()));
+        assert!(is_percentile("d%") && is_percentile("1d100") && !is_percentile("2d10"));
+        assert_eq!(roll_dice_with_crits("d%").unwrap().sides, 100);
+        assert_eq!(roll_dice_with_crits("d20").unwrap().count, 1);
+
+        let mut registry = TableRegistry::new();
+        registry.add(RandomTable::from_csv("Reaction Table", "15,Hostile\n30,Wary\n55,Friendly"));
+        assert_eq!(registry.lookup("reaction-table", 1).unwrap(), "Hostile");
+        assert_eq!(registry.lookup("Reaction Table", 15).unwrap(), "Hostile");
+        assert_eq!(registry.lookup("reaction-table", 16).unwrap(), "Wary");
+        assert_eq!(registry.lookup("reaction-table", 100).unwrap(), "Friendly");
+        assert!(registry.lookup("reaction-table", 101).is_err());
+        assert!(registry.lookup("missing", 5).is_err());
+    }
+
+    #[test]
+    fn test_roll_expression_math() {
+        use crate::dice::roll_expression;
+
+        let doubled = roll_expression("(1d8+4)*2").unwrap();
+        let die = doubled.dice[0].total;
+        assert_eq!(doubled.total, (die + 4) * 2);
+        assert_eq!(doubled.to_string(), format!("(1d8 ({}) + 4 = {}) * 2 = {}", die, die + 4, doubled.total));
+
+        let halved = roll_expression("floor(8d6/2)").unwrap();
+        assert_eq!(halved.dice[0].kept.len(), 8);
+        assert_eq!(halved.total, halved.dice[0].total / 2);
+        assert!(halved.breakdown.starts_with("floor(8d6 ("));
+
+        assert_eq!(roll_expression("2 + 3 * 4").unwrap().total, 14);
+        assert_eq!(roll_expression("7/2").unwrap().total, 3);
+        assert_eq!(roll_expression("ceil(7/2)").unwrap().to_string(), "ceil(7 / 2 = 3.5) = 4");
+        assert_eq!(roll_expression("round(7/2)").unwrap().to_string(), "round(7 / 2 = 3.5) = 4");
+        let rounded = roll_expression("round(8d6/2)").unwrap();
+        assert_eq!(rounded.total, (rounded.dice[0].total as f64 / 2.0).round() as i32);
+        assert_eq!(roll_expression("r(1d1+4)*2").unwrap().total, 10);
+        // The CLI's r prefix goes in front of functions too
+        assert_eq!(roll_expression("rround(7/2)").unwrap().total, 4);
+        assert_eq!(roll_expression("r round(7/2)").unwrap().total, 4);
+        assert_eq!(roll_expression("rceil(7/2)").unwrap().total, 4);
+        let prefixed = roll_expression("rfloor(8d6/2)").unwrap();
+        assert_eq!(prefixed.total, prefixed.dice[0].total / 2);
+        assert_eq!(roll_expression("-(2+3)").unwrap().total, -5);
+        let mixed = roll_expression("r2d6 + 1d4 + 3").unwrap();
+        assert_eq!(mixed.dice.len(), 2);
+        assert!(mixed.crit().is_none());
+        assert_eq!(roll_expression("4d6kh3").unwrap().dice[0].dropped.len(), 1);
+
+        assert!(roll_expression("(1d8+4").is_err());
+        assert!(roll_expression("1d8 / 0").is_err());
+        assert!(roll_expression("sqrt(4)").is_err());
+        assert!(roll_expression("2 +").is_err());
+        assert!(roll_expression("").is_err());
+    }
+
+    #[test]
+    fn test_dc_presets() {
+        use crate::character::AbilityScore;
+        use crate::dc::{check_ability, quick_command, DcBands};
+
+        let bands = DcBands::default();
+        assert_eq!(bands.preset("easy"), Some(10));
+        assert_eq!(bands.preset("very-hard"), Some(25));
+        assert_eq!(bands.preset("Nearly Impossible"), Some(30));
+        assert_eq!(bands.resolve("17").unwrap(), 17);
+        assert!(bands.resolve("brutal").is_err());
+        assert_eq!(quick_command(&bands, &["hard"]), vec!["🎯 hard: DC 20".to_string()]);
+        assert_eq!(quick_command(&bands, &[]).len(), 7);
+
+        let custom = DcBands::from_toml("easy = 8\nhard = 18").unwrap();
+        assert_eq!((custom.easy, custom.medium, custom.hard), (8, 15, 18));
+        assert!(DcBands::from_toml("easy = 22").is_err(), "bands must rise with difficulty");
+
+        assert_eq!(check_ability("athletics"), Some(("Athletics", AbilityScore::Strength)));
+        assert_eq!(check_ability("sleight of hand"), Some(("Sleight of Hand", AbilityScore::Dexterity)));
+        assert_eq!(check_ability("animal-handling").map(|(_, a)| a), Some(AbilityScore::Wisdom));
+        assert_eq!(check_ability("wis"), Some(("Wisdom", AbilityScore::Wisdom)));
+        assert!(check_ability("juggling").is_none());
+    }
+
+    #[test]
+    fn test_combatants_share_registry_sheets() {
+        use crate::registry::{by_id, insert, register};
+
+        let mut characters = vec![Character::new("Bram"), Character::new("Lia")];
+        register(&mut characters);
+        assert_eq!((characters[0].id, characters[1].id), (1, 2));
+        characters[1].id = 1;
+        register(&mut characters);
+        assert_ne!(characters[0].id, characters[1].id, "duplicate ids are replaced");
+        let mut cleric = Character::new("Aldo");
+        cleric.hp = Some(20);
+        cleric.max_hp = Some(20);
+        let aldo = insert(&mut characters, cleric);
+        assert_eq!(by_id(&characters, aldo).unwrap().name, "Aldo");
+
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::from_character(by_id(&characters, aldo).unwrap(), 12));
+        assert_eq!(tracker.combatants[0].sheet(&characters).unwrap().name, "Aldo");
+
+        // A fight's damage and conditions reach the sheet...
+        tracker.combatants[0].current_hp = 7;
+        tracker.combatants[0].status_effects.push(StatusEffect::new("Poisoned", None));
+        tracker.write_back(&mut characters);
+        let sheet = by_id(&characters, aldo).unwrap();
+        assert_eq!(sheet.hp, Some(7));
+        assert_eq!(sheet.conditions, vec!["Poisoned".to_string()]);
+
+        // ...and changes to the sheet reach the fight
+        crate::registry::by_id_mut(&mut characters, aldo).unwrap().hp = Some(15);
+        crate::registry::by_id_mut(&mut characters, aldo).unwrap().conditions.push("Blinded".to_string());
+        tracker.refresh_linked(&characters);
+        assert_eq!(tracker.combatants[0].current_hp, 15);
+        assert_eq!(tracker.combatants[0].status_effects.len(), 2);
+
+        let preset = crate::npc_presets::find_preset("guard").unwrap().to_combatant("Gate Guard", 10);
+        assert!(preset.character_id.is_none() && !preset.is_player);
+        assert_eq!(preset.sheet(&characters).unwrap().name, "Gate Guard");
+    }
+
+    #[test]
+    fn test_character_store_shares_updates() {
+        use crate::tui::App;
+
+        let store = CharacterStore::new(vec![Character::new("Bram")]);
+        let app = App::new(store.clone());
+        let lia = store.insert(Character::new("Lia"));
+        assert_eq!(app.characters.len(), 2, "every handle sees an insert");
+        assert_eq!(app.characters.get(lia).unwrap().name, "Lia");
+
+        app.characters.find_mut("bram").unwrap().hp = Some(4);
+        assert_eq!(store.find("Bram").unwrap().hp, Some(4));
+
+        // A fight writes back into the same sheets the menus read
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::from_character(&store.find("Lia").unwrap(), 10));
+        tracker.combatants[0].current_hp = 1;
+        tracker.write_back(&mut store.write());
+        assert_eq!(app.characters.get(lia).unwrap().hp, Some(1));
+
+        assert_eq!(store.remove("Bram").map(|c| c.name), Some("Bram".to_string()));
+        assert!(app.characters.find("Bram").is_none());
+    }
+
+    #[test]
+    fn test_prefetch_targets() {
+        use crate::search::{prefetch_targets, PrefetchReport, SearchCategory};
+
+        let mut wizard = Character::new("Lia");
+        wizard.spells = vec!["Fireball".to_string(), "Shield".to_string()];
+        let mut cleric = Character::new("Aldo");
+        cleric.spells = vec!["fireball".to_string()];
+        let combatants = vec![
+            Combatant::from_character(&wizard, 14),
+            Combatant::new_npc("Goblin 1".to_string(), 7, 13, 12),
+            Combatant::new_npc("Goblin 2".to_string(), 7, 13, 9),
+            Combatant::new_npc("Ogre".to_string(), 59, 11, 5),
+        ];
+        let targets = prefetch_targets(&[wizard, cleric], &combatants);
+        assert_eq!(targets, vec![
+            (SearchCategory::Spells, "Fireball".to_string()),
+            (SearchCategory::Spells, "Shield".to_string()),
+            (SearchCategory::Monsters, "Goblin".to_string()),
+            (SearchCategory::Monsters, "Ogre".to_string()),
+        ]);
+
+        let partial = PrefetchReport { already_cached: 1, fetched: 2, failed: vec!["Shield".to_string()] };
+        assert!(partial.summary().contains("3 page(s) ready") && partial.summary().ends_with("couldn't fetch Shield"));
+    }
+
+    #[test]
+    fn test_search_cache_compression_and_eviction() {
+        use crate::cache::{evict, read_entry, write_entry, CacheStats};
+        use std::time::{Duration, SystemTime};
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_cache_eviction_{}", std::process::id()));
+        let text = format!("http://dnd5e.wikidot.com/spell:fireball\nFireball\n{}", "A bright streak flashes. ".repeat(200));
+        let fireball = dir.join("spell").join("fireball.txt");
+        write_entry(&fireball, &text).unwrap();
+        let stored = std::fs::metadata(dir.join("spell").join("fireball.txt.gz")).unwrap().len();
+        assert!(stored < text.len() as u64 / 4, "entries are gzipped");
+        assert_eq!(read_entry(&fireball).as_deref(), Some(text.as_str()));
+
+        // Plain entries from before compression still load
+        let goblin = dir.join("monster").join("goblin.txt");
+        std::fs::create_dir_all(goblin.parent().unwrap()).unwrap();
+        std::fs::write(&goblin, "url\nGoblin\nSmall humanoid").unwrap();
+        assert_eq!(read_entry(&goblin).as_deref(), Some("url\nGoblin\nSmall humanoid"));
+
+        let stats = CacheStats::collect(&dir, 1024 * 1024, None);
+        assert_eq!((stats.entries, stats.by_type.clone()), (2, vec![("monster".to_string(), 1), ("spell".to_string(), 1)]));
+        assert!(stats.lines()[0].contains("2 page(s)"));
+
+        // The least recently used entry goes first
+        let old = SystemTime::now() - Duration::from_secs(3600);
+        std::fs::File::options().write(true).open(&goblin).unwrap().set_modified(old).unwrap();
+        assert_eq!(evict(&dir, stored), 1);
+        assert!(!goblin.exists() && read_entry(&fireball).is_some());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_search_cache_expiry() {
+        use crate::cache::{clear, entries_for, is_expired, written_at, write_entry, CacheStats};
+        use std::time::{Duration, SystemTime};
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_cache_ttl_{}", std::process::id()));
+        let month = Some(Duration::from_secs(30 * 24 * 60 * 60));
+        let fireball = dir.join("spell").join("fireball.txt");
+        write_entry(&fireball, "url\nFireball\nA bright streak").unwrap();
+        // Reading an entry marks it used, but its written time stays in the gzip header
+        let written = written_at(&fireball).unwrap();
+        assert!(written.elapsed().unwrap() < Duration::from_secs(60));
+        assert!(!is_expired(&fireball, month));
+
+        // A plain entry from an older version goes by its file time
+        let elemental = dir.join("monster").join("fireball.txt");
+        std::fs::create_dir_all(elemental.parent().unwrap()).unwrap();
+        std::fs::write(&elemental, "url\nFireball\nA fire elemental").unwrap();
+        let old = SystemTime::now() - Duration::from_secs(40 * 24 * 60 * 60);
+        std::fs::File::options().write(true).open(&elemental).unwrap().set_modified(old).unwrap();
+        assert!(is_expired(&elemental, month));
+        assert!(!is_expired(&elemental, None), "a TTL of 0 days keeps pages forever");
+
+        let stats = CacheStats::collect(&dir, 1024 * 1024, month);
+        assert_eq!((stats.entries, stats.expired), (2, 1));
+        assert!(stats.lines().last().unwrap().contains("1 older than 30 days"));
+
+        // `cache refresh fireball` finds both copies
+        let mut copies = entries_for(&dir, "fireball");
+        copies.sort();
+        assert_eq!(copies, vec![elemental.clone(), fireball.clone()]);
+        assert_eq!(clear(&dir), 2);
+        assert!(entries_for(&dir, "fireball").is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_content_normalization() {
+        use crate::content::{Content, Item, Monster, Source};
+        use crate::search::WikiPageContent;
+
+        // The parser's snapshot output is exactly what the cache holds for these pages
+        let page = |name: &str, kind: &str, snapshot: &str| WikiPageContent {
+            index: name.to_lowercase(),
+            name: name.to_string(),
+            url: format!("http://dnd5e.wikidot.com/{}:{}", kind, name.to_lowercase()),
+            content: snapshot.split_once("---\n").unwrap().1.to_string(),
+            content_type: kind.to_string(),
+        };
+        let fireball = page("Fireball", "spell", include_str!("../test_fixtures/wikidot/spell_fireball.snap"));
+        let Ok(Content::Spell(spell)) = Content::from_page(&fireball) else { panic!("fireball should be a spell") };
+        assert_eq!((spell.level, spell.school.as_str()), (3, "evocation"));
+        assert_eq!((spell.casting_time.as_str(), spell.range.as_str(), spell.duration.as_str()), ("1 action", "150 feet", "Instantaneous"));
+        assert_eq!(spell.classes, vec!["Sorcerer".to_string(), "Wizard".to_string()]);
+        assert!(spell.higher_levels.unwrap().contains("increases by 1d6"));
+        assert!(spell.description.starts_with("A bright streak"));
+        assert_eq!(spell.source, Source::Wikidot { url: fireball.url.clone() });
+
+        let goblin = page("Goblin", "monster", include_str!("../test_fixtures/wikidot/monster_goblin_main_content.snap"));
+        let monster = Monster::from_page(&goblin);
+        assert_eq!((monster.size.as_deref(), monster.kind.as_str(), monster.alignment.as_deref()), (Some("Small"), "humanoid", Some("neutral evil")));
+        assert_eq!((monster.ac, monster.hp, monster.hit_dice.as_deref(), monster.speed.as_deref()), (Some(15), Some(7), Some("2d6"), Some("30 ft.")));
+        assert_eq!(monster.actions.len(), 2);
+        assert_eq!(monster.card_lines()[1], "AC 15 · HP 7 (2d6) · Speed 30 ft.");
+
+        // Local backends map into the same shapes
+        let index = crate::monsters::MonsterIndex::default();
+        let bat = Monster::from(index.monsters.iter().find(|m| m.name == "Bat").unwrap());
+        assert!(bat.fly && bat.source == Source::Srd);
+        let guard = Monster::from(crate::npc_presets::find_preset("guard").unwrap());
+        assert_eq!((guard.ac, guard.cr.as_deref()), (Some(16), Some("1/8")));
+        let diamond = Item::from(crate::components::costly_component("Revivify").unwrap());
+        assert_eq!((diamond.cost_gp, diamond.category.as_str()), (Some(300), "material component"));
+    }
+
+    #[test]
+    fn test_content_licensing() {
+        use crate::content::{license_notice, License, Source};
+
+        let wiki = Source::Wikidot { url: "http://dnd5e.wikidot.com/spell:fireball".to_string() };
+        assert_eq!(wiki.license(), License::CcBySa30);
+        assert_eq!(wiki.attribution(), "From http://dnd5e.wikidot.com/spell:fireball · CC BY-SA 3.0");
+        assert_eq!(Source::Srd.license(), License::CcBy40);
+        assert_eq!(Source::Campaign.license(), License::Homebrew);
+
+        let goblin = crate::monsters::MonsterEntry { name: "Cave Goblin".to_string(), kind: "humanoid".to_string(), cr: "1/4".to_string(), swim: false, fly: false };
+        assert_eq!(crate::content::Monster::from(&goblin).source, Source::Campaign);
+
+        // One notice per license, however many entries share it
+        let notice = license_notice(&[wiki.clone(), Source::Srd, wiki]);
+        assert_eq!(notice.matches("Creative Commons").count(), 2);
+        assert!(notice.contains("System Reference Document 5.1") && notice.contains("dnd5e.wikidot.com"));
+        assert!(!notice.contains("Homebrew"));
+
+        let credits = crate::reference::quick_reference("credits").unwrap();
+        assert!(credits.iter().any(|line| line.trim() == "CC BY-SA 3.0") && credits.iter().any(|line| line.trim() == "Homebrew"));
+    }
+
+    #[test]
+    fn test_campaign_export_and_import() {
+        use crate::campaign::{export_campaign_in, import_campaign_in};
+        use std::fs;
+
+        let base = std::env::temp_dir().join(format!("dnd_tools_campaign_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let dm = base.join("old_dm");
+        for dir in ["characters/trash", "npcs", "campaign/tables", "cache/spell"] {
+            fs::create_dir_all(dm.join(dir)).unwrap();
+        }
+        fs::write(dm.join("characters/Aria.txt"), "(name: \"Aria\")").unwrap();
+        fs::write(dm.join("characters/trash/Old.txt"), "(name: \"Old\")").unwrap();
+        fs::write(dm.join("npcs/Bartender.ron"), "(name: \"Bartender\")").unwrap();
+        fs::write(dm.join("campaign/tables/loot.toml"), "name = \"Loot\"").unwrap();
+        fs::write(dm.join("cache/spell/fireball.gz"), "copyrighted").unwrap();
+
+        let archive = export_campaign_in(&dm, &base.join("out"), "Curse of Strahd!").unwrap();
+        assert!(archive.ends_with("curse-of-strahd.campaign.zip"));
+        let zip = zip::ZipArchive::new(fs::File::open(&archive).unwrap()).unwrap();
+        let names: Vec<String> = zip.file_names().map(String::from).collect();
+        for expected in ["campaign.toml", "NOTICE.txt", "characters/Aria.txt", "npcs/Bartender.ron", "campaign/tables/loot.toml"] {
+            assert!(names.iter().any(|n| n == expected), "missing {}", expected);
+        }
+        assert!(!names.iter().any(|n| n.starts_with("cache/") || n.contains("trash")));
+        // Exporting names the campaign, unless it already has a name
+        assert_eq!(crate::campaign::campaign_name_in(&dm).as_deref(), Some("Curse of Strahd!"));
+
+        // The new DM's own copy of a file is kept
+        let new_dm = base.join("new_dm");
+        fs::create_dir_all(new_dm.join("npcs")).unwrap();
+        fs::write(new_dm.join("npcs/Bartender.ron"), "(name: \"Mine\")").unwrap();
+        let report = import_campaign_in(&new_dm, &archive).unwrap();
+        assert_eq!(report.campaign, "Curse of Strahd!");
+        assert_eq!(report.imported.len(), 3);
+        assert_eq!(crate::campaign::campaign_name_in(&new_dm).as_deref(), Some("Curse of Strahd!"));
+        assert_eq!(report.skipped, vec!["npcs/Bartender.ron".to_string()]);
+        assert!(fs::read_to_string(new_dm.join("npcs/Bartender.ron")).unwrap().contains("Mine"));
+        assert!(new_dm.join("campaign/tables/loot.toml").exists());
+        assert!(!new_dm.join("campaign.toml").exists() && !new_dm.join("NOTICE.txt").exists());
+
+        // Renaming replaces the recorded name, which exporting and importing keep
+        crate::campaign::set_campaign_name_in(&new_dm, "Tomb of Annihilation").unwrap();
+        assert_eq!(crate::campaign::campaign_name_in(&new_dm).as_deref(), Some("Tomb of Annihilation"));
+        assert!(crate::campaign::set_campaign_name_in(&new_dm, "  ").is_err());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_character_death_and_legacy() {
+        use crate::legacy::{civil_date, Legacy};
+        use std::fs;
+
+        assert_eq!(civil_date(0), "1970-01-01");
+        assert_eq!(civil_date(1_700_000_000), "2023-11-14");
+
+        let base = std::env::temp_dir().join(format!("dnd_tools_legacy_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        fs::create_dir_all(base.join("characters")).unwrap();
+        fs::write(base.join("characters/Aria.txt"), "(name: \"Aria\")").unwrap();
+        let (characters_dir, archive_dir, session_log) = (base.join("characters"), base.join("characters/archive"), base.join("campaign/session_log.txt"));
+        let legacy = Legacy { characters_dir: &characters_dir, archive_dir: &archive_dir, session_log: &session_log };
+        assert_eq!(legacy.fallen_lines(), vec!["No fallen characters yet.".to_string()]);
+
+        let mut aria = Character::new("Aria");
+        aria.class = Some("Fighter".to_string());
+        aria.level = Some(5);
+        aria.inventory = vec!["Longsword".to_string(), "Rope".to_string()];
+        aria.conditions = vec!["Poisoned".to_string()];
+        let mut stash = vec!["Gold idol".to_string()];
+        let lines = legacy.lay_to_rest(aria, "crushed by an ogre", Some("Downtime day 12"), Some(&mut stash)).unwrap();
+        assert!(lines[0].contains("Aria, level 5 Fighter has died: crushed by an ogre"));
+        assert_eq!(stash, vec!["Gold idol", "Longsword", "Rope"]);
+
+        // The live sheet is gone and the archived one carries the death record
+        assert!(!base.join("characters/Aria.txt").exists());
+        let archived = crate::file_manager::read_character_file(&archive_dir.join("Aria.json")).unwrap();
+        let death = archived.death.unwrap();
+        assert_eq!((death.cause.as_str(), death.in_game_date.as_deref()), ("crushed by an ogre", Some("Downtime day 12")));
+        assert!(archived.inventory.is_empty() && archived.conditions.is_empty() && archived.hp == Some(0));
+
+        let log = fs::read_to_string(&session_log).unwrap();
+        assert!(log.contains("Aria, level 5 Fighter died: crushed by an ogre (in game: Downtime day 12)"));
+        assert_eq!(legacy.fallen_lines()[1], "  Aria, level 5 Fighter - crushed by an ogre (Downtime day 12)");
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_tactics_hints() {
+        use crate::search::WikiPageContent;
+        use crate::tactics::{hint, TacticsProfile};
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let page = |name: &str, content: &str| WikiPageContent {
+            index: name.to_lowercase(),
+            name: name.to_string(),
+            url: format!("http://dnd5e.wikidot.com/monster:{}", name.to_lowercase()),
+            content: content.to_string(),
+            content_type: "monster".to_string(),
+        };
+        let goblin = page("Goblin", include_str!("../test_fixtures/wikidot/monster_goblin_main_content.snap").split_once("---\n").unwrap().1);
+        assert_eq!(TacticsProfile::from_page(&goblin).ranged, vec!["Shortbow".to_string()]);
+        let drake = page("Drake", "ACTIONS\nBite. Melee Weapon Attack: +6 to hit, reach 5 ft.\nFire Breath (Recharge 5–6). The drake exhales fire in a 15-foot cone.");
+        let profile = TacticsProfile::from_page(&drake);
+        assert_eq!(profile.recharge, vec![("Fire Breath".to_string(), 5)]);
+        assert!(profile.ranged.is_empty());
+
+        let mut tracker = CombatTracker::new();
+        let mut tank = Character::new("Tank");
+        tank.ac = Some(18);
+        let mut wizard = Character::new("Wizard");
+        wizard.ac = Some(12);
+        tracker.add_combatant(Combatant::from_character(&tank, 15));
+        tracker.add_combatant(Combatant::from_character(&wizard, 12));
+        tracker.add_combatant(Combatant::new_npc("Drake".to_string(), 30, 14, 10));
+        let mut rng = StdRng::seed_from_u64(3);
+
+        // No hints on the party's turns
+        assert!(hint(&mut tracker, &profile, true, &mut rng).is_empty());
+
+        tracker.current_turn = 2;
+        let lines = hint(&mut tracker, &profile, true, &mut rng);
+        assert!(lines.iter().any(|l| l.contains("Use Fire Breath while it's ready (Recharge 5-6)")));
+        assert!(lines.iter().any(|l| l.contains("Focus Wizard (AC 12)")));
+        assert_eq!(tracker.combatants[2].spent_recharges, vec!["Fire Breath".to_string()]);
+
+        // Next turn the breath weapon has to recharge first
+        let lines = hint(&mut tracker, &profile, true, &mut rng);
+        let recharged = lines.iter().any(|l| l.starts_with("🔋"));
+        assert!(recharged || lines.iter().any(|l| l.starts_with("🪫")));
+        assert_eq!(lines.iter().any(|l| l.contains("Use Fire Breath")), recharged);
+
+        // Bloodied with a bow: hang back
+        tracker.combatants[2].current_hp = 10;
+        let archer = TacticsProfile { ranged: vec!["Shortbow".to_string()], recharge: Vec::new() };
+        let lines = hint(&mut tracker, &archer, false, &mut rng);
+        assert!(lines.iter().any(|l| l.contains("Bloodied: keep its distance and use Shortbow")));
+
+        // Downed party members aren't worth focusing
+        tracker.combatants[1].current_hp = 0;
+        let lines = hint(&mut tracker, &archer, false, &mut rng);
+        assert!(lines.iter().any(|l| l.contains("Focus Tank (AC 18)")));
+    }
+
+    #[test]
+    fn test_initiative_reroll_each_round() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let mut rogue = Character::new("Rogue");
+        rogue.dext = Some(20);
+        let characters = vec![rogue.clone()];
+        let mut tracker = CombatTracker::new();
+        tracker.reroll_each_round = false;
+        tracker.add_combatant(Combatant::from_character(&rogue, 3));
+        tracker.add_combatant(Combatant::new_npc("Ogre".to_string(), 59, 11, 20));
+        tracker.add_combatant(Combatant::new_npc("Scout".to_string(), 16, 13, 0));
+        let mut rng = StdRng::seed_from_u64(11);
+
+        tracker.round_number = 2;
+        assert!(tracker.reroll_for_new_round(&characters, &mut rng).is_empty());
+
+        tracker.reroll_each_round = true;
+        tracker.current_turn = 1;
+        assert!(tracker.reroll_for_new_round(&characters, &mut rng).is_empty(), "only at the top of a round");
+        tracker.current_turn = 0;
+        let lines = tracker.reroll_for_new_round(&characters, &mut rng);
+        assert_eq!(lines[0], "🎲 Initiative re-rolled for round 2:");
+        assert_eq!(lines.len(), 4);
+        assert!(tracker.combatants.windows(2).all(|pair| pair[0].initiative >= pair[1].initiative));
+        assert!(tracker.combatants.iter().all(|c| c.initiative >= 1));
+        let rogue_init = tracker.get_combatant("Rogue").unwrap().initiative;
+        assert!((6..=25).contains(&rogue_init), "d20 + 5 DEX, got {}", rogue_init);
+
+        // Once per round
+        assert!(tracker.reroll_for_new_round(&characters, &mut rng).is_empty());
+        tracker.round_number = 3;
+        assert!(!tracker.reroll_for_new_round(&characters, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn test_movement_modes() {
+        use crate::movement::{attack_reminders, fall_damage, knock_down, underwater_attack_reminder, Movement};
+
+        assert_eq!(Movement::parse("fly", Some("30")), Ok(Some(Movement::Flying { altitude: 30 })));
+        assert_eq!(Movement::parse("swim", None), Ok(Some(Movement::Swimming)));
+        assert_eq!(Movement::parse("land", None), Ok(None));
+        assert!(Movement::parse("fly", None).is_err() && Movement::parse("teleport", None).is_err());
+        assert_eq!((fall_damage(5), fall_damage(30), fall_damage(500)), (None, Some("3d6".to_string()), Some("20d6".to_string())));
+
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Harpy".to_string(), 38, 11, 15));
+        tracker.add_combatant(Combatant::new_npc("Fighter".to_string(), 40, 18, 10));
+        tracker.get_combatant_mut("Harpy").unwrap().movement = Some(Movement::Flying { altitude: 40 });
+        assert_eq!(tracker.get_combatant("Harpy").unwrap().movement_tag(), " [🪽 40 ft]");
+
+        // Swinging a sword at a flyer out of reach; a bow is fine
+        let (harpy, fighter) = (tracker.get_combatant("Harpy").unwrap(), tracker.get_combatant("Fighter").unwrap());
+        assert!(attack_reminders(Some(fighter), harpy, None)[0].contains("40 ft up"));
+        assert!(attack_reminders(Some(fighter), harpy, Some("longbow")).is_empty());
+
+        let harpy = tracker.get_combatant_mut("Harpy").unwrap();
+        assert_eq!(knock_down(harpy).unwrap(), "🪂 Harpy falls 40 ft: 4d6 bludgeoning damage and lands prone (unless it can hover)");
+        assert_eq!(harpy.movement, None);
+        assert_eq!(knock_down(harpy), None);
+
+        assert!(underwater_attack_reminder(Some("Trident")).contains("works normally"));
+        assert!(underwater_attack_reminder(Some("longsword")).contains("disadvantage"));
+        assert!(underwater_attack_reminder(Some("longbow")).contains("ranged attacks with the longbow have disadvantage"));
+        assert!(underwater_attack_reminder(Some("heavy crossbow")).contains("works normally within its normal range"));
+    }
+
+    #[test]
+    fn test_bulk_hp_review() {
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 15, 14));
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 15, 12));
+        tracker.add_combatant(Combatant::new_npc("Ogre Chief".to_string(), 59, 11, 8));
+
+        let review = tracker.hp_review_lines();
+        assert_eq!(review.len(), 4);
+        assert!(review[1].starts_with("  1  Goblin#1") && review[1].ends_with("7/7"));
+
+        // Accepting everything is just an empty answer, so nothing changes
+        assert!(tracker.apply_hp_overrides("").is_empty());
+
+        let results = tracker.apply_hp_overrides("2 3, ogre chief 40, Goblin#1 9, 7 1, Kobold 2, 1");
+        assert_eq!(results, vec![
+            "✅ Set Goblin's HP to 3".to_string(),
+            "✅ Set Ogre Chief's HP to 40".to_string(),
+            "❌ Goblin's HP must be between 0 and 7".to_string(),
+            "❌ No combatant '7'".to_string(),
+            "❌ No combatant 'Kobold'".to_string(),
+            "❌ '1': expected <#|name> <hp>".to_string(),
+        ]);
+        let hp: Vec<i32> = tracker.combatants.iter().map(|c| c.current_hp).collect();
+        assert_eq!(hp, vec![7, 3, 40]);
+    }
+    #[test]
+    fn test_encounter_generator() {
+        use crate::encounter::{generate, party_threshold, xp_band, Difficulty};
+        use crate::monsters::MonsterIndex;
+        use rand::{rngs::StdRng, SeedableRng};
+
+        assert_eq!(Difficulty::parse("Hard"), Some(Difficulty::Hard));
+        assert_eq!(Difficulty::parse("brutal"), None);
+        assert_eq!(party_threshold(4, 1, Difficulty::Medium), 200);
+        assert_eq!(party_threshold(5, 20, Difficulty::Deadly), 63_500);
+        assert_eq!(xp_band(4, 3, Difficulty::Hard), (900, 1_600));
+
+        let index = MonsterIndex::default();
+        let mut rng = StdRng::seed_from_u64(7);
+        for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard, Difficulty::Deadly] {
+            let encounter = generate(&index, 4, 3, difficulty, &mut rng).unwrap();
+            let (low, high) = xp_band(4, 3, difficulty);
+            assert!(encounter.adjusted_xp >= low && encounter.adjusted_xp <= high,
+                "{} encounter at {} XP is outside {}-{}", difficulty.name(), encounter.adjusted_xp, low, high);
+            assert!(!encounter.monsters.is_empty());
+            assert_eq!(encounter.lines().len(), encounter.monsters.len() + 1);
+
+            let combatants = encounter.to_combatants(&mut rng);
+            assert_eq!(combatants.len(), encounter.monsters.len());
+            assert!(combatants.iter().all(|c| c.cr.is_some() && c.max_hp > 0 && !c.is_player));
+            // Repeated creatures get numbered names so the tracker can tell them apart
+            let mut names: Vec<&str> = combatants.iter().map(|c| c.name.as_str()).collect();
+            names.sort();
+            names.dedup();
+            assert_eq!(names.len(), combatants.len());
+        }
+
+        assert!(generate(&index, 0, 3, Difficulty::Easy, &mut rng).is_err());
+    }
+    #[test]
+    fn test_pacing_dashboard() {
+        use crate::pacing::sparkline;
+        use crate::registry::insert;
+        use crate::resources::{ClassResource, Recharge};
+
+        assert_eq!(sparkline(&[0, 50, 100], 100), "▁▄█");
+        assert_eq!(sparkline(&[0, 0], 0), "▁▁");
+
+        let mut characters = Vec::new();
+        let mut fighter = Character::new("Brenna");
+        fighter.hp = Some(20);
+        fighter.max_hp = Some(20);
+        fighter.resources.push(ClassResource::new("Action Surge", 1, Recharge::ShortRest));
+        let brenna = insert(&mut characters, fighter);
+
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::from_character(&characters[0], 15));
+        tracker.add_combatant(Combatant::new_npc("Ogre".to_string(), 60, 11, 8));
+        assert_eq!(tracker.pacing.dashboard_lines(), vec!["No rounds tracked yet".to_string()]);
+
+        tracker.pacing.observe(1, &tracker.combatants, &characters);
+        tracker.apply_damage("Brenna", 10, None).unwrap();
+        tracker.apply_damage("Ogre", 30, None).unwrap();
+        characters.iter_mut().find(|c| c.id == brenna).unwrap().resources[0].current = 0;
+        tracker.pacing.observe(1, &tracker.combatants, &characters);
+        assert_eq!(tracker.pacing.rounds.len(), 1, "a round is updated in place, not duplicated");
+        let round = tracker.pacing.rounds[0];
+        assert_eq!((round.party_hp_percent, round.monster_hp_percent, round.resources_spent), (50, 50, 1));
+
+        // Two rounds of barely scratching the ogre make the fight drag
+        for round in 2..=3 {
+            tracker.apply_damage("Ogre", 1, None).unwrap();
+            tracker.pacing.observe(round, &tracker.combatants, &characters);
+        }
+        assert_eq!(tracker.pacing.dragging(), None, "the round in progress doesn't count yet");
+        tracker.pacing.observe(4, &tracker.combatants, &characters);
+        assert_eq!(tracker.pacing.dragging().unwrap(), "⏳ Dragging: monsters lost 4% HP in two rounds");
+
+        let dashboard = tracker.pacing.dashboard_lines();
+        assert!(dashboard[0].starts_with("Party ▄▄▄▄ ") && dashboard[0].ends_with(" 50%"));
+        assert!(dashboard[2].ends_with("   1"));
+        assert_eq!(tracker.pacing.table_lines().len(), 2 + 4 + 1);
+    }
+    #[test]
+    fn test_character_json_persistence() {
+        use crate::file_manager::*;
+        use crate::resources::{ClassResource, Recharge};
+        use std::fs;
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_json_sheets_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut wizard = Character::new("Mira");
+        wizard.level = Some(5);
+        wizard.inventory = vec!["Spellbook".to_string(), "Quarterstaff".to_string()];
+        wizard.spells = vec!["Fireball".to_string(), "Shield".to_string()];
+        wizard.cards = vec![Cards { suit: Suit::Spades, rank: 12, desc: "The Queen of Spades".to_string() }];
+        wizard.resources.push(ClassResource::new("Arcane Recovery", 1, Recharge::LongRest));
+
+        // Vec fields survive a save and load unchanged
+        let path = write_character_file(&dir, &wizard).unwrap();
+        assert_eq!(path, dir.join("Mira.json"));
+        let saved: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], CHARACTER_SCHEMA_VERSION);
+        assert_eq!(read_character_file(&path).unwrap(), wizard);
+
+        // An old RON sheet is loaded, rewritten as JSON and backed up
+        let mut fighter = Character::new("Brenna");
+        fighter.inventory = vec!["Longsword".to_string()];
+        fs::write(dir.join("Brenna.txt"), ron::ser::to_string_pretty(&fighter, ron::ser::PrettyConfig::default()).unwrap()).unwrap();
+        let loaded = load_characters_in(&dir);
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded.contains(&fighter) && loaded.contains(&wizard));
+        assert!(!dir.join("Brenna.txt").exists() && dir.join("Brenna.txt.v1.bak").exists());
+        assert_eq!(read_character_file(&dir.join("Brenna.json")).unwrap(), fighter);
+        assert_eq!(load_characters_in(&dir).len(), 2, "a migrated sheet is only loaded once");
+
+        // Files from a newer build are skipped rather than misread
+        fs::write(dir.join("Future.json"), r#"{"schema_version": 99, "character": {"name": "Future"}}"#).unwrap();
+        let error = read_character_file(&dir.join("Future.json")).unwrap_err().to_string();
+        assert!(error.contains("newer dnd_tools"), "{}", error);
+        assert_eq!(load_characters_in(&dir).len(), 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn test_npc_voice_prompts() {
+        use crate::voices::*;
+        use rand::{rngs::StdRng, SeedableRng};
+        use std::fs;
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let voice = Voice::random(&mut rng);
+        assert_eq!(Voice::parse_sheet_line(&voice.sheet_line()), Some(voice.clone()));
+        assert_eq!(Voice::parse_sheet_line("Name: Orc"), None);
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_voices_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Mayor Pell.txt"), "Name: Mayor Pell\nHP: 9/9\nType: NPC").unwrap();
+
+        let mut last = None;
+        assert_eq!(voice_command(&dir, &["save", "Mayor", "Pell"], &mut last, &mut rng), vec!["No voice suggested yet. Type 'voice' first.".to_string()]);
+        let suggestion = voice_command(&dir, &[], &mut last, &mut rng);
+        assert_eq!(suggestion.len(), 7);
+        assert!(voice_command(&dir, &["save", "Mayor", "Pell"], &mut last, &mut rng)[0].starts_with("💾"));
+
+        // Saving again replaces the voice and leaves the rest of the sheet alone
+        voice_command(&dir, &[], &mut last, &mut rng);
+        voice_command(&dir, &["save", "Mayor", "Pell"], &mut last, &mut rng);
+        let sheet = fs::read_to_string(dir.join("Mayor Pell.txt")).unwrap();
+        assert!(sheet.starts_with("Name: Mayor Pell\nHP: 9/9\nType: NPC\n"));
+        assert_eq!(sheet.matches("Voice: ").count(), 1);
+        assert_eq!(saved_voice_in(&dir, "Mayor Pell"), last);
+
+        // An NPC with no sheet yet gets a minimal one
+        save_voice_in(&dir, "Stablehand", last.as_ref().unwrap()).unwrap();
+        assert!(fs::read_to_string(dir.join("Stablehand.txt")).unwrap().starts_with("Name: Stablehand\nType: NPC\n"));
+        assert_eq!(voice_command(&dir, &["Nobody"], &mut last, &mut rng), vec!["No voice saved for Nobody. 'voice' suggests one.".to_string()]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn test_save_and_resume_encounter() {
+        use crate::saved_combat::*;
+        use std::fs;
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_saved_fights_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut lia = Character::new("Lia");
+        lia.id = 4;
+        lia.hp = Some(18);
+        lia.max_hp = Some(24);
+
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::from_character(&lia, 17));
+        tracker.add_combatant(Combatant::new_npc("Bandit".to_string(), 11, 12, 12));
+        tracker.add_combatant(Combatant::new_npc("Bandit".to_string(), 11, 12, 9));
+        tracker.apply_damage("Bandit#3", 5, None).unwrap();
+        tracker.combatants[2].temp_hp = 3;
+        tracker.combatants[2].status_effects.push(StatusEffect::new("Frightened", Some(2)));
+        tracker.round_number = 3;
+        tracker.current_turn = 2;
+
+        let path = save_encounter_in(&dir, "Bandit Ambush", &tracker).unwrap();
+        assert_eq!(path, dir.join("bandit-ambush.ron"));
+        assert_eq!(list_encounters_in(&dir), vec!["bandit-ambush".to_string()]);
+
+        // Next session Lia has a different registry id; the fight finds her by name
+        let mut next_session = Character::new("Lia");
+        next_session.id = 1;
+        let (resumed, warnings) = load_encounter_in(&dir, "Bandit Ambush", &[next_session]).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!((resumed.round_number, resumed.current_turn), (3, 2));
+        assert_eq!(resumed.combatants[0].character_id, Some(1));
+        let bandit = &resumed.combatants[2];
+        assert_eq!((bandit.current_hp, bandit.temp_hp, bandit.status_effects[0].duration), (6, 3, Some(2)));
+        assert_eq!(resume_summary("Bandit Ambush", &resumed), "📂 Resumed 'Bandit Ambush': round 3, 3 combatant(s), Bandit#3's turn");
+
+        // Without her sheet she stays in the fight, unlinked
+        let (resumed, warnings) = load_encounter_in(&dir, "bandit ambush", &[]).unwrap();
+        assert_eq!(resumed.combatants[0].character_id, None);
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(load_encounter_in(&dir, "Dragon", &[]), Err(crate::error_handling::AppError::NotFound(_))));
+
+        // The CLI's look-ahead turn converts both ways
+        assert_eq!(cli_turn_to_saved(0, 4, 3), (2, 3));
+        assert_eq!(saved_turn_to_cli(2, 3, 3), (0, 4));
+        assert_eq!(saved_turn_to_cli(cli_turn_to_saved(2, 2, 3).0, 2, 3), (2, 2));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_status_effect_ticking() {
+        use crate::combat::TickPoint;
+
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Mira".to_string(), 12, 13, 20));
+        tracker.add_combatant(Combatant::new_npc("Ogre".to_string(), 59, 11, 10));
+
+        // Poisoned for 2 rounds, counting down at the end of the ogre's turns
+        tracker.combatants[1].add_status(StatusEffect::new("Poisoned", Some(2)));
+        assert!(tracker.tick_status_effects(1, TickPoint::StartOfTurn).is_empty());
+        assert_eq!(tracker.combatants[1].status_effects[0].duration, Some(2));
+        assert!(tracker.tick_status_effects(1, TickPoint::EndOfTurn).is_empty());
+        assert_eq!(tracker.tick_status_effects(1, TickPoint::EndOfTurn), vec!["⌛ Ogre is no longer Poisoned".to_string()]);
+        assert!(tracker.combatants[1].status_effects.is_empty());
+
+        // The classic loop ticks the last actor's end-of-turn effects and the new actor's start-of-turn ones
+        let mut dazed = StatusEffect::new("Dazed", Some(1));
+        dazed.ticks_at = TickPoint::StartOfTurn;
+        tracker.combatants[1].add_status(dazed);
+        tracker.combatants[0].add_status(StatusEffect::new("Blessed", Some(1)));
+        assert_eq!(tracker.next_turn().unwrap().name, "Mira");
+        assert_eq!(tracker.combatants[0].status_effects.len(), 1);
+        assert_eq!(tracker.next_turn().unwrap().name, "Ogre");
+        assert!(tracker.combatants.iter().all(|c| c.status_effects.is_empty()));
+
+        // Concentration ends when the caster drops, and needs a caster in the fight
+        let mut held = StatusEffect::new("Paralyzed", Some(10));
+        held.concentration = Some("mira".to_string());
+        tracker.resolve_concentration(&mut held).unwrap();
+        assert_eq!(held.concentration.as_deref(), Some("Mira"));
+        tracker.combatants[1].add_status(held);
+        let mut stray = StatusEffect::new("Charmed", None);
+        stray.concentration = Some("Nobody".to_string());
+        assert!(tracker.resolve_concentration(&mut stray).is_err());
+
+        assert!(tracker.end_broken_concentration().is_empty());
+        tracker.apply_damage("Mira", 20, None).unwrap();
+        assert_eq!(tracker.end_broken_concentration(), vec!["💫 Mira lost concentration: Ogre is no longer Paralyzed".to_string()]);
+        assert!(tracker.combatants[1].status_effects.is_empty());
+    }
+
+    #[test]
+    fn test_weather_rules_reminders() {
+        use crate::weather::*;
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut weather = Weather::default();
+        assert!(weather.reminders(RuleScope::Combat).is_empty());
+        weather.set("Strong-Wind, heavy rain").unwrap();
+        assert_eq!(weather.conditions, vec![WeatherCondition::HeavyRain, WeatherCondition::StrongWind]);
+        assert_eq!(weather.describe(), "🌧️ heavy rain, 💨 strong wind");
+        assert_eq!(weather.reminders(RuleScope::Ranged), vec!["💨 Strong wind: disadvantage on ranged weapon attack rolls".to_string()]);
+        assert!(weather.reminders(RuleScope::Combat).iter().any(|r| r.contains("lightly obscured")));
+        assert!(weather.reminders(RuleScope::Flying).iter().any(|r| r.contains("must land")));
+        assert!(weather.set("sleet").is_err());
+
+        // Rolling keeps the temperature, and precipitation falls as snow in the cold
+        weather.set("extreme cold").unwrap();
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..20 {
+            weather.roll(&mut rng);
+            assert!(weather.is(WeatherCondition::ExtremeCold));
+            assert!(!weather.is(WeatherCondition::HeavyRain) && !weather.is(WeatherCondition::LightRain));
+        }
+        assert!(weather.reminders(RuleScope::Travel).iter().any(|r| r.contains("DC 10 Constitution")));
+
+        let (lines, changed) = weather_command(&mut weather, &["clear"], &mut rng);
+        assert!(changed);
+        assert_eq!(lines, vec!["Weather: ☀️ clear".to_string()]);
+        let (lines, changed) = weather_command(&mut weather, &["hail"], &mut rng);
+        assert!(!changed && lines[0].starts_with("❌"));
+    }
+
+    #[test]
+    fn test_character_backgrounds() {
+        use crate::backgrounds::*;
+
+        let mut book = BackgroundBook::default();
+        let soldier = book.get("soldier").unwrap().clone();
+        let mut character = Character::new("Bram");
+        character.proficiencies.push("athletics".to_string());
+        let lines = soldier.apply(&mut character);
+        assert_eq!(character.background.as_deref(), Some("Soldier"));
+        // Athletics was already there, so it isn't added twice
+        assert_eq!(character.proficiencies, vec!["athletics", "Intimidation", "Gaming set", "Vehicles (land)"]);
+        assert!(character.is_proficient("Athletics") && character.is_proficient("vehicles (land)"));
+        assert!(!character.is_proficient("Stealth"));
+        assert!(character.inventory.contains(&"Insignia of rank".to_string()));
+        assert_eq!(character.inventory.last().map(String::as_str), Some("10 gp"));
+        assert!(lines.iter().any(|l| l.contains("Military Rank")));
+
+        // Homebrew backgrounds are added, replace built-ins by name, and need real skills
+        let added = book.add_toml(r#"
+            [[backgrounds]]
+            name = "Pit Fighter"
+            skills = ["athletics", "sleight-of-hand"]
+            equipment = ["Trident"]
+            gold = 5
+            feature = "Crowd Favourite"
+            feature_text = "Arena crowds know your name"
+
+            [[backgrounds]]
+            name = "sage"
+            skills = ["Arcana", "Nature"]
+            feature = "Researcher"
+            feature_text = "You know where to look"
+        "#).unwrap();
+        assert_eq!(added, 2);
+        assert_eq!(book.get("pit fighter").unwrap().skills, vec!["Athletics", "Sleight of Hand"]);
+        assert_eq!(book.get("Sage").unwrap().skills, vec!["Arcana", "Nature"]);
+        assert_eq!(book.backgrounds.iter().filter(|b| b.name.eq_ignore_ascii_case("sage")).count(), 1);
+        assert!(book.add_toml("[[backgrounds]]\nname = \"Odd\"\nskills = [\"Juggling\"]\nfeature = \"x\"\nfeature_text = \"y\"").is_err());
+    }
+
+    #[test]
+    fn test_condition_effects() {
+        use crate::character::AbilityScore;
+
+        // Standard conditions are recognised and stored with their usual spelling
+        let status = StatusEffect::new("paralyzed", Some(2));
+        assert_eq!(status.name, "Paralyzed");
+        assert_eq!(status.condition(), Some(Condition::Paralyzed));
+        assert!(Condition::Paralyzed.rules_text().iter().any(|l| l.contains("critical hit")));
+        assert_eq!(StatusEffect::new("hasted", None).condition(), None);
+
+        let mut tracker = CombatTracker::new();
+        let mut attacker = Combatant::new_npc("Orc".to_string(), 15, 13, 12);
+        attacker.add_status(StatusEffect::new("poisoned", None));
+        let mut target = Combatant::new_npc("Mira".to_string(), 20, 14, 10);
+        target.add_status(status);
+        tracker.add_combatant(attacker.clone());
+        tracker.add_combatant(target.clone());
+
+        // Poisoned attacker against a paralyzed target: both edges, cancelling, and a crit in reach
+        let hints = condition_attack_hints(Some(&attacker), &target);
+        assert!(hints.iter().any(|h| h.contains("Disadvantage: Orc is poisoned")));
+        assert!(hints.iter().any(|h| h.contains("Advantage: Mira is paralyzed")));
+        assert!(hints.iter().any(|h| h.contains("cancel")));
+        assert!(hints.iter().any(|h| h.contains("critical hit")));
+
+        // Paralyzed creatures fail Strength and Dexterity saves without rolling
+        assert_eq!(target.auto_fails_save(AbilityScore::Dexterity), Some(Condition::Paralyzed));
+        assert_eq!(target.auto_fails_save(AbilityScore::Wisdom), None);
+        let result = tracker.make_saving_throw("Mira", "str", &[], None).unwrap();
+        assert!(result.contains("automatically fails the Strength saving throw"));
+        assert!(tracker.make_saving_throw("Mira", "wis", &[], None).unwrap().contains("makes a"));
+
+        // Restrained only gives disadvantage on Dexterity saves; removal ignores case
+        let mut bound = Combatant::new_npc("Goblin".to_string(), 7, 15, 14);
+        bound.add_status(StatusEffect::new("Restrained", None));
+        assert_eq!(bound.save_hints(AbilityScore::Dexterity).len(), 1);
+        assert!(bound.save_hints(AbilityScore::Strength).is_empty());
+        assert!(bound.remove_status("restrained"));
+        assert!(condition_attack_hints(None, &bound).is_empty());
+    }
+
+    #[test]
+    fn test_death_saves() {
+        let mut tracker = CombatTracker::new();
+        let mut hero = Combatant::new_npc("Brenna".to_string(), 12, 16, 15);
+        hero.is_player = true;
+        tracker.add_combatant(hero);
+        tracker.add_combatant(Combatant::new_npc("Ogre".to_string(), 59, 11, 8));
+
+        // Dropping to 0 HP starts death saves for party members only
+        tracker.apply_damage("Brenna", 20, None).unwrap();
+        tracker.apply_damage("Ogre", 60, None).unwrap();
+        assert_eq!(tracker.update_death_saves().len(), 1);
+        assert_eq!(tracker.get_combatant("Ogre").unwrap().death_saves, None);
+        assert!(tracker.record_death_save("Ogre", 15).is_err());
+
+        // A success, then a natural 1 for two failures
+        assert!(tracker.record_death_save("Brenna", 12).unwrap().contains("success"));
+        assert!(tracker.record_death_save("Brenna", 1).unwrap().contains("two failures"));
+        let saves = tracker.get_combatant("Brenna").unwrap().death_saves.unwrap();
+        assert_eq!((saves.successes, saves.failures), (1, 2));
+        assert_eq!(saves.tally(), "✅⬜⬜ ❌❌⬜");
+        assert!(tracker.get_combatant("Brenna").unwrap().death_save_tag().contains("💀"));
+
+        // A natural 20 brings them back with 1 HP
+        assert!(tracker.record_death_save("Brenna", 20).unwrap().contains("regains 1 HP"));
+        let brenna = tracker.get_combatant("Brenna").unwrap();
+        assert_eq!((brenna.current_hp, brenna.death_saves), (1, None));
+        assert!(tracker.update_death_saves().is_empty());
+
+        // Damage while down is a failed save, and three failures is death
+        tracker.apply_damage("Brenna", 5, None).unwrap();
+        tracker.update_death_saves();
+        tracker.record_death_save("Brenna", 4).unwrap();
+        tracker.record_death_save("Brenna", 9).unwrap();
+        let message = tracker.apply_damage("Brenna", 3, None).unwrap();
+        assert!(message.contains("dies"));
+        assert!(tracker.get_combatant("Brenna").unwrap().death_saves.unwrap().is_dead());
+        assert!(tracker.record_death_save("Brenna", 15).is_err());
+
+        // Healing ends death saves
+        let mut stable = Combatant::new_npc("Lia".to_string(), 8, 12, 10);
+        stable.is_player = true;
+        stable.current_hp = 0;
+        tracker.add_combatant(stable);
+        tracker.update_death_saves();
+        tracker.get_combatant_mut("Lia").unwrap().current_hp = 4;
+        assert!(tracker.update_death_saves()[0].contains("stops making death saves"));
+    }
+
+    #[test]
+    fn test_starting_equipment() {
+        use crate::starting_equipment::class_kit;
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let kit = class_kit("fighter").unwrap();
+        assert!(class_kit("Blood Hunter").is_none());
+        let lines = kit.describe();
+        assert!(lines[2].starts_with("  b) Leather armor, Longbow, 20 Arrows"));
+        assert!(lines.last().unwrap().contains("5d4 × 10 gp"));
+
+        // Arrows become tracked ammunition; thrown weapons go in both places
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut character = Character::new("Brenna");
+        character.inventory.push("Insignia of rank".to_string());
+        kit.equip(&mut character, "B", &mut rng).unwrap();
+        assert!(character.inventory.contains(&"Longbow".to_string()));
+        assert!(character.inventory.contains(&"2 Handaxes".to_string()));
+        assert!(!character.inventory.iter().any(|item| item.contains("Arrows")));
+        let count = |kind: &str| character.ammo.iter().find(|a| a.kind == kind).map(|a| a.count);
+        assert_eq!((count("arrows"), count("handaxes")), (Some(20), Some(2)));
+        assert!(kit.equip(&mut character, "c", &mut rng).is_err());
+
+        // Monks roll 5d4 gold without the multiplier
+        let monk = class_kit("Monk").unwrap();
+        let mut poor = Character::new("Ilse");
+        monk.equip(&mut poor, "gold", &mut rng).unwrap();
+        let gold: u32 = poor.inventory[0].trim_end_matches(" gp").parse().unwrap();
+        assert!((5..=20).contains(&gold));
+    }
+
+    #[test]
+    fn test_carousing_contacts() {
+        use crate::carousing::{carouse, carouse_command, contacts_in, purse, Company};
+        use crate::names::NameRegistry;
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_carousing_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let names = NameRegistry::default();
+        let mut rng = StdRng::seed_from_u64(5);
+
+        // A high-level character never rolls low enough for a hostile contact
+        let mut bram = Character::new("Bram");
+        bram.level = Some(20);
+        let carousal = carouse(&bram, Company::Upper, 10, &names, &mut rng).unwrap();
+        assert_eq!(carousal.gold_spent, 2500);
+        assert!(carousal.contacts.len() >= 10 && carousal.contacts.iter().all(|c| !c.hostile));
+
+        // The cost comes out of the purse, and contacts are saved as NPCs linked to Bram
+        bram.inventory = vec!["Rope".to_string(), "80 gp".to_string(), "25 gp".to_string()];
+        let lines = carouse_command(&dir, &mut bram, "middle", Some("2"), &names, &mut rng).unwrap();
+        assert!(lines[1].contains("Spent 100 gp (5 gp left)"));
+        assert_eq!(bram.inventory, vec!["Rope".to_string(), "5 gp".to_string()]);
+        let contacts = contacts_in(&dir, Some("bram"));
+        assert!(!contacts.is_empty());
+        assert!(contacts.iter().all(|(who, contact)| who == "Bram" && !contact.hostile));
+        let sheet = std::fs::read_to_string(dir.join(format!("{}.txt", contacts[0].1.name))).unwrap();
+        assert!(sheet.starts_with(&format!("Name: {}\nType: NPC\nContact: Bram | allied | ", contacts[0].1.name)));
+        assert!(contacts_in(&dir, Some("Lia")).is_empty());
+
+        // Not enough gold leaves the purse alone
+        let lines = carouse_command(&dir, &mut bram, "upper", None, &names, &mut rng).unwrap();
+        assert!(lines[1].contains("settle it by hand"));
+        assert_eq!(purse(&bram), 5);
+        assert!(carouse_command(&dir, &mut bram, "royal", None, &names, &mut rng).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_damage_types() {
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Skeleton".to_string(), 30, 13, 12));
+        assert_eq!(split_damage_type(&["goblin", "8", "Fire"]), (&["goblin", "8"][..], Some(DamageType::Fire)));
+        assert_eq!(split_damage_type(&["goblin", "8"]), (&["goblin", "8"][..], None));
+
+        tracker.set_defenses("Skeleton", "vulnerable", &["bludgeoning"]).unwrap();
+        tracker.set_defenses("Skeleton", "immune", &["poison"]).unwrap();
+        let message = tracker.set_defenses("Skeleton", "resist", &["piercing", "slashing", "piercing"]).unwrap();
+        assert!(message.contains("Resistant: piercing, slashing"));
+        assert!(tracker.set_defenses("Skeleton", "resist", &["lasers"]).is_err());
+        assert!(tracker.set_defenses("Nobody", "resist", &["fire"]).is_err());
+
+        // Resistance rounds down, vulnerability doubles, immunity ignores it all
+        let message = tracker.apply_damage("Skeleton", 7, Some(DamageType::Slashing)).unwrap();
+        assert!(message.contains("7 → 3"));
+        assert_eq!(tracker.get_combatant("Skeleton").unwrap().current_hp, 27);
+        tracker.apply_damage("Skeleton", 5, Some(DamageType::Bludgeoning)).unwrap();
+        assert_eq!(tracker.get_combatant("Skeleton").unwrap().current_hp, 17);
+        tracker.apply_damage("Skeleton", 9, Some(DamageType::Poison)).unwrap();
+        tracker.apply_damage("Skeleton", 4, None).unwrap();
+        assert_eq!(tracker.get_combatant("Skeleton").unwrap().current_hp, 13);
+
+        // A petrified creature resists everything, and 'none' clears a list
+        tracker.set_defenses("Skeleton", "resist", &["none"]).unwrap();
+        let skeleton = tracker.get_combatant_mut("Skeleton").unwrap();
+        assert!(skeleton.resistances.is_empty());
+        skeleton.add_status(StatusEffect::new("petrified", None));
+        assert_eq!(skeleton.adjust_damage(10, None).0, 5);
+        assert_eq!(skeleton.adjust_damage(10, Some(DamageType::Bludgeoning)).0, 10);
+    }
+
+    #[test]
+    fn test_adventure_outline() {
+        use crate::adventure::{scene_command, Adventure};
+        use crate::saved_combat::save_encounter_in;
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_adventure_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let (npcs, encounters) = (dir.join("npcs"), dir.join("encounters"));
+        std::fs::create_dir_all(&npcs).unwrap();
+        std::fs::write(npcs.join("Sildar.txt"), "Name: Sildar\nType: NPC\nVoice: gruff\n").unwrap();
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Goblin 1".to_string(), 7, 15, 12));
+        save_encounter_in(&encounters, "Goblin Ambush", &tracker).unwrap();
+
+        let mut adventure = Adventure::default();
+        let run = |adventure: &mut Adventure, args: &str| scene_command(adventure, &args.split_whitespace().collect::<Vec<_>>(), &npcs, &encounters);
+        assert!(adventure.scene_index(None).is_err());
+        assert!(run(&mut adventure, "add Arrival in town").1);
+        run(&mut adventure, "add Goblin Ambush");
+        run(&mut adventure, "note 2 Four goblins in the trees");
+        run(&mut adventure, "npc 2 Sildar");
+        run(&mut adventure, "encounter 2 Goblin Ambush");
+        assert!(run(&mut adventure, "encounter 2 Missing Fight").0[1].contains("No saved encounter"));
+
+        // Finishing a scene moves on and shows the next one's prep
+        let (lines, changed) = run(&mut adventure, "done");
+        assert!(changed && lines.iter().any(|l| l.contains("Scene 2: Goblin Ambush (current)")));
+        assert!(lines.iter().any(|l| l.contains("Voice: gruff")));
+        assert!(lines.iter().any(|l| l.contains("Goblin 1")));
+        assert_eq!(adventure.current(), Some(1));
+        assert!(adventure.outline().iter().any(|l| l.contains("▶ 2. Goblin Ambush  [⚔️ 2, 👤 1, 📝 1]")));
+        assert_eq!(adventure.encounter_to_run(None).unwrap(), "Goblin Ambush");
+        assert!(adventure.encounter_to_run(Some("1")).is_err());
+
+        // Bad input changes nothing
+        let (lines, changed) = run(&mut adventure, "note 9 nowhere");
+        assert!(!changed && lines[0].starts_with("❌"));
+        assert!(!run(&mut adventure, "dance").1);
+
+        run(&mut adventure, "move 2 1");
+        assert_eq!(adventure.scenes[0].title, "Goblin Ambush");
+        run(&mut adventure, "done 1");
+        assert!(adventure.scene_index(None).is_err());
+
+        let path = dir.join("adventure.ron");
+        adventure.save(&path).unwrap();
+        assert_eq!(Adventure::load(&path).unwrap(), adventure);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_monster_import() {
+        use crate::search::{MonsterStatBlock, WikiPageContent};
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let page = |name: &str, content: &str| WikiPageContent {
+            index: name.to_lowercase(),
+            name: name.to_string(),
+            url: format!("http://dnd5e.wikidot.com/monster:{}", name.to_lowercase()),
+            content: content.to_string(),
+            content_type: "monster".to_string(),
+        };
+        let snapshot = include_str!("../test_fixtures/wikidot/monster_goblin_main_content.snap");
+        let goblin = MonsterStatBlock::from_page(&page("Goblin", snapshot.split_once("---\n").unwrap().1)).unwrap();
+        assert_eq!((goblin.ac, goblin.hp, goblin.speed.as_deref()), (15, 7, Some("30 ft.")));
+        assert_eq!(goblin.attacks.len(), 2);
+        assert_eq!(goblin.attacks[0].describe(), "Scimitar: +4 to hit, 1d6+2 slashing");
+        assert_eq!(goblin.attacks[1].damage_type, Some(DamageType::Piercing));
+        // This trimmed page has no ability table, so initiative has no modifier
+        assert_eq!((goblin.abilities, goblin.dex_mod()), (None, 0));
+
+        let zombie = MonsterStatBlock::from_page(&page("Zombie", "Medium undead, neutral evil\nArmor Class 8\nHit Points 22 (3d8 + 9)\n\
+            Speed 20 ft.\nSTR | DEX | CON | INT | WIS | CHA |\n13 (+1) | 6 (-2) | 16 (+3) | 3 (-4) | 6 (-2) | 5 (-3) |\n\
+            Saving Throws Wis +0 Damage Immunities poison Condition Immunities poisoned Senses darkvision 60 ft.\n\
+            Challenge 1/4 (50 XP)\nSlam. Melee Weapon Attack: +3 to hit, reach 5 ft., one target. Hit: 4 (1d6 + 1) bludgeoning damage.")).unwrap();
+        assert_eq!(zombie.abilities, Some([13, 6, 16, 3, 6, 5]));
+        assert_eq!((zombie.dex_mod(), zombie.cr.as_deref()), (-2, Some("1/4")));
+        assert_eq!(zombie.immunities, vec![DamageType::Poison]);
+        assert!(zombie.resistances.is_empty());
+        assert!(zombie.lines()[1].contains("STR 13 (+1)  DEX 6 (-2)"));
+
+        // Copies are numbered on from those already in the fight
+        let mut tracker = CombatTracker::new();
+        let mut rng = StdRng::seed_from_u64(3);
+        zombie.add_to_tracker(&mut tracker, 1, &mut rng);
+        let lines = zombie.add_to_tracker(&mut tracker, 2, &mut rng);
+        assert!(lines.iter().any(|l| l.contains("Immune: poison")));
+        let mut names: Vec<&str> = tracker.combatants.iter().map(|c| c.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["Zombie", "Zombie 2", "Zombie 3"]);
+        let added = tracker.get_combatant("Zombie 3").unwrap();
+        assert_eq!((added.ac, added.max_hp, added.cr.as_deref()), (8, 22, Some("1/4")));
+        assert!((-1..=18).contains(&added.initiative));
+        assert_eq!(added.adjust_damage(6, Some(DamageType::Poison)).0, 0);
+
+        assert!(MonsterStatBlock::from_page(&page("Rumor", "Nothing but gossip here, no numbers at all")).is_err());
+    }
+
+    #[test]
+    fn test_initiative_csv_import() {
+        use crate::saved_combat::{import_initiative, import_initiative_file, parse_initiative_csv};
+
+        let rows = parse_initiative_csv("# rolled before the session\nname,initiative,hp,ac\n\
+            Goblin 1,14,7,15\n\"Grik, the Boss\",9,21,17\n\nLia,18,,\n").unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!((rows[1].name.as_str(), rows[1].initiative, rows[1].hp, rows[1].ac), ("Grik, the Boss", 9, Some(21), Some(17)));
+        assert_eq!((rows[2].hp, rows[2].ac), (None, None));
+        assert!(parse_initiative_csv("Goblin,14,7,15\nOrc,fast,15,13").unwrap_err().to_string().contains("Line 2"));
+        assert!(parse_initiative_csv("Goblin,14,seven,15").is_err());
+
+        // Saved characters are linked; anyone else needs HP and AC
+        let mut lia = Character::new("Lia");
+        lia.hp = Some(24);
+        lia.ac = Some(14);
+        let characters = vec![lia];
+        let mut tracker = CombatTracker::new();
+        let lines = import_initiative(&rows, &mut tracker, &characters).unwrap();
+        assert_eq!(lines.len(), 3);
+        let order: Vec<&str> = tracker.combatants.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(order, vec!["Lia", "Goblin 1", "Grik, the Boss"]);
+        assert!(tracker.get_combatant("Lia").unwrap().character_id.is_some());
+        assert_eq!(tracker.get_combatant("Grik, the Boss").unwrap().max_hp, 21);
+
+        // Nothing is added when a row can't be used
+        let bad = parse_initiative_csv("Ogre,12,59,11\nShade,15,,").unwrap();
+        assert!(import_initiative(&bad, &mut tracker, &characters).is_err());
+        assert!(import_initiative(&rows[2..], &mut tracker, &characters).is_err());
+        assert_eq!(tracker.combatants.len(), 3);
+
+        let path = std::env::temp_dir().join(format!("dnd_tools_initiative_{}.csv", std::process::id()));
+        std::fs::write(&path, "Ogre,12,59,11\n").unwrap();
+        assert!(import_initiative_file(&path, &mut tracker, &characters).unwrap()[0].contains("1 combatant"));
+        assert_eq!(tracker.combatants.len(), 4);
+        let _ = std::fs::remove_file(&path);
+        assert!(import_initiative_file(&path, &mut tracker, &characters).is_err());
+    }
+
+    #[test]
+    fn test_spell_slots() {
+        use crate::spell_slots::*;
+
+        assert_eq!(slots_for("Wizard", 5)[..3], [4, 3, 2]);
+        assert_eq!(slots_for("wizard", 20), [4, 3, 3, 3, 3, 2, 2, 1, 1]);
+        assert_eq!(slots_for("Paladin", 1), [0; 9]);
+        assert_eq!(slots_for("Paladin", 5)[..2], [4, 2]);
+        assert_eq!(slots_for("Artificer", 1)[0], 2);
+        assert_eq!(slots_for("Warlock", 5), [0; 9]);
+
+        let mut cleric = Character::new("Mira");
+        cleric.class = Some("Cleric".to_string());
+        cleric.level = Some(3);
+        assert_eq!(spend_spell_slot(&mut cleric, 1, None).unwrap().0, 1);
+        assert_eq!(spend_spell_slot(&mut cleric, 1, Some(2)).unwrap().0, 2);
+        assert!(spend_spell_slot(&mut cleric, 2, Some(1)).is_err());
+        assert!(spend_spell_slot(&mut cleric, 3, None).is_err());
+        assert_eq!(cleric.spell_slots.unwrap().describe(), "Spell slots: 1st ●●●○ 2nd ●○ (long rest)");
+        spend_spell_slot(&mut cleric, 2, None).unwrap();
+        // Out of 2nd-level slots, so nothing fits a 2nd-level spell
+        let err = spend_spell_slot(&mut cleric, 2, None).unwrap_err();
+        assert!(err.to_string().contains("no slots of 2nd level or higher"));
+        assert_eq!(cleric.spell_slots.unwrap().remaining(1), 3);
+
+        // Spent slots carry over a level up, and a long rest brings them all back
+        cleric.level = Some(5);
+        refresh_spell_slots(&mut cleric);
+        assert_eq!(cleric.spell_slots.unwrap().remaining(2), 1);
+        assert!(rest_spell_slots(&mut cleric));
+        assert_eq!(cleric.spell_slots.unwrap().expended, [0; 9]);
+        assert!(!rest_spell_slots(&mut cleric));
+
+        let mut fighter = Character::new("Vex");
+        assert!(spend_spell_slot(&mut fighter, 1, None).is_err());
+        assert!(fighter.spell_slots.is_none());
+    }
+
+    #[test]
+    fn test_combat_export() {
+        use crate::saved_combat::{combat_markdown, export_combat};
+
+        let mut mira = Character::new("Mira");
+        mira.class = Some("Cleric".to_string());
+        mira.level = Some(3);
+        mira.hp = Some(20);
+        mira.ac = Some(16);
+        let characters = vec![mira];
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::from_character(&characters[0], 18));
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 15, 12));
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 15, 9));
+        tracker.round_number = 3;
+        tracker.current_turn = 1;
+        let goblin = &mut tracker.combatants[1];
+        goblin.current_hp = 2;
+        goblin.status_effects.push(StatusEffect::new("poisoned", Some(2)));
+
+        let text = combat_markdown(&tracker, &characters);
+        assert!(text.starts_with("# Combat: round 3\n"));
+        assert!(text.contains("It's **Goblin#2**'s turn."));
+        assert!(text.contains("1. **Mira** (PC): HP 20/20, AC 16, initiative 18"));
+        assert!(text.contains("   - Spell slots: 1st ●●●● 2nd ●● (long rest)"));
+        assert!(text.contains("2. ▶ **Goblin#2** (NPC): HP 2/7, AC 15, initiative 12\n   - Poisoned (2 rounds"));
+
+        let path = std::env::temp_dir().join(format!("dnd_tools_export_{}", std::process::id()));
+        let written = export_combat(&path, &tracker, &characters).unwrap();
+        assert_eq!(written.extension().unwrap(), "md");
+        assert_eq!(std::fs::read_to_string(&written).unwrap(), text);
+        let _ = std::fs::remove_file(&written);
+    }
+
+    #[test]
+    fn test_initiative_turn_order() {
+        let mut tracker = InitiativeTracker::new();
+        assert!(tracker.advance().is_none());
+        tracker.add_entry("Goblin".to_string(), 12, false);
+        tracker.add_entry("Lia".to_string(), 18, true);
+        assert!(tracker.current().is_none());
+        assert!(tracker.order_lines()[0].contains("not started"));
+
+        assert_eq!(tracker.advance().unwrap().name, "Lia");
+        assert_eq!(tracker.round(), 1);
+        // Someone joining mid-round doesn't move the turn
+        tracker.add_entry("Ogre".to_string(), 20, false);
+        assert_eq!(tracker.current().unwrap().name, "Lia");
+        let lines = tracker.order_lines();
+        assert_eq!(lines[0], "📋 Initiative order, round 1:");
+        assert_eq!(lines[2], "  ▶  18  Lia (Player)");
+
+        assert_eq!(tracker.advance().unwrap().name, "Goblin");
+        assert_eq!(tracker.advance().unwrap().name, "Ogre");
+        assert_eq!(tracker.round(), 2);
+
+        // Removing someone earlier in the order keeps the turn; removing the last on
+        // their turn passes it to the top of the next round
+        tracker.advance();
+        assert!(tracker.remove_entry("ogre"));
+        assert_eq!(tracker.current().unwrap().name, "Lia");
+        tracker.advance();
+        assert!(tracker.remove_entry("Goblin"));
+        assert_eq!((tracker.current().unwrap().name.as_str(), tracker.round()), ("Lia", 3));
+        assert!(tracker.remove_entry("Lia"));
+        assert_eq!(tracker.round(), 0);
+    }
+
+    #[test]
+    fn test_spell_cards() {
+        use crate::content::{Source, Spell};
+        use crate::spell_cards::{card_spells, spell_cards_markdown};
+
+        let mut wizard = Character::new("Ezra");
+        wizard.class = Some("Wizard".to_string());
+        wizard.level = Some(5);
+        wizard.spells = vec!["Fireball".to_string(), "Fire Bolt".to_string(), "Shield".to_string()];
+        wizard.prepared = vec!["fireball".to_string(), "Shield".to_string()];
+        assert_eq!(card_spells(&wizard), vec!["Fireball", "Fire Bolt", "Shield"]);
+
+        let spell = |name: &str, level: u8| Spell {
+            name: name.to_string(),
+            level,
+            school: "evocation".to_string(),
+            casting_time: "1 action".to_string(),
+            range: "150 feet".to_string(),
+            components: String::new(),
+            duration: "Instantaneous".to_string(),
+            description: format!("{} description.", name),
+            higher_levels: (level > 0).then(|| "More damage.".to_string()),
+            classes: Vec::new(),
+            source: Source::Srd,
+        };
+        let spells = vec![
+            ("Fireball".to_string(), Some(spell("Fireball", 3))),
+            ("Fire Bolt".to_string(), Some(spell("Fire Bolt", 0))),
+            ("Shield".to_string(), None),
+        ];
+        let text = spell_cards_markdown(&wizard, &spells);
+        assert!(text.starts_with("# Ezra's spell cards (level 5 Wizard)"));
+        // Cantrips first, uncached spells last, prepared spells marked
+        let order: Vec<&str> = text.lines().filter(|line| line.starts_with("### ")).collect();
+        assert_eq!(order, vec!["### Fire Bolt", "### Fireball ✦", "### Shield ✦"]);
+        assert!(text.contains("*3rd-level evocation*\n\n**Casting Time:** 1 action  \n**Range:** 150 feet  \n**Duration:** Instantaneous"));
+        assert!(!text.contains("**Components:**"));
+        assert!(text.contains("**At Higher Levels.** More damage."));
+        assert!(text.contains("*Not cached yet."));
+        assert!(text.contains("Creative Commons Attribution 4.0"));
+    }
+
+    #[test]
+    fn test_exploding_and_reroll_dice() {
+        use crate::dice::{roll_dice_with_crits, roll_expression, RollResult};
+
+        // Every six adds a die, so there is always one more die than sixes rolled
+        for _ in 0..50 {
+            let roll = roll_dice_with_crits("3d6!").unwrap();
+            let sixes = roll.kept.iter().filter(|&&d| d == 6).count();
+            assert_eq!(roll.kept.len(), 3 + sixes);
+            assert_eq!(roll.total, roll.kept.iter().map(|&d| d as i32).sum::<i32>());
+        }
+        // A reroll replaces the die once; the new roll stands even if it's low again
+        for _ in 0..50 {
+            let roll = roll_dice_with_crits("2d20r1").unwrap();
+            assert_eq!(roll.kept.len(), 2);
+            assert!(roll.rerolled.iter().all(|&d| d == 1));
+            let low = roll_dice_with_crits("4d6r<3kh3").unwrap();
+            assert_eq!((low.kept.len(), low.dropped.len()), (3, 1));
+            assert!(low.rerolled.iter().all(|&d| d <= 2));
+        }
+        assert!(roll_dice_with_crits("1d1!").is_err());
+        assert!(roll_dice_with_crits("1d6r6").is_err());
+        assert!(roll_dice_with_crits("1d6x").is_err());
+
+        let shown = RollResult { count: 2, sides: 6, kept: vec![6, 3, 2], dropped: vec![], rerolled: vec![1], explodes: true, modifier: 1, total: 12, crit: None };
+        assert_eq!(shown.to_string(), "2d6! (6!,3,2, rerolled 1) + 1 = 12");
+
+        let mixed = roll_expression("1d8+1d6!-2").unwrap();
+        assert_eq!(mixed.dice.len(), 2);
+        assert!(mixed.dice[1].explodes);
+        assert_eq!(mixed.total, mixed.dice[0].total + mixed.dice[1].total - 2);
+        assert!(roll_expression("2d20r1kh1 + 5").unwrap().breakdown.starts_with("2d20 ("));
+    }
+
+    #[test]
+    fn test_token_numbering() {
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::from_character(&Character::new("Lia"), 20));
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 15, 12));
+        tracker.add_combatant(Combatant::new_npc("Goblin 2".to_string(), 7, 15, 16));
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 15, 8));
+        tracker.add_combatant(Combatant::new_npc("Ogre".to_string(), 59, 11, 5));
+
+        // Numbered in the order they joined, not by initiative; a lone ogre is left alone
+        let lines = tracker.token_command("renumber", &[]);
+        assert_eq!(lines, vec!["🔢 Goblin 1, Goblin 2, Goblin 3"]);
+        let names: Vec<String> = tracker.combatants.iter().map(|c| tracker.display_name(c)).collect();
+        assert_eq!(names, vec!["Lia", "Goblin 2", "Goblin 1", "Goblin 3", "Ogre"]);
+        assert_eq!(tracker.get_combatant("goblin 3").unwrap().initiative, 8);
+
+        // Another goblin takes the next number, and a mini can be swapped by hand
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 15, 1));
+        assert!(tracker.get_combatant("Goblin 4").is_some());
+        assert!(tracker.token_command("token", &["Goblin", "1", "2"])[0].starts_with("❌"));
+        assert_eq!(tracker.token_command("token", &["Goblin", "4", "7"]), vec!["🔢 Goblin 4 is now Goblin 7"]);
+        assert_eq!(tracker.token_command("renumber", &["ogre"]), vec!["🔢 Ogre 1"]);
+        assert!(tracker.token_command("renumber", &["dragon"])[0].starts_with("❌"));
+        assert!(tracker.apply_damage("goblin7", 3, None).is_ok());
+        assert_eq!(tracker.get_combatant("Goblin 7").unwrap().current_hp, 4);
+    }
+
+    #[test]
+    fn test_advantage_rolls() {
+        use crate::combat::{Edge, split_edge};
+        for _ in 0..50 {
+            let roll = roll_dice_with_crits("1d20adv").unwrap();
+            assert_eq!((roll.count, roll.kept.len(), roll.dropped.len()), (2, 1, 1));
+            assert!(roll.kept[0] >= roll.dropped[0]);
+            let roll = roll_dice_with_crits("d20+3disadvantage").unwrap();
+            assert!(roll.kept[0] <= roll.dropped[0]);
+            assert_eq!(roll.total, roll.kept[0] as i32 + 3);
+        }
+        assert!(roll_dice_with_crits("2d6adv").is_err());
+        let roll = roll_expression("1d20 + 5 adv").unwrap();
+        assert!(roll.to_string().contains("dropped"));
+        assert!(roll_expression("2d6+3 dis").is_err());
+
+        let parts = ["save", "dex", "self", "dis"];
+        assert_eq!(split_edge(&parts[1..]), (&parts[1..3], Some(Edge::Disadvantage)));
+        assert_eq!(split_edge(&parts[..3]), (&parts[..3], None));
+        assert_eq!(Edge::d20(Some(Edge::Advantage)), "2d20kh1");
+
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 12, 7, 15));
+        let save = tracker.make_saving_throw("Goblin", "dex", &[], Some(Edge::Advantage)).unwrap();
+        assert!(save.contains("Dexterity saving throw with advantage"));
+        assert!(save.contains("dropped"));
+    }
+
+    #[test]
+    fn test_command_palette() {
+        use crate::palette::{PaletteAction, PaletteEntry, fuzzy_score};
+        use crate::tui::{App, AppMode};
+        use crossterm::event::KeyCode;
+
+        let entry = PaletteEntry::from_help(AppMode::CombatTrackerTUI, "  attack <target> [weapon] - Roll attack").unwrap();
+        assert_eq!(entry.action, PaletteAction::Fill("attack ".to_string()));
+        let entry = PaletteEntry::from_help(AppMode::SkillChallengeTUI, "  summary|end - Close the challenge").unwrap();
+        assert_eq!(entry.action, PaletteAction::Run("summary".to_string()));
+        assert!(PaletteEntry::from_help(AppMode::DiceTUI, "    (continued) - not a command").is_none());
+        assert!(fuzzy_score("atk", "attack").is_some());
+        assert!(fuzzy_score("atk", "attack") > fuzzy_score("atk", "a thick"));
+        assert!(fuzzy_score("xyz", "attack").is_none());
+
+        let mut app = App::new(CharacterStore::default());
+        app.output_history.push("kept".to_string());
+        app.toggle_palette();
+        assert_eq!(app.output_history, vec!["kept".to_string()]);
+        for c in "attack".chars() {
+            app.handle_key(KeyCode::Char(c));
+        }
+        let palette = app.palette.as_ref().unwrap();
+        assert_eq!(palette.matches(&app.mode)[0].usage.split_whitespace().next(), Some("attack"));
+        app.handle_key(KeyCode::Enter);
+        assert!(app.palette.is_none());
+        assert_eq!(app.mode, AppMode::CombatTrackerTUI);
+        assert_eq!(app.input_buffer, "attack ");
+
+        // In its own mode a command without arguments runs straight away
+        app.mode = AppMode::DiceTUI;
+        app.input_buffer.clear();
+        app.toggle_palette();
+        for c in "stats".chars() {
+            app.handle_key(KeyCode::Char(c));
+        }
+        app.handle_key(KeyCode::Enter);
+        assert!(app.output_history.iter().any(|line| line.contains("ability scores")));
+        app.toggle_palette();
+        app.handle_key(KeyCode::Esc);
+        assert!(app.palette.is_none());
+    }
+
+    #[test]
+    fn test_attack_profiles() {
+        use crate::attacks::{AttackProfile, choose_attack, crit_damage, resolve_attack};
+
+        let longsword = AttackProfile::parse("Longsword +5, 1d8+3 slashing").unwrap();
+        assert_eq!((longsword.name.as_str(), longsword.to_hit), ("Longsword", 5));
+        assert_eq!(longsword.damage.as_deref(), Some("1d8+3"));
+        assert_eq!(longsword.damage_type, Some(DamageType::Slashing));
+        let spear = AttackProfile::parse("Spear: +3 to hit, reach 5 ft or range 20/60 ft, 1d6+1 piercing (1d8+1 two-handed)").unwrap();
+        assert_eq!(spear.describe(), "Spear: +3 to hit, 1d6+1 piercing");
+        assert!(AttackProfile::parse("Longsword").is_err());
+        assert_eq!(crit_damage("1d8+3"), "2d8+3");
+        assert_eq!(crit_damage("2d6+1d4-1"), "4d6+2d4-1");
+        assert_eq!(crit_damage("d10"), "2d10");
+        let attacks = vec![longsword.clone(), spear.clone()];
+        assert_eq!(choose_attack(&attacks, Some("spear")), Some(&spear));
+        assert_eq!(choose_attack(&attacks, Some("long")), Some(&longsword));
+        assert_eq!(choose_attack(&attacks, None), Some(&longsword));
+        assert_eq!(choose_attack(&attacks, Some("bow")), None);
+
+        // A huge bonus always hits except on a natural 1, and the hit rolls the damage
+        let mut ogre = Combatant::new_npc("Ogre".to_string(), 59, 11, 8);
+        ogre.attacks.push(AttackProfile::parse("Greatclub +50, 2d8+4 bludgeoning").unwrap());
+        let target = Combatant::new_npc("Knight".to_string(), 52, 30, 10);
+        for _ in 0..30 {
+            let outcome = resolve_attack(Some(&ogre), &target, None, None, None, &[]).unwrap();
+            assert_eq!(outcome.expression, "1d20+50");
+            assert_eq!(outcome.hit, outcome.roll.natural() != 1);
+            if let Some(damage) = outcome.damage {
+                assert_eq!(damage.kind, Some(DamageType::Bludgeoning));
+                let most = if outcome.roll.is_critical_success() { 36 } else { 20 };
+                assert!((6..=most).contains(&damage.amount));
+            }
+        }
+        // Without a profile a sheet gives proficiency plus Strength, and damage is asked for
+        let mut bram = Character::new("Bram");
+        bram.stre = Some(16);
+        bram.prof_bonus = Some(2);
+        let fighter = Combatant::from_character(&bram, 12);
+        let outcome = resolve_attack(Some(&fighter), &target, Some("club"), None, Some(crate::combat::Edge::Advantage), std::slice::from_ref(&bram)).unwrap();
+        assert_eq!(outcome.expression, "2d20kh1+5");
+        assert!(outcome.damage.is_none());
+
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 15, 12));
+        assert!(tracker.attacks_command(&[], &[])[0].contains("no attacks"));
+        assert!(tracker.attacks_command(&["goblin", "add", "Scimitar", "+4,", "1d6+2", "slashing"], &[])[0].contains("Scimitar: +4 to hit"));
+        assert_eq!(tracker.get_combatant("Goblin").unwrap().attacks.len(), 1);
+        assert!(tracker.attacks_command(&["goblin", "remove", "scimitar"], &[])[0].starts_with("Removed"));
+        assert!(tracker.get_combatant("Goblin").unwrap().attacks.is_empty());
+    }
+
+    #[test]
+    fn test_wealth_audit() {
+        use crate::wealth::{WealthAudit, coins_cp, expected_wealth_gp, gear_cp, wealth_report};
+
+        assert_eq!(coins_cp("1,200 gp"), Some(120_000));
+        assert_eq!(coins_cp("35 SP"), Some(350));
+        assert_eq!(coins_cp("Diamond (300 gp)"), None);
+        assert_eq!(gear_cp("Diamond (300 gp)"), Some(30_000));
+        assert_eq!(gear_cp("2 Handaxes"), Some(1_000));
+        assert_eq!(gear_cp("Chain mail"), Some(7_500));
+        assert_eq!(expected_wealth_gp(1, Some("Fighter")), 125);
+        assert_eq!(expected_wealth_gp(7, None), 637);
+        assert_eq!(expected_wealth_gp(12, None), 6375);
+
+        let mut bram = Character::new("Bram");
+        bram.level = Some(5);
+        bram.class = Some("Fighter".to_string());
+        bram.inventory = vec!["Chain mail".to_string(), "Longsword".to_string(), "600 gp".to_string(), "Flumph-shaped paperweight".to_string()];
+        bram.ammo.push(crate::ammo::AmmoCounter { kind: "bolts".to_string(), count: 20, expended: 0 });
+        let audit = WealthAudit::of(&bram);
+        assert_eq!((audit.coins_cp, audit.gear_cp), (60_000, 9_100));
+        assert_eq!(audit.total_gp(), 691);
+        assert_eq!(audit.unpriced, vec!["Flumph-shaped paperweight".to_string()]);
+        assert_eq!(audit.flag(), None);
+
+        let mut lia = Character::new("Lia");
+        lia.level = Some(5);
+        lia.inventory = vec!["10 gp".to_string()];
+        assert_eq!(WealthAudit::of(&lia).flag(), Some("⬇️ well below"));
+        let report = wealth_report(&[bram, lia]);
+        assert!(report.iter().any(|line| line.contains("Bram (level 5 Fighter): 691 gp") && line.contains("on track")));
+        assert!(report.iter().any(|line| line.starts_with("⚠️ Lia")));
+        assert!(report.iter().any(|line| line.starts_with("⚖️ Bram")));
+    }
+
+    #[test]
+    fn test_legendary_and_lair_actions() {
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Adult Red Dragon".to_string(), 256, 19, 22));
+        tracker.add_combatant(Combatant::new_npc("Bram".to_string(), 40, 16, 18));
+        tracker.add_combatant(Combatant::new_npc("Lia".to_string(), 30, 14, 12));
+        let order: Vec<&str> = tracker.combatants.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(order, ["Adult Red Dragon", "Bram", "Lia"]);
+
+        assert!(tracker.legendary_command(&["adult", "red", "dragon", "3"])[0].contains("3 legendary actions a round"));
+        tracker.current_turn = 1;
+        let spent = tracker.legendary_command(&["Adult", "Red", "Dragon", "wing", "attack", "2"]);
+        assert_eq!(spent, ["👑 Adult Red Dragon uses wing attack (2 legendary actions): 1/3 left"]);
+        assert!(tracker.legendary_command(&["adult", "red", "dragon", "wing", "attack", "2"])[0].starts_with("❌"));
+        assert!(tracker.legendary_command(&["Bram", "tail"])[0].contains("no legendary actions"));
+
+        // The lair acts after everyone on 20 or more, and the dragon's actions come back on its turn
+        tracker.lair_command(&["adult", "red", "dragon"]);
+        assert_eq!(tracker.lair.as_deref(), Some("Adult Red Dragon"));
+        assert_eq!(tracker.lair_row(), Some(1));
+        assert_eq!(tracker.turn_reminders(1), ["🏰 Lair action on initiative 20 (Adult Red Dragon's lair)"]);
+        assert!(tracker.turn_reminders(2).is_empty());
+        assert_eq!(tracker.turn_reminders(0), ["👑 Adult Red Dragon regains its 3 legendary actions"]);
+        assert_eq!(tracker.combatants[0].legendary.map(|pool| pool.left()), Some(3));
+
+        // With no one on 20 or more, the lair goes first in the round
+        tracker.combatants[0].initiative = 15;
+        assert_eq!(tracker.lair_row(), Some(0));
+        assert_eq!(tracker.turn_reminders(0).len(), 1);
+        assert!(tracker.turn_reminders(1).is_empty());
+        tracker.lair_command(&["off"]);
+        assert!(tracker.turn_reminders(0).is_empty() && tracker.lair_row().is_none());
+    }
+
+    #[test]
+    fn test_npc_temperament() {
+        use crate::temperament::*;
+        use rand::{rngs::StdRng, SeedableRng};
+        use std::fs;
+
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..20 {
+            let temperament = Temperament::random(&mut rng);
+            assert!((2..=3).contains(&temperament.hints.len()));
+            assert_ne!(temperament.alignment, "Neutral Neutral");
+            assert_eq!(Temperament::parse_sheet_line(&temperament.sheet_line()), Some(temperament));
+        }
+        assert_eq!(Temperament::parse_sheet_line("Voice: deep | slow | crisp | hums | proverbs"), None);
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_temperament_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Mayor Pell.txt"), "Name: Mayor Pell\nType: NPC\n").unwrap();
+        let mut last = None;
+        assert!(temperament_command(&dir, &["save", "Mayor", "Pell"], &mut last, &mut rng)[0].starts_with("No temperament"));
+        temperament_command(&dir, &[], &mut last, &mut rng);
+        assert!(temperament_command(&dir, &["save", "Mayor", "Pell"], &mut last, &mut rng)[0].starts_with("💾"));
+        assert_eq!(saved_temperament_in(&dir, "Mayor Pell"), last);
+        let reminder = join_reminder(&dir, "Mayor Pell").unwrap();
+        assert!(reminder.starts_with("  Mayor Pell 🎭 ") && reminder.contains(&last.as_ref().unwrap().hints[0]));
+        assert_eq!(join_reminder(&dir, "Nobody"), None);
+
+        // Scene prep shows the hints rather than the raw sheet line
+        let mut adventure = crate::adventure::Adventure::default();
+        crate::adventure::scene_command(&mut adventure, &["add", "Town", "hall"], &dir, &dir);
+        let (attached, _) = crate::adventure::scene_command(&mut adventure, &["npc", "1", "Mayor", "Pell"], &dir, &dir);
+        assert_eq!(attached[1], reminder);
+        let prep = adventure.prep(0, &dir, &dir);
+        assert!(prep.iter().any(|line| line.trim_start().starts_with("🎭")));
+        assert!(!prep.iter().any(|line| line.contains("Temperament: ")));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_concentration_saves() {
+        assert_eq!((concentration_dc(7), concentration_dc(20), concentration_dc(45)), (10, 10, 22));
+
+        let mut tracker = CombatTracker::new();
+        let mut mira = Combatant::new_npc("Mira".to_string(), 40, 12, 15);
+        mira.is_player = true;
+        tracker.add_combatant(mira);
+        tracker.add_combatant(Combatant::new_npc("Ogre".to_string(), 59, 11, 8));
+        tracker.add_combatant(Combatant::new_npc("Cultist".to_string(), 9, 12, 5));
+
+        assert!(tracker.concentrate_command(&["Mira", "Hold", "Person"], &[])[0].contains("concentrating on Hold Person"));
+        let mut held = StatusEffect::new("paralyzed", Some(10));
+        held.concentration = Some("Mira".to_string());
+        tracker.get_combatant_mut("Ogre").unwrap().add_status(held);
+
+        // A party member is asked to roll; their own total settles it
+        let hit = tracker.apply_damage("Mira", 30, None).unwrap();
+        assert!(hit.contains("must make a DC 15 Constitution save"));
+        assert_eq!(tracker.get_combatant("Mira").unwrap().concentration_tag(), " [🧠 Hold Person]");
+        let kept = tracker.concentrate_command(&["mira", "15"], &[]);
+        assert_eq!(kept[1], "✅ Mira keeps concentrating on Hold Person (DC 15)");
+        assert!(tracker.concentrate_command(&["mira", "roll"], &[])[0].contains("doesn't owe"));
+        tracker.apply_damage("Mira", 4, None).unwrap();
+        let failed = tracker.concentrate_command(&["mira", "9"], &[]);
+        assert!(failed[1].contains("fails the DC 10 save and loses concentration on Hold Person"));
+        assert_eq!(failed[2], "  Ogre is no longer Paralyzed");
+        assert!(tracker.get_combatant("Mira").unwrap().concentration.is_none());
+
+        // An NPC rolls at once, and dropping to 0 HP ends the spell without a save
+        tracker.concentrate_command(&["Ogre", "Bane"], &[]);
+        assert!(tracker.apply_damage("Ogre", 5, None).unwrap().contains("🎲 Ogre makes a Constitution save"));
+        tracker.concentrate_command(&["Cultist", "Bless"], &[]);
+        let dropped = tracker.apply_damage("Cultist", 20, None).unwrap();
+        assert!(dropped.contains("💫 Cultist drops to 0 HP and loses concentration on Bless"));
+        assert!(!dropped.contains("Constitution save"));
+        assert!(tracker.concentrate_command(&["Mira", "end"], &[])[0].contains("isn't concentrating"));
+    }
+
+    #[test]
+    fn test_difficulty_adjustment_hints() {
+        use crate::balance::{Tilt, adjust_command, assess, turn_hints};
+        use crate::tables::{RandomTable, TableRegistry};
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut tables = TableRegistry::new();
+        tables.add(RandomTable::from_csv("Forest Encounters", "2 wolves\n"));
+        let mut tracker = CombatTracker::new();
+        for name in ["Bram", "Lia", "Mira"] {
+            let mut pc = Combatant::new_npc(name.to_string(), 30, 15, 12);
+            pc.is_player = true;
+            tracker.add_combatant(pc);
+        }
+        tracker.add_combatant(Combatant::new_npc("Ogre".to_string(), 60, 11, 8));
+        assert_eq!(assess(&tracker), None);
+        assert!(adjust_command(&mut tracker, &[], &tables, &mut rng)[0].contains("looks balanced"));
+
+        // After three full rounds with the party barely scratched, reinforcements come from the table
+        tracker.round_number = 4;
+        tracker.get_combatant_mut("Ogre").unwrap().current_hp = 40;
+        assert!(adjust_command(&mut tracker, &["table", "nowhere"], &tables, &mut rng)[0].starts_with("❌"));
+        adjust_command(&mut tracker, &["table", "forest-encounters"], &tables, &mut rng);
+        assert_eq!(tracker.reinforcement_table.as_deref(), Some("Forest Encounters"));
+        let easy = turn_hints(&mut tracker, &tables, &mut rng);
+        assert!(easy[0].contains("cruising (100% HP after 3 rounds)"));
+        assert!(easy.contains(&"  • Reinforcements arrive: 2 wolves (rolled on 'Forest Encounters')".to_string()));
+        assert!(easy.iter().any(|line| line.contains("'heal Ogre 10'")));
+        assert!(turn_hints(&mut tracker, &tables, &mut rng).is_empty());
+
+        // Two PCs down tips it the other way
+        tracker.get_combatant_mut("Bram").unwrap().current_hp = 0;
+        tracker.get_combatant_mut("Lia").unwrap().current_hp = 0;
+        assert_eq!(assess(&tracker), Some(Tilt::TooHard));
+        let hard = turn_hints(&mut tracker, &tables, &mut rng);
+        assert!(hard[0].contains("(Bram, Lia down)"));
+        assert!(hard.iter().any(|line| line.contains("'hit Ogre 10'")));
+        assert!(hard.iter().any(|line| line.contains("morale")));
+
+        // Nothing to suggest once the foes are beaten
+        tracker.get_combatant_mut("Ogre").unwrap().current_hp = 0;
+        assert_eq!(assess(&tracker), None);
+    }
+
+    #[test]
+    fn test_offline_srd_lookups() {
+        use crate::content::{Content, Source};
+        use crate::search::{MonsterStatBlock, SearchCategory};
+        use crate::srd::{is_installed, lookup_in};
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_srd_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(lookup_in(&dir, "Fire Bolt", SearchCategory::Spells).is_err());
+        std::fs::write(dir.join("spells.json"), r#"[{"index": "fire-bolt", "name": "Fire Bolt",
+            "desc": ["You hurl a mote of fire at a creature or object within range."],
+            "higher_level": ["This spell's damage increases by 1d10 when you reach 5th level."],
+            "range": "120 feet", "components": ["V", "S"], "ritual": false, "duration": "Instantaneous",
+            "concentration": false, "casting_time": "1 action", "level": 0,
+            "school": {"index": "evocation", "name": "Evocation"},
+            "classes": [{"index": "sorcerer", "name": "Sorcerer"}, {"index": "wizard", "name": "Wizard"}]}]"#).unwrap();
+        std::fs::write(dir.join("monsters.json"), r#"[{"index": "goblin", "name": "Goblin", "size": "Small",
+            "type": "humanoid", "subtype": "goblinoid", "alignment": "neutral evil",
+            "armor_class": [{"type": "armor", "value": 15}], "hit_points": 7, "hit_dice": "2d6",
+            "hit_points_roll": "2d6", "speed": {"walk": "30 ft."}, "strength": 8, "dexterity": 14,
+            "constitution": 10, "intelligence": 10, "wisdom": 8, "charisma": 8,
+            "damage_vulnerabilities": [], "damage_resistances": [], "damage_immunities": [],
+            "condition_immunities": [], "senses": {"darkvision": "60 ft.", "passive_perception": 9},
+            "languages": "Common, Goblin", "challenge_rating": 0.25, "xp": 50,
+            "actions": [{"name": "Scimitar", "desc": "Melee Weapon Attack: +4 to hit, reach 5 ft., one target. Hit: 5 (1d6 + 2) slashing damage."}]}]"#).unwrap();
+        std::fs::write(dir.join("equipment.json"), r#"[{"index": "longsword", "name": "Longsword",
+            "equipment_category": {"name": "Weapon"}, "category_range": "Martial Melee",
+            "cost": {"quantity": 15, "unit": "gp"}, "weight": 3,
+            "damage": {"damage_dice": "1d8", "damage_type": {"name": "Slashing"}},
+            "properties": [{"name": "Versatile"}]}]"#).unwrap();
+        assert!(is_installed(&dir));
+
+        // Entries read like wiki pages, so the usual parsers understand them
+        let page = lookup_in(&dir, "fire-bolt", SearchCategory::Spells).unwrap();
+        assert_eq!(page.source(), Source::Srd);
+        let Content::Spell(spell) = Content::from_page(&page).unwrap() else { panic!("not a spell") };
+        assert_eq!((spell.level, spell.school.as_str(), spell.range.as_str()), (0, "evocation", "120 feet"));
+        assert_eq!(spell.classes, vec!["Sorcerer", "Wizard"]);
+        assert!(spell.higher_levels.unwrap().contains("1d10"));
+
+        let goblin = MonsterStatBlock::from_page(&lookup_in(&dir, "Goblin", SearchCategory::Monsters).unwrap()).unwrap();
+        assert_eq!((goblin.ac, goblin.hp, goblin.cr.as_deref()), (15, 7, Some("1/4")));
+        assert_eq!(goblin.dex_mod(), 2);
+        assert_eq!(goblin.attacks.len(), 1);
+
+        let Content::Item(sword) = Content::from_page(&lookup_in(&dir, "longsword", SearchCategory::Equipment).unwrap()).unwrap() else { panic!("not an item") };
+        assert_eq!((sword.cost_gp, sword.weight_lb), (Some(15), Some(3.0)));
+
+        assert!(lookup_in(&dir, "Tarrasque", SearchCategory::Monsters).is_err());
+        assert!(lookup_in(&dir, "Wizard", SearchCategory::Classes).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_status_presets() {
+        use crate::character::AbilityScore;
+        use crate::status_presets::StatusLibrary;
+
+        let mut library = StatusLibrary::default();
+        assert_eq!(library.status("blessed").modifiers.attacks.as_deref(), Some("+1d4"));
+        let added = library.add_toml(r#"
+            [[statuses]]
+            name = "Burning"
+            description = "Flames cling until someone spends an action to douse them"
+            ticks = "start"
+            damage = "1d6 fire"
+
+            [[statuses]]
+            name = "Frostbitten"
+            duration = 2
+            attacks = "dis"
+            saves = "-2"
+
+            [[statuses]]
+            name = "Scorched"
+            ticks = "start"
+            damage = "2d6+1d4 fire"
+        "#).unwrap();
+        assert_eq!(added, 3);
+        assert!(library.add_toml("[[statuses]]\nname = \"Odd\"\nticks = \"noon\"\n").is_err());
+        assert!(library.lines().iter().any(|line| line.contains("Burning - 1d6 fire at the start of each turn; until removed")));
+
+        let mut tracker = CombatTracker::new();
+        tracker.add_combatant(Combatant::new_npc("Bob".to_string(), 30, 12, 15));
+        tracker.add_combatant(Combatant::new_npc("Ogre".to_string(), 59, 11, 8));
+        let burning = library.status("burning");
+        assert_eq!((burning.name.as_str(), burning.ticks_at, burning.duration), ("Burning", TickPoint::StartOfTurn, None));
+        assert!(burning.detail_lines("Bob").contains(&"🔥 1d6 fire damage at the start of each of Bob's turns".to_string()));
+        tracker.get_combatant_mut("Bob").unwrap().add_status(burning);
+        tracker.get_combatant_mut("Bob").unwrap().add_status(library.status("Frostbitten"));
+        // An unknown name is still a plain status
+        assert_eq!(library.status("Glowing").damage, None);
+
+        // The fire burns at the start of Bob's turn, and not at the end
+        assert!(tracker.tick_status_effects(0, TickPoint::EndOfTurn).is_empty());
+        let burned = tracker.tick_status_effects(0, TickPoint::StartOfTurn);
+        assert!(burned[0].starts_with("🔥 Bob is burning: 1d6"));
+        let hp = tracker.get_combatant("Bob").unwrap().current_hp;
+        assert!((24..=29).contains(&hp));
+
+        // Damage can be any dice expression
+        tracker.get_combatant_mut("Ogre").unwrap().add_status(library.status("Scorched"));
+        let scorched = tracker.tick_status_effects(1, TickPoint::StartOfTurn);
+        assert!(scorched[0].starts_with("🔥 Ogre is scorched"), "{:?}", scorched);
+        assert!((43..=56).contains(&tracker.get_combatant("Ogre").unwrap().current_hp));
+
+        let bob = tracker.get_combatant("Bob").unwrap();
+        let hints = condition_attack_hints(Some(bob), tracker.get_combatant("Ogre").unwrap());
+        assert!(hints.contains(&"🔽 Disadvantage on Bob's attack rolls (Frostbitten)".to_string()));
+        assert!(bob.save_hints(AbilityScore::Wisdom).contains(&"🎲 -2 to Bob's saving throws (Frostbitten)".to_string()));
+    }
+
+    #[cfg(feature = "rest-api")]
+    #[test]
+    fn test_rest_api_requests() {
+        use crate::error_handling::AppError;
+        use crate::rest_api::{parse_request, ApiRequest, ApiServer, ApiState};
+        use std::io::{Read, Write};
+
+        assert_eq!(parse_request("GET", "/combat", "").unwrap(), ApiRequest::State);
+        assert_eq!(parse_request("POST", "/next/", "").unwrap(), ApiRequest::Next);
+        assert_eq!(parse_request("POST", "/combatants/Goblin%202/damage", r#"{"amount": 7, "type": "fire"}"#).unwrap(),
+            ApiRequest::Damage { target: "Goblin 2".to_string(), amount: 7, kind: Some(DamageType::Fire) });
+        assert!(matches!(parse_request("POST", "/combatants/Ogre/damage", r#"{"amount": -3}"#), Err(AppError::ValidationError(_))));
+        assert!(matches!(parse_request("DELETE", "/combat", ""), Err(AppError::NotFound(_))));
+
+        // Hidden HP and hidden combatants stay hidden from overlays
+        let mut tracker = CombatTracker::new();
+        let mut pc = Combatant::new_npc("Mira".to_string(), 20, 14, 15);
+        pc.is_player = true;
+        tracker.add_combatant(pc);
+        tracker.add_combatant(Combatant::new_npc("Ogre".to_string(), 59, 11, 8));
+        let mut lurker = Combatant::new_npc("Lurker".to_string(), 10, 12, 3);
+        lurker.hidden = true;
+        tracker.add_combatant(lurker);
+        tracker.hide_monster_hp = true;
+        let state = ApiState::from_tracker(&tracker);
+        assert_eq!(state.current.as_deref(), Some("Mira"));
+        assert_eq!(state.combatants.len(), 2);
+        assert_eq!((state.combatants[0].hp, state.combatants[1].hp), (Some(20), None));
+
+        // Only this machine may listen, and a request comes back as JSON
+        assert!(ApiServer::bind("0.0.0.0:0").is_err());
+        let server = ApiServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let client = std::thread::spawn(move || {
+            let mut stream = std::net::TcpStream::connect(addr).unwrap();
+            stream.write_all(b"GET /combatants HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        let mut calls = Vec::new();
+        for _ in 0..100 {
+            calls = server.accept();
+            if !calls.is_empty() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let (call, request) = calls.pop().expect("the client's request");
+        assert_eq!(request.unwrap(), ApiRequest::Combatants);
+        call.respond(Ok(serde_json::json!(state.combatants)));
+        let response = client.join().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains(r#""name":"Ogre""#) && response.contains(r#""hp":null"#));
+    }
+
+    #[test]
+    fn test_stream_overlay_files() {
+        use crate::combat::{CombatTracker, Combatant, StatusEffect};
+        use crate::overlay::{OverlayConfig, OverlayState, OverlayWriter};
+
+        let mut tracker = CombatTracker::new();
+        let mut aria = Combatant::new_npc("Aria".to_string(), 30, 15, 18);
+        aria.is_player = true;
+        tracker.add_combatant(aria);
+        tracker.add_combatant(Combatant::new_npc("Ogre".to_string(), 60, 11, 8));
+        tracker.add_combatant(Combatant::new_npc("Assassin".to_string(), 40, 15, 12));
+        tracker.set_hidden("Assassin", true).unwrap();
+        tracker.apply_damage("Ogre", 35, None).unwrap();
+        tracker.combatants[0].status_effects.push(StatusEffect::new("Blessed", Some(10)));
+
+        // By default viewers see the order, conditions and statuses, but no numbers
+        let text = OverlayState::from_tracker(Some(&tracker), &OverlayConfig::default()).to_text();
+        assert_eq!(text, "Round 1\n▶ Aria (Unhurt) [Blessed]\n  Ogre (Bloodied)\n");
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_overlay_{}", std::process::id()));
+        let config = OverlayConfig::from_toml(&format!("dir = {:?}\nplayer_hp = true\nstatuses = false\nformats = [\"txt\", \"json\", \"html\"]", dir.display().to_string())).unwrap();
+        assert!(OverlayConfig::from_toml("formats = [\"png\"]").is_err());
+        let mut writer = OverlayWriter::new(config);
+        assert!(writer.update(Some(&tracker)).unwrap());
+        assert_eq!(std::fs::read_to_string(dir.join("initiative.txt")).unwrap(), "Round 1\n▶ Aria (Unhurt, 30/30 HP)\n  Ogre (Bloodied)\n");
+        let json = std::fs::read_to_string(dir.join("initiative.json")).unwrap();
+        assert!(json.contains("\"30/30\"") && !json.contains("Assassin") && !json.contains("25/60"));
+        assert!(std::fs::read_to_string(dir.join("initiative.html")).unwrap().contains("<li class=\"current\">Aria (Unhurt, 30/30 HP)</li>"));
+
+        // Nothing is rewritten until the fight changes
+        assert!(!writer.update(Some(&tracker)).unwrap());
+        tracker.apply_damage("Aria", 10, None).unwrap();
+        assert!(writer.update(Some(&tracker)).unwrap());
+        assert!(writer.update(None).unwrap());
+        assert_eq!(std::fs::read_to_string(dir.join("initiative.txt")).unwrap(), "No combat\n");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_anonymized_exports() {
+        use crate::anonymize::{anonymize_character, anonymize_combat};
+        use crate::character::Character;
+        use crate::combat::{CombatTracker, Combatant, StatusEffect};
+        use crate::companions::{Companion, CompanionKind};
+
+        let mut aria = Character::new("Aria Stormwind");
+        aria.class = Some("Wizard".to_string());
+        aria.level = Some(5);
+        aria.stre = Some(8);
+        aria.hp = Some(27);
+        aria.desc = Some("Sworn enemy of Lord Varn of Greyhollow".to_string());
+        aria.inventory = vec!["Cloak of Greyhollow".to_string(), "Rope".to_string()];
+        aria.spells = vec!["Fireball".to_string()];
+        aria.companions.push(Companion::new("Hoot", CompanionKind::Familiar, 1, 11));
+
+        let sheet = anonymize_character(&aria).unwrap();
+        assert!(!sheet.contains("Aria") && !sheet.contains("Greyhollow") && !sheet.contains("Hoot"));
+        // Still a character file, with the numbers and rules terms intact
+        let loaded = crate::file_manager::parse_character_file(std::path::Path::new("anon.json"), &sheet).unwrap();
+        assert_eq!(loaded.name, "Character 1");
+        assert_eq!((loaded.level, loaded.stre, loaded.hp), (Some(5), Some(8), Some(27)));
+        assert_eq!(loaded.desc.as_deref(), Some("<redacted>"));
+        assert_eq!(loaded.inventory, vec!["Item 1", "Item 2"]);
+        assert_eq!(loaded.spells, vec!["Fireball"]);
+        assert_eq!(loaded.companions[0].name, "Companion 1");
+
+        let mut tracker = CombatTracker::new();
+        let mut pc = Combatant::new_npc("Aria Stormwind".to_string(), 27, 12, 15);
+        pc.is_player = true;
+        pc.status_effects.push(StatusEffect::new("Marked by Goblin Boss", Some(1)));
+        tracker.add_combatant(pc);
+        let mut familiar = Combatant::new_npc("Hoot".to_string(), 1, 11, 14);
+        familiar.owner = Some("Aria Stormwind".to_string());
+        tracker.add_combatant(familiar);
+        tracker.add_combatant(Combatant::new_npc("Goblin Boss".to_string(), 21, 17, 10));
+        tracker.add_combatant(Combatant::new_npc("Goblin".to_string(), 7, 15, 12));
+        tracker.apply_damage("Goblin", 3, None).unwrap();
+
+        let fight: serde_json::Value = serde_json::from_str(&anonymize_combat(&tracker, std::slice::from_ref(&aria)).unwrap()).unwrap();
+        let text = fight.to_string();
+        assert!(!text.contains("Aria") && !text.contains("Goblin") && !text.contains("Hoot"));
+        let combatant = |name: &str| fight["combatants"].as_array().unwrap().iter().find(|c| c["name"] == name).unwrap().clone();
+        // The same name gets the same stand-in wherever it's mentioned
+        assert_eq!(combatant("Combatant 1")["owner"], "Character 1");
+        // Stand-ins follow initiative order: Hoot, Goblin, Goblin Boss
+        assert_eq!(combatant("Character 1")["status_effects"][0]["name"], "Marked by Combatant 3");
+        assert_eq!((combatant("Combatant 2")["current_hp"].as_i64(), combatant("Combatant 2")["ac"].as_i64()), (Some(4), Some(15)));
+        assert_eq!(fight["round_number"], 1);
+    }
+
+    #[test]
+    fn test_shared_runtime() {
+        use crate::runtime::{block_on, shared};
+
+        // One runtime, shared by every thread that waits on network work
+        let first: *const tokio::runtime::Runtime = shared().unwrap();
+        let threads: Vec<_> = (0..4).map(|i| std::thread::spawn(move || block_on(async move { i * 2 }).unwrap())).collect();
+        assert_eq!(threads.into_iter().map(|t| t.join().unwrap()).collect::<Vec<_>>(), vec![0, 2, 4, 6]);
+        assert!(std::ptr::eq(first, shared().unwrap()));
+
+        // Inside async code it waits on that runtime instead of panicking
+        let nested = shared().unwrap().block_on(async { tokio::spawn(async { block_on(async { 7 }) }).await.unwrap() });
+        assert_eq!(nested.unwrap(), 7);
+        let single = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        assert!(single.block_on(async { block_on(async { 1 }) }).is_err());
+    }
+
+    #[test]
+    fn test_character_index() {
+        use crate::file_manager::{character_index_in, load_characters_in, write_character_file};
+        use std::fs;
+
+        let dir = std::env::temp_dir().join(format!("dnd_tools_index_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let index_path = dir.join("data").join("index.json");
+        let sheets = dir.join("characters");
+        fs::create_dir_all(&sheets).unwrap();
+        let mut aria = Character::new("Aria");
+        aria.class = Some("Wizard".to_string());
+        aria.level = Some(3);
+        write_character_file(&sheets, &aria).unwrap();
+        let bram_path = write_character_file(&sheets, &Character::new("Bram")).unwrap();
+
+        let first = character_index_in(&sheets, &index_path);
+        assert_eq!(first.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), vec!["Aria", "Bram"]);
+        assert_eq!((first[0].class.as_deref(), first[0].level), (Some("Wizard"), Some(3)));
+
+        // Unchanged sheets come from the index without being parsed again
+        let tampered = fs::read_to_string(&index_path).unwrap().replace("Wizard", "Sorcerer");
+        fs::write(&index_path, tampered).unwrap();
+        assert_eq!(character_index_in(&sheets, &index_path)[0].class.as_deref(), Some("Sorcerer"));
+
+        // A changed sheet is read again, and a deleted one drops out
+        aria.level = Some(4);
+        aria.desc = Some("Now with a longer description".to_string());
+        write_character_file(&sheets, &aria).unwrap();
+        fs::remove_file(&bram_path).unwrap();
+        let updated = character_index_in(&sheets, &index_path);
+        assert_eq!(updated.len(), 1);
+        assert_eq!((updated[0].class.as_deref(), updated[0].level), (Some("Wizard"), Some(4)));
+
+        // The full load still agrees with the index
+        let loaded = load_characters_in(&sheets);
+        assert_eq!(loaded.iter().map(crate::file_manager::CharacterSummary::of).collect::<Vec<_>>(), updated);
+
+        // Stores built from sheets need no index
+        let store = crate::registry::CharacterStore::new(loaded);
+        assert_eq!((store.len(), store.summaries()), (1, updated));
+
+        // A loaded store reads each sheet the first time it's looked up
+        let mut cade = Character::new("Cade");
+        cade.id = 9;
+        write_character_file(&sheets, &cade).unwrap();
+        let bram_path = write_character_file(&sheets, &Character::new("Bram")).unwrap();
+        let store = crate::registry::CharacterStore::load_in(&sheets, &index_path);
+        assert_eq!(store.summaries().iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), vec!["Aria", "Bram", "Cade"]);
+        fs::remove_file(&bram_path).unwrap();
+        assert_eq!(store.find("aria").unwrap().level, Some(4));
+        assert_eq!(store.get(9).unwrap().name, "Cade");
+        assert_eq!(store.len(), 3);
+        // New ids stay clear of sheets not read yet, and only the sheets still on disk turn up
+        let dara = store.insert(Character::new("Dara"));
+        assert!(dara > 9);
+        let names: Vec<String> = store.read().iter().map(|c| c.name.clone()).collect();
+        assert_eq!(names, vec!["Aria", "Cade", "Dara"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_level_up() {
+        use crate::leveling::{grants_improvement, hit_die, level_up, Improvement};
+
+        assert_eq!((hit_die("Barbarian"), hit_die("wizard"), hit_die("Homebrew")), (12, 6, 8));
+        assert!(grants_improvement("Fighter", 6) && !grants_improvement("Wizard", 6) && grants_improvement("Rogue", 10));
+
+        assert_eq!(Improvement::parse("+2 str").unwrap(), Improvement::Abilities(vec![(AbilityScore::Strength, 2)]));
+        assert_eq!(Improvement::parse("dex con").unwrap(), Improvement::Abilities(vec![(AbilityScore::Dexterity, 1), (AbilityScore::Constitution, 1)]));
+        assert_eq!(Improvement::parse("feat War Caster").unwrap(), Improvement::Feat("War Caster".to_string()));
+        assert!(Improvement::parse("+2 str +1 dex").is_err() && Improvement::parse("feat").is_err() && Improvement::parse("+2 luck").is_err());
+        assert!(Improvement::parse("+255 str str").is_err() && Improvement::parse("+200 str +200 str").is_err());
+
+        let mut wizard = Character::new("Aria");
+        wizard.class = Some("Wizard".to_string());
+        wizard.level = Some(3);
+        (wizard.cons, wizard.max_hp, wizard.hp, wizard.prof_bonus) = (Some(14), Some(20), Some(15), Some(2));
+        crate::spell_slots::refresh_spell_slots(&mut wizard);
+
+        // Scores stop at 20, and a refused improvement leaves the sheet alone
+        wizard.intl = Some(19);
+        assert!(level_up(&mut wizard, 4, Some(&Improvement::parse("+2 int").unwrap())).is_err());
+        assert_eq!((wizard.level, wizard.intl), (Some(3), Some(19)));
+
+        // +2 Constitution raises the modifier, which counts for the earlier levels too
+        let lines = level_up(&mut wizard, 4, Some(&Improvement::parse("+2 con").unwrap())).unwrap();
+        assert_eq!((wizard.level, wizard.cons, wizard.max_hp, wizard.hp), (Some(4), Some(16), Some(30), Some(25)));
+        assert!(lines.contains(&"  New spell slots: 2nd ●".to_string()));
+        assert_eq!(wizard.spell_slots.unwrap().max[1], 3);
+
+        let lines = level_up(&mut wizard, 1, None).unwrap();
+        assert_eq!((wizard.level, wizard.prof_bonus, wizard.max_hp), (Some(5), Some(3), Some(34)));
+        assert!(lines.iter().any(|l| l.contains("Proficiency bonus +2 → +3")) && lines.iter().any(|l| l.contains("3rd ●●")));
+
+        level_up(&mut wizard, 1, Some(&Improvement::Feat("Alert".to_string()))).unwrap();
+        assert_eq!(wizard.feats, vec!["Alert".to_string()]);
+        wizard.level = Some(20);
+        assert!(level_up(&mut wizard, 1, None).is_err());
+    }
+
+    #[test]
+    fn test_ability_score_generation() {
+        use crate::ability_scores::{assign, point_buy, roll_4d6_drop_lowest, ScoreMethod, STANDARD_ARRAY};
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        assert!((0..200).all(|_| (3..=18).contains(&roll_4d6_drop_lowest(&mut rng))));
+        let rolled = ScoreMethod::Roll.pool(&mut rng).unwrap();
+        assert!(rolled.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_eq!(ScoreMethod::parse("standard array"), Some(ScoreMethod::StandardArray));
+        assert_eq!(ScoreMethod::parse("point-buy"), Some(ScoreMethod::PointBuy));
+
+        // Each score in the pool is used exactly once, in STR DEX CON INT WIS CHA order
+        assert_eq!(assign("8 14 13 15 12 10", &STANDARD_ARRAY).unwrap(), [8, 14, 13, 15, 12, 10]);
+        assert!(assign("15 15 13 12 10 8", &STANDARD_ARRAY).is_err());
+        assert!(assign("15 14 13 12 10", &STANDARD_ARRAY).is_err());
+
+        // 27 points, 8 to 15 each
+        assert_eq!(point_buy("15 15 15 8 8 8").unwrap(), ([15, 15, 15, 8, 8, 8], 0));
+        assert_eq!(point_buy("13, 13, 13, 12, 10, 8").unwrap().1, 6);
+        assert!(point_buy("15 15 15 9 8 8").is_err());
+        assert!(point_buy("16 10 10 10 10 10").is_err() && point_buy("7 10 10 10 10 10").is_err());
+
+        let mut character = Character::new("Aria");
+        crate::ability_scores::apply(&mut character, [8, 14, 13, 15, 12, 10]);
+        assert_eq!((character.stre, character.intl, character.wisd, character.chas), (Some(8), Some(15), Some(12), Some(10)));
+    }
+
+    #[test]
+    fn test_join_combat_mid_round() {
+        let party = |arrivals: &[(&str, i32)]| {
+            let mut tracker = CombatTracker::new();
+            for (name, initiative) in arrivals {
+                tracker.add_combatant(Combatant::new_npc(name.to_string(), 10, 12, *initiative));
+            }
+            tracker
+        };
+        let order = |tracker: &CombatTracker| tracker.combatants.iter().map(|c| c.name.clone()).collect::<Vec<_>>();
+
+        // Before anyone has acted, a faster arrival goes first
+        let mut tracker = party(&[("Aria", 20), ("Bram", 15)]);
+        tracker.add_combatant(Combatant::new_npc("Scout".to_string(), 10, 12, 25));
+        assert_eq!((tracker.combatants[0].name.as_str(), tracker.current_turn), ("Scout", 0));
+
+        // Classic loop: Aria has acted and Bram is up, so Dara at 17 still acts this round
+        let mut tracker = party(&[("Aria", 20), ("Bram", 15)]);
+        assert_eq!(tracker.next_turn().unwrap().name, "Aria");
+        tracker.add_combatant(Combatant::new_npc("Dara".to_string(), 10, 12, 17));
+        assert_eq!(order(&tracker), vec!["Aria", "Dara", "Bram"]);
+        assert_eq!(tracker.next_turn().unwrap().name, "Dara");
+        assert_eq!(tracker.next_turn().unwrap().name, "Bram");
+        assert_eq!(tracker.round_number, 2);
+
+        // Once the round has wrapped, a slow arrival waits for their place in the new round
+        tracker.add_combatant(Combatant::new_npc("Eli".to_string(), 10, 12, 5));
+        assert_eq!(tracker.next_turn().unwrap().name, "Aria");
+
+        // TUI: Aria is acting at the top of round 1 and a faster arrival doesn't take Aria's turn
+        let mut tracker = party(&[("Aria", 20), ("Bram", 15)]);
+        tracker.begin_turn();
+        tracker.add_combatant(Combatant::new_npc("Scout".to_string(), 10, 12, 25));
+        assert_eq!(order(&tracker), vec!["Scout", "Aria", "Bram"]);
+        assert_eq!((tracker.combatants[tracker.current_turn].name.as_str(), tracker.round_number), ("Aria", 1));
+
+        // ...and Dara at 17 comes up straight after Aria
+        tracker.add_combatant(Combatant::new_npc("Dara".to_string(), 10, 12, 17));
+        assert_eq!(tracker.combatants[tracker.current_turn].name, "Aria");
+        assert_eq!(tracker.combatants[tracker.current_turn + 1].name, "Dara");
+    }
+}
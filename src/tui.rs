@@ -1,6 +1,6 @@
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
-    execute,
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton, MouseEvent, MouseEventKind},
+    cursor, execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
@@ -12,10 +12,17 @@ use ratatui::{
     Frame, Terminal,
 };
 use std::io;
-use crate::character::Character;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Mutex, Once, atomic::{AtomicBool, Ordering}};
+use crate::character::{AbilityScore, Character};
+use crate::combat::Condition;
+use crate::registry::CharacterStore;
+use std::cell::RefMut;
+use crate::input_handler::is_confirmation;
+use crate::scripting::{Hook, ScriptAction, ScriptEngine};
 use rand;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AppMode {
     MainMenu,
     CharactersMenu,
@@ -26,12 +33,16 @@ pub enum AppMode {
     CharacterDisplayTUI,
     CharacterDeletion,
     CharacterDeletionTUI,
+    CharacterUndeleteTUI,
     InitiativeTracker,
     InitiativeTrackerTUI,
     NpcGenerator,
     NpcGeneratorTUI,
     Dice,
     DiceTUI,
+    SkillChallengeTUI,
+    DowntimeTUI,
+    EncounterTUI,
     CombatTracker,
     CombatTrackerTUI,
     Search,
@@ -39,13 +50,81 @@ pub enum AppMode {
     Exit,
 }
 
+/// Result of the most recent search request, shown in the status bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchStatus {
+    Unknown,
+    Online,
+    Offline,
+}
+
+impl SearchStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SearchStatus::Unknown => "not checked",
+            SearchStatus::Online => "online",
+            SearchStatus::Offline => "offline",
+        }
+    }
+}
+
+/// Input the app is waiting for before normal commands resume
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PendingInput {
+    DamageFor(String, Option<crate::combat::DamageType>),
+    ConfirmDelete(String),
+    ConfirmRemoveCombatant(String),
+    ConfirmXpAward(crate::xp::XpAward),
+    /// Whether to delete every cached wiki page
+    ConfirmCacheClear,
+    /// Rolling or taking the average for a level-up's hit points
+    LevelUpHp(String),
+    /// An Ability Score Improvement or feat, once the hit die is settled
+    LevelUpImprovement { name: String, hit_die_result: u32 },
+    /// Whether a new character whose name is taken is renamed, overwrites the old sheet or is dropped
+    NameCollision(String),
+    /// How a new character's ability scores are generated
+    CreationMethod(String),
+    /// A new character's scores: assigned from `pool`, or bought with points when there's none
+    CreationScores { name: String, pool: Option<[u8; 6]> },
+    /// A levelled-up warlock choosing new invocations
+    ChooseInvocations(String),
+    PrepareSpells(crate::prepared::Preparation),
+    /// How a party character died
+    DeathCause(String),
+    /// Whether a fallen character's belongings go to the party stash
+    DeathStash { name: String, cause: String },
+}
+
+impl PendingInput {
+    pub fn description(&self) -> String {
+        match self {
+            PendingInput::DamageFor(target, _) => format!("damage against {}", target),
+            PendingInput::ConfirmDelete(name) => format!("confirm deleting {}", name),
+            PendingInput::ConfirmRemoveCombatant(name) => format!("confirm removing {}", name),
+            PendingInput::ConfirmXpAward(award) => format!("confirm awarding {} XP", award.total),
+            PendingInput::ConfirmCacheClear => "confirm clearing the search cache".to_string(),
+            PendingInput::LevelUpHp(name) => format!("hit points for {}'s new level", name),
+            PendingInput::LevelUpImprovement { name, .. } => format!("{}'s Ability Score Improvement", name),
+            PendingInput::NameCollision(name) => format!("what to do about the name {}", name),
+            PendingInput::CreationMethod(name) | PendingInput::CreationScores { name, .. } => format!("ability scores for {}", name),
+            PendingInput::ChooseInvocations(name) => format!("invocations for {}", name),
+            PendingInput::PrepareSpells(preparation) => format!("preparing spells for {}", preparation.character),
+            PendingInput::DeathCause(name) => format!("cause of {}'s death", name),
+            PendingInput::DeathStash { name, .. } => format!("stashing {}'s belongings", name),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct App {
     pub mode: AppMode,
     pub selected_index: usize,
-    pub characters: Vec<Character>,
+    pub characters: CharacterStore,
     pub should_quit: bool,
     pub message: Option<String>,
+    // Ctrl+P overlay listing every mode's commands
+    pub palette: Option<crate::palette::CommandPalette>,
     // TUI terminal fields
     pub input_buffer: String,
     pub output_history: Vec<String>,
@@ -56,19 +135,79 @@ pub struct App {
     pub combat_tracker: Option<crate::combat::CombatTracker>,
     // State tracking
     pub current_state: String,
-    pub waiting_for: Option<String>,
+    pub pending_input: Option<PendingInput>,
+    // Session info shown in the status bar
+    pub campaign_name: Option<String>,
+    pub in_game_date: Option<String>,
+    pub search_status: SearchStatus,
+    /// Page shown in the combat reference pane
+    pub reference: Option<crate::search::WikiPageContent>,
+    // Newer release found by the background update check
+    pub available_update: Option<crate::update::ReleaseInfo>,
+    pub update_rx: Option<std::sync::mpsc::Receiver<crate::update::ReleaseInfo>>,
+    // Background fetch of the fight's spell and monster pages into the search cache
+    pub prefetch_rx: Option<std::sync::mpsc::Receiver<crate::search::PrefetchReport>>,
+    // The first-run SRD download, yielding how many entries it saved
+    pub srd_rx: Option<std::sync::mpsc::Receiver<usize>>,
+    // A `crawl` of the wiki's listing pages into the search index
+    pub crawl_rx: Option<std::sync::mpsc::Receiver<crate::search::CrawlReport>>,
+    // House-rule scripts called at turn start, on damage, and on rolls
+    pub scripts: ScriptEngine,
+    // Rollable tables for the `table` command
+    pub tables: crate::tables::TableRegistry,
+    pub names: crate::names::NameRegistry,
+    pub pantheon: crate::deities::Pantheon,
+    pub status_presets: crate::status_presets::StatusLibrary,
+    pub backgrounds: crate::backgrounds::BackgroundBook,
+    pub monsters: crate::monsters::MonsterIndex,
+    pub dc_bands: crate::dc::DcBands,
+    // Large-text turn banner for a player-facing screen
+    pub table_display: bool,
+    // Socket feeding `dnd_tools player-view` screens
+    pub player_view: Option<crate::player_view::PlayerViewServer>,
+    // Initiative files for a stream overlay, started with `overlay`
+    pub overlay: Option<crate::overlay::OverlayWriter>,
+    // Local JSON API for companion apps, started with `api`
+    #[cfg(feature = "rest-api")]
+    pub rest_api: Option<crate::rest_api::ApiServer>,
+    // Social encounter / heist / ritual progress
+    pub skill_challenge: Option<crate::skill_challenge::SkillChallenge>,
+    // Last `voice` suggestion, kept so `voice save <npc>` can store it
+    pub last_voice: Option<crate::voices::Voice>,
+    /// Last `temperament` suggestion, for `temperament save <npc>`
+    pub last_temperament: Option<crate::temperament::Temperament>,
+    // Last encounter built by the generator, ready to push into the combat tracker
+    pub encounter: Option<crate::encounter::Encounter>,
+    // Crafting projects and downtime days, saved to the campaign folder
+    pub crafting: crate::crafting::CraftingLedger,
+    pub lights: crate::light::LightTracker,
+    pub supplies: crate::supplies::Supplies,
+    // Rolls made in the Initiative Tracker tool, in order with whoever is acting
+    pub initiative: crate::initiative::InitiativeTracker,
+    // Current weather, for rules reminders in combat and on the road
+    pub weather: crate::weather::Weather,
+    // Scenes of the adventure in order, with their prep
+    pub adventure: crate::adventure::Adventure,
+    pub bastions: crate::bastion::Bastions,
+    // Companions whose level follows the party
+    pub sidekicks: crate::sidekick::Sidekicks,
     // Dice rolling state
     pub dice_results: Vec<String>,
+    // Screen areas from the last draw, used to map mouse clicks
+    pub menu_area: Rect,
+    pub output_area: Rect,
+    pub initiative_area: Rect,
 }
 
 impl App {
-    pub fn new(characters: Vec<Character>) -> Self {
+    pub fn new(characters: CharacterStore) -> Self {
         Self {
             mode: AppMode::MainMenu,
             selected_index: 0,
             characters,
             should_quit: false,
             message: None,
+            palette: None,
             input_buffer: String::new(),
             output_history: Vec::new(),
             command_history: Vec::new(),
@@ -76,25 +215,329 @@ impl App {
             scroll_offset: 0,
             combat_tracker: None,
             current_state: "Ready".to_string(),
-            waiting_for: None,
+            pending_input: None,
+            campaign_name: None,
+            in_game_date: None,
+            search_status: SearchStatus::Unknown,
+            reference: None,
+            available_update: None,
+            update_rx: None,
+            prefetch_rx: None,
+            srd_rx: None,
+            crawl_rx: None,
+            scripts: ScriptEngine::new(),
+            tables: {
+                let mut tables = crate::tables::TableRegistry::new();
+                crate::rumors::register_builtin_tables(&mut tables);
+                tables
+            },
+            names: crate::names::NameRegistry::default(),
+            pantheon: crate::deities::Pantheon::default(),
+            status_presets: crate::status_presets::StatusLibrary::default(),
+            backgrounds: crate::backgrounds::BackgroundBook::default(),
+            monsters: crate::monsters::MonsterIndex::default(),
+            dc_bands: crate::dc::DcBands::default(),
+            table_display: false,
+            player_view: None,
+            overlay: None,
+            #[cfg(feature = "rest-api")]
+            rest_api: None,
+            skill_challenge: None,
+            encounter: None,
+            last_voice: None,
+            last_temperament: None,
+            crafting: crate::crafting::CraftingLedger::default(),
+            lights: crate::light::LightTracker::default(),
+            supplies: crate::supplies::Supplies::default(),
+            initiative: crate::initiative::InitiativeTracker::new(),
+            weather: crate::weather::Weather::default(),
+            adventure: crate::adventure::Adventure::default(),
+            bastions: crate::bastion::Bastions::default(),
+            sidekicks: crate::sidekick::Sidekicks::default(),
             dice_results: Vec::new(),
+            menu_area: Rect::default(),
+            output_area: Rect::default(),
+            initiative_area: Rect::default(),
         }
     }
 
     pub fn get_menu_items(&self) -> Vec<&str> {
         match self.mode {
             AppMode::MainMenu => vec!["Characters", "Tools", "Exit"],
-            AppMode::CharactersMenu => vec!["Creation", "Display single character", "Display all characters", "Character deletion", "Back to main menu"],
-            AppMode::ToolsMenu => vec!["Initiative tracker", "NPC randomizer", "Dice", "Combat tracker", "Search D&D 5e API", "Back to main menu"],
+            AppMode::CharactersMenu => vec!["Creation", "Display single character", "Display all characters", "Character deletion", "Undelete character", "Level up", "Back to main menu"],
+            AppMode::ToolsMenu => vec!["Initiative tracker", "NPC randomizer", "Dice", "Combat tracker", "Search D&D 5e API", "Skill challenge", "Downtime & crafting", "Encounter generator", "Back to main menu"],
             _ => vec![],
         }
     }
 
+    /// End concentration held by anyone who has dropped, and start or stop death saves
+    fn announce_downed(&mut self) {
+        let Some(tracker) = self.combat_tracker.as_mut() else { return };
+        let mut messages = tracker.end_broken_concentration();
+        messages.extend(tracker.update_death_saves());
+        for message in messages {
+            self.add_output(message);
+        }
+    }
+
+    /// Suggest a morale check when a group's leader or half its members have fallen
+    fn announce_morale_triggers(&mut self) {
+        let triggers = self.combat_tracker.as_mut().map(|t| t.morale_triggers()).unwrap_or_default();
+        for (group, trigger) in triggers {
+            self.add_output(format!("⚠️ {} should check morale: {}. Type 'morale {}' to roll.", group, trigger, group));
+        }
+    }
+
+    fn process_morale_command(&mut self, args: &[&str]) {
+        let Some(ref mut tracker) = self.combat_tracker else {
+            self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+            return;
+        };
+        let message = match args {
+            [] => {
+                if tracker.morale_groups.is_empty() {
+                    "No morale groups. Usage: morale add <group> <2-12> <members...> (prefix the leader with *)".to_string()
+                } else {
+                    let lines: Vec<String> = tracker.morale_groups.iter()
+                        .map(|g| {
+                            let last = g.last_outcome.map(|o| format!(", last check: {}", o)).unwrap_or_default();
+                            format!("  {} - morale {}, {}/{} fallen{}", g.name, g.morale, g.fallen(&tracker.combatants), g.members.len(), last)
+                        })
+                        .collect();
+                    format!("🏳️ Morale groups:\n{}", lines.join("\n"))
+                }
+            }
+            ["add", group, score, members @ ..] if !members.is_empty() => match score.parse::<i32>() {
+                Ok(score) => match tracker.add_morale_group(group, score, members) {
+                    Ok(summary) => format!("🏳️ Added morale group {}", summary),
+                    Err(e) => format!("❌ {}", e),
+                },
+                Err(_) => format!("❌ Morale must be a number between {} and {}", crate::morale::MIN_MORALE, crate::morale::MAX_MORALE),
+            },
+            ["add", ..] => "Usage: morale add <group> <2-12> <members...> (prefix the leader with *)".to_string(),
+            ["remove", group] => {
+                let before = tracker.morale_groups.len();
+                tracker.morale_groups.retain(|g| !g.name.eq_ignore_ascii_case(group));
+                if tracker.morale_groups.len() < before {
+                    format!("Removed morale group {}", group)
+                } else {
+                    format!("❌ Morale group '{}' not found", group)
+                }
+            }
+            [group] => match tracker.roll_morale(group) {
+                Ok(result) => result,
+                Err(e) => format!("❌ {}", e),
+            },
+            _ => "Usage: morale [<group> | add <group> <2-12> <members...> | remove <group>]".to_string(),
+        };
+        for line in message.lines() {
+            self.add_output(line.to_string());
+        }
+    }
+
+    fn process_player_view_command(&mut self, arg: Option<&str>) {
+        if arg.is_some_and(|a| a.eq_ignore_ascii_case("stop")) {
+            if self.player_view.take().is_some() {
+                self.add_output("📺 Player view stopped".to_string());
+            } else {
+                self.add_output("Player view is not running".to_string());
+            }
+            return;
+        }
+        if let Some(ref server) = self.player_view {
+            let addr = server.local_addr().map(|a| a.to_string()).unwrap_or_default();
+            self.add_output(format!("📺 Player view is running on {} with {} screen(s) connected", addr, server.client_count()));
+            return;
+        }
+        let addr = arg.unwrap_or(crate::player_view::DEFAULT_PLAYER_VIEW_ADDR);
+        match crate::player_view::PlayerViewServer::bind(addr) {
+            Ok(server) => {
+                self.player_view = Some(server);
+                self.add_output(format!("📺 Player view started. In another terminal run: dnd_tools player-view {}", addr));
+            }
+            Err(e) => self.add_output(format!("❌ {}", e)),
+        }
+    }
+
+    fn process_overlay_command(&mut self, arg: Option<&str>) {
+        if arg.is_some_and(|a| a.eq_ignore_ascii_case("stop")) {
+            let message = match self.overlay.take() {
+                Some(_) => "🎥 Overlay stopped; the files keep their last state",
+                None => "The overlay is not running",
+            };
+            self.add_output(message.to_string());
+            return;
+        }
+        if let Some(ref overlay) = self.overlay {
+            let paths: Vec<String> = overlay.paths().iter().map(|p| p.display().to_string()).collect();
+            self.add_output(format!("🎥 Overlay is writing {}", paths.join(", ")));
+            return;
+        }
+        match crate::overlay::OverlayConfig::load_file(std::path::Path::new(crate::overlay::OVERLAY_CONFIG_FILE)) {
+            Ok(config) => {
+                let overlay = crate::overlay::OverlayWriter::new(config);
+                let paths: Vec<String> = overlay.paths().iter().map(|p| p.display().to_string()).collect();
+                self.overlay = Some(overlay);
+                self.sync_overlay();
+                if self.overlay.is_some() {
+                    self.add_output(format!("🎥 Overlay started. Add {} to OBS as a text or browser source", paths.join(", ")));
+                    self.add_output(format!("   Choose what viewers see in {}", crate::overlay::OVERLAY_CONFIG_FILE));
+                }
+            }
+            Err(e) => self.add_output(format!("❌ {}", e)),
+        }
+    }
+
+    /// Rewrite the overlay files when the fight changes. A write that fails stops the
+    /// overlay, so the log isn't flooded with the same error.
+    pub fn sync_overlay(&mut self) {
+        let Some(ref mut overlay) = self.overlay else { return };
+        if let Err(e) = overlay.update(self.combat_tracker.as_ref()) {
+            self.overlay = None;
+            self.add_output(format!("❌ Overlay stopped: {}", e));
+        }
+    }
+
+    #[cfg(feature = "rest-api")]
+    fn process_api_command(&mut self, arg: Option<&str>) {
+        if arg.is_some_and(|a| a.eq_ignore_ascii_case("stop")) {
+            let message = match self.rest_api.take() {
+                Some(_) => "🔌 Combat API stopped",
+                None => "The combat API is not running",
+            };
+            self.add_output(message.to_string());
+            return;
+        }
+        if let Some(ref server) = self.rest_api {
+            let addr = server.local_addr().map(|a| a.to_string()).unwrap_or_default();
+            self.add_output(format!("🔌 Combat API is running on http://{}", addr));
+            return;
+        }
+        let addr = arg.unwrap_or(crate::rest_api::DEFAULT_API_ADDR);
+        match crate::rest_api::ApiServer::bind(addr) {
+            Ok(server) => {
+                self.rest_api = Some(server);
+                self.add_output(format!("🔌 Combat API started on http://{}: GET /combat, GET /combatants, POST /combatants/<name>/damage, POST /next", addr));
+            }
+            Err(e) => self.add_output(format!("❌ {}", e)),
+        }
+    }
+
+    #[cfg(not(feature = "rest-api"))]
+    fn process_api_command(&mut self, _arg: Option<&str>) {
+        self.add_output("❌ This build has no combat API. Rebuild with: cargo build --release --features rest-api".to_string());
+    }
+
+    /// Answer requests to the combat API. Changes go through the same commands the DM
+    /// types, so they're logged and trigger the usual hooks and reminders.
+    #[cfg(feature = "rest-api")]
+    pub fn poll_rest_api(&mut self) {
+        let Some(calls) = self.rest_api.as_ref().map(crate::rest_api::ApiServer::accept) else { return };
+        for (call, request) in calls {
+            call.respond(request.and_then(|request| self.answer_api_request(request)));
+        }
+    }
+
+    #[cfg(feature = "rest-api")]
+    fn answer_api_request(&mut self, request: crate::rest_api::ApiRequest) -> crate::error_handling::Result<serde_json::Value> {
+        use crate::error_handling::AppError;
+        use crate::rest_api::{ApiCombatant, ApiRequest, ApiState};
+        let tracker = self.combat_tracker.as_ref()
+            .ok_or_else(|| AppError::NotFound("A running fight".to_string()))?;
+        let before = self.output_history.len();
+        match request {
+            ApiRequest::State => return Ok(serde_json::json!(ApiState::from_tracker(tracker))),
+            ApiRequest::Combatants => return Ok(serde_json::json!(ApiState::from_tracker(tracker).combatants)),
+            ApiRequest::Damage { target, amount, kind } => {
+                if tracker.get_combatant(&target).is_none() {
+                    return Err(AppError::NotFound(format!("Combatant '{}'", target)));
+                }
+                log::info!("API: {} takes {} damage", target, amount);
+                self.process_hit_command(&target, amount, kind);
+            }
+            ApiRequest::Next => {
+                log::info!("API: next turn");
+                self.process_combat_command("next".to_string());
+            }
+        }
+        let messages = self.output_history[before..].to_vec();
+        let state = self.combat_tracker.as_ref().map(ApiState::from_tracker);
+        let combatants: Vec<ApiCombatant> = state.as_ref().map(|s| s.combatants.clone()).unwrap_or_default();
+        Ok(serde_json::json!({ "messages": messages, "round": state.as_ref().map(|s| s.round), "current": state.and_then(|s| s.current), "combatants": combatants }))
+    }
+
+    /// Push the player-safe combat state to any connected player screens
+    pub fn sync_player_view(&mut self) {
+        if let Some(ref mut server) = self.player_view {
+            let state = self.combat_tracker.as_ref()
+                .map(crate::player_view::PlayerViewState::from_tracker)
+                .unwrap_or_default();
+            server.publish(&state);
+        }
+    }
+
+    /// Pick up the result of the background update check, if it has arrived
+    pub fn poll_update(&mut self) {
+        if let Some(ref rx) = self.update_rx
+            && let Ok(release) = rx.try_recv() {
+            self.available_update = Some(release);
+            self.update_rx = None;
+        }
+    }
+
+    /// Report the background prefetch once it has finished
+    pub fn poll_prefetch(&mut self) {
+        if let Some(ref rx) = self.prefetch_rx
+            && let Ok(report) = rx.try_recv() {
+            self.prefetch_rx = None;
+            self.add_output(report.summary());
+        }
+    }
+
+    /// Report the first-run SRD download once it has finished
+    pub fn poll_srd_download(&mut self) {
+        if let Some(ref rx) = self.srd_rx
+            && let Ok(entries) = rx.try_recv() {
+            self.srd_rx = None;
+            self.add_output(format!("📚 Downloaded {} SRD entries; spell, monster and equipment searches now work offline", entries));
+        }
+    }
+
+    /// Report a finished crawl of the wiki's listings
+    pub fn poll_crawl(&mut self) {
+        if let Some(ref rx) = self.crawl_rx
+            && let Ok(report) = rx.try_recv() {
+            self.crawl_rx = None;
+            self.add_output(report.summary());
+        }
+    }
+
+    /// Cache the party's spells and the encounter's monsters in the background
+    fn start_prefetch(&mut self) {
+        let combatants = self.combat_tracker.as_ref().map(|t| t.combatants.as_slice()).unwrap_or_default();
+        let targets = crate::search::prefetch_targets(&self.characters.read(), combatants);
+        if targets.is_empty() {
+            self.add_output("Nothing to prefetch: no spells on the party's sheets and no monsters in the fight".to_string());
+            return;
+        }
+        self.add_output(format!("📥 Fetching {} spell and monster page(s) in the background for offline lookup", targets.len()));
+        self.prefetch_rx = Some(crate::search::spawn_prefetch(targets));
+    }
+
     pub fn handle_key(&mut self, key: KeyCode) {
+        // Any key dismisses an open popup
+        if self.message.take().is_some() {
+            return;
+        }
+        if self.palette.is_some() {
+            self.handle_palette_key(key);
+            return;
+        }
+
         match self.mode {
             AppMode::CombatTrackerTUI | AppMode::SearchTUI | AppMode::CharacterCreationTUI 
-            | AppMode::CharacterDisplayTUI | AppMode::CharacterDeletionTUI | AppMode::InitiativeTrackerTUI 
-            | AppMode::NpcGeneratorTUI | AppMode::DiceTUI => {
+            | AppMode::CharacterDisplayTUI | AppMode::CharacterDeletionTUI | AppMode::CharacterUndeleteTUI | AppMode::InitiativeTrackerTUI 
+            | AppMode::NpcGeneratorTUI | AppMode::DiceTUI | AppMode::SkillChallengeTUI | AppMode::DowntimeTUI | AppMode::EncounterTUI => {
                 self.handle_terminal_key(key);
             }
             _ => {
@@ -103,6 +546,12 @@ impl App {
                     KeyCode::Down => self.next_item(),
                     KeyCode::Enter => self.select_current(),
                     KeyCode::Esc => self.go_back(),
+                    KeyCode::Char('c') | KeyCode::Char('C') => {
+                        if let Some(ref release) = self.available_update {
+                            self.message = Some(format!("What's new in {} (you have {})\n\n{}\n\n{}",
+                                release.version, crate::update::CURRENT_VERSION, release.changelog, release.url));
+                        }
+                    }
                     // Removed auto-quit on 'q' - now requires Ctrl+Q
                     _ => {}
                 }
@@ -110,6 +559,163 @@ impl App {
         }
     }
 
+    /// Open the command palette with every mode's commands, or close it
+    pub fn toggle_palette(&mut self) {
+        if self.palette.take().is_none() {
+            self.palette = Some(crate::palette::CommandPalette::new(self.palette_entries()));
+        }
+    }
+
+    /// Read each mode's help into palette entries, putting the output history back after
+    fn palette_entries(&mut self) -> Vec<crate::palette::PaletteEntry> {
+        use crate::palette::{PALETTE_MODES, PaletteEntry};
+        let history = std::mem::take(&mut self.output_history);
+        let scroll = self.scroll_offset;
+        let mut entries = Vec::new();
+        for (mode, _) in PALETTE_MODES {
+            let help = "help".to_string();
+            match mode {
+                AppMode::CombatTrackerTUI => self.process_combat_command(help),
+                AppMode::InitiativeTrackerTUI => self.process_initiative_command(help),
+                AppMode::DiceTUI => self.process_dice_command(help),
+                AppMode::SearchTUI => self.process_search_command(help),
+                AppMode::NpcGeneratorTUI => self.process_npc_generator_command(help),
+                AppMode::SkillChallengeTUI => self.process_skill_challenge_command(help),
+                AppMode::DowntimeTUI => self.process_downtime_command(help),
+                AppMode::EncounterTUI => self.process_encounter_command(help),
+                AppMode::CharacterCreationTUI => self.process_character_creation_command(help),
+                AppMode::CharacterDisplayTUI => self.process_character_display_command(help),
+                AppMode::CharacterDeletionTUI => self.process_character_deletion_command(help),
+                AppMode::CharacterUndeleteTUI => self.process_character_undelete_command(help),
+                _ => {}
+            }
+            entries.push(PaletteEntry::open(mode.clone()));
+            let lines = std::mem::take(&mut self.output_history);
+            entries.extend(lines.iter().filter_map(|line| PaletteEntry::from_help(mode.clone(), line)));
+        }
+        self.output_history = history;
+        self.scroll_offset = scroll;
+        entries
+    }
+
+    fn handle_palette_key(&mut self, key: KeyCode) {
+        let Some(palette) = self.palette.as_mut() else { return };
+        match key {
+            KeyCode::Esc => self.palette = None,
+            KeyCode::Up => palette.move_selection(&self.mode, false),
+            KeyCode::Down => palette.move_selection(&self.mode, true),
+            KeyCode::Backspace => palette.backspace(),
+            KeyCode::Char(c) => palette.type_char(c),
+            KeyCode::Enter => {
+                if let Some(entry) = palette.chosen(&self.mode) {
+                    self.palette = None;
+                    self.run_palette_entry(entry);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Run a palette entry here, or switch to its mode with the command ready in the
+    /// input line. A running fight isn't left behind this way, since leaving ends it.
+    pub fn run_palette_entry(&mut self, entry: crate::palette::PaletteEntry) {
+        use crate::palette::PaletteAction;
+        if entry.mode != self.mode {
+            if self.combat_tracker.is_some() && self.mode == AppMode::CombatTrackerTUI {
+                self.add_output("⚠️ Leaving the combat tracker ends the fight. 'save encounter <name>' keeps it, then press Esc.".to_string());
+                return;
+            }
+            self.clear_terminal_state();
+            self.mode = entry.mode;
+            self.selected_index = 0;
+            if let PaletteAction::Run(command) | PaletteAction::Fill(command) = entry.action {
+                self.input_buffer = command;
+            }
+            return;
+        }
+        match entry.action {
+            PaletteAction::Open => {}
+            PaletteAction::Fill(command) => self.input_buffer = command,
+            PaletteAction::Run(command) => {
+                self.input_buffer.clear();
+                self.command_history.push(command.clone());
+                self.history_index = None;
+                self.process_terminal_command(command);
+            }
+        }
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let in_terminal_mode = matches!(self.mode,
+            AppMode::CombatTrackerTUI | AppMode::SearchTUI | AppMode::CharacterCreationTUI
+            | AppMode::CharacterDisplayTUI | AppMode::CharacterDeletionTUI | AppMode::CharacterUndeleteTUI | AppMode::InitiativeTrackerTUI
+            | AppMode::NpcGeneratorTUI | AppMode::DiceTUI | AppMode::SkillChallengeTUI | AppMode::DowntimeTUI | AppMode::EncounterTUI);
+
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if in_terminal_mode {
+                    // Clicking a combatant in the initiative pane shows their stats
+                    if let Some(row) = row_in_area(self.initiative_area, mouse.column, mouse.row) {
+                        // The lair's row at initiative 20 isn't a combatant
+                        let name = self.combat_tracker.as_ref().and_then(|tracker| {
+                            let row = match tracker.lair_row() {
+                                Some(lair) if row == lair => return None,
+                                Some(lair) if row > lair => row - 1,
+                                _ => row,
+                            };
+                            tracker.visible_combatants().nth(row).map(|(_, c)| tracker.display_name(c))
+                        });
+                        if let Some(name) = name {
+                            self.show_combatant_stats(&name);
+                        }
+                    }
+                } else if let Some(row) = row_in_area(self.menu_area, mouse.column, mouse.row) {
+                    if row < self.get_menu_items().len() {
+                        self.selected_index = row;
+                        self.select_current();
+                    }
+                }
+            }
+            MouseEventKind::ScrollUp if in_terminal_mode && row_in_area(self.output_area, mouse.column, mouse.row).is_some() => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+            }
+            MouseEventKind::ScrollDown if in_terminal_mode && row_in_area(self.output_area, mouse.column, mouse.row).is_some()
+                && self.scroll_offset + 10 < self.output_history.len() => {
+                self.scroll_offset += 1;
+            }
+            _ => {}
+        }
+    }
+
+    /// Build the one-line session summary shown in the status bar
+    pub fn status_bar_text(&self) -> String {
+        let mut segments = vec![
+            format!("📜 {}", self.campaign_name.as_deref().unwrap_or("No campaign")),
+        ];
+        if let Some(ref date) = self.in_game_date {
+            segments.push(format!("📅 {}", date));
+        }
+        if let Some(ref tracker) = self.combat_tracker {
+            // Players can see the status bar, so a hidden combatant's turn stays anonymous
+            let turn = match tracker.combatants.get(tracker.current_turn) {
+                Some(c) if c.hidden => "...",
+                Some(c) => c.name.as_str(),
+                None => "-",
+            };
+            segments.push(format!("⚔️ Round {} • Turn: {}", tracker.round_number, turn));
+        }
+        segments.push(format!("👥 {} characters", self.characters.len()));
+        segments.push(format!("🌐 Search: {}", self.search_status.label()));
+        if let Some(ref release) = self.available_update {
+            segments.push(format!("⬆️ {} available (C: changelog)", release.version));
+        }
+        match self.pending_input {
+            Some(ref pending) => segments.push(format!("{} (Waiting: {})", self.current_state, pending.description())),
+            None => segments.push(self.current_state.clone()),
+        }
+        segments.join(" │ ")
+    }
+
     fn previous_item(&mut self) {
         let items = self.get_menu_items();
         if !items.is_empty() {
@@ -146,7 +752,14 @@ impl App {
                     1 => self.mode = AppMode::CharacterDisplayTUI,
                     2 => self.mode = AppMode::CharacterDisplayTUI,
                     3 => self.mode = AppMode::CharacterDeletionTUI,
-                    4 => {
+                    4 => self.mode = AppMode::CharacterUndeleteTUI,
+                    5 => {
+                        self.mode = AppMode::CharacterDisplayTUI;
+                        self.add_output("⬆️ Level Up".to_string());
+                        self.add_output("Type 'levelup <name>' to take a character up a level, or 'list' to see everyone".to_string());
+                        self.current_state = "Level Up Ready".to_string();
+                    }
+                    6 => {
                         self.mode = AppMode::MainMenu;
                         self.selected_index = 0;
                     }
@@ -160,7 +773,10 @@ impl App {
                     2 => self.mode = AppMode::DiceTUI,
                     3 => self.mode = AppMode::CombatTrackerTUI,
                     4 => self.mode = AppMode::SearchTUI,
-                    5 => {
+                    5 => self.mode = AppMode::SkillChallengeTUI,
+                    6 => self.mode = AppMode::DowntimeTUI,
+                    7 => self.mode = AppMode::EncounterTUI,
+                    8 => {
                         self.mode = AppMode::MainMenu;
                         self.selected_index = 0;
                     }
@@ -178,13 +794,14 @@ impl App {
                 self.selected_index = 0;
             }
             AppMode::CharacterCreation | AppMode::CharacterDisplay | AppMode::CharacterDeletion 
-            | AppMode::CharacterCreationTUI | AppMode::CharacterDisplayTUI | AppMode::CharacterDeletionTUI => {
+            | AppMode::CharacterCreationTUI | AppMode::CharacterDisplayTUI | AppMode::CharacterDeletionTUI
+            | AppMode::CharacterUndeleteTUI => {
                 self.mode = AppMode::CharactersMenu;
                 self.selected_index = 0;
                 self.clear_terminal_state();
             }
             AppMode::InitiativeTracker | AppMode::NpcGenerator | AppMode::Dice | AppMode::CombatTracker | AppMode::Search 
-            | AppMode::InitiativeTrackerTUI | AppMode::NpcGeneratorTUI | AppMode::DiceTUI => {
+            | AppMode::InitiativeTrackerTUI | AppMode::NpcGeneratorTUI | AppMode::DiceTUI | AppMode::SkillChallengeTUI | AppMode::DowntimeTUI | AppMode::EncounterTUI => {
                 self.mode = AppMode::ToolsMenu;
                 self.selected_index = 0;
                 self.clear_terminal_state();
@@ -204,7 +821,7 @@ impl App {
         self.scroll_offset = 0;
         self.combat_tracker = None;
         self.current_state = "Ready".to_string();
-        self.waiting_for = None;
+        self.pending_input = None;
         self.dice_results.clear();
     }
 
@@ -258,7 +875,13 @@ impl App {
                 }
             }
             KeyCode::Esc => {
-                self.go_back();
+                // Esc cancels an open prompt before leaving the mode
+                if self.pending_input.take().is_some() {
+                    self.add_output("Cancelled.".to_string());
+                    self.current_state = "Ready".to_string();
+                } else {
+                    self.go_back();
+                }
             }
             KeyCode::Char(c) => {
                 self.input_buffer.push(c);
@@ -268,842 +891,3674 @@ impl App {
     }
 
     fn process_terminal_command(&mut self, command: String) {
+        log::debug!("{:?} command: {}", self.mode, command);
+        // A pending prompt takes the input before the mode's own commands
+        if let Some(pending) = self.pending_input.take() {
+            self.process_pending_input(pending, command);
+            self.announce_downed();
+            self.write_back_party();
+            self.announce_morale_triggers();
+            return;
+        }
+        // Rules lookups work the same in every mode
+        if let Some(lines) = crate::reference::quick_reference(&command) {
+            for line in lines {
+                self.add_output(line);
+            }
+            return;
+        }
+        // `dc hard` works anywhere; a bare `dc` in a skill challenge is the challenge's own
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        let challenge_dc = matches!(self.mode, AppMode::SkillChallengeTUI)
+            && (parts.len() < 2 || (self.dc_bands.preset(&parts[1..].join(" ")).is_none() && parts[1] != "list"));
+        if parts.first().is_some_and(|c| c.eq_ignore_ascii_case("dc")) && !challenge_dc {
+            for line in crate::dc::quick_command(&self.dc_bands, &parts[1..]) {
+                self.add_output(line);
+            }
+            return;
+        }
+        // Improvised NPCs can turn up in any mode
+        if parts.first().is_some_and(|c| c.eq_ignore_ascii_case("voice")) {
+            self.process_voice_command(&parts[1..]);
+            return;
+        }
+        if parts.first().is_some_and(|c| c.eq_ignore_ascii_case("temperament")) {
+            let npcs_dir = std::path::Path::new(crate::file_manager::NPCS_DIR);
+            for line in crate::temperament::temperament_command(npcs_dir, &parts[1..], &mut self.last_temperament, &mut rand::rng()) {
+                self.add_output(line);
+            }
+            return;
+        }
+        // So can the weather, whether the party is fighting or travelling
+        if parts.first().is_some_and(|c| c.eq_ignore_ascii_case("weather")) {
+            self.process_weather_command(&parts[1..]);
+            return;
+        }
+        if parts.first().is_some_and(|c| c.eq_ignore_ascii_case("campaign")) {
+            self.process_campaign_command(&parts[1..]);
+            return;
+        }
+        // The adventure outline is at hand mid-session, whatever the mode
+        if parts.first().is_some_and(|c| c.eq_ignore_ascii_case("scene")) {
+            self.process_scene_command(&parts[1..]);
+            return;
+        }
+
         match self.mode {
-            AppMode::CombatTrackerTUI => self.process_combat_command(command),
+            AppMode::CombatTrackerTUI => {
+                // Party combatants read their sheets before the command and write HP back after
+                if let Some(tracker) = self.combat_tracker.as_mut() {
+                    tracker.refresh_linked(&self.characters.read());
+                }
+                self.process_combat_command(command);
+                self.announce_downed();
+                self.write_back_party();
+                self.announce_morale_triggers();
+                if let Some(tracker) = self.combat_tracker.as_mut() {
+                    let round = tracker.round_number;
+                    tracker.pacing.observe(round, &tracker.combatants, &self.characters.read());
+                }
+            }
             AppMode::SearchTUI => self.process_search_command(command),
             AppMode::CharacterCreationTUI => self.process_character_creation_command(command),
             AppMode::CharacterDisplayTUI => self.process_character_display_command(command),
             AppMode::CharacterDeletionTUI => self.process_character_deletion_command(command),
+            AppMode::CharacterUndeleteTUI => self.process_character_undelete_command(command),
             AppMode::InitiativeTrackerTUI => self.process_initiative_command(command),
             AppMode::NpcGeneratorTUI => self.process_npc_generator_command(command),
             AppMode::DiceTUI => self.process_dice_command(command),
+            AppMode::SkillChallengeTUI => self.process_skill_challenge_command(command),
+            AppMode::DowntimeTUI => self.process_downtime_command(command),
+            AppMode::EncounterTUI => self.process_encounter_command(command),
             _ => {}
         }
     }
 
-    fn process_combat_command(&mut self, command: String) {
-        // Check if we're waiting for damage input after an attack
-        if let Some(ref waiting) = self.waiting_for.clone() {
-            if waiting.starts_with("damage_for_") {
-                let target_name = waiting.strip_prefix("damage_for_").unwrap();
-                
-                // Try to parse as damage (either dice roll or number)
-                if let Ok(damage) = command.trim().parse::<i32>() {
-                    // Direct damage number
-                    self.process_hit_command(target_name, damage);
-                    self.waiting_for = None;
-                    self.current_state = "Combat Ready".to_string();
-                    return;
-                } else {
-                    // Try as dice roll
-                    match crate::dice::roll_dice_with_crits(&command.trim()) {
-                        Ok((rolls, total, crit_message)) => {
-                            self.add_output(format!("🎲 Damage roll: {} (dice: {:?})", total, rolls));
-                            if let Some(message) = crit_message {
-                                self.add_output(message);
-                            }
-                            self.process_hit_command(target_name, total as i32);
-                            self.waiting_for = None;
-                            self.current_state = "Combat Ready".to_string();
-                            return;
-                        }
-                        Err(_) => {
-                            self.add_output("❌ Invalid damage input. Enter a number or dice expression (e.g., 2d6+3)".to_string());
-                            return;
-                        }
+    fn process_pending_input(&mut self, pending: PendingInput, command: String) {
+        match pending {
+            PendingInput::ConfirmDelete(name) => {
+                if !is_confirmation(&command, &name) {
+                    self.add_output("Deletion cancelled.".to_string());
+                } else if let Some(removed) = self.characters.remove(&name) {
+                    match crate::file_manager::trash_character(&removed.name) {
+                        Ok(_) => self.add_output(format!("🗑️  Moved '{}' to the trash. Use 'Undelete character' to restore.", removed.name)),
+                        Err(e) => self.add_output(format!("⚠️  Removed '{}' but could not move its file to the trash: {}", removed.name, e)),
                     }
                 }
             }
-        }
-
-        let parts: Vec<&str> = command.split_whitespace().collect();
-        if parts.is_empty() {
-            return;
-        }
-
-        let cmd = parts[0].to_lowercase();
-        
-        match cmd.as_str() {
-            "help" | "h" => {
-                self.add_output("Combat Mode Commands:".to_string());
-                self.add_output("  init - Initialize combat tracker".to_string());
-                self.add_output("  stats [name] - Show character stats".to_string());
-                self.add_output("  attack <target> - Roll attack against target's AC".to_string());
-                self.add_output("  save <stat> [target] - Make saving throw (str/dex/con/int/wis/cha)".to_string());
-                self.add_output("  hit <target> <amount> - Deal direct damage".to_string());
-                self.add_output("  damage <name> <amount> - Apply damage".to_string());
-                self.add_output("  heal <name> <amount> - Heal character".to_string());
-                self.add_output("  status <target> add <status> [rounds] - Add status effect".to_string());
-                self.add_output("  status <target> remove <status> - Remove status effect".to_string());
-                self.add_output("  next|continue - Advance to next combatant".to_string());
-                self.add_output("  search <query> - Search D&D 5e API".to_string());
-                self.add_output("  show|list - Display current initiative order".to_string());
-                self.add_output("  quit|exit - Exit combat mode".to_string());
-                self.add_output("".to_string());
-                self.add_output("Examples:".to_string());
-                self.add_output("  attack goblin".to_string());
-                self.add_output("  save wis fighter".to_string());
-                self.add_output("  hit goblin 8".to_string());
-                self.add_output("  status goblin add poisoned 3".to_string());
-            }
-            "init" | "initialize" => {
-                self.initialize_combat();
+            PendingInput::ConfirmRemoveCombatant(name) => {
+                if !is_confirmation(&command, &name) {
+                    self.add_output("Removal cancelled.".to_string());
+                } else if self.combat_tracker.as_mut().is_some_and(|tracker| tracker.remove_combatant(&name)) {
+                    self.add_output(format!("✅ Removed {} from combat", name));
+                }
             }
-            "search" => {
-                if let Some(_query) = parts.get(1) {
-                    let full_query = parts[1..].join(" ");
-                    self.handle_combat_search(&full_query);
+            PendingInput::DeathCause(name) => {
+                if command.trim().eq_ignore_ascii_case("cancel") {
+                    self.add_output(format!("{} is not recorded as dead.", name));
+                    return;
+                }
+                let items = self.characters.find(&name).map(|c| c.inventory.len()).unwrap_or(0);
+                if items > 0 {
+                    self.add_output(format!("Move {}'s {} item(s) to the party stash? (y/n)", name, items));
+                    self.pending_input = Some(PendingInput::DeathStash { name, cause: command.trim().to_string() });
                 } else {
-                    self.add_output("Usage: search <query>".to_string());
-                    self.add_output("Example: search fireball".to_string());
+                    self.record_death(&name, &command, false);
                 }
             }
-            "quit" | "exit" | "q" => {
-                self.add_output("Exiting combat mode...".to_string());
-                self.mode = AppMode::ToolsMenu;
-                self.selected_index = 0;
-                self.input_buffer.clear();
-                self.output_history.clear();
-                self.scroll_offset = 0;
-                self.combat_tracker = None;
+            PendingInput::DeathStash { name, cause } => {
+                self.record_death(&name, &cause, is_confirmation(&command, "stash"));
             }
-            "show" | "list" => {
-                if let Some(ref tracker) = self.combat_tracker {
-                    let mut lines = vec!["Initiative Order:".to_string()];
-                    for (i, combatant) in tracker.combatants.iter().enumerate() {
-                        let marker = if i == tracker.current_turn { "►" } else { " " };
-                        let status_text = if combatant.status_effects.is_empty() {
-                            "".to_string()
-                        } else {
-                            format!(" [{}]", combatant.status_effects.iter()
-                                .map(|s| s.name.as_str()).collect::<Vec<_>>().join(", "))
-                        };
-                        lines.push(format!("{} {}. {} (Init: {}, HP: {}/{}, AC: {}){}",
-                            marker, i + 1, combatant.name, combatant.initiative,
-                            combatant.current_hp, combatant.max_hp, combatant.ac, status_text));
-                    }
-                    for line in lines {
-                        self.add_output(line);
+            PendingInput::ConfirmXpAward(award) => {
+                if is_confirmation(&command, "award") {
+                    let mut awarded = Vec::new();
+                    for (name, xp) in &award.shares {
+                        if let Some(character) = self.characters.write().iter_mut().find(|c| c.name.eq_ignore_ascii_case(name)) {
+                            character.xp += xp;
+                            awarded.push(format!("{} now has {} XP", character.name, crate::xp::format_xp(character.xp)));
+                        }
                     }
+                    self.leave_combat();
+                    self.message = Some(format!("⭐ Awarded {} XP\n\n{}", crate::xp::format_xp(award.total), awarded.join("\n")));
                 } else {
-                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                    self.leave_combat();
+                    self.message = Some("XP award skipped.".to_string());
                 }
             }
-            "next" | "continue" => {
-                if let Some(ref mut tracker) = self.combat_tracker {
-                    if tracker.combatants.is_empty() {
-                        self.add_output("❌ No combatants in combat.".to_string());
-                    } else {
-                        let _old_turn = tracker.current_turn;
-                        tracker.current_turn = (tracker.current_turn + 1) % tracker.combatants.len();
-                        
-                        let mut messages = Vec::new();
-                        if tracker.current_turn == 0 {
-                            tracker.round_number += 1;
-                            messages.push(format!("🔄 Starting Round {}", tracker.round_number));
-                        }
-                        
-                        let current = &tracker.combatants[tracker.current_turn];
-                        messages.push(format!("🎯 It's {}'s turn! (Initiative: {}, HP: {}/{})", 
-                            current.name, current.initiative, current.current_hp, current.max_hp));
-                        
-                        for message in messages {
-                            self.add_output(message);
-                        }
+            PendingInput::PrepareSpells(mut preparation) => match command.trim().to_lowercase().as_str() {
+                "cancel" => self.add_output("Preparation cancelled; yesterday's spells stay prepared.".to_string()),
+                "done" | "save" => {
+                    if let Err(e) = preparation.validate() {
+                        self.add_output(format!("❌ {}", e));
+                        self.pending_input = Some(PendingInput::PrepareSpells(preparation));
+                        return;
+                    }
+                    let name = preparation.character.clone();
+                    let count = preparation.selected.len();
+                    let message = self.find_character_mut(&name).map(|mut character| match preparation.save(&mut character) {
+                        Ok(()) => format!("✅ {} has {} spell(s) prepared", name, count),
+                        Err(e) => format!("❌ {}", e),
+                    });
+                    if let Some(message) = message {
+                        self.add_output(message);
                     }
-                } else {
-                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
                 }
-            }
-            "stats" => {
-                if let Some(ref tracker) = self.combat_tracker {
-                    if parts.len() >= 2 {
-                        let name = parts[1];
-                        if let Some(combatant) = tracker.combatants.iter().find(|c| c.name.eq_ignore_ascii_case(name)) {
-                            let mut messages = vec![
-                                format!("📊 Stats for {}", combatant.name),
-                                format!("  HP: {}/{} ({})", combatant.current_hp, combatant.max_hp, 
-                                    if combatant.current_hp > 0 { "Alive" } else { "Unconscious/Dead" }),
-                                format!("  AC: {}", combatant.ac),
-                                format!("  Initiative: {}", combatant.initiative),
-                                format!("  Type: {}", if combatant.is_player { "Player" } else { "NPC" }),
-                            ];
-                            
-                            if !combatant.status_effects.is_empty() {
-                                messages.push("  Status Effects:".to_string());
-                                for effect in &combatant.status_effects {
-                                    let duration_text = match effect.duration {
-                                        Some(d) => format!(" ({} rounds)", d),
-                                        None => " (permanent)".to_string(),
-                                    };
-                                    messages.push(format!("    - {}{}", effect.name, duration_text));
-                                }
-                            }
-                            
-                            for message in messages {
-                                self.add_output(message);
-                            }
-                        } else {
-                            self.add_output(format!("❌ Combatant '{}' not found", name));
-                        }
-                    } else {
-                        // Show current combatant stats
-                        if let Some(current) = tracker.combatants.get(tracker.current_turn) {
-                            let messages = vec![
-                                format!("📊 Current Turn: {}", current.name),
-                                format!("  HP: {}/{}", current.current_hp, current.max_hp),
-                                format!("  AC: {}", current.ac),
-                            ];
-                            
-                            for message in messages {
-                                self.add_output(message);
-                            }
-                        } else {
-                            self.add_output("❌ No current combatant".to_string());
-                        }
+                _ => {
+                    for choice in command.split(',').filter(|c| !c.trim().is_empty()) {
+                        let line = match preparation.toggle(choice) {
+                            Ok((spell, true)) => format!("  + {}", spell),
+                            Ok((spell, false)) => format!("  - {}", spell),
+                            Err(e) => format!("❌ {}", e),
+                        };
+                        self.add_output(line);
                     }
-                } else {
-                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                    for line in preparation.lines() {
+                        self.add_output(line);
+                    }
+                    self.add_output("Toggle more, 'done' to save, or 'cancel':".to_string());
+                    self.pending_input = Some(PendingInput::PrepareSpells(preparation));
                 }
-            }
-            "attack" => {
-                if parts.len() >= 2 {
-                    let target_name = parts[1];
-                    self.process_attack_command(target_name);
+            },
+            PendingInput::ConfirmCacheClear => {
+                if is_confirmation(&command, "clear") {
+                    self.add_output(crate::cache::clear_confirmed());
                 } else {
-                    self.add_output("Usage: attack <target>".to_string());
-                    self.add_output("Example: attack goblin".to_string());
+                    self.add_output("Cache left as it was.".to_string());
                 }
             }
-            "save" => {
-                if parts.len() >= 2 {
-                    let ability = parts[1].to_lowercase();
-                    let target = if parts.len() >= 3 { parts[2] } else { "self" };
-                    self.process_save_command(&ability, target);
-                } else {
-                    self.add_output("Usage: save <ability> [target]".to_string());
-                    self.add_output("Abilities: str, dex, con, int, wis, cha".to_string());
-                    self.add_output("Example: save wis goblin".to_string());
+            PendingInput::LevelUpHp(name) => {
+                if command.trim().eq_ignore_ascii_case("cancel") {
+                    self.add_output(format!("Level up cancelled; {} is unchanged", name));
+                    return;
                 }
-            }
-            "hit" => {
-                if parts.len() >= 3 {
-                    let target_name = parts[1];
-                    if let Ok(damage_amount) = parts[2].parse::<i32>() {
-                        self.process_hit_command(target_name, damage_amount);
-                    } else {
-                        self.add_output("❌ Invalid damage amount".to_string());
-                    }
+                let Some(method) = crate::leveling::HpMethod::parse(&command) else {
+                    self.add_output("Type 'roll' or 'average' (or 'cancel')".to_string());
+                    self.pending_input = Some(PendingInput::LevelUpHp(name));
+                    return;
+                };
+                let Some((class, level)) = self.characters.find(&name).map(|c| (c.class.clone().unwrap_or_default(), c.level.unwrap_or(1))) else {
+                    return;
+                };
+                let die = crate::leveling::hit_die(&class);
+                let hit_die_result = method.hit_die_result(die);
+                if method == crate::leveling::HpMethod::Roll {
+                    self.add_output(format!("🎲 Rolled {} on 1d{}", hit_die_result, die));
+                }
+                if crate::leveling::grants_improvement(&class, level + 1) {
+                    self.add_output(format!("📈 Level {} brings an Ability Score Improvement: '+2 str', '+1 dex +1 con' or 'feat <name>'", level + 1));
+                    self.pending_input = Some(PendingInput::LevelUpImprovement { name, hit_die_result });
                 } else {
-                    self.add_output("Usage: hit <target> <amount>".to_string());
-                    self.add_output("Example: hit goblin 8".to_string());
+                    self.finish_level_up(&name, hit_die_result, None);
                 }
             }
-            "status" => {
-                if parts.len() >= 4 {
-                    let target = parts[1];
-                    let action = parts[2].to_lowercase();
-                    let status_name = parts[3];
-                    let rounds = if parts.len() >= 5 { 
-                        parts[4].parse::<i32>().ok() 
-                    } else { 
-                        None 
-                    };
-                    self.process_status_command(target, &action, status_name, rounds);
-                } else {
-                    self.add_output("Usage: status <target> <add|remove> <status> [rounds]".to_string());
-                    self.add_output("Example: status goblin add poisoned 3".to_string());
-                    self.add_output("Example: status fighter remove stunned".to_string());
+            PendingInput::LevelUpImprovement { name, hit_die_result } => {
+                if command.trim().eq_ignore_ascii_case("cancel") {
+                    self.add_output(format!("Level up cancelled; {} is unchanged", name));
+                    return;
+                }
+                match crate::leveling::Improvement::parse(&command) {
+                    Ok(improvement) => self.finish_level_up(&name, hit_die_result, Some(improvement)),
+                    Err(e) => {
+                        self.add_output(format!("❌ {} (or 'cancel')", e));
+                        self.pending_input = Some(PendingInput::LevelUpImprovement { name, hit_die_result });
+                    }
                 }
             }
-            "damage" => {
-                if parts.len() >= 3 {
-                    let target_name = parts[1];
-                    if let Ok(damage_amount) = parts[2].parse::<i32>() {
-                        if let Some(ref mut tracker) = self.combat_tracker {
-                            if let Some(combatant) = tracker.combatants.iter_mut().find(|c| c.name.eq_ignore_ascii_case(target_name)) {
-                                let old_hp = combatant.current_hp;
-                                combatant.current_hp = (combatant.current_hp - damage_amount).max(0);
-                                
-                                let mut messages = vec![
-                                    format!("⚔️ {} takes {} damage! HP: {} → {}", 
-                                        combatant.name, damage_amount, old_hp, combatant.current_hp)
-                                ];
-                                    
-                                if combatant.current_hp <= 0 {
-                                    messages.push(format!("💀 {} is unconscious/dead!", combatant.name));
-                                }
-                                
-                                for message in messages {
-                                    self.add_output(message);
-                                }
-                            } else {
-                                self.add_output(format!("❌ Combatant '{}' not found", target_name));
-                            }
+            PendingInput::NameCollision(name) => {
+                let (choice, rest) = command.trim().split_once(' ').unwrap_or((command.trim(), ""));
+                match choice.to_lowercase().as_str() {
+                    "rename" | "1" => {
+                        let new_name = match rest.trim() {
+                            "" => crate::file_manager::suggest_unique_name(&self.characters.read(), &name),
+                            new_name => new_name.to_string(),
+                        };
+                        if let Err(e) = crate::error_handling::validate_character_name(&new_name) {
+                            self.add_output(format!("❌ {}. Type 'rename [new name]', 'overwrite' or 'cancel'", e));
+                            self.pending_input = Some(PendingInput::NameCollision(name));
+                        } else if crate::file_manager::character_name_taken(&self.characters.read(), &new_name) {
+                            self.add_output(format!("❌ '{}' is also taken. Type 'rename [new name]', 'overwrite' or 'cancel'", new_name));
+                            self.pending_input = Some(PendingInput::NameCollision(name));
                         } else {
-                            self.add_output("No combat initialized.".to_string());
+                            self.begin_character_creation(new_name);
                         }
-                    } else {
-                        self.add_output("❌ Invalid damage amount".to_string());
                     }
-                } else {
-                    self.add_output("Usage: damage <target> <amount>".to_string());
+                    "overwrite" | "2" => {
+                        self.add_output(format!("The new sheet will replace {}'s", name));
+                        self.begin_character_creation(name);
+                    }
+                    "cancel" | "0" => self.add_output(format!("Creation of {} cancelled", name)),
+                    _ => {
+                        self.add_output("Type 'rename [new name]', 'overwrite' or 'cancel'".to_string());
+                        self.pending_input = Some(PendingInput::NameCollision(name));
+                    }
                 }
             }
-            "heal" => {
-                if parts.len() >= 3 {
-                    let target_name = parts[1];
-                    if let Ok(heal_amount) = parts[2].parse::<i32>() {
-                        if let Some(ref mut tracker) = self.combat_tracker {
-                            if let Some(combatant) = tracker.combatants.iter_mut().find(|c| c.name.eq_ignore_ascii_case(target_name)) {
-                                let old_hp = combatant.current_hp;
-                                combatant.current_hp = (combatant.current_hp + heal_amount).min(combatant.max_hp);
-                                
-                                let message = format!("💚 {} heals {} HP! HP: {} → {}", 
-                                    combatant.name, heal_amount, old_hp, combatant.current_hp);
-                                self.add_output(message);
-                            } else {
-                                self.add_output(format!("❌ Combatant '{}' not found", target_name));
-                            }
-                        } else {
-                            self.add_output("No combat initialized.".to_string());
+            PendingInput::CreationMethod(name) => {
+                use crate::ability_scores::ScoreMethod;
+                let method = match ScoreMethod::parse(&command) {
+                    Some(method) => method,
+                    None if command.trim().eq_ignore_ascii_case("cancel") => {
+                        self.add_output(format!("Creation of {} cancelled", name));
+                        return;
+                    }
+                    None => {
+                        self.add_output("Enter 1, 2, 3 or 4 (or 'cancel')".to_string());
+                        self.pending_input = Some(PendingInput::CreationMethod(name));
+                        return;
+                    }
+                };
+                let pool = method.pool(&mut rand::rng());
+                match (method, pool) {
+                    (ScoreMethod::Manual, _) => {
+                        self.finish_character_creation(&name, None);
+                        return;
+                    }
+                    (_, Some(pool)) => {
+                        let listed: Vec<String> = pool.iter().map(u8::to_string).collect();
+                        self.add_output(format!("🎲 Scores to assign ({}): {}", method.name(), listed.join(", ")));
+                    }
+                    (_, None) => self.add_output(crate::ability_scores::point_cost_table()),
+                }
+                self.add_output(format!("Enter six scores in the order {} (e.g. 15 14 13 12 10 8):", crate::ability_scores::order_label()));
+                self.pending_input = Some(PendingInput::CreationScores { name, pool });
+            }
+            PendingInput::CreationScores { name, pool } => {
+                if command.trim().eq_ignore_ascii_case("cancel") {
+                    self.add_output(format!("Creation of {} cancelled", name));
+                    return;
+                }
+                let scores = match pool {
+                    Some(pool) => crate::ability_scores::assign(&command, &pool).map(|scores| (scores, 0)),
+                    None => crate::ability_scores::point_buy(&command),
+                };
+                match scores {
+                    Ok((scores, left)) => {
+                        if left > 0 {
+                            self.add_output(format!("⚠️ {} point(s) left unspent", left));
                         }
-                    } else {
-                        self.add_output("❌ Invalid heal amount".to_string());
+                        self.finish_character_creation(&name, Some(scores));
+                    }
+                    Err(e) => {
+                        self.add_output(format!("❌ {} (or 'cancel')", e));
+                        self.pending_input = Some(PendingInput::CreationScores { name, pool });
                     }
-                } else {
-                    self.add_output("Usage: heal <target> <amount>".to_string());
                 }
             }
-            _ => {
-                if self.combat_tracker.is_some() {
-                    // Handle other combat commands
-                    self.add_output(format!("Unknown command '{}'. Type 'help' for available commands.", cmd));
-                } else {
-                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+            PendingInput::ChooseInvocations(name) => {
+                if command.trim().is_empty() || command.trim().eq_ignore_ascii_case("skip") {
+                    self.add_output("Invocations skipped. Use 'invocation <name> add <invocation>' later.".to_string());
+                    return;
+                }
+                let Some(mut character) = self.find_character_mut(&name) else {
+                    return;
+                };
+                let mut lines = Vec::new();
+                for choice in command.split(',').filter(|c| !c.trim().is_empty()) {
+                    lines.push(match crate::pact_magic::learn_invocation(&mut character, choice) {
+                        Ok(invocation) => format!("✨ {} learns {}", character.name, invocation),
+                        Err(e) => format!("❌ {}", e),
+                    });
+                }
+                let due = crate::pact_magic::invocations_due(&character);
+                drop(character);
+                for line in lines {
+                    self.add_output(line);
+                }
+                if due > 0 {
+                    self.add_output(format!("Choose {} more (comma-separated), or 'skip':", due));
+                    self.pending_input = Some(PendingInput::ChooseInvocations(name));
                 }
             }
-        }
-    }
-
-    fn process_attack_command(&mut self, target_name: &str) {
-        if let Some(ref tracker) = self.combat_tracker {
-            if let Some(target) = tracker.combatants.iter().find(|c| c.name.eq_ignore_ascii_case(target_name)) {
-                let target_ac = target.ac;
-                
-                // Roll d20 for attack
-                match crate::dice::roll_dice_with_crits("1d20") {
-                    Ok((rolls, total, crit_message)) => {
-                        let attack_roll = rolls[0] as i32;
-                        let hit = attack_roll >= target_ac;
-                        
-                        self.add_output(format!("⚔️  Attack Roll: {} (d20: {})", total, attack_roll));
-                        
-                        if let Some(message) = crit_message {
-                            self.add_output(message);
+            PendingInput::DamageFor(target_name, attack_kind) => {
+                // A damage type after the amount overrides the attack's
+                let words: Vec<&str> = command.split_whitespace().collect();
+                let (amount, typed) = crate::combat::split_damage_type(&words);
+                let kind = typed.or(attack_kind);
+                let amount = amount.join(" ");
+                // Try to parse as damage (either dice roll or number)
+                if let Ok(damage) = amount.parse::<i32>() {
+                    // Direct damage number
+                    self.process_hit_command(&target_name, damage, kind);
+                    self.current_state = "Combat Ready".to_string();
+                } else {
+                    // Try as dice roll
+                    match crate::dice::roll_expression(&amount) {
+                        Ok(roll) => {
+                            let total = roll.total.max(0);
+                            self.add_output(format!("🎲 Damage roll: {}", roll));
+                            if let Some(message) = roll.crit() {
+                                self.add_output(message);
+                            }
+                            self.run_script_hook(Hook::Roll {
+                                kind: "damage".to_string(),
+                                expression: amount.clone(),
+                                total,
+                                crit: false,
+                                target: target_name.clone(),
+                            });
+                            self.process_hit_command(&target_name, total, kind);
+                            self.current_state = "Combat Ready".to_string();
                         }
-                        
-                        self.add_output(format!("🎯 Target AC: {}", target_ac));
-                        
-                        if hit {
-                            self.add_output("💥 HIT! The attack connects!".to_string());
-                            self.add_output("🎲 Enter damage (e.g., '2d6+3' or just '8'):".to_string());
-                            self.current_state = format!("Waiting for damage against {}", target_name);
-                            self.waiting_for = Some(format!("damage_for_{}", target_name));
-                        } else {
-                            self.add_output("🛡️  MISS! The attack fails to connect.".to_string());
+                        Err(_) => {
+                            self.add_output("❌ Invalid damage input. Enter a number or dice expression (e.g., 2d6+3)".to_string());
+                            self.pending_input = Some(PendingInput::DamageFor(target_name, attack_kind));
                         }
                     }
-                    Err(e) => {
-                        self.add_output(format!("❌ Error rolling attack: {}", e));
-                    }
                 }
-            } else {
-                self.add_output(format!("❌ Target '{}' not found in combat", target_name));
             }
-        } else {
-            self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
         }
     }
 
-    fn process_save_command(&mut self, ability: &str, target: &str) {
-        let ability_full = match ability {
-            "str" => "Strength",
-            "dex" => "Dexterity", 
-            "con" => "Constitution",
-            "int" => "Intelligence",
-            "wis" => "Wisdom",
-            "cha" => "Charisma",
-            _ => {
-                self.add_output("❌ Invalid ability. Use: str, dex, con, int, wis, cha".to_string());
-                return;
-            }
-        };
+    fn process_combat_command(&mut self, command: String) {
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        if parts.is_empty() {
+            return;
+        }
 
-        let target_name = if target == "self" {
-            if let Some(ref tracker) = self.combat_tracker {
-                if let Some(current) = tracker.combatants.get(tracker.current_turn) {
-                    current.name.clone()
-                } else {
-                    self.add_output("❌ No current combatant".to_string());
+        let cmd = parts[0].to_lowercase();
+        
+        match cmd.as_str() {
+            "help" | "h" => {
+                self.add_output("Combat Mode Commands:".to_string());
+                self.add_output("  init - Initialize combat tracker".to_string());
+                self.add_output("  init import <file.csv> - Add name,initiative,hp,ac rows rolled during prep (saved characters are linked)".to_string());
+                self.add_output("  prefetch - Cache the party's spells and the fight's monsters for offline lookup".to_string());
+                self.add_output("  stats [name] - Show character stats".to_string());
+                self.add_output("  attack <target> [weapon] [damage type] [adv|dis] - Roll the current combatant's attack against target's AC; ranged weapons use up ammo".to_string());
+                self.add_output("  attacks [name] [add Scimitar +4, 1d6+2 slashing|remove <attack>] - Attack bonuses and damage; a hit rolls the damage".to_string());
+                self.add_output("  concentrate [name] [<spell> | end | roll [adv|dis] | <total>] - Concentration: damage calls for a CON save".to_string());
+                self.add_output("  adjust [table <name|none>] - Ways to rebalance a fight that's too easy or too hard (hints also come on 'next')".to_string());
+                self.add_output("  legendary [name] [count | <action> [cost]] - Legendary actions: set a creature's per round, or spend them".to_string());
+                self.add_output("  lair [name|off] - Fight in a creature's lair: a lair action reminder on initiative 20".to_string());
+                self.add_output("  save <stat> [target] [adv|dis] - Make saving throw (str/dex/con/int/wis/cha), e.g. save dex self dis".to_string());
+                self.add_output("  save encounter <name> - Save the whole fight to resume next session".to_string());
+                self.add_output("  load encounter <name> - Resume a saved fight ('encounters' lists them)".to_string());
+                self.add_output("  export combat <file> - Write the fight as Markdown for your notes, or to carry on with on paper".to_string());
+                self.add_output("  anonymize [character] - Copy the fight (or a sheet) with names and notes removed, for bug reports".to_string());
+                self.add_output("  hit|damage <target> <amount> [damage type] - Deal direct damage, halved, doubled or ignored by the target's defenses".to_string());
+                self.add_output("  resist|vulnerable|immune <name> <types...|none> - Set a combatant's damage resistances, vulnerabilities or immunities".to_string());
+                self.add_output("  heal <name> <amount> - Heal character".to_string());
+                self.add_output("  status <target> add <status> [rounds] [start|end] [by <name>] - Add status effect; it counts down at the end (or start) of the target's turn, and 'by' ends it when <name> drops".to_string());
+                self.add_output("  status <target> remove <status> - Remove status effect".to_string());
+                self.add_output("  status presets - List status presets; adding one by name brings its duration, damage and roll modifiers".to_string());
+                self.add_output("  remove <name> - Remove combatant (asks for confirmation)".to_string());
+                self.add_output("  hide [name] - Hide a combatant from the initiative list, or list hidden ones".to_string());
+                self.add_output("  reveal <name> - Show a hidden combatant in its initiative slot".to_string());
+                self.add_output("  renumber [monster] - Number each kind of monster 1, 2, 3... to match numbered minis".to_string());
+                self.add_output("  token <name> <number|none> - Set the number on the mini a combatant is using".to_string());
+                self.add_output("  next|continue - Advance to next combatant".to_string());
+                self.add_output("  ref <monster> - Show a monster's stat block beside the log (cached after the first lookup)".to_string());
+                self.add_output("  search <query> - Look up any spell, item, or monster in the reference pane".to_string());
+                self.add_output("  show|list - Display current initiative order".to_string());
+                self.add_output("  pacing - Party and monster HP and resources spent, round by round".to_string());
+                self.add_output("  weather [roll|clear|<conditions>] - Show or set the weather and its rules (works in any mode)".to_string());
+                self.add_output("  scene [n] | scene run [n] - The adventure outline, a scene's prep, or start its saved encounter (works in any mode)".to_string());
+                self.add_output("  campaign name <name> - Rename the campaign shown in the status bar (works in any mode)".to_string());
+                self.add_output("  sidekick <name> - Add a sidekick at the party's level with rolled initiative".to_string());
+                self.add_output("  ammo [name] - Show tracked ammunition for the party or one character".to_string());
+                self.add_output("  recover - After the fight, roll to recover about half the spent ammunition".to_string());
+                self.add_output("  light <torch|lantern|candle|...> <holder> - Light a source; it burns down 6 seconds per round".to_string());
+                self.add_output("  lights | douse <holder|kind> | vision - List lights, put them out, or show darkvision".to_string());
+                self.add_output("  cast [caster] <spell> [slot level] - Cast a spell, spending a spell slot and any costly material components".to_string());
+                self.add_output("  components [spell] - List spells with costly components, or show one".to_string());
+                self.add_output("  morale add <group> <2-12> <members...> - Group NPCs for morale checks (*name marks the leader)".to_string());
+                self.add_output("  morale [group] - List groups, or roll 2d6 morale for a group".to_string());
+                self.add_output("  tactics [on|off] - Suggest a move for the current monster, or show hints every monster turn".to_string());
+                self.add_output("  fly <name> <feet> | swim <name> | burrow <name> | land <name> - Track how a combatant is moving".to_string());
+                self.add_output("  reroll [on|off] - Variant rule: re-roll everyone's initiative at the top of each round".to_string());
+                self.add_output("  hidehp [on|off] - Show monster HP as Wounded/Bloodied/Near death instead of numbers".to_string());
+                self.add_output("  display - Toggle the large-text turn banner for players".to_string());
+                self.add_output("  playerview [addr|stop] - Share initiative with a `dnd_tools player-view` terminal".to_string());
+                self.add_output("  overlay [stop] - Keep initiative files up to date for an OBS overlay".to_string());
+                self.add_output("  api [addr|stop] - Local JSON API for overlays and LED trackers (builds with --features rest-api)".to_string());
+                self.add_output("  cond <condition> - Rules text for a condition (works in every mode)".to_string());
+                self.add_output("  rule <topic> - Rules summary for grapple, shove, hide, help, two-weapon, mounted, underwater".to_string());
+                self.add_output("  credits - Where the reference content comes from and its licenses".to_string());
+                self.add_output("  xp for cr <cr> | cr for xp <xp> | xp mult <monsters> <party> | xp adjust <party> <cr...>".to_string());
+                self.add_output("  use [name] <resource> [amount] - Spend a class resource (rage, ki...) for the current turn".to_string());
+                self.add_output("  check <skill> <name> [dc <number|easy|medium|hard...>] - Roll an ability or skill check".to_string());
+                self.add_output("  add <character> [initiative] - Bring a party character into the fight".to_string());
+                self.add_output("  add monster <name> [count] - Look a monster up and add it with its AC, HP and rolled initiative".to_string());
+                self.add_output("  sheet <name> - Show a combatant's full character sheet".to_string());
+                self.add_output("  wildshape list <level> [moon] | polymorph [true] <level> - Legal beast forms".to_string());
+                self.add_output("  companion <name> - Bring a character's familiar or beast companion into the fight".to_string());
+                self.add_output("  npc preset <role> [\"name\"] - Add a guard, thug, mage apprentice, priest or noble".to_string());
+                self.add_output("  setcr <name> <cr> - Set a combatant's challenge rating for the XP award".to_string());
+                self.add_output("  deathsave [name] - Roll a death save for a party member at 0 HP (the current combatant by default)".to_string());
+                self.add_output("  died <character> - Record a party character's death, archive their sheet and log it".to_string());
+                self.add_output("  end - Finish the fight, showing the XP from defeated NPCs and offering to award it".to_string());
+                self.add_output("  quit|exit - Exit combat mode".to_string());
+                self.add_output("".to_string());
+                self.add_output("Examples:".to_string());
+                self.add_output("  attack goblin".to_string());
+                self.add_output("  attack goblin longbow".to_string());
+                self.add_output("  save wis fighter".to_string());
+                self.add_output("  hit goblin 8".to_string());
+                self.add_output("  hit skeleton 8 bludgeoning".to_string());
+                self.add_output("  status goblin add poisoned 3".to_string());
+            }
+            "init" | "initialize" if parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("import")) => {
+                let path = parts[2..].join(" ");
+                if path.is_empty() {
+                    self.add_output("Usage: init import <file.csv>".to_string());
+                    self.add_output("Rows: name,initiative,hp,ac (hp and ac can be blank for saved characters)".to_string());
                     return;
                 }
-            } else {
-                self.add_output("No combat initialized.".to_string());
-                return;
-            }
-        } else {
-            target.to_string()
-        };
-
-        if let Some(ref tracker) = self.combat_tracker {
-            if let Some(_combatant) = tracker.combatants.iter().find(|c| c.name.eq_ignore_ascii_case(&target_name)) {
-                // Roll d20 for saving throw
-                match crate::dice::roll_dice_with_crits("1d20") {
-                    Ok((rolls, total, crit_message)) => {
-                        self.add_output(format!("🎲 {} saving throw for {}: {} (d20: {})", 
-                            ability_full, target_name, total, rolls[0]));
-                        
-                        if let Some(message) = crit_message {
-                            self.add_output(message);
+                let started = self.combat_tracker.is_none();
+                let tracker = self.combat_tracker.get_or_insert_with(crate::combat::CombatTracker::new);
+                let result = crate::saved_combat::import_initiative_file(std::path::Path::new(&path), tracker, &self.characters.read());
+                match result {
+                    Ok(lines) => {
+                        for line in lines {
+                            self.add_output(line);
                         }
+                        self.add_output("Type 'show' to see initiative order, or 'next' to start combat!".to_string());
+                        if started {
+                            self.announce_weather();
+                        }
+                        self.start_prefetch();
                     }
                     Err(e) => {
-                        self.add_output(format!("❌ Error rolling saving throw: {}", e));
+                        if started {
+                            self.combat_tracker = None;
+                        }
+                        self.add_output(format!("❌ {}", e));
                     }
                 }
-            } else {
-                self.add_output(format!("❌ Combatant '{}' not found", target_name));
             }
-        } else {
-            self.add_output("No combat initialized.".to_string());
-        }
-    }
-
-    fn process_hit_command(&mut self, target_name: &str, damage: i32) {
-        if let Some(ref mut tracker) = self.combat_tracker {
-            if let Some(combatant) = tracker.combatants.iter_mut().find(|c| c.name.eq_ignore_ascii_case(target_name)) {
-                let old_hp = combatant.current_hp;
-                combatant.current_hp = (combatant.current_hp - damage).max(0);
-                
-                let mut messages = vec![
-                    format!("⚔️ {} takes {} damage directly! HP: {} → {}", 
-                        combatant.name, damage, old_hp, combatant.current_hp)
-                ];
-                    
-                if combatant.current_hp <= 0 {
-                    messages.push(format!("💀 {} is unconscious/dead!", combatant.name));
+            "init" | "initialize" => {
+                self.initialize_combat();
+                self.start_prefetch();
+            }
+            "prefetch" => self.start_prefetch(),
+            "hide" | "reveal" => {
+                let hidden = cmd == "hide";
+                let Some(ref mut tracker) = self.combat_tracker else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                    return;
+                };
+                if parts.len() < 2 {
+                    let names = tracker.hidden_names();
+                    if !hidden {
+                        self.add_output("Usage: reveal <name>".to_string());
+                    } else if names.is_empty() {
+                        self.add_output("No hidden combatants. Usage: hide <name>".to_string());
+                    } else {
+                        self.add_output(format!("🙈 Hidden: {}", names.join(", ")));
+                    }
+                    return;
                 }
-                
-                for message in messages {
-                    self.add_output(message);
+                let message = match tracker.set_hidden(&parts[1..].join(" "), hidden) {
+                    Ok(name) if hidden => format!("🙈 {} is hidden from the initiative order", name),
+                    Ok(name) => format!("👁️ {} is revealed!", name),
+                    Err(e) => format!("❌ {}", e),
+                };
+                self.add_output(message);
+            }
+            "attacks" => {
+                let Some(tracker) = self.combat_tracker.as_mut() else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                    return;
+                };
+                let lines = tracker.attacks_command(&parts[1..], &self.characters.read());
+                for line in lines {
+                    self.add_output(line);
                 }
-            } else {
-                self.add_output(format!("❌ Combatant '{}' not found", target_name));
             }
-        } else {
-            self.add_output("No combat initialized.".to_string());
-        }
-    }
-
-    fn process_status_command(&mut self, target: &str, action: &str, status_name: &str, rounds: Option<i32>) {
+            "concentrate" | "conc" => {
+                let Some(tracker) = self.combat_tracker.as_mut() else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                    return;
+                };
+                let lines = tracker.concentrate_command(&parts[1..], &self.characters.read());
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "adjust" => {
+                let Some(tracker) = self.combat_tracker.as_mut() else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                    return;
+                };
+                for line in crate::balance::adjust_command(tracker, &parts[1..], &self.tables, &mut rand::rng()) {
+                    self.add_output(line);
+                }
+            }
+            "legendary" | "lair" => {
+                let Some(tracker) = self.combat_tracker.as_mut() else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                    return;
+                };
+                let lines = if cmd == "lair" { tracker.lair_command(&parts[1..]) } else { tracker.legendary_command(&parts[1..]) };
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "renumber" | "token" => {
+                let Some(tracker) = self.combat_tracker.as_mut() else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                    return;
+                };
+                for line in tracker.token_command(&cmd, &parts[1..]) {
+                    self.add_output(line);
+                }
+            }
+            "tactics" => {
+                let Some(tracker) = self.combat_tracker.as_mut() else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                    return;
+                };
+                let lines = match parts.get(1).map(|a| a.to_lowercase()).as_deref() {
+                    Some(setting @ ("on" | "off")) => {
+                        tracker.tactics_hints = setting == "on";
+                        vec![if tracker.tactics_hints {
+                            "💡 Tactics hints will be shown at the start of each monster's turn".to_string()
+                        } else {
+                            "Tactics hints are off".to_string()
+                        }]
+                    }
+                    _ => {
+                        let Some(current) = tracker.combatants.get(tracker.current_turn) else { return };
+                        let profile = crate::tactics::TacticsProfile::for_combatant(current);
+                        let hints = crate::tactics::hint(tracker, &profile, false, &mut rand::rng());
+                        if hints.is_empty() { vec!["No tactics hints on the party's turn".to_string()] } else { hints }
+                    }
+                };
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "fly" | "swim" | "burrow" | "land" => {
+                let (Some(name), Some(tracker)) = (parts.get(1), self.combat_tracker.as_mut()) else {
+                    self.add_output("Usage: fly <name> <feet> | swim <name> | burrow <name> | land <name>".to_string());
+                    return;
+                };
+                let movement = match crate::movement::Movement::parse(&cmd, parts.get(2).copied()) {
+                    Ok(movement) => movement,
+                    Err(e) => {
+                        self.add_output(format!("❌ {}", e));
+                        return;
+                    }
+                };
+                let Some(combatant) = tracker.get_combatant_mut(name) else {
+                    self.add_output(format!("❌ Combatant '{}' not found", name));
+                    return;
+                };
+                combatant.movement = movement;
+                let mut lines = match movement {
+                    Some(movement) => vec![format!("✅ {}: {}", combatant.name, movement.label()), format!("   {}", movement.reminder())],
+                    None => vec![format!("✅ {} is back on the ground", combatant.name)],
+                };
+                if matches!(movement, Some(crate::movement::Movement::Flying { .. })) {
+                    lines.extend(self.weather.reminders(crate::weather::RuleScope::Flying).into_iter().map(|r| format!("   {}", r)));
+                }
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "reroll" => {
+                if let Some(ref mut tracker) = self.combat_tracker {
+                    tracker.reroll_each_round = match parts.get(1).map(|a| a.to_lowercase()).as_deref() {
+                        Some("on") => true,
+                        Some("off") => false,
+                        _ => !tracker.reroll_each_round,
+                    };
+                    let message = if tracker.reroll_each_round {
+                        "🎲 Initiative will be re-rolled (d20+DEX) at the top of each round"
+                    } else {
+                        "Initiative stays the same from round to round"
+                    };
+                    self.add_output(message.to_string());
+                } else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                }
+            }
+            "hidehp" => {
+                if let Some(ref mut tracker) = self.combat_tracker {
+                    tracker.hide_monster_hp = match parts.get(1).map(|a| a.to_lowercase()).as_deref() {
+                        Some("on") => true,
+                        Some("off") => false,
+                        _ => !tracker.hide_monster_hp,
+                    };
+                    let message = if tracker.hide_monster_hp {
+                        "🙈 Monster HP is now descriptive in shared output. 'stats <name>' still shows exact numbers."
+                    } else {
+                        "👀 Monster HP is shown as numbers"
+                    };
+                    self.add_output(message.to_string());
+                } else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                }
+            }
+            "morale" => self.process_morale_command(&parts[1..]),
+            "cast" => self.process_cast_command(&parts[1..]),
+            "light" | "lights" | "douse" | "vision" => self.process_light_command(&cmd, &parts[1..]),
+            "ammo" => {
+                let name = parts[1..].join(" ");
+                let lines: Vec<String> = self.characters.read().iter()
+                    .filter(|c| name.is_empty() || c.name.eq_ignore_ascii_case(&name))
+                    .filter(|c| !name.is_empty() || !c.ammo.is_empty())
+                    .map(crate::ammo::describe_ammo)
+                    .collect();
+                if lines.is_empty() {
+                    self.add_output("No tracked ammunition. Set it with 'ammo <name> <kind> <count>' in Characters → Display".to_string());
+                }
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "recover" => {
+                let mut rng = rand::rng();
+                let mut lines = Vec::new();
+                for character in self.characters.write().iter_mut() {
+                    for (kind, recovered, expended) in crate::ammo::recover_ammo(character, &mut rng) {
+                        lines.push(format!("🏹 {} recovers {} of {} {}", character.name, recovered, expended, kind));
+                    }
+                }
+                if lines.is_empty() {
+                    self.add_output("No spent ammunition to recover".to_string());
+                }
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "components" => {
+                let spell = parts[1..].join(" ");
+                if spell.is_empty() {
+                    self.add_output("💎 Spells with costly components:".to_string());
+                    for component in crate::components::COSTLY_COMPONENTS {
+                        let consumed = if component.consumed { ", consumed" } else { "" };
+                        self.add_output(format!("  {} - {}{}", component.spell, component.description, consumed));
+                    }
+                } else {
+                    let message = match crate::components::costly_component(&spell) {
+                        Some(component) => format!("💎 {} needs {}{}", component.spell, component.description,
+                            if component.consumed { ", which the spell consumes" } else { "" }),
+                        None => format!("{} has no costly components; a focus or component pouch covers it", spell),
+                    };
+                    self.add_output(message);
+                }
+            }
+            "sidekick" => {
+                let Some(name) = parts.get(1) else {
+                    self.add_output("Usage: sidekick <name> - Add a sidekick to combat".to_string());
+                    return;
+                };
+                let level = crate::sidekick::party_level(&self.characters.read());
+                let message = match (self.sidekicks.get(name), self.combat_tracker.as_mut()) {
+                    (Some(sidekick), Some(tracker)) => {
+                        let roll = rand::random_range(1..=20);
+                        let initiative = roll + sidekick.dex_mod;
+                        tracker.add_combatant(sidekick.to_combatant(level, initiative));
+                        format!("🤝 {} joins the fight at level {} (initiative {} = d20 {} + {})",
+                            sidekick.name, level, initiative, roll, sidekick.dex_mod)
+                    }
+                    (None, _) => format!("❌ Sidekick '{}' not found. Add one from Characters → Display", name),
+                    (_, None) => "No combat initialized. Use 'init' to start combat.".to_string(),
+                };
+                self.add_output(message);
+            }
+            "check" => self.skill_check(&parts[1..]),
+            "add" if parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("monster")) => {
+                // `add monster goblin 3` reads the wiki page (or the cache) for each copy's stats
+                let (name, count) = match parts.last().and_then(|p| p.parse::<usize>().ok()) {
+                    Some(count) if parts.len() > 3 => (parts[2..parts.len() - 1].join(" "), count.clamp(1, 20)),
+                    _ => (parts[2..].join(" "), 1),
+                };
+                if name.is_empty() {
+                    self.add_output("Usage: add monster <name> [count]".to_string());
+                    return;
+                }
+                if self.combat_tracker.is_none() {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                    return;
+                }
+                self.add_output(format!("🔍 Looking up {}...", name));
+                let lines = match crate::search::fetch_monster(&name) {
+                    Ok(monster) => match self.combat_tracker.as_mut() {
+                        Some(tracker) => monster.add_to_tracker(tracker, count, &mut rand::rng()),
+                        None => Vec::new(),
+                    },
+                    Err(crate::error_handling::AppError::NotFound(_)) => vec![format!("❌ No monster page found for '{}'", name)],
+                    Err(e) => vec![format!("❌ Could not add {}: {}", name, e)],
+                };
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "add" | "join" => {
+                // `add Lia [initiative]` brings a party character in, linked to their sheet
+                let (name, initiative) = match parts.last().and_then(|p| p.parse::<i32>().ok()) {
+                    Some(initiative) if parts.len() > 2 => (parts[1..parts.len() - 1].join(" "), Some(initiative)),
+                    _ => (parts[1..].join(" "), None),
+                };
+                let found = self.characters.find(&name);
+                let message = match (found, self.combat_tracker.as_mut()) {
+                    _ if name.is_empty() => "Usage: add <character> [initiative]".to_string(),
+                    (Some(character), Some(tracker)) => {
+                        if tracker.combatants.iter().any(|c| c.character_id == Some(character.id)) {
+                            format!("❌ {} is already in the fight", character.name)
+                        } else {
+                            let dex = character.get_dexterity_modifier() as i32;
+                            let initiative = initiative.unwrap_or_else(|| rand::random_range(1..=20) + dex);
+                            tracker.add_combatant(crate::combat::Combatant::from_character(&character, initiative));
+                            format!("✅ {} joins the fight (initiative {})", character.name, initiative)
+                        }
+                    }
+                    (None, _) => format!("❌ Character '{}' not found", name),
+                    (_, None) => "No combat initialized. Use 'init' to start combat.".to_string(),
+                };
+                self.add_output(message);
+            }
+            "sheet" => {
+                let name = parts[1..].join(" ");
+                let characters = self.characters.read();
+                let sheet = self.combat_tracker.as_ref()
+                    .and_then(|tracker| tracker.get_combatant(&name))
+                    .and_then(|c| c.sheet(&characters))
+                    .cloned();
+                drop(characters);
+                match sheet {
+                    Some(character) => self.display_character_details(&character),
+                    None => self.add_output(format!("❌ No character sheet for '{}' in this fight", name)),
+                }
+            }
+            "use" => {
+                // `use rage` for whoever's turn it is, or `use Borin rage`
+                let named = parts.get(1).filter(|name| self.characters.read().iter().any(|c| c.name.eq_ignore_ascii_case(name)));
+                let current = self.combat_tracker.as_ref()
+                    .and_then(|tracker| tracker.combatants.get(tracker.current_turn))
+                    .map(|c| c.name.clone());
+                let message = match (named, current) {
+                    (Some(name), _) => self.use_character_resource(name, &parts[2..]),
+                    (None, Some(name)) if parts.len() > 1 => self.use_character_resource(&name, &parts[1..]),
+                    _ => "Usage: use <resource> [amount] (for the current turn) or use <name> <resource> [amount]".to_string(),
+                };
+                self.add_output(message);
+            }
+            "wildshape" | "polymorph" => self.show_shape_forms(&cmd, &parts[1..]),
+            "companion" | "familiar" => {
+                let name = parts[1..].join(" ");
+                let found = crate::companions::find_owner(&self.characters.read(), &name).map(|(owner, c)| (owner.name.clone(), c.clone()));
+                let message = match (found, self.combat_tracker.as_mut()) {
+                    _ if name.is_empty() => "Usage: companion <name> - Bring a familiar or beast companion into combat".to_string(),
+                    (Some((owner, companion)), Some(tracker)) => {
+                        // Companions act alongside their master; without the master in the fight they roll their own
+                        let master = tracker.combatants.iter().find(|c| c.name.eq_ignore_ascii_case(&owner)).map(|c| c.initiative);
+                        let initiative = master.unwrap_or_else(|| rand::random_range(1..=20));
+                        tracker.add_combatant(companion.to_combatant(&owner, initiative));
+                        format!("🐾 {}'s {} {} joins the fight (initiative {})", owner, companion.kind, companion.name, initiative)
+                    }
+                    (None, _) => format!("❌ No character has a companion named '{}'", name),
+                    (_, None) => "No combat initialized. Use 'init' to start combat.".to_string(),
+                };
+                self.add_output(message);
+            }
+            "npc" => match parts.get(1).map(|p| p.to_lowercase()).as_deref() {
+                Some("preset") => {
+                    let args = command.splitn(3, char::is_whitespace).nth(2).unwrap_or_default();
+                    let lines = match (crate::npc_presets::parse_preset_args(args), self.combat_tracker.as_mut()) {
+                        (Ok((preset, name)), Some(tracker)) => {
+                            let roll = rand::random_range(1..=20);
+                            let initiative = roll + preset.dex_mod();
+                            tracker.add_combatant(preset.to_combatant(&name, initiative));
+                            // A saved NPC's temperament, so they're played the way the party knows them
+                            std::iter::once(format!("🛡️ {} the {} joins the fight (CR {}, initiative {} = d20 {} + {})",
+                                name, preset.role, preset.cr, initiative, roll, preset.dex_mod()))
+                                .chain(crate::temperament::join_reminder(std::path::Path::new(crate::file_manager::NPCS_DIR), &name))
+                                .collect()
+                        }
+                        (Err(e), _) => vec![format!("❌ {}", e)],
+                        (_, None) => vec!["No combat initialized. Use 'init' to start combat.".to_string()],
+                    };
+                    for line in lines {
+                        self.add_output(line);
+                    }
+                }
+                _ => self.add_output(format!("Usage: npc preset <{}> [\"name\"]", crate::npc_presets::preset_roles().join("|"))),
+            },
+            "playerview" | "pv" => {
+                self.process_player_view_command(parts.get(1).copied());
+            }
+            "overlay" => {
+                self.process_overlay_command(parts.get(1).copied());
+            }
+            "api" => {
+                self.process_api_command(parts.get(1).copied());
+            }
+            "display" => {
+                self.table_display = !self.table_display;
+                if self.table_display {
+                    self.add_output("📺 Table display on - type 'display' again to return to the log".to_string());
+                } else {
+                    self.add_output("📺 Table display off".to_string());
+                }
+            }
+            "ref" | "reference" => match parts.get(1).map(|p| p.to_lowercase()).as_deref() {
+                None => self.add_output("Usage: ref <monster> | ref close".to_string()),
+                Some("close" | "off") => self.reference = None,
+                Some(_) => self.load_reference(&parts[1..].join(" "), Some(crate::search::SearchCategory::Monsters)),
+            },
+            "search" => {
+                if let Some(_query) = parts.get(1) {
+                    let full_query = parts[1..].join(" ");
+                    self.load_reference(&full_query, None);
+                } else {
+                    self.add_output("Usage: search <query>".to_string());
+                    self.add_output("Example: search fireball".to_string());
+                }
+            }
+            "quit" | "exit" | "q" => {
+                self.add_output("Exiting combat mode...".to_string());
+                self.leave_combat();
+            }
+            "end" => {
+                let award = self.combat_tracker.as_ref()
+                    .and_then(|tracker| crate::xp::XpAward::from_combat(tracker, &self.combat_party()));
+                match award {
+                    Some(award) => {
+                        for line in award.describe() {
+                            self.add_output(line);
+                        }
+                        self.add_output("Apply this to the party's XP? (y/n)".to_string());
+                        self.pending_input = Some(PendingInput::ConfirmXpAward(award));
+                    }
+                    None => {
+                        self.leave_combat();
+                        self.message = Some("Combat over. No defeated NPCs had a CR, so there's no XP to award.".to_string());
+                    }
+                }
+            }
+            "resist" | "resistant" | "vulnerable" | "vuln" | "immune" => {
+                let Some(ref mut tracker) = self.combat_tracker else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                    return;
+                };
+                if parts.len() < 3 {
+                    self.add_output(format!("Usage: {} <name> <types...|none>", cmd));
+                    self.add_output("Example: resist skeleton piercing slashing".to_string());
+                    return;
+                }
+                let message = match tracker.set_defenses(parts[1], &cmd, &parts[2..]) {
+                    Ok(message) => message,
+                    Err(e) => format!("❌ {}", e),
+                };
+                self.add_output(message);
+            }
+            "deathsave" | "ds" => {
+                let Some(ref mut tracker) = self.combat_tracker else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                    return;
+                };
+                let name = match parts[1..].join(" ") {
+                    name if name.is_empty() || name == "self" => tracker.combatants.get(tracker.current_turn).map(|c| c.name.clone()).unwrap_or_default(),
+                    name => name,
+                };
+                let roll = match crate::dice::roll_dice_with_crits("1d20") {
+                    Ok(roll) => roll.total,
+                    Err(e) => {
+                        self.add_output(format!("❌ Error rolling death save: {}", e));
+                        return;
+                    }
+                };
+                let lines = match tracker.record_death_save(&name, roll) {
+                    Ok(message) => {
+                        let mut lines: Vec<String> = message.lines().map(str::to_string).collect();
+                        if let Some(dead) = tracker.get_combatant(&name).filter(|c| c.death_saves.is_some_and(|s| s.is_dead()) && c.character_id.is_some()) {
+                            lines.push(format!("   'died {}' records it", dead.name));
+                        }
+                        lines
+                    }
+                    Err(e) => vec![format!("❌ {}", e)],
+                };
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "died" | "death" => {
+                let name = parts[1..].join(" ");
+                let linked = self.combat_tracker.as_ref()
+                    .and_then(|tracker| tracker.get_combatant(&name))
+                    .and_then(|combatant| combatant.character_id.map(|_| combatant.name.clone()));
+                match linked {
+                    _ if name.is_empty() => self.add_output("Usage: died <character>".to_string()),
+                    Some(name) => {
+                        self.add_output(format!("🪦 How did {} die? (or 'cancel')", name));
+                        self.pending_input = Some(PendingInput::DeathCause(name));
+                    }
+                    None => self.add_output(format!("❌ '{}' is not a party character in this fight", name)),
+                }
+            }
+            "setcr" => match parts.get(1..) {
+                Some([name @ .., cr]) if !name.is_empty() => {
+                    let name = name.join(" ");
+                    let message = match (crate::xp::xp_for_cr(cr), self.combat_tracker.as_mut()
+                        .and_then(|tracker| tracker.get_combatant_mut(&name)))
+                    {
+                        (None, _) => format!("❌ Unknown challenge rating '{}'", cr),
+                        (Some(_), None) => format!("❌ Combatant '{}' not found", name),
+                        (Some(xp), Some(combatant)) => {
+                            combatant.cr = Some(cr.to_string());
+                            format!("✅ {} is CR {} ({} XP)", combatant.name, cr, crate::xp::format_xp(xp))
+                        }
+                    };
+                    self.add_output(message);
+                }
+                _ => self.add_output("Usage: setcr <name> <cr>".to_string()),
+            },
+            "pacing" => {
+                let lines = match self.combat_tracker.as_ref() {
+                    Some(tracker) => tracker.pacing.table_lines(),
+                    None => vec!["No combat initialized. Use 'init' to start combat.".to_string()],
+                };
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "show" | "list" => {
+                if let Some(ref tracker) = self.combat_tracker {
+                    let mut lines = vec!["Initiative Order:".to_string()];
+                    let lair_row = tracker.lair_row();
+                    for (row, (i, combatant)) in tracker.visible_combatants().enumerate() {
+                        if lair_row == Some(row) && let Some(lair) = &tracker.lair {
+                            lines.push(format!("  🏰 Init 20: {}'s lair action", lair));
+                        }
+                        let marker = if i == tracker.current_turn { "►" } else { " " };
+                        let status_text = if combatant.status_effects.is_empty() {
+                            "".to_string()
+                        } else {
+                            format!(" [{}]", combatant.status_effects.iter()
+                                .map(|s| s.name.as_str()).collect::<Vec<_>>().join(", "))
+                        };
+                        let owner_text = combatant.owner.as_ref()
+                            .map(|owner| format!(" ({}'s)", owner))
+                            .unwrap_or_default();
+                        lines.push(format!("{} {}. {}{} (Init: {}, HP: {}, AC: {}){}{}{}",
+                            marker, i + 1, tracker.display_name(combatant), owner_text, combatant.initiative,
+                            combatant.hp_display(tracker.hide_monster_hp), combatant.ac, status_text, combatant.death_save_tag(), combatant.concentration_tag()));
+                    }
+                    if let Some(lair) = &tracker.lair && lair_row == Some(tracker.visible_combatants().count()) {
+                        lines.push(format!("  🏰 Init 20: {}'s lair action", lair));
+                    }
+                    for line in lines {
+                        self.add_output(line);
+                    }
+                } else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                }
+            }
+            "next" | "continue" => {
+                if let Some(ref mut tracker) = self.combat_tracker {
+                    if tracker.combatants.is_empty() {
+                        self.add_output("❌ No combatants in combat.".to_string());
+                    } else {
+                        let mut messages = tracker.tick_status_effects(tracker.current_turn, crate::combat::TickPoint::EndOfTurn);
+                        tracker.current_turn = (tracker.current_turn + 1) % tracker.combatants.len();
+                        
+                        let new_round = tracker.current_turn == 0;
+                        if new_round {
+                            tracker.round_number += 1;
+                            messages.push(format!("🔄 Starting Round {}", tracker.round_number));
+                            messages.extend(tracker.reroll_for_new_round(&self.characters.read(), &mut rand::rng()));
+                        }
+                        messages.extend(tracker.tick_status_effects(tracker.current_turn, crate::combat::TickPoint::StartOfTurn));
+                        messages.extend(tracker.turn_reminders(tracker.current_turn));
+                        
+                        let current = &tracker.combatants[tracker.current_turn];
+                        if current.hidden {
+                            messages.push(format!("🙈 {} acts from hiding ('reveal {}' to show them)", current.name, current.name));
+                        } else {
+                            messages.push(format!("🎯 It's {}'s turn! (Initiative: {}, HP: {})", 
+                                current.name, current.initiative, current.hp_display(tracker.hide_monster_hp)));
+                        }
+                        if current.death_saves.is_some_and(|saves| !saves.is_stable() && !saves.is_dead()) {
+                            messages.push(format!("💀 {} is dying: 'deathsave' rolls their death save", current.name));
+                        }
+                        let hook = Hook::TurnStart { name: current.name.clone(), round: tracker.round_number };
+                        if tracker.tactics_hints {
+                            let profile = crate::tactics::TacticsProfile::for_combatant(current);
+                            messages.extend(crate::tactics::hint(tracker, &profile, true, &mut rand::rng()));
+                        }
+                        messages.extend(crate::balance::turn_hints(tracker, &self.tables, &mut rand::rng()));
+                        
+                        for message in messages {
+                            self.add_output(message);
+                        }
+                        if new_round {
+                            self.burn_lights(crate::light::SECONDS_PER_ROUND);
+                        }
+                        self.run_script_hook(hook);
+                    }
+                } else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                }
+            }
+            "stats" => {
+                if let Some(ref tracker) = self.combat_tracker {
+                    if parts.len() >= 2 {
+                        let name = parts[1].to_string();
+                        self.show_combatant_stats(&name);
+                    } else {
+                        // Show current combatant stats
+                        if let Some(current) = tracker.combatants.get(tracker.current_turn) {
+                            let messages = vec![
+                                format!("📊 Current Turn: {}", current.name),
+                                format!("  HP: {}/{}", current.current_hp, current.max_hp),
+                                format!("  AC: {}", current.ac),
+                            ];
+                            
+                            for message in messages {
+                                self.add_output(message);
+                            }
+                        } else {
+                            self.add_output("❌ No current combatant".to_string());
+                        }
+                    }
+                } else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                }
+            }
+            "attack" => {
+                let (args, edge) = crate::combat::split_edge(&parts[1..]);
+                if let Some((&target_name, rest)) = args.split_first() {
+                    let (weapon, kind) = crate::combat::split_damage_type(rest);
+                    let weapon = (!weapon.is_empty()).then(|| weapon.join(" "));
+                    self.process_attack_command(target_name, weapon.as_deref(), kind, edge);
+                } else {
+                    self.add_output("Usage: attack <target> [weapon] [damage type] [adv|dis]".to_string());
+                    self.add_output("Example: attack goblin adv".to_string());
+                }
+            }
+            "save" | "load" if parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("encounter")) => {
+                let name = parts[2..].join(" ");
+                if name.is_empty() {
+                    self.add_output(format!("Usage: {} encounter <name>", cmd));
+                } else if cmd == "save" {
+                    self.save_encounter(&name);
+                } else {
+                    self.load_encounter(&name);
+                }
+            }
+            "anonymize" => {
+                let message = crate::anonymize::anonymize_command(&parts[1..], &self.characters.read(), self.combat_tracker.as_ref());
+                self.add_output(message);
+            }
+            "export" if parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("combat")) => {
+                let file = parts[2..].join(" ");
+                let message = match (self.combat_tracker.as_ref(), file.is_empty()) {
+                    (_, true) => "Usage: export combat <file>".to_string(),
+                    (None, false) => "No combat initialized. Use 'init' to start combat.".to_string(),
+                    (Some(tracker), false) => match crate::saved_combat::export_combat(std::path::Path::new(&file), tracker, &self.characters.read()) {
+                        Ok(path) => format!("📝 Wrote the fight (round {}) to {}", tracker.round_number, path.display()),
+                        Err(e) => format!("❌ Could not export the fight: {}", e),
+                    },
+                };
+                self.add_output(message);
+            }
+            "encounters" => {
+                let names = crate::saved_combat::list_encounters_in(std::path::Path::new(crate::saved_combat::ENCOUNTERS_DIR));
+                if names.is_empty() {
+                    self.add_output("No saved encounters. 'save encounter <name>' keeps the current fight.".to_string());
+                } else {
+                    self.add_output(format!("📂 Saved encounters: {}", names.join(", ")));
+                }
+            }
+            "save" => {
+                let (args, edge) = crate::combat::split_edge(&parts[1..]);
+                if let Some((ability, rest)) = args.split_first() {
+                    let ability = ability.to_lowercase();
+                    let target = rest.first().copied().unwrap_or("self");
+                    self.process_save_command(&ability, target, edge);
+                } else {
+                    self.add_output("Usage: save <ability> [target] [adv|dis]".to_string());
+                    self.add_output("Abilities: str, dex, con, int, wis, cha".to_string());
+                    self.add_output("Example: save wis goblin".to_string());
+                }
+            }
+            "status" if parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("presets")) => {
+                for line in self.status_presets.lines() {
+                    self.add_output(line);
+                }
+            }
+            "status" => {
+                if parts.len() >= 4 {
+                    let target = parts[1];
+                    let action = parts[2].to_lowercase();
+                    let mut status = self.status_presets.status(parts[3]);
+                    let mut options = parts[4..].iter();
+                    while let Some(option) = options.next() {
+                        if let Ok(rounds) = option.parse::<i32>() {
+                            status.duration = Some(rounds);
+                        } else if let Some(point) = crate::combat::TickPoint::parse(option) {
+                            status.ticks_at = point;
+                        } else if option.eq_ignore_ascii_case("by") {
+                            status.concentration = Some(options.by_ref().copied().collect::<Vec<_>>().join(" "));
+                        }
+                    }
+                    self.process_status_command(target, &action, status);
+                } else {
+                    self.add_output("Usage: status <target> <add|remove> <status> [rounds] [start|end] [by <concentrating>]".to_string());
+                    self.add_output("Example: status goblin add poisoned 3".to_string());
+                    self.add_output("Example: status ogre add held 10 start by Mira".to_string());
+                    self.add_output("Example: status fighter remove stunned".to_string());
+                }
+            }
+            "remove" => {
+                if parts.len() >= 2 {
+                    let name = parts[1];
+                    let found = self.combat_tracker.as_ref()
+                        .and_then(|tracker| tracker.get_combatant(name).map(|c| tracker.display_name(c)));
+                    if let Some(name) = found {
+                        self.add_output(format!("⚠️  Remove {} from combat? Type 'y' or the name to confirm.", name));
+                        self.pending_input = Some(PendingInput::ConfirmRemoveCombatant(name));
+                    } else {
+                        self.add_output(format!("❌ Combatant '{}' not found", name));
+                    }
+                } else {
+                    self.add_output("Usage: remove <name>".to_string());
+                }
+            }
+            "hit" | "damage" => {
+                let (args, kind) = crate::combat::split_damage_type(&parts[1..]);
+                if let [target_name, amount] = args {
+                    if let Ok(damage_amount) = amount.parse::<i32>() {
+                        self.process_hit_command(target_name, damage_amount, kind);
+                    } else {
+                        self.add_output("❌ Invalid damage amount".to_string());
+                    }
+                } else {
+                    self.add_output(format!("Usage: {} <target> <amount> [damage type]", cmd));
+                    self.add_output("Example: hit goblin 8 fire".to_string());
+                }
+            }
+            "heal" => {
+                if parts.len() >= 3 {
+                    let target_name = parts[1];
+                    if let Ok(heal_amount) = parts[2].parse::<i32>() {
+                        if let Some(ref mut tracker) = self.combat_tracker {
+                            let hide = tracker.hide_monster_hp;
+                            if let Some(combatant) = tracker.get_combatant_mut(target_name) {
+                                let old_hp = combatant.current_hp;
+                                combatant.current_hp = (combatant.current_hp + heal_amount).min(combatant.max_hp);
+                                
+                                let message = format!("💚 {} heals {} HP! {}", 
+                                    combatant.name, heal_amount, combatant.hp_change_display(old_hp, hide));
+                                self.add_output(message);
+                            } else {
+                                self.add_output(format!("❌ Combatant '{}' not found", target_name));
+                            }
+                        } else {
+                            self.add_output("No combat initialized.".to_string());
+                        }
+                    } else {
+                        self.add_output("❌ Invalid heal amount".to_string());
+                    }
+                } else {
+                    self.add_output("Usage: heal <target> <amount>".to_string());
+                }
+            }
+            _ => {
+                if self.combat_tracker.is_some() {
+                    // Handle other combat commands
+                    self.add_output(format!("Unknown command '{}'. Type 'help' for available commands.", cmd));
+                } else {
+                    self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+                }
+            }
+        }
+    }
+
+    fn show_combatant_stats(&mut self, name: &str) {
+        let Some(ref tracker) = self.combat_tracker else {
+            self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+            return;
+        };
+
+        if let Some(combatant) = tracker.get_combatant(name) {
+            let mut messages = vec![
+                format!("📊 Stats for {}", combatant.name),
+                format!("  HP: {}/{} ({})", combatant.current_hp, combatant.max_hp, 
+                    if combatant.current_hp > 0 { "Alive" } else { "Unconscious/Dead" }),
+                format!("  AC: {}", combatant.ac),
+                format!("  Initiative: {}", combatant.initiative),
+                format!("  Type: {}", if combatant.is_player { "Player" } else { "NPC" }),
+            ];
+            if let Some(saves) = combatant.death_saves {
+                messages.push(format!("  Death Saves: {}", saves.tally()));
+            }
+            messages.extend(combatant.defense_lines().into_iter().map(|line| format!("  {}", line)));
+            if let Some(cr) = &combatant.cr {
+                messages.push(format!("  CR: {}", cr));
+            }
+            if let Some(character) = combatant.sheet(&self.characters.read()) {
+                messages.extend(Self::resource_lines(character));
+            }
+            
+            if !combatant.status_effects.is_empty() {
+                messages.push("  Status Effects:".to_string());
+                for effect in &combatant.status_effects {
+                    messages.push(format!("    - {}{}", effect.name, effect.duration_text()));
+                }
+            }
+            
+            for message in messages {
+                self.add_output(message);
+            }
+        } else {
+            self.add_output(format!("❌ Combatant '{}' not found", name));
+        }
+    }
+
+    fn process_attack_command(&mut self, target_name: &str, weapon: Option<&str>, kind: Option<crate::combat::DamageType>, edge: Option<crate::combat::Edge>) {
+        if let Some(weapon) = weapon
+            && !self.expend_attack_ammo(weapon)
+        {
+            return;
+        }
+        let Some(ref mut tracker) = self.combat_tracker else {
+            self.add_output("No combat initialized. Use 'init' to start combat.".to_string());
+            return;
+        };
+        // Attacking starts the attacker's turn, even at the very top of the fight
+        tracker.begin_turn();
+        let tracker = &*tracker;
+        let Some(target) = tracker.get_combatant(target_name) else {
+            self.add_output(format!("❌ Target '{}' not found in combat", target_name));
+            return;
+        };
+        let attacker = tracker.combatants.get(tracker.current_turn);
+        let mut reminders = crate::combat::condition_attack_hints(attacker, target);
+        reminders.extend(crate::movement::attack_reminders(attacker, target, weapon));
+        if weapon.is_some_and(|w| crate::ammo::ammo_for_weapon(w).is_some()) {
+            reminders.extend(self.weather.reminders(crate::weather::RuleScope::Ranged));
+        }
+        // The attacker's bonus and damage dice come from their attack profiles or sheet
+        let outcome = crate::attacks::resolve_attack(attacker, target, weapon, kind, edge, &self.characters.read());
+        let outcome = match outcome {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                self.add_output(format!("❌ Error rolling attack: {}", e));
+                return;
+            }
+        };
+        for line in outcome.lines {
+            self.add_output(line);
+        }
+        for reminder in reminders {
+            self.add_output(reminder);
+        }
+        self.run_script_hook(Hook::Roll {
+            kind: "attack".to_string(),
+            expression: outcome.expression,
+            total: outcome.roll.total,
+            crit: outcome.roll.is_critical_success(),
+            target: target_name.to_string(),
+        });
+
+        if !outcome.hit {
+            self.add_output("🛡️  MISS! The attack fails to connect.".to_string());
+            return;
+        }
+        self.add_output("💥 HIT! The attack connects!".to_string());
+        match outcome.damage {
+            Some(damage) => {
+                self.add_output(damage.line);
+                self.process_hit_command(target_name, damage.amount, damage.kind);
+            }
+            None => {
+                self.add_output("🎲 Enter damage (e.g., '2d6+3' or just '8'):".to_string());
+                self.current_state = format!("Waiting for damage against {}", target_name);
+                self.pending_input = Some(PendingInput::DamageFor(target_name.to_string(), kind));
+            }
+        }
+    }
+
+    fn process_save_command(&mut self, ability: &str, target: &str, edge: Option<crate::combat::Edge>) {
+        let ability_score = match ability {
+            "str" => AbilityScore::Strength,
+            "dex" => AbilityScore::Dexterity,
+            "con" => AbilityScore::Constitution,
+            "int" => AbilityScore::Intelligence,
+            "wis" => AbilityScore::Wisdom,
+            "cha" => AbilityScore::Charisma,
+            _ => {
+                self.add_output("❌ Invalid ability. Use: str, dex, con, int, wis, cha".to_string());
+                return;
+            }
+        };
+
+        let target_name = if target == "self" {
+            if let Some(ref tracker) = self.combat_tracker {
+                if let Some(current) = tracker.combatants.get(tracker.current_turn) {
+                    current.name.clone()
+                } else {
+                    self.add_output("❌ No current combatant".to_string());
+                    return;
+                }
+            } else {
+                self.add_output("No combat initialized.".to_string());
+                return;
+            }
+        } else {
+            target.to_string()
+        };
+
+        if let Some(ref tracker) = self.combat_tracker {
+            if let Some(combatant) = tracker.get_combatant(&target_name) {
+                let ability_full = ability_score.name();
+                if let Some(condition) = combatant.auto_fails_save(ability_score) {
+                    self.add_output(format!("🎲 {} saving throw for {}: automatic failure ({})",
+                        ability_full, target_name, condition.name().to_lowercase()));
+                    return;
+                }
+                let hints = combatant.save_hints(ability_score);
+                // Roll d20 for saving throw, two of them with advantage or disadvantage
+                match crate::dice::roll_dice_with_crits(crate::combat::Edge::d20(edge)) {
+                    Ok(roll) => {
+                        self.add_output(format!("🎲 {} saving throw for {}{}: {}", 
+                            ability_full, target_name, crate::combat::Edge::with(edge), roll));
+                        
+                        if let Some(message) = roll.crit {
+                            self.add_output(message);
+                        }
+                        for hint in hints {
+                            self.add_output(hint);
+                        }
+                    }
+                    Err(e) => {
+                        self.add_output(format!("❌ Error rolling saving throw: {}", e));
+                    }
+                }
+            } else {
+                self.add_output(format!("❌ Combatant '{}' not found", target_name));
+            }
+        } else {
+            self.add_output("No combat initialized.".to_string());
+        }
+    }
+
+    fn process_hit_command(&mut self, target_name: &str, damage: i32, kind: Option<crate::combat::DamageType>) {
+        if let Some(ref mut tracker) = self.combat_tracker {
+            tracker.begin_turn();
+            let hide = tracker.hide_monster_hp;
+            if let Some(combatant) = tracker.get_combatant_mut(target_name) {
+                let old_hp = combatant.current_hp;
+                let (damage, note) = combatant.adjust_damage(damage, kind);
+                let dying = if damage > 0 { combatant.damaged_while_dying() } else { None };
+                combatant.current_hp = (combatant.current_hp - damage).max(0);
+                
+                let mut messages: Vec<String> = note.into_iter().collect();
+                messages.push(format!("⚔️ {} takes {} damage directly! {}", 
+                    combatant.name, damage, combatant.hp_change_display(old_hp, hide)));
+                messages.extend(dying.iter().flat_map(|d| d.lines().map(str::to_string)));
+                    
+                if combatant.current_hp <= 0 {
+                    messages.push(format!("💀 {} is unconscious/dead!", combatant.name));
+                    if combatant.character_id.is_some() {
+                        messages.push(format!("   If {} has died, 'died {}' records it", combatant.name, combatant.name));
+                    }
+                    if let Some(fall) = crate::movement::knock_down(combatant) {
+                        messages.push(fall);
+                    }
+                }
+                let hook = Hook::Damage { target: combatant.name.clone(), amount: damage, hp: combatant.current_hp, max_hp: combatant.max_hp };
+                messages.extend(tracker.concentration_check(target_name, damage));
+                
+                for message in messages {
+                    self.add_output(message);
+                }
+                self.run_script_hook(hook);
+            } else {
+                self.add_output(format!("❌ Combatant '{}' not found", target_name));
+            }
+        } else {
+            self.add_output("No combat initialized.".to_string());
+        }
+    }
+
+    fn process_status_command(&mut self, target: &str, action: &str, mut status: crate::combat::StatusEffect) {
         let target_name = if target == "self" {
             if let Some(ref tracker) = self.combat_tracker {
                 if let Some(current) = tracker.combatants.get(tracker.current_turn) {
                     current.name.clone()
                 } else {
-                    self.add_output("❌ No current combatant".to_string());
+                    self.add_output("❌ No current combatant".to_string());
+                    return;
+                }
+            } else {
+                self.add_output("No combat initialized.".to_string());
+                return;
+            }
+        } else {
+            target.to_string()
+        };
+
+        if let Some(ref mut tracker) = self.combat_tracker {
+            if let Err(e) = tracker.resolve_concentration(&mut status) {
+                self.add_output(format!("❌ {}", e));
+                return;
+            }
+            if let Some(combatant) = tracker.get_combatant_mut(&target_name) {
+                let status_name = status.name.clone();
+                match action {
+                    "add" => {
+                        let duration_text = status.duration_text();
+                        let condition = status.condition();
+                        let details = status.detail_lines(&target_name);
+                        combatant.add_status(status);
+                        let fall = if matches!(condition, Some(Condition::Prone | Condition::Unconscious)) {
+                            crate::movement::knock_down(combatant)
+                        } else {
+                            None
+                        };
+                        
+                        self.add_output(format!("✅ Added status '{}' to {}{}", 
+                            status_name, target_name, duration_text));
+                        for line in condition.map(|c| c.rules_text()).unwrap_or_default() {
+                            self.add_output(format!("   • {}", line));
+                        }
+                        for line in details {
+                            self.add_output(format!("   {}", line));
+                        }
+                        if let Some(fall) = fall {
+                            self.add_output(fall);
+                        }
+                    }
+                    "remove" => {
+                        if combatant.remove_status(&status_name) {
+                            self.add_output(format!("✅ Removed status '{}' from {}", 
+                                status_name, target_name));
+                        } else {
+                            self.add_output(format!("❌ Status '{}' not found on {}", 
+                                status_name, target_name));
+                        }
+                    }
+                    _ => {
+                        self.add_output("❌ Invalid action. Use 'add' or 'remove'".to_string());
+                    }
+                }
+            } else {
+                self.add_output(format!("❌ Combatant '{}' not found", target_name));
+            }
+        } else {
+            self.add_output("No combat initialized.".to_string());
+        }
+    }
+
+    fn process_search_command(&mut self, command: String) {
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        if parts.is_empty() {
+            return;
+        }
+
+        let cmd = parts[0].to_lowercase();
+        
+        match cmd.as_str() {
+            "help" | "h" => {
+                self.add_output("Search Commands:".to_string());
+                self.add_output("  search <query> - Search all categories".to_string());
+                self.add_output("  search <category> <query> - Search specific category".to_string());
+                self.add_output("  categories - List available categories".to_string());
+                self.add_output("  deity [name|domain|symbol] - Search the local deity reference".to_string());
+                self.add_output("  wildshape list <druid level> [moon] - Beasts a druid can turn into".to_string());
+                self.add_output("  polymorph [true] <level> - Legal Polymorph / True Polymorph forms for a target".to_string());
+                self.add_output("  cache stats - Pages cached for offline lookup and the disk space they use".to_string());
+                self.add_output("  cache clear - Remove every cached page (asks first)".to_string());
+                self.add_output("  cache refresh <name> - Fetch a cached page again from the wiki".to_string());
+                self.add_output("  crawl - Index every spell, monster and item on the wiki's listing pages".to_string());
+                self.add_output("  list <category> [level] [class] [school] - Browse the index, e.g. list spells 3 wizard".to_string());
+                self.add_output("  back - Return to tools menu".to_string());
+                self.add_output("".to_string());
+                self.add_output("Categories: spells, classes, equipment, monsters, races".to_string());
+                self.add_output("Example: search fireball".to_string());
+                self.add_output("Example: search spell magic missile".to_string());
+            }
+            "search" => {
+                if let Some(_query) = parts.get(1) {
+                    let full_query = parts[1..].join(" ");
+                    self.handle_search_query(&full_query);
+                } else {
+                    self.add_output("Usage: search <query> or search <category> <query>".to_string());
+                }
+            }
+            "deity" | "deities" | "gods" => self.show_deities(&parts[1..].join(" ")),
+            "wildshape" | "polymorph" => self.show_shape_forms(&cmd, &parts[1..]),
+            "cache" if parts.get(1).is_some_and(|arg| arg.eq_ignore_ascii_case("refresh")) => {
+                if parts.len() < 3 {
+                    self.add_output("Usage: cache refresh <name>".to_string());
+                } else {
+                    self.refresh_cached_page(&parts[2..].join(" "));
+                }
+            }
+            "cache" if parts.get(1).is_some_and(|arg| arg.eq_ignore_ascii_case("clear")) => {
+                self.add_output(format!("{} (y/n)", crate::cache::clear_prompt()));
+                self.pending_input = Some(PendingInput::ConfirmCacheClear);
+            }
+            "cache" => {
+                for line in crate::cache::cache_command(&parts[1..]) {
+                    self.add_output(line);
+                }
+            }
+            "crawl" => {
+                if self.crawl_rx.is_some() {
+                    self.add_output("Already crawling; the index is reported when it's done".to_string());
+                } else if crate::srd::is_offline() {
+                    self.add_output("❌ Offline: crawling needs the wiki".to_string());
+                } else {
+                    self.add_output("🗂️ Crawling the wiki's listing pages in the background...".to_string());
+                    self.crawl_rx = Some(crate::search::spawn_crawl());
+                }
+            }
+            "list" | "browse" => {
+                for line in crate::search::list_command(&parts[1..]) {
+                    self.add_output(line);
+                }
+            }
+            "categories" => {
+                self.add_output("Available Categories:".to_string());
+                self.add_output("  • spells - Magic spells".to_string());
+                self.add_output("  • classes - Character classes".to_string());
+                self.add_output("  • equipment (or items/gear) - Weapons, armor, and gear".to_string());
+                self.add_output("  • monsters (or creatures) - Monsters and NPCs".to_string());
+                self.add_output("  • races - Character races".to_string());
+            }
+            "back" | "exit" | "quit" => {
+                self.add_output("Returning to tools menu...".to_string());
+                self.mode = AppMode::ToolsMenu;
+                self.selected_index = 0;
+                self.input_buffer.clear();
+                self.output_history.clear();
+                self.scroll_offset = 0;
+            }
+            _ => {
+                // Try to interpret the entire command as a search query
+                self.handle_search_query(&command);
+            }
+        }
+    }
+
+    fn process_character_creation_command(&mut self, command: String) {
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        let cmd_string = if parts.is_empty() { 
+            String::new() 
+        } else { 
+            parts[0].to_lowercase() 
+        };
+        let cmd = cmd_string.as_str();
+
+        match cmd {
+            "help" | "h" => {
+                self.add_output("Character Creation Commands:".to_string());
+                self.add_output("  create <name> - Start character creation: roll 4d6, use the standard array or point-buy".to_string());
+                self.add_output("  back - Return to characters menu".to_string());
+            }
+            "create" => {
+                let name = parts[1..].join(" ");
+                if let Err(e) = crate::error_handling::validate_character_name(&name) {
+                    self.add_output(format!("❌ {}. Usage: create <name>", e));
+                } else if crate::file_manager::character_name_taken(&self.characters.read(), &name) {
+                    let suggestion = crate::file_manager::suggest_unique_name(&self.characters.read(), &name);
+                    self.add_output(format!("⚠️ A character named '{}' already exists. Type 'rename [new name]' (default: {}), 'overwrite' or 'cancel'", name, suggestion));
+                    self.pending_input = Some(PendingInput::NameCollision(name));
+                } else {
+                    self.begin_character_creation(name);
+                }
+            }
+            "back" | "exit" => {
+                self.mode = AppMode::CharactersMenu;
+                self.selected_index = 0;
+                self.clear_terminal_state();
+            }
+            _ => {
+                self.add_output(format!("Unknown command '{}'. Type 'help' for commands.", cmd));
+            }
+        }
+    }
+
+    fn process_character_display_command(&mut self, command: String) {
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        let cmd_string = if parts.is_empty() { 
+            String::new() 
+        } else { 
+            parts[0].to_lowercase() 
+        };
+        let cmd = cmd_string.as_str();
+
+        match cmd {
+            "help" | "h" => {
+                self.add_output("Character Display Commands:".to_string());
+                self.add_output("  list - List all characters and sidekicks".to_string());
+                self.add_output("  show <name> - Show specific character details".to_string());
+                self.add_output("  ammo <name> [<kind|weapon> <count>] - Show or set a character's ammunition".to_string());
+                self.add_output("  use <name> <resource> [amount] - Spend rage, ki, sorcery points, channel divinity...".to_string());
+                self.add_output("  check <skill> <name> [dc <number|easy|medium|hard...>] - Roll an ability or skill check".to_string());
+                self.add_output("  resource <name> <resource> <max> <short|long> - Track a resource of your own".to_string());
+                self.add_output("  rest <short|long> [name] - Recover resources for one character or the party".to_string());
+                self.add_output("  levelup <name> - Go up a level: hit points, proficiency, improvements, spell slots and invocations".to_string());
+                self.add_output("  spell <name> <add|remove> <spell> - Edit a character's spell list or spellbook".to_string());
+                self.add_output("  prepare <name> - Choose a cleric, druid or wizard's prepared spells for the day".to_string());
+                self.add_output("  spellcards <name> - Write printable cards for the spells a character knows or has prepared".to_string());
+                self.add_output("  attacks <name> [add Longsword +5, 1d8+3 slashing|remove <attack>] - The attacks combat rolls for a character".to_string());
+                self.add_output("  wealth [name] - Compare coins and gear value with the expected wealth for each character's level".to_string());
+                self.add_output("  invocations [level] | invocation <name> <add|remove> <invocation> - Eldritch invocations".to_string());
+                self.add_output("  deity [name|domain|symbol] - Search the deity reference".to_string());
+                self.add_output("  faith <name> <deity|none> - Set the deity a character worships".to_string());
+                self.add_output("  backgrounds [name] - List backgrounds, or show one in full".to_string());
+                self.add_output("  background <name> <background> - Give a character a background's proficiencies and gear".to_string());
+                self.add_output("  equipment <name> [a|b|gold] - Show the class's starting equipment packages, or take one (or roll starting gold)".to_string());
+                self.add_output("  companion add <owner> <familiar|beast> <name> <hp> <ac> [creature] - Attach a familiar or companion".to_string());
+                self.add_output("  companion attack <owner> <name> <attack> | companion remove <owner> <name>".to_string());
+                self.add_output("  sidekick new <name> <expert|spellcaster|warrior> <hp> <ac> [creature] - Add a sidekick".to_string());
+                self.add_output("  sidekick show <name> - Show a sidekick at the party's level".to_string());
+                self.add_output("  sidekick remove <name> - Dismiss a sidekick".to_string());
+                self.add_output("  back - Return to characters menu".to_string());
+            }
+            "list" => {
+                self.add_output("📋 Available Characters:".to_string());
+                if self.characters.is_empty() {
+                    self.add_output("  No characters found.".to_string());
+                } else {
+                    let character_list: Vec<String> = self.characters.read().iter().enumerate()
+                        .flat_map(|(i, character)| {
+                            std::iter::once(format!("  {}. {} (Level {}, {})", 
+                                i + 1, character.name, 
+                                character.level.unwrap_or(1), 
+                                character.class.as_ref().unwrap_or(&"Unknown".to_string())))
+                                .chain(character.companions.iter().flat_map(|c| c.describe()))
+                        })
+                        .collect();
+                    for line in character_list {
+                        self.add_output(line);
+                    }
+                }
+                if !self.sidekicks.sidekicks.is_empty() {
+                    let level = crate::sidekick::party_level(&self.characters.read());
+                    let lines: Vec<String> = self.sidekicks.sidekicks.iter().map(|s| format!("  🤝 {}", s.summary(level))).collect();
+                    self.add_output(format!("Sidekicks (level {} with the party):", level));
+                    for line in lines {
+                        self.add_output(line);
+                    }
+                }
+            }
+            "sidekick" | "sidekicks" => self.process_sidekick_command(&parts[1..]),
+            "companion" | "companions" | "familiar" => self.process_companion_command(&parts[1..]),
+            "check" => self.skill_check(&parts[1..]),
+            "wealth" => {
+                let characters = self.characters.read().clone();
+                let lines = match parts.get(1) {
+                    None => crate::wealth::wealth_report(&characters),
+                    Some(name) => match characters.iter().find(|c| c.name.eq_ignore_ascii_case(name)) {
+                        Some(character) => crate::wealth::wealth_report(std::slice::from_ref(character)),
+                        None => vec![format!("❌ Character '{}' not found", name)],
+                    },
+                };
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "attacks" => {
+                let lines = match parts.get(1).map(|name| self.find_character_mut(name)) {
+                    None => vec!["Usage: attacks <name> [add Longsword +5, 1d8+3 slashing | remove <attack>]".to_string()],
+                    Some(None) => vec![format!("❌ Character '{}' not found", parts[1])],
+                    Some(Some(mut character)) => {
+                        let owner = character.name.clone();
+                        crate::attacks::attacks_command(&owner, &mut character.attacks, &parts[2..])
+                    }
+                };
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "spellcards" => {
+                let name = parts[1..].join(" ");
+                let character = self.characters.read().iter().find(|c| c.name.eq_ignore_ascii_case(&name)).cloned();
+                let lines = match character {
+                    _ if name.is_empty() => vec!["Usage: spellcards <name>".to_string()],
+                    None => vec![format!("❌ Character '{}' not found", name)],
+                    Some(character) if crate::spell_cards::card_spells(&character).is_empty() => {
+                        vec![format!("{} has no spells on their sheet. Add some with 'spell {} add <spell>'", character.name, character.name)]
+                    }
+                    Some(character) => match crate::spell_cards::write_spell_cards_in(std::path::Path::new(crate::spell_cards::SPELL_CARDS_DIR), &character) {
+                        Ok((path, missing)) => {
+                            let mut lines = vec![format!("🃏 Wrote {}'s spell cards to {}", character.name, path.display())];
+                            if !missing.is_empty() {
+                                lines.push(format!("  ⚠️ Not cached yet, so their cards are blank: {}", missing.join(", ")));
+                                lines.push("  Look them up in Search, or run 'prefetch' in combat, then print again".to_string());
+                            }
+                            lines
+                        }
+                        Err(e) => vec![format!("❌ Could not write the spell cards: {}", e)],
+                    },
+                };
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "use" => {
+                let message = match parts.get(1) {
+                    Some(name) => self.use_character_resource(name, &parts[2..]),
+                    None => "Usage: use <name> <resource> [amount]".to_string(),
+                };
+                self.add_output(message);
+            }
+            "stash" => {
+                if self.supplies.stash.is_empty() {
+                    self.add_output("🎒 The party stash is empty.".to_string());
+                } else {
+                    let lines: Vec<String> = std::iter::once(format!("🎒 Party stash ({} item(s)):", self.supplies.stash.len()))
+                        .chain(self.supplies.stash.iter().map(|item| format!("  {}", item)))
+                        .collect();
+                    for line in lines {
+                        self.add_output(line);
+                    }
+                }
+            }
+            "fallen" => {
+                for line in crate::legacy::Legacy::campaign().fallen_lines() {
+                    self.add_output(line);
+                }
+            }
+            "rest" => self.rest_characters(&parts[1..]),
+            "levelup" => match parts.get(1) {
+                Some(_) => self.level_up(&parts[1..].join(" ")),
+                None => self.add_output("Usage: levelup <name>".to_string()),
+            },
+            "spell" => {
+                let message = match &parts[1..] {
+                    [name, action, spell @ ..] if !spell.is_empty() => {
+                        let spell = spell.join(" ");
+                        match (action.to_lowercase().as_str(), self.find_character_mut(name)) {
+                            (_, None) => format!("❌ Character '{}' not found", name),
+                            ("add", Some(character)) if character.spells.iter().any(|s| s.eq_ignore_ascii_case(&spell)) => {
+                                format!("{} already has {}", character.name, spell)
+                            }
+                            ("add", Some(mut character)) => {
+                                character.spells.push(spell.clone());
+                                format!("✅ Added {} to {}'s spells", spell, character.name)
+                            }
+                            ("remove", Some(mut character)) => {
+                                let before = character.spells.len();
+                                character.spells.retain(|s| !s.eq_ignore_ascii_case(&spell));
+                                character.prepared.retain(|s| !s.eq_ignore_ascii_case(&spell));
+                                if character.spells.len() < before {
+                                    format!("Removed {} from {}'s spells", spell, character.name)
+                                } else {
+                                    format!("❌ {} doesn't have {}", character.name, spell)
+                                }
+                            }
+                            _ => "Usage: spell <name> <add|remove> <spell>".to_string(),
+                        }
+                    }
+                    _ => "Usage: spell <name> <add|remove> <spell>".to_string(),
+                };
+                self.add_output(message);
+            }
+            "prepare" => {
+                let name = parts[1..].join(" ");
+                let preparation = self.characters.read().iter()
+                    .find(|c| c.name.eq_ignore_ascii_case(&name))
+                    .ok_or_else(|| crate::error_handling::AppError::NotFound(format!("Character '{}'", name)))
+                    .and_then(crate::prepared::Preparation::start);
+                match preparation {
+                    _ if name.is_empty() => self.add_output("Usage: prepare <name>".to_string()),
+                    Ok(preparation) => {
+                        for line in preparation.lines() {
+                            self.add_output(line);
+                        }
+                        self.add_output("Type spell names or numbers (comma-separated) to toggle them, 'done' to save, or 'cancel':".to_string());
+                        self.pending_input = Some(PendingInput::PrepareSpells(preparation));
+                    }
+                    Err(e) => self.add_output(format!("❌ {}", e)),
+                }
+            }
+            "invocations" => {
+                let level = parts.get(1).and_then(|l| l.parse::<u32>().ok()).unwrap_or(20);
+                self.add_output(format!("✨ Invocations up to warlock level {}:", level));
+                self.add_output(format!("  {}", crate::pact_magic::available_invocations(level).join(", ")));
+            }
+            "invocation" => {
+                let message = match &parts[1..] {
+                    [name, action, invocation @ ..] if !invocation.is_empty() => {
+                        let invocation = invocation.join(" ");
+                        match (action.to_lowercase().as_str(), self.find_character_mut(name)) {
+                            (_, None) => format!("❌ Character '{}' not found", name),
+                            ("add", Some(mut character)) => match crate::pact_magic::learn_invocation(&mut character, &invocation) {
+                                Ok(learned) => format!("✨ {} learns {}", character.name, learned),
+                                Err(e) => format!("❌ {}", e),
+                            },
+                            ("remove", Some(mut character)) => {
+                                let before = character.invocations.len();
+                                character.invocations.retain(|i| !i.eq_ignore_ascii_case(&invocation));
+                                if character.invocations.len() < before {
+                                    format!("{} forgets {}", character.name, invocation)
+                                } else {
+                                    format!("❌ {} doesn't know {}", character.name, invocation)
+                                }
+                            }
+                            _ => "Usage: invocation <name> <add|remove> <invocation>".to_string(),
+                        }
+                    }
+                    _ => "Usage: invocation <name> <add|remove> <invocation>".to_string(),
+                };
+                self.add_output(message);
+            }
+            "resource" => {
+                let message = match &parts[1..] {
+                    [name, resource @ .., max, recharge] if !resource.is_empty() => {
+                        match (max.parse::<u32>(), crate::resources::Recharge::parse(recharge), self.find_character_mut(name)) {
+                            (Ok(max), Some(recharge), Some(mut character)) => {
+                                let resource = resource.join(" ");
+                                crate::resources::add_custom_resource(&mut character, &resource, max, recharge);
+                                format!("✅ {} now tracks {} ({} per {})", character.name, resource, max, recharge)
+                            }
+                            (Err(_), _, _) => "❌ The maximum must be a whole number".to_string(),
+                            (_, None, _) => "❌ Recharge must be 'short' or 'long'".to_string(),
+                            (_, _, None) => format!("❌ Character '{}' not found", name),
+                        }
+                    }
+                    _ => "Usage: resource <name> <resource> <max> <short|long>  e.g. resource Lia Luck 3 long".to_string(),
+                };
+                self.add_output(message);
+            }
+            "deity" | "deities" | "gods" => self.show_deities(&parts[1..].join(" ")),
+            "backgrounds" => {
+                let query = parts[1..].join(" ");
+                let lines = match self.backgrounds.get(&query) {
+                    _ if query.is_empty() => self.backgrounds.backgrounds.iter().map(|b| format!("  {}", b.summary())).collect(),
+                    Some(background) => background.describe(),
+                    None => vec![format!("❌ Background '{}' not found. Add your own to {}", query, crate::backgrounds::BACKGROUNDS_FILE)],
+                };
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "background" => {
+                let lines = match &parts[1..] {
+                    [name, background @ ..] if !background.is_empty() => {
+                        let background = background.join(" ");
+                        match (self.backgrounds.get(&background), self.find_character_mut(name)) {
+                            (_, None) => vec![format!("❌ Character '{}' not found", name)],
+                            (_, Some(character)) if character.background.is_some() => vec![format!(
+                                "❌ {} already has the {} background", character.name, character.background.as_deref().unwrap_or_default())],
+                            (Some(background), Some(mut character)) => background.apply(&mut character),
+                            (None, Some(_)) => vec![format!("❌ Background '{}' not found. Try 'backgrounds', or add it to {}", background, crate::backgrounds::BACKGROUNDS_FILE)],
+                        }
+                    }
+                    _ => vec!["Usage: background <name> <background>".to_string()],
+                };
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "equipment" | "equip" => {
+                let lines = match &parts[1..] {
+                    [name, choice @ ..] => match self.find_character_mut(name) {
+                        None => vec![format!("❌ Character '{}' not found", name)],
+                        Some(mut character) => match character.class.as_deref().and_then(crate::starting_equipment::class_kit) {
+                            None => vec![format!("❌ {} needs a class with standard starting equipment first", character.name)],
+                            Some(kit) if choice.is_empty() => {
+                                let mut lines = kit.describe();
+                                lines.push(format!("Type 'equipment {} <letter|gold>' to take one", character.name));
+                                lines
+                            }
+                            Some(kit) => kit.equip(&mut character, &choice.join(" "), &mut rand::rng())
+                                .unwrap_or_else(|e| vec![format!("❌ {}", e)]),
+                        },
+                    },
+                    _ => vec!["Usage: equipment <name> [a|b|gold]".to_string()],
+                };
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "faith" => {
+                let message = match &parts[1..] {
+                    [name, deity @ ..] if !deity.is_empty() => {
+                        let deity = deity.join(" ");
+                        let found = self.pantheon.get(&deity).map(|d| d.name.clone());
+                        match (found, self.find_character_mut(name)) {
+                            (_, None) => format!("❌ Character '{}' not found", name),
+                            (_, Some(mut character)) if deity.eq_ignore_ascii_case("none") => {
+                                character.faith = None;
+                                format!("{} no longer follows a deity", character.name)
+                            }
+                            (Some(deity), Some(mut character)) => {
+                                character.faith = Some(deity.clone());
+                                format!("🛐 {} now worships {}", character.name, deity)
+                            }
+                            (None, Some(_)) => format!("❌ Deity '{}' not found. Try 'deity' to search, or add it to {}", deity, crate::deities::DEITIES_FILE),
+                        }
+                    }
+                    _ => "Usage: faith <name> <deity|none>".to_string(),
+                };
+                self.add_output(message);
+            }
+            "ammo" => {
+                let message = match &parts[1..] {
+                    [name] => self.characters.read().iter()
+                        .find(|c| c.name.eq_ignore_ascii_case(name))
+                        .map(crate::ammo::describe_ammo)
+                        .unwrap_or_else(|| format!("❌ Character '{}' not found", name)),
+                    [name, kind @ .., count] if !kind.is_empty() => match (count.parse::<u32>(), self.find_character_mut(name)) {
+                        (Ok(count), Some(mut character)) => {
+                            crate::ammo::set_ammo(&mut character, &kind.join(" "), count);
+                            crate::ammo::describe_ammo(&character)
+                        }
+                        (Err(_), _) => "❌ Count must be a whole number".to_string(),
+                        (_, None) => format!("❌ Character '{}' not found", name),
+                    },
+                    _ => "Usage: ammo <name> [<kind|weapon> <count>]  e.g. ammo Lia arrows 20".to_string(),
+                };
+                self.add_output(message);
+            }
+            "show" => {
+                if parts.len() >= 2 {
+                    let char_name = parts[1..].join(" ");
+                    let character_data = self.characters.read().iter()
+                        .find(|c| c.name.eq_ignore_ascii_case(&char_name))
+                        .cloned();
+                    
+                    if let Some(character) = character_data {
+                        self.display_character_details(&character);
+                    } else {
+                        self.add_output(format!("❌ Character '{}' not found", char_name));
+                    }
+                } else {
+                    self.add_output("Usage: show <character_name>".to_string());
+                }
+            }
+            "back" | "exit" => {
+                self.mode = AppMode::CharactersMenu;
+                self.selected_index = 0;
+                self.clear_terminal_state();
+            }
+            _ => {
+                self.add_output(format!("Unknown command '{}'. Type 'help' for commands.", cmd));
+            }
+        }
+    }
+
+    fn process_character_deletion_command(&mut self, command: String) {
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        let cmd_string = if parts.is_empty() { 
+            String::new() 
+        } else { 
+            parts[0].to_lowercase() 
+        };
+        let cmd = cmd_string.as_str();
+
+        match cmd {
+            "help" | "h" => {
+                self.add_output("Character Deletion Commands:".to_string());
+                self.add_output("  list - List all characters".to_string());
+                self.add_output("  delete <name> - Delete specific character (asks for confirmation)".to_string());
+                self.add_output("  back - Return to characters menu".to_string());
+            }
+            "list" => {
+                self.add_output("📋 Characters available for deletion:".to_string());
+                if self.characters.is_empty() {
+                    self.add_output("  No characters found.".to_string());
+                } else {
+                    let character_list: Vec<String> = self.characters.read().iter().enumerate()
+                        .map(|(i, character)| format!("  {}. {}", i + 1, character.name))
+                        .collect();
+                    for line in character_list {
+                        self.add_output(line);
+                    }
+                }
+            }
+            "delete" => {
+                if parts.len() >= 2 {
+                    let char_name = parts[1..].join(" ");
+                    let found = self.characters.find(&char_name).map(|c| c.name.clone());
+                    if let Some(name) = found {
+                        self.add_output(format!("⚠️  Really delete '{}'? Type 'y' or the name to confirm, anything else cancels.", name));
+                        self.pending_input = Some(PendingInput::ConfirmDelete(name));
+                    } else {
+                        self.add_output(format!("❌ Character '{}' not found", char_name));
+                    }
+                } else {
+                    self.add_output("Usage: delete <character_name>".to_string());
+                }
+            }
+            "back" | "exit" => {
+                self.mode = AppMode::CharactersMenu;
+                self.selected_index = 0;
+                self.clear_terminal_state();
+            }
+            _ => {
+                self.add_output(format!("Unknown command '{}'. Type 'help' for commands.", cmd));
+            }
+        }
+    }
+
+    fn process_character_undelete_command(&mut self, command: String) {
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        let cmd_string = if parts.is_empty() { 
+            String::new() 
+        } else { 
+            parts[0].to_lowercase() 
+        };
+        let cmd = cmd_string.as_str();
+
+        match cmd {
+            "help" | "h" => {
+                self.add_output("Undelete Commands:".to_string());
+                self.add_output("  list - List trashed characters".to_string());
+                self.add_output("  undelete <name> - Restore the most recently trashed character with that name".to_string());
+                self.add_output("  back - Return to characters menu".to_string());
+            }
+            "list" => {
+                let trashed = crate::file_manager::list_trashed_characters();
+                self.add_output("♻️  Trashed characters:".to_string());
+                if trashed.is_empty() {
+                    self.add_output("  Trash is empty.".to_string());
+                } else {
+                    for (i, entry) in trashed.iter().enumerate() {
+                        self.add_output(format!("  {}. {}", i + 1, entry.name));
+                    }
+                }
+            }
+            "undelete" | "restore" => {
+                if parts.len() >= 2 {
+                    let char_name = parts[1..].join(" ");
+                    let entry = crate::file_manager::list_trashed_characters()
+                        .into_iter()
+                        .find(|e| e.name.eq_ignore_ascii_case(&char_name));
+                    match entry {
+                        Some(entry) => match crate::file_manager::restore_character(&entry) {
+                            Ok(character) => {
+                                self.add_output(format!("♻️  Restored character '{}'", character.name));
+                                self.characters.insert(character);
+                            }
+                            Err(e) => self.add_output(format!("❌ {}", e)),
+                        },
+                        None => self.add_output(format!("❌ '{}' is not in the trash", char_name)),
+                    }
+                } else {
+                    self.add_output("Usage: undelete <character_name>".to_string());
+                }
+            }
+            "back" | "exit" => {
+                self.mode = AppMode::CharactersMenu;
+                self.selected_index = 0;
+                self.clear_terminal_state();
+            }
+            _ => {
+                self.add_output(format!("Unknown command '{}'. Type 'help' for commands.", cmd));
+            }
+        }
+    }
+
+    fn process_initiative_command(&mut self, command: String) {
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        let cmd_string = if parts.is_empty() { 
+            String::new() 
+        } else { 
+            parts[0].to_lowercase() 
+        };
+        let cmd = cmd_string.as_str();
+
+        match cmd {
+            "help" | "h" => {
+                self.add_output("Initiative Tracker Commands:".to_string());
+                self.add_output("  roll <name> [modifier] - Roll initiative and add them to the order (saved characters add their Dex)".to_string());
+                self.add_output("  add <name> <initiative> [player|npc] - Add someone who rolled at the table".to_string());
+                self.add_output("  list - Show the initiative order and whose turn it is".to_string());
+                self.add_output("  next - Move to the next turn; the first starts round 1".to_string());
+                self.add_output("  remove <name> - Take someone out of the order".to_string());
+                self.add_output("  clear - Clear all initiative rolls".to_string());
+                self.add_output("  back - Return to tools menu".to_string());
+            }
+            "roll" => {
+                let (name, modifier) = match parts[1..].split_last() {
+                    Some((last, rest)) if !rest.is_empty() && last.parse::<i32>().is_ok() => (rest.join(" "), last.parse::<i32>().ok()),
+                    _ => (parts[1..].join(" "), None),
+                };
+                if name.is_empty() {
+                    self.add_output("Usage: roll <name> [modifier]".to_string());
+                    return;
+                }
+                // A saved character rolls with their Dex modifier and goes in as a player
+                let character = self.characters.read().iter()
+                    .find(|c| c.name.eq_ignore_ascii_case(&name))
+                    .map(|c| (c.name.clone(), c.get_ability_modifier(crate::character::AbilityScore::Dexterity) as i32));
+                let is_player = character.is_some();
+                let (name, modifier) = match character {
+                    Some((name, dex)) => (name, modifier.unwrap_or(dex)),
+                    None => (name, modifier.unwrap_or(0)),
+                };
+                match crate::dice::roll_dice_with_crits("1d20") {
+                    Ok(roll) => {
+                        let roll = roll.plus(modifier);
+                        self.add_output(format!("🎲 {} rolled initiative: {}", name, roll));
+                        if let Some(message) = &roll.crit {
+                            self.add_output(message.clone());
+                        }
+                        self.initiative.add_entry(name, roll.total, is_player);
+                        self.show_initiative_order();
+                    }
+                    Err(e) => {
+                        self.add_output(format!("❌ Error rolling initiative: {}", e));
+                    }
+                }
+            }
+            "add" => {
+                let kind = parts.last().map(|p| p.to_lowercase()).filter(|p| p == "player" || p == "npc");
+                let words = &parts[1..parts.len() - kind.is_some() as usize];
+                match words.split_last() {
+                    Some((initiative, name)) if !name.is_empty() && initiative.parse::<i32>().is_ok() => {
+                        let name = name.join(" ");
+                        let is_player = match kind.as_deref() {
+                            Some(kind) => kind == "player",
+                            None => self.characters.read().iter().any(|c| c.name.eq_ignore_ascii_case(&name)),
+                        };
+                        self.initiative.add_entry(name, initiative.parse().unwrap_or_default(), is_player);
+                        self.show_initiative_order();
+                    }
+                    _ => {
+                        self.add_output("Usage: add <name> <initiative> [player|npc]".to_string());
+                        self.add_output("Example: add Gandalf 18 player".to_string());
+                    }
+                }
+            }
+            "list" | "show" => self.show_initiative_order(),
+            "next" => {
+                let Some(entry) = self.initiative.advance().cloned() else {
+                    self.add_output("Nobody has rolled yet. 'roll <name>' adds someone".to_string());
+                    return;
+                };
+                self.add_output(format!("🎯 Round {}: {}'s turn (initiative {})", self.initiative.round(), entry.name, entry.initiative));
+                self.show_initiative_order();
+            }
+            "remove" => {
+                let name = parts[1..].join(" ");
+                if name.is_empty() {
+                    self.add_output("Usage: remove <name>".to_string());
+                } else if self.initiative.remove_entry(&name) {
+                    self.add_output(format!("🗑️ Removed {} from the order", name));
+                    self.show_initiative_order();
+                } else {
+                    self.add_output(format!("❌ {} isn't in the initiative order", name));
+                }
+            }
+            "clear" => {
+                self.initiative = crate::initiative::InitiativeTracker::new();
+                self.add_output("🧹 Cleared all initiative rolls".to_string());
+            }
+            "back" | "exit" => {
+                self.mode = AppMode::ToolsMenu;
+                self.selected_index = 0;
+                self.clear_terminal_state();
+            }
+            _ => {
+                self.add_output(format!("Unknown command '{}'. Type 'help' for commands.", cmd));
+            }
+        }
+    }
+
+    fn show_initiative_order(&mut self) {
+        for line in self.initiative.order_lines() {
+            self.add_output(line);
+        }
+    }
+
+    fn process_npc_generator_command(&mut self, command: String) {
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        let cmd_string = if parts.is_empty() { 
+            String::new() 
+        } else { 
+            parts[0].to_lowercase() 
+        };
+        let cmd = cmd_string.as_str();
+
+        match cmd {
+            "help" | "h" => {
+                self.add_output("NPC Generator Commands:".to_string());
+                self.add_output("  random - Generate completely random NPC".to_string());
+                self.add_output("  custom <race> <class> - Generate NPC with specific race/class".to_string());
+                self.add_output(format!("  preset <role> [\"name\"] - Stock NPC with stats, gear and attacks ({})", crate::npc_presets::preset_roles().join(", ")));
+                self.add_output("  name [culture] [n] - Generate names from a culture's name list".to_string());
+                self.add_output("  cultures - List name lists (add your own in campaign/names)".to_string());
+                self.add_output("  races - List available races".to_string());
+                self.add_output("  classes - List available classes".to_string());
+                self.add_output("  voice [save <npc> | <npc>] - Suggest a voice, keep it on an NPC's sheet, or recall it".to_string());
+                self.add_output("  temperament [save <npc> | <npc>] - Alignment, temperament and behavior hints, kept on an NPC's sheet".to_string());
+                self.add_output("  rumors [n] [village|town|city] [theme] [npcs] - Tavern gossip".to_string());
+                self.add_output(format!("    themes: {}", crate::rumors::THEMES.join(", ")));
+                self.add_output("  back - Return to tools menu".to_string());
+            }
+            "random" => {
+                self.generate_random_npc();
+            }
+            "preset" | "npc" => {
+                // Both `preset guard "Gate Sergeant"` and `npc preset guard "Gate Sergeant"`
+                let skip = if cmd == "npc" { 2 } else { 1 };
+                let args = command.splitn(skip + 1, char::is_whitespace).nth(skip).unwrap_or_default();
+                let is_preset = cmd == "preset" || parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("preset"));
+                if !is_preset || args.trim().is_empty() {
+                    self.add_output(format!("Usage: preset <{}> [\"name\"]", crate::npc_presets::preset_roles().join("|")));
+                    return;
+                }
+                match crate::npc_presets::parse_preset_args(args) {
+                    Ok((preset, name)) => {
+                        for line in preset.describe(&name) {
+                            self.add_output(line);
+                        }
+                    }
+                    Err(e) => self.add_output(format!("❌ {}", e)),
+                }
+            }
+            "rumors" | "rumours" | "gossip" => {
+                let result = crate::rumors::RumorOptions::parse(&parts[1..]).and_then(|options| {
+                    let npcs = if options.use_npcs { crate::file_manager::saved_npc_names() } else { Vec::new() };
+                    if options.use_npcs && npcs.is_empty() {
+                        self.add_output("ℹ️  No saved NPCs found, using random subjects.".to_string());
+                    }
+                    crate::rumors::generate_rumors(&self.tables, &options, &npcs, &mut rand::rng())
+                });
+                match result {
+                    Ok(rumors) => {
+                        self.add_output("🍺 Overheard in the tavern:".to_string());
+                        for rumor in rumors {
+                            self.add_output(format!("  • {}", rumor));
+                        }
+                    }
+                    Err(e) => self.add_output(format!("❌ {}", e)),
+                }
+            }
+            "custom" => {
+                if parts.len() >= 3 {
+                    let race = parts[1];
+                    let class = parts[2];
+                    self.generate_custom_npc(race, class);
+                } else {
+                    self.add_output("Usage: custom <race> <class>".to_string());
+                    self.add_output("Example: custom elf wizard".to_string());
+                }
+            }
+            "name" | "names" => {
+                let count = parts[1..].iter().find_map(|p| p.parse::<usize>().ok()).unwrap_or(1).clamp(1, 20);
+                let culture: Vec<&str> = parts[1..].iter().copied().filter(|p| p.parse::<usize>().is_err()).collect();
+                let culture = (!culture.is_empty()).then(|| culture.join(" "));
+                if let Some(ref culture) = culture
+                    && !self.names.has_culture(culture)
+                {
+                    self.add_output(format!("ℹ️  No '{}' name list, using {}. Known: {}", culture, crate::names::DEFAULT_CULTURE, self.names.cultures().join(", ")));
+                }
+                let mut rng = rand::rng();
+                let names: crate::error_handling::Result<Vec<String>> = (0..count)
+                    .map(|_| self.names.generate(culture.as_deref(), &mut rng))
+                    .collect();
+                match names {
+                    Ok(names) => {
+                        for name in names {
+                            self.add_output(format!("  📛 {}", name));
+                        }
+                    }
+                    Err(e) => self.add_output(format!("❌ {}", e)),
+                }
+            }
+            "cultures" => {
+                self.add_output(format!("📛 Name lists: {}", self.names.cultures().join(", ")));
+            }
+            "races" => {
+                self.add_output("Available Races:".to_string());
+                self.add_output("human, elf, dwarf, halfling, dragonborn, gnome, half-elf, half-orc, tiefling".to_string());
+            }
+            "classes" => {
+                self.add_output("Available Classes:".to_string());
+                self.add_output("fighter, wizard, cleric, rogue, ranger, paladin, barbarian, bard, druid, monk, sorcerer, warlock".to_string());
+            }
+            "back" | "exit" => {
+                self.mode = AppMode::ToolsMenu;
+                self.selected_index = 0;
+                self.clear_terminal_state();
+            }
+            _ => {
+                self.add_output(format!("Unknown command '{}'. Type 'help' for commands.", cmd));
+            }
+        }
+    }
+
+    fn process_dice_command(&mut self, command: String) {
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        let cmd_string = if parts.is_empty() { 
+            String::new() 
+        } else { 
+            parts[0].to_lowercase() 
+        };
+        let cmd = cmd_string.as_str();
+
+        match cmd {
+            "help" | "h" => {
+                self.add_output("🎲 Dice Roller Commands:".to_string());
+                self.add_output("  roll <dice> - Roll dice (e.g., 1d20, 2d6+3, 4d6kh3, 3d6!, 2d6r1, 1d8+1d6+3, (1d8+4)*2, floor(8d6/2))".to_string());
+                self.add_output("  roll d100 | roll 2d10 as percentile - Percentile dice (00 and 0 is 100)".to_string());
+                self.add_output("  roll <dice> on <table> - Read the result off a table (e.g., roll d100 on reaction-table)".to_string());
+                self.add_output("  advantage [+mod] - Roll with advantage (2d20, keep higher; e.g., advantage +5)".to_string());
+                self.add_output("  disadvantage [+mod] - Roll with disadvantage (2d20, keep lower)".to_string());
+                self.add_output("  roll 1d20+5 adv | roll 1d20 dis - Advantage or disadvantage on any d20 roll".to_string());
+                self.add_output("  stats - Roll 4d6 drop lowest for ability scores".to_string());
+                self.add_output("  table list - List rollable tables".to_string());
+                self.add_output("  table roll <name> [times] - Roll on a table".to_string());
+                self.add_output("  back - Return to tools menu".to_string());
+            }
+            "roll" => {
+                if parts.len() >= 2 {
+                    // roll <dice> [as percentile] [on <table>]
+                    let args: Vec<String> = parts[1..].iter().map(|p| p.to_lowercase()).collect();
+                    let on = args.iter().position(|a| a == "on");
+                    let table = on.map(|i| parts[i + 2..].join(" "));
+                    let mut dice: Vec<&str> = args[..on.unwrap_or(args.len())].iter().map(|a| a.as_str()).collect();
+                    let as_percentile = dice.ends_with(&["as", "percentile"]);
+                    if as_percentile {
+                        dice.truncate(dice.len() - 2);
+                    }
+                    let dice_expr = dice.concat();
+                    self.roll_on_table(&dice_expr, as_percentile, table.as_deref());
+                } else {
+                    self.add_output("Usage: roll <dice_expression>".to_string());
+                    self.add_output("Examples: roll 1d20, roll 2d6+3, roll 4d8".to_string());
+                }
+            }
+            "advantage" | "disadvantage" | "adv" | "dis" => {
+                // An optional modifier: 'advantage +5'
+                let modifier = match parts[1..].concat() {
+                    m if m.is_empty() || m.starts_with(['+', '-']) => m,
+                    m => format!("+{}", m),
+                };
+                let edge = crate::combat::Edge::parse(cmd);
+                let keep = if edge == Some(crate::combat::Edge::Advantage) { "higher" } else { "lower" };
+                self.add_output(format!("🎲 Rolling with {} (2d20, keep {}):", edge.map_or("", |e| e.name()), keep));
+                self.roll_dice_with_display(&format!("1d20{} {}", modifier, cmd));
+            }
+            "stats" => {
+                self.add_output("🎲 Rolling ability scores (4d6, drop lowest):".to_string());
+                self.add_output("".to_string());
+                for ability in &["Strength", "Dexterity", "Constitution", "Intelligence", "Wisdom", "Charisma"] {
+                    self.roll_ability_score(ability);
+                }
+            }
+            "table" => self.process_table_command(&parts[1..]),
+            "back" | "exit" => {
+                self.mode = AppMode::ToolsMenu;
+                self.selected_index = 0;
+                self.clear_terminal_state();
+            }
+            _ => {
+                // Try to interpret as dice roll
+                self.roll_dice_with_display(&command);
+            }
+        }
+    }
+
+    fn process_skill_challenge_command(&mut self, command: String) {
+        use crate::skill_challenge::{ChallengeStatus, SkillChallenge, DEFAULT_CHALLENGE_DC};
+
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        let cmd = parts.first().map(|s| s.to_lowercase()).unwrap_or_default();
+
+        match cmd.as_str() {
+            "help" | "h" => {
+                self.add_output("📜 Skill Challenge Commands:".to_string());
+                self.add_output("  start <successes> <failures> [dc] [name] - Begin a challenge (e.g. start 5 3 13 Heist)".to_string());
+                self.add_output("  check <character> <skill> <roll> [dc] - Record a check (uses the suggested DC if none given; dc may be easy, hard...)".to_string());
+                self.add_output("  dc [skill] - Suggested DC for the next check".to_string());
+                self.add_output("  status - Show progress".to_string());
+                self.add_output("  summary|end - Summarize the outcome and close the challenge".to_string());
+                self.add_output("  back - Return to tools menu".to_string());
+            }
+            "start" | "new" => {
+                let successes = parts.get(1).and_then(|s| s.parse::<usize>().ok());
+                let failures = parts.get(2).and_then(|s| s.parse::<usize>().ok());
+                let (Some(successes), Some(failures)) = (successes, failures) else {
+                    self.add_output("Usage: start <successes> <failures> [dc] [name]".to_string());
+                    return;
+                };
+                let (dc, name_start) = match parts.get(3).and_then(|s| s.parse::<i32>().ok()) {
+                    Some(dc) => (dc, 4),
+                    None => (DEFAULT_CHALLENGE_DC, 3),
+                };
+                let name = if parts.len() > name_start { parts[name_start..].join(" ") } else { "Skill Challenge".to_string() };
+                match SkillChallenge::new(&name, successes, failures, dc) {
+                    Ok(challenge) => {
+                        self.add_output(format!("📜 {}: {} successes before {} failures, base DC {}", name, successes, failures, dc));
+                        self.current_state = format!("Challenge: {}", name);
+                        self.skill_challenge = Some(challenge);
+                    }
+                    Err(e) => self.add_output(format!("❌ {}", e)),
+                }
+            }
+            "check" => {
+                if self.skill_challenge.is_none() {
+                    self.add_output("No challenge running. Use 'start' first.".to_string());
+                    return;
+                }
+                let (Some(character), Some(skill), Some(roll)) = (parts.get(1), parts.get(2), parts.get(3).and_then(|s| s.parse::<i32>().ok())) else {
+                    self.add_output("Usage: check <character> <skill> <roll> [dc]".to_string());
+                    return;
+                };
+                let dc = match parts.get(4..).filter(|dc| !dc.is_empty()).map(|dc| self.dc_bands.resolve(&dc.join(" "))) {
+                    Some(Ok(dc)) => Some(dc),
+                    Some(Err(e)) => {
+                        self.add_output(format!("❌ {}", e));
+                        return;
+                    }
+                    None => None,
+                };
+                let Some(ref mut challenge) = self.skill_challenge else {
                     return;
+                };
+                let mut messages = match challenge.record(character, skill, roll, dc) {
+                    Ok(attempt) => vec![format!("{} {} tries {}: {} vs DC {} - {}",
+                        if attempt.success { "✅" } else { "❌" }, attempt.character, attempt.skill, attempt.roll, attempt.dc,
+                        if attempt.success { "success" } else { "failure" })],
+                    Err(e) => vec![format!("❌ {}", e)],
+                };
+                messages.push(format!("   {}", challenge.progress()));
+                match challenge.status() {
+                    ChallengeStatus::InProgress => messages.push(format!("   Next suggested DC: {}", challenge.suggested_dc(None))),
+                    _ => messages.extend(challenge.summary()),
+                }
+                for message in messages {
+                    self.add_output(message);
+                }
+            }
+            "dc" => {
+                let message = match self.skill_challenge {
+                    Some(ref challenge) => format!("🎯 Suggested DC{}: {}",
+                        parts.get(1).map(|s| format!(" for {}", s)).unwrap_or_default(), challenge.suggested_dc(parts.get(1).copied())),
+                    None => "No challenge running. Use 'start' first.".to_string(),
+                };
+                self.add_output(message);
+            }
+            "status" => {
+                let message = match self.skill_challenge {
+                    Some(ref challenge) => format!("📜 {}: {}", challenge.name, challenge.progress()),
+                    None => "No challenge running. Use 'start' first.".to_string(),
+                };
+                self.add_output(message);
+            }
+            "summary" | "end" => {
+                match self.skill_challenge.take() {
+                    Some(challenge) => {
+                        for line in challenge.summary() {
+                            self.add_output(line);
+                        }
+                        self.current_state = "Skill Challenge Ready".to_string();
+                    }
+                    None => self.add_output("No challenge running. Use 'start' first.".to_string()),
+                }
+            }
+            "back" | "exit" => {
+                self.mode = AppMode::ToolsMenu;
+                self.selected_index = 0;
+                self.clear_terminal_state();
+            }
+            "" => {}
+            _ => self.add_output(format!("Unknown command '{}'. Type 'help' for available commands.", cmd)),
+        }
+    }
+
+    fn process_light_command(&mut self, cmd: &str, args: &[&str]) {
+        let changed = match (cmd, args) {
+            ("light", [kind @ .., holder]) if !kind.is_empty() => {
+                let message = match self.lights.light(&kind.join(" "), holder) {
+                    Ok(source) => format!("Lit: {}", source.describe()),
+                    Err(e) => format!("❌ {}", e),
+                };
+                self.add_output(message);
+                true
+            }
+            ("light", _) => {
+                self.add_output(format!("Usage: light <kind> <holder>  (kinds: {})", crate::light::light_kinds().join(", ")));
+                false
+            }
+            ("douse", target) if !target.is_empty() => {
+                let doused = self.lights.douse(&target.join(" "));
+                if doused.is_empty() {
+                    self.add_output(format!("❌ No light matching '{}'", target.join(" ")));
+                }
+                for source in &doused {
+                    self.add_output(format!("💨 {}'s {} is put out", source.holder, source.kind));
+                }
+                !doused.is_empty()
+            }
+            ("douse", _) => {
+                self.add_output("Usage: douse <holder|kind>".to_string());
+                false
+            }
+            ("vision", [name, feet]) => {
+                let message = match self.find_character_mut(name).map(|mut c| c.set_stat("darkvision", feet).map(|_| c.name.clone())) {
+                    Some(Ok(name)) => format!("👁️ {} has darkvision {} ft", name, feet.trim_end_matches("ft")),
+                    Some(Err(e)) => format!("❌ {}", e),
+                    None => format!("❌ Character '{}' not found", name),
+                };
+                self.add_output(message);
+                false
+            }
+            ("vision", _) => {
+                let lines: Vec<String> = self.characters.read().iter()
+                    .map(|c| match crate::light::darkvision_range(c) {
+                        0 => format!("  {} - no darkvision", c.name),
+                        range => format!("  {} - darkvision {} ft", c.name, range),
+                    })
+                    .collect();
+                self.add_output("👁️ Vision (set with 'vision <name> <feet>'):".to_string());
+                for line in lines {
+                    self.add_output(line);
+                }
+                false
+            }
+            _ => {
+                let lines: Vec<String> = self.lights.sources.iter().map(|s| format!("  {}", s.describe())).collect();
+                if lines.is_empty() {
+                    self.add_output("🌑 No lights burning".to_string());
+                }
+                for line in lines {
+                    self.add_output(line);
+                }
+                false
+            }
+        };
+        if changed {
+            self.save_lights();
+        }
+    }
+
+    /// Let time pass for every burning light and report any that gutter or go out
+    fn burn_lights(&mut self, seconds: u32) {
+        if self.lights.sources.is_empty() {
+            return;
+        }
+        for notice in self.lights.pass_seconds(seconds) {
+            self.add_output(notice);
+        }
+        self.save_lights();
+    }
+
+    /// Finish the death flow: the character leaves the party and the fight, their
+    /// sheet goes to the archive and the death is noted in the session log
+    fn record_death(&mut self, name: &str, cause: &str, to_stash: bool) {
+        let Some(character) = self.characters.remove(name) else {
+            self.add_output(format!("❌ Character '{}' not found", name));
+            return;
+        };
+        if let Some(tracker) = self.combat_tracker.as_mut() {
+            tracker.remove_combatant(&character.name);
+        }
+        let stash = to_stash.then_some(&mut self.supplies.stash);
+        let result = crate::legacy::Legacy::campaign().lay_to_rest(character, cause, self.in_game_date.as_deref(), stash);
+        match result {
+            Ok(lines) => {
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            Err(e) => self.add_output(format!("⚠️ Could not archive {}: {}", name, e)),
+        }
+        if to_stash {
+            self.save_supplies();
+        }
+    }
+
+    fn save_supplies(&mut self) {
+        if let Err(e) = self.supplies.save(std::path::Path::new(crate::supplies::SUPPLIES_FILE)) {
+            self.add_output(format!("⚠️ Could not save supplies: {}", e));
+        }
+    }
+
+    fn save_lights(&mut self) {
+        if let Err(e) = self.lights.save(std::path::Path::new(crate::light::LIGHTS_FILE)) {
+            self.add_output(format!("⚠️ Could not save light sources: {}", e));
+        }
+    }
+
+    /// Cast against the saved character so used-up components stay gone, then mirror
+    /// the inventory onto the combatant's copy
+    /// `cast [caster] <spell> [slot level]`. The caster defaults to whoever's turn it is.
+    /// Leveled spells spend a slot (a pact slot for warlocks), the lowest that fits unless
+    /// a level is given.
+    fn process_cast_command(&mut self, args: &[&str]) {
+        const USAGE: &str = "Usage: cast [caster] <spell> [slot level]";
+        let (words, slot_level) = match args.split_last() {
+            Some((last, rest)) if last.parse::<u32>().is_ok() => (rest, last.parse::<u32>().ok()),
+            _ => (args, None),
+        };
+        let (caster, spell) = match words {
+            [first, spell @ ..] if !spell.is_empty() && self.find_character_mut(first).is_some() => (first.to_string(), spell.join(" ")),
+            _ => {
+                let current = self.combat_tracker.as_ref()
+                    .and_then(|tracker| tracker.combatants.get(tracker.current_turn))
+                    .map(|c| c.name.clone());
+                match current {
+                    Some(current) if !words.is_empty() => (current, words.join(" ")),
+                    _ => {
+                        self.add_output(USAGE.to_string());
+                        return;
+                    }
+                }
+            }
+        };
+        let Some(mut character) = self.find_character_mut(&caster) else {
+            self.add_output(format!("❌ Character '{}' not found", caster));
+            return;
+        };
+        let known = character.spells.is_empty() || character.spells.iter().any(|s| s.eq_ignore_ascii_case(&spell));
+        let result = crate::components::cast_spell(&mut character, &spell);
+        // Details only if the spell's page is already cached; casting never waits on the network
+        let details = crate::content::cached_spell(&spell);
+        let spell_level = details.as_ref().map(|d| d.level as u32);
+        let slot = if spell_level == Some(0) {
+            None
+        } else if crate::pact_magic::is_warlock(&character) {
+            Some(crate::pact_magic::spend_pact_slot(&mut character)
+                .map(|slots| format!("✨ {} casts with a pact slot - {}", character.name, slots.describe())))
+        } else {
+            Some(crate::spell_slots::spend_spell_slot(&mut character, spell_level.unwrap_or(1), slot_level)
+                .map(|(level, slots)| format!("🔮 {} spends a {}-level slot - {}", character.name,
+                    crate::spell_slots::ordinal(level), slots.describe())))
+        };
+        let name = character.name.clone();
+        drop(character);
+
+        if !known {
+            self.add_output(format!("⚠️ {} isn't on {}'s spell list", spell, name));
+        }
+        match result {
+            Ok(message) => self.add_output(message),
+            Err(e) => self.add_output(format!("⚠️ {}", e)),
+        }
+        match slot {
+            Some(Ok(message)) => {
+                self.add_output(message);
+                if spell_level.is_none() && slot_level.is_none() {
+                    self.add_output(format!("  {}'s level isn't known until its page is cached; add a slot level to pick one", spell));
+                }
+            }
+            Some(Err(e)) => self.add_output(format!("⚠️ {}", e)),
+            None => {}
+        }
+        if let Some(details) = details {
+            self.add_output(format!("  📜 {}", details.card_lines().into_iter().take(2).collect::<Vec<_>>().join(" · ")));
+        }
+    }
+
+    /// Ranged attacks by the current combatant use up a piece of ammunition.
+    /// Returns false when they're out and the attack can't be made.
+    fn expend_attack_ammo(&mut self, weapon: &str) -> bool {
+        let Some(attacker) = self.combat_tracker.as_ref()
+            .and_then(|tracker| tracker.combatants.get(tracker.current_turn))
+            .map(|c| c.name.clone()) else {
+            return true;
+        };
+        let Some(mut character) = self.find_character_mut(&attacker) else {
+            return true;
+        };
+        let expended = crate::ammo::expend_ammo(&mut character, weapon);
+        drop(character);
+        match expended {
+            Ok(Some((kind, left))) => {
+                self.add_output(format!("🏹 {} fires {} ({} {} left)", attacker, weapon, left, kind));
+                if left == 0 {
+                    self.add_output(format!("⚠️ That was {}'s last of the {}", attacker, kind));
                 }
-            } else {
-                self.add_output("No combat initialized.".to_string());
-                return;
+                true
+            }
+            Ok(None) => true,
+            Err(e) => {
+                self.add_output(format!("❌ {}", e));
+                false
             }
+        }
+    }
+
+    /// Copy a saved character's sheet onto its combatant so both stay in step
+    /// Everyone who shares the XP from a fight: the saved characters fighting in it,
+    /// or the whole saved party when none of them are in the tracker
+    fn combat_party(&self) -> Vec<String> {
+        let in_combat: Vec<String> = self.combat_tracker.iter()
+            .flat_map(|tracker| tracker.combatants.iter())
+            .filter(|c| c.is_player && self.characters.read().iter().any(|ch| ch.name.eq_ignore_ascii_case(&c.name)))
+            .map(|c| c.name.clone())
+            .collect();
+        if in_combat.is_empty() {
+            self.characters.read().iter().map(|c| c.name.clone()).collect()
         } else {
-            target.to_string()
+            in_combat
+        }
+    }
+
+    /// Copy party combatants' HP and conditions to their sheets
+    fn save_encounter(&mut self, name: &str) {
+        let dir = std::path::Path::new(crate::saved_combat::ENCOUNTERS_DIR);
+        let message = match self.combat_tracker.as_ref() {
+            Some(tracker) => match crate::saved_combat::save_encounter_in(dir, name, tracker) {
+                Ok(path) => format!("💾 Saved the fight (round {}) to {}", tracker.round_number, path.display()),
+                Err(e) => format!("❌ Could not save the encounter: {}", e),
+            },
+            None => "No combat initialized. Use 'init' to start combat.".to_string(),
         };
+        self.add_output(message);
+    }
 
-        if let Some(ref mut tracker) = self.combat_tracker {
-            if let Some(combatant) = tracker.combatants.iter_mut().find(|c| c.name.eq_ignore_ascii_case(&target_name)) {
-                match action {
-                    "add" => {
-                        let status = crate::combat::StatusEffect {
-                            name: status_name.to_string(),
-                            description: None,
-                            duration: rounds,
-                        };
-                        combatant.add_status(status);
-                        
-                        let duration_text = match rounds {
-                            Some(r) => format!(" for {} rounds", r),
-                            None => " (permanent)".to_string(),
-                        };
-                        self.add_output(format!("✅ Added status '{}' to {}{}", 
-                            status_name, target_name, duration_text));
-                    }
-                    "remove" => {
-                        if combatant.remove_status(status_name) {
-                            self.add_output(format!("✅ Removed status '{}' from {}", 
-                                status_name, target_name));
-                        } else {
-                            self.add_output(format!("❌ Status '{}' not found on {}", 
-                                status_name, target_name));
-                        }
-                    }
-                    _ => {
-                        self.add_output("❌ Invalid action. Use 'add' or 'remove'".to_string());
-                    }
+    fn load_encounter(&mut self, name: &str) {
+        let dir = std::path::Path::new(crate::saved_combat::ENCOUNTERS_DIR);
+        let loaded = crate::saved_combat::load_encounter_in(dir, name, &self.characters.read());
+        match loaded {
+            Ok((tracker, warnings)) => {
+                self.add_output(crate::saved_combat::resume_summary(name, &tracker));
+                // The fight's HP wins over the sheets, which may not have been saved when play stopped
+                tracker.write_back(&mut self.characters.write());
+                self.combat_tracker = Some(tracker);
+                for warning in warnings {
+                    self.add_output(warning);
                 }
-            } else {
-                self.add_output(format!("❌ Combatant '{}' not found", target_name));
             }
-        } else {
-            self.add_output("No combat initialized.".to_string());
+            Err(e) => self.add_output(format!("❌ Could not load the encounter: {}", e)),
         }
     }
 
-    fn process_search_command(&mut self, command: String) {
-        let parts: Vec<&str> = command.split_whitespace().collect();
-        if parts.is_empty() {
-            return;
+    fn write_back_party(&mut self) {
+        if let Some(tracker) = self.combat_tracker.as_ref() {
+            tracker.write_back(&mut self.characters.write());
         }
+    }
 
-        let cmd = parts[0].to_lowercase();
-        
-        match cmd.as_str() {
-            "help" | "h" => {
-                self.add_output("Search Commands:".to_string());
-                self.add_output("  search <query> - Search all categories".to_string());
-                self.add_output("  search <category> <query> - Search specific category".to_string());
-                self.add_output("  categories - List available categories".to_string());
-                self.add_output("  back - Return to tools menu".to_string());
-                self.add_output("".to_string());
-                self.add_output("Categories: spells, classes, equipment, monsters, races".to_string());
-                self.add_output("Example: search fireball".to_string());
-                self.add_output("Example: search spell magic missile".to_string());
-            }
-            "search" => {
-                if let Some(_query) = parts.get(1) {
-                    let full_query = parts[1..].join(" ");
-                    self.handle_search_query(&full_query);
-                } else {
-                    self.add_output("Usage: search <query> or search <category> <query>".to_string());
+    fn leave_combat(&mut self) {
+        self.write_back_party();
+        self.mode = AppMode::ToolsMenu;
+        self.selected_index = 0;
+        self.input_buffer.clear();
+        self.output_history.clear();
+        self.scroll_offset = 0;
+        self.combat_tracker = None;
+    }
+
+    /// `wildshape list <level> [moon]` and `polymorph [true] <level>` against the local monster index
+    fn show_shape_forms(&mut self, cmd: &str, args: &[&str]) {
+        let words: Vec<String> = args.iter().map(|a| a.to_lowercase()).collect();
+        let level = words.iter().find_map(|w| w.parse::<u32>().ok());
+        let moon = words.iter().any(|w| w == "moon");
+        let true_polymorph = words.iter().any(|w| w == "true");
+        let (title, forms) = match (cmd, level) {
+            ("wildshape", Some(level)) => match self.monsters.wild_shape_forms(level, moon) {
+                Ok(forms) => {
+                    let (max_cr, swim, fly) = crate::monsters::wild_shape_limits(level, moon).unwrap_or_default();
+                    let movement = match (swim, fly) {
+                        (false, _) => ", no swimming or flying",
+                        (true, false) => ", no flying",
+                        _ => "",
+                    };
+                    (format!("🐾 Wild Shape for a level {}{} druid (CR {}{}):", level, if moon { " moon" } else { "" },
+                        crate::xp::cr_label(max_cr), movement), forms)
+                }
+                Err(e) => {
+                    self.add_output(format!("❌ {}", e));
+                    return;
                 }
+            },
+            ("polymorph", Some(level)) => (format!("🐸 {} forms for a level {} target (CR {} or lower):",
+                if true_polymorph { "True Polymorph" } else { "Polymorph" }, level, level),
+                self.monsters.polymorph_forms(level, true_polymorph)),
+            _ => {
+                self.add_output("Usage: wildshape list <druid level> [moon] | polymorph [true] <level>".to_string());
+                return;
             }
-            "categories" => {
-                self.add_output("Available Categories:".to_string());
-                self.add_output("  • spells - Magic spells".to_string());
-                self.add_output("  • classes - Character classes".to_string());
-                self.add_output("  • equipment (or items/gear) - Weapons, armor, and gear".to_string());
-                self.add_output("  • monsters (or creatures) - Monsters and NPCs".to_string());
-                self.add_output("  • races - Character races".to_string());
+        };
+        let lines: Vec<String> = forms.iter().map(|m| format!("  {}", m.summary())).collect();
+        self.add_output(title);
+        if lines.is_empty() {
+            self.add_output("  Nothing in the monster index qualifies".to_string());
+        }
+        for line in lines {
+            self.add_output(line);
+        }
+    }
+
+    /// One deity in full, or a list of matches for a name, domain, or symbol
+    fn show_deities(&mut self, query: &str) {
+        let lines: Vec<String> = if query.is_empty() {
+            self.pantheon.deities.iter().map(|d| format!("  {}", d.summary())).collect()
+        } else if let Some(deity) = self.pantheon.get(query) {
+            deity.describe()
+        } else {
+            match self.pantheon.search(query).as_slice() {
+                [] => vec![format!("No deity matches '{}'", query)],
+                [deity] => deity.describe(),
+                matches => matches.iter().map(|d| format!("  {}", d.summary())).collect(),
             }
-            "back" | "exit" | "quit" => {
-                self.add_output("Returning to tools menu...".to_string());
-                self.mode = AppMode::ToolsMenu;
-                self.selected_index = 0;
-                self.input_buffer.clear();
-                self.output_history.clear();
-                self.scroll_offset = 0;
+        };
+        for line in lines {
+            self.add_output(line);
+        }
+    }
+
+    fn find_character_mut(&self, name: &str) -> Option<RefMut<'_, Character>> {
+        self.characters.find_mut(name)
+    }
+
+    /// `<name> <resource> [amount]`: spend a class resource such as rage or ki
+    /// `check athletics Bram dc hard`: d20 plus the ability modifier, against a DC
+    /// given as a number or a difficulty name
+    fn skill_check(&mut self, args: &[&str]) {
+        use crate::dc::check_ability;
+
+        let usage = "Usage: check <skill> <name> [dc <number|very-easy|easy|medium|hard|very-hard|nearly-impossible>]";
+        let (before, dc) = match args.iter().position(|a| a.eq_ignore_ascii_case("dc")) {
+            Some(at) => (&args[..at], Some(args[at + 1..].join(" "))),
+            None => (args, None),
+        };
+        // Skills can be several words ("sleight of hand"), so try the longest first
+        let Some((skill, ability, name)) = (1..before.len()).rev()
+            .find_map(|split| check_ability(&before[..split].join(" ")).map(|(skill, ability)| (skill, ability, before[split..].join(" ")))) else {
+            self.add_output(usage.to_string());
+            return;
+        };
+        let dc = match dc.as_deref().map(|dc| self.dc_bands.resolve(dc).map(|n| (n, dc))) {
+            Some(Ok((n, label))) => Some((n, label.parse::<i32>().is_err().then(|| label.to_lowercase()))),
+            Some(Err(e)) => {
+                self.add_output(format!("❌ {}", e));
+                return;
             }
-            _ => {
-                // Try to interpret the entire command as a search query
-                self.handle_search_query(&command);
+            None => None,
+        };
+
+        let combatants = self.combat_tracker.as_ref().map(|t| t.combatants.as_slice()).unwrap_or_default();
+        // Proficiency from the sheet (a background's skills, say) adds the proficiency bonus
+        let found = self.characters.read().iter()
+            .find(|c| c.name.eq_ignore_ascii_case(&name))
+            .map(|c| (c.name.clone(), c.get_ability_modifier(ability) as i32
+                + if c.is_proficient(skill) { c.prof_bonus.unwrap_or(2) as i32 } else { 0 }))
+            .or_else(|| combatants.iter().find(|c| c.name.eq_ignore_ascii_case(&name)).map(|c| (c.name.clone(),
+                c.sheet(&self.characters.read()).map(|d| d.get_ability_modifier(ability) as i32).unwrap_or(0))));
+        let Some((name, modifier)) = found else {
+            self.add_output(format!("❌ No character or combatant named '{}'", name));
+            return;
+        };
+
+        let roll = match crate::dice::roll_dice_with_crits("1d20") {
+            Ok(roll) => roll.plus(modifier),
+            Err(e) => {
+                self.add_output(format!("❌ Error rolling check: {}", e));
+                return;
             }
+        };
+        let mut message = format!("🎲 {}'s {} check: {}", name, skill, roll);
+        if let Some((dc, label)) = dc {
+            let label = label.map(|l| format!(" ({})", l)).unwrap_or_default();
+            let outcome = if roll.total >= dc { "✅ success" } else { "❌ failure" };
+            message.push_str(&format!(" vs DC {}{} → {}", dc, label, outcome));
         }
+        self.add_output(message);
     }
 
-    fn process_character_creation_command(&mut self, command: String) {
-        let parts: Vec<&str> = command.split_whitespace().collect();
-        let cmd_string = if parts.is_empty() { 
-            String::new() 
-        } else { 
-            parts[0].to_lowercase() 
+    fn use_character_resource(&mut self, name: &str, args: &[&str]) -> String {
+        let (resource, amount) = match args {
+            [resource @ .., amount] if !resource.is_empty() && amount.parse::<u32>().is_ok() => (resource.join(" "), amount.parse().unwrap_or(1)),
+            resource => (resource.join(" "), 1),
         };
-        let cmd = cmd_string.as_str();
+        if resource.is_empty() {
+            return "Usage: use <name> <resource> [amount]".to_string();
+        }
+        let Some(mut character) = self.find_character_mut(name) else {
+            return format!("❌ Character '{}' not found", name);
+        };
+        if resource.to_lowercase().starts_with("pact") {
+            return match crate::pact_magic::spend_pact_slot(&mut character) {
+                Ok(slots) => format!("✨ {} casts with a pact slot - {}", character.name, slots.describe()),
+                Err(e) => format!("❌ {}", e),
+            };
+        }
+        crate::resources::refresh_resources(&mut character);
+        let character_name = character.name.clone();
+        match crate::resources::use_resource(&mut character, &resource, amount) {
+            Ok(resource) => format!("✨ {} uses {} - {}", character_name, resource.name, resource.describe()),
+            Err(e) => format!("❌ {}", e),
+        }
+    }
 
-        match cmd {
-            "help" | "h" => {
-                self.add_output("Character Creation Commands:".to_string());
-                self.add_output("  create - Start character creation wizard".to_string());
-                self.add_output("  back - Return to characters menu".to_string());
-            }
-            "create" => {
-                self.add_output("🎭 Starting character creation wizard...".to_string());
-                self.add_output("This will guide you through creating a new character.".to_string());
-                self.add_output("Feature coming soon - interactive character creation!".to_string());
+    /// Short or long rest for one character, or the whole party
+    fn rest_characters(&mut self, args: &[&str]) {
+        let Some(kind) = args.first().and_then(|k| crate::resources::Recharge::parse(k)) else {
+            self.add_output("Usage: rest <short|long> [name]".to_string());
+            return;
+        };
+        let who = args[1..].join(" ");
+        let mut lines = Vec::new();
+        for character in self.characters.write().iter_mut().filter(|c| who.is_empty() || c.name.eq_ignore_ascii_case(&who)) {
+            crate::resources::refresh_resources(character);
+            let mut restored = crate::resources::rest(character, kind);
+            if crate::pact_magic::rest_pact_slots(character) {
+                restored.push("Pact slots".to_string());
             }
-            "back" | "exit" => {
-                self.mode = AppMode::CharactersMenu;
-                self.selected_index = 0;
-                self.clear_terminal_state();
+            if kind == crate::resources::Recharge::LongRest && crate::spell_slots::rest_spell_slots(character) {
+                restored.push("Spell slots".to_string());
             }
-            _ => {
-                self.add_output(format!("Unknown command '{}'. Type 'help' for commands.", cmd));
+            if !restored.is_empty() {
+                lines.push(format!("  {} regains {}", character.name, restored.join(", ")));
             }
         }
+        if !who.is_empty() && !self.characters.read().iter().any(|c| c.name.eq_ignore_ascii_case(&who)) {
+            self.add_output(format!("❌ Character '{}' not found", who));
+            return;
+        }
+        self.add_output(format!("🏕️ The {} is over", kind));
+        if lines.is_empty() {
+            self.add_output("  No resources needed recovering".to_string());
+        }
+        for line in lines {
+            self.add_output(line);
+        }
     }
 
-    fn process_character_display_command(&mut self, command: String) {
-        let parts: Vec<&str> = command.split_whitespace().collect();
-        let cmd_string = if parts.is_empty() { 
-            String::new() 
-        } else { 
-            parts[0].to_lowercase() 
+    fn resource_lines(character: &Character) -> Vec<String> {
+        let mut character = character.clone();
+        crate::resources::refresh_resources(&mut character);
+        crate::pact_magic::refresh_pact_slots(&mut character);
+        crate::spell_slots::refresh_spell_slots(&mut character);
+        let mut lines: Vec<String> = character.resources.iter().map(|r| format!("  {}", r.describe())).collect();
+        if let Some(slots) = character.spell_slots {
+            lines.push(format!("  {}", slots.describe()));
+        }
+        if let Some(slots) = character.pact_slots {
+            lines.push(format!("  {}", slots.describe()));
+        }
+        if !character.invocations.is_empty() {
+            lines.push(format!("  Invocations: {}", character.invocations.join(", ")));
+        }
+        lines
+    }
+
+    /// Add and save a new level 1 character with the chosen scores
+    fn begin_character_creation(&mut self, name: String) {
+        self.add_output(format!("🎭 Creating {}", name));
+        self.add_output(crate::ability_scores::ScoreMethod::menu());
+        self.pending_input = Some(PendingInput::CreationMethod(name));
+    }
+
+    /// Save and add the new character. A name that's still taken is one the user chose
+    /// to overwrite when creation started.
+    fn finish_character_creation(&mut self, name: &str, scores: Option<[u8; 6]>) {
+        let mut character = Character::new(name);
+        character.level = Some(1);
+        character.prof_bonus = Some(crate::leveling::proficiency_bonus(1));
+        if let Some(scores) = scores {
+            crate::ability_scores::apply(&mut character, scores);
+            self.add_output(format!("Ability scores: {}", crate::ability_scores::describe(&scores)));
+        }
+        character.update_passive_perception();
+        if crate::file_manager::character_name_taken(&self.characters.read(), name) {
+            crate::file_manager::add_character_with_choice(&mut self.characters.write(), character, crate::file_manager::NameCollisionChoice::Overwrite);
+        } else {
+            self.characters.insert(character);
+        }
+        let saved = match self.characters.find(name) {
+            Some(character) => crate::file_manager::write_character_file(std::path::Path::new(crate::file_manager::CHARACTERS_DIR), &character),
+            None => Err(crate::error_handling::AppError::NotFound(name.to_string())),
         };
-        let cmd = cmd_string.as_str();
+        match saved {
+            Ok(path) => self.add_output(format!("✅ Created {} (saved to {})", name, path.display())),
+            Err(e) => self.add_output(format!("⚠️ Created {} but could not save the sheet: {}", name, e)),
+        }
+    }
 
-        match cmd {
-            "help" | "h" => {
-                self.add_output("Character Display Commands:".to_string());
-                self.add_output("  list - List all characters".to_string());
-                self.add_output("  show <name> - Show specific character details".to_string());
-                self.add_output("  back - Return to characters menu".to_string());
-            }
-            "list" => {
-                self.add_output("📋 Available Characters:".to_string());
-                if self.characters.is_empty() {
-                    self.add_output("  No characters found.".to_string());
-                } else {
-                    let character_list: Vec<String> = self.characters.iter().enumerate()
-                        .map(|(i, character)| {
-                            format!("  {}. {} (Level {}, {})", 
-                                i + 1, character.name, 
-                                character.level.unwrap_or(1), 
-                                character.class.as_ref().unwrap_or(&"Unknown".to_string()))
-                        })
-                        .collect();
-                    for line in character_list {
-                        self.add_output(line);
-                    }
-                }
-            }
-            "show" => {
-                if parts.len() >= 2 {
-                    let char_name = parts[1..].join(" ");
-                    let character_data = self.characters.iter()
-                        .find(|c| c.name.eq_ignore_ascii_case(&char_name))
-                        .cloned();
-                    
-                    if let Some(character) = character_data {
-                        self.display_character_details(&character);
-                    } else {
-                        self.add_output(format!("❌ Character '{}' not found", char_name));
-                    }
-                } else {
-                    self.add_output("Usage: show <character_name>".to_string());
+    /// Start the level-up wizard: hit points first, then an improvement if one is due
+    fn level_up(&mut self, name: &str) {
+        let Some((name, class, level)) = self.characters.find(name)
+            .map(|c| (c.name.clone(), c.class.clone().unwrap_or_default(), c.level.unwrap_or(1))) else {
+            self.add_output(format!("❌ Character '{}' not found", name));
+            return;
+        };
+        if level >= 20 {
+            self.add_output(format!("{} is already level 20", name));
+            return;
+        }
+        let die = crate::leveling::hit_die(&class);
+        self.add_output(format!("⬆️ {} is going to level {}. Hit points: 'roll' 1d{} or take the 'average' ({})?",
+            name, level + 1, die, crate::leveling::average_roll(die)));
+        self.pending_input = Some(PendingInput::LevelUpHp(name));
+    }
+
+    /// Apply the level and save the sheet; a warlock is then asked for any new invocations
+    fn finish_level_up(&mut self, name: &str, hit_die_result: u32, improvement: Option<crate::leveling::Improvement>) {
+        let Some(mut character) = self.find_character_mut(name) else {
+            self.add_output(format!("❌ Character '{}' not found", name));
+            return;
+        };
+        let result = crate::leveling::level_up(&mut character, hit_die_result, improvement.as_ref());
+        let saved = crate::file_manager::write_character_file(std::path::Path::new(crate::file_manager::CHARACTERS_DIR), &character);
+        let name = character.name.clone();
+        let level = character.level.unwrap_or(1);
+        let due = crate::pact_magic::invocations_due(&character);
+        let resources = Self::resource_lines(&character);
+        drop(character);
+        match result {
+            Ok(lines) => {
+                for line in lines.into_iter().chain(resources) {
+                    self.add_output(line);
                 }
             }
-            "back" | "exit" => {
-                self.mode = AppMode::CharactersMenu;
-                self.selected_index = 0;
-                self.clear_terminal_state();
-            }
-            _ => {
-                self.add_output(format!("Unknown command '{}'. Type 'help' for commands.", cmd));
+            Err(e) => {
+                self.add_output(format!("❌ {}", e));
+                return;
             }
         }
+        if let Err(e) = saved {
+            self.add_output(format!("⚠️ Could not save {}: {}", name, e));
+        }
+        if due > 0 {
+            self.add_output(format!("✨ Choose {} eldritch invocation(s), comma-separated (or 'skip'):", due));
+            self.add_output(format!("  {}", crate::pact_magic::available_invocations(level as u32).join(", ")));
+            self.pending_input = Some(PendingInput::ChooseInvocations(name));
+        }
     }
 
-    fn process_character_deletion_command(&mut self, command: String) {
-        let parts: Vec<&str> = command.split_whitespace().collect();
-        let cmd_string = if parts.is_empty() { 
-            String::new() 
-        } else { 
-            parts[0].to_lowercase() 
-        };
-        let cmd = cmd_string.as_str();
+    fn save_crafting(&mut self) {
+        if let Err(e) = self.crafting.save(std::path::Path::new(crate::crafting::DOWNTIME_FILE)) {
+            self.add_output(format!("⚠️ Could not save downtime progress: {}", e));
+        }
+    }
 
-        match cmd {
-            "help" | "h" => {
-                self.add_output("Character Deletion Commands:".to_string());
-                self.add_output("  list - List all characters".to_string());
-                self.add_output("  delete <name> - Delete specific character".to_string());
-                self.add_output("  back - Return to characters menu".to_string());
+    fn process_sidekick_command(&mut self, args: &[&str]) {
+        use crate::sidekick::{party_level, Sidekick, SidekickClass};
+
+        let level = party_level(&self.characters.read());
+        let result: crate::error_handling::Result<Vec<String>> = match args {
+            ["new", name, class, hp, ac, creature @ ..] => {
+                match (SidekickClass::parse(class), hp.parse::<i32>(), ac.parse::<i32>()) {
+                    (Some(class), Ok(hp), Ok(ac)) => {
+                        let mut sidekick = Sidekick::new(name, class, hp, ac);
+                        if !creature.is_empty() {
+                            sidekick.creature = Some(creature.join(" "));
+                        }
+                        let summary = format!("🤝 New sidekick: {}", sidekick.summary(level));
+                        self.sidekicks.add(sidekick).map(|_| vec![summary])
+                    }
+                    (None, _, _) => Ok(vec![format!("❌ Unknown sidekick class '{}'. Use expert, spellcaster or warrior", class)]),
+                    _ => Ok(vec!["❌ HP and AC must be numbers".to_string()]),
+                }
             }
-            "list" => {
-                self.add_output("📋 Characters available for deletion:".to_string());
-                if self.characters.is_empty() {
-                    self.add_output("  No characters found.".to_string());
-                } else {
-                    let character_list: Vec<String> = self.characters.iter().enumerate()
-                        .map(|(i, character)| format!("  {}. {}", i + 1, character.name))
-                        .collect();
-                    for line in character_list {
-                        self.add_output(line);
+            ["show", name] => match self.sidekicks.get(name) {
+                Some(sidekick) => Ok(vec![
+                    format!("🤝 {}", sidekick.summary(level)),
+                    format!("  Features: {}", sidekick.features(level).join(", ")),
+                ]),
+                None => Err(crate::error_handling::AppError::NotFound(format!("Sidekick '{}'", name))),
+            },
+            ["remove", name] => self.sidekicks.remove(name).map(|s| vec![format!("👋 {} leaves the party", s.name)]),
+            [] => Ok(self.sidekicks.sidekicks.iter().map(|s| format!("🤝 {}", s.summary(level))).collect()),
+            _ => Ok(vec!["Usage: sidekick new <name> <expert|spellcaster|warrior> <hp> <ac> [creature] | show <name> | remove <name>".to_string()]),
+        };
+
+        match result {
+            Ok(lines) => {
+                for line in lines {
+                    self.add_output(line);
+                }
+                if matches!(args.first(), Some(&"new") | Some(&"remove"))
+                    && let Err(e) = self.sidekicks.save(std::path::Path::new(crate::sidekick::SIDEKICKS_FILE)) {
+                    self.add_output(format!("⚠️ Could not save sidekicks: {}", e));
+                }
+            }
+            Err(e) => self.add_output(format!("❌ {}", e)),
+        }
+    }
+
+    fn process_companion_command(&mut self, args: &[&str]) {
+        use crate::companions::{add_companion, find_companion_mut, Companion, CompanionKind};
+        use crate::error_handling::AppError;
+
+        let usage = "Usage: companion add <owner> <familiar|beast> <name> <hp> <ac> [creature] | attack <owner> <name> <attack> | remove <owner> <name> | [owner]";
+        let result: crate::error_handling::Result<Vec<String>> = match args {
+            ["add", owner, kind, name, hp, ac, creature @ ..] => {
+                match (CompanionKind::parse(kind), hp.parse::<i32>(), ac.parse::<i32>(), self.find_character_mut(owner)) {
+                    (_, _, _, None) => Err(AppError::NotFound(format!("Character '{}'", owner))),
+                    (Some(kind), Ok(hp), Ok(ac), Some(mut character)) => {
+                        let mut companion = Companion::new(name, kind, hp, ac);
+                        if !creature.is_empty() {
+                            companion.creature = Some(creature.join(" "));
+                        }
+                        let line = format!("🐾 {} gains a {}: {}", character.name, kind, companion.summary());
+                        add_companion(&mut character, companion).map(|_| vec![line])
                     }
+                    (None, _, _, _) => Err(AppError::ValidationError(format!("Unknown companion kind '{}'. Use familiar or beast", kind))),
+                    _ => Err(AppError::ValidationError("HP and AC must be numbers".to_string())),
                 }
             }
-            "delete" => {
-                if parts.len() >= 2 {
-                    let char_name = parts[1..].join(" ");
-                    if let Some(index) = self.characters.iter().position(|c| c.name.eq_ignore_ascii_case(&char_name)) {
-                        let removed = self.characters.remove(index);
-                        self.add_output(format!("🗑️  Deleted character '{}'", removed.name));
-                        crate::file_manager::save_characters(self.characters.clone());
+            ["attack", owner, name, attack @ ..] if !attack.is_empty() => match self.find_character_mut(owner) {
+                Some(mut character) => find_companion_mut(&mut character, name).map(|companion| {
+                    companion.attacks.push(attack.join(" "));
+                    vec![format!("⚔️ {} can now use: {}", companion.name, attack.join(" "))]
+                }),
+                None => Err(AppError::NotFound(format!("Character '{}'", owner))),
+            },
+            ["remove", owner, name] => match self.find_character_mut(owner) {
+                Some(mut character) => {
+                    let before = character.companions.len();
+                    character.companions.retain(|c| !c.name.eq_ignore_ascii_case(name));
+                    if character.companions.len() < before {
+                        Ok(vec![format!("👋 {} parts ways with {}", character.name, name)])
                     } else {
-                        self.add_output(format!("❌ Character '{}' not found", char_name));
+                        Err(AppError::NotFound(format!("{}'s companion '{}'", character.name, name)))
                     }
-                } else {
-                    self.add_output("Usage: delete <character_name>".to_string());
                 }
+                None => Err(AppError::NotFound(format!("Character '{}'", owner))),
+            },
+            [] | [_] => {
+                let lines: Vec<String> = self.characters.read().iter()
+                    .filter(|c| args.first().is_none_or(|owner| c.name.eq_ignore_ascii_case(owner)))
+                    .filter(|c| !c.companions.is_empty())
+                    .flat_map(|c| std::iter::once(format!("{}:", c.name)).chain(c.companions.iter().flat_map(|comp| comp.describe())))
+                    .collect();
+                Ok(if lines.is_empty() { vec![usage.to_string()] } else { lines })
             }
-            "back" | "exit" => {
-                self.mode = AppMode::CharactersMenu;
-                self.selected_index = 0;
-                self.clear_terminal_state();
-            }
-            _ => {
-                self.add_output(format!("Unknown command '{}'. Type 'help' for commands.", cmd));
+            _ => Ok(vec![usage.to_string()]),
+        };
+        match result {
+            Ok(lines) => {
+                for line in lines {
+                    self.add_output(line);
+                }
             }
+            Err(e) => self.add_output(format!("❌ {}", e)),
         }
     }
 
-    fn process_initiative_command(&mut self, command: String) {
-        let parts: Vec<&str> = command.split_whitespace().collect();
-        let cmd_string = if parts.is_empty() { 
-            String::new() 
-        } else { 
-            parts[0].to_lowercase() 
-        };
-        let cmd = cmd_string.as_str();
+    fn process_bastion_command(&mut self, args: &[&str]) {
+        use crate::bastion::{Bastion, Order, DAYS_PER_BASTION_TURN};
 
-        match cmd {
-            "help" | "h" => {
-                self.add_output("Initiative Tracker Commands:".to_string());
-                self.add_output("  roll <name> - Roll initiative for character/monster".to_string());
-                self.add_output("  list - Show current initiative order".to_string());
-                self.add_output("  clear - Clear all initiative rolls".to_string());
-                self.add_output("  back - Return to tools menu".to_string());
-            }
-            "roll" => {
-                if parts.len() >= 2 {
-                    let name = parts[1..].join(" ");
-                    match crate::dice::roll_dice_with_crits("1d20") {
-                        Ok((rolls, total, crit_message)) => {
-                            self.add_output(format!("🎲 {} rolled initiative: {} (d20: {})", 
-                                name, total, rolls[0]));
-                            if let Some(message) = crit_message {
-                                self.add_output(message);
-                            }
-                        }
-                        Err(e) => {
-                            self.add_output(format!("❌ Error rolling initiative: {}", e));
-                        }
+        let result: crate::error_handling::Result<Vec<String>> = match args {
+            [] if self.bastions.bastions.is_empty() => Ok(vec!["No bastions yet. Usage: bastion new <name> <owner>".to_string()]),
+            [] => Ok(self.bastions.bastions.iter().flat_map(Bastion::describe).collect()),
+            ["new", name, owner] => self.bastions.add(Bastion::new(name, owner))
+                .map(|_| vec![format!("🏰 {} founds {}", owner, name)]),
+            ["add", name, facility, hirelings @ ..] => {
+                let hirelings = hirelings.first().and_then(|h| h.parse::<u32>().ok()).unwrap_or(0);
+                self.bastions.get_mut(name).and_then(|bastion| {
+                    if bastion.facility_mut(facility).is_ok() {
+                        return Err(crate::error_handling::AppError::ValidationError(format!("{} already has a {}", bastion.name, facility)));
                     }
-                } else {
-                    self.add_output("Usage: roll <name>".to_string());
-                }
+                    bastion.facilities.push(crate::bastion::Facility { name: facility.to_string(), hirelings, order: None });
+                    Ok(vec![format!("🏗️ {} builds a {} with {} hireling(s)", bastion.name, facility, hirelings)])
+                })
             }
-            "list" => {
-                self.add_output("📋 Initiative Order: (Feature coming soon)".to_string());
+            ["hire", name, facility, count] => match count.parse::<u32>() {
+                Ok(count) => self.bastions.get_mut(name)
+                    .and_then(|b| b.facility_mut(facility))
+                    .map(|f| {
+                        f.hirelings += count;
+                        vec![format!("👷 {} now has {} hireling(s)", f.name, f.hirelings)]
+                    }),
+                Err(_) => Ok(vec!["❌ The hireling count must be a number".to_string()]),
+            },
+            ["order", name, facility, order] => match Order::parse(order) {
+                Some(order) => self.bastions.get_mut(name)
+                    .and_then(|b| b.facility_mut(facility))
+                    .map(|f| {
+                        f.order = Some(order);
+                        vec![format!("📋 {} will {} this week", f.name, order.to_string().to_lowercase())]
+                    }),
+                None => Ok(vec![format!("❌ Unknown order '{}'. Orders: {}", order,
+                    Order::ALL.iter().map(|o| o.to_string().to_lowercase()).collect::<Vec<_>>().join(", "))]),
+            },
+            ["turn"] => {
+                let mut rng = rand::rng();
+                let mut report: Vec<String> = self.bastions.bastions.iter_mut()
+                    .flat_map(|b| b.take_turn(&mut rng))
+                    .collect();
+                let finished = self.crafting.pass_days(DAYS_PER_BASTION_TURN, &mut self.characters.write());
+                self.in_game_date = Some(self.crafting.date_label());
+                report.push(format!("📅 A week passes. It is now {}.", self.crafting.date_label()));
+                report.extend(self.lights.pass_seconds(DAYS_PER_BASTION_TURN * 24 * 60 * 60));
+                report.extend(finished.into_iter()
+                    .map(|p| format!("✨ {} finishes {} (added to their inventory)", p.crafter, p.item)));
+                self.save_crafting();
+                self.save_lights();
+                Ok(report)
             }
-            "clear" => {
-                self.add_output("🧹 Cleared all initiative rolls".to_string());
+            [name] => self.bastions.get_mut(name).map(|b| b.describe()),
+            _ => Ok(vec!["Usage: bastion [name | new | add | hire | order | turn] - see 'help'".to_string()]),
+        };
+
+        match result {
+            Ok(lines) => {
+                for line in lines {
+                    self.add_output(line);
+                }
+                if !args.is_empty()
+                    && let Err(e) = self.bastions.save(std::path::Path::new(crate::bastion::BASTIONS_FILE)) {
+                    self.add_output(format!("⚠️ Could not save bastions: {}", e));
+                }
             }
-            "back" | "exit" => {
-                self.mode = AppMode::ToolsMenu;
-                self.selected_index = 0;
-                self.clear_terminal_state();
+            Err(e) => self.add_output(format!("❌ {}", e)),
+        }
+    }
+
+    fn process_voice_command(&mut self, args: &[&str]) {
+        let npcs_dir = std::path::Path::new(crate::file_manager::NPCS_DIR);
+        for line in crate::voices::voice_command(npcs_dir, args, &mut self.last_voice, &mut rand::rng()) {
+            self.add_output(line);
+        }
+    }
+
+    fn process_weather_command(&mut self, args: &[&str]) {
+        let (lines, changed) = crate::weather::weather_command(&mut self.weather, args, &mut rand::rng());
+        for line in lines {
+            self.add_output(line);
+        }
+        if changed && let Err(e) = self.weather.save(std::path::Path::new(crate::weather::WEATHER_FILE)) {
+            self.add_output(format!("⚠️ Could not save the weather: {}", e));
+        }
+    }
+
+    /// `campaign name <name>`: rename the campaign, in campaign.toml and the status bar
+    fn process_campaign_command(&mut self, args: &[&str]) {
+        match args {
+            [sub, name @ ..] if sub.eq_ignore_ascii_case("name") && !name.is_empty() => {
+                let name = name.join(" ");
+                match crate::campaign::set_campaign_name_in(std::path::Path::new("."), &name) {
+                    Ok(()) => {
+                        self.add_output(format!("📜 This campaign is now called '{}'", name));
+                        self.campaign_name = Some(name);
+                    }
+                    Err(e) => self.add_output(format!("❌ Could not name the campaign: {}", e)),
+                }
             }
             _ => {
-                self.add_output(format!("Unknown command '{}'. Type 'help' for commands.", cmd));
+                self.add_output(format!("📜 Campaign: {}", self.campaign_name.as_deref().unwrap_or("not named yet")));
+                self.add_output("Usage: campaign name <name>".to_string());
             }
         }
     }
 
-    fn process_npc_generator_command(&mut self, command: String) {
-        let parts: Vec<&str> = command.split_whitespace().collect();
-        let cmd_string = if parts.is_empty() { 
-            String::new() 
-        } else { 
-            parts[0].to_lowercase() 
+    fn process_scene_command(&mut self, args: &[&str]) {
+        if args.first().is_some_and(|a| a.eq_ignore_ascii_case("run")) {
+            self.run_scene_encounter(args.get(1).copied());
+            return;
+        }
+        let (lines, changed) = crate::adventure::scene_command(&mut self.adventure, args,
+            std::path::Path::new(crate::file_manager::NPCS_DIR), std::path::Path::new(crate::saved_combat::ENCOUNTERS_DIR));
+        for line in lines {
+            self.add_output(line);
+        }
+        if changed && let Err(e) = self.adventure.save(std::path::Path::new(crate::adventure::ADVENTURE_FILE)) {
+            self.add_output(format!("⚠️ Could not save the adventure outline: {}", e));
+        }
+    }
+
+    /// `scene run [n]`: load the scene's first encounter into the combat tracker
+    fn run_scene_encounter(&mut self, number: Option<&str>) {
+        let name = match self.adventure.encounter_to_run(number) {
+            Ok(name) => name,
+            Err(e) => {
+                self.add_output(format!("❌ {}", e));
+                return;
+            }
         };
-        let cmd = cmd_string.as_str();
+        if self.combat_tracker.as_ref().is_some_and(|tracker| !tracker.combatants.is_empty()) {
+            self.add_output(format!("⚔️ A fight is already running. 'end' it first, or 'load encounter {}' in combat to replace it", name));
+            return;
+        }
+        // Keep the tracker: clear_terminal_state would drop it
+        self.mode = AppMode::CombatTrackerTUI;
+        self.output_history.clear();
+        self.scroll_offset = 0;
+        self.load_encounter(&name);
+        if self.combat_tracker.is_some() {
+            self.announce_weather();
+            self.start_prefetch();
+        }
+    }
 
-        match cmd {
+    /// What the weather means for the fight, shown as combat starts
+    fn announce_weather(&mut self) {
+        if self.weather.conditions.is_empty() {
+            return;
+        }
+        self.add_output(format!("Weather: {}", self.weather.describe()));
+        for reminder in self.weather.reminders(crate::weather::RuleScope::Combat) {
+            self.add_output(format!("  {}", reminder));
+        }
+    }
+
+    fn process_encounter_command(&mut self, command: String) {
+        use crate::encounter::{generate, Difficulty};
+
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        let cmd = parts.first().map(|s| s.to_lowercase()).unwrap_or_default();
+
+        match cmd.as_str() {
             "help" | "h" => {
-                self.add_output("NPC Generator Commands:".to_string());
-                self.add_output("  random - Generate completely random NPC".to_string());
-                self.add_output("  custom <race> <class> - Generate NPC with specific race/class".to_string());
-                self.add_output("  races - List available races".to_string());
-                self.add_output("  classes - List available classes".to_string());
+                self.add_output("👹 Encounter Generator Commands:".to_string());
+                self.add_output("  generate [party size] [level] <easy|medium|hard|deadly> - Build an encounter from the XP budget".to_string());
+                self.add_output("    (party size and level default to the loaded characters)".to_string());
+                self.add_output("  show - List the last encounter again".to_string());
+                self.add_output("  fight - Push the encounter into the combat tracker".to_string());
                 self.add_output("  back - Return to tools menu".to_string());
             }
-            "random" => {
-                self.generate_random_npc();
-            }
-            "custom" => {
-                if parts.len() >= 3 {
-                    let race = parts[1];
-                    let class = parts[2];
-                    self.generate_custom_npc(race, class);
-                } else {
-                    self.add_output("Usage: custom <race> <class>".to_string());
-                    self.add_output("Example: custom elf wizard".to_string());
+            "generate" | "gen" | "new" => {
+                let Some(difficulty) = parts.last().and_then(|d| Difficulty::parse(d)) else {
+                    self.add_output("Usage: generate [party size] [level] <easy|medium|hard|deadly>".to_string());
+                    return;
+                };
+                let numbers: Vec<&str> = parts[1..parts.len() - 1].to_vec();
+                let (size, level) = {
+                    let characters = self.characters.read();
+                    (characters.len().max(1), crate::sidekick::party_level(&characters))
+                };
+                let size = match numbers.first().map(|n| n.parse::<usize>()) {
+                    Some(Ok(size)) if size > 0 => size,
+                    Some(_) => {
+                        self.add_output("❌ Party size must be a positive number".to_string());
+                        return;
+                    }
+                    None => size,
+                };
+                let level = match numbers.get(1).map(|n| n.parse::<u8>()) {
+                    Some(Ok(level)) if (1..=20).contains(&level) => level,
+                    Some(_) => {
+                        self.add_output("❌ Party level must be between 1 and 20".to_string());
+                        return;
+                    }
+                    None => level,
+                };
+                match generate(&self.monsters, size, level, difficulty, &mut rand::rng()) {
+                    Ok(encounter) => {
+                        for line in encounter.lines() {
+                            self.add_output(line);
+                        }
+                        self.add_output("Type 'fight' to send it to the combat tracker".to_string());
+                        self.current_state = format!("Encounter: {}", difficulty.name());
+                        self.encounter = Some(encounter);
+                    }
+                    Err(e) => self.add_output(format!("❌ Could not build an encounter: {}", e)),
                 }
             }
-            "races" => {
-                self.add_output("Available Races:".to_string());
-                self.add_output("human, elf, dwarf, halfling, dragonborn, gnome, half-elf, half-orc, tiefling".to_string());
-            }
-            "classes" => {
-                self.add_output("Available Classes:".to_string());
-                self.add_output("fighter, wizard, cleric, rogue, ranger, paladin, barbarian, bard, druid, monk, sorcerer, warlock".to_string());
-            }
-            "back" | "exit" => {
-                self.mode = AppMode::ToolsMenu;
-                self.selected_index = 0;
-                self.clear_terminal_state();
-            }
-            _ => {
-                self.add_output(format!("Unknown command '{}'. Type 'help' for commands.", cmd));
+            "show" | "list" => match self.encounter.as_ref().map(|encounter| encounter.lines()) {
+                Some(lines) => for line in lines {
+                    self.add_output(line);
+                },
+                None => self.add_output("No encounter yet. Try 'generate medium'.".to_string()),
+            },
+            "fight" | "push" | "start" => {
+                let Some(encounter) = self.encounter.take() else {
+                    self.add_output("No encounter yet. Try 'generate medium'.".to_string());
+                    return;
+                };
+                let tracker = self.combat_tracker.get_or_insert_with(crate::combat::CombatTracker::new);
+                for combatant in encounter.to_combatants(&mut rand::rng()) {
+                    tracker.add_combatant(combatant);
+                }
+                // Keep the tracker: clear_terminal_state would drop it
+                self.mode = AppMode::CombatTrackerTUI;
+                self.output_history.clear();
+                self.scroll_offset = 0;
+                self.add_output(format!("⚔️ {} creature(s) added to the combat tracker", encounter.monsters.len()));
+                self.add_output("Use 'add <character>' to bring the party in, then 'show' and 'next'".to_string());
+                self.announce_weather();
+                self.start_prefetch();
             }
+            "back" | "exit" | "quit" => self.go_back(),
+            "" => {}
+            _ => self.add_output(format!("Unknown command '{}'. Type 'help' for commands.", cmd)),
         }
     }
 
-    fn process_dice_command(&mut self, command: String) {
+    fn process_downtime_command(&mut self, command: String) {
+        use crate::crafting::{crafting_days, creature_types, harvest, harvest_table};
+
         let parts: Vec<&str> = command.split_whitespace().collect();
-        let cmd_string = if parts.is_empty() { 
-            String::new() 
-        } else { 
-            parts[0].to_lowercase() 
-        };
-        let cmd = cmd_string.as_str();
+        let cmd = parts.first().map(|s| s.to_lowercase()).unwrap_or_default();
 
-        match cmd {
+        match cmd.as_str() {
             "help" | "h" => {
-                self.add_output("🎲 Dice Roller Commands:".to_string());
-                self.add_output("  roll <dice> - Roll dice (e.g., 1d20, 2d6+3, 4d8)".to_string());
-                self.add_output("  advantage - Roll with advantage (2d20, keep higher)".to_string());
-                self.add_output("  disadvantage - Roll with disadvantage (2d20, keep lower)".to_string());
-                self.add_output("  stats - Roll 4d6 drop lowest for ability scores".to_string());
+                self.add_output("🔨 Downtime & Crafting Commands:".to_string());
+                self.add_output("  harvest types - List creature types with harvest tables".to_string());
+                self.add_output("  harvest <type> - Show materials and DCs for a creature type".to_string());
+                self.add_output("  harvest <character> <type> <roll> - Harvest a creature into a character's inventory".to_string());
+                self.add_output("  inventory <character> - Show a character's inventory".to_string());
+                self.add_output("  craft <character> <days|value gp> <item> [with <material>, ...] - Start a crafting project".to_string());
+                self.add_output("  projects - Show crafting in progress".to_string());
+                self.add_output("  downtime <days> - Spend downtime days on every project".to_string());
+                self.add_output("  carouse <character> <lower|middle|upper> [weeks] - Carouse for contacts, at a cost and with a risk of complications".to_string());
+                self.add_output("  contacts [character] - List the contacts made while carousing".to_string());
+                self.add_output("  time <n> [minutes|hours] - Let time pass for burning lights".to_string());
+                self.add_output("  supplies [add|use <rations|water|feed|mounts> <n>] - Show or change expedition supplies".to_string());
+                self.add_output("  travel <days> [hours per day] [hot] - Travel, eating and drinking from the supplies".to_string());
+                self.add_output("  weather [roll|clear|<conditions>] - Set the weather; travel shows its rules, and extreme heat counts as hot".to_string());
+                self.add_output("  scene add <title> | done [n] | note|npc|encounter <n> <...> - Build the adventure outline and mark scenes done".to_string());
+                self.add_output("  stash - Show the party stash".to_string());
+                self.add_output("  fallen - List characters who have died, and how".to_string());
+                self.add_output("  exhaustion <character> [level] - Show or set a character's exhaustion".to_string());
+                self.add_output("  rest <short|long> [character] - Recover class resources".to_string());
+                self.add_output("  light <kind> <holder> | lights | douse <holder|kind> | vision - Track light and darkvision".to_string());
+                self.add_output("  bastion [name] - List strongholds, or show one".to_string());
+                self.add_output("  bastion new <name> <owner> - Found a stronghold".to_string());
+                self.add_output("  bastion add <name> <facility> [hirelings] - Build a facility".to_string());
+                self.add_output("  bastion hire <name> <facility> <count> - Add hirelings to a facility".to_string());
+                self.add_output("  bastion order <name> <facility> <craft|harvest|maintain|recruit|research|trade>".to_string());
+                self.add_output("  bastion turn - Resolve a week for every bastion (7 downtime days)".to_string());
                 self.add_output("  back - Return to tools menu".to_string());
             }
-            "roll" => {
-                if parts.len() >= 2 {
-                    let dice_expr = parts[1..].join("");
-                    self.roll_dice_with_display(&dice_expr);
+            "harvest" => match &parts[1..] {
+                ["types"] | [] => self.add_output(format!("🦴 Creature types: {}", creature_types().join(", "))),
+                [kind] => match harvest_table(kind) {
+                    Some(table) => {
+                        self.add_output(format!("🦴 Harvesting a {}:", kind.to_lowercase()));
+                        for (material, dc) in table {
+                            self.add_output(format!("  DC {:>2} - {}", dc, material));
+                        }
+                    }
+                    None => self.add_output(format!("❌ Unknown creature type '{}'. Try 'harvest types'.", kind)),
+                },
+                [character, kind, roll] => {
+                    let Ok(roll) = roll.parse::<i32>() else {
+                        self.add_output("❌ The roll must be a number".to_string());
+                        return;
+                    };
+                    let results = match harvest(kind, roll) {
+                        Ok(results) => results,
+                        Err(e) => {
+                            self.add_output(format!("❌ {}", e));
+                            return;
+                        }
+                    };
+                    let Some(mut crafter) = self.find_character_mut(character) else {
+                        self.add_output(format!("❌ Character '{}' not found", character));
+                        return;
+                    };
+                    let gained: Vec<&str> = results.iter().filter(|(_, _, ok)| *ok).map(|(m, _, _)| *m).collect();
+                    crafter.inventory.extend(gained.iter().map(|m| m.to_string()));
+                    let name = crafter.name.clone();
+                    drop(crafter);
+                    for (material, dc, ok) in results {
+                        self.add_output(format!("  {} {} (DC {})", if ok { "✅" } else { "❌" }, material, dc));
+                    }
+                    if gained.is_empty() {
+                        self.add_output(format!("🦴 {} ruins the carcass and salvages nothing.", name));
+                    } else {
+                        self.add_output(format!("🦴 {} harvests {}", name, gained.join(", ")));
+                    }
+                }
+                _ => self.add_output("Usage: harvest <character> <type> <roll>".to_string()),
+            },
+            "inventory" | "inv" => {
+                let Some(name) = parts.get(1) else {
+                    self.add_output("Usage: inventory <character>".to_string());
+                    return;
+                };
+                let message = match self.characters.find(name) {
+                    Some(c) if c.inventory.is_empty() => format!("🎒 {} carries nothing", c.name),
+                    Some(c) => format!("🎒 {}: {}", c.name, c.inventory.join(", ")),
+                    None => format!("❌ Character '{}' not found", name),
+                };
+                self.add_output(message);
+            }
+            "craft" => {
+                if parts.len() < 4 {
+                    self.add_output("Usage: craft <character> <days|value gp> <item> [with <material>, ...]".to_string());
+                    return;
+                }
+                let time = parts[2].to_lowercase();
+                let days = match time.strip_suffix("gp").map(|v| v.parse::<u32>()) {
+                    Some(Ok(value)) => crafting_days(value),
+                    Some(Err(_)) => 0,
+                    None => time.parse::<u32>().unwrap_or(0),
+                };
+                if days == 0 {
+                    self.add_output("❌ Give the crafting time in days (e.g. 5) or the item's value (e.g. 50gp)".to_string());
+                    return;
+                }
+                let rest = parts[3..].join(" ");
+                let (item, materials) = match rest.split_once(" with ") {
+                    Some((item, materials)) => (item.to_string(), materials.split(',').map(|m| m.trim().to_string()).filter(|m| !m.is_empty()).collect()),
+                    None => (rest, Vec::new()),
+                };
+                let Some(index) = self.characters.read().iter().position(|c| c.name.eq_ignore_ascii_case(parts[1])) else {
+                    self.add_output(format!("❌ Character '{}' not found", parts[1]));
+                    return;
+                };
+                let message = match self.crafting.start_project(&mut self.characters.write()[index], &item, days, &materials) {
+                    Ok(project) => format!("🔨 Started {}", project.progress()),
+                    Err(e) => format!("❌ {}", e),
+                };
+                self.add_output(message);
+                self.save_crafting();
+            }
+            "bastion" | "stronghold" => self.process_bastion_command(&parts[1..]),
+            "projects" => {
+                if self.crafting.projects.is_empty() {
+                    self.add_output("No crafting in progress.".to_string());
                 } else {
-                    self.add_output("Usage: roll <dice_expression>".to_string());
-                    self.add_output("Examples: roll 1d20, roll 2d6+3, roll 4d8".to_string());
+                    let lines: Vec<String> = self.crafting.projects.iter().map(|p| format!("  🔨 {}", p.progress())).collect();
+                    self.add_output(format!("{}:", self.crafting.date_label()));
+                    for line in lines {
+                        self.add_output(line);
+                    }
+                }
+            }
+            "light" | "lights" | "douse" | "vision" => self.process_light_command(&cmd, &parts[1..]),
+            "supplies" => {
+                let party_size = self.characters.len() as u32;
+                let message = match &parts[1..] {
+                    [] => Ok(self.supplies.describe(party_size)),
+                    [action @ ("add" | "use"), kind, amount] => match amount.parse::<i64>() {
+                        Ok(amount) => {
+                            let amount = if *action == "use" { -amount } else { amount };
+                            self.supplies.adjust(kind, amount).map(|_| self.supplies.describe(party_size))
+                        }
+                        Err(_) => Ok("❌ Amount must be a whole number".to_string()),
+                    },
+                    _ => Ok("Usage: supplies [add|use <rations|water|feed|mounts> <n>]".to_string()),
+                };
+                match message {
+                    Ok(message) => self.add_output(message),
+                    Err(e) => self.add_output(format!("❌ {}", e)),
                 }
+                self.save_supplies();
             }
-            "advantage" => {
-                self.add_output("🎲 Rolling with advantage (2d20, keep higher):".to_string());
-                self.roll_dice_with_display("2d20");
-                self.add_output("📈 Use the HIGHER roll for advantage!".to_string());
+            "travel" => {
+                let hot = parts.iter().any(|p| p.eq_ignore_ascii_case("hot"))
+                    || self.weather.is(crate::weather::WeatherCondition::ExtremeHeat);
+                let numbers: Vec<u32> = parts[1..].iter().filter_map(|p| p.parse().ok()).collect();
+                let Some(&days) = numbers.first().filter(|d| **d > 0) else {
+                    self.add_output("Usage: travel <days> [hours per day] [hot]".to_string());
+                    return;
+                };
+                let hours = numbers.get(1).copied().unwrap_or(crate::supplies::TRAVEL_HOURS_PER_DAY);
+                let mut rng = rand::rng();
+                let mut lines = Vec::new();
+                if !self.weather.conditions.is_empty() {
+                    lines.push(format!("Weather: {}", self.weather.describe()));
+                    lines.extend(self.weather.reminders(crate::weather::RuleScope::Travel).into_iter().map(|r| format!("  {}", r)));
+                }
+                for day in 1..=days {
+                    let notices = self.supplies.travel_day(&mut self.characters.write(), hours, hot, &mut rng);
+                    lines.push(format!("🧭 Travel day {} ({} hours{})", day, hours, if hot { ", hot" } else { "" }));
+                    lines.extend(notices.into_iter().map(|n| format!("  {}", n)));
+                }
+                lines.push(self.supplies.describe(self.characters.len() as u32));
+                for line in lines {
+                    self.add_output(line);
+                }
+                self.burn_lights(days * 24 * 60 * 60);
+                self.save_supplies();
             }
-            "disadvantage" => {
-                self.add_output("🎲 Rolling with disadvantage (2d20, keep lower):".to_string());
-                self.roll_dice_with_display("2d20");
-                self.add_output("📉 Use the LOWER roll for disadvantage!".to_string());
+            "rest" => self.rest_characters(&parts[1..]),
+            "exhaustion" => {
+                let message = match &parts[1..] {
+                    [name] => self.characters.read().iter()
+                        .find(|c| c.name.eq_ignore_ascii_case(name))
+                        .map(|c| format!("😫 {} has {} level(s) of exhaustion", c.name, c.exhaustion))
+                        .unwrap_or_else(|| format!("❌ Character '{}' not found", name)),
+                    [name, level] => match (level.parse::<u8>(), self.find_character_mut(name)) {
+                        (Ok(level), Some(mut character)) => {
+                            character.exhaustion = level.min(crate::supplies::MAX_EXHAUSTION);
+                            format!("😫 {} now has {} level(s) of exhaustion", character.name, character.exhaustion)
+                        }
+                        (Err(_), _) => "❌ Exhaustion must be a level from 0 to 6".to_string(),
+                        (_, None) => format!("❌ Character '{}' not found", name),
+                    },
+                    _ => "Usage: exhaustion <character> [level]".to_string(),
+                };
+                self.add_output(message);
             }
-            "stats" => {
-                self.add_output("🎲 Rolling ability scores (4d6, drop lowest):".to_string());
-                self.add_output("".to_string());
-                for ability in &["Strength", "Dexterity", "Constitution", "Intelligence", "Wisdom", "Charisma"] {
-                    self.roll_ability_score(ability);
+            "time" => {
+                let minutes = match &parts[1..] {
+                    [n] => n.trim_end_matches(['m', 'h']).parse::<u32>().ok()
+                        .map(|n| if parts[1].ends_with('h') { n * 60 } else { n }),
+                    [n, unit] => n.parse::<u32>().ok()
+                        .map(|n| if unit.to_lowercase().starts_with('h') { n * 60 } else { n }),
+                    _ => None,
+                };
+                let Some(minutes) = minutes.filter(|m| *m > 0) else {
+                    self.add_output("Usage: time <n> [minutes|hours]  e.g. time 30, time 2 hours".to_string());
+                    return;
+                };
+                self.add_output(format!("⏳ {} minute(s) pass", minutes));
+                self.burn_lights(minutes * 60);
+            }
+            "downtime" | "days" => {
+                let Some(days) = parts.get(1).and_then(|d| d.parse::<u32>().ok()).filter(|d| *d > 0) else {
+                    self.add_output("Usage: downtime <days>".to_string());
+                    return;
+                };
+                let finished = self.crafting.pass_days(days, &mut self.characters.write());
+                self.in_game_date = Some(self.crafting.date_label());
+                self.add_output(format!("📅 {} day(s) pass. It is now {}.", days, self.crafting.date_label()));
+                self.burn_lights(days * 24 * 60 * 60);
+                for project in finished {
+                    self.add_output(format!("✨ {} finishes {} (added to their inventory)", project.crafter, project.item));
+                }
+                let ongoing: Vec<String> = self.crafting.projects.iter().map(|p| format!("  🔨 {}", p.progress())).collect();
+                for line in ongoing {
+                    self.add_output(line);
+                }
+                self.save_crafting();
+            }
+            "carouse" => {
+                let npcs_dir = std::path::Path::new(crate::file_manager::NPCS_DIR);
+                let lines = match &parts[1..] {
+                    [name, company, weeks @ ..] if weeks.len() <= 1 => match self.find_character_mut(name) {
+                        Some(mut character) => crate::carousing::carouse_command(npcs_dir, &mut character, company, weeks.first().copied(), &self.names, &mut rand::rng())
+                            .unwrap_or_else(|e| vec![format!("❌ {}", e)]),
+                        None => vec![format!("❌ Character '{}' not found", name)],
+                    },
+                    _ => vec!["Usage: carouse <character> <lower|middle|upper> [workweeks]".to_string()],
+                };
+                for line in lines {
+                    self.add_output(line);
+                }
+            }
+            "contacts" => {
+                let contacts = crate::carousing::contacts_in(std::path::Path::new(crate::file_manager::NPCS_DIR), parts.get(1).copied());
+                if contacts.is_empty() {
+                    self.add_output("🤝 No contacts yet. 'carouse' to make some.".to_string());
+                }
+                for (character, contact) in contacts {
+                    self.add_output(format!("  {} {}: {}, a {} ({})", if contact.hostile { "😠" } else { "🤝" }, character, contact.name, contact.occupation, contact.attitude()));
                 }
             }
             "back" | "exit" => {
@@ -1111,10 +4566,8 @@ impl App {
                 self.selected_index = 0;
                 self.clear_terminal_state();
             }
-            _ => {
-                // Try to interpret as dice roll
-                self.roll_dice_with_display(&command);
-            }
+            "" => {}
+            _ => self.add_output(format!("Unknown command '{}'. Type 'help' for available commands.", cmd)),
         }
     }
 
@@ -1130,10 +4583,34 @@ impl App {
         if let Some(class) = &character.class {
             self.add_output(format!("Class: {}", class));
         }
+
+        if character.xp > 0 {
+            self.add_output(format!("XP: {}", crate::xp::format_xp(character.xp)));
+        }
         
         if let Some(race) = &character.race {
             self.add_output(format!("Race: {}", race));
         }
+
+        if let Some(faith) = &character.faith {
+            let domains = self.pantheon.get(faith).map(|d| format!(" ({})", d.domains.join(", "))).unwrap_or_default();
+            self.add_output(format!("Faith: {}{}", faith, domains));
+        }
+        if let Some(background) = &character.background {
+            let feature = self.backgrounds.get(background).map(|b| format!(" ({})", b.feature)).unwrap_or_default();
+            self.add_output(format!("Background: {}{}", background, feature));
+        }
+        if !character.proficiencies.is_empty() {
+            self.add_output(format!("Proficiencies: {}", character.proficiencies.join(", ")));
+        }
+
+        let resources = Self::resource_lines(character);
+        if !resources.is_empty() {
+            self.add_output("Resources:".to_string());
+            for line in resources {
+                self.add_output(line);
+            }
+        }
         
         self.add_output("".to_string());
         
@@ -1168,6 +4645,23 @@ impl App {
         if let Some(speed) = character.speed {
             self.add_output(format!("Speed: {} ft", speed));
         }
+
+        if !character.spells.is_empty() {
+            self.add_output(format!("Spells: {}", character.spells.join(", ")));
+        }
+        if !character.conditions.is_empty() {
+            self.add_output(format!("Conditions: {}", character.conditions.join(", ")));
+        }
+        if !character.companions.is_empty() {
+            self.add_output("Companions:".to_string());
+            for line in character.companions.iter().flat_map(|c| c.describe()) {
+                self.add_output(line);
+            }
+        }
+        if let Some(max) = crate::prepared::max_prepared(character) {
+            self.add_output(format!("Prepared ({}/{}): {}", character.prepared.len(), max,
+                if character.prepared.is_empty() { "none - use 'prepare'".to_string() } else { character.prepared.join(", ") }));
+        }
     }
 
     fn generate_random_npc(&mut self) {
@@ -1177,6 +4671,7 @@ impl App {
         
         let race = get_random_race();
         let class = get_random_class();
+        let name = self.names.generate(Some(&race), &mut rand::rng()).unwrap_or_default();
         let ac = (rand::random::<u8>() % 11) + 10; // 10-20
         let hp = (rand::random::<u8>() % 41) + 10; // 10-50
         let speed = ((rand::random::<u8>() % 7) + 2) * 10; // 20-80
@@ -1185,6 +4680,7 @@ impl App {
         self.add_output("╔═══════════════════════════════════════╗".to_string());
         self.add_output("║            Generated NPC              ║".to_string());
         self.add_output("╠═══════════════════════════════════════╣".to_string());
+        self.add_output(format!("║ Name: {:<31} ║", name));
         self.add_output(format!("║ Race: {:<31} ║", race));
         self.add_output(format!("║ Class: {:<30} ║", class));
         self.add_output(format!("║ AC: {:<33} ║", ac));
@@ -1195,6 +4691,7 @@ impl App {
 
     fn generate_custom_npc(&mut self, race: &str, class: &str) {
         self.add_output(format!("🎲 Generating {} {}...", race, class));
+        let name = self.names.generate(Some(race), &mut rand::rng()).unwrap_or_default();
         
         let ac = (rand::random::<u8>() % 11) + 10; // 10-20
         let hp = (rand::random::<u8>() % 41) + 10; // 10-50
@@ -1204,6 +4701,7 @@ impl App {
         self.add_output("╔═══════════════════════════════════════╗".to_string());
         self.add_output("║          Generated Custom NPC         ║".to_string());
         self.add_output("╠═══════════════════════════════════════╣".to_string());
+        self.add_output(format!("║ Name: {:<31} ║", name));
         self.add_output(format!("║ Race: {:<31} ║", race));
         self.add_output(format!("║ Class: {:<30} ║", class));
         self.add_output(format!("║ AC: {:<33} ║", ac));
@@ -1213,27 +4711,116 @@ impl App {
     }
 
     fn roll_dice_with_display(&mut self, dice_expr: &str) {
-        match crate::dice::roll_dice_with_crits(dice_expr) {
-            Ok((rolls, total, crit_message)) => {
+        match crate::dice::roll_expression(dice_expr) {
+            Ok(roll) => {
                 self.add_output("".to_string());
                 self.add_output("┌─────────────────────────────────┐".to_string());
                 self.add_output("│         🎲 DICE ROLL! 🎲         │".to_string());
                 self.add_output("├─────────────────────────────────┤".to_string());
                 self.add_output(format!("│ Expression: {:<19} │", dice_expr));
-                self.add_output(format!("│ Individual Rolls: {:<13} │", format!("{:?}", rolls)));
-                self.add_output(format!("│ TOTAL: {:<23} │", total));
+                self.add_output(format!("│ {:<31} │", roll.to_string()));
+                self.add_output(format!("│ TOTAL: {:<23} │", roll.total));
                 
-                if let Some(message) = crit_message {
+                if let Some(message) = roll.crit() {
                     self.add_output("├─────────────────────────────────┤".to_string());
                     self.add_output(format!("│ {:<31} │", message));
                 }
-                
-                self.add_output("└─────────────────────────────────┘".to_string());
-                self.add_output("".to_string());
+                
+                self.add_output("└─────────────────────────────────┘".to_string());
+                self.add_output("".to_string());
+                self.run_script_hook(Hook::Roll {
+                    kind: "roll".to_string(),
+                    expression: dice_expr.to_string(),
+                    total: roll.total,
+                    crit: roll.is_critical_success(),
+                    target: String::new(),
+                });
+            }
+            Err(e) => {
+                self.add_output(format!("❌ Error rolling dice: {}", e));
+                self.add_output("💡 Try format like: 1d20, 2d6+3, 4d8, (1d8+4)*2".to_string());
+            }
+        }
+    }
+
+    /// Roll for the `roll` command: percentile dice read as one d100, and with a
+    /// table the total picks its entry
+    fn roll_on_table(&mut self, dice_expr: &str, as_percentile: bool, table: Option<&str>) {
+        let percentile = if as_percentile {
+            if dice_expr != "2d10" {
+                self.add_output("❌ Only 2d10 can be read as percentile dice".to_string());
+                return;
+            }
+            true
+        } else {
+            crate::dice::is_percentile(dice_expr)
+        };
+
+        let total = if percentile {
+            let roll = crate::dice::roll_percentile();
+            self.add_output(format!("🎲 {}", roll));
+            roll.total()
+        } else if table.is_some() {
+            match crate::dice::roll_expression(dice_expr) {
+                Ok(roll) => {
+                    self.add_output(format!("🎲 {}", roll));
+                    roll.total.max(0) as u32
+                }
+                Err(e) => {
+                    self.add_output(format!("❌ Error rolling dice: {}", e));
+                    return;
+                }
+            }
+        } else {
+            self.roll_dice_with_display(dice_expr);
+            return;
+        };
+
+        if let Some(table) = table {
+            match self.tables.lookup(table, total) {
+                Ok(result) => self.add_output(format!("📜 {} [{}]: {}", table, total, result)),
+                Err(e) => self.add_output(format!("❌ {}", e)),
+            }
+        }
+    }
+
+    fn process_table_command(&mut self, args: &[&str]) {
+        match args.first().map(|a| a.to_lowercase()).as_deref() {
+            Some("list") => {
+                if self.tables.is_empty() {
+                    self.add_output(format!("No tables loaded. Add .toml or .csv files to {}", crate::tables::TABLES_DIR));
+                    return;
+                }
+                let lines: Vec<String> = self.tables.tables()
+                    .map(|t| match t.description {
+                        Some(ref description) => format!("  {} ({} entries) - {}", t.name, t.entries.len(), description),
+                        None => format!("  {} ({} entries)", t.name, t.entries.len()),
+                    })
+                    .collect();
+                self.add_output("📜 Tables:".to_string());
+                for line in lines {
+                    self.add_output(line);
+                }
             }
-            Err(e) => {
-                self.add_output(format!("❌ Error rolling dice: {}", e));
-                self.add_output("💡 Try format like: 1d20, 2d6+3, 4d8".to_string());
+            Some("roll") if args.len() >= 2 => {
+                // A trailing number is a repeat count: "table roll Wild Magic 3"
+                let (name_parts, times) = match args[args.len() - 1].parse::<usize>() {
+                    Ok(times) if args.len() >= 3 => (&args[1..args.len() - 1], times.clamp(1, 20)),
+                    _ => (&args[1..], 1),
+                };
+                let name = name_parts.join(" ");
+                for _ in 0..times {
+                    match self.tables.roll(&name) {
+                        Ok(result) => self.add_output(format!("🎲 {}: {}", name, result)),
+                        Err(e) => {
+                            self.add_output(format!("❌ {}", e));
+                            break;
+                        }
+                    }
+                }
+            }
+            _ => {
+                self.add_output("Usage: table list | table roll <name> [times]".to_string());
             }
         }
     }
@@ -1251,6 +4838,21 @@ impl App {
             ability_name, total, rolls[0], rolls[1], rolls[2], rolls[3], rolls[3]));
     }
 
+    fn run_script_hook(&mut self, hook: Hook) {
+        if self.scripts.is_empty() {
+            return;
+        }
+        let actions = self.scripts.run_hook(&hook);
+        self.apply_script_actions(actions);
+    }
+
+    /// Carry out what house-rule scripts asked for. Script damage doesn't re-trigger hooks.
+    pub fn apply_script_actions(&mut self, actions: Vec<ScriptAction>) {
+        for line in crate::scripting::apply_actions(self.combat_tracker.as_mut(), actions) {
+            self.add_output(line);
+        }
+    }
+
     fn add_output(&mut self, text: String) {
         self.output_history.push(text);
         // Auto-scroll to bottom
@@ -1273,19 +4875,17 @@ impl App {
             16, // AC
             15, // Initiative
         );
-        tracker.combatants.push(fighter);
+        tracker.add_combatant(fighter);
         
         // Add a sample goblin
-        let goblin = crate::combat::Combatant::new_npc(
+        let mut goblin = crate::combat::Combatant::new_npc(
             "Goblin".to_string(),
             7,  // HP
             13, // AC
             12, // Initiative
         );
-        tracker.combatants.push(goblin);
-        
-        // Sort by initiative (highest first)
-        tracker.combatants.sort_by(|a, b| b.initiative.cmp(&a.initiative));
+        goblin.cr = Some("1/4".to_string());
+        tracker.add_combatant(goblin);
         
         self.combat_tracker = Some(tracker);
         
@@ -1293,75 +4893,42 @@ impl App {
         self.add_output("".to_string());
         self.add_output("Combatants added:".to_string());
         self.add_output("  • Fighter (HP: 30, AC: 16, Init: 15)".to_string());
-        self.add_output("  • Goblin (HP: 7, AC: 13, Init: 12)".to_string());
+        self.add_output("  • Goblin (HP: 7, AC: 13, Init: 12, CR 1/4)".to_string());
         self.add_output("".to_string());
         self.add_output("Type 'show' to see initiative order, or 'next' to start combat!".to_string());
+        self.announce_weather();
     }
 
-    fn handle_combat_search(&mut self, query: &str) {
-        self.add_output(format!("🔍 Searching for '{}'...", query));
-        
-        // Create a blocking task to handle the async search
-        let query_clone = query.to_string();
-        
-        // Create runtime for async operations
-        match tokio::runtime::Runtime::new() {
-            Ok(rt) => {
-                let client = crate::search::DndSearchClient::new();
-                
-                rt.block_on(async {
-                    match client.search(&query_clone, None).await {
-                        Ok(results) => {
-                            if results.is_empty() {
-                                self.add_output(format!("❌ No exact match found for '{}'", query_clone));
-                                
-                                let suggestions = client.get_suggestions(&query_clone, None).await;
-                                if !suggestions.is_empty() {
-                                    self.add_output("🔍 Similar items found:".to_string());
-                                    for (i, suggestion) in suggestions.iter().take(3).enumerate() {
-                                        self.add_output(format!("  {}. {}", i + 1, suggestion));
-                                    }
-                                }
-                            } else {
-                                self.add_output(format!("✅ Found {} result(s):", results.len()));
-                                
-                                for (i, result) in results.iter().take(2).enumerate() { // Show max 2 results in combat
-                                    if results.len() > 1 {
-                                        self.add_output(format!("--- Result {} ---", i + 1));
-                                    }
-                                    
-                                    self.add_output(format!("📝 {}: {}", result.index(), result.name()));
-                                    
-                                    // Display key info only (first 10 lines)
-                                    let content_lines: Vec<&str> = result.page.content.lines().collect();
-                                    for line in content_lines.iter().take(10) {
-                                        self.add_output(line.to_string());
-                                    }
-                                    
-                                    if content_lines.len() > 10 {
-                                        self.add_output(format!("... (use search mode for full details)"));
-                                    }
-                                    
-                                    if i == 0 && results.len() > 1 {
-                                        self.add_output("".to_string());
-                                    }
-                                }
-                            }
-                        },
-                        Err(e) => {
-                            self.add_output(format!("❌ Search failed: {}", e));
-                        }
-                    }
-                });
+    /// Load a page into the combat reference pane, leaving the combat log alone
+    fn load_reference(&mut self, query: &str, category: Option<crate::search::SearchCategory>) {
+        match crate::search::lookup_reference(query, category) {
+            Ok(page) => {
+                self.add_output(format!("📖 {} is in the reference pane ('ref close' to hide it)", page.name));
+                self.reference = Some(page);
             }
+            Err(crate::error_handling::AppError::NotFound(_)) => self.add_output(format!("❌ No match found for '{}'", query)),
+            Err(e @ crate::error_handling::AppError::LayoutChanged(_)) => self.add_output(format!("❌ {}", e)),
             Err(e) => {
-                self.add_output(format!("❌ Failed to create async runtime: {}", e));
-                self.add_output("Search functionality unavailable.".to_string());
+                self.search_status = SearchStatus::Offline;
+                self.add_output(format!("❌ Lookup failed and '{}' isn't cached: {}", query, e));
             }
         }
-        
-        self.add_output("".to_string());
-        self.add_output("📋 Returning to combat...".to_string());
+    }
+
+    /// Fetch a cached page again, for `cache refresh <name>`
+    fn refresh_cached_page(&mut self, name: &str) {
+        if crate::srd::is_offline() {
+            self.add_output("❌ Offline: refreshing needs the wiki".to_string());
+            return;
+        }
+        let lines = crate::search::DndSearchClient::try_new()
+            .and_then(|client| crate::runtime::block_on(client.refresh_cached(name)));
+        match lines {
+            Ok(lines) => for line in lines {
+                self.add_output(line);
+            },
+            Err(e) => self.add_output(format!("❌ {}", e)),
+        }
     }
 
     fn handle_search_query(&mut self, query: &str) {
@@ -1370,14 +4937,21 @@ impl App {
         // Create a blocking task to handle the async search
         let query_clone = query.to_string();
         
-        // Create runtime for async operations
-        match tokio::runtime::Runtime::new() {
+        match crate::runtime::shared() {
             Ok(rt) => {
-                let client = crate::search::DndSearchClient::new();
+                let client = match crate::search::DndSearchClient::try_new() {
+                    Ok(client) => client,
+                    Err(e) => {
+                        self.search_status = SearchStatus::Offline;
+                        self.add_output(format!("❌ {}", e));
+                        return;
+                    }
+                };
                 
                 rt.block_on(async {
                     match client.search(&query_clone, None).await {
                         Ok(results) => {
+                            self.search_status = SearchStatus::Online;
                             if results.is_empty() {
                                 self.add_output(format!("❌ No exact match found for '{}'", query_clone));
                                 
@@ -1403,6 +4977,7 @@ impl App {
                                     self.add_output(format!("📝 Name: {}", result.name()));
                                     self.add_output(format!("🏷️ Type: {}", result.index()));
                                     self.add_output(format!("🔗 Source: {}", result.page.url));
+                                    self.add_output(format!("📜 License: {}", result.page.source().license().name()));
                                     self.add_output("".to_string());
                                     
                                     // Display content with line breaks
@@ -1421,7 +4996,13 @@ impl App {
                                 }
                             }
                         },
+                        Err(e @ crate::error_handling::AppError::LayoutChanged(_)) => {
+                            self.search_status = SearchStatus::Online;
+                            self.add_output(format!("❌ {}", e));
+                            self.add_output("💡 Cached pages still work. Please report this with 'dnd_tools diagnostics'".to_string());
+                        }
                         Err(e) => {
+                            self.search_status = SearchStatus::Offline;
                             self.add_output(format!("❌ Search failed: {}", e));
                             self.add_output("💡 This might be due to network issues".to_string());
                         }
@@ -1429,7 +5010,7 @@ impl App {
                 });
             }
             Err(e) => {
-                self.add_output(format!("❌ Failed to create async runtime: {}", e));
+                self.add_output(format!("❌ {}", e));
                 self.add_output("Search functionality unavailable.".to_string());
             }
         }
@@ -1443,27 +5024,135 @@ pub const SELECTED_COLOR: Color = Color::Rgb(64, 96, 192);        // Lighter blu
 pub const TEXT_COLOR: Color = Color::White;
 pub const BORDER_COLOR: Color = Color::Rgb(128, 144, 192);        // Light blue-gray
 
+/// Set while the TUI owns the terminal so the panic hook knows to stay quiet
+static TUI_ACTIVE: AtomicBool = AtomicBool::new(false);
+/// Panic message captured by the hook, shown on the error screen
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+static PANIC_HOOK: Once = Once::new();
+
+/// Restores the terminal when dropped, including while unwinding from a panic
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+fn restore_terminal() {
+    TUI_ACTIVE.store(false, Ordering::SeqCst);
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, cursor::Show);
+}
+
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if TUI_ACTIVE.load(Ordering::SeqCst) {
+                // Printing now would garble the alternate screen; run_tui reports it instead
+                if let Ok(mut last) = LAST_PANIC.lock() {
+                    *last = Some(info.to_string());
+                }
+            } else {
+                previous(info);
+            }
+        }));
+    });
+}
+
+/// Extract a readable message from a caught panic payload
+pub fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+fn show_panic_screen(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, message: &str) {
+    let text = format!("💥 The TUI crashed unexpectedly:\n\n{}\n\nPress any key to exit. Unsaved changes since the last save may be lost.", message);
+    let _ = terminal.draw(|f| {
+        let paragraph = Paragraph::new(text.as_str())
+            .style(Style::default().fg(Color::Red).bg(BACKGROUND_COLOR))
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::default()
+                    .title("Error")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Red))
+            );
+        f.render_widget(paragraph, f.area());
+    });
+    loop {
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => break,
+            Ok(_) => continue,
+            Err(_) => break,
+        }
+    }
+}
+
 pub fn run_tui(mut app: App) -> Result<App, Box<dyn std::error::Error>> {
-    // Setup terminal
+    install_panic_hook();
+
+    // Setup terminal; the guard undoes this however we leave
     enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    TUI_ACTIVE.store(true, Ordering::SeqCst);
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Run main loop
+    let outcome = std::panic::catch_unwind(AssertUnwindSafe(|| run_event_loop(&mut terminal, &mut app)));
+    match outcome {
+        Ok(result) => result?,
+        Err(payload) => {
+            let message = LAST_PANIC.lock().ok()
+                .and_then(|mut last| last.take())
+                .unwrap_or_else(|| panic_message(payload.as_ref()));
+            show_panic_screen(&mut terminal, &message);
+            restore_terminal();
+            return Err(format!("TUI panicked: {}", message).into());
+        }
+    }
+
+    Ok(app)
+}
+
+fn run_event_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App) -> io::Result<()> {
     loop {
-        terminal.draw(|f| ui(f, &mut app))?;
+        app.poll_update();
+        app.poll_prefetch();
+        app.poll_srd_download();
+        app.poll_crawl();
+        app.sync_player_view();
+        app.sync_overlay();
+        #[cfg(feature = "rest-api")]
+        app.poll_rest_api();
+        terminal.draw(|f| ui(f, app))?;
+
+        // Wake up periodically so background results (like the update check) get drawn
+        if !event::poll(std::time::Duration::from_millis(500))? {
+            continue;
+        }
 
         // Handle input
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
                 match key.code {
                     // Ctrl+Q to quit
                     KeyCode::Char('q') if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => break,
+                    // Ctrl+P opens the command palette
+                    KeyCode::Char('p') if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => app.toggle_palette(),
                     _ => app.handle_key(key.code),
                 }
             }
+            Event::Mouse(mouse) => app.handle_mouse(mouse),
+            _ => {}
         }
 
         // Initialize TUI modes when switching to them
@@ -1473,7 +5162,7 @@ pub fn run_tui(mut app: App) -> Result<App, Box<dyn std::error::Error>> {
                 // Initialize character creation TUI
                 if app.output_history.is_empty() {
                     app.add_output("🎭 Character Creation - Interactive Mode 🎭".to_string());
-                    app.add_output("Type 'help' for available commands or 'create' to start".to_string());
+                    app.add_output("Type 'help' for available commands or 'create <name>' to start".to_string());
                     app.current_state = "Character Creation Ready".to_string();
                 }
             }
@@ -1493,6 +5182,14 @@ pub fn run_tui(mut app: App) -> Result<App, Box<dyn std::error::Error>> {
                     app.current_state = "Character Deletion Ready".to_string();
                 }
             }
+            AppMode::CharacterUndeleteTUI => {
+                // Initialize character undelete TUI
+                if app.output_history.is_empty() {
+                    app.add_output("♻️  Undelete Character - Interactive Mode ♻️".to_string());
+                    app.add_output("Type 'help' for commands or 'list' to see trashed characters".to_string());
+                    app.current_state = "Undelete Ready".to_string();
+                }
+            }
             AppMode::InitiativeTrackerTUI => {
                 // Initialize initiative tracker TUI
                 if app.output_history.is_empty() {
@@ -1517,6 +5214,27 @@ pub fn run_tui(mut app: App) -> Result<App, Box<dyn std::error::Error>> {
                     app.current_state = "Dice Roller Ready".to_string();
                 }
             }
+            AppMode::SkillChallengeTUI => {
+                if app.output_history.is_empty() {
+                    app.add_output("📜 Skill Challenge - Interactive Mode 📜".to_string());
+                    app.add_output("Type 'help' for commands or 'start <successes> <failures> [dc] [name]' to begin".to_string());
+                    app.current_state = "Skill Challenge Ready".to_string();
+                }
+            }
+            AppMode::DowntimeTUI => {
+                if app.output_history.is_empty() {
+                    app.add_output("🔨 Downtime & Crafting - Interactive Mode 🔨".to_string());
+                    app.add_output("Type 'help' for commands or 'projects' to see crafting in progress".to_string());
+                    app.current_state = "Downtime Ready".to_string();
+                }
+            }
+            AppMode::EncounterTUI => {
+                if app.output_history.is_empty() {
+                    app.add_output("👹 Encounter Generator - Interactive Mode 👹".to_string());
+                    app.add_output("Type 'help' for commands or 'generate medium' to build an encounter for the party".to_string());
+                    app.current_state = "Encounter Generator Ready".to_string();
+                }
+            }
             AppMode::CombatTrackerTUI => {
                 // Initialize combat tracker if not already done
                 if app.combat_tracker.is_none() {
@@ -1541,16 +5259,7 @@ pub fn run_tui(mut app: App) -> Result<App, Box<dyn std::error::Error>> {
         }
     }
 
-    // Cleanup terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
-
-    Ok(app)
+    Ok(())
 }
 
 pub fn ui(f: &mut Frame, app: &mut App) {
@@ -1563,7 +5272,8 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         .constraints([
             Constraint::Length(3),      // Title
             Constraint::Min(10),        // Main content
-            Constraint::Length(3),      // Help/Status
+            Constraint::Length(1),      // Status bar
+            Constraint::Length(3),      // Help
         ])
         .split(size);
 
@@ -1583,8 +5293,8 @@ pub fn ui(f: &mut Frame, app: &mut App) {
     // Main content
     match app.mode {
         AppMode::CombatTrackerTUI | AppMode::SearchTUI | AppMode::CharacterCreationTUI 
-        | AppMode::CharacterDisplayTUI | AppMode::CharacterDeletionTUI | AppMode::InitiativeTrackerTUI 
-        | AppMode::NpcGeneratorTUI | AppMode::DiceTUI => {
+        | AppMode::CharacterDisplayTUI | AppMode::CharacterDeletionTUI | AppMode::CharacterUndeleteTUI | AppMode::InitiativeTrackerTUI 
+        | AppMode::NpcGeneratorTUI | AppMode::DiceTUI | AppMode::SkillChallengeTUI | AppMode::DowntimeTUI | AppMode::EncounterTUI => {
             render_terminal_content(f, chunks[1], app);
         }
         _ => {
@@ -1592,6 +5302,11 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         }
     }
 
+    // Status bar
+    let status_paragraph = Paragraph::new(app.status_bar_text())
+        .style(Style::default().fg(TEXT_COLOR).bg(MENU_COLOR));
+    f.render_widget(status_paragraph, chunks[2]);
+
     // Help text
     let help_text = get_help_text(&app.mode);
     let help_paragraph = Paragraph::new(help_text)
@@ -1603,28 +5318,82 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                 .border_style(Style::default().fg(BORDER_COLOR))
                 .style(Style::default().bg(BACKGROUND_COLOR))
         );
-    f.render_widget(help_paragraph, chunks[2]);
+    f.render_widget(help_paragraph, chunks[3]);
+
+    if app.palette.is_some() {
+        render_palette(f, size, app);
+    }
 
     // Show message if present
     if let Some(ref message) = app.message {
-        let popup_area = centered_rect(60, 20, size);
+        let popup_area = centered_rect(70, 60, size);
         f.render_widget(Clear, popup_area);
         let message_popup = Paragraph::new(message.as_str())
             .style(Style::default().fg(TEXT_COLOR))
-            .alignment(Alignment::Center)
-            .wrap(Wrap { trim: true })
+            .alignment(Alignment::Left)
+            .wrap(Wrap { trim: false })
             .block(
                 Block::default()
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(BORDER_COLOR))
                     .style(Style::default().bg(MENU_COLOR))
-                    .title("Message")
+                    .title("Message (any key to close)")
             );
         f.render_widget(message_popup, popup_area);
     }
 }
 
+/// The command palette: the query on top and the matching commands below, scrolled
+/// so the highlighted one stays in view
+fn render_palette(f: &mut Frame, size: Rect, app: &App) {
+    let Some(ref palette) = app.palette else { return };
+    let area = centered_rect(80, 70, size);
+    f.render_widget(Clear, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)])
+        .split(area);
+
+    let query = Paragraph::new(format!("🔎 {}▏", palette.query))
+        .style(Style::default().fg(TEXT_COLOR))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(BORDER_COLOR))
+                .style(Style::default().bg(MENU_COLOR))
+                .title("Commands (type to filter • ↑↓ choose • Enter run • Esc close)")
+        );
+    f.render_widget(query, chunks[0]);
+
+    let matches = palette.matches(&app.mode);
+    let height = chunks[1].height.saturating_sub(2).max(1) as usize;
+    let skip = palette.selected.saturating_sub(height - 1);
+    let list_items: Vec<ListItem> = matches.iter()
+        .enumerate()
+        .skip(skip)
+        .take(height)
+        .map(|(i, entry)| {
+            if i == palette.selected {
+                ListItem::new(format!("► {}", entry.line()))
+                    .style(Style::default().bg(SELECTED_COLOR).fg(TEXT_COLOR).add_modifier(Modifier::BOLD))
+            } else {
+                ListItem::new(format!("  {}", entry.line())).style(Style::default().fg(TEXT_COLOR))
+            }
+        })
+        .collect();
+    let list = List::new(list_items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(BORDER_COLOR))
+                .style(Style::default().bg(MENU_COLOR))
+                .title(format!("{} of {}", matches.len(), palette.entries.len()))
+        );
+    f.render_widget(list, chunks[1]);
+}
+
 fn render_main_content(f: &mut Frame, area: Rect, app: &mut App) {
+    app.menu_area = area;
     let items = app.get_menu_items();
     
     if items.is_empty() {
@@ -1687,8 +5456,38 @@ fn render_terminal_content(f: &mut Frame, area: Rect, app: &mut App) {
         ])
         .split(area);
 
-    // Render output area
-    render_output_area(f, terminal_chunks[0], app);
+    // Combat mode gets an initiative pane beside the output, and a reference pane when one is open
+    let output_area = if matches!(app.mode, AppMode::CombatTrackerTUI) && app.combat_tracker.is_some() {
+        let reference_width = if app.reference.is_some() { 40 } else { 0 };
+        let combat_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(20),                    // Output area
+                Constraint::Percentage(reference_width), // Reference pane
+                Constraint::Length(32),                 // Initiative pane
+            ])
+            .split(terminal_chunks[0]);
+        // The pacing dashboard sits under the initiative list
+        let side_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(6)])
+            .split(combat_chunks[2]);
+        render_reference_pane(f, combat_chunks[1], app);
+        render_initiative_pane(f, side_chunks[0], app);
+        render_pacing_pane(f, side_chunks[1], app);
+        combat_chunks[0]
+    } else {
+        app.initiative_area = Rect::default();
+        terminal_chunks[0]
+    };
+
+    // Render output area, or the turn banner when the table display is on
+    app.output_area = output_area;
+    if app.table_display && matches!(app.mode, AppMode::CombatTrackerTUI) && app.combat_tracker.is_some() {
+        render_turn_banner(f, output_area, app);
+    } else {
+        render_output_area(f, output_area, app);
+    }
     
     // Render input area
     render_input_area(f, terminal_chunks[1], app);
@@ -1701,8 +5500,6 @@ fn render_output_area(f: &mut Frame, area: Rect, app: &mut App) {
                 vec![
                     "⚔️ Combat Tracker - Interactive Mode ⚔️".to_string(),
                     "".to_string(),
-                    format!("State: {}", app.current_state),
-                    "".to_string(),
                     "Type 'help' for available commands".to_string(),
                     "Type 'init' to initialize combat".to_string(),
                     "".to_string(),
@@ -1712,8 +5509,6 @@ fn render_output_area(f: &mut Frame, area: Rect, app: &mut App) {
                 vec![
                     "🔍 D&D 5e Search - Interactive Mode 🔍".to_string(),
                     "".to_string(),
-                    format!("State: {}", app.current_state),
-                    "".to_string(),
                     "Type 'help' for available commands".to_string(),
                     "Type 'search <query>' to search".to_string(),
                     "Example: search fireball".to_string(),
@@ -1724,10 +5519,8 @@ fn render_output_area(f: &mut Frame, area: Rect, app: &mut App) {
                 vec![
                     "🎭 Character Creation - Interactive Mode 🎭".to_string(),
                     "".to_string(),
-                    format!("State: {}", app.current_state),
-                    "".to_string(),
                     "Type 'help' for available commands".to_string(),
-                    "Type 'create' to start character creation".to_string(),
+                    "Type 'create <name>' to start character creation".to_string(),
                     "".to_string(),
                 ]
             },
@@ -1735,8 +5528,6 @@ fn render_output_area(f: &mut Frame, area: Rect, app: &mut App) {
                 vec![
                     "📋 Character Display - Interactive Mode 📋".to_string(),
                     "".to_string(),
-                    format!("State: {}", app.current_state),
-                    "".to_string(),
                     "Type 'help' for available commands".to_string(),
                     "Type 'list' to see all characters".to_string(),
                     "".to_string(),
@@ -1746,11 +5537,18 @@ fn render_output_area(f: &mut Frame, area: Rect, app: &mut App) {
                 vec![
                     "🗑️  Character Deletion - Interactive Mode 🗑️".to_string(),
                     "".to_string(),
-                    format!("State: {}", app.current_state),
-                    "".to_string(),
                     "Type 'help' for available commands".to_string(),
                     "Type 'list' to see characters to delete".to_string(),
-                    "⚠️  Warning: Deletions are permanent!".to_string(),
+                    "Deleted characters go to the trash and can be restored".to_string(),
+                    "".to_string(),
+                ]
+            },
+            AppMode::CharacterUndeleteTUI => {
+                vec![
+                    "♻️  Undelete Character - Interactive Mode ♻️".to_string(),
+                    "".to_string(),
+                    "Type 'help' for available commands".to_string(),
+                    "Type 'list' to see trashed characters".to_string(),
                     "".to_string(),
                 ]
             },
@@ -1758,8 +5556,6 @@ fn render_output_area(f: &mut Frame, area: Rect, app: &mut App) {
                 vec![
                     "⚡ Initiative Tracker - Interactive Mode ⚡".to_string(),
                     "".to_string(),
-                    format!("State: {}", app.current_state),
-                    "".to_string(),
                     "Type 'help' for available commands".to_string(),
                     "Type 'roll <name>' to roll initiative".to_string(),
                     "".to_string(),
@@ -1769,8 +5565,6 @@ fn render_output_area(f: &mut Frame, area: Rect, app: &mut App) {
                 vec![
                     "🎭 NPC Generator - Interactive Mode 🎭".to_string(),
                     "".to_string(),
-                    format!("State: {}", app.current_state),
-                    "".to_string(),
                     "Type 'help' for available commands".to_string(),
                     "Type 'random' to generate a random NPC".to_string(),
                     "Type 'custom <race> <class>' for custom NPC".to_string(),
@@ -1781,30 +5575,49 @@ fn render_output_area(f: &mut Frame, area: Rect, app: &mut App) {
                 vec![
                     "🎲 Dice Roller - Interactive Mode 🎲".to_string(),
                     "".to_string(),
-                    format!("State: {}", app.current_state),
-                    "".to_string(),
                     "Type 'help' for available commands".to_string(),
                     "Type 'roll 1d20' to roll dice".to_string(),
                     "Examples: roll 2d6+3, roll 4d8, advantage, disadvantage".to_string(),
                     "".to_string(),
                 ]
             },
-            _ => vec![format!("State: {}", app.current_state)],
+            AppMode::SkillChallengeTUI => {
+                vec![
+                    "📜 Skill Challenge - Interactive Mode 📜".to_string(),
+                    "".to_string(),
+                    "Type 'help' for available commands".to_string(),
+                    "Type 'start 5 3 13 Negotiate with the duke' to begin".to_string(),
+                    "".to_string(),
+                ]
+            },
+            AppMode::DowntimeTUI => {
+                vec![
+                    "🔨 Downtime & Crafting - Interactive Mode 🔨".to_string(),
+                    "".to_string(),
+                    "Type 'help' for available commands".to_string(),
+                    "Type 'harvest types' to see what can be harvested".to_string(),
+                    "".to_string(),
+                ]
+            },
+            AppMode::EncounterTUI => {
+                vec![
+                    "👹 Encounter Generator - Interactive Mode 👹".to_string(),
+                    "".to_string(),
+                    "Type 'help' for available commands".to_string(),
+                    "Type 'generate 4 3 hard' for a hard fight for four level-3 characters".to_string(),
+                    "".to_string(),
+                ]
+            },
+            _ => Vec::new(),
         }
     } else {
-        // Show recent output with scrolling, but add state header
-        let mut lines = vec![format!("State: {} {}", app.current_state,
-            if let Some(ref waiting) = app.waiting_for {
-                format!("(Waiting: {})", waiting)
-            } else {
-                "".to_string()
-            }
-        ), "".to_string()];
+        // Show recent output with scrolling
+        let mut lines = Vec::new();
         
         let start_index = app.scroll_offset;
         let end_index = std::cmp::min(
             app.output_history.len(),
-            start_index + (area.height as usize).saturating_sub(4) // Leave room for state header
+            start_index + (area.height as usize).saturating_sub(2) // Leave room for borders
         );
         
         if start_index < app.output_history.len() {
@@ -1831,6 +5644,118 @@ fn render_output_area(f: &mut Frame, area: Rect, app: &mut App) {
     f.render_widget(output_paragraph, area);
 }
 
+fn render_initiative_pane(f: &mut Frame, area: Rect, app: &mut App) {
+    app.initiative_area = area;
+
+    let mut list_items: Vec<ListItem> = match app.combat_tracker {
+        Some(ref tracker) => tracker.visible_combatants()
+            .map(|(i, combatant)| {
+                let marker = if i == tracker.current_turn { "►" } else { " " };
+                let style = if i == tracker.current_turn {
+                    Style::default().bg(SELECTED_COLOR).fg(TEXT_COLOR).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(TEXT_COLOR)
+                };
+                ListItem::new(format!("{} {} ({}){}{}{}", marker, tracker.display_name(combatant), 
+                    combatant.hp_display(tracker.hide_monster_hp), combatant.movement_tag(), combatant.death_save_tag(), combatant.concentration_tag())).style(style)
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    if let Some(tracker) = app.combat_tracker.as_ref() && let (Some(row), Some(lair)) = (tracker.lair_row(), &tracker.lair) {
+        list_items.insert(row, ListItem::new(format!("  🏰 Lair ({}) - Init 20", lair)).style(Style::default().fg(TEXT_COLOR)));
+    }
+
+    let list = List::new(list_items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(BORDER_COLOR))
+                .style(Style::default().bg(MENU_COLOR))
+                .title("Initiative (click for stats)")
+        );
+
+    f.render_widget(list, area);
+}
+
+fn render_pacing_pane(f: &mut Frame, area: Rect, app: &App) {
+    let lines = app.combat_tracker.as_ref()
+        .map(|tracker| tracker.pacing.dashboard_lines())
+        .unwrap_or_default();
+    let pacing = Paragraph::new(lines.join("\n"))
+        .style(Style::default().fg(TEXT_COLOR))
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(BORDER_COLOR))
+                .style(Style::default().bg(MENU_COLOR))
+                .title("Pacing")
+        );
+    f.render_widget(pacing, area);
+}
+
+fn render_reference_pane(f: &mut Frame, area: Rect, app: &App) {
+    let Some(ref page) = app.reference else {
+        return;
+    };
+    // A stat card on top when the page could be normalized, then the page itself
+    let mut lines: Vec<String> = crate::content::Content::from_page(page)
+        .map(|c| c.card_lines().into_iter().chain([c.source().attribution()]).collect())
+        .unwrap_or_default();
+    if !lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines.extend(crate::search::condensed_lines(&page.content, crate::search::REFERENCE_LINES));
+    let reference = Paragraph::new(lines.join("\n"))
+        .style(Style::default().fg(TEXT_COLOR))
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(BORDER_COLOR))
+                .style(Style::default().bg(MENU_COLOR))
+                .title(format!("📖 {}", page.name))
+        );
+    f.render_widget(reference, area);
+}
+
+/// Lines for the table display: the current combatant's name and the round, in block letters
+pub fn turn_banner_lines(tracker: &crate::combat::CombatTracker, max_width: usize) -> Vec<String> {
+    let name = match tracker.combatants.get(tracker.current_turn) {
+        Some(c) if c.hidden => "...".to_string(),
+        Some(c) => tracker.display_name(c),
+        None => "No combatants".to_string(),
+    };
+    let mut lines = crate::banner::render_banner_wrapped(&name, max_width);
+    lines.push(String::new());
+    lines.extend(crate::banner::render_banner_wrapped(&format!("Round {}", tracker.round_number), max_width));
+    lines
+}
+
+fn render_turn_banner(f: &mut Frame, area: Rect, app: &App) {
+    let Some(ref tracker) = app.combat_tracker else {
+        return;
+    };
+    let inner_width = area.width.saturating_sub(2) as usize;
+    let inner_height = area.height.saturating_sub(2) as usize;
+    let mut lines = turn_banner_lines(tracker, inner_width);
+    let top_padding = inner_height.saturating_sub(lines.len()) / 2;
+    lines.splice(0..0, std::iter::repeat_n(String::new(), top_padding));
+
+    let banner = Paragraph::new(lines.join("\n"))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(SELECTED_COLOR).add_modifier(Modifier::BOLD))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(BORDER_COLOR))
+                .style(Style::default().bg(MENU_COLOR))
+                .title("Now Up (type 'display' to return to the log)")
+        );
+    f.render_widget(banner, area);
+}
+
 fn render_input_area(f: &mut Frame, area: Rect, app: &mut App) {
     let input_text = format!("> {}", app.input_buffer);
     
@@ -1858,12 +5783,16 @@ fn get_title_for_mode(mode: &AppMode) -> Text {
         AppMode::CharacterDisplayTUI => "📋 Character Display (Interactive) 📋",
         AppMode::CharacterDeletion => "🗑️  Character Deletion 🗑️",
         AppMode::CharacterDeletionTUI => "🗑️  Character Deletion (Interactive) 🗑️",
+        AppMode::CharacterUndeleteTUI => "♻️  Undelete Character (Interactive) ♻️",
         AppMode::InitiativeTracker => "⚡ Initiative Tracker ⚡",
         AppMode::InitiativeTrackerTUI => "⚡ Initiative Tracker (Interactive) ⚡",
         AppMode::NpcGenerator => "🎭 NPC Generator 🎭",
         AppMode::NpcGeneratorTUI => "🎭 NPC Generator (Interactive) 🎭",
         AppMode::Dice => "🎲 Dice Roller 🎲",
         AppMode::DiceTUI => "🎲 Dice Roller (Interactive) 🎲",
+        AppMode::SkillChallengeTUI => "📜 Skill Challenge (Interactive) 📜",
+        AppMode::DowntimeTUI => "🔨 Downtime & Crafting (Interactive) 🔨",
+        AppMode::EncounterTUI => "👹 Encounter Generator (Interactive) 👹",
         AppMode::CombatTracker => "⚔️  Combat Tracker ⚔️",
         AppMode::CombatTrackerTUI => "⚔️  Combat Tracker (Interactive) ⚔️",
         AppMode::Search => "🔍 D&D 5e Search 🔍",
@@ -1876,16 +5805,27 @@ fn get_title_for_mode(mode: &AppMode) -> Text {
 fn get_help_text(mode: &AppMode) -> Text {
     let help = match mode {
         AppMode::MainMenu | AppMode::CharactersMenu | AppMode::ToolsMenu => 
-            "↑↓ Navigate • Enter Select • Esc Back • Ctrl+Q Quit",
+            "↑↓ Navigate • Enter/Click Select • Esc Back • Ctrl+P Commands • Ctrl+Q Quit",
         AppMode::CombatTrackerTUI | AppMode::SearchTUI | AppMode::CharacterCreationTUI 
-        | AppMode::CharacterDisplayTUI | AppMode::CharacterDeletionTUI | AppMode::InitiativeTrackerTUI 
-        | AppMode::NpcGeneratorTUI | AppMode::DiceTUI => 
-            "Type commands • Enter Execute • ↑↓ History • PgUp/PgDn Scroll • Esc Back • Ctrl+Q Quit",
+        | AppMode::CharacterDisplayTUI | AppMode::CharacterDeletionTUI | AppMode::CharacterUndeleteTUI | AppMode::InitiativeTrackerTUI 
+        | AppMode::NpcGeneratorTUI | AppMode::DiceTUI | AppMode::SkillChallengeTUI | AppMode::DowntimeTUI | AppMode::EncounterTUI => 
+            "Type commands • Enter Execute • ↑↓ History • PgUp/PgDn/Wheel Scroll • Esc Back • Ctrl+P Commands • Ctrl+Q Quit",
         _ => "Press any key to continue...",
     };
     Text::from(help)
 }
 
+/// Map a click position to a row index inside a bordered widget, if it lands on one
+fn row_in_area(area: Rect, column: u16, row: u16) -> Option<usize> {
+    let inside_x = column > area.x && column + 1 < area.x + area.width;
+    let inside_y = row > area.y && row + 1 < area.y + area.height;
+    if inside_x && inside_y {
+        Some((row - area.y - 1) as usize)
+    } else {
+        None
+    }
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -1911,8 +5851,8 @@ fn npc_randomizer_tui_mode() {
     super::npc_randomizer_mode();
 }
 
-fn combat_tracker_tui_mode() {
-    super::combat_tracker_mode();
+fn combat_tracker_tui_mode(characters: &CharacterStore) {
+    super::combat_tracker_mode(characters);
 }
 
 fn search_tui_mode() {
@@ -0,0 +1,104 @@
+use crate::error_handling::{AppError, Result, ResultExt};
+use std::sync::mpsc::{self, Receiver};
+
+pub const RELEASES_URL: &str = "https://api.github.com/repos/brettknowlton/dnd_tools/releases/latest";
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The latest published release
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseInfo {
+    pub version: String,
+    pub changelog: String,
+    pub url: String,
+}
+
+/// Parse "v1.2.3" / "1.2" into comparable parts; pre-release suffixes are ignored
+pub fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.trim().trim_start_matches(['v', 'V']);
+    let core = core.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    Some((major, minor, patch))
+}
+
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// Pull the fields we need out of a GitHub "latest release" response
+pub fn parse_release(json: &str) -> Result<ReleaseInfo> {
+    let value: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| AppError::ParseError(format!("Invalid release data: {}", e)))?;
+    let version = value["tag_name"].as_str()
+        .ok_or_else(|| AppError::ParseError("Release has no tag_name".to_string()))?;
+    Ok(ReleaseInfo {
+        version: version.to_string(),
+        changelog: value["body"].as_str().unwrap_or("No changelog provided.").trim().to_string(),
+        url: value["html_url"].as_str().unwrap_or_default().to_string(),
+    })
+}
+
+/// Update checks are skipped with --offline / --no-update-check or DND_TOOLS_NO_UPDATE_CHECK
+pub fn update_checks_enabled(args: &[String]) -> bool {
+    let opted_out_by_flag = args.iter().any(|arg| arg == "--offline" || arg == "--no-update-check");
+    let opted_out_by_env = std::env::var("DND_TOOLS_NO_UPDATE_CHECK")
+        .is_ok_and(|v| !v.is_empty() && v != "0");
+    !opted_out_by_flag && !opted_out_by_env
+}
+
+pub fn fetch_latest_release() -> Result<ReleaseInfo> {
+    crate::runtime::block_on(async {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(5))
+            .user_agent(format!("dnd_tools/{}", CURRENT_VERSION))
+            .build()
+            .context("Failed to create HTTP client")?;
+        log::debug!("GET {}", RELEASES_URL);
+        let response = client.get(RELEASES_URL).send().await.context("Update check failed")?;
+        if !response.status().is_success() {
+            return Err(AppError::NetworkError(format!("Update check returned {}", response.status())));
+        }
+        let body = response.text().await.context("Failed to read release data")?;
+        parse_release(&body)
+    })?
+}
+
+/// Check in the background; the receiver yields a release only if it is newer than this build
+pub fn spawn_update_check() -> Receiver<ReleaseInfo> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        match fetch_latest_release() {
+            Ok(release) if is_newer(&release.version, CURRENT_VERSION) => {
+                log::info!("Update available: {}", release.version);
+                let _ = tx.send(release);
+            }
+            Ok(release) => log::debug!("Up to date (latest release {})", release.version),
+            Err(e) => log::debug!("Update check skipped: {}", e),
+        }
+    });
+    rx
+}
+
+/// `dnd_tools changelog`: print the notes for the latest release
+pub fn run_changelog_command() {
+    match fetch_latest_release() {
+        Ok(release) => {
+            let status = if is_newer(&release.version, CURRENT_VERSION) {
+                format!("newer than your version ({})", CURRENT_VERSION)
+            } else {
+                "you're up to date".to_string()
+            };
+            println!("📰 dnd_tools {} — {}", release.version, status);
+            if !release.url.is_empty() {
+                println!("{}", release.url);
+            }
+            println!("\n{}", release.changelog);
+        }
+        Err(e) => println!("❌ Could not fetch the changelog: {}", e),
+    }
+}
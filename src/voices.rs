@@ -0,0 +1,143 @@
+use crate::error_handling::{Result, ResultExt};
+use rand::Rng;
+use rand::seq::IndexedRandom;
+use std::{fs, path::{Path, PathBuf}};
+
+/// Line prefix the voice is stored under on a saved NPC sheet
+const SHEET_PREFIX: &str = "Voice: ";
+
+const PITCHES: &[&str] = &[
+    "deep and booming", "low and gravelly", "husky", "breathy", "reedy", "nasal",
+    "bright and high", "squeaky", "warm and mellow", "thin and wavering",
+];
+const PACES: &[&str] = &[
+    "rapid-fire", "slow and deliberate", "halting, with long pauses", "sing-song",
+    "clipped and precise", "meandering", "breathless, running sentences together", "measured, like a sermon",
+];
+const ACCENTS: &[&str] = &[
+    "rolled r's", "dropped h's", "drawn-out vowels", "crisp, courtly diction",
+    "a rustic drawl", "a whistle on every s", "hard consonants", "a lilting rise at the end of sentences",
+    "swallowed word endings", "an old-fashioned, formal cadence",
+];
+const VERBAL_TICS: &[&str] = &[
+    "clears their throat before every answer", "ends questions with \"eh?\"", "hums while thinking",
+    "repeats the last word someone said", "laughs nervously after jokes nobody made",
+    "says \"mark my words\" a lot", "trails off mid-sentence", "whispers the important parts",
+    "sniffs loudly when doubtful", "taps a finger with every point",
+];
+const VOCABULARY_QUIRKS: &[&str] = &[
+    "uses nautical terms for everything", "never uses contractions", "calls everyone \"friend\"",
+    "speaks of themselves in the third person", "mangles long words", "peppers speech with old proverbs",
+    "swears by an obscure saint", "counts things off on their fingers out loud",
+    "uses trade jargon from their old job", "gives everyone a nickname",
+];
+
+/// A voice to play an improvised NPC with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Voice {
+    pub pitch: String,
+    pub pace: String,
+    pub accent: String,
+    pub tic: String,
+    pub quirk: String,
+}
+
+impl Voice {
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let pick = |table: &[&str], rng: &mut R| table.choose(rng).copied().unwrap_or_default().to_string();
+        Voice {
+            pitch: pick(PITCHES, rng),
+            pace: pick(PACES, rng),
+            accent: pick(ACCENTS, rng),
+            tic: pick(VERBAL_TICS, rng),
+            quirk: pick(VOCABULARY_QUIRKS, rng),
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format!("  Pitch: {}", self.pitch),
+            format!("  Pace: {}", self.pace),
+            format!("  Accent: {}", self.accent),
+            format!("  Verbal tic: {}", self.tic),
+            format!("  Vocabulary: {}", self.quirk),
+        ]
+    }
+
+    /// One line for an NPC sheet, readable by `parse_sheet_line`
+    pub fn sheet_line(&self) -> String {
+        format!("{}{} | {} | {} | {} | {}", SHEET_PREFIX, self.pitch, self.pace, self.accent, self.tic, self.quirk)
+    }
+
+    pub fn parse_sheet_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.strip_prefix(SHEET_PREFIX)?.split(" | ").collect();
+        let [pitch, pace, accent, tic, quirk] = fields.as_slice() else { return None };
+        Some(Voice {
+            pitch: pitch.to_string(),
+            pace: pace.to_string(),
+            accent: accent.to_string(),
+            tic: tic.to_string(),
+            quirk: quirk.to_string(),
+        })
+    }
+}
+
+fn sheet_path(npcs_dir: &Path, name: &str) -> PathBuf {
+    npcs_dir.join(format!("{}.txt", name))
+}
+
+/// The voice saved on an NPC's sheet, if it has one
+pub fn saved_voice_in(npcs_dir: &Path, name: &str) -> Option<Voice> {
+    fs::read_to_string(sheet_path(npcs_dir, name)).ok()?
+        .lines()
+        .find_map(Voice::parse_sheet_line)
+}
+
+/// Put the voice on the NPC's sheet, replacing any voice already there. An NPC
+/// without a sheet gets a minimal one.
+pub fn save_voice_in(npcs_dir: &Path, name: &str, voice: &Voice) -> Result<PathBuf> {
+    fs::create_dir_all(npcs_dir).context("Failed to create npcs directory")?;
+    let path = sheet_path(npcs_dir, name);
+    let existing = fs::read_to_string(&path).unwrap_or_else(|_| format!("Name: {}\nType: NPC\n", name));
+    let mut lines: Vec<String> = existing.lines()
+        .filter(|line| !line.starts_with(SHEET_PREFIX))
+        .map(str::to_string)
+        .collect();
+    lines.push(voice.sheet_line());
+    fs::write(&path, lines.join("\n") + "\n").context(format!("Failed to write {}", path.display()))?;
+    log::info!("Saved a voice for {} to {}", name, path.display());
+    Ok(path)
+}
+
+/// `voice` suggests a voice, `voice save <npc>` keeps the last suggestion on the
+/// NPC's sheet, and `voice <npc>` recalls a saved one
+pub fn voice_command<R: Rng + ?Sized>(npcs_dir: &Path, args: &[&str], last: &mut Option<Voice>, rng: &mut R) -> Vec<String> {
+    match args {
+        [] => {
+            let voice = Voice::random(rng);
+            let mut lines = vec!["🗣️ Try this voice:".to_string()];
+            lines.extend(voice.lines());
+            lines.push("'voice save <npc>' keeps it on the NPC's sheet for next session".to_string());
+            *last = Some(voice);
+            lines
+        }
+        [save, name @ ..] if save.eq_ignore_ascii_case("save") && !name.is_empty() => {
+            let name = name.join(" ");
+            let message = match last.as_ref() {
+                Some(voice) => match save_voice_in(npcs_dir, &name, voice) {
+                    Ok(path) => format!("💾 Saved the voice to {}", path.display()),
+                    Err(e) => format!("❌ Could not save the voice: {}", e),
+                },
+                None => "No voice suggested yet. Type 'voice' first.".to_string(),
+            };
+            vec![message]
+        }
+        name => {
+            let name = name.join(" ");
+            match saved_voice_in(npcs_dir, &name) {
+                Some(voice) => std::iter::once(format!("🗣️ {}'s voice:", name)).chain(voice.lines()).collect(),
+                None => vec![format!("No voice saved for {}. 'voice' suggests one.", name)],
+            }
+        }
+    }
+}
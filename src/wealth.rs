@@ -0,0 +1,170 @@
+use crate::character::Character;
+use regex::Regex;
+
+/// Prices in copper for the gear characters usually carry (SRD equipment tables)
+const PRICES_CP: &[(&str, u64)] = &[
+    // Simple and martial weapons
+    ("club", 10), ("dagger", 200), ("greatclub", 20), ("handaxe", 500), ("javelin", 50), ("light hammer", 200),
+    ("mace", 500), ("quarterstaff", 20), ("sickle", 100), ("spear", 100), ("light crossbow", 2500), ("dart", 5),
+    ("shortbow", 2500), ("sling", 10), ("battleaxe", 1000), ("flail", 1000), ("glaive", 2000), ("greataxe", 3000),
+    ("greatsword", 5000), ("halberd", 2000), ("lance", 1000), ("longsword", 1500), ("maul", 1000),
+    ("morningstar", 1500), ("pike", 500), ("rapier", 2500), ("scimitar", 2500), ("shortsword", 1000),
+    ("trident", 500), ("war pick", 500), ("warhammer", 1500), ("whip", 200), ("blowgun", 1000),
+    ("hand crossbow", 7500), ("heavy crossbow", 5000), ("longbow", 5000), ("net", 100),
+    // Ammunition, each
+    ("arrow", 5), ("bolt", 5), ("needle", 2), ("bullet", 0),
+    // Armor and shields
+    ("padded armor", 500), ("leather armor", 1000), ("studded leather armor", 4500), ("hide armor", 1000),
+    ("chain shirt", 5000), ("scale mail", 5000), ("breastplate", 40000), ("half plate", 75000),
+    ("ring mail", 3000), ("chain mail", 7500), ("splint armor", 20000), ("plate armor", 150000),
+    ("shield", 1000), ("wooden shield", 1000),
+    // Packs, tools and focuses
+    ("burglar's pack", 1600), ("diplomat's pack", 3900), ("dungeoneer's pack", 1200), ("entertainer's pack", 4000),
+    ("explorer's pack", 1000), ("priest's pack", 1900), ("scholar's pack", 4000), ("thieves' tools", 2500),
+    ("holy symbol", 500), ("druidic focus", 100), ("arcane focus", 1000), ("component pouch", 2500),
+    ("spellbook", 5000), ("quiver", 100), ("lute", 3500), ("flute", 200), ("healer's kit", 500),
+    ("potion of healing", 5000), ("backpack", 200), ("bedroll", 100), ("rope", 100), ("torch", 1),
+    ("rations", 50), ("waterskin", 20), ("tinderbox", 50), ("crowbar", 200), ("lantern", 500),
+];
+
+/// Copper in each kind of coin
+const COINS_CP: [(&str, u64); 5] = [("pp", 1000), ("gp", 100), ("ep", 50), ("sp", 10), ("cp", 1)];
+
+/// "120 gp", "35 sp": an inventory entry that is only coins, in copper
+pub fn coins_cp(entry: &str) -> Option<u64> {
+    let coins = Regex::new(r"^(\d[\d,]*)\s*(pp|gp|ep|sp|cp)$").expect("valid coin regex");
+    let entry = entry.trim().to_lowercase();
+    let caps = coins.captures(&entry)?;
+    let count: u64 = caps[1].replace(',', "").parse().ok()?;
+    COINS_CP.iter().find(|(coin, _)| *coin == &caps[2]).map(|(_, cp)| count * cp)
+}
+
+/// The price of one item by name, singular or plural: the table first, then an
+/// equipment page in the search cache
+fn price_cp(name: &str) -> Option<u64> {
+    let name = name.trim().to_lowercase();
+    let singular = [name.as_str(), name.strip_suffix("es").unwrap_or(&name), name.strip_suffix('s').unwrap_or(&name)];
+    singular.iter()
+        .find_map(|candidate| PRICES_CP.iter().find(|(item, _)| item == candidate).map(|(_, cp)| *cp))
+        .or_else(|| {
+            let page = crate::search::read_cached_page(std::path::Path::new(crate::search::CACHE_DIR), "equipment", &name)?;
+            crate::content::Item::from_page(&page).cost_gp.map(|gp| gp as u64 * 100)
+        })
+}
+
+/// What a gear entry is worth in copper: a value written on it ("Diamond (300 gp)"),
+/// or the count ("2 Handaxes") times the price. None when the price isn't known.
+pub fn gear_cp(entry: &str) -> Option<u64> {
+    if let Some(gp) = crate::components::item_value_gp(entry) {
+        return Some(gp as u64 * 100);
+    }
+    let (count, name) = match entry.trim().split_once(' ') {
+        Some((count, name)) if count.parse::<u64>().is_ok() => (count.parse().unwrap_or(1), name),
+        _ => (1, entry.trim()),
+    };
+    price_cp(name).map(|cp| cp * count)
+}
+
+/// Expected wealth in gold by level, from the DMG's guidance for starting above 1st level:
+/// the class's starting gold up to 4th, then 500 gp + 1d10 × 25 gp, 5,000 gp + 1d10 × 250 gp
+/// from 11th and 20,000 gp + 1d10 × 250 gp from 17th, with the dice at their average
+pub fn expected_wealth_gp(level: u8, class: Option<&str>) -> u64 {
+    match level {
+        0..=4 => class.and_then(crate::starting_equipment::class_kit)
+            .map_or(100, |kit| kit.gold_dice as u64 * 5 * kit.gold_multiplier as u64 / 2),
+        5..=10 => 500 + 55 * 25 / 10,
+        11..=16 => 5000 + 55 * 250 / 10,
+        _ => 20000 + 55 * 250 / 10,
+    }
+}
+
+/// One character's coins and gear against the guideline for their level
+#[derive(Debug, Clone, PartialEq)]
+pub struct WealthAudit {
+    pub name: String,
+    pub level: u8,
+    pub class: Option<String>,
+    pub coins_cp: u64,
+    pub gear_cp: u64,
+    /// Entries with no known price, such as magic items, left out of the total
+    pub unpriced: Vec<String>,
+    pub expected_gp: u64,
+}
+
+impl WealthAudit {
+    pub fn of(character: &Character) -> Self {
+        let mut coins = 0;
+        let mut gear = 0;
+        let mut unpriced = Vec::new();
+        for entry in &character.inventory {
+            match (coins_cp(entry), gear_cp(entry)) {
+                (Some(cp), _) => coins += cp,
+                (None, Some(cp)) => gear += cp,
+                (None, None) => unpriced.push(entry.clone()),
+            }
+        }
+        // Arrows and bolts are counted with the ammunition rather than the inventory
+        gear += character.ammo.iter().filter_map(|ammo| price_cp(&ammo.kind).map(|cp| cp * ammo.count as u64)).sum::<u64>();
+        let level = character.level.unwrap_or(1);
+        WealthAudit {
+            name: character.name.clone(),
+            level,
+            class: character.class.clone(),
+            coins_cp: coins,
+            gear_cp: gear,
+            unpriced,
+            expected_gp: expected_wealth_gp(level, character.class.as_deref()),
+        }
+    }
+
+    pub fn total_gp(&self) -> u64 {
+        (self.coins_cp + self.gear_cp) / 100
+    }
+
+    /// Total wealth as a share of the guideline, in percent
+    pub fn percent(&self) -> u64 {
+        self.total_gp() * 100 / self.expected_gp.max(1)
+    }
+
+    /// Under half or over twice the guideline
+    pub fn flag(&self) -> Option<&'static str> {
+        match self.percent() {
+            0..50 => Some("⬇️ well below"),
+            201.. => Some("⬆️ well above"),
+            _ => None,
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let class = self.class.as_ref().map(|c| format!(" {}", c)).unwrap_or_default();
+        let verdict = self.flag().unwrap_or("✅ on track");
+        let mut lines = vec![format!("  {} (level {}{}): {} gp (coins {} gp, gear {} gp) of ~{} gp expected, {}% {}",
+            self.name, self.level, class, self.total_gp(), self.coins_cp / 100, self.gear_cp / 100, self.expected_gp, self.percent(), verdict)];
+        if !self.unpriced.is_empty() {
+            lines.push(format!("    Not priced: {}", self.unpriced.join(", ")));
+        }
+        lines
+    }
+}
+
+/// Every living character's wealth against the guideline, with the outliers called out
+pub fn wealth_report(characters: &[Character]) -> Vec<String> {
+    let audits: Vec<WealthAudit> = characters.iter().filter(|c| c.death.is_none()).map(WealthAudit::of).collect();
+    if audits.is_empty() {
+        return vec!["No characters to audit".to_string()];
+    }
+    let mut lines = vec!["💰 Wealth by level (DMG starting wealth for the level, dice at their average):".to_string()];
+    lines.extend(audits.iter().flat_map(WealthAudit::lines));
+    for audit in audits.iter().filter(|a| a.flag().is_some()) {
+        let advice = if audit.percent() < 50 { "steer some loot their way" } else { "go easy on their share of treasure" };
+        lines.push(format!("⚠️ {} is {} the guideline for level {}; {}", audit.name, audit.flag().unwrap_or_default(), audit.level, advice));
+    }
+    if let (Some(richest), Some(poorest)) = (audits.iter().max_by_key(|a| a.percent()), audits.iter().min_by_key(|a| a.percent()))
+        && audits.len() > 1 && richest.percent() > poorest.percent().max(1) * 3 {
+        lines.push(format!("⚖️ {} has over three times the share of {} for their level", richest.name, poorest.name));
+    }
+    if audits.iter().any(|a| !a.unpriced.is_empty()) {
+        lines.push("Entries without a price (like magic items) aren't counted; write a value on them, e.g. 'Ruby (500 gp)'".to_string());
+    }
+    lines
+}
@@ -0,0 +1,200 @@
+use crate::error_handling::{AppError, Result, ResultExt};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// The current weather is saved with the rest of the campaign
+pub const WEATHER_FILE: &str = "campaign/weather.ron";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum WeatherCondition {
+    LightRain,
+    HeavyRain,
+    LightSnow,
+    HeavySnow,
+    Fog,
+    LightWind,
+    StrongWind,
+    ExtremeHeat,
+    ExtremeCold,
+}
+
+const CONDITIONS: &[(WeatherCondition, &str, &str)] = &[
+    (WeatherCondition::LightRain, "light rain", "🌦️"),
+    (WeatherCondition::HeavyRain, "heavy rain", "🌧️"),
+    (WeatherCondition::LightSnow, "light snow", "🌨️"),
+    (WeatherCondition::HeavySnow, "heavy snow", "❄️"),
+    (WeatherCondition::Fog, "fog", "🌫️"),
+    (WeatherCondition::LightWind, "light wind", "🍃"),
+    (WeatherCondition::StrongWind, "strong wind", "💨"),
+    (WeatherCondition::ExtremeHeat, "extreme heat", "🥵"),
+    (WeatherCondition::ExtremeCold, "extreme cold", "🥶"),
+];
+
+/// Where a weather rule comes up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleScope {
+    /// Attacks with a ranged weapon
+    Ranged,
+    /// A combatant taking to the air
+    Flying,
+    /// Anything else in a fight
+    Combat,
+    Travel,
+}
+
+/// The mechanical effects of weather (DMG ch. 5, weather and wilderness hazards)
+const RULES: &[(WeatherCondition, RuleScope, &str)] = &[
+    (WeatherCondition::HeavyRain, RuleScope::Combat, "The area is lightly obscured: disadvantage on Wisdom (Perception) checks that rely on sight"),
+    (WeatherCondition::HeavyRain, RuleScope::Combat, "Open flames such as torches go out"),
+    (WeatherCondition::HeavyRain, RuleScope::Travel, "Disadvantage on Wisdom (Perception) checks that rely on sight, so the party is easier to surprise"),
+    (WeatherCondition::HeavyRain, RuleScope::Travel, "Campfires and torches left in the open go out"),
+    (WeatherCondition::LightSnow, RuleScope::Combat, "Snow on the ground is difficult terrain"),
+    (WeatherCondition::LightSnow, RuleScope::Travel, "Snow on the ground is difficult terrain: the party covers half the usual distance"),
+    (WeatherCondition::HeavySnow, RuleScope::Combat, "The area is lightly obscured: disadvantage on Wisdom (Perception) checks that rely on sight"),
+    (WeatherCondition::HeavySnow, RuleScope::Combat, "Snow on the ground is difficult terrain"),
+    (WeatherCondition::HeavySnow, RuleScope::Travel, "Disadvantage on Wisdom (Perception) checks that rely on sight"),
+    (WeatherCondition::HeavySnow, RuleScope::Travel, "Deep snow is difficult terrain: the party covers half the usual distance"),
+    (WeatherCondition::Fog, RuleScope::Combat, "The area is heavily obscured: a creature trying to see into it is effectively blinded"),
+    (WeatherCondition::Fog, RuleScope::Ranged, "In fog a target more than a few feet away is unseen: the attack has disadvantage"),
+    (WeatherCondition::Fog, RuleScope::Travel, "Travelers see only a few feet ahead: disadvantage on Perception that relies on sight and on checks to stay on course"),
+    (WeatherCondition::StrongWind, RuleScope::Ranged, "Strong wind: disadvantage on ranged weapon attack rolls"),
+    (WeatherCondition::StrongWind, RuleScope::Combat, "Disadvantage on Wisdom (Perception) checks that rely on hearing"),
+    (WeatherCondition::StrongWind, RuleScope::Combat, "Open flames go out and fog disperses"),
+    (WeatherCondition::StrongWind, RuleScope::Flying, "Strong wind: a creature flying by nonmagical means must land at the end of its turn or fall"),
+    (WeatherCondition::StrongWind, RuleScope::Travel, "Flying by nonmagical means is nearly impossible"),
+    (WeatherCondition::StrongWind, RuleScope::Travel, "Disadvantage on Wisdom (Perception) checks that rely on hearing"),
+    (WeatherCondition::ExtremeHeat, RuleScope::Travel, "Each hour without drinkable water: DC 5 Constitution save (+1 per extra hour) or gain a level of exhaustion"),
+    (WeatherCondition::ExtremeHeat, RuleScope::Travel, "Medium or heavy armor, or heavy clothing, gives disadvantage on that save"),
+    (WeatherCondition::ExtremeHeat, RuleScope::Travel, "Everyone needs twice the usual water"),
+    (WeatherCondition::ExtremeCold, RuleScope::Travel, "Each hour: DC 10 Constitution save or gain a level of exhaustion. Cold resistance or cold weather gear passes automatically"),
+];
+
+impl WeatherCondition {
+    /// "heavy rain", "heavy-rain", "Heavy Rain"
+    pub fn parse(text: &str) -> Option<WeatherCondition> {
+        let text = text.trim().to_lowercase().replace(['-', '_'], " ");
+        CONDITIONS.iter().find(|(_, name, _)| *name == text).map(|(condition, ..)| *condition)
+    }
+
+    pub fn name(&self) -> &'static str {
+        CONDITIONS.iter().find(|(c, ..)| c == self).map(|(_, name, _)| *name).unwrap_or_default()
+    }
+
+    pub fn icon(&self) -> &'static str {
+        CONDITIONS.iter().find(|(c, ..)| c == self).map(|(.., icon)| *icon).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Weather {
+    pub conditions: Vec<WeatherCondition>,
+}
+
+impl Weather {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        ron::de::from_str(&contents).map_err(|e| AppError::ParseError(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create campaign folder")?;
+        }
+        let serialized = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| AppError::ParseError(e.to_string()))?;
+        fs::write(path, serialized).context(format!("Failed to write {}", path.display()))
+    }
+
+    /// Replace the weather with a comma-separated list, e.g. "heavy rain, strong wind"
+    pub fn set(&mut self, text: &str) -> Result<()> {
+        let mut conditions = Vec::new();
+        for part in text.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let condition = WeatherCondition::parse(part).ok_or_else(|| AppError::ValidationError(format!(
+                "Unknown weather '{}' ({})", part, CONDITIONS.iter().map(|(_, name, _)| *name).collect::<Vec<_>>().join(", "))))?;
+            if !conditions.contains(&condition) {
+                conditions.push(condition);
+            }
+        }
+        conditions.sort();
+        self.conditions = conditions;
+        Ok(())
+    }
+
+    /// Roll wind and precipitation on the DMG's d20 tables, keeping any extreme temperature.
+    /// Precipitation falls as snow in extreme cold.
+    pub fn roll<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let cold = self.conditions.contains(&WeatherCondition::ExtremeCold);
+        self.conditions.retain(|c| matches!(c, WeatherCondition::ExtremeHeat | WeatherCondition::ExtremeCold));
+        match rng.random_range(1..=20) {
+            13..=17 => self.conditions.push(WeatherCondition::LightWind),
+            18..=20 => self.conditions.push(WeatherCondition::StrongWind),
+            _ => {}
+        }
+        match (rng.random_range(1..=20), cold) {
+            (13..=17, false) => self.conditions.push(WeatherCondition::LightRain),
+            (13..=17, true) => self.conditions.push(WeatherCondition::LightSnow),
+            (18..=20, false) => self.conditions.push(WeatherCondition::HeavyRain),
+            (18..=20, true) => self.conditions.push(WeatherCondition::HeavySnow),
+            _ => {}
+        }
+        self.conditions.sort();
+    }
+
+    pub fn is(&self, condition: WeatherCondition) -> bool {
+        self.conditions.contains(&condition)
+    }
+
+    /// "🌧️ heavy rain, 💨 strong wind", or "clear"
+    pub fn describe(&self) -> String {
+        if self.conditions.is_empty() {
+            return "☀️ clear".to_string();
+        }
+        self.conditions.iter().map(|c| format!("{} {}", c.icon(), c.name())).collect::<Vec<_>>().join(", ")
+    }
+
+    /// The rules the current weather brings into play, marked with the condition's icon
+    pub fn reminders(&self, scope: RuleScope) -> Vec<String> {
+        RULES.iter()
+            .filter(|(condition, rule_scope, _)| *rule_scope == scope && self.is(*condition))
+            .map(|(condition, _, rule)| format!("{} {}", condition.icon(), rule))
+            .collect()
+    }
+}
+
+/// `weather` shows the weather and its rules, `weather roll` rolls new weather,
+/// `weather clear` clears it, and `weather <conditions>` sets it. Returns the
+/// lines to show and whether the weather changed.
+pub fn weather_command<R: Rng + ?Sized>(weather: &mut Weather, args: &[&str], rng: &mut R) -> (Vec<String>, bool) {
+    let changed = match args.first().map(|a| a.to_lowercase()).as_deref() {
+        None => false,
+        Some("roll") => {
+            weather.roll(rng);
+            true
+        }
+        Some("clear") => {
+            weather.conditions.clear();
+            true
+        }
+        Some(_) => match weather.set(&args.join(" ")) {
+            Ok(()) => true,
+            Err(e) => return (vec![format!("❌ {}", e), "Usage: weather [roll|clear|<condition>, <condition>...]".to_string()], false),
+        },
+    };
+    let mut lines = vec![format!("Weather: {}", weather.describe())];
+    let sections = [("In combat", RuleScope::Combat), ("Ranged attacks", RuleScope::Ranged), ("Flying", RuleScope::Flying), ("Travel", RuleScope::Travel)];
+    for (title, scope) in sections {
+        let reminders = weather.reminders(scope);
+        if !reminders.is_empty() {
+            lines.push(format!("{}:", title));
+            lines.extend(reminders.into_iter().map(|r| format!("  {}", r)));
+        }
+    }
+    if !weather.conditions.is_empty() && lines.len() == 1 {
+        lines.push("No mechanical effects".to_string());
+    }
+    (lines, changed)
+}
@@ -0,0 +1,173 @@
+use crate::combat::CombatTracker;
+use crate::error_handling::{AppError, Result};
+
+/// (challenge rating, XP for defeating a creature of that rating)
+const CR_XP: &[(&str, u32)] = &[
+    ("0", 10), ("1/8", 25), ("1/4", 50), ("1/2", 100),
+    ("1", 200), ("2", 450), ("3", 700), ("4", 1_100), ("5", 1_800),
+    ("6", 2_300), ("7", 2_900), ("8", 3_900), ("9", 5_000), ("10", 5_900),
+    ("11", 7_200), ("12", 8_400), ("13", 10_000), ("14", 11_500), ("15", 13_000),
+    ("16", 15_000), ("17", 18_000), ("18", 20_000), ("19", 22_000), ("20", 25_000),
+    ("21", 33_000), ("22", 41_000), ("23", 50_000), ("24", 62_000), ("25", 75_000),
+    ("26", 90_000), ("27", 105_000), ("28", 120_000), ("29", 135_000), ("30", 155_000),
+];
+
+/// Encounter multipliers in order; the party size shifts which one applies
+const MULTIPLIERS: &[f64] = &[0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 4.0, 5.0];
+
+/// Accepts "5", "1/4", or the decimals "0.25" / ".5" that some stat blocks use
+fn normalize_cr(cr: &str) -> String {
+    match cr.trim().trim_start_matches("cr").trim() {
+        "0.125" | ".125" => "1/8".to_string(),
+        "0.25" | ".25" => "1/4".to_string(),
+        "0.5" | ".5" => "1/2".to_string(),
+        other => other.to_string(),
+    }
+}
+
+pub fn xp_for_cr(cr: &str) -> Option<u32> {
+    let cr = normalize_cr(&cr.to_lowercase());
+    CR_XP.iter().find(|(rating, _)| *rating == cr).map(|(_, xp)| *xp)
+}
+
+/// A challenge rating as a number, so "1/4" sorts below "1"
+pub fn cr_value(cr: &str) -> Option<f64> {
+    let cr = normalize_cr(&cr.to_lowercase());
+    xp_for_cr(&cr)?;
+    match cr.split_once('/') {
+        Some((num, den)) => Some(num.parse::<f64>().ok()? / den.parse::<f64>().ok()?),
+        None => cr.parse().ok(),
+    }
+}
+
+/// Back from a number to how a CR is written
+pub fn cr_label(cr: f64) -> String {
+    CR_XP.iter()
+        .map(|(rating, _)| *rating)
+        .find(|rating| cr_value(rating) == Some(cr))
+        .map(str::to_string)
+        .unwrap_or_else(|| cr.to_string())
+}
+
+/// The highest challenge rating worth no more than `xp`
+pub fn cr_for_xp(xp: u32) -> &'static str {
+    CR_XP.iter().rev().find(|(_, value)| *value <= xp).map(|(rating, _)| *rating).unwrap_or("0")
+}
+
+/// The DMG multiplier for `monsters` opponents: one step higher against a party
+/// of fewer than three, one step lower against six or more
+pub fn encounter_multiplier(monsters: usize, party_size: usize) -> f64 {
+    let step: usize = match monsters {
+        0 | 1 => 1,
+        2 => 2,
+        3..=6 => 3,
+        7..=10 => 4,
+        11..=14 => 5,
+        _ => 6,
+    };
+    let step = match party_size {
+        0..=2 => step + 1,
+        3..=5 => step,
+        _ => step - 1,
+    };
+    MULTIPLIERS[step]
+}
+
+/// (base XP, multiplier, adjusted XP) for a fight against creatures of the given ratings
+pub fn adjusted_xp(party_size: usize, ratings: &[&str]) -> Result<(u32, f64, u32)> {
+    let base = ratings.iter()
+        .map(|cr| xp_for_cr(cr).ok_or_else(|| AppError::ValidationError(format!("Unknown challenge rating '{}'", cr))))
+        .sum::<Result<u32>>()?;
+    let multiplier = encounter_multiplier(ratings.len(), party_size);
+    Ok((base, multiplier, (base as f64 * multiplier).round() as u32))
+}
+
+/// Group digits in thousands, e.g. 1800 -> "1,800"
+pub fn format_xp(xp: u32) -> String {
+    let digits = xp.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// XP earned from a fight: what each defeated creature was worth and each character's share
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XpAward {
+    pub defeated: Vec<(String, String, u32)>,
+    pub total: u32,
+    pub shares: Vec<(String, u32)>,
+}
+
+impl XpAward {
+    /// Split the XP of every defeated, rated NPC evenly across `party`; the
+    /// remainder is dropped. None when nothing worth XP was defeated.
+    pub fn from_combat(tracker: &CombatTracker, party: &[String]) -> Option<Self> {
+        let defeated: Vec<(String, String, u32)> = tracker.combatants.iter()
+            .filter(|c| !c.is_player && c.current_hp <= 0)
+            .filter_map(|c| {
+                let cr = c.cr.as_deref()?;
+                Some((tracker.display_name(c), normalize_cr(cr), xp_for_cr(cr)?))
+            })
+            .collect();
+        let total: u32 = defeated.iter().map(|(_, _, xp)| xp).sum();
+        if total == 0 || party.is_empty() {
+            return None;
+        }
+        let share = total / party.len() as u32;
+        Some(XpAward {
+            defeated,
+            total,
+            shares: party.iter().map(|name| (name.clone(), share)).collect(),
+        })
+    }
+
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = vec![format!("⭐ {} XP from the fight:", format_xp(self.total))];
+        lines.extend(self.defeated.iter().map(|(name, cr, xp)| format!("  • {} (CR {}): {} XP", name, cr, format_xp(*xp))));
+        lines.extend(self.shares.iter().map(|(name, xp)| format!("  → {}: {} XP", name, format_xp(*xp))));
+        lines
+    }
+}
+
+fn usage() -> Vec<String> {
+    vec![
+        "Usage: xp for cr <cr> | cr for xp <xp> | xp mult <monsters> <party size>".to_string(),
+        "       xp adjust <party size> <cr> [cr...]  e.g. xp adjust 4 2 1/2 1/2".to_string(),
+    ]
+}
+
+/// The `xp` and `cr` quick commands; `cmd` is the command word and `args` the rest
+pub fn quick_command(cmd: &str, args: &[&str]) -> Vec<String> {
+    match (cmd, args) {
+        ("xp", ["for", "cr", cr] | ["cr", cr] | [cr]) => match xp_for_cr(cr) {
+            Some(xp) => vec![format!("⭐ CR {} = {} XP", normalize_cr(cr), format_xp(xp))],
+            None => vec![format!("❌ Unknown challenge rating '{}'", cr)],
+        },
+        ("cr", ["for", "xp", xp] | ["xp", xp] | [xp]) => match xp.replace(',', "").parse::<u32>() {
+            Ok(value) => {
+                let cr = cr_for_xp(value);
+                vec![format!("⭐ {} XP is worth CR {} ({} XP)", format_xp(value), cr, format_xp(xp_for_cr(cr).unwrap_or(0)))]
+            }
+            Err(_) => vec![format!("❌ '{}' is not an XP amount", xp)],
+        },
+        ("xp", ["mult" | "multiplier", monsters, party]) => match (monsters.parse::<usize>(), party.parse::<usize>()) {
+            (Ok(monsters), Ok(party)) => vec![format!("⭐ {} monster(s) vs a party of {}: ×{}",
+                monsters, party, encounter_multiplier(monsters, party))],
+            _ => usage(),
+        },
+        ("xp", ["adjust" | "adjusted", party, ratings @ ..]) if !ratings.is_empty() => match party.parse::<usize>() {
+            Ok(party) => match adjusted_xp(party, ratings) {
+                Ok((base, multiplier, adjusted)) => vec![format!("⭐ {} XP × {} = {} adjusted XP ({} monster(s), party of {})",
+                    format_xp(base), multiplier, format_xp(adjusted), ratings.len(), party)],
+                Err(e) => vec![format!("❌ {}", e)],
+            },
+            Err(_) => usage(),
+        },
+        _ => usage(),
+    }
+}